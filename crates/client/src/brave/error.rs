@@ -13,16 +13,24 @@ pub enum BraveError {
     #[error("invalid query: {0}")]
     InvalidQuery(String),
 
-    /// Invalid count parameter (must be 1-20).
-    #[error("invalid count: must be 1-20")]
-    InvalidCount,
+    /// Invalid count parameter; holds the endpoint's maximum (20 for web search).
+    #[error("invalid count: must be 1-{0}")]
+    InvalidCount(u8),
 
     /// Invalid offset parameter (must be 0-9).
     #[error("invalid offset: must be 0-9")]
     InvalidOffset,
 
-    /// Invalid freshness format.
-    #[error("invalid freshness format: {0}")]
+    /// Safe search level the endpoint does not support.
+    #[error("invalid safesearch: {0}")]
+    InvalidSafeSearch(String),
+
+    /// Section name `result_filter` does not recognize.
+    #[error("invalid result_filter section: {0:?}")]
+    InvalidResultFilter(String),
+
+    /// Malformed freshness filter or impossible custom range; says which.
+    #[error("invalid freshness: {0}")]
     InvalidFreshness(String),
 
     /// Authentication failed (invalid API key).
@@ -0,0 +1,237 @@
+//! Brave Image Search API request and response types.
+//!
+//! The image endpoint (`/images/search`) takes a smaller parameter set than
+//! web search: no offset or freshness, up to 100 results per request, and
+//! safesearch limited to off or strict.
+
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+use super::BraveError;
+use super::request::{SafeSearch, validate_query};
+use super::response::{DebugInfo, QueryInfo, QueryMeta};
+
+/// Search request parameters for Brave Image Search API.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct ImageSearchRequest {
+    /// Search query (required, max 400 chars / 50 words).
+    pub q: String,
+
+    /// Number of results (1-100, default 50).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u8>,
+
+    /// Safe search: off|strict (default strict; images have no moderate level).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safesearch: Option<SafeSearch>,
+
+    /// Country code (ISO 3166-1 alpha-2, e.g., "US").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+
+    /// Content language (ISO 639-1, e.g., "en").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_lang: Option<String>,
+
+    /// Enable spell-check on query.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spellcheck: Option<bool>,
+}
+
+impl ImageSearchRequest {
+    /// Validate the request against the image endpoint's limits.
+    pub fn validate(&self) -> Result<(), BraveError> {
+        validate_query(&self.q)?;
+
+        if let Some(count) = self.count
+            && !(1..=100).contains(&count)
+        {
+            return Err(BraveError::InvalidCount(100));
+        }
+
+        if self.safesearch == Some(SafeSearch::Moderate) {
+            return Err(BraveError::InvalidSafeSearch(
+                "image search supports off or strict".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Raw response from Brave Image Search API.
+#[derive(Debug, Deserialize)]
+pub struct BraveImageApiResponse {
+    pub query: QueryInfo,
+    #[serde(default)]
+    pub results: Vec<ImageApiResult>,
+}
+
+/// Individual image result from Brave.
+#[derive(Debug, Deserialize)]
+pub struct ImageApiResult {
+    #[serde(default)]
+    pub title: String,
+    /// Page the image appears on.
+    pub url: String,
+    #[serde(default)]
+    pub thumbnail: Option<ImageRef>,
+    /// The full-size image.
+    #[serde(default)]
+    pub properties: Option<ImageRef>,
+}
+
+/// An image URL with its dimensions, when Brave knows them.
+#[derive(Debug, Deserialize)]
+pub struct ImageRef {
+    #[serde(default, alias = "src")]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+}
+
+/// Normalized image search response.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageSearchResponse {
+    pub results: Vec<ImageResult>,
+    pub query: QueryMeta,
+    pub debug: DebugInfo,
+}
+
+/// Normalized image result.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageResult {
+    pub title: String,
+    /// The full-size image; the thumbnail when Brave omits it.
+    pub url: String,
+    /// Page the image appears on.
+    pub source_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+    pub rank: usize,
+}
+
+impl From<BraveImageApiResponse> for ImageSearchResponse {
+    /// Convert raw Brave image response to normalized internal format.
+    ///
+    /// Results with neither a full-size image nor a thumbnail are dropped.
+    fn from(raw: BraveImageApiResponse) -> Self {
+        let results = raw
+            .results
+            .into_iter()
+            .filter_map(|r| {
+                let thumbnail = r.thumbnail.and_then(|t| t.url);
+                let (image, width, height) = match r.properties {
+                    Some(p) => (p.url, p.width, p.height),
+                    None => (None, None, None),
+                };
+                Some(ImageResult {
+                    title: r.title,
+                    url: image.or_else(|| thumbnail.clone())?,
+                    source_url: r.url,
+                    width,
+                    height,
+                    thumbnail,
+                    rank: 0,
+                })
+            })
+            .enumerate()
+            .map(|(idx, r)| ImageResult { rank: idx + 1, ..r })
+            .collect();
+
+        ImageSearchResponse { results, query: raw.query.into(), debug: DebugInfo { request_id: None, attempts: 1 } }
+    }
+}
+
+impl ImageSearchResponse {
+    /// Create a new image response with timing info.
+    pub fn with_timing(mut self, start: Instant) -> Self {
+        self.debug.request_id = Some(format!("{:?}", start.elapsed()));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE_JSON: &str = r#"{
+        "type": "images",
+        "query": { "original": "ferris crab" },
+        "results": [
+            {
+                "type": "image_result",
+                "title": "Ferris the crab",
+                "url": "https://rustacean.example.net/",
+                "source": "rustacean.example.net",
+                "thumbnail": { "src": "https://imgs.example.com/ferris-thumb.jpg", "width": 200 },
+                "properties": { "url": "https://rustacean.example.net/ferris.png", "width": 1200, "height": 800 }
+            },
+            {
+                "title": "No dimensions",
+                "url": "https://other.example.org/page",
+                "thumbnail": { "src": "https://imgs.example.com/other-thumb.jpg" },
+                "properties": { "url": "https://other.example.org/img.jpg" }
+            },
+            {
+                "title": "Thumbnail only",
+                "url": "https://third.example.org/page",
+                "thumbnail": { "src": "https://imgs.example.com/third-thumb.jpg" }
+            },
+            {
+                "title": "No image at all",
+                "url": "https://empty.example.org/page"
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_normalize_image_response() {
+        let raw: BraveImageApiResponse = serde_json::from_str(FIXTURE_JSON).unwrap();
+        let response = ImageSearchResponse::from(raw);
+
+        assert_eq!(response.query.original, "ferris crab");
+        assert_eq!(response.results.len(), 3);
+
+        let first = &response.results[0];
+        assert_eq!(first.url, "https://rustacean.example.net/ferris.png");
+        assert_eq!(first.source_url, "https://rustacean.example.net/");
+        assert_eq!((first.width, first.height), (Some(1200), Some(800)));
+        assert_eq!(
+            first.thumbnail.as_deref(),
+            Some("https://imgs.example.com/ferris-thumb.jpg")
+        );
+
+        let second = &response.results[1];
+        assert_eq!((second.rank, second.width, second.height), (2, None, None));
+
+        let third = &response.results[2];
+        assert_eq!(
+            (third.rank, third.url.as_str()),
+            (3, "https://imgs.example.com/third-thumb.jpg")
+        );
+    }
+
+    #[test]
+    fn test_validate_image_request() {
+        let req = |count, safesearch| ImageSearchRequest {
+            q: "crab".into(),
+            count: Some(count),
+            safesearch,
+            ..Default::default()
+        };
+        assert!(req(100, Some(SafeSearch::Strict)).validate().is_ok());
+        assert!(matches!(req(101, None).validate(), Err(BraveError::InvalidCount(100))));
+        assert!(matches!(req(0, None).validate(), Err(BraveError::InvalidCount(100))));
+        assert!(matches!(
+            req(10, Some(SafeSearch::Moderate)).validate(),
+            Err(BraveError::InvalidSafeSearch(_))
+        ));
+    }
+}
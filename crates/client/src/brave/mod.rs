@@ -5,27 +5,40 @@
 //!
 //! ### Specification
 //!
-//! - **Endpoint**: `https://api.search.brave.com/res/v1/web/search`
+//! - **Endpoints**: `https://api.search.brave.com/res/v1/web/search`, plus
+//!   `news/search`, `images/search`, and `suggest/search` under the same base URL.
 //! - **Authentication**: Uses `X-Subscription-Token` header.
 //! - **Rate Limiting**:
-//!   - Respects Brave's published rate limits (token bucket).
-//!   - Default 1s interval for free tier.
-//!   - Retries on 429 with backoff and transient 5xx.
+//!   - Default 1s interval for free tier, adjusted to the plan's per-second
+//!     limit once Brave reports it in `X-RateLimit-*` headers.
+//!   - Holds requests while a reported window has none remaining.
+//!   - Retries 429, 502/503/504, and timeouts with exponential backoff and
+//!     jitter, waiting out `Retry-After` instead when Brave sends one.
 //! - **Normalization**: Converts Brave's response into a stable `SearchResult` struct.
 
 pub mod error;
+pub mod images;
+pub mod news;
+pub mod rate_limit;
 pub mod request;
 pub mod response;
+pub mod suggest;
 
 pub use error::BraveError;
-pub use request::{SafeSearch, SearchRequest};
-pub use response::{DebugInfo, QueryMeta, SearchResponse, SearchResult};
-
+pub use images::{ImageResult, ImageSearchRequest, ImageSearchResponse};
+pub use news::{NewsResult, NewsSearchRequest, NewsSearchResponse};
+pub use rate_limit::{RateLimitStatus, RateLimitWindow};
+pub use request::{RESULT_FILTER_SECTIONS, SafeSearch, SearchRequest};
+pub use response::{DebugInfo, DiscussionResult, FaqResult, QueryMeta, SearchResponse, SearchResult, VideoResult};
+pub use suggest::Suggestion;
+
+use crate::fetch::parse_retry_after;
+use rate_limit::RateLimiter;
 use reqwest::header;
+use serde::{Serialize, de::DeserializeOwned};
 use sha2::{Digest, Sha256};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::Mutex;
 
 /// Default base URL for Brave Search API.
 const DEFAULT_BASE_URL: &str = "https://api.search.brave.com/res/v1";
@@ -36,8 +49,21 @@ const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
 /// Default user agent.
 const DEFAULT_USER_AGENT: &str = "mcp-web/0.1";
 
-/// Minimum interval between requests for rate limiting (1 second for free tier).
-const MIN_REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+/// Default minimum interval between requests (1 second for free tier).
+const DEFAULT_MIN_REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Default number of retries after the first attempt.
+const DEFAULT_RETRIES: u8 = 3;
+
+/// Default delay before the first retry; doubled for each one after.
+const DEFAULT_RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Prefix hashed into every cache key; bump it when the key's inputs change so
+/// entries stored under the old scheme are no longer found.
+pub(crate) const CACHE_KEY_VERSION: &str = "v3\n";
+
+/// Longest `Retry-After` waited out; a longer one fails the search instead.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
 
 /// Brave API client configuration.
 #[derive(Debug, Clone)]
@@ -50,6 +76,13 @@ pub struct BraveConfig {
     pub timeout: Duration,
     /// User-agent string (default: mcp-web/0.x).
     pub user_agent: String,
+    /// Retries after a 429, 502/503/504, or timeout (default: 3).
+    pub retries: u8,
+    /// Backoff before the first retry, doubled for each later one (default: 500ms).
+    pub retry_base_delay: Duration,
+    /// Minimum spacing of requests from this client and its clones, until
+    /// Brave reports a per-second limit (default: 1s).
+    pub min_request_interval: Duration,
 }
 
 impl Default for BraveConfig {
@@ -59,6 +92,9 @@ impl Default for BraveConfig {
             base_url: DEFAULT_BASE_URL.to_string(),
             timeout: DEFAULT_TIMEOUT,
             user_agent: DEFAULT_USER_AGENT.to_string(),
+            retries: DEFAULT_RETRIES,
+            retry_base_delay: DEFAULT_RETRY_BASE_DELAY,
+            min_request_interval: DEFAULT_MIN_REQUEST_INTERVAL,
         }
     }
 }
@@ -74,30 +110,28 @@ impl BraveConfig {
     }
 }
 
-/// Rate limiter to enforce request intervals.
-#[derive(Debug)]
-struct RateLimiter {
-    last_request: Mutex<Instant>,
-    min_interval: Duration,
+/// A failed request and the `Retry-After` Brave sent with it, if any.
+struct FailedAttempt {
+    error: BraveError,
+    retry_after: Option<Duration>,
 }
 
-impl RateLimiter {
-    fn new(min_interval: Duration) -> Self {
-        Self {
-            last_request: Mutex::new(Instant::now().checked_sub(min_interval).unwrap_or_else(Instant::now)),
-            min_interval,
-        }
+impl From<BraveError> for FailedAttempt {
+    fn from(error: BraveError) -> Self {
+        Self { error, retry_after: None }
     }
+}
 
-    /// Acquire permission to make a request, waiting if necessary.
-    async fn acquire(&self) {
-        let mut last = self.last_request.lock().await;
-        let elapsed = last.elapsed();
-        if elapsed < self.min_interval {
-            tokio::time::sleep(self.min_interval - elapsed).await;
-        }
-        *last = Instant::now();
-    }
+/// Exponential backoff before retry number `retry`: `base * 2^(retry - 1)`,
+/// of which a random half is dropped so concurrent clients spread out.
+fn backoff(base: Duration, retry: u8) -> Duration {
+    use std::hash::{BuildHasher, Hasher};
+
+    let full = base.saturating_mul(1 << retry.saturating_sub(1).min(16));
+    // RandomState is randomly seeded, which is all the randomness jitter needs.
+    let bits = std::collections::hash_map::RandomState::new().build_hasher().finish();
+    let fraction = (bits >> 11) as f64 / (1u64 << 53) as f64;
+    full / 2 + (full / 2).mul_f64(fraction)
 }
 
 /// Brave Search API client.
@@ -120,7 +154,8 @@ impl BraveClient {
             .build()
             .map_err(|e| BraveError::Network(Arc::new(e)))?;
 
-        Ok(Self { http, config, rate_limiter: Arc::new(RateLimiter::new(MIN_REQUEST_INTERVAL)) })
+        let rate_limiter = Arc::new(RateLimiter::new(config.min_request_interval));
+        Ok(Self { http, config, rate_limiter })
     }
 
     /// Create a new Brave client from environment variables.
@@ -130,66 +165,189 @@ impl BraveClient {
 
     /// Execute a web search query.
     ///
-    /// This method handles rate limiting, request validation, and response normalization.
+    /// This method handles rate limiting, request validation, retries, and
+    /// response normalization. `debug.attempts` records how many requests it took.
     pub async fn search(&self, req: SearchRequest) -> Result<SearchResponse, BraveError> {
         req.validate()?;
 
-        self.rate_limiter.acquire().await;
+        let start = Instant::now();
+        tracing::debug!("searching Brave API: query={}", req.q);
+        let (api_response, attempts) = self.get::<response::BraveApiResponse>("web/search", &req).await?;
+
+        tracing::debug!(
+            "search completed in {:?} after {attempts} attempt(s), {} results",
+            start.elapsed(),
+            api_response.web.as_ref().map(|w| w.results.len()).unwrap_or(0)
+        );
+
+        let mut response = SearchResponse::from(api_response)
+            .with_published_dates(chrono::Utc::now())
+            .with_timing(start);
+        response.debug.attempts = attempts;
+        Ok(response)
+    }
+
+    /// Execute a news search query against the news endpoint.
+    ///
+    /// Shares rate limiting, authentication, and retries with [`search`](Self::search).
+    pub async fn search_news(&self, req: NewsSearchRequest) -> Result<NewsSearchResponse, BraveError> {
+        req.validate()?;
 
         let start = Instant::now();
-        let url = format!("{}/web/search", self.config.base_url);
+        tracing::debug!("searching Brave news: query={}", req.q);
+        let (api_response, attempts) = self.get::<news::BraveNewsApiResponse>("news/search", &req).await?;
 
-        tracing::debug!("searching Brave API: query={}", req.q);
+        tracing::debug!(
+            "news search completed in {:?} after {attempts} attempt(s), {} results",
+            start.elapsed(),
+            api_response.results.len()
+        );
+
+        let mut response = NewsSearchResponse::from(api_response)
+            .with_published_dates(chrono::Utc::now())
+            .with_timing(start);
+        response.debug.attempts = attempts;
+        Ok(response)
+    }
+
+    /// Execute an image search query against the image endpoint.
+    ///
+    /// Shares rate limiting, authentication, and retries with [`search`](Self::search).
+    pub async fn search_images(&self, req: ImageSearchRequest) -> Result<ImageSearchResponse, BraveError> {
+        req.validate()?;
+
+        let start = Instant::now();
+        tracing::debug!("searching Brave images: query={}", req.q);
+        let (api_response, attempts) = self.get::<images::BraveImageApiResponse>("images/search", &req).await?;
 
+        tracing::debug!(
+            "image search completed in {:?} after {attempts} attempt(s), {} results",
+            start.elapsed(),
+            api_response.results.len()
+        );
+
+        let mut response = ImageSearchResponse::from(api_response).with_timing(start);
+        response.debug.attempts = attempts;
+        Ok(response)
+    }
+
+    /// Fetch Brave's suggested completions for `query`.
+    ///
+    /// Validates the query like [`search`](Self::search) and shares its rate
+    /// limiting, authentication, and retries.
+    pub async fn suggest(&self, query: &str, country: Option<&str>) -> Result<Vec<Suggestion>, BraveError> {
+        request::validate_query(query)?;
+
+        tracing::debug!("fetching Brave suggestions: query={}", query);
+        let req = suggest::SuggestRequest { q: query, country };
+        let (api_response, _) = self
+            .get::<suggest::BraveSuggestApiResponse>("suggest/search", &req)
+            .await?;
+        Ok(api_response.results)
+    }
+
+    /// GET `endpoint` with `query`, retrying per [`retry_delay`](Self::retry_delay).
+    ///
+    /// Returns the parsed body and the number of requests it took.
+    async fn get<T: DeserializeOwned>(
+        &self, endpoint: &str, query: &(impl Serialize + Sync),
+    ) -> Result<(T, u8), BraveError> {
+        let mut attempts = 0u8;
+        loop {
+            attempts += 1;
+            let failure = match self.attempt(endpoint, query).await {
+                Ok(body) => return Ok((body, attempts)),
+                Err(failure) => failure,
+            };
+            let Some(delay) = self.retry_delay(&failure, attempts) else {
+                return Err(failure.error);
+            };
+            tracing::debug!(
+                "Brave attempt {attempts} failed ({}), retrying in {delay:?}",
+                failure.error
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Make one rate-limited request to `endpoint`.
+    async fn attempt<T: DeserializeOwned>(
+        &self, endpoint: &str, query: &(impl Serialize + Sync),
+    ) -> Result<T, FailedAttempt> {
+        // A turn further off than any Retry-After we would wait out fails like one.
+        if let Err(wait) = self.rate_limiter.acquire(MAX_RETRY_AFTER).await {
+            return Err(FailedAttempt { error: BraveError::RateLimited, retry_after: Some(wait) });
+        }
+
+        let url = format!("{}/{endpoint}", self.config.base_url);
         let http_response = self
             .http
             .get(&url)
             .header("X-Subscription-Token", &self.config.api_key)
             .header("Accept", "application/json")
             .header(header::USER_AGENT, &self.config.user_agent)
-            .query(&req)
+            .query(query)
             .send()
             .await
-            .map_err(
-                |e| {
-                    if e.is_timeout() { BraveError::Timeout } else { BraveError::Network(Arc::new(e)) }
-                },
-            )?;
+            .map_err(|e| FailedAttempt::from(BraveError::from(e)))?;
 
         let status = http_response.status();
         tracing::debug!("Brave API response status: {}", status);
+        self.rate_limiter.observe(http_response.headers());
 
         if status == 401 || status == 403 {
-            return Err(BraveError::AuthError);
-        }
-
-        if status == 429 {
-            return Err(BraveError::RateLimited);
+            return Err(BraveError::AuthError.into());
         }
 
         if status.is_client_error() || status.is_server_error() {
-            return Err(BraveError::HttpError { status: status.as_u16() });
+            let retry_after = http_response
+                .headers()
+                .get(header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| parse_retry_after(v, chrono::Utc::now()));
+            let error = if status == 429 {
+                BraveError::RateLimited
+            } else {
+                BraveError::HttpError { status: status.as_u16() }
+            };
+            return Err(FailedAttempt { error, retry_after });
         }
 
         let bytes = http_response
             .bytes()
             .await
-            .map_err(|e| BraveError::Network(Arc::new(e)))?;
-        let api_response: response::BraveApiResponse =
-            serde_json::from_slice(&bytes).map_err(|e| BraveError::Parse(e.to_string()))?;
+            .map_err(|e| FailedAttempt::from(BraveError::Network(Arc::new(e))))?;
+        serde_json::from_slice(&bytes).map_err(|e| BraveError::Parse(e.to_string()).into())
+    }
 
-        tracing::debug!(
-            "search completed in {:?}, {} results",
-            start.elapsed(),
-            api_response.web.as_ref().map(|w| w.results.len()).unwrap_or(0)
+    /// How long to wait before retrying after `failure`, or `None` to give up.
+    ///
+    /// `attempts` counts the requests made so far, the failed one included.
+    fn retry_delay(&self, failure: &FailedAttempt, attempts: u8) -> Option<Duration> {
+        let retryable = matches!(
+            failure.error,
+            BraveError::RateLimited | BraveError::Timeout | BraveError::HttpError { status: 502..=504 }
         );
+        if !retryable || attempts > self.config.retries {
+            return None;
+        }
+        match failure.retry_after {
+            Some(wait) if wait > MAX_RETRY_AFTER => None,
+            Some(wait) => Some(wait),
+            None => Some(backoff(self.config.retry_base_delay, attempts)),
+        }
+    }
 
-        Ok(SearchResponse::from(api_response).with_timing(start))
+    /// Rate limits Brave reported on the most recent response, shared by clones of this client.
+    pub fn rate_limit_status(&self) -> Option<RateLimitStatus> {
+        self.rate_limiter.status()
     }
 
     /// Generate a cache key for the search request.
     ///
-    /// The key is a SHA-256 hash of the normalized request parameters.
+    /// The key is a SHA-256 hash of [`CACHE_KEY_VERSION`] and every request
+    /// parameter that changes Brave's response, with unset parameters at the
+    /// API's defaults so leaving one out and sending its default share a key.
     pub fn cache_key(req: &SearchRequest) -> String {
         let params = serde_json::json!({
             "q": req.q,
@@ -199,24 +357,98 @@ impl BraveClient {
             "safesearch": req.safesearch,
             "country": req.country,
             "search_lang": req.search_lang,
+            "ui_lang": req.ui_lang,
+            "extra_snippets": req.extra_snippets.unwrap_or(false),
+            "goggles": req.goggles,
+            "spellcheck": req.spellcheck.unwrap_or(true),
+            "result_filter": req.result_filter,
         });
 
         let mut hasher = Sha256::new();
+        hasher.update(CACHE_KEY_VERSION.as_bytes());
         hasher.update(params.to_string().as_bytes());
         hex::encode(hasher.finalize())
     }
 
+    /// Generate a cache key for a news search request.
+    ///
+    /// Hashed like [`cache_key`](Self::cache_key) (news has no goggles) and
+    /// prefixed with `news:` so news and web entries never collide.
+    pub fn news_cache_key(req: &NewsSearchRequest) -> String {
+        let params = serde_json::json!({
+            "q": req.q,
+            "count": req.count.unwrap_or(20),
+            "offset": req.offset.unwrap_or(0),
+            "freshness": req.freshness,
+            "safesearch": req.safesearch,
+            "country": req.country,
+            "search_lang": req.search_lang,
+            "ui_lang": req.ui_lang,
+            "extra_snippets": req.extra_snippets.unwrap_or(false),
+            "spellcheck": req.spellcheck.unwrap_or(true),
+        });
+
+        let mut hasher = Sha256::new();
+        hasher.update(CACHE_KEY_VERSION.as_bytes());
+        hasher.update(params.to_string().as_bytes());
+        format!("news:{}", hex::encode(hasher.finalize()))
+    }
+
+    /// Generate a cache key for an image search request, prefixed with `images:`
+    /// like [`news_cache_key`](Self::news_cache_key).
+    pub fn image_cache_key(req: &ImageSearchRequest) -> String {
+        let params = serde_json::json!({
+            "q": req.q,
+            "count": req.count.unwrap_or(50),
+            "safesearch": req.safesearch.unwrap_or(SafeSearch::Strict),
+            "country": req.country,
+            "search_lang": req.search_lang,
+            "spellcheck": req.spellcheck.unwrap_or(true),
+        });
+
+        let mut hasher = Sha256::new();
+        hasher.update(CACHE_KEY_VERSION.as_bytes());
+        hasher.update(params.to_string().as_bytes());
+        format!("images:{}", hex::encode(hasher.finalize()))
+    }
+
+    /// Generate a cache key for a suggest request, prefixed with `suggest:`
+    /// like [`news_cache_key`](Self::news_cache_key).
+    pub fn suggest_cache_key(query: &str, country: Option<&str>) -> String {
+        let params = serde_json::json!({ "q": query, "country": country });
+
+        let mut hasher = Sha256::new();
+        hasher.update(CACHE_KEY_VERSION.as_bytes());
+        hasher.update(params.to_string().as_bytes());
+        format!("suggest:{}", hex::encode(hasher.finalize()))
+    }
+
+    /// Calculate TTL for news results: as [`ttl_for_freshness`](Self::ttl_for_freshness),
+    /// but 1 hour without a freshness filter since news goes stale quickly.
+    pub fn news_ttl_for_freshness(freshness: &Option<String>) -> i64 {
+        match freshness {
+            Some(_) => Self::ttl_for_freshness(freshness),
+            None => 3600,
+        }
+    }
+
     /// Calculate TTL for search results based on freshness parameter.
     ///
-    /// Returns TTL in seconds.
+    /// A custom range that ended before today covers pages that no longer
+    /// change, so it is kept for a week; one ending today gets an hour, like
+    /// "pd". Returns TTL in seconds.
     pub fn ttl_for_freshness(freshness: &Option<String>) -> i64 {
         match freshness.as_deref() {
             Some("pd") => 3600,  // 1 hour for past day
             Some("pw") => 21600, // 6 hours for past week
             Some("pm") => 43200, // 12 hours for past month
             Some("py") => 86400, // 24 hours for past year
-            Some(_) => 21600,    // 6 hours for custom ranges
-            None => 21600,       // 6 hours default
+            Some(range) => match request::parse_freshness_range(range) {
+                Ok((_, end)) if end < chrono::Utc::now().date_naive() => 604800,
+                Ok(_) => 3600,
+                Err(_) => 21600,
+            },
+            None => 21600, // 6 hours default
         }
     }
 }
@@ -269,6 +501,148 @@ mod tests {
         assert_ne!(key1, key2);
     }
 
+    #[test]
+    fn test_cache_key_covers_response_params() {
+        let base = SearchRequest { q: "test query".to_string(), ..Default::default() };
+        let key = BraveClient::cache_key(&base);
+        assert_eq!(key.len(), 64);
+        assert!(key.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase()));
+
+        let goggles = |g: &str| SearchRequest { goggles: Some(g.to_string()), ..base.clone() };
+        let a = BraveClient::cache_key(&goggles("https://example.com/a.goggle"));
+        let b = BraveClient::cache_key(&goggles("https://example.com/b.goggle"));
+        assert_ne!(a, b);
+        assert_ne!(a, key);
+
+        for changed in [
+            SearchRequest { extra_snippets: Some(true), ..base.clone() },
+            SearchRequest { ui_lang: Some("de-DE".into()), ..base.clone() },
+            SearchRequest { spellcheck: Some(false), ..base.clone() },
+            SearchRequest { result_filter: Some("web".into()), ..base.clone() },
+        ] {
+            assert_ne!(BraveClient::cache_key(&changed), key, "{changed:?}");
+        }
+
+        let defaults = SearchRequest { extra_snippets: Some(false), spellcheck: Some(true), ..base.clone() };
+        assert_eq!(BraveClient::cache_key(&defaults), key);
+    }
+
+    #[test]
+    fn test_news_cache_key_namespaced() {
+        let web = SearchRequest { q: "rust".into(), ..Default::default() };
+        let news = NewsSearchRequest { q: "rust".into(), ..Default::default() };
+        let key = BraveClient::news_cache_key(&news);
+        assert!(key.starts_with("news:"));
+        assert_eq!(key.len(), "news:".len() + 64);
+        assert_ne!(key.trim_start_matches("news:"), BraveClient::cache_key(&web));
+
+        let fresher = NewsSearchRequest { freshness: Some("pd".into()), ..news.clone() };
+        assert_ne!(BraveClient::news_cache_key(&fresher), key);
+        assert_eq!(BraveClient::news_ttl_for_freshness(&None), 3600);
+        assert_eq!(BraveClient::news_ttl_for_freshness(&Some("pw".into())), 21600);
+    }
+
+    #[test]
+    fn test_image_cache_key_namespaced() {
+        let req = ImageSearchRequest { q: "ferris".into(), ..Default::default() };
+        let key = BraveClient::image_cache_key(&req);
+        assert!(key.starts_with("images:"));
+        assert_ne!(
+            key.trim_start_matches("images:"),
+            BraveClient::cache_key(&SearchRequest { q: "ferris".into(), ..Default::default() })
+        );
+
+        let strict = ImageSearchRequest { count: Some(50), safesearch: Some(SafeSearch::Strict), ..req.clone() };
+        assert_eq!(BraveClient::image_cache_key(&strict), key);
+        let off = ImageSearchRequest { safesearch: Some(SafeSearch::Off), ..req };
+        assert_ne!(BraveClient::image_cache_key(&off), key);
+    }
+
+    #[tokio::test]
+    async fn test_suggest_sends_query_and_country() {
+        use crate::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|req| {
+            if req.path.starts_with("/suggest/search?") && req.path.contains("q=rust") && req.path.contains("country=DE") {
+                let body = r#"{"type":"suggest","query":{"original":"rust"},"results":[{"query":"rust lang","is_entity":true}]}"#;
+                FixtureResponse::ok("application/json", body)
+            } else {
+                FixtureResponse::status(404)
+            }
+        })
+        .await;
+
+        let client = fixture_client(&server);
+        let suggestions = client.suggest("rust", Some("DE")).await.unwrap();
+        assert_eq!(
+            suggestions,
+            vec![Suggestion { query: "rust lang".into(), is_entity: true }]
+        );
+        assert!(matches!(
+            client.suggest("", None).await,
+            Err(BraveError::InvalidQuery(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_search_news_hits_news_endpoint() {
+        use crate::fixture::{FixtureResponse, FixtureServer};
+
+        let body = serde_json::json!({
+            "type": "news",
+            "query": { "original": "rust" },
+            "results": [{
+                "title": "Rust 2.0 announced",
+                "url": "https://news.example.com/rust",
+                "description": "The announcement.",
+                "age": "3 hours ago",
+                "meta_url": { "hostname": "news.example.com" },
+                "thumbnail": { "src": "https://imgs.example.com/t.jpg" }
+            }]
+        })
+        .to_string();
+        let server = FixtureServer::start(move |req| match req.path.split('?').next() {
+            Some("/news/search") => FixtureResponse::ok("application/json", body.clone()),
+            _ => FixtureResponse::status(404),
+        })
+        .await;
+
+        let response = fixture_client(&server)
+            .search_news(NewsSearchRequest { q: "rust".into(), ..Default::default() })
+            .await
+            .unwrap();
+        assert_eq!(response.debug.attempts, 1);
+        let result = &response.results[0];
+        assert_eq!(result.source, "news.example.com");
+        assert_eq!(result.thumbnail_url.as_deref(), Some("https://imgs.example.com/t.jpg"));
+        assert!(result.published_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_search_sends_result_filter() {
+        use crate::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|req| {
+            if req.path.starts_with("/web/search?") && req.path.contains("result_filter=web%2Cnews") {
+                FixtureResponse::ok(
+                    "application/json",
+                    r#"{"query":{"original":"rust"},"web":{"results":[]}}"#,
+                )
+            } else {
+                FixtureResponse::status(400)
+            }
+        })
+        .await;
+
+        let client = fixture_client(&server);
+        let req = SearchRequest { q: "rust".into(), result_filter: Some("web,news".into()), ..Default::default() };
+        assert!(client.search(req).await.is_ok());
+
+        let invalid = SearchRequest { q: "rust".into(), result_filter: Some("web,maps".into()), ..Default::default() };
+        let err = client.search(invalid).await.unwrap_err();
+        assert_eq!(err.to_string(), "invalid result_filter section: \"maps\"");
+    }
+
     #[test]
     fn test_ttl_calculation() {
         assert_eq!(BraveClient::ttl_for_freshness(&Some("pd".to_string())), 3600);
@@ -277,6 +651,116 @@ mod tests {
         assert_eq!(BraveClient::ttl_for_freshness(&Some("py".to_string())), 86400);
         assert_eq!(BraveClient::ttl_for_freshness(&Some("custom".to_string())), 21600);
         assert_eq!(BraveClient::ttl_for_freshness(&None), 21600);
+
+        let today = chrono::Utc::now().date_naive();
+        let historical = Some("2023-01-01to2023-12-31".to_string());
+        assert_eq!(BraveClient::ttl_for_freshness(&historical), 604800);
+        let ending_today = Some(format!("2024-01-01to{today}"));
+        assert_eq!(BraveClient::ttl_for_freshness(&ending_today), 3600);
+    }
+
+    #[test]
+    fn test_backoff_doubles_with_jitter() {
+        let base = Duration::from_millis(100);
+        for retry in 1..=4u8 {
+            let full = base * (1 << (retry - 1));
+            let delay = backoff(base, retry);
+            assert!(delay >= full / 2 && delay <= full, "retry {retry}: {delay:?}");
+        }
+    }
+
+    fn fixture_body() -> String {
+        serde_json::json!({
+            "query": { "original": "retry", "moreResultsAvailable": false },
+            "web": { "results": [{ "title": "Result", "url": "https://example.com/", "description": "" }] }
+        })
+        .to_string()
+    }
+
+    fn fixture_client(server: &crate::fixture::FixtureServer) -> BraveClient {
+        BraveClient::new(BraveConfig {
+            api_key: "test-key".into(),
+            base_url: server.url(""),
+            retry_base_delay: Duration::from_millis(10),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_search_retries_rate_limits() {
+        use crate::fixture::{FixtureResponse, FixtureServer};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let body = fixture_body();
+        let server = FixtureServer::start(move |_| match counter.fetch_add(1, Ordering::SeqCst) {
+            0 | 1 => FixtureResponse::status(429),
+            _ => FixtureResponse::ok("application/json", body.clone()),
+        })
+        .await;
+
+        let response = fixture_client(&server)
+            .search(SearchRequest { q: "retry".into(), ..Default::default() })
+            .await
+            .unwrap();
+        assert_eq!(response.results.len(), 1);
+        assert_eq!(response.debug.attempts, 3);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_clones_share_request_pacing() {
+        use crate::fixture::{FixtureResponse, FixtureServer};
+
+        let arrivals = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = arrivals.clone();
+        let body = fixture_body();
+        let server = FixtureServer::start(move |_| {
+            recorder.lock().unwrap().push(Instant::now());
+            FixtureResponse::ok("application/json", body.clone())
+        })
+        .await;
+
+        let interval = Duration::from_millis(300);
+        let client = BraveClient::new(BraveConfig {
+            api_key: "test-key".into(),
+            base_url: server.url(""),
+            min_request_interval: interval,
+            ..Default::default()
+        })
+        .unwrap();
+        let other = client.clone();
+        let req = SearchRequest { q: "pacing".into(), ..Default::default() };
+        let (a, b) = tokio::join!(client.search(req.clone()), other.search(req));
+        a.unwrap();
+        b.unwrap();
+
+        let arrivals = arrivals.lock().unwrap();
+        assert_eq!(arrivals.len(), 2);
+        assert!(arrivals[1] - arrivals[0] >= interval, "{:?}", arrivals[1] - arrivals[0]);
+    }
+
+    #[tokio::test]
+    async fn test_search_does_not_retry_client_errors() {
+        use crate::fixture::{FixtureResponse, FixtureServer};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let server = FixtureServer::start(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            FixtureResponse::status(400)
+        })
+        .await;
+
+        let err = fixture_client(&server)
+            .search(SearchRequest { q: "retry".into(), ..Default::default() })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BraveError::HttpError { status: 400 }));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
     }
 
     #[test]
@@ -0,0 +1,268 @@
+//! Brave News Search API request and response types.
+//!
+//! The news endpoint (`/news/search`) takes the web search parameters minus
+//! goggles, allows up to 50 results per page, and returns article metadata
+//! (publisher host, thumbnail, age) that web results lack.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+use super::BraveError;
+use super::request::{SafeSearch, validate_freshness, validate_query};
+use super::response::{DebugInfo, QueryInfo, QueryMeta, published_at};
+
+/// Search request parameters for Brave News Search API.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct NewsSearchRequest {
+    /// Search query (required, max 400 chars / 50 words).
+    pub q: String,
+
+    /// Number of results (1-50, default 20).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u8>,
+
+    /// Page offset (0-9, default 0).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u8>,
+
+    /// Freshness filter: pd|pw|pm|py or YYYY-MM-DDtoYYYY-MM-DD.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freshness: Option<String>,
+
+    /// Safe search: off|moderate|strict (default strict for news).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safesearch: Option<SafeSearch>,
+
+    /// Country code (ISO 3166-1 alpha-2, e.g., "US").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+
+    /// Content language (ISO 639-1, e.g., "en").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_lang: Option<String>,
+
+    /// UI/response metadata language (e.g., "en-US").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ui_lang: Option<String>,
+
+    /// Enable up to 5 extra snippets per result.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra_snippets: Option<bool>,
+
+    /// Enable spell-check on query.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spellcheck: Option<bool>,
+}
+
+impl NewsSearchRequest {
+    /// Validate the request against the news endpoint's limits.
+    pub fn validate(&self) -> Result<(), BraveError> {
+        validate_query(&self.q)?;
+
+        if let Some(count) = self.count
+            && !(1..=50).contains(&count)
+        {
+            return Err(BraveError::InvalidCount(50));
+        }
+
+        if let Some(offset) = self.offset
+            && offset > 9
+        {
+            return Err(BraveError::InvalidOffset);
+        }
+
+        if let Some(freshness) = &self.freshness {
+            validate_freshness(freshness)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Raw response from Brave News Search API.
+#[derive(Debug, Deserialize)]
+pub struct BraveNewsApiResponse {
+    pub query: QueryInfo,
+    #[serde(default)]
+    pub results: Vec<NewsApiResult>,
+}
+
+/// Individual news result from Brave.
+#[derive(Debug, Deserialize)]
+pub struct NewsApiResult {
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub extra_snippets: Vec<String>,
+    /// Display age, e.g. "3 hours ago".
+    #[serde(default)]
+    pub age: Option<String>,
+    /// Article date as an ISO timestamp without offset.
+    #[serde(default)]
+    pub page_age: Option<String>,
+    #[serde(default)]
+    pub meta_url: Option<MetaUrl>,
+    #[serde(default)]
+    pub thumbnail: Option<Thumbnail>,
+}
+
+/// Site a result comes from.
+#[derive(Debug, Deserialize)]
+pub struct MetaUrl {
+    #[serde(default)]
+    pub hostname: Option<String>,
+    /// Brave-proxied favicon of the site.
+    #[serde(default)]
+    pub favicon: Option<String>,
+}
+
+/// A result's preview image.
+#[derive(Debug, Deserialize)]
+pub struct Thumbnail {
+    pub src: String,
+}
+
+/// Normalized news search response.
+#[derive(Debug, Clone, Serialize)]
+pub struct NewsSearchResponse {
+    pub results: Vec<NewsResult>,
+    pub query: QueryMeta,
+    pub debug: DebugInfo,
+}
+
+/// Normalized news result.
+#[derive(Debug, Clone, Serialize)]
+pub struct NewsResult {
+    pub title: String,
+    pub url: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra_snippets: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_age: Option<String>,
+    /// `page_age` or `age` normalized to RFC3339 UTC; see [`NewsSearchResponse::with_published_dates`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<String>,
+    /// Publisher host, e.g. "www.example.com"; the result URL's host when Brave omits it.
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_url: Option<String>,
+    pub rank: usize,
+}
+
+impl From<BraveNewsApiResponse> for NewsSearchResponse {
+    /// Convert raw Brave news response to normalized internal format.
+    fn from(raw: BraveNewsApiResponse) -> Self {
+        NewsSearchResponse {
+            results: normalize_news(raw.results),
+            query: raw.query.into(),
+            debug: DebugInfo { request_id: None, attempts: 1 },
+        }
+    }
+}
+
+/// Normalize raw news results, ranked in Brave's order.
+///
+/// Shared with the news cluster of web search responses.
+pub(crate) fn normalize_news(results: Vec<NewsApiResult>) -> Vec<NewsResult> {
+    results
+        .into_iter()
+        .enumerate()
+        .map(|(idx, r)| {
+            let source = r
+                .meta_url
+                .and_then(|m| m.hostname)
+                .or_else(|| url::Url::parse(&r.url).ok()?.host_str().map(str::to_string))
+                .unwrap_or_default();
+            NewsResult {
+                title: r.title,
+                url: r.url,
+                description: r.description,
+                extra_snippets: r.extra_snippets,
+                age: r.age,
+                page_age: r.page_age,
+                published_at: None,
+                source,
+                thumbnail_url: r.thumbnail.map(|t| t.src),
+                rank: idx + 1,
+            }
+        })
+        .collect()
+}
+
+impl NewsSearchResponse {
+    /// Create a new news response with timing info.
+    pub fn with_timing(mut self, start: Instant) -> Self {
+        self.debug.request_id = Some(format!("{:?}", start.elapsed()));
+        self
+    }
+
+    /// Fill in each result's `published_at` from `page_age`, else `age`, resolved against `now`.
+    pub fn with_published_dates(mut self, now: DateTime<Utc>) -> Self {
+        for result in &mut self.results {
+            result.published_at = published_at(result.page_age.as_deref(), result.age.as_deref(), now);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE_JSON: &str = r#"{
+        "type": "news",
+        "query": { "original": "rust release" },
+        "results": [
+            {
+                "title": "Rust 1.90 released",
+                "url": "https://blog.example.org/rust-1-90",
+                "description": "Highlights of the release.",
+                "age": "2 days ago",
+                "page_age": "2024-03-08T09:30:00",
+                "meta_url": { "scheme": "https", "netloc": "blog.example.org", "hostname": "blog.example.org" },
+                "thumbnail": { "src": "https://imgs.example.com/rust.jpg" }
+            },
+            {
+                "title": "No metadata",
+                "url": "https://other.example.net/story"
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_normalize_news_response() {
+        let raw: BraveNewsApiResponse = serde_json::from_str(FIXTURE_JSON).unwrap();
+        let now = DateTime::parse_from_rfc3339("2024-03-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let response = NewsSearchResponse::from(raw).with_published_dates(now);
+
+        assert_eq!(response.query.original, "rust release");
+        let first = &response.results[0];
+        assert_eq!((first.rank, first.source.as_str()), (1, "blog.example.org"));
+        assert_eq!(
+            first.thumbnail_url.as_deref(),
+            Some("https://imgs.example.com/rust.jpg")
+        );
+        assert_eq!(first.published_at.as_deref(), Some("2024-03-08T09:30:00Z"));
+
+        let second = &response.results[1];
+        assert_eq!((second.rank, second.source.as_str()), (2, "other.example.net"));
+        assert_eq!(second.description, "");
+        assert!(second.thumbnail_url.is_none() && second.published_at.is_none());
+    }
+
+    #[test]
+    fn test_news_count_allows_fifty() {
+        let req = |count| NewsSearchRequest { q: "news".into(), count: Some(count), ..Default::default() };
+        assert!(req(50).validate().is_ok());
+        assert!(matches!(req(51).validate(), Err(BraveError::InvalidCount(50))));
+        assert!(matches!(req(0).validate(), Err(BraveError::InvalidCount(50))));
+    }
+}
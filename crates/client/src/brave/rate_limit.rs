@@ -0,0 +1,243 @@
+//! Client-side pacing for Brave API requests.
+//!
+//! Requests are spaced at least an interval apart. Brave reports the plan's
+//! limits on every response in comma-separated headers, one entry per window
+//! (per second first, then per month):
+//!
+//! ```text
+//! X-RateLimit-Limit: 1, 15000
+//! X-RateLimit-Remaining: 0, 14213
+//! X-RateLimit-Reset: 1, 1419704
+//! ```
+//!
+//! The per-second limit replaces the configured interval, so paid plans go
+//! faster, and a window with nothing remaining holds requests until it resets.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use reqwest::header::HeaderMap;
+
+/// One rate-limit window as last reported by Brave.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitWindow {
+    /// Requests the plan allows in this window.
+    pub limit: u64,
+    /// Requests left in this window.
+    pub remaining: u64,
+    /// Seconds until the window resets.
+    pub reset_secs: u64,
+}
+
+/// Brave's rate-limit headers from the most recent response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    /// Windows in header order: per second, then per month.
+    pub windows: Vec<RateLimitWindow>,
+}
+
+impl RateLimitStatus {
+    /// Parse the `X-RateLimit-*` headers; `None` unless all three are present
+    /// and list the same number of windows.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let values = |name: &str| -> Option<Vec<u64>> {
+            headers
+                .get(name)?
+                .to_str()
+                .ok()?
+                .split(',')
+                .map(|v| v.trim().parse().ok())
+                .collect()
+        };
+        let limits = values("x-ratelimit-limit")?;
+        let remaining = values("x-ratelimit-remaining")?;
+        let resets = values("x-ratelimit-reset")?;
+        if limits.is_empty() || limits.len() != remaining.len() || limits.len() != resets.len() {
+            return None;
+        }
+
+        let windows = limits
+            .into_iter()
+            .zip(remaining)
+            .zip(resets)
+            .map(|((limit, remaining), reset_secs)| RateLimitWindow { limit, remaining, reset_secs })
+            .collect();
+        Some(Self { windows })
+    }
+}
+
+/// Spaces requests out, adapting to the limits Brave reports.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    /// Held while waiting for a turn, so requests go out one at a time.
+    turn: tokio::sync::Mutex<()>,
+    state: Mutex<LimiterState>,
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    /// Interval used until Brave reports a per-second limit.
+    base_interval: Duration,
+    interval: Duration,
+    last_request: Option<Instant>,
+    /// Set while a reported window has no requests remaining.
+    blocked_until: Option<Instant>,
+    status: Option<RateLimitStatus>,
+}
+
+impl LimiterState {
+    /// Earliest time the next request may be sent.
+    fn next_allowed(&self) -> Option<Instant> {
+        let paced = self.last_request.map(|last| last + self.interval);
+        paced.max(self.blocked_until)
+    }
+
+    /// Adopt the limits in `status`, observed at `now`.
+    fn observe(&mut self, status: RateLimitStatus, now: Instant) {
+        self.interval = match status.windows.first() {
+            Some(per_second) if per_second.limit > 0 => {
+                Duration::from_secs(1) / u32::try_from(per_second.limit).unwrap_or(u32::MAX)
+            }
+            _ => self.base_interval,
+        };
+        self.blocked_until = status
+            .windows
+            .iter()
+            .filter(|w| w.remaining == 0)
+            .map(|w| now + Duration::from_secs(w.reset_secs))
+            .max();
+        self.status = Some(status);
+    }
+}
+
+impl RateLimiter {
+    pub(crate) fn new(min_interval: Duration) -> Self {
+        Self {
+            turn: tokio::sync::Mutex::new(()),
+            state: Mutex::new(LimiterState {
+                base_interval: min_interval,
+                interval: min_interval,
+                last_request: None,
+                blocked_until: None,
+                status: None,
+            }),
+        }
+    }
+
+    /// Wait for this request's turn.
+    ///
+    /// Fails with the time left instead of waiting when that exceeds `max_wait`,
+    /// e.g. once the monthly quota is used up.
+    pub(crate) async fn acquire(&self, max_wait: Duration) -> Result<(), Duration> {
+        let _turn = self.turn.lock().await;
+        loop {
+            let now = Instant::now();
+            let next = self.state.lock().unwrap().next_allowed();
+            match next {
+                Some(at) if at > now => {
+                    let wait = at - now;
+                    if wait > max_wait {
+                        return Err(wait);
+                    }
+                    tokio::time::sleep(wait).await;
+                }
+                _ => break,
+            }
+        }
+        self.state.lock().unwrap().last_request = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Feed the rate-limit headers of a response, if it has them, into the pacing.
+    pub(crate) fn observe(&self, headers: &HeaderMap) {
+        if let Some(status) = RateLimitStatus::from_headers(headers) {
+            self.state.lock().unwrap().observe(status, Instant::now());
+        }
+    }
+
+    /// Limits from the most recent response that reported them.
+    pub(crate) fn status(&self) -> Option<RateLimitStatus> {
+        self.state.lock().unwrap().status.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(limit: &str, remaining: &str, reset: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-RateLimit-Limit", HeaderValue::from_str(limit).unwrap());
+        headers.insert("X-RateLimit-Remaining", HeaderValue::from_str(remaining).unwrap());
+        headers.insert("X-RateLimit-Reset", HeaderValue::from_str(reset).unwrap());
+        headers
+    }
+
+    fn state() -> LimiterState {
+        LimiterState {
+            base_interval: Duration::from_secs(1),
+            interval: Duration::from_secs(1),
+            last_request: None,
+            blocked_until: None,
+            status: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_windows() {
+        let status = RateLimitStatus::from_headers(&headers("20, 15000", "19, 14213", "1, 1419704")).unwrap();
+        assert_eq!(
+            status.windows,
+            vec![
+                RateLimitWindow { limit: 20, remaining: 19, reset_secs: 1 },
+                RateLimitWindow { limit: 15000, remaining: 14213, reset_secs: 1419704 },
+            ]
+        );
+
+        assert!(RateLimitStatus::from_headers(&headers("1, 15000", "0", "1, 100")).is_none());
+        assert!(RateLimitStatus::from_headers(&headers("1, x", "0, 1", "1, 100")).is_none());
+        assert!(RateLimitStatus::from_headers(&HeaderMap::new()).is_none());
+    }
+
+    #[test]
+    fn test_higher_plan_limit_shrinks_interval() {
+        let now = Instant::now();
+        let mut state = state();
+        state.last_request = Some(now);
+        assert_eq!(state.next_allowed(), Some(now + Duration::from_secs(1)));
+
+        let status = RateLimitStatus::from_headers(&headers("20, 15000", "19, 14000", "1, 1000")).unwrap();
+        state.observe(status, now);
+        assert_eq!(state.next_allowed(), Some(now + Duration::from_millis(50)));
+    }
+
+    #[test]
+    fn test_exhausted_window_holds_until_reset() {
+        let now = Instant::now();
+        let mut state = state();
+        state.last_request = Some(now);
+
+        let status = RateLimitStatus::from_headers(&headers("50, 15000", "0, 14000", "1, 1000")).unwrap();
+        state.observe(status, now);
+        assert_eq!(state.next_allowed(), Some(now + Duration::from_secs(1)));
+
+        let status = RateLimitStatus::from_headers(&headers("50, 15000", "49, 0", "1, 86400")).unwrap();
+        state.observe(status, now);
+        assert_eq!(state.next_allowed(), Some(now + Duration::from_secs(86400)));
+
+        let status = RateLimitStatus::from_headers(&headers("50, 15000", "49, 14000", "1, 86400")).unwrap();
+        state.observe(status, now);
+        assert_eq!(state.next_allowed(), Some(now + Duration::from_millis(20)));
+    }
+
+    #[tokio::test]
+    async fn test_acquire_refuses_long_waits() {
+        let limiter = RateLimiter::new(Duration::ZERO);
+        limiter.observe(&headers("1, 15000", "1, 0", "1, 86400"));
+        assert!(limiter.status().is_some());
+
+        let wait = limiter.acquire(Duration::from_secs(30)).await.unwrap_err();
+        assert!(wait > Duration::from_secs(86000), "{wait:?}");
+    }
+}
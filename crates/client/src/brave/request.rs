@@ -1,7 +1,10 @@
 //! Brave Search API request types and validation.
 
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
+use crate::brave::BraveError;
+
 /// Search request parameters for Brave Web Search API.
 ///
 /// Based on Brave Web Search API documentation:
@@ -50,8 +53,25 @@ pub struct SearchRequest {
     /// Enable spell-check on query.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub spellcheck: Option<bool>,
+
+    /// Comma-separated sections to return, e.g. "web,news" (default: all).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_filter: Option<String>,
 }
 
+/// Sections `result_filter` can name.
+pub const RESULT_FILTER_SECTIONS: &[&str] = &[
+    "discussions",
+    "faq",
+    "infobox",
+    "locations",
+    "news",
+    "query",
+    "summarizer",
+    "videos",
+    "web",
+];
+
 /// Safe search filtering levels.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -65,32 +85,13 @@ impl SearchRequest {
     /// Validate the search request parameters.
     ///
     /// Returns an error if any parameters are out of range or malformed.
-    pub fn validate(&self) -> Result<(), crate::brave::BraveError> {
-        use crate::brave::BraveError;
-
-        if self.q.is_empty() {
-            return Err(BraveError::InvalidQuery("query cannot be empty".to_string()));
-        }
-
-        if self.q.len() > 400 {
-            return Err(BraveError::InvalidQuery(format!(
-                "query too long: {} chars (max 400)",
-                self.q.len()
-            )));
-        }
-
-        let word_count = self.q.split_whitespace().count();
-        if word_count > 50 {
-            return Err(BraveError::InvalidQuery(format!(
-                "query too long: {} words (max 50)",
-                word_count
-            )));
-        }
+    pub fn validate(&self) -> Result<(), BraveError> {
+        validate_query(&self.q)?;
 
         if let Some(count) = self.count
             && !(1..=20).contains(&count)
         {
-            return Err(BraveError::InvalidCount);
+            return Err(BraveError::InvalidCount(20));
         }
 
         if let Some(offset) = self.offset
@@ -100,31 +101,14 @@ impl SearchRequest {
         }
 
         if let Some(freshness) = &self.freshness {
-            Self::validate_freshness(freshness)?;
-        }
-
-        Ok(())
-    }
-
-    /// Validate freshness parameter format.
-    fn validate_freshness(freshness: &str) -> Result<(), crate::brave::BraveError> {
-        const VALID_PRESETS: &[&str] = &["pd", "pw", "pm", "py"];
-
-        if VALID_PRESETS.contains(&freshness) {
-            return Ok(());
+            validate_freshness(freshness)?;
         }
 
-        if freshness.len() == 22 && freshness.contains("to") {
-            let parts: Vec<&str> = freshness.split("to").collect();
-            if parts.len() == 2 {
-                let date_regex = regex::Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap();
-                if date_regex.is_match(parts[0]) && date_regex.is_match(parts[1]) {
-                    return Ok(());
-                }
-            }
+        if let Some(filter) = &self.result_filter {
+            validate_result_filter(filter)?;
         }
 
-        Err(crate::brave::BraveError::InvalidFreshness(freshness.to_string()))
+        Ok(())
     }
 
     /// Get the effective count (default 20).
@@ -143,10 +127,82 @@ impl SearchRequest {
     }
 }
 
+/// Validate a query against Brave's limits: non-empty, at most 400 chars and 50 words.
+pub(crate) fn validate_query(q: &str) -> Result<(), BraveError> {
+    if q.is_empty() {
+        return Err(BraveError::InvalidQuery("query cannot be empty".to_string()));
+    }
+
+    if q.len() > 400 {
+        return Err(BraveError::InvalidQuery(format!(
+            "query too long: {} chars (max 400)",
+            q.len()
+        )));
+    }
+
+    let word_count = q.split_whitespace().count();
+    if word_count > 50 {
+        return Err(BraveError::InvalidQuery(format!(
+            "query too long: {} words (max 50)",
+            word_count
+        )));
+    }
+
+    Ok(())
+}
+
+/// Validate a freshness filter: pd|pw|pm|py or YYYY-MM-DDtoYYYY-MM-DD.
+///
+/// A custom range must run forwards and end no later than today (UTC).
+pub(crate) fn validate_freshness(freshness: &str) -> Result<(), BraveError> {
+    const VALID_PRESETS: &[&str] = &["pd", "pw", "pm", "py"];
+
+    if VALID_PRESETS.contains(&freshness) {
+        return Ok(());
+    }
+
+    let (start, end) = parse_freshness_range(freshness)?;
+    if start > end {
+        return Err(BraveError::InvalidFreshness(format!(
+            "{freshness}: range starts after it ends"
+        )));
+    }
+    let today = chrono::Utc::now().date_naive();
+    if end > today {
+        return Err(BraveError::InvalidFreshness(format!(
+            "{freshness}: range ends after today ({today})"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Parse a custom freshness range "YYYY-MM-DDtoYYYY-MM-DD" into its start and end dates.
+pub(crate) fn parse_freshness_range(freshness: &str) -> Result<(NaiveDate, NaiveDate), BraveError> {
+    let syntax_error = || {
+        BraveError::InvalidFreshness(format!(
+            "{freshness}: expected pd, pw, pm, py, or YYYY-MM-DDtoYYYY-MM-DD"
+        ))
+    };
+    let (start, end) = freshness.split_once("to").ok_or_else(syntax_error)?;
+    let parse = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| syntax_error());
+    Ok((parse(start)?, parse(end)?))
+}
+
+/// Validate a result filter: comma-separated names from [`RESULT_FILTER_SECTIONS`].
+pub(crate) fn validate_result_filter(filter: &str) -> Result<(), BraveError> {
+    match filter
+        .split(',')
+        .map(str::trim)
+        .find(|section| !RESULT_FILTER_SECTIONS.contains(section))
+    {
+        Some(section) => Err(BraveError::InvalidResultFilter(section.to_string())),
+        None => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::BraveError;
-
     use super::*;
 
     #[test]
@@ -170,7 +226,7 @@ mod tests {
     #[test]
     fn test_invalid_count() {
         let req = SearchRequest { q: "test".to_string(), count: Some(25), ..Default::default() };
-        assert!(matches!(req.validate(), Err(BraveError::InvalidCount)));
+        assert!(matches!(req.validate(), Err(BraveError::InvalidCount(20))));
     }
 
     #[test]
@@ -207,6 +263,62 @@ mod tests {
         assert!(matches!(req.validate(), Err(BraveError::InvalidFreshness(_))));
     }
 
+    #[test]
+    fn test_freshness_range_syntax() {
+        for freshness in [
+            "2024-99-99to2024-00-00",
+            "2024-01-01to",
+            "2024-01-01to2024-02-30",
+            "2024-01-01..2024-02-01",
+        ] {
+            match validate_freshness(freshness) {
+                Err(BraveError::InvalidFreshness(msg)) => assert!(msg.contains("expected"), "{freshness}: {msg}"),
+                other => panic!("{freshness}: expected InvalidFreshness, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_freshness_range_order() {
+        assert!(validate_freshness("2024-01-01to2024-01-01").is_ok());
+        match validate_freshness("2024-02-01to2024-01-01") {
+            Err(BraveError::InvalidFreshness(msg)) => assert!(msg.contains("starts after it ends"), "{msg}"),
+            other => panic!("expected InvalidFreshness, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_freshness_range_end_not_in_future() {
+        let today = chrono::Utc::now().date_naive();
+        let tomorrow = today + chrono::Duration::days(1);
+        assert!(validate_freshness(&format!("2024-01-01to{today}")).is_ok());
+        match validate_freshness(&format!("2024-01-01to{tomorrow}")) {
+            Err(BraveError::InvalidFreshness(msg)) => assert!(msg.contains("after today"), "{msg}"),
+            other => panic!("expected InvalidFreshness, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_valid_result_filters() {
+        for filter in ["web", "web,news,videos", "faq, discussions"] {
+            let req =
+                SearchRequest { q: "test".to_string(), result_filter: Some(filter.to_string()), ..Default::default() };
+            assert!(req.validate().is_ok(), "result_filter {filter} should be valid");
+        }
+    }
+
+    #[test]
+    fn test_invalid_result_filter_names_token() {
+        for (filter, token) in [("images", "images"), ("web,imgs,news", "imgs"), ("web,", "")] {
+            let req =
+                SearchRequest { q: "test".to_string(), result_filter: Some(filter.to_string()), ..Default::default() };
+            match req.validate() {
+                Err(BraveError::InvalidResultFilter(invalid)) => assert_eq!(invalid, token, "{filter}"),
+                other => panic!("{filter}: expected InvalidResultFilter, got {other:?}"),
+            }
+        }
+    }
+
     #[test]
     fn test_defaults() {
         let req = SearchRequest { q: "test".to_string(), ..Default::default() };
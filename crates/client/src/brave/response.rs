@@ -1,14 +1,94 @@
 //! Brave Search API response types and normalization.
 
+use chrono::{DateTime, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
 use std::time::Instant;
 
+use super::news::{MetaUrl, NewsApiResult, NewsResult, normalize_news};
+
+/// Absolute date formats seen in Brave's `age` field, e.g. "January 5, 2024".
+const DATE_FORMATS: &[&str] = &["%B %d, %Y", "%d %B %Y", "%Y-%m-%d"];
+
 /// Raw response from Brave Web Search API.
 #[derive(Debug, Deserialize)]
 pub struct BraveApiResponse {
     pub query: QueryInfo,
     #[serde(default)]
     pub web: Option<WebResults>,
+    #[serde(default)]
+    pub videos: Option<Section<VideoApiResult>>,
+    #[serde(default)]
+    pub faq: Option<Section<FaqApiResult>>,
+    #[serde(default)]
+    pub discussions: Option<Section<DiscussionApiResult>>,
+    #[serde(default)]
+    pub news: Option<Section<NewsApiResult>>,
+}
+
+/// A non-web result section (videos, faq, discussions, news).
+#[derive(Debug, Deserialize)]
+pub struct Section<T> {
+    #[serde(default)]
+    pub results: Vec<T>,
+}
+
+/// Video result from Brave's `videos` section.
+#[derive(Debug, Deserialize)]
+pub struct VideoApiResult {
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub age: Option<String>,
+    #[serde(default)]
+    pub thumbnail: Option<super::news::Thumbnail>,
+    #[serde(default)]
+    pub video: Option<VideoData>,
+}
+
+/// Video details nested in a video result.
+#[derive(Debug, Default, Deserialize)]
+pub struct VideoData {
+    /// Duration as displayed, e.g. "12:34".
+    #[serde(default)]
+    pub duration: Option<String>,
+    #[serde(default)]
+    pub creator: Option<String>,
+    #[serde(default)]
+    pub publisher: Option<String>,
+}
+
+/// Question and answer from Brave's `faq` section.
+#[derive(Debug, Deserialize)]
+pub struct FaqApiResult {
+    pub question: String,
+    pub answer: String,
+    #[serde(default)]
+    pub title: String,
+    pub url: String,
+}
+
+/// Forum thread from Brave's `discussions` section.
+#[derive(Debug, Deserialize)]
+pub struct DiscussionApiResult {
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub age: Option<String>,
+    #[serde(default)]
+    pub data: Option<DiscussionData>,
+}
+
+/// Forum details nested in a discussion result.
+#[derive(Debug, Default, Deserialize)]
+pub struct DiscussionData {
+    #[serde(default)]
+    pub forum_name: Option<String>,
+    #[serde(default)]
+    pub num_answers: Option<u32>,
 }
 
 /// Query metadata from Brave response.
@@ -18,6 +98,15 @@ pub struct QueryInfo {
     #[serde(default)]
     #[serde(alias = "moreResultsAvailable")]
     pub more_results_available: bool,
+    /// The query Brave searched for instead, e.g. after a spelling correction.
+    #[serde(default)]
+    pub altered: Option<String>,
+    /// Whether spellcheck was disabled for the query.
+    #[serde(default)]
+    pub spellcheck_off: Option<bool>,
+    /// Whether Brave considers the query a lookup of a specific site.
+    #[serde(default)]
+    pub is_navigational: Option<bool>,
 }
 
 /// Web search results container.
@@ -35,12 +124,42 @@ pub struct WebResult {
     pub description: String,
     #[serde(default)]
     pub extra_snippets: Vec<String>,
+    /// Display age, relative ("2 days ago") or absolute ("January 5, 2024").
+    #[serde(default)]
+    pub age: Option<String>,
+    /// Page date as an ISO timestamp without offset, e.g. "2024-01-05T12:00:00".
+    #[serde(default)]
+    pub page_age: Option<String>,
+    /// Page language, e.g. "en".
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub meta_url: Option<MetaUrl>,
+    #[serde(default)]
+    pub profile: Option<Profile>,
+}
+
+/// Site profile Brave attaches to web results.
+#[derive(Debug, Deserialize)]
+pub struct Profile {
+    /// Site icon; used when `meta_url` has no favicon.
+    #[serde(default)]
+    pub img: Option<String>,
 }
 
 /// Normalized search response for internal use.
 #[derive(Debug, Clone, Serialize)]
 pub struct SearchResponse {
     pub results: Vec<SearchResult>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub videos: Vec<VideoResult>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub faq: Vec<FaqResult>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub discussions: Vec<DiscussionResult>,
+    /// The news cluster Brave mixes into web results for topical queries.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub news: Vec<NewsResult>,
     pub query: QueryMeta,
     pub debug: DebugInfo,
 }
@@ -53,15 +172,88 @@ pub struct SearchResult {
     pub description: String,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub extra_snippets: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_age: Option<String>,
+    /// `page_age` or `age` normalized to RFC3339 UTC; see [`SearchResponse::with_published_dates`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub favicon: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
     pub source: String,
     pub rank: usize,
 }
 
+/// Normalized video result.
+#[derive(Debug, Clone, Serialize)]
+pub struct VideoResult {
+    pub title: String,
+    pub url: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<String>,
+    /// Channel or creator, falling back to the publishing platform.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_url: Option<String>,
+}
+
+/// Normalized FAQ entry.
+#[derive(Debug, Clone, Serialize)]
+pub struct FaqResult {
+    pub question: String,
+    pub answer: String,
+    /// Title of the page the answer comes from.
+    pub title: String,
+    pub url: String,
+}
+
+/// Normalized forum discussion.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscussionResult {
+    pub title: String,
+    pub url: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forum_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_answers: Option<u32>,
+}
+
 /// Normalized query metadata.
 #[derive(Debug, Clone, Serialize)]
 pub struct QueryMeta {
     pub original: String,
     pub more_results_available: bool,
+    /// Set when Brave rewrote the query; results are for this query, not `original`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub altered: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spellcheck_off: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_navigational: Option<bool>,
+}
+
+impl From<QueryInfo> for QueryMeta {
+    fn from(raw: QueryInfo) -> Self {
+        Self {
+            original: raw.original,
+            more_results_available: raw.more_results_available,
+            altered: raw.altered,
+            spellcheck_off: raw.spellcheck_off,
+            is_navigational: raw.is_navigational,
+        }
+    }
 }
 
 /// Debug information for the search.
@@ -69,6 +261,8 @@ pub struct QueryMeta {
 pub struct DebugInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub request_id: Option<String>,
+    /// Requests made, retries included; more than 1 means Brave throttled or failed transiently.
+    pub attempts: u8,
 }
 
 impl From<BraveApiResponse> for SearchResponse {
@@ -80,22 +274,67 @@ impl From<BraveApiResponse> for SearchResponse {
                 w.results
                     .into_iter()
                     .enumerate()
-                    .map(|(idx, r)| SearchResult {
-                        title: r.title,
-                        url: r.source_url.clone(),
-                        description: r.description,
-                        extra_snippets: r.extra_snippets,
-                        source: "brave".to_string(),
-                        rank: idx + 1,
+                    .map(|(idx, r)| {
+                        let (hostname, favicon) = r.meta_url.map(|m| (m.hostname, m.favicon)).unwrap_or_default();
+                        SearchResult {
+                            title: r.title,
+                            url: r.source_url.clone(),
+                            description: r.description,
+                            extra_snippets: r.extra_snippets,
+                            age: r.age,
+                            page_age: r.page_age,
+                            published_at: None,
+                            language: r.language,
+                            favicon: favicon.or_else(|| r.profile.and_then(|p| p.img)),
+                            hostname,
+                            source: "brave".to_string(),
+                            rank: idx + 1,
+                        }
                     })
                     .collect()
             })
             .unwrap_or_default();
 
+        let videos = section(raw.videos)
+            .map(|v| {
+                let video = v.video.unwrap_or_default();
+                VideoResult {
+                    title: v.title,
+                    url: v.url,
+                    description: v.description,
+                    age: v.age,
+                    duration: video.duration,
+                    creator: video.creator.or(video.publisher),
+                    thumbnail_url: v.thumbnail.map(|t| t.src),
+                }
+            })
+            .collect();
+        let faq = section(raw.faq)
+            .map(|f| FaqResult { question: f.question, answer: f.answer, title: f.title, url: f.url })
+            .collect();
+        let discussions = section(raw.discussions)
+            .map(|d| {
+                let data = d.data.unwrap_or_default();
+                DiscussionResult {
+                    title: d.title,
+                    url: d.url,
+                    description: d.description,
+                    age: d.age,
+                    forum_name: data.forum_name,
+                    num_answers: data.num_answers,
+                }
+            })
+            .collect();
+        let news = normalize_news(raw.news.map(|n| n.results).unwrap_or_default());
+
         SearchResponse {
             results,
-            query: QueryMeta { original: raw.query.original, more_results_available: raw.query.more_results_available },
-            debug: DebugInfo { request_id: None },
+            videos,
+            faq,
+            discussions,
+            news,
+            query: raw.query.into(),
+            debug: DebugInfo { request_id: None, attempts: 1 },
         }
     }
 }
@@ -107,6 +346,20 @@ impl SearchResponse {
         self
     }
 
+    /// Fill in each result's `published_at`, resolving relative ages against `now`.
+    ///
+    /// `page_age` is preferred since it is a full timestamp; results whose
+    /// dates can't be parsed stay undated.
+    pub fn with_published_dates(mut self, now: DateTime<Utc>) -> Self {
+        for result in &mut self.results {
+            result.published_at = published_at(result.page_age.as_deref(), result.age.as_deref(), now);
+        }
+        for result in &mut self.news {
+            result.published_at = published_at(result.page_age.as_deref(), result.age.as_deref(), now);
+        }
+        self
+    }
+
     /// Check if there are more results available.
     pub fn has_more(&self) -> bool {
         self.query.more_results_available
@@ -118,6 +371,75 @@ impl SearchResponse {
     }
 }
 
+/// Results of an optional response section.
+fn section<T>(section: Option<Section<T>>) -> impl Iterator<Item = T> {
+    section.map(|s| s.results).unwrap_or_default().into_iter()
+}
+
+/// `page_age`, else `age`, resolved against `now` and formatted as RFC3339 UTC.
+pub(crate) fn published_at(page_age: Option<&str>, age: Option<&str>, now: DateTime<Utc>) -> Option<String> {
+    [page_age, age]
+        .into_iter()
+        .flatten()
+        .find_map(|value| parse_age(value, now))
+        .map(|date| date.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+/// Parse one of Brave's age formats into a UTC timestamp.
+///
+/// Accepts RFC3339, ISO timestamps without an offset (taken as UTC), dates like
+/// "2024-01-05", "January 5, 2024" or "5 Jan 2024", and relative ages like
+/// "2 days ago", "an hour ago", "3h ago" or "yesterday", resolved against `now`.
+/// Months and years are calendar months back from `now`.
+pub fn parse_age(value: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f") {
+        return Some(date.and_utc());
+    }
+    if let Some(date) = DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+    {
+        return Some(date.and_time(NaiveTime::MIN).and_utc());
+    }
+    parse_relative_age(&value.to_ascii_lowercase(), now)
+}
+
+/// Parse a relative age such as "2 days ago" or "5m ago".
+fn parse_relative_age(value: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    match value {
+        "just now" | "now" | "today" => return Some(now),
+        "yesterday" => return Some(now - Duration::days(1)),
+        _ => {}
+    }
+
+    let rest = value.strip_suffix("ago")?.trim();
+    let (amount, unit) = match rest.split_once(' ') {
+        Some((amount, unit)) => (amount, unit.trim()),
+        None => rest.split_at(rest.find(|c: char| !c.is_ascii_digit())?),
+    };
+    let amount: u32 = match amount {
+        "a" | "an" | "one" => 1,
+        digits => digits.parse().ok()?,
+    };
+    let unit = unit.strip_suffix('s').filter(|u| !u.is_empty()).unwrap_or(unit);
+
+    let seconds = match unit {
+        "second" | "sec" | "s" => 1,
+        "minute" | "min" | "m" => 60,
+        "hour" | "hr" | "h" => 3600,
+        "day" | "d" => 86_400,
+        "week" | "wk" | "w" => 7 * 86_400,
+        "month" | "mo" => return now.checked_sub_months(Months::new(amount)),
+        "year" | "yr" | "y" => return now.checked_sub_months(Months::new(amount.checked_mul(12)?)),
+        _ => return None,
+    };
+    now.checked_sub_signed(Duration::seconds(i64::from(amount) * seconds))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -133,7 +455,9 @@ mod tests {
                     "title": "Example Domain",
                     "url": "https://example.com",
                     "description": "This domain is for use in illustrative examples",
-                    "extra_snippets": ["First snippet", "Second snippet"]
+                    "extra_snippets": ["First snippet", "Second snippet"],
+                    "age": "2 days ago",
+                    "page_age": "2024-03-08T09:30:00"
                 },
                 {
                     "title": "Test Page",
@@ -175,6 +499,329 @@ mod tests {
         assert_eq!(second.extra_snippets.len(), 0);
     }
 
+    /// A trimmed mixed response, shaped like Brave's for "rust async runtime".
+    const MIXED_FIXTURE_JSON: &str = r#"{
+        "type": "search",
+        "query": { "original": "rust async runtime", "more_results_available": true },
+        "mixed": {
+            "type": "mixed",
+            "main": [
+                { "type": "web", "index": 0, "all": false },
+                { "type": "faq", "all": true },
+                { "type": "discussions", "all": true },
+                { "type": "videos", "all": true },
+                { "type": "news", "all": true }
+            ]
+        },
+        "web": {
+            "type": "search",
+            "results": [
+                {
+                    "type": "search_result",
+                    "title": "Tokio - An asynchronous Rust runtime",
+                    "url": "https://tokio.rs/",
+                    "description": "Tokio is an asynchronous runtime for the Rust programming language.",
+                    "language": "en",
+                    "profile": { "name": "Tokio", "url": "https://tokio.rs/" }
+                }
+            ]
+        },
+        "faq": {
+            "type": "faq",
+            "results": [
+                {
+                    "question": "What is the best async runtime for Rust?",
+                    "answer": "Tokio is the most widely used async runtime.",
+                    "title": "Choosing an async runtime",
+                    "url": "https://forum.example.org/t/choosing-a-runtime/1234",
+                    "meta_url": { "hostname": "forum.example.org" }
+                }
+            ]
+        },
+        "discussions": {
+            "type": "search",
+            "results": [
+                {
+                    "type": "discussion",
+                    "title": "Tokio vs async-std in 2024?",
+                    "url": "https://www.reddit.com/r/rust/comments/abc123/tokio_vs_asyncstd/",
+                    "description": "Which runtime are people using for new projects?",
+                    "age": "March 2, 2024",
+                    "data": {
+                        "forum_name": "r/rust",
+                        "num_answers": 87,
+                        "score": "212",
+                        "question": "Tokio vs async-std in 2024?",
+                        "top_comment": "Tokio, unless you need something embedded."
+                    }
+                }
+            ]
+        },
+        "videos": {
+            "type": "videos",
+            "results": [
+                {
+                    "type": "video_result",
+                    "title": "Async Rust in depth",
+                    "url": "https://www.youtube.com/watch?v=abcdefghijk",
+                    "description": "A walkthrough of futures and executors.",
+                    "age": "1 year ago",
+                    "thumbnail": { "src": "https://imgs.search.brave.com/video.jpg", "original": "https://i.ytimg.com/vi/abc/hq.jpg" },
+                    "video": { "duration": "1:02:15", "views": 48000, "creator": "Rust Talks", "publisher": "YouTube" },
+                    "meta_url": { "hostname": "www.youtube.com" }
+                },
+                {
+                    "title": "Tokio tutorial",
+                    "url": "https://videos.example.com/tokio",
+                    "video": { "publisher": "Example Videos" }
+                }
+            ]
+        },
+        "news": {
+            "type": "news",
+            "results": [
+                {
+                    "title": "Tokio 1.36 released",
+                    "url": "https://blog.example.com/tokio-1-36",
+                    "description": "The release adds new runtime metrics.",
+                    "age": "2 days ago",
+                    "page_age": "2024-03-08T09:30:00",
+                    "meta_url": { "hostname": "blog.example.com" },
+                    "thumbnail": { "src": "https://imgs.search.brave.com/news.jpg" }
+                }
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn test_result_metadata_is_kept() {
+        let raw: BraveApiResponse = serde_json::from_str(
+            r#"{
+                "query": { "original": "tokio" },
+                "web": { "results": [
+                    {
+                        "title": "Tokio",
+                        "url": "https://tokio.rs/tokio/tutorial",
+                        "description": "Tutorial",
+                        "language": "en",
+                        "family_friendly": true,
+                        "profile": { "name": "Tokio", "img": "https://imgs.search.brave.com/profile.png" },
+                        "meta_url": {
+                            "scheme": "https",
+                            "netloc": "tokio.rs",
+                            "hostname": "tokio.rs",
+                            "favicon": "https://imgs.search.brave.com/favicon.png",
+                            "path": "› tokio › tutorial"
+                        }
+                    },
+                    {
+                        "title": "Profile only",
+                        "url": "https://example.com/",
+                        "description": "No meta_url",
+                        "profile": { "img": "https://imgs.search.brave.com/example.png" }
+                    }
+                ] }
+            }"#,
+        )
+        .unwrap();
+        let results = SearchResponse::from(raw).results;
+
+        let first = &results[0];
+        assert_eq!(first.language.as_deref(), Some("en"));
+        assert_eq!(first.hostname.as_deref(), Some("tokio.rs"));
+        assert_eq!(
+            first.favicon.as_deref(),
+            Some("https://imgs.search.brave.com/favicon.png")
+        );
+
+        let second = &results[1];
+        assert_eq!((second.language.as_deref(), second.hostname.as_deref()), (None, None));
+        assert_eq!(
+            second.favicon.as_deref(),
+            Some("https://imgs.search.brave.com/example.png")
+        );
+    }
+
+    #[test]
+    fn test_altered_query_is_kept() {
+        let raw: BraveApiResponse = serde_json::from_str(
+            r#"{
+                "query": {
+                    "original": "rust programing",
+                    "altered": "rust programming",
+                    "show_strict_warning": false,
+                    "spellcheck_off": false,
+                    "is_navigational": false,
+                    "more_results_available": true
+                },
+                "web": { "results": [] }
+            }"#,
+        )
+        .unwrap();
+        let query = SearchResponse::from(raw).query;
+        assert_eq!(query.altered.as_deref(), Some("rust programming"));
+        assert_eq!(
+            (query.spellcheck_off, query.is_navigational),
+            (Some(false), Some(false))
+        );
+
+        let raw: BraveApiResponse = serde_json::from_str(FIXTURE_JSON).unwrap();
+        let json = serde_json::to_value(SearchResponse::from(raw).query).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "original": "test query", "more_results_available": true })
+        );
+    }
+
+    #[test]
+    fn test_sections_absent_when_not_returned() {
+        let raw: BraveApiResponse = serde_json::from_str(FIXTURE_JSON).unwrap();
+        let normalized = SearchResponse::from(raw);
+        assert!(normalized.videos.is_empty() && normalized.faq.is_empty());
+        assert!(normalized.discussions.is_empty() && normalized.news.is_empty());
+
+        let json = serde_json::to_value(&normalized).unwrap();
+        for key in ["videos", "faq", "discussions", "news"] {
+            assert!(json.get(key).is_none(), "{key} should be skipped when empty");
+        }
+    }
+
+    #[test]
+    fn test_parse_video_section() {
+        let raw: BraveApiResponse = serde_json::from_str(MIXED_FIXTURE_JSON).unwrap();
+        let videos = SearchResponse::from(raw).videos;
+        assert_eq!(videos.len(), 2);
+        assert_eq!(videos[0].title, "Async Rust in depth");
+        assert_eq!(videos[0].duration.as_deref(), Some("1:02:15"));
+        assert_eq!(videos[0].creator.as_deref(), Some("Rust Talks"));
+        assert_eq!(
+            videos[0].thumbnail_url.as_deref(),
+            Some("https://imgs.search.brave.com/video.jpg")
+        );
+        assert_eq!(videos[1].creator.as_deref(), Some("Example Videos"));
+        assert_eq!(
+            (videos[1].description.as_str(), videos[1].duration.as_deref()),
+            ("", None)
+        );
+    }
+
+    #[test]
+    fn test_parse_faq_section() {
+        let raw: BraveApiResponse = serde_json::from_str(MIXED_FIXTURE_JSON).unwrap();
+        let faq = SearchResponse::from(raw).faq;
+        assert_eq!(faq.len(), 1);
+        assert_eq!(faq[0].question, "What is the best async runtime for Rust?");
+        assert_eq!(faq[0].answer, "Tokio is the most widely used async runtime.");
+        assert_eq!(faq[0].url, "https://forum.example.org/t/choosing-a-runtime/1234");
+    }
+
+    #[test]
+    fn test_parse_discussion_section() {
+        let raw: BraveApiResponse = serde_json::from_str(MIXED_FIXTURE_JSON).unwrap();
+        let discussions = SearchResponse::from(raw).discussions;
+        assert_eq!(discussions.len(), 1);
+        assert_eq!(discussions[0].forum_name.as_deref(), Some("r/rust"));
+        assert_eq!(discussions[0].num_answers, Some(87));
+        assert_eq!(discussions[0].age.as_deref(), Some("March 2, 2024"));
+    }
+
+    #[test]
+    fn test_parse_news_section() {
+        let raw: BraveApiResponse = serde_json::from_str(MIXED_FIXTURE_JSON).unwrap();
+        let normalized = SearchResponse::from(raw).with_published_dates(now());
+        assert_eq!(normalized.results.len(), 1);
+        assert_eq!(normalized.news.len(), 1);
+
+        let news = &normalized.news[0];
+        assert_eq!((news.rank, news.source.as_str()), (1, "blog.example.com"));
+        assert_eq!(
+            news.thumbnail_url.as_deref(),
+            Some("https://imgs.search.brave.com/news.jpg")
+        );
+        assert_eq!(news.published_at.as_deref(), Some("2024-03-08T09:30:00Z"));
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-03-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn age(value: &str) -> Option<String> {
+        parse_age(value, now()).map(|d| d.to_rfc3339_opts(SecondsFormat::Secs, true))
+    }
+
+    #[test]
+    fn test_parse_age_absolute_formats() {
+        assert_eq!(age("2024-03-08T09:30:00").as_deref(), Some("2024-03-08T09:30:00Z"));
+        assert_eq!(
+            age("2024-03-08T09:30:00.123456").as_deref(),
+            Some("2024-03-08T09:30:00Z")
+        );
+        assert_eq!(
+            age("2024-03-08T09:30:00+02:00").as_deref(),
+            Some("2024-03-08T07:30:00Z")
+        );
+        assert_eq!(age("2024-03-08").as_deref(), Some("2024-03-08T00:00:00Z"));
+        assert_eq!(age("January 5, 2024").as_deref(), Some("2024-01-05T00:00:00Z"));
+        assert_eq!(age("Jan 5, 2024").as_deref(), Some("2024-01-05T00:00:00Z"));
+        assert_eq!(age("5 January 2024").as_deref(), Some("2024-01-05T00:00:00Z"));
+        assert_eq!(age(" 05 Jan 2024 ").as_deref(), Some("2024-01-05T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_parse_age_relative_formats() {
+        assert_eq!(age("2 days ago").as_deref(), Some("2024-03-08T12:00:00Z"));
+        assert_eq!(age("1 day ago").as_deref(), Some("2024-03-09T12:00:00Z"));
+        assert_eq!(age("an hour ago").as_deref(), Some("2024-03-10T11:00:00Z"));
+        assert_eq!(age("a minute ago").as_deref(), Some("2024-03-10T11:59:00Z"));
+        assert_eq!(age("30 seconds ago").as_deref(), Some("2024-03-10T11:59:30Z"));
+        assert_eq!(age("15 mins ago").as_deref(), Some("2024-03-10T11:45:00Z"));
+        assert_eq!(age("3 hrs ago").as_deref(), Some("2024-03-10T09:00:00Z"));
+        assert_eq!(age("3h ago").as_deref(), Some("2024-03-10T09:00:00Z"));
+        assert_eq!(age("2 weeks ago").as_deref(), Some("2024-02-25T12:00:00Z"));
+        assert_eq!(age("1 month ago").as_deref(), Some("2024-02-10T12:00:00Z"));
+        assert_eq!(age("2 Years Ago").as_deref(), Some("2022-03-10T12:00:00Z"));
+        assert_eq!(age("yesterday").as_deref(), Some("2024-03-09T12:00:00Z"));
+        assert_eq!(age("just now").as_deref(), Some("2024-03-10T12:00:00Z"));
+    }
+
+    #[test]
+    fn test_parse_age_rejects_unknown() {
+        for value in [
+            "",
+            "recently",
+            "2 fortnights ago",
+            "ago",
+            "many days ago",
+            "March 2024",
+            "13/01/2024",
+        ] {
+            assert_eq!(age(value), None, "{value:?}");
+        }
+    }
+
+    #[test]
+    fn test_with_published_dates_prefers_page_age() {
+        let raw: BraveApiResponse = serde_json::from_str(FIXTURE_JSON).unwrap();
+        let response = SearchResponse::from(raw).with_published_dates(now());
+
+        assert_eq!(response.results[0].age.as_deref(), Some("2 days ago"));
+        assert_eq!(
+            response.results[0].published_at.as_deref(),
+            Some("2024-03-08T09:30:00Z")
+        );
+        assert_eq!(response.results[1].published_at, None);
+
+        let mut relative = response.clone();
+        relative.results[0].page_age = Some("not a date".into());
+        let relative = relative.with_published_dates(now());
+        assert_eq!(
+            relative.results[0].published_at.as_deref(),
+            Some("2024-03-08T12:00:00Z")
+        );
+    }
+
     #[test]
     fn test_empty_results() {
         let json = r#"{"query": {"original": "test"}, "web": {"results": []}}"#;
@@ -0,0 +1,56 @@
+//! Brave Suggest API request and response types.
+//!
+//! The suggest endpoint (`/suggest/search`) returns query completions, which
+//! are cheap to fetch and help refine a vague query before a full search.
+
+use serde::{Deserialize, Serialize};
+
+/// Query parameters sent to the suggest endpoint.
+#[derive(Debug, Serialize)]
+pub(crate) struct SuggestRequest<'a> {
+    pub q: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<&'a str>,
+}
+
+/// Raw response from Brave Suggest API.
+#[derive(Debug, Deserialize)]
+pub struct BraveSuggestApiResponse {
+    #[serde(default)]
+    pub results: Vec<Suggestion>,
+}
+
+/// One suggested query completion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suggestion {
+    /// The suggested query.
+    pub query: String,
+    /// Whether the suggestion names an entity (a person, place, product, ...).
+    #[serde(default)]
+    pub is_entity: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_suggestions() {
+        let raw: BraveSuggestApiResponse = serde_json::from_str(
+            r#"{
+                "type": "suggest",
+                "query": { "original": "rust lang" },
+                "results": [
+                    { "query": "rust language" },
+                    { "query": "rust lang book", "is_entity": false },
+                    { "query": "rust programming language", "is_entity": true, "title": "Rust" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let entities: Vec<bool> = raw.results.iter().map(|s| s.is_entity).collect();
+        assert_eq!(entities, vec![false, false, true]);
+        assert_eq!(raw.results[0].query, "rust language");
+    }
+}
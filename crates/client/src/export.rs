@@ -0,0 +1,294 @@
+//! Best-effort export of newly cached documents.
+//!
+//! After a snapshot is written, its normalized markdown can be mirrored into a
+//! directory as `<slugified-title>-<short-hash>.md` (for notes tools such as
+//! Obsidian) and/or the tool output POSTed to a webhook. Export failures are
+//! logged and never surface to the caller.
+//!
+//! Webhook hosts that are not allowlisted are connected to through
+//! [`PublicResolver`], so the addresses validated are the ones posted to and a
+//! name re-resolving to a private address between the check and the connect
+//! still fails. Redirects are never followed.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use thndrs_core::Error;
+
+use crate::fetch::ssrf::blocked_ip;
+use crate::fetch::{PublicResolver, canonicalize, validate_host};
+
+/// Maximum length of the title slug in exported filenames.
+const MAX_SLUG_CHARS: usize = 60;
+
+/// Number of hash characters appended to exported filenames.
+const SHORT_HASH_CHARS: usize = 12;
+
+/// Export destinations.
+#[derive(Debug, Clone, Default)]
+pub struct ExportConfig {
+    /// Directory to write markdown files into.
+    pub dir: Option<PathBuf>,
+    /// Webhook receiving the tool output as JSON.
+    pub webhook_url: Option<String>,
+    /// Webhook hosts exempt from SSRF validation (e.g. a local notes service).
+    pub webhook_allow_hosts: Vec<String>,
+}
+
+impl ExportConfig {
+    /// Whether any export destination is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.dir.is_some() || self.webhook_url.is_some()
+    }
+}
+
+/// A document to export.
+#[derive(Debug, Clone)]
+pub struct ExportDoc {
+    pub hash: String,
+    pub title: Option<String>,
+    /// Normalized markdown (with frontmatter); nothing is written to `dir` without it.
+    pub markdown: Option<String>,
+    /// JSON payload POSTed to the webhook.
+    pub payload: serde_json::Value,
+}
+
+/// Writes exported documents to the configured destinations.
+///
+/// Build one and share it: clones share the webhook connection pools.
+#[derive(Debug, Clone)]
+pub struct Exporter {
+    config: ExportConfig,
+    /// Client for hosts that must resolve to public addresses.
+    http: reqwest::Client,
+    /// Client for `webhook_allow_hosts`, resolved by the system resolver.
+    allowlisted_http: reqwest::Client,
+}
+
+impl Exporter {
+    /// Create an exporter for the given destinations.
+    pub fn new(config: ExportConfig) -> Result<Self, Error> {
+        Ok(Self { config, http: webhook_client(true)?, allowlisted_http: webhook_client(false)? })
+    }
+
+    /// Export to every configured destination, logging failures.
+    pub async fn export(&self, doc: &ExportDoc) {
+        if let Some(dir) = &self.config.dir
+            && let Some(markdown) = &doc.markdown
+        {
+            match write_markdown(dir, doc.title.as_deref(), &doc.hash, markdown).await {
+                Ok(path) => tracing::debug!("exported {} to {}", doc.hash, path.display()),
+                Err(e) => tracing::warn!("markdown export failed for {}: {}", doc.hash, e),
+            }
+        }
+
+        if let Some(url) = &self.config.webhook_url
+            && let Err(e) = self.post_webhook(url, &doc.payload).await
+        {
+            tracing::warn!("webhook export failed for {}: {}", doc.hash, e);
+        }
+    }
+
+    /// POST a JSON payload to the webhook after SSRF validation.
+    pub async fn post_webhook(&self, url: &str, payload: &serde_json::Value) -> Result<(), Error> {
+        let url = canonicalize(url).map_err(|e| Error::InvalidUrl(e.to_string()))?;
+        let host = url.host_str().unwrap_or_default();
+
+        let allowlisted = self
+            .config
+            .webhook_allow_hosts
+            .iter()
+            .any(|h| h.eq_ignore_ascii_case(host));
+        let http = if allowlisted {
+            &self.allowlisted_http
+        } else {
+            validate_host(host, url.port_or_known_default().unwrap_or(443))
+                .await
+                .map_err(|e| Error::SsrfBlocked(format!("webhook {host}: {e}")))?;
+            &self.http
+        };
+
+        let response = http
+            .post(url.as_str())
+            .json(payload)
+            .send()
+            .await
+            .map_err(|e| match blocked_ip(&e) {
+                Some(ip) => Error::SsrfBlocked(format!("webhook {host} resolved to blocked IP {ip}")),
+                None => Error::HttpError(format!("webhook: {e}")),
+            })?;
+
+        if !response.status().is_success() {
+            return Err(Error::HttpError(format!(
+                "webhook status {}",
+                response.status().as_u16()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Webhook HTTP client; never follows redirects, and with `public_only` resolves
+/// names through [`PublicResolver`].
+fn webhook_client(public_only: bool) -> Result<reqwest::Client, Error> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .redirect(reqwest::redirect::Policy::none());
+    if public_only {
+        builder = builder.dns_resolver(std::sync::Arc::new(PublicResolver));
+    }
+    builder
+        .build()
+        .map_err(|e| Error::HttpError(format!("failed to build webhook client: {e}")))
+}
+
+/// Build the export filename `<slugified-title>-<short-hash>.md`.
+///
+/// The slug keeps only lowercase alphanumerics separated by single dashes, so
+/// titles can never introduce path separators or `..` components. Re-exporting
+/// the same snapshot yields the same name and replaces the earlier file.
+pub fn export_filename(title: Option<&str>, hash: &str) -> String {
+    let mut slug = String::new();
+    for c in title.unwrap_or_default().chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    let mut slug: String = slug.chars().take(MAX_SLUG_CHARS).collect();
+    while slug.ends_with('-') {
+        slug.pop();
+    }
+    if slug.is_empty() {
+        slug.push_str("untitled");
+    }
+
+    let short_hash: String = hash
+        .chars()
+        .filter(char::is_ascii_hexdigit)
+        .take(SHORT_HASH_CHARS)
+        .collect();
+
+    format!("{slug}-{short_hash}.md")
+}
+
+/// Write markdown into `dir`, creating the directory if needed.
+///
+/// The file is written to a temporary name and renamed into place so readers
+/// never observe a partially written document.
+pub async fn write_markdown(dir: &Path, title: Option<&str>, hash: &str, markdown: &str) -> std::io::Result<PathBuf> {
+    tokio::fs::create_dir_all(dir).await?;
+
+    let path = dir.join(export_filename(title, hash));
+    let tmp = path.with_extension("md.tmp");
+    tokio::fs::write(&tmp, markdown).await?;
+    tokio::fs::rename(&tmp, &path).await?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{FixtureResponse, FixtureServer};
+    use std::sync::{Arc, Mutex};
+
+    const HASH: &str = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn test_export_filename() {
+        assert_eq!(
+            export_filename(Some("Hello, World!"), HASH),
+            "hello-world-0123456789ab.md"
+        );
+        assert_eq!(export_filename(None, HASH), "untitled-0123456789ab.md");
+        assert_eq!(export_filename(Some("  ***  "), HASH), "untitled-0123456789ab.md");
+    }
+
+    #[test]
+    fn test_export_filename_path_traversal() {
+        let name = export_filename(Some("../../etc/passwd"), HASH);
+        assert_eq!(name, "etc-passwd-0123456789ab.md");
+        assert!(!name.contains('/') && !name.contains(".."));
+
+        let name = export_filename(Some("C:\\Windows\\system32"), HASH);
+        assert_eq!(name, "c-windows-system32-0123456789ab.md");
+    }
+
+    #[test]
+    fn test_export_filename_truncates_long_titles() {
+        let name = export_filename(Some(&"word ".repeat(40)), HASH);
+        assert!(name.len() <= MAX_SLUG_CHARS + SHORT_HASH_CHARS + 4);
+        assert!(!name.contains("--"));
+    }
+
+    #[tokio::test]
+    async fn test_export_writes_markdown_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let markdown = "---\ntitle: \"Guide\"\nsource: https://example.com/\n---\n# Guide\n";
+        let exporter =
+            Exporter::new(ExportConfig { dir: Some(dir.path().join("notes")), ..Default::default() }).unwrap();
+
+        let doc = ExportDoc {
+            hash: HASH.to_string(),
+            title: Some("Guide".to_string()),
+            markdown: Some(markdown.to_string()),
+            payload: serde_json::json!({}),
+        };
+        exporter.export(&doc).await;
+        exporter.export(&doc).await;
+
+        let entries: Vec<_> = std::fs::read_dir(dir.path().join("notes")).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+
+        let written = std::fs::read_to_string(dir.path().join("notes/guide-0123456789ab.md")).unwrap();
+        assert_eq!(written, markdown);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_blocked_by_ssrf_unless_allowlisted() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let server = {
+            let received = received.clone();
+            FixtureServer::start(move |req| {
+                received.lock().unwrap().push(req.body.clone());
+                FixtureResponse::status(204)
+            })
+            .await
+        };
+        let payload = serde_json::json!({ "hash": HASH });
+
+        let exporter = Exporter::new(ExportConfig::default()).unwrap();
+        let err = exporter.post_webhook(&server.url("/hook"), &payload).await.unwrap_err();
+        assert!(matches!(err, Error::SsrfBlocked(_)));
+        assert!(received.lock().unwrap().is_empty());
+
+        let exporter =
+            Exporter::new(ExportConfig { webhook_allow_hosts: vec!["127.0.0.1".into()], ..Default::default() })
+                .unwrap();
+        exporter.post_webhook(&server.url("/hook"), &payload).await.unwrap();
+
+        let bodies = received.lock().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bodies[0]).unwrap();
+        assert_eq!(body, payload);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_client_refuses_private_answers_at_connect() {
+        let server = FixtureServer::start(|_| FixtureResponse::status(204)).await;
+        let by_name = server.url("/hook").replace("127.0.0.1", "localhost");
+
+        // Past the up-front check, the connection itself still resolves only to public addresses.
+        let exporter = Exporter::new(ExportConfig::default()).unwrap();
+        let err = exporter.http.post(by_name.as_str()).send().await.unwrap_err();
+        assert!(blocked_ip(&err).is_some(), "{err}");
+
+        let exporter =
+            Exporter::new(ExportConfig { webhook_allow_hosts: vec!["localhost".into()], ..Default::default() })
+                .unwrap();
+        exporter.post_webhook(&by_name, &serde_json::json!({})).await.unwrap();
+    }
+}
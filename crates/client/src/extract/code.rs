@@ -0,0 +1,317 @@
+//! Code handling for the markdown conversion.
+//!
+//! The markdown converter collapses the whitespace inside `<pre>` and drops the
+//! language hints syntax highlighters leave in class names, so code in the
+//! extracted content is swapped for placeholders before conversion and spliced
+//! back afterwards. Each outermost `<pre>` comes back as a fenced block whose
+//! info string is the detected language; `<code>` outside a `<pre>` comes back
+//! as inline code in backticks.
+//!
+//! Languages are read from `data-lang`/`data-language`, `language-*` and
+//! `lang-*` classes (Prism, highlight.js, CommonMark renderers), GitHub's
+//! `highlight-source-*`, MDN's `brush: *`, and bare language classes such as
+//! rustdoc's `rust` on the `<pre>` or its `<code>`.
+
+use scraper::{ElementRef, Html, Node, Selector};
+
+use super::placeholder::Placeholders;
+
+/// Bare class names taken as a language when found on a `<pre>` or `<code>`.
+const KNOWN_LANGUAGES: &[&str] = &[
+    "bash",
+    "c",
+    "cpp",
+    "csharp",
+    "css",
+    "diff",
+    "go",
+    "haskell",
+    "html",
+    "java",
+    "javascript",
+    "js",
+    "json",
+    "jsx",
+    "kotlin",
+    "lua",
+    "php",
+    "python",
+    "ruby",
+    "rust",
+    "scss",
+    "sh",
+    "shell",
+    "sql",
+    "swift",
+    "toml",
+    "ts",
+    "tsx",
+    "typescript",
+    "xml",
+    "yaml",
+    "zig",
+];
+
+/// Hints that mean the block has no language.
+const NO_LANGUAGE: &[&str] = &[
+    "none",
+    "nohighlight",
+    "no-highlight",
+    "plain",
+    "plaintext",
+    "text",
+    "txt",
+];
+
+/// Longest language hint kept as an info string.
+const MAX_LANGUAGE_LEN: usize = 32;
+
+/// Content HTML with its code replaced by placeholders.
+#[derive(Debug, Clone)]
+pub struct CodePass {
+    /// HTML to hand to the markdown converter
+    pub html: String,
+    placeholders: Placeholders,
+}
+
+impl CodePass {
+    /// Find the code in `content_html` and replace it with placeholders.
+    ///
+    /// Content without code is passed through unchanged.
+    pub fn new(content_html: &str) -> Self {
+        let mut fragment = Html::parse_fragment(content_html);
+        let selector = Selector::parse("pre, code").expect("valid selector");
+        let found: Vec<_> = fragment
+            .select(&selector)
+            .filter(|element| !in_pre(element))
+            .filter_map(|element| Some((element.id(), render(element)?)))
+            .collect();
+        let mut pass = Self { html: String::new(), placeholders: Placeholders::new("thndrscode") };
+        if found.is_empty() {
+            pass.html = content_html.to_string();
+            return pass;
+        }
+
+        for (id, (markdown, block)) in found {
+            let token = pass.placeholders.push(markdown, block);
+            let mut node = fragment.tree.get_mut(id).expect("selected code is in the tree");
+            node.insert_before(token);
+            node.detach();
+        }
+        pass.html = fragment.root_element().inner_html();
+        pass
+    }
+
+    /// Put the code back into the converted markdown, blocks set off by blank lines.
+    pub fn splice(&self, markdown: &str) -> String {
+        self.placeholders.splice(markdown)
+    }
+}
+
+/// Whether an element sits inside a `<pre>`, which is rendered as a whole.
+fn in_pre(element: &ElementRef<'_>) -> bool {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .any(|ancestor| ancestor.value().name() == "pre")
+}
+
+/// Markdown for a `<pre>` (a block) or an inline `<code>`, or `None` for a
+/// `<code>` wrapping a `<pre>`, which is rendered when the `<pre>` is.
+fn render(element: ElementRef<'_>) -> Option<(String, bool)> {
+    if element.value().name() == "code" {
+        let has_pre = element
+            .descendants()
+            .filter_map(ElementRef::wrap)
+            .any(|descendant| descendant.value().name() == "pre");
+        return (!has_pre).then(|| (inline_code(&code_text(element)), false));
+    }
+
+    let text = code_text(element);
+    let text = text.trim_start_matches('\n').trim_end();
+    if text.is_empty() {
+        return Some((String::new(), false));
+    }
+    let fence = "`".repeat((longest_backtick_run(text) + 1).max(3));
+    let language = language(element).unwrap_or_default();
+    Some((format!("{fence}{language}\n{text}\n{fence}"), true))
+}
+
+/// The text of a code element as written, `<br>` as a line break.
+fn code_text(element: ElementRef<'_>) -> String {
+    let mut text = String::new();
+    for node in element.descendants() {
+        match node.value() {
+            Node::Text(t) => text.push_str(t),
+            Node::Element(e) if e.name() == "br" => text.push('\n'),
+            _ => {}
+        }
+    }
+    text.replace("\r\n", "\n")
+}
+
+/// Inline code in a backtick run longer than any inside it.
+fn inline_code(text: &str) -> String {
+    let text = text.replace('\n', " ");
+    if text.trim().is_empty() {
+        return String::new();
+    }
+    let fence = "`".repeat(longest_backtick_run(&text) + 1);
+    if text.starts_with('`') || text.ends_with('`') {
+        format!("{fence} {text} {fence}")
+    } else {
+        format!("{fence}{text}{fence}")
+    }
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+/// The language of a `<pre>`, from its `<code>` child, itself, then its parent.
+fn language(pre: ElementRef<'_>) -> Option<String> {
+    let code = pre
+        .children()
+        .filter_map(ElementRef::wrap)
+        .find(|child| child.value().name() == "code");
+    let parent = pre.parent().and_then(ElementRef::wrap);
+    let hint = code
+        .into_iter()
+        .chain([pre])
+        .map(|element| (element, true))
+        .chain(parent.map(|element| (element, false)))
+        .find_map(|(element, bare)| element_language(element, bare))?;
+
+    let hint = hint.to_ascii_lowercase();
+    let valid = hint.len() <= MAX_LANGUAGE_LEN
+        && hint
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '#' | '.' | '_'));
+    (valid && !NO_LANGUAGE.contains(&hint.as_str())).then_some(hint)
+}
+
+/// A language hint on one element; bare class names count only when `bare`.
+fn element_language(element: ElementRef<'_>, bare: bool) -> Option<&str> {
+    let value = element.value();
+    if let Some(lang) = value.attr("data-lang").or_else(|| value.attr("data-language")) {
+        let lang = lang.trim();
+        if !lang.is_empty() {
+            return Some(lang);
+        }
+    }
+
+    let mut tokens = value.attr("class")?.split_whitespace();
+    let mut known = None;
+    while let Some(token) = tokens.next() {
+        let hint = if let Some(lang) = token.strip_prefix("language-").or_else(|| token.strip_prefix("lang-")) {
+            Some(lang)
+        } else if let Some(scope) = token
+            .strip_prefix("highlight-source-")
+            .or_else(|| token.strip_prefix("highlight-text-"))
+        {
+            scope.split('-').next()
+        } else if let Some(lang) = token.strip_prefix("brush:") {
+            if lang.is_empty() { tokens.next() } else { Some(lang) }
+        } else {
+            if bare && known.is_none() && KNOWN_LANGUAGES.contains(&token) {
+                known = Some(token);
+            }
+            None
+        };
+        if let Some(hint) = hint.filter(|hint| !hint.is_empty()) {
+            return Some(hint.trim_end_matches(';'));
+        }
+    }
+    known
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mdn_brush_class() {
+        let html = concat!(
+            "<p>Declare it:</p>",
+            r#"<div class="code-example"><pre class="brush: js notranslate"><code>const total = items"#,
+            "\n  .map((item) =&gt; item.price)\n  .reduce((a, b) =&gt; a + b, 0);\n</code></pre></div>",
+        );
+        let pass = CodePass::new(html);
+        assert!(!pass.html.contains("<pre"));
+        assert_eq!(
+            pass.splice("Declare it:\n\nthndrscode0x\n"),
+            concat!(
+                "Declare it:\n\n```js\nconst total = items\n",
+                "  .map((item) => item.price)\n  .reduce((a, b) => a + b, 0);\n```"
+            )
+        );
+    }
+
+    #[test]
+    fn test_rustdoc_highlighting() {
+        let html = concat!(
+            r#"<div class="example-wrap"><pre class="rust rust-example-rendered"><code>"#,
+            r#"<span class="kw">fn </span>main() {"#,
+            "\n    <span class=\"macro\">println!</span>(<span class=\"string\">\"hi\"</span>);\n}</code></pre></div>",
+            "<p>Run it with <code>cargo run</code>.</p>",
+        );
+        let pass = CodePass::new(html);
+        assert_eq!(
+            pass.splice("thndrscode0x\n\nRun it with thndrscode1x."),
+            "```rust\nfn main() {\n    println!(\"hi\");\n}\n```\n\nRun it with `cargo run`."
+        );
+    }
+
+    #[test]
+    fn test_github_readme() {
+        let html = concat!(
+            r#"<div class="highlight highlight-source-shell notranslate position-relative overflow-auto">"#,
+            r#"<pre><span class="pl-c1">cargo</span> install thndrs</pre></div>"#,
+            r#"<pre lang="toml"><code class="language-toml">[dependencies]"#,
+            "\nserde = \"1\"</code></pre>",
+            "<pre><code>no language</code></pre>",
+        );
+        let pass = CodePass::new(html);
+        assert_eq!(
+            pass.splice("Install:\nthndrscode0x\nthndrscode1x\nthndrscode2x"),
+            concat!(
+                "Install:\n\n```shell\ncargo install thndrs\n```\n\n",
+                "```toml\n[dependencies]\nserde = \"1\"\n```\n\n```\nno language\n```"
+            )
+        );
+    }
+
+    #[test]
+    fn test_language_hints() {
+        let pass = |html: &str| CodePass::new(html).splice("thndrscode0x");
+        assert_eq!(pass(r#"<pre class="hljs python">x = 1</pre>"#), "```python\nx = 1\n```");
+        assert_eq!(pass(r#"<pre data-lang="Go">x := 1</pre>"#), "```go\nx := 1\n```");
+        assert_eq!(
+            pass(r#"<pre><code class="lang-c++">int x;</code></pre>"#),
+            "```c++\nint x;\n```"
+        );
+        assert_eq!(pass(r#"<pre class="nohighlight">plain</pre>"#), "```\nplain\n```");
+        assert_eq!(pass(r#"<pre class="language-a&lt;b">x</pre>"#), "```\nx\n```");
+        assert_eq!(pass(r#"<div class="go"><pre>x</pre></div>"#), "```\nx\n```");
+    }
+
+    #[test]
+    fn test_fences_outgrow_backticks() {
+        let pass = CodePass::new("<pre>```md\n# Title\n```</pre><p><code>`a`</code> and <code>a``b</code></p>");
+        assert_eq!(
+            pass.splice("thndrscode0x\n\nthndrscode1x and thndrscode2x"),
+            "````\n```md\n# Title\n```\n````\n\n`` `a` `` and ```a``b```"
+        );
+    }
+
+    #[test]
+    fn test_line_breaks_and_plain_content() {
+        let pass = CodePass::new("<pre>one<br>two</pre>");
+        assert_eq!(pass.splice("thndrscode0x"), "```\none\ntwo\n```");
+
+        let content = "<p>No code here.</p>";
+        let pass = CodePass::new(content);
+        assert_eq!(pass.html, content);
+        assert_eq!(pass.splice("No code here."), "No code here.");
+    }
+}
@@ -0,0 +1,205 @@
+//! Input guards for extraction.
+//!
+//! Readability scoring walks the whole DOM, and pathological documents (tens of
+//! thousands of unclosed `<div>`s, generated tables with millions of cells) can
+//! keep it busy for minutes. [`check_dom`] runs a cheap tag scan before parsing
+//! and rejects documents that exceed the configured depth or element count;
+//! [`run_with_budget`] runs the extraction itself on the blocking pool and gives
+//! up once the time budget is spent.
+
+use std::time::Duration;
+use thndrs_core::Error;
+
+/// Elements that never have children.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source", "track", "wbr",
+];
+
+/// Elements whose content is text up to the matching end tag.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "textarea", "title", "xmp", "noscript"];
+
+/// Elements implicitly closed by an opening sibling of the same name.
+const SELF_CLOSING_SIBLINGS: &[&str] = &["p", "li", "dt", "dd", "option", "tr", "td", "th"];
+
+/// Element count and maximum nesting depth of a document.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct DomShape {
+    nodes: usize,
+    depth: usize,
+}
+
+/// Reject documents deeper than `max_depth` or with more than `max_nodes` elements.
+pub fn check_dom(html: &str, max_depth: Option<usize>, max_nodes: Option<usize>) -> Result<(), Error> {
+    if max_depth.is_none() && max_nodes.is_none() {
+        return Ok(());
+    }
+
+    let shape = scan(html, max_depth.unwrap_or(usize::MAX), max_nodes.unwrap_or(usize::MAX));
+    if let Some(max) = max_depth
+        && shape.depth > max
+    {
+        return Err(Error::ExtractFailed(format!("document nesting exceeds {} levels", max)));
+    }
+    if let Some(max) = max_nodes
+        && shape.nodes > max
+    {
+        return Err(Error::ExtractFailed(format!("document has more than {} elements", max)));
+    }
+    Ok(())
+}
+
+/// Run `f` on the blocking pool, failing once `budget` has elapsed.
+///
+/// The blocking thread cannot be interrupted, so on timeout it keeps running
+/// until `f` returns and its result is discarded.
+pub async fn run_with_budget<T, F>(budget: Option<Duration>, f: F) -> Result<T, Error>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, Error> + Send + 'static,
+{
+    let task = tokio::task::spawn_blocking(f);
+    let joined = match budget {
+        Some(budget) => tokio::time::timeout(budget, task)
+            .await
+            .map_err(|_| Error::ExtractFailed("extraction exceeded time budget".into()))?,
+        None => task.await,
+    };
+    joined.map_err(|e| Error::ExtractFailed(format!("extraction task failed: {}", e)))?
+}
+
+/// Count elements and track nesting, stopping early once either limit is passed.
+///
+/// This is a tag scan, not a parser: it follows end tags and the common
+/// implied closes closely enough to bound the work the real parse will do.
+fn scan(html: &str, max_depth: usize, max_nodes: usize) -> DomShape {
+    let bytes = html.as_bytes();
+    let mut shape = DomShape::default();
+    let mut open: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    while let Some(offset) = bytes[i..].iter().position(|&b| b == b'<') {
+        i += offset + 1;
+        let rest = &bytes[i..];
+
+        if rest.starts_with(b"!--") {
+            i += find(rest, b"-->").map_or(rest.len(), |end| end + 3);
+            continue;
+        }
+        if rest.first().is_some_and(|&b| b == b'!' || b == b'?') {
+            i += find(rest, b">").map_or(rest.len(), |end| end + 1);
+            continue;
+        }
+
+        let closing = rest.first() == Some(&b'/');
+        let name_start = if closing { 1 } else { 0 };
+        let name_len = rest[name_start..]
+            .iter()
+            .take_while(|b| b.is_ascii_alphanumeric() || **b == b'-')
+            .count();
+        if name_len == 0 || !rest[name_start].is_ascii_alphabetic() {
+            continue;
+        }
+        let name = String::from_utf8_lossy(&rest[name_start..name_start + name_len]).to_ascii_lowercase();
+        let tag_end = find(rest, b">").map_or(rest.len(), |end| end + 1);
+        let self_closed = tag_end >= 2 && rest[tag_end - 2] == b'/';
+        i += tag_end;
+
+        if closing {
+            if let Some(pos) = open.iter().rposition(|n| *n == name) {
+                open.truncate(pos);
+            }
+            continue;
+        }
+
+        shape.nodes += 1;
+        if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+            let end_tag = format!("</{}", name);
+            i += find_ignore_case(&bytes[i..], end_tag.as_bytes()).unwrap_or(bytes.len() - i);
+            continue;
+        }
+        if self_closed || VOID_ELEMENTS.contains(&name.as_str()) {
+            continue;
+        }
+        if SELF_CLOSING_SIBLINGS.contains(&name.as_str()) && open.last() == Some(&name) {
+            open.pop();
+        }
+
+        open.push(name);
+        shape.depth = shape.depth.max(open.len());
+        if shape.depth > max_depth || shape.nodes > max_nodes {
+            break;
+        }
+    }
+
+    shape
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn find_ignore_case(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|w| w.eq_ignore_ascii_case(needle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn shape(html: &str) -> DomShape {
+        scan(html, usize::MAX, usize::MAX)
+    }
+
+    #[test]
+    fn test_scan_counts_depth_and_nodes() {
+        let html = r#"<!DOCTYPE html><html><head><meta charset="utf-8"><title>a < b</title></head>
+            <body><!-- <div><div> --><div><p>One<p>Two<br/><img src="x"></div>
+            <script>if (a <b) { document.write("<div><div><div>") }</script></body></html>"#;
+
+        assert_eq!(shape(html), DomShape { nodes: 11, depth: 4 });
+    }
+
+    #[test]
+    fn test_check_dom_limits() {
+        let nested = "<div>".repeat(20);
+
+        assert!(check_dom(&nested, Some(20), Some(20)).is_ok());
+        assert!(check_dom(&nested, None, None).is_ok());
+
+        let err = check_dom(&nested, Some(10), None).unwrap_err();
+        assert!(matches!(err, Error::ExtractFailed(ref msg) if msg == "document nesting exceeds 10 levels"));
+
+        let flat = "<span></span>".repeat(20);
+        assert!(check_dom(&flat, Some(2), None).is_ok());
+        let err = check_dom(&flat, None, Some(10)).unwrap_err();
+        assert!(matches!(err, Error::ExtractFailed(ref msg) if msg == "document has more than 10 elements"));
+    }
+
+    #[test]
+    fn test_check_dom_fails_fast_on_huge_nesting() {
+        let html = format!("<html><body>{}text</body></html>", "<div>".repeat(500_000));
+        let start = Instant::now();
+
+        assert!(check_dom(&html, Some(512), Some(200_000)).is_err());
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_run_with_budget() {
+        let ok = run_with_budget(Some(Duration::from_secs(5)), || Ok(42)).await.unwrap();
+        assert_eq!(ok, 42);
+
+        let start = Instant::now();
+        let err = run_with_budget(Some(Duration::from_millis(50)), || {
+            std::thread::sleep(Duration::from_millis(500));
+            Ok(())
+        })
+        .await
+        .unwrap_err();
+        assert!(matches!(err, Error::ExtractFailed(ref msg) if msg == "extraction exceeded time budget"));
+        assert!(start.elapsed() < Duration::from_millis(400));
+    }
+}
@@ -1,9 +1,18 @@
 //! Link harvesting and URL fixing from HTML documents.
-
-use scraper::{Html, Selector};
+//!
+//! Links are classified against the page URL as internal (same host),
+//! external, or anchors (a fragment of the page itself), and deduplicated with
+//! fragments stripped, so `/docs#install` and `/docs#usage` are one link.
+//! Anchors keep their fragment, since it is the whole point of the link.
+//! [`mark_in_content`] then flags the links that also appear in the extracted
+//! article, as opposed to only in navigation, sidebars, or footers.
+
+use scraper::{ElementRef, Html, Selector};
 use std::collections::HashSet;
 use url::Url;
 
+pub use thndrs_core::cache::LinkKind;
+
 /// A harvested link with text and href.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Link {
@@ -11,12 +20,19 @@ pub struct Link {
     pub text: String,
     /// Resolved href URL
     pub href: String,
+    /// Internal, external, or anchor, relative to the page
+    #[serde(default)]
+    pub kind: LinkKind,
+    /// Whether the link appears in the extracted content
+    #[serde(default)]
+    pub in_content: bool,
 }
 
 /// Extract links from an HTML document, resolving relative URLs against the base URL.
 ///
 /// This extracts all `<a>` tags with href attributes, resolves relative URLs,
-/// and removes duplicates (by href).
+/// classifies them, and removes duplicates (by href without its fragment, or
+/// the full href for anchors). No link is marked `in_content` yet.
 pub fn extract_links(html: &str, base_url: &Url) -> Vec<Link> {
     let document = Html::parse_document(html);
     let selector = Selector::parse("a[href]").expect("invalid selector");
@@ -25,31 +41,81 @@ pub fn extract_links(html: &str, base_url: &Url) -> Vec<Link> {
     let mut links = Vec::new();
 
     for element in document.select(&selector) {
-        let href = match element.value().attr("href") {
-            Some(h) => h.to_string(),
-            None => continue,
-        };
-
-        let resolved = match base_url.join(&href) {
-            Ok(u) => u.to_string(),
-            Err(_) => continue,
+        let Some((resolved, kind)) = resolve(element, base_url) else {
+            continue;
         };
 
-        if seen.contains(&resolved) {
+        if !seen.insert(dedupe_key(&resolved, kind)) {
             continue;
         }
 
-        seen.insert(resolved.clone());
-
         let text = element.text().collect::<Vec<_>>().join(" ").trim().to_string();
         let text = if text.is_empty() { "[link]".to_string() } else { text };
 
-        links.push(Link { text, href: resolved });
+        links.push(Link { text, href: resolved.to_string(), kind, in_content: false });
     }
 
     links
 }
 
+/// Where `url` points relative to the page at `base_url`.
+///
+/// Hosts are compared without a leading `www.`; URLs without a host (`mailto:`,
+/// `tel:`) are external.
+pub fn link_kind(url: &Url, base_url: &Url) -> LinkKind {
+    if url.fragment().is_some() && without_fragment(url) == without_fragment(base_url) {
+        return LinkKind::Anchor;
+    }
+    let site = |url: &Url| {
+        url.host_str()
+            .map(|host| host.trim_start_matches("www.").to_ascii_lowercase())
+    };
+    match site(url) {
+        Some(host) if Some(&host) == site(base_url).as_ref() => LinkKind::Internal,
+        _ => LinkKind::External,
+    }
+}
+
+/// Mark the links that also appear in `content_html`, the extracted article's HTML.
+pub fn mark_in_content(links: &mut [Link], content_html: &str, base_url: &Url) {
+    let fragment = Html::parse_fragment(content_html);
+    let selector = Selector::parse("a[href]").expect("invalid selector");
+    mark_anchors(links, fragment.select(&selector), base_url);
+}
+
+/// Mark the links whose targets match one of `anchors`.
+pub(crate) fn mark_anchors<'a>(links: &mut [Link], anchors: impl IntoIterator<Item = ElementRef<'a>>, base_url: &Url) {
+    let content: HashSet<String> = anchors
+        .into_iter()
+        .filter_map(|element| resolve(element, base_url))
+        .map(|(resolved, kind)| dedupe_key(&resolved, kind))
+        .collect();
+    for link in links {
+        let key = Url::parse(&link.href).map(|url| dedupe_key(&url, link.kind));
+        link.in_content = key.is_ok_and(|key| content.contains(&key));
+    }
+}
+
+/// An `<a>`'s resolved href and kind.
+fn resolve(element: ElementRef<'_>, base_url: &Url) -> Option<(Url, LinkKind)> {
+    let resolved = base_url.join(element.value().attr("href")?).ok()?;
+    let kind = link_kind(&resolved, base_url);
+    Some((resolved, kind))
+}
+
+fn dedupe_key(url: &Url, kind: LinkKind) -> String {
+    match kind {
+        LinkKind::Anchor => url.to_string(),
+        _ => without_fragment(url),
+    }
+}
+
+fn without_fragment(url: &Url) -> String {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url.into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -231,4 +297,64 @@ mod tests {
         assert_eq!(links.len(), 1);
         assert_eq!(links[0].href, "https://example.com/search?q=test");
     }
+
+    const PAGE: &str = r##"
+        <html>
+            <body>
+                <nav>
+                    <a href="/">Home</a>
+                    <a href="/docs">Docs</a>
+                    <a href="https://twitter.com/example">Twitter</a>
+                </nav>
+                <article>
+                    <p>See <a href="#setup">setup</a>, the <a href="/docs#install">install guide</a>,
+                    and <a href="https://www.rust-lang.org/learn">the book</a>.</p>
+                    <p>Back to <a href="#setup">setup</a>; mail <a href="mailto:hi@example.com">us</a>.</p>
+                </article>
+                <footer>
+                    <a href="https://www.example.com/privacy">Privacy</a>
+                    <a href="/docs#usage">Usage</a>
+                </footer>
+            </body>
+        </html>
+    "##;
+
+    const ARTICLE: &str = r##"<div><p>See <a href="#setup">setup</a>, the <a href="/docs#install">install guide</a>,
+        and <a href="https://www.rust-lang.org/learn">the book</a>.</p></div>"##;
+
+    #[test]
+    fn test_links_classified_and_deduped_without_fragments() {
+        let base = Url::parse("https://example.com/guide").unwrap();
+        let mut links = extract_links(PAGE, &base);
+        mark_in_content(&mut links, ARTICLE, &base);
+
+        let summary: Vec<(&str, LinkKind, bool)> =
+            links.iter().map(|l| (l.href.as_str(), l.kind, l.in_content)).collect();
+        assert_eq!(
+            summary,
+            [
+                ("https://example.com/", LinkKind::Internal, false),
+                ("https://example.com/docs", LinkKind::Internal, true),
+                ("https://twitter.com/example", LinkKind::External, false),
+                ("https://example.com/guide#setup", LinkKind::Anchor, true),
+                ("https://www.rust-lang.org/learn", LinkKind::External, true),
+                ("mailto:hi@example.com", LinkKind::External, false),
+                ("https://www.example.com/privacy", LinkKind::Internal, false),
+            ]
+        );
+        assert_eq!(links[1].text, "Docs");
+    }
+
+    #[test]
+    fn test_link_kind() {
+        let base = Url::parse("https://www.example.com/a?x=1").unwrap();
+        let kind = |href: &str| link_kind(&base.join(href).unwrap(), &base);
+        assert_eq!(kind("#top"), LinkKind::Anchor);
+        assert_eq!(kind("/a?x=1#top"), LinkKind::Anchor);
+        assert_eq!(kind("/a?x=2#top"), LinkKind::Internal);
+        assert_eq!(kind("/a?x=1"), LinkKind::Internal);
+        assert_eq!(kind("https://EXAMPLE.com/b"), LinkKind::Internal);
+        assert_eq!(kind("https://docs.example.com/"), LinkKind::External);
+        assert_eq!(kind("tel:+15551234"), LinkKind::External);
+    }
 }
@@ -0,0 +1,224 @@
+//! Image harvesting from HTML documents.
+//!
+//! Each `<img>` yields its resolved source, alt text, title, and declared
+//! size. A `srcset` contributes its largest candidate; lazy-loading pages that
+//! keep the real source in `data-src`/`data-srcset` are read too. Data URIs,
+//! 1x1 tracking pixels, and images on known beacon hosts are skipped, and
+//! duplicates (by resolved source) are dropped.
+
+use scraper::{Html, Selector};
+use std::collections::HashSet;
+use url::Url;
+
+pub use thndrs_core::cache::Image;
+
+/// Most images kept from one page.
+pub const MAX_IMAGES: usize = 100;
+
+/// Hosts that serve tracking beacons rather than content.
+const BEACON_HOSTS: &[&str] = &[
+    "pixel.wp.com",
+    "stats.wp.com",
+    "google-analytics.com",
+    "googletagmanager.com",
+    "doubleclick.net",
+    "scorecardresearch.com",
+    "quantserve.com",
+    "bat.bing.com",
+    "px.ads.linkedin.com",
+    "analytics.twitter.com",
+    "ct.pinterest.com",
+    "pixel.mathtag.com",
+];
+
+/// Extract images from an HTML document, resolving sources against the base URL.
+pub fn extract_images(html: &str, base_url: &Url) -> Vec<Image> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("img").expect("valid selector");
+
+    let mut seen = HashSet::new();
+    let mut images = Vec::new();
+    for img in document.select(&selector) {
+        let element = img.value();
+        let attr = |name: &str| element.attr(name).map(str::trim).filter(|v| !v.is_empty());
+
+        let srcset = attr("srcset")
+            .or_else(|| attr("data-srcset"))
+            .and_then(largest_candidate);
+        let src = attr("src").filter(|src| !is_data_uri(src));
+        let Some(source) = srcset.or(src).or_else(|| attr("data-src")) else {
+            continue;
+        };
+        if is_data_uri(source) {
+            continue;
+        }
+        let Ok(resolved) = base_url.join(source) else {
+            continue;
+        };
+        if !matches!(resolved.scheme(), "http" | "https") || is_beacon_host(&resolved) {
+            continue;
+        }
+
+        let width = attr("width").and_then(dimension);
+        let height = attr("height").and_then(dimension);
+        if width.is_some_and(|w| w <= 1) && height.is_some_and(|h| h <= 1) {
+            continue;
+        }
+        if !seen.insert(resolved.to_string()) {
+            continue;
+        }
+
+        images.push(Image {
+            src: resolved.to_string(),
+            alt: attr("alt").map(collapse),
+            title: attr("title").map(collapse),
+            width,
+            height,
+        });
+        if images.len() == MAX_IMAGES {
+            break;
+        }
+    }
+    images
+}
+
+/// The URL of the largest `srcset` candidate, by width descriptor or pixel density.
+///
+/// Candidates are a URL (which may itself contain commas) followed by an
+/// optional descriptor such as `640w` or `2x`; a bare URL counts as `1x`.
+fn largest_candidate(srcset: &str) -> Option<&str> {
+    let mut best: Option<(&str, f64)> = None;
+    let mut rest = srcset;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if rest.is_empty() {
+            break;
+        }
+        let url_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let (url, after) = rest.split_at(url_end);
+        let (url, descriptor) = match url.strip_suffix(',') {
+            Some(url) => {
+                rest = after;
+                (url.trim_end_matches(','), "")
+            }
+            None => {
+                let descriptor_end = after.find(',').unwrap_or(after.len());
+                rest = &after[descriptor_end..];
+                (url, after[..descriptor_end].trim())
+            }
+        };
+
+        let size = match descriptor.strip_suffix(['w', 'x']) {
+            Some(value) => value.parse::<f64>().ok(),
+            None if descriptor.is_empty() => Some(1.0),
+            None => None,
+        };
+        let Some(size) = size.filter(|s| s.is_finite() && *s > 0.0) else {
+            continue;
+        };
+        if best.is_none_or(|(_, best_size)| size > best_size) {
+            best = Some((url, size));
+        }
+    }
+    best.map(|(url, _)| url)
+}
+
+/// A `width`/`height` attribute in CSS pixels, e.g. `640` or `640px`.
+fn dimension(value: &str) -> Option<u32> {
+    let value = value.strip_suffix("px").unwrap_or(value).trim();
+    value
+        .parse::<u32>()
+        .ok()
+        .or_else(|| value.parse::<f64>().ok().filter(|v| *v >= 0.0).map(|v| v as u32))
+}
+
+fn is_data_uri(src: &str) -> bool {
+    src.get(..5).is_some_and(|scheme| scheme.eq_ignore_ascii_case("data:"))
+}
+
+fn is_beacon_host(url: &Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    BEACON_HOSTS
+        .iter()
+        .any(|beacon| host == *beacon || host.strip_suffix(beacon).is_some_and(|sub| sub.ends_with('.')))
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> Url {
+        Url::parse("https://example.com/blog/post").unwrap()
+    }
+
+    #[test]
+    fn test_srcset_picks_largest_candidate() {
+        assert_eq!(
+            largest_candidate("small.jpg 320w, large.jpg 1280w, medium.jpg 640w"),
+            Some("large.jpg")
+        );
+        assert_eq!(largest_candidate("a.png, b.png 2x, c.png 1.5x"), Some("b.png"));
+        assert_eq!(
+            largest_candidate("https://cdn.test/w_100,h_100/a.jpg 100w,https://cdn.test/w_900,h_900/a.jpg 900w"),
+            Some("https://cdn.test/w_900,h_900/a.jpg")
+        );
+        assert_eq!(largest_candidate("only.png,"), Some("only.png"));
+        assert_eq!(largest_candidate("bad.png 10q"), None);
+        assert_eq!(largest_candidate(" , "), None);
+    }
+
+    #[test]
+    fn test_extract_images_resolves_and_dedupes() {
+        let html = r#"<html><body>
+            <img src="/img/chart.png" alt=" Revenue
+                by year " width="640" height="480px">
+            <img src="thumb.jpg" srcset="thumb.jpg 200w, full.jpg 1600w" title="Full size">
+            <img src="https://example.com/img/chart.png" alt="Duplicate">
+            <img data-src="lazy.webp" src="data:image/gif;base64,R0lGODlhAQABAAAAACw=">
+            <img src="">
+            </body></html>"#;
+
+        let images = extract_images(html, &base());
+        assert_eq!(
+            images,
+            vec![
+                Image {
+                    src: "https://example.com/img/chart.png".into(),
+                    alt: Some("Revenue by year".into()),
+                    title: None,
+                    width: Some(640),
+                    height: Some(480),
+                },
+                Image {
+                    src: "https://example.com/blog/full.jpg".into(),
+                    title: Some("Full size".into()),
+                    ..Default::default()
+                },
+                Image { src: "https://example.com/blog/lazy.webp".into(), ..Default::default() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_images_skips_data_uris_and_trackers() {
+        let html = r#"<html><body>
+            <img src="data:image/png;base64,iVBORw0KGgo=" alt="inline">
+            <img src="DATA:image/svg+xml,%3Csvg%3E%3C/svg%3E">
+            <img src="/spacer.gif" width="1" height="1">
+            <img src="https://pixel.wp.com/g.gif?blog=1">
+            <img src="https://www.google-analytics.com/collect?v=1">
+            <img src="https://notgoogle-analytics.com/photo.jpg">
+            <img src="javascript:alert(1)">
+            </body></html>"#;
+
+        let images = extract_images(html, &base());
+        let srcs: Vec<&str> = images.iter().map(|i| i.src.as_str()).collect();
+        assert_eq!(srcs, ["https://notgoogle-analytics.com/photo.jpg"]);
+    }
+}
@@ -0,0 +1,183 @@
+//! Page metadata: author, publication date, description, social cards, and canonical URL.
+//!
+//! A pass over the document separate from content extraction, so every
+//! strategy reports the same metadata. Each field takes the first value found
+//! among its sources in order: the document's own metadata (`<meta name>`,
+//! `itemprop`, `<link rel="canonical">`), then a JSON-LD Article for author and
+//! date, then OpenGraph, then Twitter cards. Within one source the first tag in
+//! the document wins.
+
+use scraper::{Html, Selector};
+use url::Url;
+
+use super::structured::{article_data, structured_data};
+
+pub use thndrs_core::cache::PageMetadata;
+
+/// Sources of each field, most authoritative first. Keys are lowercase and
+/// match a `<meta>` tag's `name`, `property`, or `itemprop`.
+const AUTHOR: &[&str] = &["author", "dc.creator"];
+const CARD_AUTHOR: &[&str] = &["article:author", "twitter:creator"];
+const PUBLISHED: &[&str] = &["datepublished", "date", "dc.date", "dc.date.issued"];
+const CARD_PUBLISHED: &[&str] = &["article:published_time"];
+const DESCRIPTION: &[&str] = &["description", "og:description", "twitter:description"];
+const SITE_NAME: &[&str] = &["application-name", "og:site_name", "twitter:site"];
+const IMAGE: &[&str] = &[
+    "image",
+    "og:image",
+    "og:image:url",
+    "og:image:secure_url",
+    "twitter:image",
+    "twitter:image:src",
+];
+
+/// Read the metadata an HTML document declares about itself.
+///
+/// URLs (canonical, image) are resolved against `base_url` when given and
+/// kept as written otherwise.
+pub fn page_metadata(html: &str, base_url: Option<&Url>) -> PageMetadata {
+    let document = Html::parse_document(html);
+    let meta_selector = Selector::parse("meta[content]").expect("valid selector");
+    let tags: Vec<(String, String)> = document
+        .select(&meta_selector)
+        .filter_map(|meta| {
+            let element = meta.value();
+            let key = element
+                .attr("name")
+                .or_else(|| element.attr("property"))
+                .or_else(|| element.attr("itemprop"))?;
+            let content = collapse(element.attr("content")?);
+            (!content.is_empty()).then(|| (key.trim().to_ascii_lowercase(), content))
+        })
+        .collect();
+    let first = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| tags.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone()))
+    };
+    let resolve = |href: String| match base_url {
+        Some(base) => base.join(&href).map_or(href, String::from),
+        None => href,
+    };
+
+    let canonical_selector = Selector::parse(r#"link[rel~="canonical"][href]"#).expect("valid selector");
+    let canonical = document
+        .select(&canonical_selector)
+        .filter_map(|link| link.value().attr("href"))
+        .map(str::trim)
+        .find(|href| !href.is_empty())
+        .map(str::to_string);
+    let article = article_data(&structured_data(&document)).unwrap_or_default();
+
+    PageMetadata {
+        author: first(AUTHOR).or(article.author).or_else(|| first(CARD_AUTHOR)),
+        published: first(PUBLISHED)
+            .or(article.date_published)
+            .or_else(|| first(CARD_PUBLISHED)),
+        description: first(DESCRIPTION),
+        site_name: first(SITE_NAME),
+        image: first(IMAGE).map(resolve),
+        twitter_card: first(&["twitter:card"]),
+        canonical_url: canonical.or_else(|| first(&["og:url"])).map(resolve),
+    }
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> Url {
+        Url::parse("https://example.com/blog/post?utm_source=feed").unwrap()
+    }
+
+    #[test]
+    fn test_opengraph_only_page() {
+        let html = r#"<html><head>
+            <meta property="og:description" content="A short
+                summary.">
+            <meta property="og:image" content="/img/cover.png">
+            <meta property="og:site_name" content="Example Blog">
+            <meta property="og:url" content="https://example.com/blog/post">
+            <meta property="article:published_time" content="2025-03-01T09:00:00Z">
+            <meta name="twitter:card" content="summary_large_image">
+            </head><body><p>Hi</p></body></html>"#;
+
+        let metadata = page_metadata(html, Some(&base()));
+        assert_eq!(
+            metadata,
+            PageMetadata {
+                author: None,
+                published: Some("2025-03-01T09:00:00Z".into()),
+                description: Some("A short summary.".into()),
+                site_name: Some("Example Blog".into()),
+                image: Some("https://example.com/img/cover.png".into()),
+                twitter_card: Some("summary_large_image".into()),
+                canonical_url: Some("https://example.com/blog/post".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_meta_only_page() {
+        let html = r#"<html><head>
+            <meta name="Author" content="Ada Lovelace">
+            <meta name="description" content="Notes on the engine.">
+            <meta itemprop="datePublished" content="2025-02-10">
+            <link rel="canonical" href="/blog/post">
+            </head><body></body></html>"#;
+
+        let metadata = page_metadata(html, Some(&base()));
+        assert_eq!(metadata.author.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(metadata.description.as_deref(), Some("Notes on the engine."));
+        assert_eq!(metadata.published.as_deref(), Some("2025-02-10"));
+        assert_eq!(metadata.canonical_url.as_deref(), Some("https://example.com/blog/post"));
+        assert_eq!(metadata.image, None);
+
+        let unresolved = page_metadata(html, None);
+        assert_eq!(unresolved.canonical_url.as_deref(), Some("/blog/post"));
+        assert!(page_metadata("<p>no head</p>", None).is_empty());
+    }
+
+    #[test]
+    fn test_json_ld_article_fills_author_and_date() {
+        let html = r#"<html><head>
+            <meta property="article:author" content="https://example.com/ada">
+            <meta property="article:published_time" content="2025-01-01">
+            <script type="application/ld+json">
+              {"@type": "BlogPosting", "author": {"@type": "Person", "name": "Ada"},
+               "datePublished": "2025-01-02T08:00:00Z"}
+            </script>
+            <script type="application/ld+json">{ not json </script>
+            </head><body></body></html>"#;
+
+        let metadata = page_metadata(html, None);
+        assert_eq!(metadata.author.as_deref(), Some("Ada"));
+        assert_eq!(metadata.published.as_deref(), Some("2025-01-02T08:00:00Z"));
+
+        let with_meta = html.replace("<head>", r#"<head><meta name="author" content="A. Lovelace">"#);
+        assert_eq!(page_metadata(&with_meta, None).author.as_deref(), Some("A. Lovelace"));
+    }
+
+    #[test]
+    fn test_document_metadata_wins_conflicts() {
+        let html = r#"<html><head>
+            <meta property="og:description" content="Social summary">
+            <meta name="twitter:description" content="Tweet summary">
+            <meta name="description" content="Page summary">
+            <meta name="description" content="Second page summary">
+            <meta property="og:url" content="https://example.com/og">
+            <link rel="canonical" href="https://example.com/canonical">
+            <meta name="twitter:creator" content="@ada">
+            <meta name="author" content="">
+            <meta name="author" content="Ada">
+            </head><body></body></html>"#;
+
+        let metadata = page_metadata(html, Some(&base()));
+        assert_eq!(metadata.description.as_deref(), Some("Page summary"));
+        assert_eq!(metadata.canonical_url.as_deref(), Some("https://example.com/canonical"));
+        assert_eq!(metadata.author.as_deref(), Some("Ada"));
+    }
+}
@@ -8,34 +8,187 @@
 //!
 //! ### Stable Abstraction
 //! - Uses the `Extractor` trait for loose coupling between tools and the extraction engine.
+//! - [`ExtractStrategy::PlainText`] swaps in [`PlainTextExtractor`], which keeps the body
+//!   text without scoring, for pages too short for readability.
+//! - [`extract_with_fallback`] runs readability and falls back to plain text when it fails
+//!   or keeps too little; [`ExtractionResult::strategy`] records which one won.
+//!
+//! ### Tables and Code
+//! - [`TablePass`] swaps tables in the extracted content for placeholders before markdown
+//!   conversion: simple ones return as GitHub-flavored tables, spanned ones as [`Table`]s.
+//! - [`CodePass`] does the same for `<pre>` and `<code>`, so code keeps its whitespace and
+//!   blocks come back fenced with the language their highlighting classes name.
+//!
+//! ### Page Metadata
+//! - [`page_metadata`] reads author, dates, description, OpenGraph/Twitter cards, and the
+//!   canonical URL in its own pass, so every strategy reports the same [`PageMetadata`].
+//! - [`extract_structured_data`] collects JSON-LD blocks; an Article among them
+//!   supplies author and date when the page's meta tags do not.
+//! - [`extract_outline`] lists the content's headings with their levels and anchor ids.
+//! - [`extract_images`] harvests `<img>` sources, alt text, and sizes alongside the links,
+//!   skipping data URIs and tracking pixels.
+//!
+//! ### Content Quality
+//! - [`content_quality`] counts the content's words and paragraphs and its share of link
+//!   text, flagging link-heavy content with little prose as likely boilerplate.
+//!
+//! ### Scoping
+//! - [`scope_to_selector`] and [`scope_to_section`] cut a document down to one element or
+//!   one heading's section, noting the scope in the frontmatter.
+//! - [`paginate`] splits a long document into pages between paragraphs, never inside a
+//!   fenced code block.
+//!
+//! ### Site Configs
+//! - [`SiteConfigRegistry`] maps hosts to [`SiteConfig`]s loaded from a directory; a
+//!   config set on [`ExtractConfig::siteconfig`] prunes the page with its selectors and
+//!   overrides the extraction settings, and its id is recorded on the result.
 //!
 //! ### Output Normalization
 //! - Enforces consistent Markdown headers: `title`, `source`, `fetched_at`, `extractor`, `siteconfig`.
 //! - Ensures reproducibility by storing siteconfig IDs and extractor versions.
+//!
+//! ### Versioning
+//! - [`LECTITO_EXTRACTOR_VERSION`] is the lectito-core version resolved in `Cargo.lock`.
+//! - [`PIPELINE_VERSION`] is bumped whenever normalization or link extraction changes output,
+//!   so cached extractions from an older pipeline can be redone.
 
+pub mod code;
+pub mod guard;
 pub mod links;
+pub mod media;
+pub mod metadata;
 pub mod normalize;
-
-pub use links::{Link, extract_links};
-pub use normalize::{ExtractedDoc, normalize_markdown};
+pub mod outline;
+mod placeholder;
+pub mod plain;
+pub mod quality;
+pub mod scope;
+pub mod siteconfig;
+pub mod structured;
+pub mod tables;
+
+pub use code::CodePass;
+pub use guard::{check_dom, run_with_budget};
+pub use links::{Link, LinkKind, extract_links, link_kind, mark_in_content};
+pub use media::{Image, extract_images};
+pub use metadata::{PageMetadata, page_metadata};
+pub use normalize::{
+    ExtractedDoc, estimate_tokens, normalize_markdown, paginate, refresh_fetched_at, truncate_to_tokens,
+};
+pub use outline::{Heading, extract_outline};
+pub use plain::{PLAIN_TEXT_EXTRACTOR_VERSION, PlainTextExtractor, plain_outline, plain_text};
+pub use quality::{ContentQuality, content_quality};
+pub use scope::{check_selector, scope_to_section, scope_to_selector};
+pub use siteconfig::{SiteConfig, SiteConfigRegistry, SiteExtractOverrides};
+pub use structured::{ArticleData, article_data, extract_structured_data};
+pub use tables::{Table, TablePass};
 
 use lectito_core::{Document, ExtractConfig as LectitoConfig};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 use thndrs_core::Error;
 use url::Url;
 
+/// Version string of the Lectito extractor: its crate version plus the short git revision.
+///
+/// Must match the `rev` lectito-core is pinned to in this crate's Cargo.toml.
+pub const LECTITO_EXTRACTOR_VERSION: &str = "lectito-core@1.0.0+5c4acaa";
+
+/// Revision of the normalization and link-extraction pipeline.
+///
+/// Bump this whenever `normalize` or `links` output changes for the same input.
+pub const PIPELINE_VERSION: i64 = 4;
+
+/// How the main content of an HTML page is found.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractStrategy {
+    /// Readability-style candidate scoring (the configured extractor)
+    #[default]
+    Readability,
+    /// All visible body text, see [`PlainTextExtractor`]
+    PlainText,
+}
+
+impl ExtractStrategy {
+    /// Name used in tool parameters.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Readability => "readability",
+            Self::PlainText => "plain_text",
+        }
+    }
+}
+
+impl FromStr for ExtractStrategy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Readability, Self::PlainText]
+            .into_iter()
+            .find(|strategy| strategy.as_str() == s)
+            .ok_or_else(|| {
+                Error::InvalidInput(format!(
+                    "unsupported strategy: {s} (expected one of: readability, plain_text)"
+                ))
+            })
+    }
+}
+
 /// Configuration for content extraction.
 #[derive(Debug, Clone)]
 pub struct ExtractConfig {
+    /// Readability or plain text (default: readability)
+    pub strategy: ExtractStrategy,
+
+    /// Fall back to plain text when readability fails or keeps too little (default: true)
+    pub fallback: bool,
+
+    /// Readability output shorter than this, in characters, falls back (default: 200)
+    pub fallback_min_chars: Option<usize>,
+
     /// Minimum character count for content (default: 200)
     pub char_threshold: Option<usize>,
 
     /// Maximum number of top candidates to consider (default: 5)
     pub max_top_candidates: Option<usize>,
+
+    /// Deepest element nesting accepted before parsing (default: 512)
+    pub max_dom_depth: Option<usize>,
+
+    /// Most elements accepted before parsing (default: 200000)
+    pub max_dom_nodes: Option<usize>,
+
+    /// Wall-clock budget for one extraction (default: 10s)
+    pub time_budget: Option<Duration>,
+
+    /// Most pages read from a PDF before it is refused (default: 500)
+    pub max_pdf_pages: Option<usize>,
+
+    /// Site selectors and overrides applied before extraction, see [`SiteConfig`] (default: none)
+    pub siteconfig: Option<Arc<SiteConfig>>,
+
+    /// Look up `siteconfig` by host when it is unset, see [`SiteConfigRegistry::configure`] (default: true)
+    pub use_siteconfig: bool,
 }
 
 impl Default for ExtractConfig {
     fn default() -> Self {
-        Self { char_threshold: Some(200), max_top_candidates: Some(5) }
+        Self {
+            strategy: ExtractStrategy::Readability,
+            fallback: true,
+            fallback_min_chars: Some(200),
+            char_threshold: Some(200),
+            max_top_candidates: Some(5),
+            max_dom_depth: Some(512),
+            max_dom_nodes: Some(200_000),
+            time_budget: Some(Duration::from_secs(10)),
+            max_pdf_pages: Some(500),
+            siteconfig: None,
+            use_siteconfig: true,
+        }
     }
 }
 
@@ -62,8 +215,22 @@ pub struct ExtractionResult {
     pub markdown: String,
     /// Extracted links
     pub links: Vec<Link>,
+    /// Images in document order, see [`extract_images`]
+    pub images: Vec<Image>,
+    /// Tables too complex for markdown, see [`TablePass`]
+    pub tables: Vec<Table>,
+    /// Headings of the extracted content, see [`extract_outline`]
+    pub outline: Vec<Heading>,
+    /// Word count, paragraphs, and link density of the content, see [`content_quality`]
+    pub quality: ContentQuality,
     /// Extractor version string
     pub extractor_version: String,
+    /// Strategy that produced the content; plain text is the lower-fidelity one
+    pub strategy: ExtractStrategy,
+    /// Author, dates, description, social cards, and canonical URL
+    pub metadata: PageMetadata,
+    /// Id of the [`SiteConfig`] applied before extraction
+    pub siteconfig_id: Option<String>,
 }
 
 /// Stable extractor trait for content extraction.
@@ -72,6 +239,9 @@ pub struct ExtractionResult {
 pub trait Extractor: Send + Sync {
     /// Extract readable content from HTML.
     fn extract(&self, html: &str, base_url: &Url, config: &ExtractConfig) -> Result<ExtractionResult, Error>;
+
+    /// Version string recorded on snapshots produced by this extractor.
+    fn version(&self) -> &str;
 }
 
 /// Lectito-based extractor implementation.
@@ -82,7 +252,7 @@ pub struct LectitoExtractor {
 impl LectitoExtractor {
     /// Create a new Lectito extractor.
     pub fn new() -> Self {
-        Self { version: "lectito-core@0.2.0" }
+        Self { version: LECTITO_EXTRACTOR_VERSION }
     }
 }
 
@@ -94,6 +264,7 @@ impl Default for LectitoExtractor {
 
 impl Extractor for LectitoExtractor {
     fn extract(&self, html: &str, base_url: &Url, config: &ExtractConfig) -> Result<ExtractionResult, Error> {
+        check_dom(html, config.max_dom_depth, config.max_dom_nodes)?;
         let doc = Document::parse(html).map_err(|e| Error::ExtractFailed(format!("failed to parse HTML: {}", e)))?;
 
         let lectito_cfg = config.to_lectito_config();
@@ -103,12 +274,79 @@ impl Extractor for LectitoExtractor {
         let metadata = doc.extract_metadata();
         let title = metadata.title.clone();
 
-        let markdown = lectito_core::convert_to_markdown(&extracted.content, &metadata, &Default::default())
+        let tables = TablePass::new(&extracted.content);
+        let code = CodePass::new(&tables.html);
+        let markdown = lectito_core::convert_to_markdown(&code.html, &metadata, &Default::default())
             .map_err(|e| Error::ExtractFailed(format!("markdown conversion failed: {}", e)))?;
+        let markdown = tables.splice(&code.splice(&markdown));
+
+        let mut links = extract_links(html, base_url);
+        mark_in_content(&mut links, &extracted.content, base_url);
+        let images = extract_images(html, base_url);
+
+        Ok(ExtractionResult {
+            title,
+            markdown,
+            links,
+            images,
+            tables: tables.tables,
+            outline: extract_outline(&extracted.content),
+            quality: content_quality(&extracted.content),
+            extractor_version: self.version.to_string(),
+            strategy: ExtractStrategy::Readability,
+            metadata: page_metadata(html, Some(base_url)),
+            siteconfig_id: None,
+        })
+    }
 
-        let links = extract_links(html, base_url);
+    fn version(&self) -> &str {
+        self.version
+    }
+}
 
-        Ok(ExtractionResult { title, markdown, links, extractor_version: self.version.to_string() })
+/// Extract with `extractor`, or as plain text when `config.strategy` asks for it.
+///
+/// With `config.fallback`, a readability failure or a result shorter than
+/// `fallback_min_chars` is retried as plain text, which is kept only if it
+/// finds more text; otherwise readability's result or error stands.
+///
+/// A `config.siteconfig` prunes `html` first and its overrides replace the
+/// matching settings of `config`; either strategy then sees only what is left.
+pub fn extract_with_fallback(
+    extractor: &dyn Extractor, html: &str, base_url: &Url, config: &ExtractConfig,
+) -> Result<ExtractionResult, Error> {
+    if let Some(site) = &config.siteconfig {
+        check_dom(html, config.max_dom_depth, config.max_dom_nodes)?;
+        let pruned = site.prune(html);
+        let config = ExtractConfig { siteconfig: None, ..site.apply(config) };
+        let mut result = extract_with_fallback(extractor, &pruned, base_url, &config)?;
+        result.siteconfig_id = Some(site.id.clone());
+        return Ok(result);
+    }
+
+    if config.strategy == ExtractStrategy::PlainText {
+        return PlainTextExtractor.extract(html, base_url, config);
+    }
+
+    let readable = extractor.extract(html, base_url, config);
+    if !config.fallback {
+        return readable;
+    }
+    let kept = match &readable {
+        Ok(result) if result.markdown.trim().chars().count() >= config.fallback_min_chars.unwrap_or(0) => {
+            return readable;
+        }
+        Ok(result) => result.markdown.trim().chars().count(),
+        Err(Error::ExtractFailed(_)) => 0,
+        Err(_) => return readable,
+    };
+
+    match PlainTextExtractor.extract(html, base_url, config) {
+        Ok(plain) if plain.markdown.chars().count() > kept => {
+            tracing::debug!("readability kept {} chars of {}, using plain text", kept, base_url);
+            Ok(plain)
+        }
+        _ => readable,
     }
 }
 
@@ -144,14 +382,25 @@ mod tests {
     #[test]
     fn test_extract_config_default() {
         let config = ExtractConfig::default();
+        assert_eq!(config.strategy, ExtractStrategy::Readability);
+        assert!(config.fallback);
+        assert_eq!(config.fallback_min_chars, Some(200));
         assert_eq!(config.char_threshold, Some(200));
         assert_eq!(config.max_top_candidates, Some(5));
+        assert_eq!(config.max_dom_depth, Some(512));
+        assert_eq!(config.max_dom_nodes, Some(200_000));
+        assert_eq!(config.time_budget, Some(Duration::from_secs(10)));
+        assert_eq!(config.max_pdf_pages, Some(500));
+        assert!(config.siteconfig.is_none());
+        assert!(config.use_siteconfig);
     }
 
     #[test]
     fn test_lectito_extractor_new() {
         let extractor = LectitoExtractor::new();
-        assert_eq!(extractor.version, "lectito-core@0.2.0");
+        assert_eq!(extractor.version(), LECTITO_EXTRACTOR_VERSION);
+        assert!(extractor.version().starts_with("lectito-core@"));
+        assert_ne!(extractor.version(), "lectito-core@unknown");
     }
 
     #[test]
@@ -166,7 +415,8 @@ mod tests {
         assert!(!extracted.markdown.is_empty());
         assert_eq!(extracted.links.len(), 1);
         assert_eq!(extracted.links[0].href, "https://example.com/");
-        assert_eq!(extracted.extractor_version, "lectito-core@0.2.0");
+        assert_eq!(extracted.extractor_version, LECTITO_EXTRACTOR_VERSION);
+        assert_eq!(extracted.strategy, ExtractStrategy::Readability);
     }
 
     #[test]
@@ -202,9 +452,110 @@ mod tests {
         assert_eq!(extracted.links[0].href, "https://example.com/about");
     }
 
+    const SHORT_HTML: &str = r#"<html><head><title>Status</title></head><body>
+        <nav><a href="/">Home</a></nav><p>All systems operational.</p></body></html>"#;
+
+    #[test]
+    fn test_fallback_to_plain_text() {
+        let base = Url::parse("https://example.com/status").unwrap();
+        let config = ExtractConfig::default();
+
+        let result = extract_with_fallback(&LectitoExtractor::new(), SHORT_HTML, &base, &config).unwrap();
+        assert_eq!(result.strategy, ExtractStrategy::PlainText);
+        assert_eq!(result.extractor_version, PLAIN_TEXT_EXTRACTOR_VERSION);
+        assert_eq!(result.markdown, "All systems operational.");
+
+        let no_fallback = ExtractConfig { fallback: false, ..Default::default() };
+        if let Ok(result) = extract_with_fallback(&LectitoExtractor::new(), SHORT_HTML, &base, &no_fallback) {
+            assert_eq!(result.strategy, ExtractStrategy::Readability);
+        }
+
+        let any_length = ExtractConfig { fallback_min_chars: Some(0), ..Default::default() };
+        let kept = extract_with_fallback(&LectitoExtractor::new(), SIMPLE_HTML, &base, &any_length).unwrap();
+        assert_eq!(kept.strategy, ExtractStrategy::Readability);
+    }
+
+    #[test]
+    fn test_extract_keeps_tables() {
+        let paragraph = "<p>Each release is supported until the next two ship, and security fixes are \
+                         backported to every supported line. The table below lists the current dates.</p>";
+        let html = format!(
+            "<html><head><title>Releases</title></head><body><article>{}<table><caption>Support</caption>\
+             <thead><tr><th>Version</th><th>Until</th></tr></thead>\
+             <tbody><tr><td>1.0</td><td>2026-01-01</td></tr><tr><td>1.1</td><td>2026-06-01</td></tr></tbody>\
+             </table>{}</article></body></html>",
+            paragraph.repeat(3),
+            paragraph.repeat(3)
+        );
+        let base = Url::parse("https://example.com/releases").unwrap();
+
+        let extracted = extract_readable(&html, &base).unwrap();
+        assert!(
+            extracted
+                .markdown
+                .contains("| Version | Until |\n| --- | --- |\n| 1.0 | 2026-01-01 |\n| 1.1 | 2026-06-01 |"),
+            "{}",
+            extracted.markdown
+        );
+        assert!(!extracted.markdown.contains("thndrstable"));
+        assert!(extracted.tables.is_empty());
+    }
+
+    #[test]
+    fn test_extract_keeps_code_blocks() {
+        let paragraph = "<p>The parser reads one token at a time and keeps the indentation of every line, \
+                         so the example below should survive extraction exactly as written.</p>";
+        let html = format!(
+            "<html><head><title>Parsing</title></head><body><article>{}\
+             <pre class=\"rust\"><code>fn parse(input: &amp;str) {{\n    for token in input.split(' ') {{\n        \
+             println!(\"{{token}}\");\n    }}\n}}</code></pre>\
+             <p>Call <code>parse_all</code> for files.</p>{}</article></body></html>",
+            paragraph.repeat(3),
+            paragraph.repeat(3)
+        );
+        let base = Url::parse("https://example.com/parsing").unwrap();
+
+        let extracted = extract_readable(&html, &base).unwrap();
+        assert!(
+            extracted.markdown.contains(
+                "```rust\nfn parse(input: &str) {\n    for token in input.split(' ') {\n        \
+                 println!(\"{token}\");\n    }\n}\n```"
+            ),
+            "{}",
+            extracted.markdown
+        );
+        assert!(
+            extracted.markdown.contains("Call `parse_all` for files."),
+            "{}",
+            extracted.markdown
+        );
+        assert!(!extracted.markdown.contains("thndrscode"));
+    }
+
+    #[test]
+    fn test_extract_strategy_from_str() {
+        assert_eq!(
+            "plain_text".parse::<ExtractStrategy>().unwrap(),
+            ExtractStrategy::PlainText
+        );
+        assert_eq!(
+            "readability".parse::<ExtractStrategy>().unwrap(),
+            ExtractStrategy::Readability
+        );
+        match "markdown".parse::<ExtractStrategy>() {
+            Err(Error::InvalidInput(msg)) => {
+                assert_eq!(
+                    msg,
+                    "unsupported strategy: markdown (expected one of: readability, plain_text)"
+                )
+            }
+            other => panic!("expected InvalidInput, got {other:?}"),
+        }
+    }
+
     #[test]
     fn test_extract_custom_config() {
-        let config = ExtractConfig { char_threshold: Some(100), max_top_candidates: Some(3) };
+        let config = ExtractConfig { char_threshold: Some(100), max_top_candidates: Some(3), ..Default::default() };
         let lectito_cfg = config.to_lectito_config();
         assert_eq!(lectito_cfg.char_threshold, 100);
         assert_eq!(lectito_cfg.max_top_candidates, 3);
@@ -223,4 +574,20 @@ mod tests {
         let result = extract_readable("not really html", &base);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_extract_rejects_pathological_nesting() {
+        let base = Url::parse("https://example.com").unwrap();
+        let html = format!(
+            "<html><head><title>Deep</title></head><body>{}<p>bottom</p></body></html>",
+            "<div><span>".repeat(50_000)
+        );
+        let start = std::time::Instant::now();
+
+        let err = extract_readable(&html, &base).unwrap_err();
+        assert!(matches!(err, Error::ExtractFailed(ref msg) if msg.contains("nesting exceeds 512")));
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        assert!(extract_readable(SIMPLE_HTML, &base).is_ok());
+    }
 }
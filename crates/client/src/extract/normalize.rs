@@ -45,6 +45,249 @@ pub fn normalize_markdown(
     )
 }
 
+/// Rewrite the `fetched_at` field of an already-normalized document.
+///
+/// Used when a refetch returns identical content and the previous extraction
+/// is reused; only the frontmatter timestamp changes. Documents without a
+/// frontmatter header are returned unchanged.
+pub fn refresh_fetched_at(markdown: &str, fetched_at: &DateTime<Utc>) -> String {
+    let Some(rest) = markdown.strip_prefix("---\n") else {
+        return markdown.to_string();
+    };
+    let Some(end) = rest.find("\n---\n") else {
+        return markdown.to_string();
+    };
+
+    let timestamp = fetched_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let header: Vec<String> = rest[..end]
+        .lines()
+        .map(|line| match line.strip_prefix("fetched_at: ") {
+            Some(_) => format!("fetched_at: {timestamp}"),
+            None => line.to_string(),
+        })
+        .collect();
+
+    format!("---\n{}{}", header.join("\n"), &rest[end..])
+}
+
+/// Estimate how many tokens `text` occupies for a cl100k-style tokenizer.
+///
+/// A cheap heuristic rather than a real tokenizer: runs of ASCII letters cost
+/// one token per five characters, digit runs one per three digits, each run of
+/// newlines or of repeated spaces one token, and every other character
+/// (punctuation, non-ASCII) one token. Single spaces fold into the next word.
+pub fn estimate_tokens(text: &str) -> usize {
+    let mut tokens = 0;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c.is_ascii_alphabetic() {
+            let mut len: usize = 1;
+            while chars.next_if(char::is_ascii_alphabetic).is_some() {
+                len += 1;
+            }
+            tokens += len.div_ceil(5);
+        } else if c.is_ascii_digit() {
+            let mut len: usize = 1;
+            while chars.next_if(char::is_ascii_digit).is_some() {
+                len += 1;
+            }
+            tokens += len.div_ceil(3);
+        } else if c == '\n' {
+            while chars.next_if_eq(&'\n').is_some() {}
+            tokens += 1;
+        } else if c.is_whitespace() {
+            if chars.next_if(|c| c.is_whitespace() && *c != '\n').is_some() {
+                while chars.next_if(|c| c.is_whitespace() && *c != '\n').is_some() {}
+                tokens += 1;
+            }
+        } else {
+            tokens += 1;
+        }
+    }
+
+    tokens
+}
+
+/// Trim `markdown` at paragraph boundaries until it fits in `max_tokens`.
+///
+/// Whole paragraphs are kept from the start of the document, so the
+/// frontmatter header survives whenever the budget allows it. If even the first
+/// paragraph is over budget it is cut at a word boundary instead. Returns the
+/// (possibly shortened) text and whether anything was removed.
+pub fn truncate_to_tokens(markdown: &str, max_tokens: usize) -> (String, bool) {
+    if estimate_tokens(markdown) <= max_tokens {
+        return (markdown.to_string(), false);
+    }
+
+    let mut kept = String::new();
+    let mut used = 0;
+    for paragraph in markdown.split("\n\n") {
+        let separator = usize::from(!kept.is_empty());
+        let cost = estimate_tokens(paragraph) + separator;
+        if used + cost > max_tokens {
+            if kept.is_empty() {
+                kept = truncate_words(paragraph, max_tokens);
+            }
+            break;
+        }
+
+        if separator > 0 {
+            kept.push_str("\n\n");
+        }
+        kept.push_str(paragraph);
+        used += cost;
+    }
+
+    (kept, true)
+}
+
+/// Keep leading words of `text` while they fit in `max_tokens`.
+fn truncate_words(text: &str, max_tokens: usize) -> String {
+    let mut kept = String::new();
+    let mut used = 0;
+    for word in text.split_inclusive(char::is_whitespace) {
+        used += estimate_tokens(word);
+        if used > max_tokens {
+            break;
+        }
+        kept.push_str(word);
+    }
+    kept.trim_end().to_string()
+}
+
+/// Split `markdown` into pages of at most `max_chars` characters.
+///
+/// Pages break between paragraphs where they can; a paragraph longer than a
+/// page is broken between lines, then between words. The frontmatter header
+/// and fenced code blocks are never broken, so a page holding one longer than
+/// `max_chars` is longer too, as is one holding a single overlong word.
+/// Concatenating the pages gives back `markdown`; there is always at least one.
+pub fn paginate(markdown: &str, max_chars: usize) -> Vec<&str> {
+    let mut pages = Pages { max_chars: max_chars.max(1), breaks: Vec::new(), len: 0 };
+    for (range, whole) in blocks(markdown) {
+        let block = &markdown[range.clone()];
+        let chars = block.chars().count();
+        if whole || chars <= pages.max_chars {
+            pages.push(range.start, chars);
+            continue;
+        }
+
+        for (line_start, line) in pieces(block, |c| c == '\n') {
+            let line_chars = line.chars().count();
+            if line_chars <= pages.max_chars {
+                pages.push(range.start + line_start, line_chars);
+                continue;
+            }
+            for (word_start, word) in pieces(line, char::is_whitespace) {
+                pages.push(range.start + line_start + word_start, word.chars().count());
+            }
+        }
+    }
+
+    let mut bounds = vec![0];
+    bounds.extend(pages.breaks);
+    bounds.push(markdown.len());
+    bounds.windows(2).map(|pair| &markdown[pair[0]..pair[1]]).collect()
+}
+
+/// Page breaks placed so far, and the characters on the page being filled.
+struct Pages {
+    max_chars: usize,
+    breaks: Vec<usize>,
+    len: usize,
+}
+
+impl Pages {
+    /// Add `chars` characters starting at byte `offset`, breaking the page
+    /// before them when they would overflow it.
+    fn push(&mut self, offset: usize, chars: usize) {
+        if self.len > 0 && self.len + chars > self.max_chars {
+            self.breaks.push(offset);
+            self.len = 0;
+        }
+        self.len += chars;
+    }
+}
+
+/// Byte ranges of the paragraphs of `markdown`, each with the blank lines that
+/// follow it, and whether it must stay whole: the frontmatter header, and any
+/// paragraph holding (part of) a fenced code block.
+fn blocks(markdown: &str) -> Vec<(std::ops::Range<usize>, bool)> {
+    let mut blocks = Vec::new();
+    let mut block_start = 0;
+    if let Some(end) = markdown.strip_prefix("---\n").and_then(|rest| rest.find("\n---\n")) {
+        block_start = "---\n".len() + end + "\n---\n".len();
+        blocks.push((0..block_start, true));
+    }
+
+    let mut fences = Fences::default();
+    let mut fenced = false;
+    let mut after_blank = false;
+    let mut offset = block_start;
+    for line in markdown[block_start..].split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let blank = line.trim().is_empty();
+        if !fences.is_open() && after_blank && !blank {
+            blocks.push((block_start..start, fenced));
+            block_start = start;
+            fenced = false;
+        }
+
+        fenced |= fences.line(line);
+        after_blank = !fences.is_open() && blank;
+    }
+    if block_start < markdown.len() {
+        blocks.push((block_start..markdown.len(), fenced));
+    }
+    blocks
+}
+
+/// `text` split after each character matching `split`, with each piece's byte offset.
+fn pieces(text: &str, split: fn(char) -> bool) -> impl Iterator<Item = (usize, &str)> {
+    text.split_inclusive(split).scan(0, |offset, piece| {
+        let start = *offset;
+        *offset += piece.len();
+        Some((start, piece))
+    })
+}
+
+/// Tracks whether successive lines of markdown are inside a fenced code block.
+#[derive(Debug, Default)]
+pub(crate) struct Fences {
+    open: Option<(char, usize)>,
+}
+
+impl Fences {
+    /// Take the next line; returns whether it is a fence line, whether or not
+    /// it opens or closes a block.
+    pub(crate) fn line(&mut self, line: &str) -> bool {
+        let line = line.trim();
+        let Some((marker, len)) = fence_marker(line) else {
+            return false;
+        };
+        match self.open {
+            None => self.open = Some((marker, len)),
+            Some((open, open_len)) if marker == open && len >= open_len && line.len() == len => self.open = None,
+            Some(_) => {}
+        }
+        true
+    }
+
+    /// Whether the lines taken so far leave a fence open.
+    pub(crate) fn is_open(&self) -> bool {
+        self.open.is_some()
+    }
+}
+
+/// The character and length of a code fence opening `line`.
+fn fence_marker(line: &str) -> Option<(char, usize)> {
+    let marker = line.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = line.chars().take_while(|c| *c == marker).count();
+    (len >= 3).then_some((marker, len))
+}
+
 /// Escape special YAML characters in a string.
 fn escape_yaml(s: &str) -> String {
     if s.contains('\n') || s.contains(':') && s.len() > 1 {
@@ -156,4 +399,110 @@ mod tests {
         let escaped = escape_yaml("a:b");
         assert!(escaped.contains("a:b"));
     }
+
+    #[test]
+    fn test_refresh_fetched_at() {
+        let doc = ExtractedDoc {
+            title: Some("Test".to_string()),
+            markdown: "fetched_at: in body".to_string(),
+            extractor_version: "lectito-core@0.1.0".to_string(),
+        };
+        let url = Url::parse("https://example.com").unwrap();
+        let first = DateTime::parse_from_rfc3339("2025-01-20T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let second = DateTime::parse_from_rfc3339("2025-02-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let refreshed = refresh_fetched_at(&normalize_markdown(&doc, &url, &first, None), &second);
+        assert_eq!(refreshed, normalize_markdown(&doc, &url, &second, None));
+        assert!(refreshed.ends_with("fetched_at: in body"));
+    }
+
+    #[test]
+    fn test_refresh_fetched_at_without_frontmatter() {
+        let refreshed = refresh_fetched_at("# Plain", &Utc::now());
+        assert_eq!(refreshed, "# Plain");
+    }
+
+    #[test]
+    fn test_estimate_tokens_pinned() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("Hello, world!"), 4);
+        assert_eq!(estimate_tokens("The quick brown fox jumps over the lazy dog."), 10);
+        assert_eq!(estimate_tokens("internationalization"), 4);
+        assert_eq!(estimate_tokens("12345678"), 3);
+        assert_eq!(estimate_tokens("line one\n\nline two"), 5);
+        assert_eq!(estimate_tokens("    indented"), 3);
+        assert_eq!(estimate_tokens("日本語"), 3);
+        assert_eq!(estimate_tokens("# Heading\n\n- [link](https://example.com)"), 18);
+    }
+
+    #[test]
+    fn test_truncate_to_tokens_within_budget() {
+        let (text, truncated) = truncate_to_tokens("one two\n\nthree", 10);
+        assert_eq!(text, "one two\n\nthree");
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn test_truncate_to_tokens_paragraph_boundary() {
+        let markdown = "---\ntitle: Test\n---\n# Heading\n\nFirst paragraph here.\n\nSecond paragraph here.";
+        let (text, truncated) = truncate_to_tokens(markdown, 22);
+        assert_eq!(text, "---\ntitle: Test\n---\n# Heading\n\nFirst paragraph here.");
+        assert!(truncated);
+        assert!(estimate_tokens(&text) <= 22);
+    }
+
+    #[test]
+    fn test_truncate_to_tokens_cuts_oversized_first_paragraph() {
+        let (text, truncated) = truncate_to_tokens("alpha beta gamma delta epsilon", 3);
+        assert_eq!(text, "alpha beta gamma");
+        assert!(truncated);
+
+        let (text, truncated) = truncate_to_tokens("alpha beta", 0);
+        assert_eq!(text, "");
+        assert!(truncated);
+    }
+
+    #[test]
+    fn test_paginate_keeps_header_and_code_whole() {
+        let markdown = concat!(
+            "---\ntitle: Test\n---\n# Heading\n\nFirst paragraph.\n\n",
+            "```rust\nfn main() {\n\n    run();\n}\n```\n\nLast one.\n"
+        );
+        assert_eq!(
+            paginate(markdown, 20),
+            [
+                "---\ntitle: Test\n---\n",
+                "# Heading\n\n",
+                "First paragraph.\n\n",
+                "```rust\nfn main() {\n\n    run();\n}\n```\n\n",
+                "Last one.\n",
+            ]
+        );
+        assert_eq!(
+            paginate(markdown, 40),
+            [
+                "---\ntitle: Test\n---\n# Heading\n\n",
+                "First paragraph.\n\n",
+                "```rust\nfn main() {\n\n    run();\n}\n```\n\n",
+                "Last one.\n",
+            ]
+        );
+        assert_eq!(paginate(markdown, 1000), [markdown]);
+
+        // An unclosed fence runs to the end of the document.
+        assert_eq!(paginate("a\n\n```\nb\n\nc", 3), ["a\n\n", "```\nb\n\nc"]);
+    }
+
+    #[test]
+    fn test_paginate_breaks_long_paragraphs() {
+        assert_eq!(
+            paginate("alpha beta gamma\ndelta epsilon", 12),
+            ["alpha beta ", "gamma\ndelta ", "epsilon"]
+        );
+        assert_eq!(paginate("", 10), [""]);
+    }
 }
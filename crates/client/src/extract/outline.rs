@@ -0,0 +1,140 @@
+//! Heading outline of the extracted content.
+//!
+//! Walks the h1–h6 headings in document order, keeping each one's level, its
+//! text with inline markup flattened, and the fragment identifier that links to
+//! it: the heading's own `id`, an `id`/`name` on an anchor inside it or just
+//! before it, or the `#fragment` of a permalink inside it. Links whose text is
+//! only a permalink glyph (`¶`, `§`, `#`) are left out of the text.
+
+use scraper::{ElementRef, Html, Selector};
+
+pub use thndrs_core::cache::Heading;
+
+/// Most headings kept from one document.
+pub const MAX_HEADINGS: usize = 500;
+
+/// Selector for the elements an outline is made of.
+pub(crate) const HEADING_SELECTOR: &str = "h1, h2, h3, h4, h5, h6";
+
+/// Text of the permalinks put beside a heading.
+const PERMALINK_GLYPHS: &[char] = &['¶', '§', '#', '🔗'];
+
+/// Outline of an HTML fragment, typically the extracted content.
+pub fn extract_outline(html: &str) -> Vec<Heading> {
+    let fragment = Html::parse_fragment(html);
+    let selector = Selector::parse(HEADING_SELECTOR).expect("valid selector");
+    fragment
+        .select(&selector)
+        .filter_map(heading)
+        .take(MAX_HEADINGS)
+        .collect()
+}
+
+/// The outline entry for an h1–h6 element, or `None` when it has no text.
+pub(crate) fn heading(element: ElementRef<'_>) -> Option<Heading> {
+    let level = element.value().name().strip_prefix('h')?.parse().ok()?;
+    let text = element
+        .descendants()
+        .filter_map(|node| {
+            let text = node.value().as_text()?;
+            let in_link = node
+                .parent()
+                .and_then(ElementRef::wrap)
+                .is_some_and(|parent| parent.value().name() == "a");
+            (!(in_link && is_permalink_glyph(text))).then_some(&**text)
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.is_empty() {
+        return None;
+    }
+    Some(Heading { level, text, id: heading_id(element) })
+}
+
+fn is_permalink_glyph(text: &str) -> bool {
+    let text = text.trim();
+    !text.is_empty() && text.chars().all(|c| PERMALINK_GLYPHS.contains(&c))
+}
+
+fn heading_id(element: ElementRef<'_>) -> Option<String> {
+    let anchor_id = |element: ElementRef<'_>| {
+        let value = element.value();
+        value
+            .attr("id")
+            .or_else(|| value.attr("name").filter(|_| value.name() == "a"))
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+    };
+
+    let descendants = || element.descendants().filter_map(ElementRef::wrap);
+    if let Some(id) = descendants().find_map(anchor_id) {
+        return Some(id);
+    }
+    let permalink = descendants().find_map(|descendant| {
+        let href = descendant.value().attr("href")?.trim().strip_prefix('#')?;
+        (descendant.value().name() == "a" && !href.is_empty()).then(|| href.to_string())
+    });
+    if permalink.is_some() {
+        return permalink;
+    }
+
+    // `<a name="x"></a><h2>…</h2>`, the pre-`id` way of marking a heading.
+    element
+        .prev_siblings()
+        .find_map(ElementRef::wrap)
+        .filter(|previous| previous.value().name() == "a" && previous.text().all(|t| t.trim().is_empty()))
+        .and_then(anchor_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outline(html: &str) -> Vec<(u8, String, Option<String>)> {
+        extract_outline(html)
+            .into_iter()
+            .map(|heading| (heading.level, heading.text, heading.id))
+            .collect()
+    }
+
+    #[test]
+    fn test_levels_in_document_order() {
+        let html = r#"<h1>Guide</h1><p>Intro</p>
+            <h2 id="install">Install</h2><h3>From source</h3>
+            <h2>Usage</h2><h6>Footnote</h6><h2>   </h2>"#;
+        assert_eq!(
+            outline(html),
+            [
+                (1, "Guide".into(), None),
+                (2, "Install".into(), Some("install".into())),
+                (3, "From source".into(), None),
+                (2, "Usage".into(), None),
+                (6, "Footnote".into(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inline_markup_and_permalinks() {
+        let html = concat!(
+            r#"<h2 id="the-parse-fn">The <code>parse</code>   function, <em>fast</em></h2>"#,
+            r##"<h2><a class="doc-anchor" href="#examples">§</a>Examples</h2>"##,
+            r##"<h3>Writing <b>C</b>#<a class="headerlink" href="#writing-c" title="Link">¶</a></h3>"##,
+            r##"<h2><a id="user-content-faq" class="anchor" href="#faq"><svg></svg></a>FAQ</h2>"##,
+            r#"<a name="legacy"></a><h4>Legacy</h4>"#,
+        );
+        assert_eq!(
+            outline(html),
+            [
+                (2, "The parse function, fast".into(), Some("the-parse-fn".into())),
+                (2, "Examples".into(), Some("examples".into())),
+                (3, "Writing C#".into(), Some("writing-c".into())),
+                (2, "FAQ".into(), Some("user-content-faq".into())),
+                (4, "Legacy".into(), Some("legacy".into())),
+            ]
+        );
+    }
+}
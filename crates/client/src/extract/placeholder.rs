@@ -0,0 +1,83 @@
+//! Placeholders that carry pre-rendered markdown through the converter.
+//!
+//! Elements the markdown converter handles badly (tables, code) are swapped for
+//! a short alphanumeric token before conversion, which the converter passes
+//! through as text, and the token is replaced by our own markdown afterwards.
+
+use scraper::{Node, node::Text};
+
+/// Markdown waiting to replace the tokens in converted output.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Placeholders {
+    /// Token prefix; alphanumeric, so the converter never escapes it
+    prefix: &'static str,
+    /// Markdown for each token, by index, and whether it is a block of its own
+    replacements: Vec<(String, bool)>,
+}
+
+impl Placeholders {
+    pub(crate) fn new(prefix: &'static str) -> Self {
+        Self { prefix, replacements: Vec::new() }
+    }
+
+    /// A text node holding a new token that stands for `markdown`, to put in
+    /// place of the element it renders.
+    ///
+    /// A block is set off by blank lines when spliced; inline markdown replaces
+    /// the token in place.
+    pub(crate) fn push(&mut self, markdown: String, block: bool) -> Node {
+        let token = format!("{}{}x", self.prefix, self.replacements.len());
+        self.replacements.push((markdown, block));
+        Node::Text(Text { text: token.as_str().into() })
+    }
+
+    /// Put the markdown back in place of its tokens.
+    pub(crate) fn splice(&self, markdown: &str) -> String {
+        if self.replacements.is_empty() {
+            return markdown.to_string();
+        }
+
+        let mut out = String::with_capacity(markdown.len());
+        let mut rest = markdown;
+        while let Some((start, index, end)) = self.find(rest) {
+            let Some((replacement, block)) = self.replacements.get(index) else {
+                break;
+            };
+            if *block {
+                out.push_str(&rest[..start]);
+                out.truncate(out.trim_end().len());
+                if !out.is_empty() {
+                    out.push_str("\n\n");
+                }
+                out.push_str(replacement);
+                rest = rest[end..].trim_start();
+                if !rest.is_empty() {
+                    out.push_str("\n\n");
+                }
+            } else {
+                out.push_str(&rest[..start]);
+                out.push_str(replacement);
+                rest = &rest[end..];
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// The next token in `text`: its start, index, and end.
+    fn find(&self, text: &str) -> Option<(usize, usize, usize)> {
+        let mut from = 0;
+        while let Some(offset) = text[from..].find(self.prefix) {
+            let start = from + offset;
+            let digits_start = start + self.prefix.len();
+            let digits = text[digits_start..].bytes().take_while(u8::is_ascii_digit).count();
+            let digits_end = digits_start + digits;
+            if digits > 0 && text[digits_end..].starts_with('x') {
+                let index = text[digits_start..digits_end].parse().ok()?;
+                return Some((start, index, digits_end + 1));
+            }
+            from = digits_start;
+        }
+        None
+    }
+}
@@ -0,0 +1,249 @@
+//! Plain-text extraction: the page's visible text without candidate scoring.
+//!
+//! Readability rejects pages with too little content to score, such as short
+//! notices, status pages, and link lists. This strategy keeps everything in the
+//! body except scripts, styles, navigation, and footers, putting a blank line
+//! between block elements and collapsing whitespace within them.
+//! Links outside the skipped elements count as part of the content.
+
+use scraper::{ElementRef, Html, Selector};
+use url::Url;
+
+use super::links::mark_anchors;
+use super::outline::{HEADING_SELECTOR, Heading, MAX_HEADINGS, heading};
+use super::{
+    ExtractConfig, ExtractStrategy, ExtractionResult, Extractor, check_dom, content_quality, extract_images,
+    extract_links, page_metadata,
+};
+use thndrs_core::Error;
+
+/// Version string of the plain-text extractor, e.g. `plain-text@0.1.0`.
+pub const PLAIN_TEXT_EXTRACTOR_VERSION: &str = concat!("plain-text@", env!("CARGO_PKG_VERSION"));
+
+/// Elements whose text is never kept.
+pub(crate) const SKIPPED_ELEMENTS: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "svg", "iframe", "nav", "footer",
+];
+
+/// Elements that start a new paragraph.
+pub(crate) const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+/// Extractor that returns the body text of a page.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlainTextExtractor;
+
+impl Extractor for PlainTextExtractor {
+    fn extract(&self, html: &str, base_url: &Url, config: &ExtractConfig) -> Result<ExtractionResult, Error> {
+        check_dom(html, config.max_dom_depth, config.max_dom_nodes)?;
+        let (title, text) = plain_text(html);
+        if text.is_empty() {
+            return Err(Error::ExtractFailed("page has no visible text".into()));
+        }
+
+        let mut links = extract_links(html, base_url);
+        let document = Html::parse_document(html);
+        let anchors = Selector::parse("body a[href]").expect("valid selector");
+        mark_anchors(
+            &mut links,
+            document.select(&anchors).filter(|a| !is_skipped(a)),
+            base_url,
+        );
+
+        Ok(ExtractionResult {
+            title,
+            markdown: text,
+            links,
+            images: extract_images(html, base_url),
+            tables: Vec::new(),
+            outline: plain_outline(html),
+            quality: content_quality(html),
+            extractor_version: PLAIN_TEXT_EXTRACTOR_VERSION.to_string(),
+            strategy: ExtractStrategy::PlainText,
+            metadata: page_metadata(html, Some(base_url)),
+            siteconfig_id: None,
+        })
+    }
+
+    fn version(&self) -> &str {
+        PLAIN_TEXT_EXTRACTOR_VERSION
+    }
+}
+
+/// The title and visible text of an HTML document, paragraphs separated by blank lines.
+///
+/// The title is the `<title>`, else the first `<h1>`.
+pub fn plain_text(html: &str) -> (Option<String>, String) {
+    let document = Html::parse_document(html);
+    let body_selector = Selector::parse("body").expect("valid selector");
+    let root = document
+        .select(&body_selector)
+        .next()
+        .unwrap_or_else(|| document.root_element());
+
+    let mut blocks = Blocks::default();
+    collect_text(root, &mut blocks);
+
+    (title(&document), blocks.finish())
+}
+
+/// Headings of an HTML document outside the elements whose text is never kept.
+pub fn plain_outline(html: &str) -> Vec<Heading> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse(HEADING_SELECTOR).expect("valid selector");
+    document
+        .select(&selector)
+        .filter(|h| !is_skipped(h))
+        .filter_map(heading)
+        .take(MAX_HEADINGS)
+        .collect()
+}
+
+fn title(document: &Html) -> Option<String> {
+    ["title", "h1"].into_iter().find_map(|name| {
+        let selector = Selector::parse(name).expect("valid selector");
+        let text = document.select(&selector).next()?.text().collect::<String>();
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        Some(text).filter(|t| !t.is_empty())
+    })
+}
+
+/// Whether an element sits inside one whose text is never kept.
+fn is_skipped(element: &ElementRef<'_>) -> bool {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .any(|ancestor| SKIPPED_ELEMENTS.contains(&ancestor.value().name()))
+}
+
+fn collect_text(element: ElementRef<'_>, blocks: &mut Blocks) {
+    for child in element.children() {
+        if let Some(child) = ElementRef::wrap(child) {
+            let name = child.value().name();
+            if SKIPPED_ELEMENTS.contains(&name) {
+                continue;
+            }
+            let block = BLOCK_ELEMENTS.contains(&name);
+            if block {
+                blocks.end_block();
+            }
+            collect_text(child, blocks);
+            if block {
+                blocks.end_block();
+            }
+        } else if let Some(text) = child.value().as_text() {
+            blocks.current.push_str(text);
+        }
+    }
+}
+
+/// Paragraphs collected so far and the text of the one in progress.
+#[derive(Default)]
+struct Blocks {
+    done: Vec<String>,
+    current: String,
+}
+
+impl Blocks {
+    fn end_block(&mut self) {
+        let text = self.current.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !text.is_empty() {
+            self.done.push(text);
+        }
+        self.current.clear();
+    }
+
+    fn finish(mut self) -> String {
+        self.end_block();
+        self.done.join("\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTICE: &str = r#"<html><head><title>Maintenance</title><style>p { color: red }</style></head>
+        <body>
+          <nav><a href="/">Home</a> <a href="/docs">Docs</a></nav>
+          <h1>Down for maintenance</h1>
+          <p>We'll be   back
+             at <b>10:00 UTC</b>.</p>
+          <script>track();</script>
+          <ul><li>Status: <a href="/status">status page</a></li><li>Support</li></ul>
+          <footer>© Example</footer>
+        </body></html>"#;
+
+    #[test]
+    fn test_plain_text_keeps_body_text() {
+        let (title, text) = plain_text(NOTICE);
+        assert_eq!(title.as_deref(), Some("Maintenance"));
+        assert_eq!(
+            text,
+            "Down for maintenance\n\nWe'll be back at 10:00 UTC.\n\nStatus: status page\n\nSupport"
+        );
+    }
+
+    #[test]
+    fn test_plain_text_extractor() {
+        let base = Url::parse("https://example.com/notice").unwrap();
+        let result = PlainTextExtractor
+            .extract(NOTICE, &base, &ExtractConfig::default())
+            .unwrap();
+        assert_eq!(result.extractor_version, PLAIN_TEXT_EXTRACTOR_VERSION);
+        let in_content: Vec<&str> = result
+            .links
+            .iter()
+            .filter(|l| l.in_content)
+            .map(|l| l.href.as_str())
+            .collect();
+        assert_eq!(in_content, ["https://example.com/status"]);
+        assert_eq!(result.links.len(), 3);
+        assert_eq!(result.outline.len(), 1);
+        assert_eq!(result.outline[0].text, "Down for maintenance");
+
+        let empty = PlainTextExtractor.extract(
+            "<html><body><script>x()</script></body></html>",
+            &base,
+            &Default::default(),
+        );
+        assert!(matches!(empty, Err(Error::ExtractFailed(_))));
+    }
+
+    #[test]
+    fn test_title_falls_back_to_h1() {
+        let (title, _) = plain_text("<body><h1> Hello\n world </h1></body>");
+        assert_eq!(title.as_deref(), Some("Hello world"));
+    }
+}
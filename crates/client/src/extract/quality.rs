@@ -0,0 +1,150 @@
+//! Content-quality signals of the extracted content.
+//!
+//! Counts words and paragraphs and measures how much of the text is link text,
+//! then flags content that is mostly links with little prose as likely
+//! boilerplate: what comes back when extraction settles on a menu, a link list,
+//! or a banner instead of an article. Text inside scripts, styles, navigation,
+//! and footers is not counted, as with the plain-text strategy.
+
+use scraper::{ElementRef, Html};
+
+use super::plain::{BLOCK_ELEMENTS, SKIPPED_ELEMENTS};
+
+pub use thndrs_core::cache::ContentQuality;
+
+/// Average adult silent-reading pace, in words per minute.
+pub const WORDS_PER_MINUTE: usize = 238;
+
+/// Fewest words in a block of text for it to count as a paragraph.
+const MIN_PARAGRAPH_WORDS: usize = 10;
+
+/// Link density from which content with few paragraphs is likely boilerplate.
+const BOILERPLATE_LINK_DENSITY: f64 = 0.5;
+
+/// Paragraphs that make content more than boilerplate however many links it has.
+const MIN_CONTENT_PARAGRAPHS: usize = 3;
+
+/// Quality signals of an HTML document or fragment, typically the extracted content.
+pub fn content_quality(html: &str) -> ContentQuality {
+    let document = Html::parse_document(html);
+    let mut counts = Counts::default();
+    counts.walk(document.root_element(), false);
+    counts.finish()
+}
+
+/// Totals so far, and the text of the block in progress.
+#[derive(Default)]
+struct Counts {
+    words: usize,
+    paragraphs: usize,
+    chars: usize,
+    linked_chars: usize,
+    block: String,
+    block_chars: usize,
+    block_linked_chars: usize,
+}
+
+impl Counts {
+    fn walk(&mut self, element: ElementRef<'_>, in_link: bool) {
+        for child in element.children() {
+            if let Some(child) = ElementRef::wrap(child) {
+                let name = child.value().name();
+                if SKIPPED_ELEMENTS.contains(&name) {
+                    continue;
+                }
+                let block = BLOCK_ELEMENTS.contains(&name);
+                if block {
+                    self.end_block();
+                }
+                self.walk(child, in_link || name == "a");
+                if block {
+                    self.end_block();
+                }
+            } else if let Some(text) = child.value().as_text() {
+                let chars = text.chars().filter(|c| !c.is_whitespace()).count();
+                self.block_chars += chars;
+                if in_link {
+                    self.block_linked_chars += chars;
+                }
+                self.block.push_str(text);
+            }
+        }
+    }
+
+    /// Count the block in progress; it is a paragraph when it has enough words
+    /// and is mostly not link text.
+    fn end_block(&mut self) {
+        let words = self.block.split_whitespace().count();
+        if words >= MIN_PARAGRAPH_WORDS && self.block_linked_chars * 2 < self.block_chars {
+            self.paragraphs += 1;
+        }
+        self.words += words;
+        self.chars += self.block_chars;
+        self.linked_chars += self.block_linked_chars;
+        self.block.clear();
+        self.block_chars = 0;
+        self.block_linked_chars = 0;
+    }
+
+    fn finish(mut self) -> ContentQuality {
+        self.end_block();
+        let link_density = match self.chars {
+            0 => 0.0,
+            chars => (self.linked_chars as f64 / chars as f64 * 1000.0).round() / 1000.0,
+        };
+        ContentQuality {
+            word_count: self.words,
+            estimated_reading_minutes: self.words.div_ceil(WORDS_PER_MINUTE),
+            paragraph_count: self.paragraphs,
+            link_density,
+            likely_boilerplate: link_density >= BOILERPLATE_LINK_DENSITY && self.paragraphs < MIN_CONTENT_PARAGRAPHS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_article_is_content() {
+        let paragraph = "<p>Readers who reach the end of a long article should find that every \
+                         section was worth their time, with <a href=\"/notes\">notes</a> \
+                         linked where they help.</p>";
+        let html = format!(
+            "<html><head><title>Guide</title></head><body><nav><a href=\"/\">Home</a></nav>\
+             <article><h1>Guide</h1>{}</article><footer><a href=\"/about\">About</a></footer></body></html>",
+            paragraph.repeat(60)
+        );
+
+        let quality = content_quality(&html);
+        assert_eq!(quality.word_count, 1 + 60 * 24);
+        assert_eq!(quality.estimated_reading_minutes, 7);
+        assert_eq!(quality.paragraph_count, 60);
+        assert!(quality.link_density < 0.05, "{quality:?}");
+        assert!(!quality.likely_boilerplate);
+    }
+
+    #[test]
+    fn test_navigation_is_boilerplate() {
+        let html = r#"<div><p>We use cookies to make this site work. See our <a href="/privacy">privacy policy</a>.</p>
+            <ul>
+              <li><a href="/products">Products and pricing for every team</a></li>
+              <li><a href="/docs">Documentation</a></li>
+              <li><a href="/blog">Blog</a></li>
+              <li><a href="/careers">Careers at Example</a></li>
+            </ul>
+            <p><a href="/accept">Accept all</a> <a href="/reject">Reject</a></p></div>"#;
+
+        let quality = content_quality(html);
+        assert_eq!(quality.paragraph_count, 1);
+        assert!(quality.link_density > 0.5, "{quality:?}");
+        assert!(quality.likely_boilerplate);
+        assert_eq!(quality.estimated_reading_minutes, 1);
+    }
+
+    #[test]
+    fn test_empty_content() {
+        assert_eq!(content_quality("<div>  </div>"), ContentQuality::default());
+    }
+}
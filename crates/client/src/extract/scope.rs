@@ -0,0 +1,261 @@
+//! Scoping a document to one part of the page.
+//!
+//! A `selector` keeps the content of the first element a CSS selector matches,
+//! converted to markdown the way the readable content is. A `section` keeps one
+//! heading of the markdown and everything under it, up to the next heading of
+//! the same or a higher level. Either way the frontmatter is kept and records
+//! the scope, and a scope that matches nothing fails with the document's
+//! headings listed so the caller can pick one.
+
+use lectito_core::Document;
+use scraper::{Html, Selector};
+use thndrs_core::Error;
+
+use super::code::CodePass;
+use super::normalize::Fences;
+use super::plain::plain_outline;
+use super::tables::TablePass;
+
+/// Most headings listed when a scope matches nothing.
+const MAX_LISTED_HEADINGS: usize = 50;
+
+/// Check that `selector` parses as a CSS selector.
+pub fn check_selector(selector: &str) -> Result<(), Error> {
+    parse_selector(selector).map(|_| ())
+}
+
+/// Replace the body of `markdown` with the content of the first element in
+/// `html` that `selector` matches.
+pub fn scope_to_selector(markdown: &str, html: &str, selector: &str) -> Result<String, Error> {
+    let parsed = parse_selector(selector)?;
+    let document = Html::parse_document(html);
+    let Some(element) = document.select(&parsed).next() else {
+        let headings: Vec<String> = plain_outline(html).into_iter().map(|heading| heading.text).collect();
+        return Err(not_found(&format!("no element matches {selector:?}"), &headings));
+    };
+
+    let page = Document::parse(html).map_err(|e| Error::ExtractFailed(format!("failed to parse HTML: {}", e)))?;
+    let tables = TablePass::new(&element.html());
+    let code = CodePass::new(&tables.html);
+    let body = lectito_core::convert_to_markdown(&code.html, &page.extract_metadata(), &Default::default())
+        .map_err(|e| Error::ExtractFailed(format!("markdown conversion failed: {}", e)))?;
+    let body = tables.splice(&code.splice(&body));
+
+    let (header, _) = split_frontmatter(markdown);
+    Ok(with_scope(header, "selector", selector, body.trim()))
+}
+
+/// Cut the body of `markdown` down to the section under the heading whose
+/// text is `section`, compared case-insensitively.
+pub fn scope_to_section(markdown: &str, section: &str) -> Result<String, Error> {
+    let (header, body) = split_frontmatter(markdown);
+    let headings = markdown_headings(body);
+    let wanted = heading_key(section);
+    let Some(index) = headings.iter().position(|heading| heading_key(&heading.text) == wanted) else {
+        let texts: Vec<String> = headings.into_iter().map(|heading| heading.text).collect();
+        return Err(not_found(&format!("no heading matches {section:?}"), &texts));
+    };
+
+    let start = &headings[index];
+    let end = headings[index + 1..]
+        .iter()
+        .find(|heading| heading.level <= start.level)
+        .map_or(body.len(), |heading| heading.offset);
+    Ok(with_scope(header, "section", section, body[start.offset..end].trim()))
+}
+
+fn parse_selector(selector: &str) -> Result<Selector, Error> {
+    Selector::parse(selector).map_err(|e| Error::InvalidInput(format!("invalid selector {selector:?}: {e}")))
+}
+
+fn not_found(reason: &str, headings: &[String]) -> Error {
+    let mut listed: Vec<String> = headings
+        .iter()
+        .take(MAX_LISTED_HEADINGS)
+        .map(|heading| format!("{heading:?}"))
+        .collect();
+    if headings.len() > MAX_LISTED_HEADINGS {
+        listed.push(format!("and {} more", headings.len() - MAX_LISTED_HEADINGS));
+    }
+    let listed = if listed.is_empty() { "none".to_string() } else { listed.join(", ") };
+    Error::ExtractFailed(format!(
+        "selector/section not found: {reason}; available headings: {listed}"
+    ))
+}
+
+/// The frontmatter lines between the `---` fences, if any, and the body after them.
+fn split_frontmatter(markdown: &str) -> (Option<&str>, &str) {
+    let Some(rest) = markdown.strip_prefix("---\n") else {
+        return (None, markdown);
+    };
+    match rest.find("\n---\n") {
+        Some(end) => (Some(&rest[..end]), &rest[end + "\n---\n".len()..]),
+        None => (None, markdown),
+    }
+}
+
+/// Reassemble a document, recording the scope as a frontmatter field.
+fn with_scope(header: Option<&str>, key: &str, value: &str, body: &str) -> String {
+    // Always quoted: selectors start with `#` and `.`, which YAML reads otherwise.
+    let value = format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+    match header {
+        Some(header) => format!("---\n{header}\n{key}: {value}\n---\n{body}"),
+        None => format!("---\n{key}: {value}\n---\n{body}"),
+    }
+}
+
+/// An ATX heading in markdown: its level, plain text, and byte offset.
+struct MarkdownHeading {
+    level: usize,
+    text: String,
+    offset: usize,
+}
+
+/// The ATX headings of a markdown body, skipping fenced code.
+fn markdown_headings(body: &str) -> Vec<MarkdownHeading> {
+    let mut headings = Vec::new();
+    let mut fences = Fences::default();
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+
+        if fences.line(line) || fences.is_open() {
+            continue;
+        }
+        if let Some((level, text)) = atx_heading(line) {
+            headings.push(MarkdownHeading { level, text: heading_text(text), offset: start });
+        }
+    }
+    headings
+}
+
+/// Level and raw text of an ATX heading line (`## Text ##`).
+fn atx_heading(line: &str) -> Option<(usize, &str)> {
+    let line = line.trim_end();
+    let rest = line.trim_start_matches(' ');
+    if line.len() - rest.len() > 3 {
+        return None;
+    }
+    let level = rest.chars().take_while(|c| *c == '#').count();
+    let text = &rest[level..];
+    if !(1..=6).contains(&level) || !(text.is_empty() || text.starts_with([' ', '\t'])) {
+        return None;
+    }
+    let text = text.trim();
+    // A closing run of `#` counts only when set off by a space, so `C#` keeps its `#`.
+    let unclosed = text.trim_end_matches('#');
+    Some((
+        level,
+        if unclosed.is_empty() || unclosed.ends_with(' ') { unclosed.trim_end() } else { text },
+    ))
+}
+
+/// Heading text without link targets, code spans, emphasis, or permalink glyphs.
+fn heading_text(markdown: &str) -> String {
+    let mut text = String::new();
+    let mut rest = markdown;
+    while let Some(open) = rest.find('[') {
+        let Some(close) = rest[open..].find("](").map(|i| open + i) else {
+            break;
+        };
+        let Some(end) = rest[close..].find(')').map(|i| close + i) else {
+            break;
+        };
+        text.push_str(&rest[..open]);
+        text.push_str(&rest[open + 1..close]);
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+    text.replace(['`', '*'], "")
+        .trim_matches(|c: char| c.is_whitespace() || matches!(c, '¶' | '§'))
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Text compared when matching a section: lowercase, whitespace collapsed.
+fn heading_key(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUIDE: &str = "---\ntitle: Guide\nsource: https://example.com/guide\n---\n\
+        # Guide\n\nIntro.\n\n\
+        ## [§](#install)Installation\n\nDownload it.\n\n\
+        ### From `source`\n\n```sh\n# not a heading\ncargo build\n```\n\n\
+        ## Usage ##\n\nRun it.\n\n\
+        #### Writing C#\n\nNotes.\n";
+
+    #[test]
+    fn test_section_runs_to_next_heading_of_same_level() {
+        let scoped = scope_to_section(GUIDE, "  installation ").unwrap();
+        assert_eq!(
+            scoped,
+            "---\ntitle: Guide\nsource: https://example.com/guide\nsection: \"  installation \"\n---\n\
+             ## [§](#install)Installation\n\nDownload it.\n\n\
+             ### From `source`\n\n```sh\n# not a heading\ncargo build\n```"
+        );
+
+        let nested = scope_to_section(GUIDE, "FROM SOURCE").unwrap();
+        assert!(nested.ends_with("---\n### From `source`\n\n```sh\n# not a heading\ncargo build\n```"));
+
+        // A skipped level is still under the heading above it.
+        let usage = scope_to_section(GUIDE, "usage").unwrap();
+        assert!(usage.ends_with("## Usage ##\n\nRun it.\n\n#### Writing C#\n\nNotes."));
+        assert!(
+            scope_to_section(GUIDE, "writing c#")
+                .unwrap()
+                .ends_with("#### Writing C#\n\nNotes.")
+        );
+    }
+
+    #[test]
+    fn test_section_not_found_lists_headings() {
+        let Err(Error::ExtractFailed(message)) = scope_to_section(GUIDE, "Uninstall") else {
+            panic!("expected ExtractFailed");
+        };
+        assert_eq!(
+            message,
+            "selector/section not found: no heading matches \"Uninstall\"; available headings: \
+             \"Guide\", \"Installation\", \"From source\", \"Usage\", \"Writing C#\""
+        );
+
+        let Err(Error::ExtractFailed(message)) = scope_to_section("No headings.", "Intro") else {
+            panic!("expected ExtractFailed");
+        };
+        assert!(message.ends_with("available headings: none"));
+    }
+
+    #[test]
+    fn test_selector_keeps_matching_element() {
+        let html = r#"<html><head><title>Guide</title></head><body>
+            <nav><a href="/">Home</a></nav>
+            <main><h1>Guide</h1><section id="install"><h2>Installation</h2>
+            <p>Download the <strong>latest</strong> release.</p></section></main></body></html>"#;
+
+        let scoped = scope_to_selector("---\ntitle: Guide\n---\nWhole page.", html, "#install").unwrap();
+        assert!(
+            scoped.starts_with("---\ntitle: Guide\nselector: \"#install\"\n---\n"),
+            "{scoped}"
+        );
+        assert!(scoped.contains("Installation"), "{scoped}");
+        assert!(scoped.contains("Download the") && scoped.contains("latest"), "{scoped}");
+        assert!(!scoped.contains("Whole page") && !scoped.contains("Home"), "{scoped}");
+
+        let Err(Error::ExtractFailed(message)) = scope_to_selector("", html, "#usage") else {
+            panic!("expected ExtractFailed");
+        };
+        assert!(message.contains("no element matches \"#usage\""), "{message}");
+        assert!(
+            message.ends_with("available headings: \"Guide\", \"Installation\""),
+            "{message}"
+        );
+
+        assert!(matches!(check_selector("main >"), Err(Error::InvalidInput(_))));
+        assert!(check_selector("main > section#install").is_ok());
+    }
+}
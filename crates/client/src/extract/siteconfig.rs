@@ -0,0 +1,450 @@
+//! Per-site extraction configuration.
+//!
+//! A siteconfig directory holds one file per site, TOML (`*.toml`) or JSON
+//! (`*.json`), read once when the server starts:
+//!
+//! ```toml
+//! id = "example-docs"             # default: the file name without extension
+//! hosts = ["docs.example.com"]    # each also matches its subdomains
+//! content = "main .article-body"  # keep only what this selector matches
+//! ignore = [".sidebar", ".cookie-banner"]
+//!
+//! [extract]                       # overrides of the call's ExtractConfig
+//! strategy = "readability"
+//! char_threshold = 100
+//! ```
+//!
+//! [`SiteConfigRegistry::for_host`] picks the config whose host pattern is the
+//! longest suffix of the host on a label boundary. Before extraction the
+//! `ignore` elements are removed from the page and, when `content` matches, the
+//! body is cut down to its matches; the page's `<head>` is kept for the title
+//! and metadata. The config's id is recorded on the result, so it lands in the
+//! frontmatter and on the snapshot.
+
+use scraper::{Html, Selector};
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::Arc;
+use thndrs_core::Error;
+use url::Url;
+
+use super::scope::check_selector;
+use super::{ExtractConfig, ExtractStrategy};
+
+/// Extraction settings a site config overrides; unset ones keep the call's.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SiteExtractOverrides {
+    /// Readability or plain text
+    pub strategy: Option<ExtractStrategy>,
+    /// Fall back to plain text when readability fails or keeps too little
+    pub fallback: Option<bool>,
+    /// Readability output shorter than this, in characters, falls back
+    pub fallback_min_chars: Option<usize>,
+    /// Minimum character count for content
+    pub char_threshold: Option<usize>,
+    /// Maximum number of top candidates to consider
+    pub max_top_candidates: Option<usize>,
+}
+
+/// Selectors and extraction overrides for the hosts of one site.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SiteConfig {
+    /// Name recorded as `siteconfig_id`; defaults to the file name without extension
+    #[serde(default)]
+    pub id: String,
+    /// Host patterns, e.g. `example.com` (also matching `docs.example.com`)
+    pub hosts: Vec<String>,
+    /// CSS selector of the content; the body is cut down to its matches
+    #[serde(default)]
+    pub content: Option<String>,
+    /// CSS selectors of elements removed before extraction
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// Extraction settings that replace the call's
+    #[serde(default)]
+    pub extract: SiteExtractOverrides,
+}
+
+impl SiteConfig {
+    /// Length of the host pattern that matches `host`, if any; longer is more specific.
+    pub fn match_len(&self, host: &str) -> Option<usize> {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        self.hosts
+            .iter()
+            .map(|pattern| host_pattern(pattern))
+            .filter(|pattern| {
+                host == *pattern
+                    || host
+                        .strip_suffix(pattern.as_str())
+                        .is_some_and(|prefix| prefix.ends_with('.'))
+            })
+            .map(|pattern| pattern.len())
+            .max()
+    }
+
+    /// `config` with this site's overrides applied.
+    pub fn apply(&self, config: &ExtractConfig) -> ExtractConfig {
+        let overrides = &self.extract;
+        ExtractConfig {
+            strategy: overrides.strategy.unwrap_or(config.strategy),
+            fallback: overrides.fallback.unwrap_or(config.fallback),
+            fallback_min_chars: overrides.fallback_min_chars.or(config.fallback_min_chars),
+            char_threshold: overrides.char_threshold.or(config.char_threshold),
+            max_top_candidates: overrides.max_top_candidates.or(config.max_top_candidates),
+            ..config.clone()
+        }
+    }
+
+    /// Remove the `ignore` elements from `html` and cut its body down to the
+    /// `content` matches; when `content` matches nothing the rest of the body stays.
+    pub fn prune(&self, html: &str) -> String {
+        if self.content.is_none() && self.ignore.is_empty() {
+            return html.to_string();
+        }
+
+        let mut document = Html::parse_document(html);
+        let ignored: Vec<_> = self
+            .ignore
+            .iter()
+            .filter_map(|selector| Selector::parse(selector).ok())
+            .flat_map(|selector| {
+                document
+                    .select(&selector)
+                    .map(|element| element.id())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        for id in ignored {
+            if let Some(mut node) = document.tree.get_mut(id) {
+                node.detach();
+            }
+        }
+
+        let Some(content) = self
+            .content
+            .as_deref()
+            .and_then(|selector| Selector::parse(selector).ok())
+        else {
+            return document.html();
+        };
+        let matched: Vec<_> = document.select(&content).collect();
+        if matched.is_empty() {
+            tracing::debug!("siteconfig {} content selector matched nothing", self.id);
+            return document.html();
+        }
+        // A match inside another is already part of it.
+        let kept: String = matched
+            .iter()
+            .filter(|element| {
+                !element
+                    .ancestors()
+                    .any(|ancestor| matched.iter().any(|m| m.id() == ancestor.id()))
+            })
+            .map(|element| element.html())
+            .collect();
+        let head_selector = Selector::parse("head").expect("valid selector");
+        let head = document
+            .select(&head_selector)
+            .next()
+            .map(|head| head.html())
+            .unwrap_or_default();
+        format!("<html>{head}<body>{kept}</body></html>")
+    }
+
+    /// Check the config can be applied: it names hosts and its selectors parse.
+    fn check(&self) -> Result<(), Error> {
+        if self.hosts.iter().all(|pattern| host_pattern(pattern).is_empty()) {
+            return Err(Error::InvalidInput(format!("siteconfig {} names no hosts", self.id)));
+        }
+        for selector in self.content.iter().chain(&self.ignore) {
+            check_selector(selector).map_err(|e| Error::InvalidInput(format!("siteconfig {}: {}", self.id, e)))?;
+        }
+        Ok(())
+    }
+}
+
+/// A host pattern as matched: lowercase, without a leading `*.` or `.`.
+fn host_pattern(pattern: &str) -> String {
+    let pattern = pattern.trim();
+    let pattern = pattern.strip_prefix("*.").unwrap_or(pattern);
+    pattern
+        .trim_start_matches('.')
+        .trim_end_matches('.')
+        .to_ascii_lowercase()
+}
+
+/// Site configs by host, see [`SiteConfigRegistry::for_host`].
+#[derive(Debug, Clone, Default)]
+pub struct SiteConfigRegistry {
+    configs: Vec<Arc<SiteConfig>>,
+}
+
+impl SiteConfigRegistry {
+    /// Build a registry from configs, checking each and that ids are unique.
+    pub fn new(configs: Vec<SiteConfig>) -> Result<Self, Error> {
+        let mut registry = Self::default();
+        for config in configs {
+            config.check()?;
+            if registry.get(&config.id).is_some() {
+                return Err(Error::InvalidInput(format!("duplicate siteconfig id {}", config.id)));
+            }
+            registry.configs.push(Arc::new(config));
+        }
+        Ok(registry)
+    }
+
+    /// Read every `*.toml` and `*.json` file in `dir`, in file name order.
+    pub fn load(dir: &Path) -> Result<Self, Error> {
+        let unreadable = |e: std::io::Error| Error::InvalidInput(format!("siteconfig dir {}: {}", dir.display(), e));
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .map_err(unreadable)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<_, _>>()
+            .map_err(unreadable)?;
+        paths.sort();
+
+        let mut configs = Vec::new();
+        for path in paths {
+            let extension = path.extension().and_then(|ext| ext.to_str());
+            if !matches!(extension, Some("toml" | "json")) || !path.is_file() {
+                continue;
+            }
+            let invalid =
+                |e: &dyn std::fmt::Display| Error::InvalidInput(format!("siteconfig {}: {}", path.display(), e));
+            let text = std::fs::read_to_string(&path).map_err(|e| invalid(&e))?;
+            let mut config: SiteConfig = match extension {
+                Some("toml") => toml::from_str(&text).map_err(|e| invalid(&e))?,
+                _ => serde_json::from_str(&text).map_err(|e| invalid(&e))?,
+            };
+            if config.id.is_empty() {
+                config.id = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .unwrap_or_default()
+                    .to_string();
+            }
+            configs.push(config);
+        }
+        let registry = Self::new(configs)?;
+        tracing::debug!("loaded {} siteconfigs from {}", registry.len(), dir.display());
+        Ok(registry)
+    }
+
+    /// Number of configs.
+    pub fn len(&self) -> usize {
+        self.configs.len()
+    }
+
+    /// Whether the registry has no configs.
+    pub fn is_empty(&self) -> bool {
+        self.configs.is_empty()
+    }
+
+    /// Ids of the configs, in load order.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.configs.iter().map(|config| config.id.as_str())
+    }
+
+    /// The config with this id.
+    pub fn get(&self, id: &str) -> Option<&Arc<SiteConfig>> {
+        self.configs.iter().find(|config| config.id == id)
+    }
+
+    /// The config whose host pattern most specifically matches `host`; the
+    /// first loaded wins a tie.
+    pub fn for_host(&self, host: &str) -> Option<&Arc<SiteConfig>> {
+        self.configs
+            .iter()
+            .filter_map(|config| config.match_len(host).map(|len| (len, config)))
+            .fold(
+                None,
+                |best: Option<(usize, &Arc<SiteConfig>)>, (len, config)| match best {
+                    Some((best_len, _)) if best_len >= len => best,
+                    _ => Some((len, config)),
+                },
+            )
+            .map(|(_, config)| config)
+    }
+
+    /// `config` with `siteconfig` set to the config for `url`'s host, unless one
+    /// is already set or `use_siteconfig` is off.
+    pub fn configure(&self, config: &ExtractConfig, url: &Url) -> ExtractConfig {
+        let mut config = config.clone();
+        if config.siteconfig.is_none() && config.use_siteconfig {
+            config.siteconfig = url.host_str().and_then(|host| self.for_host(host)).cloned();
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::{LectitoExtractor, extract_with_fallback};
+    use crate::pipeline::extract_document;
+    use chrono::Utc;
+
+    fn site(id: &str, hosts: &[&str]) -> SiteConfig {
+        SiteConfig { id: id.into(), hosts: hosts.iter().map(|h| h.to_string()).collect(), ..Default::default() }
+    }
+
+    const SIDEBAR_HTML: &str = r#"<html><head><title>Release notes</title></head><body>
+        <article>
+            <h1>Release notes</h1>
+            <div class="sidebar"><p>Related posts: seven tips for faster builds.</p></div>
+            <p>This release rewrites the scheduler so long-running jobs no longer starve short ones, and it
+            reports queue wait times for every job alongside how long the job itself ran.</p>
+            <p>Upgrading needs no configuration changes, although deployments that pinned the old scheduler
+            with the legacy flag should remove it, since the flag is now ignored and logs a warning.</p>
+            <p>Metrics exported by earlier versions keep their names, and the new queue wait metric appears
+            next to them, so existing dashboards continue to work without any edits.</p>
+        </article>
+    </body></html>"#;
+
+    #[test]
+    fn test_for_host_matches_suffix_on_label_boundary() {
+        let registry = SiteConfigRegistry::new(vec![
+            site("example", &["example.com"]),
+            site("docs", &["*.docs.example.com", "DOCS.example.com"]),
+        ])
+        .unwrap();
+
+        let id = |host: &str| registry.for_host(host).map(|config| config.id.clone());
+        assert_eq!(id("example.com").as_deref(), Some("example"));
+        assert_eq!(id("www.example.com").as_deref(), Some("example"));
+        assert_eq!(id("docs.example.com").as_deref(), Some("docs"));
+        assert_eq!(id("api.docs.example.com.").as_deref(), Some("docs"));
+        assert_eq!(id("notexample.com"), None);
+        assert_eq!(id("example.org"), None);
+    }
+
+    #[test]
+    fn test_new_rejects_bad_configs() {
+        assert!(SiteConfigRegistry::new(vec![site("a", &["a.com"]), site("a", &["b.com"])]).is_err());
+        assert!(SiteConfigRegistry::new(vec![site("none", &[])]).is_err());
+        let bad_selector = SiteConfig { ignore: vec!["div[".into()], ..site("bad", &["a.com"]) };
+        assert!(SiteConfigRegistry::new(vec![bad_selector]).is_err());
+    }
+
+    #[test]
+    fn test_load_toml_and_json() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("example.toml"),
+            "hosts = [\"example.com\"]\nignore = [\".sidebar\"]\n\n[extract]\nstrategy = \"plain_text\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("blog.json"),
+            r#"{"id": "example-blog", "hosts": ["blog.example.com"], "content": "article"}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("README.md"), "not a config").unwrap();
+
+        let registry = SiteConfigRegistry::load(dir.path()).unwrap();
+        assert_eq!(registry.ids().collect::<Vec<_>>(), ["example-blog", "example"]);
+        let example = registry.get("example").unwrap();
+        assert_eq!(example.ignore, [".sidebar"]);
+        assert_eq!(example.extract.strategy, Some(ExtractStrategy::PlainText));
+        assert_eq!(
+            registry.for_host("blog.example.com").unwrap().content.as_deref(),
+            Some("article")
+        );
+
+        std::fs::write(dir.path().join("broken.toml"), "hosts = [\"a.com\"]\nunknown = 1\n").unwrap();
+        assert!(SiteConfigRegistry::load(dir.path()).is_err());
+        assert!(SiteConfigRegistry::load(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_configure_looks_up_by_host() {
+        let registry = SiteConfigRegistry::new(vec![site("example", &["example.com"])]).unwrap();
+        let url = Url::parse("https://www.example.com/notes").unwrap();
+
+        let config = registry.configure(&ExtractConfig::default(), &url);
+        assert_eq!(
+            config.siteconfig.map(|site| site.id.clone()).as_deref(),
+            Some("example")
+        );
+
+        let off = ExtractConfig { use_siteconfig: false, ..Default::default() };
+        assert!(registry.configure(&off, &url).siteconfig.is_none());
+
+        let other = Url::parse("https://example.org/").unwrap();
+        assert!(
+            registry
+                .configure(&ExtractConfig::default(), &other)
+                .siteconfig
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_apply_overrides() {
+        let config = SiteConfig {
+            extract: SiteExtractOverrides {
+                strategy: Some(ExtractStrategy::PlainText),
+                char_threshold: Some(50),
+                ..Default::default()
+            },
+            ..site("example", &["example.com"])
+        };
+        let applied = config.apply(&ExtractConfig::default());
+        assert_eq!(applied.strategy, ExtractStrategy::PlainText);
+        assert_eq!(applied.char_threshold, Some(50));
+        assert_eq!(applied.max_top_candidates, Some(5));
+        assert!(applied.fallback);
+    }
+
+    #[test]
+    fn test_prune_keeps_content_and_head() {
+        let config = SiteConfig {
+            content: Some("article".into()),
+            ignore: vec![".sidebar".into()],
+            ..site("example", &["example.com"])
+        };
+        let html = r#"<html><head><title>Notes</title></head><body><nav>Menu</nav>
+            <article><p>Kept</p><article><p>Nested</p></article><aside class="sidebar">Dropped</aside></article>
+            </body></html>"#;
+
+        let pruned = config.prune(html);
+        assert!(pruned.contains("<title>Notes</title>"));
+        assert!(pruned.contains("Kept"));
+        assert_eq!(pruned.matches("Nested").count(), 1);
+        assert!(!pruned.contains("Menu"));
+        assert!(!pruned.contains("Dropped"));
+
+        let unmatched = SiteConfig { content: Some("main".into()), ..config };
+        assert!(unmatched.prune(html).contains("Menu"));
+    }
+
+    #[test]
+    fn test_siteconfig_strips_sidebar() {
+        let base = Url::parse("https://example.com/notes").unwrap();
+        let registry = SiteConfigRegistry::new(vec![SiteConfig {
+            ignore: vec![".sidebar".into()],
+            ..site("example", &["example.com"])
+        }])
+        .unwrap();
+        let plain = ExtractConfig { strategy: ExtractStrategy::PlainText, ..Default::default() };
+
+        let unconfigured = extract_with_fallback(&LectitoExtractor::new(), SIDEBAR_HTML, &base, &plain).unwrap();
+        assert!(unconfigured.markdown.contains("Related posts"));
+        assert!(unconfigured.siteconfig_id.is_none());
+
+        for config in [plain, ExtractConfig::default()] {
+            let config = registry.configure(&config, &base);
+            let result = extract_with_fallback(&LectitoExtractor::new(), SIDEBAR_HTML, &base, &config).unwrap();
+            assert!(!result.markdown.contains("Related posts"), "{}", result.markdown);
+            assert!(result.markdown.contains("scheduler"));
+            assert_eq!(result.siteconfig_id.as_deref(), Some("example"));
+        }
+
+        let config = registry.configure(&ExtractConfig::default(), &base);
+        let extraction = extract_document(&LectitoExtractor::new(), SIDEBAR_HTML, &base, &Utc::now(), &config).unwrap();
+        assert!(extraction.markdown.contains("\nsiteconfig: example\n"));
+        assert_eq!(extraction.siteconfig_id.as_deref(), Some("example"));
+    }
+}
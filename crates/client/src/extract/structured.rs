@@ -0,0 +1,215 @@
+//! JSON-LD structured data: `<script type="application/ld+json">` blocks.
+//!
+//! News sites and documentation pages often describe themselves with
+//! schema.org Article, BreadcrumbList, FAQPage, or HowTo objects that are
+//! cleaner than anything in the visible DOM. Blocks are parsed leniently
+//! (trailing commas, raw control characters in strings, HTML comment
+//! wrappers) and arrays at the root are flattened; blocks that still fail to
+//! parse are skipped so they never break extraction.
+
+use scraper::{Html, Selector};
+use serde_json::Value;
+
+/// Most JSON-LD items kept from one page.
+pub const MAX_STRUCTURED_ITEMS: usize = 32;
+
+/// schema.org types read by [`article_data`].
+const ARTICLE_TYPES: &[&str] = &[
+    "Article",
+    "NewsArticle",
+    "BlogPosting",
+    "TechArticle",
+    "ScholarlyArticle",
+    "Report",
+    "LiveBlogPosting",
+];
+
+/// Citation fields of an Article-like JSON-LD item.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArticleData {
+    /// `headline`
+    pub headline: Option<String>,
+    /// `author`: a name, or several joined with ", "
+    pub author: Option<String>,
+    /// `datePublished` as written
+    pub date_published: Option<String>,
+}
+
+/// Every JSON-LD item in an HTML document, in document order.
+pub fn extract_structured_data(html: &str) -> Vec<Value> {
+    structured_data(&Html::parse_document(html))
+}
+
+/// [`extract_structured_data`] over an already parsed document.
+pub(crate) fn structured_data(document: &Html) -> Vec<Value> {
+    let selector = Selector::parse("script[type]").expect("valid selector");
+    document
+        .select(&selector)
+        .filter(|script| {
+            let media_type = script.value().attr("type").unwrap_or_default();
+            media_type
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .eq_ignore_ascii_case("application/ld+json")
+        })
+        .filter_map(|script| parse_block(&script.text().collect::<String>()))
+        .flat_map(|value| match value {
+            Value::Array(items) => items,
+            item => vec![item],
+        })
+        .filter(Value::is_object)
+        .take(MAX_STRUCTURED_ITEMS)
+        .collect()
+}
+
+/// The first Article-like item, looking inside `@graph` containers too.
+pub fn article_data(items: &[Value]) -> Option<ArticleData> {
+    let article = items
+        .iter()
+        .flat_map(|item| {
+            std::iter::once(item).chain(item.get("@graph").and_then(Value::as_array).into_iter().flatten())
+        })
+        .find(|item| is_article(item))?;
+
+    Some(ArticleData {
+        headline: text(article.get("headline")),
+        author: author(article.get("author")),
+        date_published: text(article.get("datePublished")),
+    })
+}
+
+/// Parse one block, retrying with trailing commas and raw control characters removed.
+fn parse_block(text: &str) -> Option<Value> {
+    let text = text.trim();
+    let text = text.strip_prefix("<!--").unwrap_or(text);
+    let text = text.strip_suffix("-->").unwrap_or(text).trim();
+    if text.is_empty() {
+        return None;
+    }
+    match serde_json::from_str(text).or_else(|_| serde_json::from_str(&relax(text))) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            tracing::debug!("skipping unparsable JSON-LD block: {}", e);
+            None
+        }
+    }
+}
+
+/// Drop commas directly before `]` or `}` and replace control characters inside strings.
+fn relax(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    let (mut in_string, mut escaped) = (false, false);
+    while let Some(c) = chars.next() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                c if c.is_control() => {
+                    out.push(' ');
+                    continue;
+                }
+                _ => {}
+            }
+        } else if c == ',' {
+            let next = chars.clone().find(|c| !c.is_whitespace());
+            if matches!(next, Some(']' | '}')) {
+                continue;
+            }
+        } else if c == '"' {
+            in_string = true;
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn is_article(item: &Value) -> bool {
+    let is_article_type = |t: &Value| {
+        t.as_str()
+            .map(|t| t.rsplit(|c| c == '/' || c == ':').next().unwrap_or(t))
+            .is_some_and(|t| ARTICLE_TYPES.contains(&t))
+    };
+    match item.get("@type") {
+        Some(Value::Array(types)) => types.iter().any(is_article_type),
+        Some(t) => is_article_type(t),
+        None => false,
+    }
+}
+
+fn text(value: Option<&Value>) -> Option<String> {
+    let text = value?.as_str()?.split_whitespace().collect::<Vec<_>>().join(" ");
+    Some(text).filter(|t| !t.is_empty())
+}
+
+/// An author given as a name, a Person/Organization object, or a list of either.
+fn author(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::Array(authors) => {
+            let names: Vec<String> = authors.iter().filter_map(|a| author(Some(a))).collect();
+            Some(names.join(", ")).filter(|n| !n.is_empty())
+        }
+        Value::Object(person) => text(person.get("name")),
+        other => text(Some(other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const PAGE: &str = r#"<html><head>
+        <script type="application/ld+json">
+          {"@context": "https://schema.org", "@type": "NewsArticle",
+           "headline": "Rust 2027 ships",
+           "author": [{"@type": "Person", "name": "Ada"}, {"@type": "Person", "name": "Grace"}],
+           "datePublished": "2027-01-05T10:00:00Z",}
+        </script>
+        <script type="application/ld+json">
+          [{"@type": "BreadcrumbList", "itemListElement": []}, "stray", {"@type": "FAQPage"}]
+        </script>
+        <script type="application/ld+json">{"@type": "HowTo", "name": </script>
+        <script type="application/json">{"@type": "Article"}</script>
+        </head><body><p>Body</p></body></html>"#;
+
+    #[test]
+    fn test_collects_lenient_blocks_and_flattens_arrays() {
+        let items = extract_structured_data(PAGE);
+        let types: Vec<&str> = items.iter().filter_map(|i| i["@type"].as_str()).collect();
+        assert_eq!(types, ["NewsArticle", "BreadcrumbList", "FAQPage"]);
+    }
+
+    #[test]
+    fn test_article_accessors() {
+        let article = article_data(&extract_structured_data(PAGE)).unwrap();
+        assert_eq!(
+            article,
+            ArticleData {
+                headline: Some("Rust 2027 ships".into()),
+                author: Some("Ada, Grace".into()),
+                date_published: Some("2027-01-05T10:00:00Z".into()),
+            }
+        );
+
+        let graph = json!({"@graph": [
+            {"@type": "WebPage"},
+            {"@type": ["schema:BlogPosting"], "author": "Linus", "headline": "Notes"}
+        ]});
+        let article = article_data(&[graph]).unwrap();
+        assert_eq!(article.author.as_deref(), Some("Linus"));
+        assert!(article_data(&[json!({"@type": "Recipe"})]).is_none());
+    }
+
+    #[test]
+    fn test_relax_keeps_strings_intact() {
+        let relaxed = relax("{\"a\": \"x,}\", \"b\": [1, 2, ],\n \"c\": \"line\nbreak\",}");
+        let value: Value = serde_json::from_str(&relaxed).unwrap();
+        assert_eq!(value, json!({"a": "x,}", "b": [1, 2], "c": "line break"}));
+        assert!(parse_block("<!-- {\"@type\": \"Article\"} -->").is_some());
+        assert!(parse_block("   ").is_none());
+    }
+}
@@ -0,0 +1,332 @@
+//! Table handling for the markdown conversion.
+//!
+//! The markdown converter flattens `<table>` cells into run-on paragraphs, so
+//! tables in the extracted content are swapped for placeholders before
+//! conversion and spliced back afterwards. Simple tables (no row or column
+//! spans) come back as GitHub-flavored markdown tables. Spanned tables don't
+//! fit that grid: they are returned as structured [`Table`]s, with a marker in
+//! the markdown where they stood. Tables that contain other tables, or have
+//! fewer than two columns, are layout and are left to the converter.
+
+use scraper::{ElementRef, Html, Selector};
+
+use super::placeholder::Placeholders;
+
+pub use thndrs_core::cache::Table;
+
+/// Widest `colspan` honored; the HTML limit is 1000.
+const MAX_COLSPAN: usize = 100;
+
+/// Content HTML with its tables replaced by placeholders.
+#[derive(Debug, Clone)]
+pub struct TablePass {
+    /// HTML to hand to the markdown converter
+    pub html: String,
+    /// Tables too complex for markdown, in document order
+    pub tables: Vec<Table>,
+    placeholders: Placeholders,
+}
+
+impl TablePass {
+    /// Find the data tables in `content_html` and replace them with placeholders.
+    ///
+    /// Content without data tables is passed through unchanged.
+    pub fn new(content_html: &str) -> Self {
+        let mut fragment = Html::parse_fragment(content_html);
+        let selector = Selector::parse("table").expect("valid selector");
+        let found: Vec<_> = fragment
+            .select(&selector)
+            .filter(|table| !has_nested_table(table))
+            .filter_map(|table| Some((table.id(), read_table(table)?)))
+            .collect();
+        let mut pass = Self { html: String::new(), tables: Vec::new(), placeholders: Placeholders::new("thndrstable") };
+        if found.is_empty() {
+            pass.html = content_html.to_string();
+            return pass;
+        }
+
+        for (id, grid) in found {
+            let block = if grid.spanned {
+                let marker = match &grid.caption {
+                    Some(caption) => format!("[Table: {caption}, see tables[{}]]", pass.tables.len()),
+                    None => format!("[Table: see tables[{}]]", pass.tables.len()),
+                };
+                pass.tables.push(grid.into_table());
+                marker
+            } else {
+                grid.to_markdown()
+            };
+            let token = pass.placeholders.push(block, true);
+            let mut node = fragment.tree.get_mut(id).expect("selected table is in the tree");
+            node.insert_before(token);
+            node.detach();
+        }
+        pass.html = fragment.root_element().inner_html();
+        pass
+    }
+
+    /// Put the tables back into the converted markdown, each as its own block.
+    pub fn splice(&self, markdown: &str) -> String {
+        self.placeholders.splice(markdown)
+    }
+}
+
+fn has_nested_table(table: &ElementRef<'_>) -> bool {
+    table
+        .descendants()
+        .skip(1)
+        .filter_map(ElementRef::wrap)
+        .any(|element| element.value().name() == "table")
+}
+
+/// A table's cells laid out on a grid, spans repeated into every slot they cover.
+struct Grid {
+    caption: Option<String>,
+    /// Header rows (from `<thead>`, or a leading row of `<th>` cells)
+    header_rows: usize,
+    rows: Vec<Vec<String>>,
+    /// Whether any cell spans more than one row or column
+    spanned: bool,
+}
+
+impl Grid {
+    fn width(&self) -> usize {
+        self.rows.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    /// Header cells, several header rows joined per column with " / ".
+    fn headers(&self) -> Vec<String> {
+        let header_rows = &self.rows[..self.header_rows];
+        (0..self.width())
+            .map(|column| {
+                let mut parts: Vec<&str> = Vec::new();
+                for cell in header_rows.iter().filter_map(|row| row.get(column)) {
+                    if !cell.is_empty() && parts.last() != Some(&cell.as_str()) {
+                        parts.push(cell);
+                    }
+                }
+                parts.join(" / ")
+            })
+            .collect()
+    }
+
+    fn into_table(self) -> Table {
+        let headers = if self.header_rows > 0 { self.headers() } else { Vec::new() };
+        let width = self.width();
+        let rows = self
+            .rows
+            .into_iter()
+            .skip(self.header_rows)
+            .map(|mut row| {
+                row.resize(width, String::new());
+                row
+            })
+            .collect();
+        Table { caption: self.caption, headers, rows }
+    }
+
+    fn to_markdown(&self) -> String {
+        let row = |cells: &[String]| {
+            let cells: Vec<String> = (0..self.width())
+                .map(|column| cells.get(column).map_or(String::new(), |cell| cell.replace('|', "\\|")))
+                .collect();
+            format!("| {} |", cells.join(" | "))
+        };
+
+        let mut lines = Vec::new();
+        if let Some(caption) = &self.caption {
+            lines.push(caption.clone());
+            lines.push(String::new());
+        }
+        lines.push(row(&self.headers()));
+        lines.push(format!("|{}", " --- |".repeat(self.width())));
+        lines.extend(self.rows[self.header_rows..].iter().map(|cells| row(cells)));
+        lines.join("\n")
+    }
+}
+
+/// Lay out a table's rows, or `None` for layout tables not worth converting.
+fn read_table(table: ElementRef<'_>) -> Option<Grid> {
+    let row_selector = Selector::parse("tr").expect("valid selector");
+    let rows: Vec<ElementRef<'_>> = table.select(&row_selector).collect();
+
+    let mut grid = Vec::with_capacity(rows.len());
+    // Per column: rows still covered by a rowspan above, and that cell's text.
+    let mut carried: Vec<(usize, String)> = Vec::new();
+    let mut spanned = false;
+    let mut thead_rows = 0;
+    for (row_index, row) in rows.iter().enumerate() {
+        if row_index == thead_rows
+            && row
+                .parent()
+                .and_then(ElementRef::wrap)
+                .is_some_and(|p| p.value().name() == "thead")
+        {
+            thead_rows += 1;
+        }
+
+        let mut cells = Vec::new();
+        let mut column = 0;
+        for cell in row.children().filter_map(ElementRef::wrap) {
+            if !matches!(cell.value().name(), "td" | "th") {
+                continue;
+            }
+            take_carried(&mut carried, &mut cells, &mut column);
+            let colspan = span(cell, "colspan").min(MAX_COLSPAN);
+            let rowspan = span(cell, "rowspan").min(rows.len() - row_index);
+            spanned |= colspan > 1 || rowspan > 1;
+
+            let text = cell_text(cell);
+            for _ in 0..colspan {
+                if carried.len() <= column {
+                    carried.resize(column + 1, (0, String::new()));
+                }
+                carried[column] = (rowspan - 1, text.clone());
+                cells.push(text.clone());
+                column += 1;
+            }
+        }
+        take_carried(&mut carried, &mut cells, &mut column);
+        grid.push(cells);
+    }
+
+    let header_rows = match thead_rows {
+        0 if rows.first().is_some_and(|row| is_header_row(*row)) => 1,
+        n => n,
+    };
+    let caption_selector = Selector::parse("caption").expect("valid selector");
+    let caption = table
+        .select(&caption_selector)
+        .next()
+        .map(cell_text)
+        .filter(|caption| !caption.is_empty());
+
+    let grid = Grid { caption, header_rows, rows: grid, spanned: spanned || header_rows > 1 };
+    (grid.rows.len() > grid.header_rows && grid.width() >= 2).then_some(grid)
+}
+
+/// Fill the slots from `column` on that rowspans above still cover.
+fn take_carried(carried: &mut [(usize, String)], cells: &mut Vec<String>, column: &mut usize) {
+    while let Some((remaining, text)) = carried.get_mut(*column).filter(|(remaining, _)| *remaining > 0) {
+        *remaining -= 1;
+        cells.push(text.clone());
+        *column += 1;
+    }
+}
+
+fn is_header_row(row: ElementRef<'_>) -> bool {
+    let mut cells = row
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+        .peekable();
+    cells.peek().is_some() && cells.all(|cell| cell.value().name() == "th")
+}
+
+/// A `colspan` or `rowspan`, at least 1.
+fn span(cell: ElementRef<'_>, attr: &str) -> usize {
+    cell.value()
+        .attr(attr)
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .unwrap_or(1)
+        .max(1)
+}
+
+fn cell_text(cell: ElementRef<'_>) -> String {
+    cell.text()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIMPLE: &str = r#"<p>Plans:</p><table>
+        <tr><td>Free</td><td>$0</td></tr>
+        <tr><td>Pro | Team</td><td>$10</td></tr>
+        </table><p>Prices are monthly.</p>"#;
+
+    #[test]
+    fn test_simple_table_becomes_markdown() {
+        let pass = TablePass::new(SIMPLE);
+        assert!(pass.tables.is_empty());
+        assert!(!pass.html.contains("<table"));
+        assert_eq!(
+            pass.splice("Plans:\n\nthndrstable0x\n\nPrices are monthly.\n"),
+            "Plans:\n\n|  |  |\n| --- | --- |\n| Free | $0 |\n| Pro \\| Team | $10 |\n\nPrices are monthly.\n"
+        );
+    }
+
+    #[test]
+    fn test_thead_and_tbody() {
+        let html = r#"<table><caption> Release
+            dates </caption>
+            <thead><tr><th>Version</th><th>Date</th></tr></thead>
+            <tbody><tr><td>1.0</td><td>2025-01-01</td></tr><tr><td>1.1</td><td>2025-03-01</td></tr></tbody>
+            </table>"#;
+        let pass = TablePass::new(html);
+        assert!(pass.tables.is_empty());
+        assert_eq!(
+            pass.splice("Intro thndrstable0x"),
+            "Intro\n\nRelease dates\n\n| Version | Date |\n| --- | --- |\n| 1.0 | 2025-01-01 |\n| 1.1 | 2025-03-01 |"
+        );
+
+        let th_row = "<table><tr><th>A</th><th>B</th></tr><tr><td>1</td><td>2</td></tr></table>";
+        let pass = TablePass::new(th_row);
+        assert_eq!(pass.splice("thndrstable0x"), "| A | B |\n| --- | --- |\n| 1 | 2 |");
+    }
+
+    #[test]
+    fn test_spanned_table_goes_to_structured_path() {
+        let html = r#"<p>Before</p><table>
+            <tr><th>Region</th><th colspan="2">Sales</th></tr>
+            <tr><td rowspan="2">EU</td><td>Q1</td><td>10</td></tr>
+            <tr><td>Q2</td><td>12</td></tr>
+            <tr><td>US</td><td>Q1</td></tr>
+            </table>"#;
+        let pass = TablePass::new(html);
+        assert_eq!(
+            pass.tables,
+            vec![Table {
+                caption: None,
+                headers: vec!["Region".into(), "Sales".into(), "Sales".into()],
+                rows: vec![
+                    vec!["EU".into(), "Q1".into(), "10".into()],
+                    vec!["EU".into(), "Q2".into(), "12".into()],
+                    vec!["US".into(), "Q1".into(), String::new()],
+                ],
+            }]
+        );
+        assert_eq!(
+            pass.splice("Before\n\nthndrstable0x"),
+            "Before\n\n[Table: see tables[0]]"
+        );
+    }
+
+    #[test]
+    fn test_layout_tables_and_plain_content_pass_through() {
+        let content = "<p>No tables here.</p>";
+        let pass = TablePass::new(content);
+        assert_eq!(pass.html, content);
+        assert_eq!(pass.splice("No tables here."), "No tables here.");
+
+        let layout = concat!(
+            "<table><tr><td>",
+            "<table><tr><td>x</td><td>y</td></tr><tr><td>1</td><td>2</td></tr></table>",
+            "</td></tr></table>"
+        );
+        let pass = TablePass::new(layout);
+        assert_eq!(pass.html.matches("<table").count(), 1);
+        assert_eq!(
+            pass.splice("thndrstable0x"),
+            "|  |  |\n| --- | --- |\n| x | y |\n| 1 | 2 |"
+        );
+
+        let single_column = "<table><tr><td>Only</td></tr><tr><td>One</td></tr></table>";
+        assert_eq!(TablePass::new(single_column).html, single_column);
+    }
+}
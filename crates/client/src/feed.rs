@@ -0,0 +1,371 @@
+//! RSS and Atom feed parsing and discovery.
+//!
+//! Feeds are recognized by Content-Type (`application/rss+xml`,
+//! `application/atom+xml`) or, for XML and untyped bodies, by an `<rss>`,
+//! `<feed>`, or `<rdf:RDF>` root element. [`parse_feed`] turns one into a list
+//! of entries with plain-text summaries; [`Feed::to_markdown`] renders that list
+//! for readable mode. [`discover_feeds`] finds the feeds an HTML page
+//! advertises with `<link rel="alternate">`.
+
+use chrono::{DateTime, Utc};
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use thndrs_core::Error;
+use thndrs_core::cache::FeedLink;
+use url::Url;
+
+use crate::fetch::content_type::essence;
+
+/// Version string of the feed parser.
+///
+/// Must match the version feed-rs is pinned to in this crate's Cargo.toml.
+pub const FEED_EXTRACTOR_VERSION: &str = "feed-rs@2.3.1";
+
+/// Content types that always hold a feed.
+pub const FEED_CONTENT_TYPES: &[&str] = &["application/rss+xml", "application/atom+xml"];
+
+/// Feed types accepted from `<link rel="alternate" type=...>`.
+const ADVERTISED_FEED_TYPES: &[&str] = &["application/rss+xml", "application/atom+xml", "application/feed+json"];
+
+/// Longest entry summary kept, in characters.
+pub const MAX_SUMMARY_CHARS: usize = 500;
+
+/// Most feed links taken from one page.
+const MAX_DISCOVERED_FEEDS: usize = 16;
+
+/// How far into a body the root element is looked for.
+const SNIFF_BYTES: usize = 1024;
+
+/// A parsed feed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Feed {
+    /// `rss`, `atom`, or `json`
+    pub kind: String,
+    /// Feed title
+    pub title: Option<String>,
+    /// The site the feed belongs to
+    pub link: Option<String>,
+    /// Entries in feed order, usually newest first
+    pub entries: Vec<FeedEntry>,
+}
+
+/// One item of a feed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedEntry {
+    /// Entry title
+    pub title: Option<String>,
+    /// Link to the entry's page
+    pub link: Option<String>,
+    /// Publication time (RFC 3339), falling back to the last update
+    pub published: Option<String>,
+    /// Summary or content as plain text, cut at [`MAX_SUMMARY_CHARS`]
+    pub summary: Option<String>,
+}
+
+impl FeedEntry {
+    /// Publication time, parsed.
+    pub fn published_at(&self) -> Option<DateTime<Utc>> {
+        let published = DateTime::parse_from_rfc3339(self.published.as_deref()?).ok()?;
+        Some(published.with_timezone(&Utc))
+    }
+}
+
+impl Feed {
+    /// Markdown body listing each entry under a heading linked to its page.
+    pub fn to_markdown(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let title = entry.title.as_deref().unwrap_or("Untitled");
+                let mut section = match &entry.link {
+                    Some(link) => format!("## [{}]({})", title, link),
+                    None => format!("## {}", title),
+                };
+                if let Some(published) = &entry.published {
+                    section.push_str(&format!("\n\n*{}*", published));
+                }
+                if let Some(summary) = &entry.summary {
+                    section.push_str(&format!("\n\n{}", summary));
+                }
+                section
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// Whether a body is an RSS or Atom feed.
+///
+/// Feed content types always are; XML, plain-text, and untyped bodies are when
+/// their root element is `<rss>`, `<feed>`, or `<rdf:RDF>`.
+pub fn is_feed(content_type: Option<&str>, bytes: &[u8]) -> bool {
+    let essence = content_type.map(essence);
+    match essence.as_deref() {
+        Some(ct) if FEED_CONTENT_TYPES.contains(&ct) => true,
+        Some(ct) if !ct.contains("xml") && ct != "text/plain" => false,
+        _ => matches!(root_element(bytes), Some("rss" | "feed" | "rdf:RDF")),
+    }
+}
+
+/// Parse an RSS, Atom, or JSON feed, resolving relative links against `base_url`.
+pub fn parse_feed(bytes: &[u8], base_url: &Url) -> Result<Feed, Error> {
+    let parsed = feed_rs::parser::Builder::new()
+        .base_uri(Some(base_url.as_str()))
+        .build()
+        .parse(bytes)
+        .map_err(|e| Error::ExtractFailed(format!("failed to parse feed: {}", e)))?;
+
+    let kind = match parsed.feed_type {
+        feed_rs::model::FeedType::Atom => "atom",
+        feed_rs::model::FeedType::JSON => "json",
+        _ => "rss",
+    };
+    let entries = parsed
+        .entries
+        .into_iter()
+        .map(|entry| FeedEntry {
+            title: entry
+                .title
+                .map(|t| plain_text(&t.content, usize::MAX))
+                .filter(|t| !t.is_empty()),
+            link: page_link(&entry.links),
+            published: entry
+                .published
+                .or(entry.updated)
+                .map(|at| at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+            summary: entry
+                .summary
+                .map(|s| s.content)
+                .or_else(|| entry.content.and_then(|c| c.body))
+                .map(|s| plain_text(&s, MAX_SUMMARY_CHARS))
+                .filter(|s| !s.is_empty()),
+        })
+        .collect();
+
+    Ok(Feed {
+        kind: kind.to_string(),
+        title: parsed
+            .title
+            .map(|t| plain_text(&t.content, usize::MAX))
+            .filter(|t| !t.is_empty()),
+        link: page_link(&parsed.links),
+        entries,
+    })
+}
+
+/// Feeds an HTML page advertises with `<link rel="alternate" type="application/rss+xml">`
+/// (or Atom, or JSON Feed), resolved against `base_url`, without duplicates.
+pub fn discover_feeds(html: &str, base_url: &Url) -> Vec<FeedLink> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse(r#"link[rel~="alternate"][href][type]"#).expect("valid selector");
+
+    let mut feeds: Vec<FeedLink> = Vec::new();
+    for link in document.select(&selector) {
+        let element = link.value();
+        let media_type = essence(element.attr("type").unwrap_or_default());
+        if !ADVERTISED_FEED_TYPES.contains(&media_type.as_str()) {
+            continue;
+        }
+        let Some(href) = element.attr("href").and_then(|href| base_url.join(href.trim()).ok()) else {
+            continue;
+        };
+        if feeds.iter().any(|feed| feed.href == href.as_str()) {
+            continue;
+        }
+        feeds.push(FeedLink {
+            href: href.to_string(),
+            title: element
+                .attr("title")
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string),
+            media_type,
+        });
+        if feeds.len() == MAX_DISCOVERED_FEEDS {
+            break;
+        }
+    }
+    feeds
+}
+
+/// The entry's page: its `alternate` link, else the first link without a rel.
+fn page_link(links: &[feed_rs::model::Link]) -> Option<String> {
+    links
+        .iter()
+        .find(|link| link.rel.as_deref() == Some("alternate"))
+        .or_else(|| links.iter().find(|link| link.rel.is_none()))
+        .map(|link| link.href.clone())
+}
+
+/// Strip markup from feed text, collapse whitespace, and cut it at `max_chars`.
+fn plain_text(text: &str, max_chars: usize) -> String {
+    let text = if text.contains('<') {
+        Html::parse_fragment(text).root_element().text().collect::<String>()
+    } else {
+        text.to_string()
+    };
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match collapsed.char_indices().nth(max_chars) {
+        Some((cut, _)) => format!("{}…", collapsed[..cut].trim_end()),
+        None => collapsed,
+    }
+}
+
+/// Name of the first element in a body, skipping the XML declaration,
+/// processing instructions, comments, and a doctype.
+fn root_element(bytes: &[u8]) -> Option<&str> {
+    let head = &bytes[..bytes.len().min(SNIFF_BYTES)];
+    let head = head.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(head);
+    let mut rest = std::str::from_utf8(head)
+        .or_else(|e| std::str::from_utf8(&head[..e.valid_up_to()]))
+        .ok()?;
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("<?") {
+            rest = &after[after.find("?>")? + 2..];
+        } else if let Some(after) = rest.strip_prefix("<!--") {
+            rest = &after[after.find("-->")? + 3..];
+        } else if let Some(after) = rest.strip_prefix("<!") {
+            rest = &after[after.find('>')? + 1..];
+        } else {
+            let name = rest.strip_prefix('<')?;
+            let end = name
+                .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                .unwrap_or(name.len());
+            return Some(&name[..end]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0">
+  <channel>
+    <title>Example Blog</title>
+    <link>https://example.com/</link>
+    <item>
+      <title>Second post</title>
+      <link>https://example.com/posts/2</link>
+      <pubDate>Tue, 03 Feb 2026 10:00:00 GMT</pubDate>
+      <description>&lt;p&gt;The &lt;b&gt;second&lt;/b&gt;   post.&lt;/p&gt;</description>
+    </item>
+    <item>
+      <title>First post</title>
+      <link>https://example.com/posts/1</link>
+      <pubDate>Mon, 05 Jan 2026 10:00:00 GMT</pubDate>
+      <description>The first post.</description>
+    </item>
+  </channel>
+</rss>"#;
+
+    const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<!-- generated -->
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Example Changelog</title>
+  <link href="https://example.com/changelog" rel="alternate"/>
+  <id>urn:example:changelog</id>
+  <updated>2026-02-01T00:00:00Z</updated>
+  <entry>
+    <title>Version 2.0</title>
+    <link href="/changelog/2.0" rel="alternate"/>
+    <id>urn:example:2.0</id>
+    <updated>2026-02-01T00:00:00Z</updated>
+    <summary>Big release.</summary>
+  </entry>
+</feed>"#;
+
+    fn base() -> Url {
+        Url::parse("https://example.com/feed.xml").unwrap()
+    }
+
+    #[test]
+    fn test_parse_rss() {
+        let feed = parse_feed(RSS.as_bytes(), &base()).unwrap();
+        assert_eq!(feed.kind, "rss");
+        assert_eq!(feed.title.as_deref(), Some("Example Blog"));
+        assert_eq!(feed.entries.len(), 2);
+
+        let entry = &feed.entries[0];
+        assert_eq!(entry.title.as_deref(), Some("Second post"));
+        assert_eq!(entry.link.as_deref(), Some("https://example.com/posts/2"));
+        assert_eq!(entry.published.as_deref(), Some("2026-02-03T10:00:00Z"));
+        assert_eq!(entry.summary.as_deref(), Some("The second post."));
+    }
+
+    #[test]
+    fn test_parse_atom_resolves_links_and_falls_back_to_updated() {
+        let feed = parse_feed(ATOM.as_bytes(), &base()).unwrap();
+        assert_eq!(feed.kind, "atom");
+        assert_eq!(feed.title.as_deref(), Some("Example Changelog"));
+        assert_eq!(feed.link.as_deref(), Some("https://example.com/changelog"));
+
+        let entry = &feed.entries[0];
+        assert_eq!(entry.link.as_deref(), Some("https://example.com/changelog/2.0"));
+        assert_eq!(entry.published.as_deref(), Some("2026-02-01T00:00:00Z"));
+        assert_eq!(entry.summary.as_deref(), Some("Big release."));
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let feed = parse_feed(RSS.as_bytes(), &base()).unwrap();
+        let markdown = feed.to_markdown();
+        assert!(markdown.starts_with(
+            "## [Second post](https://example.com/posts/2)\n\n*2026-02-03T10:00:00Z*\n\nThe second post."
+        ));
+        assert!(markdown.contains("\n\n## [First post](https://example.com/posts/1)"));
+    }
+
+    #[test]
+    fn test_is_feed() {
+        assert!(is_feed(Some("application/rss+xml; charset=utf-8"), b""));
+        assert!(is_feed(Some("application/atom+xml"), b""));
+        assert!(is_feed(Some("text/xml"), RSS.as_bytes()));
+        assert!(is_feed(None, ATOM.as_bytes()));
+        assert!(is_feed(
+            Some("application/xml"),
+            b"<rdf:RDF xmlns:rdf=\"x\"><channel/></rdf:RDF>"
+        ));
+
+        assert!(!is_feed(Some("text/html"), RSS.as_bytes()));
+        assert!(!is_feed(Some("application/xml"), b"<urlset><url/></urlset>"));
+        assert!(!is_feed(None, b"<!DOCTYPE html><html></html>"));
+    }
+
+    #[test]
+    fn test_discover_feeds() {
+        let html = r#"<html><head>
+            <link rel="alternate" type="application/rss+xml" title="Posts" href="/feed.xml">
+            <link rel="alternate" type="application/atom+xml" href="https://example.com/atom.xml">
+            <link rel="alternate" type="application/rss+xml" href="/feed.xml">
+            <link rel="alternate" hreflang="de" href="/de/">
+            <link rel="stylesheet" type="text/css" href="/style.css">
+        </head><body></body></html>"#;
+        let feeds = discover_feeds(html, &Url::parse("https://example.com/blog/").unwrap());
+        assert_eq!(
+            feeds,
+            vec![
+                FeedLink {
+                    href: "https://example.com/feed.xml".into(),
+                    title: Some("Posts".into()),
+                    media_type: "application/rss+xml".into(),
+                },
+                FeedLink {
+                    href: "https://example.com/atom.xml".into(),
+                    title: None,
+                    media_type: "application/atom+xml".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plain_text_cuts_long_summaries() {
+        let long = "word ".repeat(200);
+        let cut = plain_text(&long, 12);
+        assert_eq!(cut, "word word wo…");
+        assert_eq!(plain_text("<p>a\n\n<i>b</i></p>", 100), "a b");
+    }
+}
@@ -0,0 +1,241 @@
+//! Process-wide ceiling on response bytes held by in-flight fetches.
+//!
+//! Each fetch reserves `min(content_length, max_bytes)` before reading its
+//! body, grows the reservation to `max_bytes` before inflating a body that is
+//! still compressed, and hands it to the `FetchResponse`, so it is released
+//! once the response (and anything extracted from it) is dropped, or as soon
+//! as the fetch fails. When the budget is spent, fetches queue for a
+//! reservation instead of all buffering bodies at once; one that can't get a
+//! reservation within its timeout fails with `FetchTimeout`.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use thndrs_core::Error;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Default budget: 64MB.
+pub const DEFAULT_MAX_INFLIGHT_BYTES: usize = 64 * 1024 * 1024;
+
+/// Shared byte budget for response bodies.
+///
+/// Cloning is cheap and clones share the budget. A capacity of 0 disables it.
+#[derive(Debug, Clone)]
+pub struct ByteBudget {
+    inner: Option<Arc<BudgetInner>>,
+}
+
+#[derive(Debug)]
+struct BudgetInner {
+    semaphore: Arc<Semaphore>,
+    capacity: usize,
+    waiting: AtomicUsize,
+}
+
+/// Bytes held for one fetch; released on drop.
+#[derive(Debug)]
+pub struct BudgetReservation {
+    _permit: Option<OwnedSemaphorePermit>,
+    /// Bytes reserved for this fetch.
+    pub bytes: usize,
+    /// Time spent queued for the reservation.
+    pub waited: Duration,
+}
+
+impl Default for ByteBudget {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_INFLIGHT_BYTES)
+    }
+}
+
+impl ByteBudget {
+    /// Create a budget of `capacity` bytes; 0 means unlimited.
+    ///
+    /// Capacities above `u32::MAX` bytes are clamped to it.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.min(u32::MAX as usize);
+        let inner = (capacity > 0).then(|| {
+            Arc::new(BudgetInner {
+                semaphore: Arc::new(Semaphore::new(capacity)),
+                capacity,
+                waiting: AtomicUsize::new(0),
+            })
+        });
+        Self { inner }
+    }
+
+    /// A budget that never queues.
+    pub fn unlimited() -> Self {
+        Self { inner: None }
+    }
+
+    /// Reserve `bytes`, waiting at most `timeout` for earlier fetches to release theirs.
+    ///
+    /// A request larger than the whole budget reserves all of it, so it runs
+    /// alone rather than never.
+    pub async fn reserve(&self, bytes: usize, timeout: Duration) -> Result<BudgetReservation, Error> {
+        let Some(inner) = &self.inner else {
+            return Ok(BudgetReservation { _permit: None, bytes, waited: Duration::ZERO });
+        };
+
+        let bytes = bytes.min(inner.capacity);
+        let start = Instant::now();
+        let permit = match inner.semaphore.clone().try_acquire_many_owned(bytes as u32) {
+            Ok(permit) => permit,
+            Err(_) => {
+                tracing::debug!(
+                    "fetch memory budget spent ({} of {} bytes reserved), queueing {} bytes",
+                    self.reserved(),
+                    inner.capacity,
+                    bytes
+                );
+                inner.waiting.fetch_add(1, Ordering::Relaxed);
+                let acquired =
+                    tokio::time::timeout(timeout, inner.semaphore.clone().acquire_many_owned(bytes as u32)).await;
+                inner.waiting.fetch_sub(1, Ordering::Relaxed);
+                match acquired {
+                    Ok(Ok(permit)) => permit,
+                    Ok(Err(_)) => return Err(Error::FetchTimeout("fetch memory budget closed".into())),
+                    Err(_) => {
+                        return Err(Error::FetchTimeout(format!(
+                            "waited {}ms for {} bytes of fetch memory budget ({} of {} reserved)",
+                            timeout.as_millis(),
+                            bytes,
+                            self.reserved(),
+                            inner.capacity
+                        )));
+                    }
+                }
+            }
+        };
+
+        Ok(BudgetReservation { _permit: Some(permit), bytes, waited: start.elapsed() })
+    }
+
+    /// Grow `reservation` to `bytes`, waiting at most `timeout`.
+    ///
+    /// The held bytes are given back while queued, so two fetches growing at
+    /// once can't wait on each other. The returned reservation's `waited`
+    /// includes the original wait.
+    pub async fn grow(
+        &self, reservation: BudgetReservation, bytes: usize, timeout: Duration,
+    ) -> Result<BudgetReservation, Error> {
+        let capped = self.capacity().map_or(bytes, |capacity| bytes.min(capacity));
+        if reservation.bytes >= capped {
+            return Ok(reservation);
+        }
+        let waited = reservation.waited;
+        drop(reservation);
+        let mut grown = self.reserve(bytes, timeout).await?;
+        grown.waited += waited;
+        Ok(grown)
+    }
+
+    /// Total budget in bytes; `None` when unlimited.
+    pub fn capacity(&self) -> Option<usize> {
+        self.inner.as_ref().map(|inner| inner.capacity)
+    }
+
+    /// Bytes currently reserved by in-flight fetches (debug accessor).
+    pub fn reserved(&self) -> usize {
+        self.inner
+            .as_ref()
+            .map_or(0, |inner| inner.capacity - inner.semaphore.available_permits())
+    }
+
+    /// Fetches currently queued for a reservation (debug accessor).
+    pub fn waiting(&self) -> usize {
+        self.inner
+            .as_ref()
+            .map_or(0, |inner| inner.waiting.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reserve_and_release() {
+        let budget = ByteBudget::new(100);
+        let a = budget.reserve(60, Duration::from_secs(1)).await.unwrap();
+        assert_eq!(budget.reserved(), 60);
+        let b = budget.reserve(40, Duration::from_secs(1)).await.unwrap();
+        assert_eq!(budget.reserved(), 100);
+        drop(a);
+        assert_eq!(budget.reserved(), 40);
+        drop(b);
+        assert_eq!(budget.reserved(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_queues_until_released() {
+        let budget = ByteBudget::new(100);
+        let held = budget.reserve(80, Duration::from_secs(1)).await.unwrap();
+
+        let waiter = {
+            let budget = budget.clone();
+            tokio::spawn(async move { budget.reserve(50, Duration::from_secs(5)).await })
+        };
+        while budget.waiting() == 0 {
+            tokio::task::yield_now().await;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        drop(held);
+
+        let reservation = waiter.await.unwrap().unwrap();
+        assert_eq!(reservation.bytes, 50);
+        assert!(reservation.waited >= Duration::from_millis(50));
+        assert_eq!(budget.waiting(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_times_out_when_spent() {
+        let budget = ByteBudget::new(100);
+        let _held = budget.reserve(100, Duration::from_secs(1)).await.unwrap();
+
+        let err = budget.reserve(1, Duration::from_millis(20)).await.unwrap_err();
+        assert!(matches!(err, Error::FetchTimeout(_)), "{err}");
+        assert_eq!(budget.waiting(), 0);
+        assert_eq!(budget.reserved(), 100);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_request_takes_whole_budget() {
+        let budget = ByteBudget::new(100);
+        let reservation = budget.reserve(500, Duration::from_secs(1)).await.unwrap();
+        assert_eq!(reservation.bytes, 100);
+        assert_eq!(budget.reserved(), 100);
+    }
+
+    #[tokio::test]
+    async fn test_grow_reserves_the_difference_or_requeues() {
+        let budget = ByteBudget::new(100);
+        let reservation = budget.reserve(10, Duration::from_secs(1)).await.unwrap();
+        let reservation = budget.grow(reservation, 60, Duration::from_secs(1)).await.unwrap();
+        assert_eq!(reservation.bytes, 60);
+        assert_eq!(budget.reserved(), 60);
+
+        let reservation = budget.grow(reservation, 30, Duration::from_secs(1)).await.unwrap();
+        assert_eq!(reservation.bytes, 60, "never shrinks");
+
+        let other = budget.reserve(40, Duration::from_secs(1)).await.unwrap();
+        let err = budget
+            .grow(reservation, 100, Duration::from_millis(20))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::FetchTimeout(_)), "{err}");
+        assert_eq!(budget.reserved(), 40, "the held bytes were given back");
+        drop(other);
+    }
+
+    #[tokio::test]
+    async fn test_unlimited_never_queues() {
+        let budget = ByteBudget::new(0);
+        assert_eq!(budget.capacity(), None);
+        let _a = budget.reserve(usize::MAX, Duration::ZERO).await.unwrap();
+        let _b = budget.reserve(usize::MAX, Duration::ZERO).await.unwrap();
+        assert_eq!(budget.reserved(), 0);
+    }
+}
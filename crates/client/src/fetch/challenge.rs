@@ -0,0 +1,227 @@
+//! Detection of bot-protection challenge pages.
+//!
+//! Cloudflare, Akamai, DataDome and similar services answer suspected bots
+//! with an interstitial ("Just a moment...", "Checking your browser") instead
+//! of the page, often with status 200. Extracted and cached, that interstitial
+//! would stand in for the real content. [`detect_challenge`] classifies a
+//! response from its status, headers and body so callers can refuse it.
+
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, SERVER, SET_COOKIE};
+use std::fmt;
+
+/// Only this much of the body is scanned for markers.
+const SCAN_BYTES: usize = 64 * 1024;
+
+/// Bodies larger than this are real pages, even if they mention a marker
+/// (an article about Cloudflare, or a page that loads a detection script).
+pub(crate) const MAX_CHALLENGE_BYTES: usize = 48 * 1024;
+
+/// Bodies smaller than this are suspicious when a challenge cookie or an
+/// edge error status comes with them.
+const SMALL_BODY_BYTES: usize = 4 * 1024;
+
+/// Body markers specific to challenge pages, as `(provider, marker)`; matched
+/// case-insensitively.
+const BODY_MARKERS: &[(&str, &str)] = &[
+    ("cloudflare", "<title>just a moment...</title>"),
+    ("cloudflare", "window._cf_chl_opt"),
+    ("cloudflare", "cf-browser-verification"),
+    ("cloudflare", "checking your browser before accessing"),
+    ("akamai", "/_sec/cp_challenge/"),
+    ("akamai", "errors.edgesuite.net"),
+    ("akamai", "errors&#46;edgesuite&#46;net"),
+    ("datadome", "captcha-delivery.com"),
+    ("perimeterx", "px-captcha"),
+    ("imperva", "_incapsula_resource"),
+    ("sucuri", "sucuri_cloudproxy_js"),
+    ("aws-waf", "awswafintegration"),
+];
+
+/// Cookie name prefixes set by challenge flows, as `(provider, prefix)`.
+const CHALLENGE_COOKIES: &[(&str, &str)] = &[
+    ("cloudflare", "cf_chl"),
+    ("datadome", "datadome"),
+    ("imperva", "reese84"),
+    ("sucuri", "sucuri_cloudproxy"),
+    ("aws-waf", "aws-waf-token"),
+];
+
+/// `Server` header values of edge networks that serve their own block pages.
+const EDGE_SERVERS: &[(&str, &str)] = &[("cloudflare", "cloudflare"), ("akamai", "akamaighost")];
+
+/// A response identified as a bot-protection challenge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Challenge {
+    /// Protection service, e.g. `cloudflare`.
+    pub provider: &'static str,
+    /// What gave it away, e.g. a body marker or header.
+    pub signal: &'static str,
+}
+
+impl fmt::Display for Challenge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} challenge page ({})", self.provider, self.signal)
+    }
+}
+
+/// Classify a response as a bot-protection challenge, if it is one.
+///
+/// Checked in order: Cloudflare's `cf-mitigated: challenge` header; a known
+/// challenge marker in a body of at most 48KB; a body under 4KB that sets a
+/// challenge cookie; and a body under 4KB served with 403/429/503 by an edge
+/// network's own server.
+pub fn detect_challenge(status: StatusCode, headers: &HeaderMap, body: &[u8]) -> Option<Challenge> {
+    if headers
+        .get("cf-mitigated")
+        .is_some_and(|v| v.as_bytes().eq_ignore_ascii_case(b"challenge"))
+    {
+        return Some(Challenge { provider: "cloudflare", signal: "cf-mitigated header" });
+    }
+
+    if body.len() <= MAX_CHALLENGE_BYTES {
+        let scanned = String::from_utf8_lossy(&body[..body.len().min(SCAN_BYTES)]).to_ascii_lowercase();
+        if let Some(&(provider, marker)) = BODY_MARKERS.iter().find(|(_, marker)| scanned.contains(marker)) {
+            return Some(Challenge { provider, signal: marker });
+        }
+    }
+
+    if body.len() >= SMALL_BODY_BYTES {
+        return None;
+    }
+
+    let cookie = headers
+        .get_all(SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .find_map(|v| {
+            let name = v.split('=').next().unwrap_or("").trim().to_ascii_lowercase();
+            CHALLENGE_COOKIES.iter().find(|(_, prefix)| name.starts_with(prefix))
+        });
+    if let Some(&(provider, _)) = cookie {
+        return Some(Challenge { provider, signal: "challenge cookie on a small body" });
+    }
+
+    let edge_error = matches!(
+        status,
+        StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+    );
+    let server = headers
+        .get(SERVER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    EDGE_SERVERS
+        .iter()
+        .find(|(_, name)| edge_error && server.contains(name))
+        .map(|&(provider, _)| Challenge { provider, signal: "edge block status on a small body" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    const CLOUDFLARE: &str = r#"<!DOCTYPE html><html lang="en-US"><head><title>Just a moment...</title>
+<meta http-equiv="refresh" content="390"></head><body><div class="main-wrapper" role="main">
+<noscript><div class="h2"><span id="challenge-error-text">Enable JavaScript and cookies to continue</span></div></noscript>
+</div><script>(function(){window._cf_chl_opt={cvId: '3',cZone: "example.com",cType: 'managed'};
+var cpo=document.createElement('script');cpo.src='/cdn-cgi/challenge-platform/h/g/orchestrate/chl_page/v1';
+document.getElementsByTagName('head')[0].appendChild(cpo);}());</script></body></html>"#;
+
+    const CLOUDFLARE_LEGACY: &str = r#"<html><head><title>Attention Required</title></head><body>
+<div class="cf-browser-verification cf-im-under-attack"><h1>Checking your browser before accessing example.com.</h1>
+<p>This process is automatic. Your browser will redirect to your requested content shortly.</p></div></body></html>"#;
+
+    const AKAMAI: &str = r#"<HTML><HEAD><TITLE>Access Denied</TITLE></HEAD><BODY>
+<H1>Access Denied</H1>You don't have permission to access "http://www.example.com/" on this server.<P>
+Reference&#32;&#35;18&#46;2f1e3e17&#46;1700000000&#46;1a2b3c4d
+<P>https&#58;&#47;&#47;errors&#46;edgesuite&#46;net&#47;18&#46;2f1e3e17
+</BODY></HTML>"#;
+
+    const AKAMAI_BOT_MANAGER: &str = r#"<html><head><title>Please wait</title></head><body>
+<div id="sec-if-cpt-container"><script src="/_sec/cp_challenge/sec-cpt-4-1.js" async></script></div></body></html>"#;
+
+    const DATADOME: &str = r#"<html><head><title>example.com</title></head><body style="margin:0">
+<script data-cfasync="false">var dd={'rt':'c','cid':'AHrlqAAAAAMA','hsh':'2211F522B61E269B','t':'fe','s':17434,'host':'geo.captcha-delivery.com'}</script>
+<script data-cfasync="false" src="https://ct.captcha-delivery.com/c.js"></script></body></html>"#;
+
+    const PERIMETERX: &str = r#"<html><head><title>Access to this page has been denied.</title></head>
+<body><div id="px-captcha"></div><script>window._pxAppId = 'PXabcdef12';</script>
+<script src="/abcdef12/captcha/captcha.js?a=c&m=0"></script></body></html>"#;
+
+    const IMPERVA: &str = r#"<html style="height:100%"><head><META NAME="ROBOTS" CONTENT="NOINDEX, NOFOLLOW"></head>
+<body style="margin:0px;height:100%"><iframe id="main-iframe" src="/_Incapsula_Resource?CWUDNSAI=24&xinfo=8-1234" frameborder=0 width="100%" height="100%"></iframe></body></html>"#;
+
+    const ARTICLE: &str = r#"<html><head><title>How we ship</title></head><body><article>
+<h1>How we ship</h1><p>Deploys go out twice a day behind our CDN.</p></article>
+<script src="/cdn-cgi/challenge-platform/scripts/jsd/main.js"></script></body></html>"#;
+
+    fn detect(status: u16, headers: &[(&'static str, &str)], body: &str) -> Option<Challenge> {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.append(*name, HeaderValue::from_str(value).unwrap());
+        }
+        detect_challenge(StatusCode::from_u16(status).unwrap(), &map, body.as_bytes())
+    }
+
+    fn provider(status: u16, headers: &[(&'static str, &str)], body: &str) -> Option<&'static str> {
+        detect(status, headers, body).map(|c| c.provider)
+    }
+
+    #[test]
+    fn test_detects_provider_pages() {
+        assert_eq!(provider(200, &[], CLOUDFLARE), Some("cloudflare"));
+        assert_eq!(provider(503, &[], CLOUDFLARE_LEGACY), Some("cloudflare"));
+        assert_eq!(provider(403, &[], AKAMAI), Some("akamai"));
+        assert_eq!(provider(200, &[], AKAMAI_BOT_MANAGER), Some("akamai"));
+        assert_eq!(provider(403, &[], DATADOME), Some("datadome"));
+        assert_eq!(provider(200, &[], PERIMETERX), Some("perimeterx"));
+        assert_eq!(provider(200, &[], IMPERVA), Some("imperva"));
+    }
+
+    #[test]
+    fn test_cf_mitigated_header() {
+        let challenge = detect(200, &[("cf-mitigated", "challenge")], "<html></html>").unwrap();
+        assert_eq!(challenge.provider, "cloudflare");
+        assert_eq!(challenge.to_string(), "cloudflare challenge page (cf-mitigated header)");
+    }
+
+    #[test]
+    fn test_ordinary_pages_pass() {
+        assert_eq!(detect(200, &[("server", "cloudflare")], ARTICLE), None);
+        assert_eq!(detect(200, &[("set-cookie", "__cf_bm=abc; path=/")], ARTICLE), None);
+        assert_eq!(detect(404, &[("server", "cloudflare")], "<html>Not found</html>"), None);
+    }
+
+    #[test]
+    fn test_large_body_mentioning_marker_passes() {
+        let body = format!(
+            "<html><body><p>{}</p><code>window._cf_chl_opt</code></body></html>",
+            "text ".repeat(20_000)
+        );
+        assert_eq!(detect(200, &[], &body), None);
+    }
+
+    #[test]
+    fn test_small_body_with_challenge_cookie() {
+        let cookie = [("set-cookie", "datadome=abc123; Max-Age=31536000; Path=/")];
+        assert_eq!(provider(200, &cookie, "<html><body></body></html>"), Some("datadome"));
+
+        let big = format!("<html><body>{}</body></html>", "<p>content</p>".repeat(500));
+        assert_eq!(detect(200, &cookie, &big), None);
+    }
+
+    #[test]
+    fn test_small_edge_block_page() {
+        assert_eq!(
+            provider(403, &[("server", "AkamaiGHost")], "<html>Denied</html>"),
+            Some("akamai")
+        );
+        assert_eq!(
+            provider(429, &[("server", "cloudflare")], "<html>Slow down</html>"),
+            Some("cloudflare")
+        );
+        assert_eq!(detect(200, &[("server", "cloudflare")], "<html>ok</html>"), None);
+    }
+}
@@ -0,0 +1,361 @@
+//! Per-host circuit breaker for the fetch pipeline.
+//!
+//! After `failure_threshold` consecutive failures against a host within `window`,
+//! the circuit opens and fetches to that host fail fast for `cooldown`. Once the
+//! cool-down elapses a single probe request is let through (half-open): success
+//! closes the circuit, failure re-opens it for another cool-down. The probe is
+//! held by a [`CircuitPermit`]; dropping it without recording an outcome (an
+//! early return, a cancelled fetch) releases the probe for the next request.
+//!
+//! The breaker tracks only failures we observed; back-offs origins ask for,
+//! pacing, and the byte budget live in [`HostState`](super::HostState).
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Error returned when a host's circuit is open.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("host {host} circuit open, retry in {}s", retry_in.as_secs().max(1))]
+pub struct CircuitOpen {
+    pub host: String,
+    pub retry_in: Duration,
+}
+
+/// Observable state of a host's circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests flow normally; `failures` consecutive failures recorded so far.
+    Closed { failures: u32 },
+    /// Requests fail fast until the cool-down elapses.
+    Open { retry_in: Duration },
+    /// Cool-down elapsed; the next request is a probe.
+    HalfOpen,
+}
+
+#[derive(Debug, Default)]
+struct HostCircuit {
+    failures: u32,
+    first_failure: Option<Instant>,
+    opened_at: Option<Instant>,
+    probing: bool,
+}
+
+/// Admission for one request to a host, returned by [`CircuitBreaker::check`].
+///
+/// Record the outcome with [`record_success`](Self::record_success) or
+/// [`record_failure`](Self::record_failure). A half-open probe dropped without
+/// an outcome is released, so the next check is admitted as the probe instead
+/// of the circuit staying half-open forever.
+#[derive(Debug)]
+#[must_use = "dropping a permit releases a half-open probe without an outcome"]
+pub struct CircuitPermit {
+    breaker: CircuitBreaker,
+    host: String,
+    probe: bool,
+}
+
+impl CircuitPermit {
+    /// Whether this permit is the half-open probe for its host.
+    pub fn is_probe(&self) -> bool {
+        self.probe
+    }
+
+    /// Record a successful request, closing the host's circuit.
+    pub fn record_success(mut self) {
+        self.probe = false;
+        self.breaker.record_success(&self.host);
+    }
+
+    /// Record a failed request against the host.
+    pub fn record_failure(mut self) {
+        self.probe = false;
+        self.breaker.record_failure(&self.host);
+    }
+}
+
+impl Drop for CircuitPermit {
+    fn drop(&mut self) {
+        if self.probe {
+            self.breaker.release_probe(&self.host);
+        }
+    }
+}
+
+/// Shared, in-memory circuit breaker keyed by host.
+///
+/// Cloning is cheap and clones share state, so one breaker can back many
+/// short-lived `FetchClient`s.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    hosts: Arc<Mutex<HashMap<String, HostCircuit>>>,
+    failure_threshold: u32,
+    window: Duration,
+    cooldown: Duration,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(3, Duration::from_secs(60), Duration::from_secs(30))
+    }
+}
+
+impl CircuitBreaker {
+    /// Create a breaker that opens after `failure_threshold` failures within `window`.
+    pub fn new(failure_threshold: u32, window: Duration, cooldown: Duration) -> Self {
+        Self {
+            hosts: Arc::new(Mutex::new(HashMap::new())),
+            failure_threshold: failure_threshold.max(1),
+            window,
+            cooldown,
+        }
+    }
+
+    /// Check whether a request to `host` may proceed.
+    ///
+    /// Transitions an open circuit to half-open once the cool-down has elapsed,
+    /// admitting exactly one probe; the returned permit holds it until an
+    /// outcome is recorded or the permit is dropped.
+    pub fn check(&self, host: &str) -> Result<CircuitPermit, CircuitOpen> {
+        self.check_at(host, Instant::now())
+    }
+
+    /// Record a successful request, closing the host's circuit.
+    pub fn record_success(&self, host: &str) {
+        self.lock().remove(host);
+    }
+
+    /// Record a failed request against `host`.
+    pub fn record_failure(&self, host: &str) {
+        self.record_failure_at(host, Instant::now());
+    }
+
+    /// Current state of the circuit for `host` (debug accessor).
+    pub fn state(&self, host: &str) -> CircuitState {
+        self.state_at(host, Instant::now())
+    }
+
+    /// Close the circuit for a single host.
+    pub fn reset_host(&self, host: &str) {
+        self.lock().remove(host);
+    }
+
+    /// Close every circuit.
+    pub fn reset(&self) {
+        self.lock().clear();
+    }
+
+    fn check_at(&self, host: &str, now: Instant) -> Result<CircuitPermit, CircuitOpen> {
+        let permit = |probe| CircuitPermit { breaker: self.clone(), host: host.to_string(), probe };
+        let mut hosts = self.lock();
+        let Some(circuit) = hosts.get_mut(host) else {
+            return Ok(permit(false));
+        };
+        let Some(opened_at) = circuit.opened_at else {
+            return Ok(permit(false));
+        };
+
+        let elapsed = now.saturating_duration_since(opened_at);
+        if elapsed < self.cooldown {
+            return Err(CircuitOpen { host: host.to_string(), retry_in: self.cooldown - elapsed });
+        }
+
+        if circuit.probing {
+            return Err(CircuitOpen { host: host.to_string(), retry_in: Duration::ZERO });
+        }
+
+        tracing::debug!("circuit for {} half-open, sending probe", host);
+        circuit.probing = true;
+        Ok(permit(true))
+    }
+
+    /// Let the next check through as the probe after one ended without an outcome.
+    fn release_probe(&self, host: &str) {
+        if let Some(circuit) = self.lock().get_mut(host)
+            && circuit.probing
+        {
+            tracing::debug!("probe to {} ended without an outcome, releasing it", host);
+            circuit.probing = false;
+        }
+    }
+
+    fn record_failure_at(&self, host: &str, now: Instant) {
+        let mut hosts = self.lock();
+        let circuit = hosts.entry(host.to_string()).or_default();
+
+        if circuit.probing {
+            tracing::debug!("probe to {} failed, re-opening circuit", host);
+            circuit.probing = false;
+            circuit.opened_at = Some(now);
+            return;
+        }
+
+        let in_window = circuit
+            .first_failure
+            .is_some_and(|first| now.saturating_duration_since(first) <= self.window);
+        if !in_window {
+            circuit.failures = 0;
+            circuit.first_failure = Some(now);
+        }
+
+        circuit.failures += 1;
+        if circuit.failures >= self.failure_threshold && circuit.opened_at.is_none() {
+            tracing::warn!(
+                "{} consecutive failures for {}, opening circuit",
+                circuit.failures,
+                host
+            );
+            circuit.opened_at = Some(now);
+        }
+    }
+
+    fn state_at(&self, host: &str, now: Instant) -> CircuitState {
+        let hosts = self.lock();
+        let Some(circuit) = hosts.get(host) else {
+            return CircuitState::Closed { failures: 0 };
+        };
+
+        match circuit.opened_at {
+            None => CircuitState::Closed { failures: circuit.failures },
+            Some(_) if circuit.probing => CircuitState::HalfOpen,
+            Some(opened_at) => {
+                let elapsed = now.saturating_duration_since(opened_at);
+                if elapsed < self.cooldown {
+                    CircuitState::Open { retry_in: self.cooldown - elapsed }
+                } else {
+                    CircuitState::HalfOpen
+                }
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, HostCircuit>> {
+        self.hosts.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(3, Duration::from_secs(60), Duration::from_secs(30))
+    }
+
+    #[test]
+    fn test_opens_after_threshold() {
+        let cb = breaker();
+        let t0 = Instant::now();
+
+        cb.record_failure_at("example.com", t0);
+        cb.record_failure_at("example.com", t0);
+        assert!(cb.check_at("example.com", t0).is_ok());
+        assert_eq!(cb.state_at("example.com", t0), CircuitState::Closed { failures: 2 });
+
+        cb.record_failure_at("example.com", t0);
+        let err = cb.check_at("example.com", t0 + Duration::from_secs(18)).unwrap_err();
+        assert_eq!(err.retry_in, Duration::from_secs(12));
+        assert_eq!(err.to_string(), "host example.com circuit open, retry in 12s");
+
+        assert!(cb.check_at("other.com", t0).is_ok());
+    }
+
+    #[test]
+    fn test_failures_outside_window_do_not_accumulate() {
+        let cb = breaker();
+        let t0 = Instant::now();
+
+        cb.record_failure_at("example.com", t0);
+        cb.record_failure_at("example.com", t0 + Duration::from_secs(30));
+        cb.record_failure_at("example.com", t0 + Duration::from_secs(61));
+
+        assert!(cb.check_at("example.com", t0 + Duration::from_secs(61)).is_ok());
+    }
+
+    #[test]
+    fn test_success_resets_failures() {
+        let cb = breaker();
+        let t0 = Instant::now();
+
+        cb.record_failure_at("example.com", t0);
+        cb.record_failure_at("example.com", t0);
+        cb.record_success("example.com");
+        cb.record_failure_at("example.com", t0);
+
+        assert_eq!(cb.state_at("example.com", t0), CircuitState::Closed { failures: 1 });
+    }
+
+    #[test]
+    fn test_half_open_single_probe_then_recover() {
+        let cb = breaker();
+        let t0 = Instant::now();
+        for _ in 0..3 {
+            cb.record_failure_at("example.com", t0);
+        }
+
+        let after = t0 + Duration::from_secs(31);
+        assert_eq!(cb.state_at("example.com", after), CircuitState::HalfOpen);
+        let probe = cb.check_at("example.com", after).unwrap();
+        assert!(probe.is_probe());
+        assert!(cb.check_at("example.com", after).is_err());
+
+        probe.record_success();
+        assert_eq!(cb.state_at("example.com", after), CircuitState::Closed { failures: 0 });
+        assert!(cb.check_at("example.com", after).is_ok());
+    }
+
+    #[test]
+    fn test_failed_probe_reopens() {
+        let cb = breaker();
+        let t0 = Instant::now();
+        for _ in 0..3 {
+            cb.record_failure_at("example.com", t0);
+        }
+
+        let probe_at = t0 + Duration::from_secs(31);
+        let _probe = cb.check_at("example.com", probe_at).unwrap();
+        cb.record_failure_at("example.com", probe_at);
+
+        assert!(matches!(
+            cb.state_at("example.com", probe_at),
+            CircuitState::Open { .. }
+        ));
+        assert!(cb.check_at("example.com", probe_at + Duration::from_secs(29)).is_err());
+        assert!(cb.check_at("example.com", probe_at + Duration::from_secs(30)).is_ok());
+    }
+
+    #[test]
+    fn test_dropped_probe_is_released() {
+        let cb = breaker();
+        let t0 = Instant::now();
+        for _ in 0..3 {
+            cb.record_failure_at("example.com", t0);
+        }
+
+        let after = t0 + Duration::from_secs(31);
+        let probe = cb.check_at("example.com", after).unwrap();
+        assert!(cb.check_at("example.com", after).is_err());
+        drop(probe);
+
+        let probe = cb.check_at("example.com", after).unwrap();
+        assert!(probe.is_probe());
+        assert!(cb.check_at("example.com", after).is_err());
+    }
+
+    #[test]
+    fn test_reset() {
+        let cb = breaker();
+        let t0 = Instant::now();
+        for _ in 0..3 {
+            cb.record_failure_at("a.com", t0);
+            cb.record_failure_at("b.com", t0);
+        }
+
+        cb.reset_host("a.com");
+        assert!(cb.check_at("a.com", t0).is_ok());
+        assert!(cb.check_at("b.com", t0).is_err());
+
+        cb.reset();
+        assert!(cb.check_at("b.com", t0).is_ok());
+    }
+}
@@ -0,0 +1,110 @@
+//! Content-Type allowlist checked once response headers arrive.
+//!
+//! Bodies of disallowed types (video, archives, images) are never read, so a
+//! multi-megabyte download that extraction cannot use is aborted up front.
+
+/// Content types fetched by default.
+pub const DEFAULT_ALLOWED_CONTENT_TYPES: &[&str] = &[
+    "text/html",
+    "application/xhtml+xml",
+    "application/xml",
+    "text/xml",
+    "text/plain",
+    "text/markdown",
+    "application/json",
+    "application/rss+xml",
+    "application/atom+xml",
+    #[cfg(feature = "pdf")]
+    "application/pdf",
+];
+
+/// Media type of a Content-Type header value, lowercased and without parameters.
+///
+/// `text/HTML; charset=utf-8` becomes `text/html`.
+pub fn essence(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// `charset` parameter of a Content-Type header value, without quotes.
+///
+/// `text/html; Charset="Shift_JIS"` gives `Shift_JIS`.
+pub fn charset(content_type: &str) -> Option<&str> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches(['"', '\'']))
+            .filter(|value| !value.is_empty())
+    })
+}
+
+/// Check a Content-Type header value against an allowlist.
+///
+/// Entries match the media type exactly, or every subtype when written as
+/// `type/*`. An empty allowlist allows everything.
+pub fn is_allowed_content_type(content_type: &str, allowed: &[String]) -> bool {
+    if allowed.is_empty() {
+        return true;
+    }
+
+    let essence = essence(content_type);
+    allowed.iter().any(|entry| {
+        let entry = entry.trim().to_ascii_lowercase();
+        match entry.strip_suffix("/*") {
+            Some(main_type) => essence.split('/').next() == Some(main_type),
+            None => essence == entry,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn defaults() -> Vec<String> {
+        DEFAULT_ALLOWED_CONTENT_TYPES.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_essence() {
+        assert_eq!(essence("text/HTML; charset=utf-8"), "text/html");
+        assert_eq!(essence("  application/json "), "application/json");
+    }
+
+    #[test]
+    fn test_charset() {
+        assert_eq!(charset("text/html; Charset=\"Shift_JIS\""), Some("Shift_JIS"));
+        assert_eq!(charset("text/html;q=1; charset=windows-1251 "), Some("windows-1251"));
+        assert_eq!(charset("text/html"), None);
+        assert_eq!(charset("text/html; charset="), None);
+    }
+
+    #[test]
+    fn test_default_allowlist() {
+        let allowed = defaults();
+        assert!(is_allowed_content_type("text/html; charset=utf-8", &allowed));
+        assert!(is_allowed_content_type("application/xhtml+xml", &allowed));
+        assert!(is_allowed_content_type("text/markdown", &allowed));
+        assert!(is_allowed_content_type("application/rss+xml", &allowed));
+        assert!(!is_allowed_content_type("video/mp4", &allowed));
+        assert!(!is_allowed_content_type("application/zip", &allowed));
+        assert_eq!(
+            is_allowed_content_type("application/pdf", &allowed),
+            cfg!(feature = "pdf")
+        );
+    }
+
+    #[test]
+    fn test_wildcard_and_empty_allowlist() {
+        let allowed = vec!["text/*".to_string()];
+        assert!(is_allowed_content_type("text/csv", &allowed));
+        assert!(!is_allowed_content_type("application/json", &allowed));
+
+        assert!(is_allowed_content_type("video/mp4", &[]));
+    }
+}
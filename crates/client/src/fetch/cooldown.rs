@@ -0,0 +1,200 @@
+//! Per-host cool-downs requested by origins.
+//!
+//! When an origin answers 429 or 503 with `Retry-After`, the host is recorded
+//! as cooling down until that time. Later fetches to the host either wait out a
+//! short cool-down or fail fast with the remaining time, instead of provoking
+//! another rate-limit response. State is in-memory and capped at `max_hosts`
+//! entries; expired entries go first, then the ones closest to expiry.
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Longest cool-down honored from a single `Retry-After`.
+pub const MAX_COOLDOWN: Duration = Duration::from_secs(3600);
+
+/// Error returned when a host is cooling down.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("host {host} cooling down, retry after {}s", retry_in.as_secs().max(1))]
+pub struct HostCooling {
+    pub host: String,
+    pub retry_in: Duration,
+}
+
+/// Shared, in-memory cool-down table keyed by host.
+///
+/// Cloning is cheap and clones share state.
+#[derive(Debug, Clone)]
+pub struct HostCooldowns {
+    hosts: Arc<Mutex<HashMap<String, Instant>>>,
+    max_hosts: usize,
+}
+
+impl Default for HostCooldowns {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+impl HostCooldowns {
+    /// Create a table tracking at most `max_hosts` hosts.
+    pub fn new(max_hosts: usize) -> Self {
+        Self { hosts: Arc::new(Mutex::new(HashMap::new())), max_hosts: max_hosts.max(1) }
+    }
+
+    /// Record that `host` asked us to back off for `retry_after` (capped at [`MAX_COOLDOWN`]).
+    pub fn record(&self, host: &str, retry_after: Duration) {
+        self.record_at(host, retry_after, Instant::now());
+    }
+
+    /// Check whether `host` may be fetched now.
+    pub fn check(&self, host: &str) -> Result<(), HostCooling> {
+        self.check_at(host, Instant::now())
+    }
+
+    /// Remaining cool-down for `host`, if any (debug accessor).
+    pub fn remaining(&self, host: &str) -> Option<Duration> {
+        self.check(host).err().map(|e| e.retry_in)
+    }
+
+    /// Hosts currently cooling down with their remaining time (debug accessor).
+    pub fn active(&self) -> Vec<(String, Duration)> {
+        let now = Instant::now();
+        let mut active: Vec<_> = self
+            .lock()
+            .iter()
+            .filter(|(_, until)| **until > now)
+            .map(|(host, until)| (host.clone(), *until - now))
+            .collect();
+        active.sort();
+        active
+    }
+
+    /// Forget every cool-down.
+    pub fn reset(&self) {
+        self.lock().clear();
+    }
+
+    fn record_at(&self, host: &str, retry_after: Duration, now: Instant) {
+        let until = now + retry_after.min(MAX_COOLDOWN);
+        let mut hosts = self.lock();
+
+        if !hosts.contains_key(host) && hosts.len() >= self.max_hosts {
+            hosts.retain(|_, until| *until > now);
+            if hosts.len() >= self.max_hosts
+                && let Some(soonest) = hosts.iter().min_by_key(|(_, until)| **until).map(|(h, _)| h.clone())
+            {
+                hosts.remove(&soonest);
+            }
+        }
+
+        let entry = hosts.entry(host.to_string()).or_insert(until);
+        *entry = (*entry).max(until);
+        tracing::debug!("{} asked to back off, cooling down for {:?}", host, *entry - now);
+    }
+
+    fn check_at(&self, host: &str, now: Instant) -> Result<(), HostCooling> {
+        let mut hosts = self.lock();
+        match hosts.get(host).copied() {
+            Some(until) if until > now => Err(HostCooling { host: host.to_string(), retry_in: until - now }),
+            Some(_) => {
+                hosts.remove(host);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Instant>> {
+        self.hosts.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Parse a `Retry-After` value: delta-seconds or an HTTP-date.
+///
+/// Dates in the past yield a zero duration.
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+    Some((date - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cooldown_expires() {
+        let cooldowns = HostCooldowns::default();
+        let t0 = Instant::now();
+
+        cooldowns.record_at("example.com", Duration::from_secs(30), t0);
+        let err = cooldowns
+            .check_at("example.com", t0 + Duration::from_secs(10))
+            .unwrap_err();
+        assert_eq!(err.retry_in, Duration::from_secs(20));
+        assert_eq!(err.to_string(), "host example.com cooling down, retry after 20s");
+
+        assert!(cooldowns.check_at("other.com", t0).is_ok());
+        assert!(cooldowns.check_at("example.com", t0 + Duration::from_secs(30)).is_ok());
+        assert!(cooldowns.lock().is_empty());
+    }
+
+    #[test]
+    fn test_cooldown_keeps_longest_and_caps() {
+        let cooldowns = HostCooldowns::default();
+        let t0 = Instant::now();
+
+        cooldowns.record_at("example.com", Duration::from_secs(60), t0);
+        cooldowns.record_at("example.com", Duration::from_secs(5), t0);
+        assert_eq!(
+            cooldowns.check_at("example.com", t0).unwrap_err().retry_in,
+            Duration::from_secs(60)
+        );
+
+        cooldowns.record_at("slow.com", Duration::from_secs(86400), t0);
+        assert_eq!(cooldowns.check_at("slow.com", t0).unwrap_err().retry_in, MAX_COOLDOWN);
+    }
+
+    #[test]
+    fn test_cooldown_table_is_bounded() {
+        let cooldowns = HostCooldowns::new(2);
+        let t0 = Instant::now();
+
+        cooldowns.record_at("a.com", Duration::from_secs(10), t0);
+        cooldowns.record_at("b.com", Duration::from_secs(20), t0);
+        cooldowns.record_at("c.com", Duration::from_secs(30), t0);
+
+        assert_eq!(cooldowns.lock().len(), 2);
+        assert!(
+            cooldowns.check_at("a.com", t0).is_ok(),
+            "soonest-expiring host is evicted"
+        );
+        assert!(cooldowns.check_at("c.com", t0).is_err());
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(parse_retry_after("120", now), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 0 ", now), Some(Duration::ZERO));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:29:30 GMT", now),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+        assert_eq!(parse_retry_after("-5", now), None);
+    }
+}
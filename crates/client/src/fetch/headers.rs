@@ -0,0 +1,212 @@
+//! Bounded, printable copy of response headers for storage.
+//!
+//! Origins can send megabytes of headers (huge cookies, repeated `Link`
+//! headers). The live [`HeaderMap`] is kept for redirect and validator
+//! handling, while snapshots and debug output get a sanitized copy:
+//! non-UTF-8 bytes are replaced rather than dropped, control characters are
+//! stripped, long values are truncated, and headers past the total budget are
+//! dropped with a marker entry.
+//!
+//! Caller-supplied request headers (auth tokens, cookies) go through
+//! [`request_headers`], which refuses the ones that describe the connection
+//! rather than the request, and the ones that would change what the response
+//! body is (a partial range, a 304, another encoding) behind the cache's back.
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use thndrs_core::Error;
+
+/// Appended to a header value cut at the per-value limit.
+pub const TRUNCATED_MARKER: &str = "...[truncated]";
+
+/// Name of the marker entry recording how many headers were dropped.
+pub const DROPPED_HEADER: &str = "x-thndrs-dropped-headers";
+
+/// Request headers a caller can never set.
+///
+/// The first group is derived from the connection and body, or is hop-by-hop;
+/// the second would make the origin send a partial, empty (304), or
+/// differently encoded body that the fetch pipeline and cache can't use.
+pub const FORBIDDEN_REQUEST_HEADERS: &[&str] = &[
+    "host",
+    "content-length",
+    "transfer-encoding",
+    "connection",
+    "keep-alive",
+    "te",
+    "upgrade",
+    "expect",
+    "proxy-authorization",
+    "range",
+    "if-none-match",
+    "if-modified-since",
+    "accept-encoding",
+];
+
+/// Build the extra headers for one request from caller-supplied `(name, value)` pairs.
+///
+/// Names are case-insensitive. [`FORBIDDEN_REQUEST_HEADERS`] are always
+/// rejected, and `User-Agent` is rejected unless `allow_user_agent` is set.
+pub fn request_headers<'a>(
+    pairs: impl IntoIterator<Item = (&'a str, &'a str)>, allow_user_agent: bool,
+) -> Result<HeaderMap, Error> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in pairs {
+        let name = HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|_| Error::InvalidInput(format!("invalid header name: {name:?}")))?;
+        if FORBIDDEN_REQUEST_HEADERS.contains(&name.as_str()) {
+            return Err(Error::InvalidInput(format!("header {name} cannot be set")));
+        }
+        if name == reqwest::header::USER_AGENT && !allow_user_agent {
+            return Err(Error::InvalidInput(
+                "user-agent header overrides are disabled (set MCP_WEB_FETCH__ALLOW_USER_AGENT_OVERRIDE=true)".into(),
+            ));
+        }
+        let value = HeaderValue::from_str(value)
+            .map_err(|_| Error::InvalidInput(format!("invalid value for header {name}")))?;
+        headers.insert(name, value);
+    }
+    Ok(headers)
+}
+
+/// Copy headers into `(name, value)` pairs within the given byte budgets.
+///
+/// `max_value_bytes` caps each value and `max_total_bytes` caps the sum of
+/// names and values; headers that don't fit are dropped and counted in a
+/// trailing [`DROPPED_HEADER`] entry. Order and repeated headers are preserved.
+pub fn sanitize_headers(headers: &HeaderMap, max_total_bytes: usize, max_value_bytes: usize) -> Vec<(String, String)> {
+    let mut stored = Vec::new();
+    let mut total = 0;
+    let mut dropped = 0;
+
+    for (name, value) in headers {
+        let name = name.as_str();
+        let value = truncate_value(sanitize_value(value.as_bytes()), max_value_bytes);
+
+        if dropped > 0 || total + name.len() + value.len() > max_total_bytes {
+            dropped += 1;
+            continue;
+        }
+
+        total += name.len() + value.len();
+        stored.push((name.to_string(), value));
+    }
+
+    if dropped > 0 {
+        stored.push((DROPPED_HEADER.to_string(), dropped.to_string()));
+    }
+    stored
+}
+
+/// Decode a header value lossily and strip control characters.
+fn sanitize_value(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .chars()
+        .filter(|c| !c.is_control() || *c == '\t')
+        .collect()
+}
+
+/// Cut a value to at most `max_bytes` (plus the marker) on a char boundary.
+fn truncate_value(value: String, max_bytes: usize) -> String {
+    if value.len() <= max_bytes {
+        return value;
+    }
+
+    let mut end = max_bytes;
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{TRUNCATED_MARKER}", &value[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_headers_within_limits() {
+        let mut headers = HeaderMap::new();
+        headers.insert("etag", HeaderValue::from_static("\"abc\""));
+        headers.append("link", HeaderValue::from_static("</a>; rel=next"));
+        headers.append("link", HeaderValue::from_static("</b>; rel=prev"));
+
+        let stored = sanitize_headers(&headers, 1024, 256);
+        assert_eq!(
+            stored,
+            vec![
+                ("etag".to_string(), "\"abc\"".to_string()),
+                ("link".to_string(), "</a>; rel=next".to_string()),
+                ("link".to_string(), "</b>; rel=prev".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sanitize_headers_truncates_long_values() {
+        let mut headers = HeaderMap::new();
+        headers.insert("set-cookie", HeaderValue::from_str(&"a".repeat(100)).unwrap());
+
+        let stored = sanitize_headers(&headers, 1024, 10);
+        assert_eq!(stored[0].1, format!("aaaaaaaaaa{TRUNCATED_MARKER}"));
+    }
+
+    #[test]
+    fn test_sanitize_headers_drops_past_total_budget() {
+        let mut headers = HeaderMap::new();
+        for i in 0..10 {
+            headers.append("link", HeaderValue::from_str(&format!("</{i}>; rel=preload")).unwrap());
+        }
+
+        let stored = sanitize_headers(&headers, 64, 256);
+        let kept: usize = stored
+            .iter()
+            .filter(|(name, _)| name != DROPPED_HEADER)
+            .map(|(n, v)| n.len() + v.len())
+            .sum();
+        assert!(kept <= 64);
+        assert_eq!(stored.last().unwrap(), &(DROPPED_HEADER.to_string(), "7".to_string()));
+    }
+
+    #[test]
+    fn test_sanitize_value_replaces_invalid_utf8() {
+        assert_eq!(sanitize_value(b"caf\xe9 ok"), "caf\u{fffd} ok");
+        assert_eq!(sanitize_value("café".as_bytes()), "café");
+        assert_eq!(sanitize_value(b"a\x7fb\tc"), "ab\tc");
+    }
+
+    #[test]
+    fn test_truncate_value_respects_char_boundary() {
+        assert_eq!(truncate_value("ééé".to_string(), 3), format!("é{TRUNCATED_MARKER}"));
+        assert_eq!(truncate_value("short".to_string(), 10), "short");
+    }
+
+    #[test]
+    fn test_request_headers_refuses_connection_headers() {
+        let headers = request_headers([("Authorization", "Bearer t"), ("cookie", "session=1")], false).unwrap();
+        assert_eq!(headers.get("authorization").unwrap(), "Bearer t");
+        assert_eq!(headers.get("cookie").unwrap(), "session=1");
+
+        for name in [
+            "Host",
+            "content-length",
+            "Transfer-Encoding",
+            "connection",
+            "Range",
+            "If-None-Match",
+            "If-Modified-Since",
+            "Accept-Encoding",
+            "Proxy-Authorization",
+            "TE",
+            "Upgrade",
+            "Expect",
+            "Keep-Alive",
+        ] {
+            let err = request_headers([(name, "x")], true).unwrap_err();
+            assert!(err.to_string().contains("cannot be set"), "{name}: {err}");
+        }
+
+        assert!(request_headers([("User-Agent", "Mozilla/5.0")], false).is_err());
+        assert!(request_headers([("User-Agent", "Mozilla/5.0")], true).is_ok());
+        assert!(request_headers([("bad name", "x")], false).is_err());
+        assert!(request_headers([("x-token", "a\nb")], false).is_err());
+    }
+}
@@ -0,0 +1,44 @@
+//! Per-host request state shared by every fetch a [`FetchClient`](super::FetchClient) sends.
+//!
+//! Holds the [`HostCooldowns`] origins asked for via `Retry-After`, the
+//! [`HostPacer`] spacing requests to one host, and the in-flight [`ByteBudget`].
+//! Failures we observe are tracked separately, by the
+//! [`CircuitBreaker`](super::CircuitBreaker).
+
+use super::budget::ByteBudget;
+use super::cooldown::HostCooldowns;
+use super::pacing::HostPacer;
+
+/// Cool-downs, pacing, and byte budget for one fetch client and its clones.
+///
+/// Cloning is cheap and clones share state, so clients built around one
+/// `HostState` back off, pace, and buffer bodies as one.
+#[derive(Debug, Clone, Default)]
+pub struct HostState {
+    cooldowns: HostCooldowns,
+    pacer: HostPacer,
+    budget: ByteBudget,
+}
+
+impl HostState {
+    /// Bound the response bytes fetches sharing this state hold at once.
+    pub fn with_byte_budget(mut self, budget: ByteBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Per-host cool-downs requested via `Retry-After`, shared with clones.
+    pub fn cooldowns(&self) -> &HostCooldowns {
+        &self.cooldowns
+    }
+
+    /// Per-host request spacing, shared with clones.
+    pub fn pacer(&self) -> &HostPacer {
+        &self.pacer
+    }
+
+    /// Byte budget for in-flight response bodies, shared with clones.
+    pub fn byte_budget(&self) -> &ByteBudget {
+        &self.budget
+    }
+}
@@ -7,28 +7,120 @@
 //!
 //! ### SSRF & Safety Gates
 //! - Deny private ranges (RFC1918, link-local, localhost, etc.)
-//! - Resolve DNS and validate all A/AAAA answers are public.
-//! - Max redirects: 5
-//! - Max body bytes: 5MB (configurable)
+//! - Deny explicit ports used by non-HTTP services (the Fetch standard's bad-port list).
+//! - Resolve DNS and validate all A/AAAA answers are public before sending; a private
+//!   answer fails with `SsrfBlocked` naming the address.
+//! - Connections resolve through [`PublicResolver`], so a name re-resolving to a private
+//!   address after the check (DNS rebinding) is still refused.
+//! - `allow_private_addresses` turns these checks off, for intranet deployments and local fixtures.
+//!
+//! ### Redirects
+//! - Followed by hand, at most 5 (configurable). Every hop is canonicalized and goes through
+//!   the scheme, domain policy, port, and address checks before it is requested; a blocked
+//!   hop fails with its index in the message.
+//! - The URLs that redirected are listed in `FetchResponse::redirect_chain`.
+//! - Max body bytes: 5MB (configurable). Bodies are read chunk by chunk and the fetch
+//!   fails with `FetchTooLarge` as soon as one grows past the limit; nothing past it is buffered.
+//! - Bodies being read count against a shared [`ByteBudget`] (default 64MB); fetches
+//!   queue for it rather than buffering without bound. The reservation covers an inflated
+//!   body too and travels with the [`FetchResponse`] until it is dropped.
+//!
+//! ### Domain Policy
+//! - Server allow/deny lists ([`DomainPolicy`]) gate the requested host and every redirect target.
+//!
+//! ### Content-Type Gate
+//! - Checked as soon as headers arrive; disallowed types fail before the body is read.
+//! - Default allowlist: HTML, XHTML, XML, plain text, markdown, JSON.
+//!
+//! ### Body Sniffing
+//! - Text bodies that still start with gzip/zlib magic bytes are inflated (within `max_bytes`).
+//! - UTF-16 (BOM or NUL pattern) and Windows-1252 bodies are re-decoded instead of becoming mojibake.
+//! - Corrections are listed in `FetchResponse::body_fixups`.
+//! - [`FetchResponse::text`] decodes in the declared charset (Content-Type, BOM, or `<meta charset>`).
+//! - [`body_kind`] tells HTML, JSON, plain text, and binary bodies apart, sniffing untyped ones.
+//!
+//! ### Challenge Detection
+//! - [`detect_challenge`] recognizes bot-protection interstitials (Cloudflare, Akamai,
+//!   DataDome, ...) served in place of the page; callers decide what to do with them.
+//!
+//! ### Private Content
+//! - [`private_content_reason`] flags responses fetched with URL or header credentials, marked
+//!   `Cache-Control: private`, or setting a session cookie, which must not be shared.
+//!
+//! ### Stored Headers
+//! - Snapshots and debug output get a sanitized copy of the response headers:
+//!   lossy UTF-8, values capped at 2KB, 16KB in total (configurable).
+//! - Redirect and validator handling still read the live headers.
+//!
+//! ### Host Circuit Breaker
+//! - After 3 consecutive failures (network errors or 5xx) within 60s, fail fast for 30s.
+//! - Then admit a single half-open probe; success closes the circuit.
+//! - The probe is taken after the cool-down, robots.txt, and pacing waits; a fetch that
+//!   ends before an outcome (or is dropped) releases it for the next one.
+//!
+//! ### Host Cool-downs
+//! - 429, or 503 with `Retry-After`, puts the host in a cool-down (capped at 1h).
+//! - Later fetches wait out a cool-down of up to 5s (and under the timeout);
+//!   longer ones fail fast with the remaining time.
 //!
 //! ### robots.txt Compliance
 //! - Fetch and cache `robots.txt` per host (24h cache).
+//! - With [`FetchClient::new_with_cache`], bodies are also written through to the
+//!   SQLite `robots_cache` table and read back on a memory miss.
 //! - Evaluate `*` and current User-Agent.
+//! - Keep at most `robots_cache_max_entries` origins in memory, evicting the least
+//!   recently used; [`RobotsCache::stats`] reports entries, hits, and misses.
+//! - A robots.txt that answers 5xx or can't be reached fails the fetch with
+//!   `ROBOTS_UNAVAILABLE`, or with [`RobotsFailurePolicy::FailOpen`] is treated
+//!   as allowing everything for 5 minutes.
+//!
+//! ### Politeness Pacing
+//! - Requests to one host are spaced by the larger of `per_host_interval` (default
+//!   500ms) and the host's robots.txt `Crawl-delay` (capped at 10s); different hosts
+//!   stay fully parallel. The spacing lives on the client's [`HostState`], with its
+//!   cool-downs and byte budget.
 
+pub mod budget;
+pub mod challenge;
+pub mod circuit;
+pub mod content_type;
+pub mod cooldown;
+pub mod headers;
+pub mod host;
+pub mod pacing;
+pub mod private;
 pub mod robots;
+pub mod sniff;
 pub mod ssrf;
 pub mod url;
 
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use reqwest::Url;
 use reqwest::{Client, StatusCode, header};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-pub use robots::{RobotsCache, RobotsError};
-pub use ssrf::{SsrfError, validate_ip};
-pub use url::{UrlError, canonicalize};
+pub use budget::{BudgetReservation, ByteBudget, DEFAULT_MAX_INFLIGHT_BYTES};
+pub use challenge::{Challenge, detect_challenge};
+pub use circuit::{CircuitBreaker, CircuitOpen, CircuitPermit, CircuitState};
+pub use content_type::{DEFAULT_ALLOWED_CONTENT_TYPES, charset, is_allowed_content_type};
+pub use cooldown::{HostCooldowns, HostCooling, parse_retry_after};
+pub use headers::{FORBIDDEN_REQUEST_HEADERS, request_headers, sanitize_headers};
+pub use host::HostState;
+pub use pacing::HostPacer;
+pub use private::private_content_reason;
+pub use reqwest::header::HeaderMap;
+pub use robots::{RobotsCache, RobotsCacheStats, RobotsError};
+pub use sniff::{
+    BodyFixup, BodyKind, DecodedText, body_kind, decode_text, decode_with_charset, looks_compressed, unwrap_compressed,
+};
+pub use ssrf::{PublicResolver, SsrfError, resolve_public, validate_host, validate_ip, validate_port};
+pub use url::{
+    CanonicalizeOptions, DEFAULT_MAX_URL_LEN, TRACKING_PARAMS, UrlError, canonicalize, canonicalize_with_options,
+};
 
-use thndrs_core::Error;
+use thndrs_core::cache::CacheDb;
+use thndrs_core::{DomainPolicy, Error, RobotsFailurePolicy};
 
 /// Configuration for the fetch client.
 #[derive(Debug, Clone)]
@@ -47,6 +139,78 @@ pub struct FetchConfig {
 
     /// Whether to respect robots.txt (default: true)
     pub respect_robots: bool,
+
+    /// Accept-Language header value (default: unset, no header sent)
+    pub accept_language: Option<String>,
+
+    /// Consecutive failures before a host's circuit opens (default: 3)
+    pub circuit_failure_threshold: u32,
+
+    /// Window in which failures count as consecutive (default: 60s)
+    pub circuit_window: Duration,
+
+    /// How long an open circuit fails fast before probing (default: 30s)
+    pub circuit_cooldown: Duration,
+
+    /// Content types whose bodies are downloaded; empty allows any (default: `DEFAULT_ALLOWED_CONTENT_TYPES`)
+    pub allowed_content_types: Vec<String>,
+
+    /// Hosts that may be fetched or redirected to (default: unrestricted)
+    pub domain_policy: DomainPolicy,
+
+    /// Longest host cool-down to wait out before fetching; longer ones fail fast (default: 5s)
+    pub max_cooldown_wait: Duration,
+
+    /// Total bytes of header names and values kept in `stored_headers` (default: 16KB)
+    pub max_header_bytes: usize,
+
+    /// Bytes kept per header value in `stored_headers` before truncation (default: 2KB)
+    pub max_header_value_bytes: usize,
+
+    /// Fetch hosts resolving to private or reserved addresses (default: false)
+    pub allow_private_addresses: bool,
+
+    /// Least time between requests to one host; a longer robots.txt `Crawl-delay` wins (default: 500ms)
+    pub per_host_interval: Duration,
+
+    /// What to do when robots.txt answers 5xx or can't be reached (default: fail closed)
+    pub robots_failure_policy: RobotsFailurePolicy,
+
+    /// Origins whose robots.txt is kept in memory before the least recently used is evicted (default: 1024)
+    pub robots_cache_max_entries: usize,
+
+    /// Drop `utm_*` and other tracking parameters from requested URLs (default: true)
+    pub strip_tracking_params: bool,
+
+    /// Parameter names stripped along with the built-in tracking list (default: none)
+    pub extra_tracking_params: Vec<String>,
+
+    /// Drop `user:password@` from requested URLs instead of rejecting them (default: false)
+    pub strip_url_credentials: bool,
+
+    /// Longest canonical URL fetched, in bytes (default: 8KB)
+    pub max_url_len: usize,
+
+    /// Retries after a connect error, timeout, or 502/503/504 response (default: 2)
+    pub retries: u8,
+
+    /// Wait before the first retry, doubled for each one after it (default: 250ms)
+    pub retry_backoff: Duration,
+
+    /// Report a non-2xx challenge page as `BLOCKED_BY_BOT_PROTECTION` rather than `HTTP_ERROR` (default: true)
+    pub detect_bot_protection: bool,
+}
+
+impl FetchConfig {
+    /// Canonicalization applied to requested URLs.
+    pub fn canonicalize_options(&self) -> CanonicalizeOptions {
+        CanonicalizeOptions {
+            strip_tracking_params: self.strip_tracking_params,
+            extra_tracking_params: self.extra_tracking_params.clone(),
+            strip_credentials: self.strip_url_credentials,
+            max_len: self.max_url_len,
+        }
+    }
 }
 
 impl Default for FetchConfig {
@@ -57,10 +221,52 @@ impl Default for FetchConfig {
             timeout: Duration::from_millis(20000),
             max_redirects: 5,
             respect_robots: true,
+            accept_language: None,
+            circuit_failure_threshold: 3,
+            circuit_window: Duration::from_secs(60),
+            circuit_cooldown: Duration::from_secs(30),
+            allowed_content_types: DEFAULT_ALLOWED_CONTENT_TYPES.iter().map(|s| s.to_string()).collect(),
+            domain_policy: DomainPolicy::default(),
+            max_cooldown_wait: Duration::from_secs(5),
+            max_header_bytes: 16 * 1024,
+            max_header_value_bytes: 2 * 1024,
+            allow_private_addresses: false,
+            per_host_interval: Duration::from_millis(500),
+            robots_failure_policy: RobotsFailurePolicy::FailClosed,
+            robots_cache_max_entries: robots::DEFAULT_MAX_ENTRIES,
+            strip_tracking_params: true,
+            extra_tracking_params: Vec::new(),
+            strip_url_credentials: false,
+            max_url_len: DEFAULT_MAX_URL_LEN,
+            retries: 2,
+            retry_backoff: Duration::from_millis(250),
+            detect_bot_protection: true,
         }
     }
 }
 
+/// Per-request overrides of a [`FetchClient`]'s configuration.
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    /// Timeout in place of the configured one (default: unset)
+    pub timeout: Option<Duration>,
+
+    /// User-Agent for the request and its robots.txt check (default: unset, the configured one)
+    pub user_agent: Option<String>,
+
+    /// Accept-Language in place of the configured one (default: unset)
+    pub accept_language: Option<String>,
+
+    /// Download any content type, skipping `allowed_content_types` (default: false)
+    pub any_content_type: bool,
+
+    /// Content types allowed in place of `allowed_content_types` (default: unset)
+    pub content_types: Option<Vec<String>>,
+
+    /// Extra request headers, built with [`request_headers`] (default: none)
+    pub headers: header::HeaderMap,
+}
+
 /// Response from a fetch operation.
 #[derive(Debug, Clone)]
 pub struct FetchResponse {
@@ -68,6 +274,8 @@ pub struct FetchResponse {
     pub url: Url,
     /// The final URL after redirects
     pub final_url: Url,
+    /// URLs that answered with a redirect, in order, starting with `url`; empty when none was followed
+    pub redirect_chain: Vec<Url>,
     /// HTTP status code
     pub status: StatusCode,
     /// Content-Type header
@@ -76,8 +284,70 @@ pub struct FetchResponse {
     pub bytes: Bytes,
     /// Response headers
     pub headers: header::HeaderMap,
+    /// Sanitized, size-capped copy of `headers` for snapshots and debug output
+    pub stored_headers: Vec<(String, String)>,
     /// Time taken to fetch in milliseconds
     pub fetch_ms: u64,
+    /// Requests sent, counting retries but not redirect hops; 0 when rebuilt from the cache
+    pub attempts: u8,
+    /// Time spent waiting out a host cool-down before sending, in milliseconds
+    pub cooldown_wait_ms: u64,
+    /// Time spent waiting for this host's politeness spacing before sending, in milliseconds
+    pub pacing_wait_ms: u64,
+    /// Time spent queued for the in-flight byte budget before reading the body, in milliseconds
+    pub budget_wait_ms: u64,
+    /// Bytes reserved by all in-flight fetches, this one included, once its reservation was granted
+    pub inflight_bytes: usize,
+    /// Corrections applied to a mislabeled body (see [`sniff`])
+    pub body_fixups: Vec<BodyFixup>,
+    /// Byte budget held for `bytes`, released when the last clone of this response is dropped
+    pub reservation: Option<Arc<BudgetReservation>>,
+}
+
+impl FetchResponse {
+    /// Rebuild a response from a body kept in the cache, without touching the network.
+    ///
+    /// `stored_headers` are the sanitized pairs saved with the body; entries that
+    /// aren't valid header names or values are left out of `headers`. An invalid
+    /// status falls back to 200.
+    pub fn from_stored(
+        url: Url, final_url: Url, status: u16, content_type: Option<String>, bytes: Vec<u8>,
+        stored_headers: Vec<(String, String)>,
+    ) -> Self {
+        let mut headers = header::HeaderMap::new();
+        for (name, value) in &stored_headers {
+            if let (Ok(name), Ok(value)) = (
+                header::HeaderName::from_bytes(name.as_bytes()),
+                header::HeaderValue::from_str(value),
+            ) {
+                headers.append(name, value);
+            }
+        }
+
+        Self {
+            url,
+            final_url,
+            redirect_chain: Vec::new(),
+            status: StatusCode::from_u16(status).unwrap_or(StatusCode::OK),
+            content_type,
+            bytes: Bytes::from(bytes),
+            headers,
+            stored_headers,
+            fetch_ms: 0,
+            attempts: 0,
+            cooldown_wait_ms: 0,
+            pacing_wait_ms: 0,
+            budget_wait_ms: 0,
+            inflight_bytes: 0,
+            body_fixups: Vec::new(),
+            reservation: None,
+        }
+    }
+
+    /// Decode the body in the charset its Content-Type declares (see [`decode_with_charset`]).
+    pub fn text(&self) -> DecodedText {
+        decode_with_charset(&self.bytes, self.content_type.as_deref().and_then(charset))
+    }
 }
 
 /// HTTP fetch client with safety checks.
@@ -85,88 +355,255 @@ pub struct FetchClient {
     http: Client,
     config: FetchConfig,
     robots_cache: RobotsCache,
+    circuit: CircuitBreaker,
+    hosts: HostState,
 }
 
 impl FetchClient {
     /// Create a new fetch client with the given configuration.
     pub fn new(config: FetchConfig) -> Result<Self, Error> {
-        let http = Client::builder()
+        Self::build(config, None)
+    }
+
+    /// Create a fetch client whose robots.txt cache persists to `db`.
+    ///
+    /// robots.txt rules then survive restarts and are shared with every other
+    /// client built on the same database.
+    pub fn new_with_cache(config: FetchConfig, db: CacheDb) -> Result<Self, Error> {
+        Self::build(config, Some(db))
+    }
+
+    fn build(config: FetchConfig, db: Option<CacheDb>) -> Result<Self, Error> {
+        // Redirects are followed in `fetch_with_limit` so each hop is checked before it is requested.
+        let mut builder = Client::builder()
             .user_agent(&config.user_agent)
             .timeout(config.timeout)
-            .redirect(reqwest::redirect::Policy::limited(config.max_redirects))
+            .redirect(reqwest::redirect::Policy::none())
             .use_rustls_tls()
             .gzip(true)
             .brotli(true)
-            .deflate(true)
+            .deflate(true);
+        if !config.allow_private_addresses {
+            builder = builder.dns_resolver(Arc::new(PublicResolver));
+        }
+        let http = builder
             .build()
             .map_err(|e| Error::FetchTimeout(format!("failed to build HTTP client: {}", e)))?;
 
-        let robots_cache = RobotsCache::new(config.user_agent.clone());
+        let robots_cache = RobotsCache::new(config.user_agent.clone(), db)
+            .with_private_addresses(config.allow_private_addresses)
+            .with_failure_policy(config.robots_failure_policy)
+            .with_max_entries(config.robots_cache_max_entries);
+        let circuit = CircuitBreaker::new(
+            config.circuit_failure_threshold,
+            config.circuit_window,
+            config.circuit_cooldown,
+        );
+
+        Ok(Self { http, config, robots_cache, circuit, hosts: HostState::default() })
+    }
+
+    /// Use a shared circuit breaker so host failure state outlives this client.
+    pub fn with_circuit_breaker(mut self, circuit: CircuitBreaker) -> Self {
+        self.circuit = circuit;
+        self
+    }
 
-        Ok(Self { http, config, robots_cache })
+    /// Share cool-downs, pacing, and the byte budget with other clients.
+    pub fn with_host_state(mut self, hosts: HostState) -> Self {
+        self.hosts = hosts;
+        self
     }
 
     /// Fetch a URL, returning raw bytes and metadata.
     ///
-    /// Performs SSRF check, robots.txt check, and respects redirect/byte limits.
+    /// Performs domain policy check, SSRF check, robots.txt check, and respects redirect/byte limits.
+    /// Responses whose Content-Type is not allowed are rejected before the body is read.
     pub async fn fetch(&self, url_str: &str) -> Result<FetchResponse, Error> {
+        self.fetch_with_limit(url_str, self.config.max_bytes).await
+    }
+
+    /// Fetch a URL with a per-call body limit in place of the configured `max_bytes`.
+    pub async fn fetch_with_limit(&self, url_str: &str, max_bytes: usize) -> Result<FetchResponse, Error> {
+        self.fetch_with_options(url_str, max_bytes, &RequestOptions::default())
+            .await
+    }
+
+    /// Fetch a URL, sending `extra_headers` along with the client's own.
+    ///
+    /// Build `extra_headers` with [`request_headers`] so connection headers
+    /// can't be overridden. They are sent to the requested host only; redirect
+    /// hops to another host don't get credentials meant for the first.
+    pub async fn fetch_with_headers(
+        &self, url_str: &str, extra_headers: &header::HeaderMap,
+    ) -> Result<FetchResponse, Error> {
+        let options = RequestOptions { headers: extra_headers.clone(), ..Default::default() };
+        self.fetch_with_options(url_str, self.config.max_bytes, &options).await
+    }
+
+    /// Fetch a URL with a per-call body limit and [`RequestOptions`] overriding the configuration.
+    ///
+    /// One client can serve callers with different limits, agents, and headers
+    /// this way while they share its connection pool and robots.txt cache.
+    pub async fn fetch_with_options(
+        &self, url_str: &str, max_bytes: usize, options: &RequestOptions,
+    ) -> Result<FetchResponse, Error> {
         let start = Instant::now();
-        let url = canonicalize(url_str).map_err(|e| Error::InvalidUrl(e.to_string()))?;
+        let timeout = options.timeout.unwrap_or(self.config.timeout);
+        let user_agent = options.user_agent.as_deref().unwrap_or(&self.config.user_agent);
+        let url = canonicalize_with_options(url_str, &self.config.canonicalize_options())
+            .map_err(|e| Error::InvalidUrl(e.to_string()))?;
+        let host = url.host_str().unwrap_or_default().to_string();
+
+        self.config.domain_policy.check(&host)?;
+        if let Some(port) = url.port() {
+            validate_port(port).map_err(|e| Error::SsrfBlocked(e.to_string()))?;
+        }
+        self.check_addresses(&url, 0).await?;
+
+        if let CircuitState::Open { retry_in } = self.circuit.state(&host) {
+            return Err(Error::CircuitOpen(CircuitOpen { host, retry_in }.to_string()));
+        }
+        let cooldown_wait = self.wait_for_cooldown(&host, timeout).await?;
 
+        let mut interval = self.config.per_host_interval;
         if self.config.respect_robots {
+            // Rules are evaluated for the agent the page is fetched as.
             self.robots_cache
-                .is_allowed(&url)
+                .is_allowed_as(&url, user_agent)
                 .await
-                .map_err(|e| Error::RobotsDisallowed(e.to_string()))?;
+                .map_err(|e| match e {
+                    RobotsError::Disallowed { .. } => Error::RobotsDisallowed(e.to_string()),
+                    RobotsError::FetchError(_) | RobotsError::TooLarge => {
+                        Error::RobotsUnavailable(format!("{e} (robots_url: {})", robots::robots_url(&url)))
+                    }
+                })?;
+            if let Some(delay) = self.robots_cache.crawl_delay_as(&url, user_agent).await {
+                interval = interval.max(delay);
+            }
         }
+        let pacing_wait = self.hosts.pacer().wait_turn(&host, interval).await;
 
-        let mut request = self.http.get(url.as_str());
-        request = request.header(
-            "Accept",
-            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
-        );
-
-        let response = request
-            .send()
-            .await
-            .map_err(|e| Error::HttpError(format!("network error: {}", e)))?;
+        // Admitted only now, so a half-open probe is held just while requests are
+        // in flight; an early return or a dropped future releases it unrecorded.
+        let permit = self
+            .circuit
+            .check(&host)
+            .map_err(|e| Error::CircuitOpen(e.to_string()))?;
+        let sent = Instant::now();
+        let deadline = sent + timeout.saturating_sub(cooldown_wait + pacing_wait);
+        let mut retries = 0;
+        let mut redirect_chain = Vec::new();
+        let mut current = url.clone();
+        let response = loop {
+            let response = match self
+                .send_with_retries(&current, &url, redirect_chain.len(), deadline, options, &mut retries)
+                .await
+            {
+                Ok(response) => response,
+                Err(e) => {
+                    if matches!(e, Error::HttpError(_)) {
+                        permit.record_failure();
+                    }
+                    return Err(e);
+                }
+            };
+            let Some(target) = redirect_target(&response) else {
+                break response;
+            };
+            if redirect_chain.len() >= self.config.max_redirects {
+                permit.record_success();
+                return Err(Error::HttpError(format!(
+                    "too many redirects (more than {})",
+                    self.config.max_redirects
+                )));
+            }
+            redirect_chain.push(current);
+            current = self.check_redirect(redirect_chain.len(), &target).await?;
+        };
 
         let status = response.status();
+        if status.is_server_error() {
+            permit.record_failure();
+        } else {
+            permit.record_success();
+        }
+
+        if matches!(status, StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE)
+            && let Some(retry_after) = response
+                .headers()
+                .get(header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| parse_retry_after(v, chrono::Utc::now()))
+        {
+            self.hosts.cooldowns().record(&host, retry_after);
+        }
 
         if !status.is_success() {
+            // Challenges usually come as 403/429/503, so look at them before they
+            // become a plain status error.
+            if self.config.detect_bot_protection {
+                let headers = response.headers().clone();
+                let prefix = read_prefix(response, challenge::MAX_CHALLENGE_BYTES + 1).await;
+                if let Some(challenge) = detect_challenge(status, &headers, &prefix) {
+                    tracing::info!("{} served a {} with status {}", url, challenge, status.as_u16());
+                    return Err(Error::BlockedByBotProtection(challenge.to_string()));
+                }
+            }
             return Err(Error::HttpError(format!("status {}", status.as_u16())));
         }
 
         let content_length = response.content_length();
-        if let Some(len) = content_length
-            && len as usize > self.config.max_bytes
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string());
+
+        if let Some(ct) = &content_type
+            && !options.any_content_type
+            && !is_allowed_content_type(
+                ct,
+                options
+                    .content_types
+                    .as_ref()
+                    .unwrap_or(&self.config.allowed_content_types),
+            )
         {
-            return Err(Error::FetchTooLarge(format!(
-                "{} bytes exceeds {}",
-                len, self.config.max_bytes
+            let size = content_length.map_or_else(|| "unknown size".to_string(), |len| format!("{len} bytes"));
+            return Err(Error::UnsupportedContentType(format!(
+                "{} ({size}) is not an allowed content type",
+                content_type::essence(ct)
             )));
         }
 
-        let final_url = response.url().clone();
-        let headers = response.headers().clone();
-
-        let bytes = response
-            .bytes()
-            .await
-            .map_err(|e| Error::HttpError(format!("failed to read response: {}", e)))?;
-
-        if bytes.len() > self.config.max_bytes {
-            return Err(Error::FetchTooLarge(format!(
-                "{} bytes exceeds {}",
-                bytes.len(),
-                self.config.max_bytes
-            )));
+        if let Some(len) = content_length
+            && len as usize > max_bytes
+        {
+            return Err(Error::FetchTooLarge(format!("{} bytes exceeds {}", len, max_bytes)));
         }
 
-        let content_type = headers
-            .get(header::CONTENT_TYPE)
-            .and_then(|v| v.to_str().ok())
-            .map(|s| s.to_string());
+        let final_url = current;
+        let headers = response.headers().clone();
+
+        let reserve = content_length.map_or(max_bytes, |len| (len as usize).min(max_bytes));
+        let reservation = self
+            .hosts
+            .byte_budget()
+            .reserve(reserve, timeout.saturating_sub(start.elapsed()))
+            .await?;
+        let bytes = read_body(response, max_bytes).await?;
+        // Inflating can grow the body up to max_bytes; reserve for that first.
+        let reservation = if looks_compressed(&bytes, content_type.as_deref()) {
+            self.hosts
+                .byte_budget()
+                .grow(reservation, max_bytes, timeout.saturating_sub(start.elapsed()))
+                .await?
+        } else {
+            reservation
+        };
+        let inflight_bytes = self.hosts.byte_budget().reserved();
+        let (bytes, body_fixup) = unwrap_compressed(bytes, content_type.as_deref(), max_bytes)?;
 
         let fetch_ms = start.elapsed().as_millis() as u64;
 
@@ -178,7 +615,195 @@ impl FetchClient {
             bytes.len()
         );
 
-        Ok(FetchResponse { url, final_url, status, content_type, bytes, headers: headers.clone(), fetch_ms })
+        let stored_headers = sanitize_headers(
+            &headers,
+            self.config.max_header_bytes,
+            self.config.max_header_value_bytes,
+        );
+
+        Ok(FetchResponse {
+            url,
+            final_url,
+            redirect_chain,
+            status,
+            content_type,
+            bytes,
+            headers,
+            stored_headers,
+            fetch_ms,
+            attempts: retries.saturating_add(1),
+            cooldown_wait_ms: cooldown_wait.as_millis() as u64,
+            pacing_wait_ms: pacing_wait.as_millis() as u64,
+            budget_wait_ms: reservation.waited.as_millis() as u64,
+            inflight_bytes,
+            body_fixups: body_fixup.into_iter().collect(),
+            reservation: Some(Arc::new(reservation)),
+        })
+    }
+
+    /// Send a HEAD request to check that `url_str` is reachable, returning its status.
+    ///
+    /// Applies the same domain policy and port checks as [`fetch`](Self::fetch)
+    /// but skips robots.txt, the circuit breaker, and the body. Any HTTP
+    /// response counts as reachable; only network failures are errors.
+    pub async fn probe(&self, url_str: &str) -> Result<StatusCode, Error> {
+        let url = canonicalize(url_str).map_err(|e| Error::InvalidUrl(e.to_string()))?;
+        self.config.domain_policy.check(url.host_str().unwrap_or_default())?;
+        if let Some(port) = url.port() {
+            validate_port(port).map_err(|e| Error::SsrfBlocked(e.to_string()))?;
+        }
+        self.check_addresses(&url, 0).await?;
+
+        let response = self
+            .http
+            .head(url.as_str())
+            .send()
+            .await
+            .map_err(|e| match ssrf::blocked_ip(&e) {
+                Some(ip) => Error::SsrfBlocked(format!("{url} resolved to blocked IP {ip}")),
+                None => Error::HttpError(format!("network error: {}", e)),
+            })?;
+        Ok(response.status())
+    }
+
+    /// Send one GET, retrying transient failures up to `retries` times.
+    ///
+    /// Connect errors, timeouts, and 502/503/504 responses without a
+    /// `Retry-After` are retried (a `Retry-After` is left to the host
+    /// cool-down); nothing else is. The backoff starts at `retry_backoff` and
+    /// doubles, and a retry is only made when its wait ends before `deadline`.
+    /// `requested` is the URL the fetch started from; `hop` is 0 for it and
+    /// counts redirects after that; `retries` counts retries across hops.
+    async fn send_with_retries(
+        &self, url: &Url, requested: &Url, hop: usize, deadline: Instant, options: &RequestOptions, retries: &mut u8,
+    ) -> Result<reqwest::Response, Error> {
+        let mut backoff = self.config.retry_backoff;
+        let mut attempt = 0;
+        loop {
+            let result = self
+                .send(
+                    url,
+                    requested,
+                    deadline.saturating_duration_since(Instant::now()),
+                    options,
+                )
+                .await;
+            let transient = match &result {
+                Ok(response) => {
+                    matches!(
+                        response.status(),
+                        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+                    ) && !response.headers().contains_key(header::RETRY_AFTER)
+                }
+                Err(e) => ssrf::blocked_ip(e).is_none() && (e.is_connect() || e.is_timeout()),
+            };
+            if transient && attempt < self.config.retries && Instant::now() + backoff < deadline {
+                attempt += 1;
+                *retries = retries.saturating_add(1);
+                tracing::debug!(
+                    "retrying {url} in {backoff:?} (retry {attempt} of {})",
+                    self.config.retries
+                );
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                continue;
+            }
+
+            return result.map_err(|e| {
+                if let Some(ip) = ssrf::blocked_ip(&e) {
+                    return Error::SsrfBlocked(format!("{}{url} resolved to blocked IP {ip}", hop_prefix(hop)));
+                }
+                Error::HttpError(format!("network error: {}", e))
+            });
+        }
+    }
+
+    /// Send one GET, without following redirects.
+    ///
+    /// `options.headers` are only sent while `url` has the same origin (scheme,
+    /// host, and port) as `requested`, so a redirect to another port or from
+    /// https to http never carries the caller's credentials.
+    async fn send(
+        &self, url: &Url, requested: &Url, timeout: Duration, options: &RequestOptions,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let mut request = self.http.get(url.as_str()).timeout(timeout);
+        request = request.header(
+            "Accept",
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+        );
+        if let Some(lang) = options
+            .accept_language
+            .as_ref()
+            .or(self.config.accept_language.as_ref())
+        {
+            request = request.header(header::ACCEPT_LANGUAGE, lang);
+        }
+        if let Some(ua) = &options.user_agent {
+            request = request.header(header::USER_AGENT, ua);
+        }
+        if same_origin(url, requested) {
+            request = request.headers(options.headers.clone());
+        }
+
+        request.send().await
+    }
+
+    /// Canonicalize redirect hop `hop` and run it through the same gates as the requested URL.
+    ///
+    /// The configured canonicalization (length limit, credential and tracking
+    /// parameter stripping) applies to every hop as it does to the first URL.
+    async fn check_redirect(&self, hop: usize, target: &Url) -> Result<Url, Error> {
+        let options = self.config.canonicalize_options();
+        let url = canonicalize_with_options(target.as_str(), &options).map_err(|e| match e {
+            UrlError::UnsupportedScheme(_) | UrlError::CredentialsNotAllowed => {
+                Error::SsrfBlocked(format!("{}{target}: {e}", hop_prefix(hop)))
+            }
+            UrlError::TooLong { .. } => Error::InvalidUrl(format!("{}{target}: {e}", hop_prefix(hop))),
+            _ => Error::HttpError(format!("{}{target}: {e}", hop_prefix(hop))),
+        })?;
+        self.config.domain_policy.check(url.host_str().unwrap_or_default())?;
+        if let Some(port) = url.port() {
+            validate_port(port).map_err(|e| Error::SsrfBlocked(format!("{}{url}: {e}", hop_prefix(hop))))?;
+        }
+        self.check_addresses(&url, hop).await?;
+        Ok(url)
+    }
+
+    /// Resolve `url`'s host and reject it if any address is private or reserved.
+    ///
+    /// Runs before robots.txt or the URL is requested, so a blocked host is never
+    /// contacted. Lookup failures are reported as network errors, as the request
+    /// itself would.
+    async fn check_addresses(&self, url: &Url, hop: usize) -> Result<(), Error> {
+        if self.config.allow_private_addresses {
+            return Ok(());
+        }
+
+        let host = url.host_str().unwrap_or_default();
+        match resolve_public(host, url.port_or_known_default().unwrap_or(443)).await {
+            Ok(_) => Ok(()),
+            Err(e @ SsrfError::DnsError(_)) => Err(Error::HttpError(format!("network error: {e}"))),
+            Err(e) if hop == 0 => Err(Error::SsrfBlocked(format!("{host}: {e}"))),
+            Err(e) => Err(Error::SsrfBlocked(format!("{}{url}: {e}", hop_prefix(hop)))),
+        }
+    }
+
+    /// Wait out a short cool-down for `host`, or fail fast if it is too long.
+    ///
+    /// A cool-down is waited out only if it is within `max_cooldown_wait` and
+    /// shorter than the request `timeout`. Returns how long we waited.
+    async fn wait_for_cooldown(&self, host: &str, timeout: Duration) -> Result<Duration, Error> {
+        let Err(cooling) = self.hosts.cooldowns().check(host) else {
+            return Ok(Duration::ZERO);
+        };
+
+        if cooling.retry_in > self.config.max_cooldown_wait || cooling.retry_in >= timeout {
+            return Err(Error::HostCoolingDown(cooling.to_string()));
+        }
+
+        tracing::debug!("{} is cooling down, waiting {:?}", host, cooling.retry_in);
+        tokio::time::sleep(cooling.retry_in).await;
+        Ok(cooling.retry_in)
     }
 
     /// Get reference to the robots cache.
@@ -186,15 +811,79 @@ impl FetchClient {
         &self.robots_cache
     }
 
+    /// Get reference to the host circuit breaker.
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.circuit
+    }
+
+    /// Get reference to the per-host cool-downs, pacing, and byte budget.
+    pub fn host_state(&self) -> &HostState {
+        &self.hosts
+    }
+
     /// Get reference to the configuration.
     pub fn config(&self) -> &FetchConfig {
         &self.config
     }
 }
 
+/// Read a response body chunk by chunk, failing as soon as it grows past `max_bytes`.
+///
+/// The partial body is dropped, so a server streaming without end costs at
+/// most `max_bytes` of memory.
+async fn read_body(mut response: reqwest::Response, max_bytes: usize) -> Result<Bytes, Error> {
+    let capacity = response.content_length().map_or(0, |len| len as usize).min(max_bytes);
+    let mut body = BytesMut::with_capacity(capacity);
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| Error::HttpError(format!("failed to read response: {}", e)))?
+    {
+        if body.len() + chunk.len() > max_bytes {
+            return Err(Error::FetchTooLarge(format!(
+                "body exceeds {} bytes; stopped reading",
+                max_bytes
+            )));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body.freeze())
+}
+
+/// Read at most `limit` bytes of a response body, keeping whatever arrived if reading fails.
+async fn read_prefix(mut response: reqwest::Response, limit: usize) -> Bytes {
+    let mut body = BytesMut::new();
+    while body.len() < limit
+        && let Ok(Some(chunk)) = response.chunk().await
+    {
+        body.extend_from_slice(&chunk[..chunk.len().min(limit - body.len())]);
+    }
+    body.freeze()
+}
+
+/// Where a redirect response points, resolved against the URL that sent it.
+fn redirect_target(response: &reqwest::Response) -> Option<Url> {
+    if !response.status().is_redirection() {
+        return None;
+    }
+    let location = response.headers().get(header::LOCATION)?.to_str().ok()?;
+    response.url().join(location).ok()
+}
+
+/// Message prefix naming a redirect hop; empty for the requested URL.
+fn hop_prefix(hop: usize) -> String {
+    if hop == 0 { String::new() } else { format!("redirect hop {hop} to ") }
+}
+
+/// Whether `a` and `b` share scheme, host, and (default-filled) port.
+fn same_origin(a: &Url, b: &Url) -> bool {
+    a.origin() == b.origin()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixture::{FixtureRequest, FixtureResponse, FixtureServer};
 
     #[test]
     fn test_fetch_config_default() {
@@ -204,6 +893,16 @@ mod tests {
         assert_eq!(config.timeout, Duration::from_millis(20000));
         assert_eq!(config.max_redirects, 5);
         assert!(config.respect_robots);
+        assert!(config.accept_language.is_none());
+        assert_eq!(config.circuit_failure_threshold, 3);
+        assert!(config.allowed_content_types.contains(&"text/html".to_string()));
+        assert_eq!(config.max_header_bytes, 16 * 1024);
+        assert_eq!(config.max_header_value_bytes, 2 * 1024);
+        assert_eq!(config.max_cooldown_wait, Duration::from_secs(5));
+        assert!(!config.allow_private_addresses);
+        assert_eq!(config.per_host_interval, Duration::from_millis(500));
+        assert_eq!(config.retries, 2);
+        assert_eq!(config.retry_backoff, Duration::from_millis(250));
     }
 
     #[test]
@@ -211,11 +910,20 @@ mod tests {
         let response = FetchResponse {
             url: Url::parse("https://example.com").unwrap(),
             final_url: Url::parse("https://example.com/redirected").unwrap(),
+            redirect_chain: vec![Url::parse("https://example.com").unwrap()],
             status: StatusCode::OK,
             content_type: Some("text/html".to_string()),
             bytes: Bytes::new(),
             headers: header::HeaderMap::new(),
+            stored_headers: Vec::new(),
             fetch_ms: 100,
+            attempts: 1,
+            cooldown_wait_ms: 0,
+            pacing_wait_ms: 0,
+            budget_wait_ms: 0,
+            inflight_bytes: 0,
+            body_fixups: Vec::new(),
+            reservation: None,
         };
 
         assert_eq!(response.url.as_str(), "https://example.com/");
@@ -225,10 +933,950 @@ mod tests {
         assert_eq!(response.fetch_ms, 100);
     }
 
+    #[test]
+    fn test_fetch_response_from_stored() {
+        let stored = vec![
+            ("etag".to_string(), "\"v1\"".to_string()),
+            ("bad header".to_string(), "x".to_string()),
+        ];
+        let response = FetchResponse::from_stored(
+            Url::parse("https://example.com/a").unwrap(),
+            Url::parse("https://example.com/b").unwrap(),
+            203,
+            Some("text/html".to_string()),
+            b"<p>hi</p>".to_vec(),
+            stored.clone(),
+        );
+
+        assert_eq!(response.status, StatusCode::NON_AUTHORITATIVE_INFORMATION);
+        assert_eq!(response.headers.get("etag").unwrap(), "\"v1\"");
+        assert_eq!(response.headers.len(), 1);
+        assert_eq!(response.stored_headers, stored);
+        assert_eq!(&response.bytes[..], b"<p>hi</p>");
+        assert_eq!(response.fetch_ms, 0);
+    }
+
     #[tokio::test]
     async fn test_fetch_client_new() {
         let config = FetchConfig::default();
         let client = FetchClient::new(config);
         assert!(client.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_fetch_sends_accept_language() {
+        let server = FixtureServer::start(|req| {
+            let lang = req.header("accept-language").unwrap_or("none").to_string();
+            FixtureResponse::html(format!("<p>{lang}</p>"))
+        })
+        .await;
+
+        let base = FetchConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = FetchClient::new(base.clone()).unwrap();
+        let response = client.fetch(&server.url("/")).await.unwrap();
+        assert_eq!(&response.bytes[..], b"<p>none</p>");
+
+        let config = FetchConfig { accept_language: Some("de-DE,de;q=0.9".to_string()), ..base };
+        let client = FetchClient::new(config).unwrap();
+        let response = client.fetch(&server.url("/")).await.unwrap();
+        assert_eq!(&response.bytes[..], b"<p>de-DE,de;q=0.9</p>");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_with_options_overrides_config_per_request() {
+        let server = FixtureServer::start(|req| match req.path.as_str() {
+            "/video" => FixtureResponse::ok("video/mp4", "not really a video"),
+            _ => FixtureResponse::html(format!(
+                "{}|{}",
+                req.header("user-agent").unwrap_or("none"),
+                req.header("accept-language").unwrap_or("none")
+            )),
+        })
+        .await;
+
+        let config = FetchConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = FetchClient::new(config).unwrap();
+        let options = RequestOptions {
+            user_agent: Some("Custom/1.0".to_string()),
+            accept_language: Some("fr".to_string()),
+            ..Default::default()
+        };
+
+        let response = client
+            .fetch_with_options(&server.url("/"), 1024, &options)
+            .await
+            .unwrap();
+        assert_eq!(&response.bytes[..], b"Custom/1.0|fr");
+        let response = client.fetch(&server.url("/")).await.unwrap();
+        assert_eq!(&response.bytes[..], b"mcp-web/0.1|none");
+
+        let err = client.fetch(&server.url("/video")).await.unwrap_err();
+        assert!(matches!(err, Error::UnsupportedContentType(_)));
+        let any = RequestOptions { any_content_type: true, ..Default::default() };
+        assert!(
+            client
+                .fetch_with_options(&server.url("/video"), 1024, &any)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_with_headers_sends_them_to_the_requested_host_only() {
+        let server = FixtureServer::start(|req| {
+            let port = req
+                .header("host")
+                .and_then(|h| h.rsplit(':').next())
+                .unwrap_or_default()
+                .to_string();
+            match req.path.as_str() {
+                "/away" => {
+                    FixtureResponse::status(302).with_header("Location", &format!("http://localhost:{port}/echo"))
+                }
+                _ => FixtureResponse::html(format!(
+                    "{}|{}|{}",
+                    req.header("authorization").unwrap_or("none"),
+                    req.header("cookie").unwrap_or("none"),
+                    req.header("accept").unwrap_or("none"),
+                )),
+            }
+        })
+        .await;
+
+        let config = FetchConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = FetchClient::new(config).unwrap();
+        let extra = request_headers(
+            [
+                ("Authorization", "Bearer t0ken"),
+                ("Cookie", "session=abc"),
+                ("Accept", "application/json"),
+            ],
+            false,
+        )
+        .unwrap();
+
+        let response = client.fetch_with_headers(&server.url("/echo"), &extra).await.unwrap();
+        assert_eq!(&response.bytes[..], b"Bearer t0ken|session=abc|application/json");
+
+        let response = client.fetch_with_headers(&server.url("/away"), &extra).await.unwrap();
+        assert_eq!(response.final_url.host_str(), Some("localhost"));
+        assert!(response.bytes.starts_with(b"none|none|"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_retries_transient_failures_only() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let server = {
+            let hits = hits.clone();
+            FixtureServer::start(move |req| {
+                let n = hits.fetch_add(1, Ordering::SeqCst);
+                match req.path.as_str() {
+                    "/flaky" if n == 0 => FixtureResponse::status(503),
+                    "/flaky" => FixtureResponse::html("ok"),
+                    "/missing" => FixtureResponse::status(404),
+                    _ => FixtureResponse::status(502),
+                }
+            })
+            .await
+        };
+
+        let config = FetchConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            per_host_interval: Duration::ZERO,
+            retry_backoff: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let client = FetchClient::new(config).unwrap();
+
+        let response = client.fetch(&server.url("/flaky")).await.unwrap();
+        assert_eq!(&response.bytes[..], b"ok");
+        assert_eq!(response.attempts, 2);
+        assert_eq!(hits.swap(0, Ordering::SeqCst), 2);
+
+        let err = client.fetch(&server.url("/missing")).await.unwrap_err();
+        assert!(err.to_string().contains("status 404"), "{err}");
+        assert_eq!(hits.swap(0, Ordering::SeqCst), 1, "4xx is never retried");
+
+        let err = client.fetch(&server.url("/down")).await.unwrap_err();
+        assert!(err.to_string().contains("status 502"), "{err}");
+        assert_eq!(
+            hits.swap(0, Ordering::SeqCst),
+            3,
+            "two retries, then the last 502 is returned"
+        );
+    }
+
+    /// Fixture that answers the first request with `status` + `Retry-After` and later ones with 200.
+    async fn rate_limited_server(
+        status: u16, retry_after: &'static str,
+    ) -> (FixtureServer, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let server = {
+            let hits = hits.clone();
+            FixtureServer::start(move |_| {
+                if hits.fetch_add(1, Ordering::SeqCst) == 0 {
+                    FixtureResponse::status(status).with_header("Retry-After", retry_after)
+                } else {
+                    FixtureResponse::html("ok")
+                }
+            })
+            .await
+        };
+        (server, hits)
+    }
+
+    #[tokio::test]
+    async fn test_redirect_to_another_origin_drops_extra_headers() {
+        let echo =
+            |req: &FixtureRequest| FixtureResponse::html(req.header("authorization").unwrap_or("none").to_string());
+        let other = FixtureServer::start(echo).await;
+        let target = other.url("/echo");
+        let server = FixtureServer::start(move |req| match req.path.as_str() {
+            "/away" => FixtureResponse::status(302).with_header("Location", &target),
+            "/here" => FixtureResponse::status(302).with_header("Location", "/echo"),
+            _ => echo(req),
+        })
+        .await;
+
+        let config = FetchConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = FetchClient::new(config).unwrap();
+        let extra = request_headers([("Authorization", "Bearer t0ken")], false).unwrap();
+
+        let response = client.fetch_with_headers(&server.url("/here"), &extra).await.unwrap();
+        assert_eq!(&response.bytes[..], b"Bearer t0ken");
+
+        // Same host, another port: a different origin.
+        let response = client.fetch_with_headers(&server.url("/away"), &extra).await.unwrap();
+        assert_eq!(response.final_url.as_str(), other.url("/echo"));
+        assert_eq!(&response.bytes[..], b"none");
+    }
+
+    #[test]
+    fn test_same_origin_compares_scheme_host_and_port() {
+        let url = |s: &str| Url::parse(s).unwrap();
+        assert!(same_origin(
+            &url("https://example.com/a"),
+            &url("https://example.com:443/b?q")
+        ));
+        assert!(!same_origin(
+            &url("https://example.com/a"),
+            &url("http://example.com/a")
+        ));
+        assert!(!same_origin(
+            &url("https://example.com/a"),
+            &url("https://example.com:8443/a")
+        ));
+        assert!(!same_origin(
+            &url("https://example.com/a"),
+            &url("https://www.example.com/a")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_refuses_challenge_served_with_error_status() {
+        let server = FixtureServer::start(|req| match req.path.as_str() {
+            "/challenge" => FixtureResponse::status(403)
+                .with_header("cf-mitigated", "challenge")
+                .with_header("Content-Type", "text/html"),
+            _ => FixtureResponse::status(403).with_header("Content-Type", "text/html"),
+        })
+        .await;
+        let config = FetchConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = FetchClient::new(config.clone()).unwrap();
+
+        let err = client.fetch(&server.url("/challenge")).await.unwrap_err();
+        assert!(matches!(&err, Error::BlockedByBotProtection(msg) if msg.contains("cf-mitigated")));
+
+        let err = client.fetch(&server.url("/forbidden")).await.unwrap_err();
+        assert!(matches!(&err, Error::HttpError(msg) if msg == "status 403"));
+
+        let client = FetchClient::new(FetchConfig { detect_bot_protection: false, ..config }).unwrap();
+        let err = client.fetch(&server.url("/challenge")).await.unwrap_err();
+        assert!(matches!(&err, Error::HttpError(msg) if msg == "status 403"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_waits_out_short_cooldown() {
+        use std::sync::atomic::Ordering;
+
+        let (server, hits) = rate_limited_server(429, "1").await;
+        let config = FetchConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = FetchClient::new(config).unwrap();
+
+        let err = client.fetch(&server.url("/")).await.unwrap_err();
+        assert!(matches!(err, Error::HttpError(_)));
+        assert!(client.host_state().cooldowns().remaining("127.0.0.1").is_some());
+
+        let start = Instant::now();
+        let response = client.fetch(&server.url("/")).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(900));
+        assert!(response.cooldown_wait_ms >= 900);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_honors_robots_crawl_delay() {
+        let server = FixtureServer::start(|req| match req.path.as_str() {
+            "/robots.txt" => FixtureResponse::ok("text/plain", "User-agent: *\nCrawl-delay: 1\n"),
+            _ => FixtureResponse::html("ok"),
+        })
+        .await;
+        let config = FetchConfig {
+            allow_private_addresses: true,
+            per_host_interval: Duration::from_millis(100),
+            ..Default::default()
+        };
+        let client = FetchClient::new(config).unwrap();
+
+        let first = client.fetch(&server.url("/one")).await.unwrap();
+        assert_eq!(first.pacing_wait_ms, 0);
+        let start = Instant::now();
+        let second = client.fetch(&server.url("/two")).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(900), "{:?}", start.elapsed());
+        assert!(second.pacing_wait_ms >= 900);
+
+        let unpaced = FetchClient::new(FetchConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            per_host_interval: Duration::ZERO,
+            ..Default::default()
+        })
+        .unwrap();
+        unpaced.fetch(&server.url("/one")).await.unwrap();
+        assert_eq!(unpaced.fetch(&server.url("/two")).await.unwrap().pacing_wait_ms, 0);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_fails_fast_on_long_cooldown() {
+        use std::sync::atomic::Ordering;
+
+        let (server, hits) = rate_limited_server(503, "120").await;
+        let config = FetchConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = FetchClient::new(config).unwrap();
+
+        assert!(client.fetch(&server.url("/")).await.is_err());
+
+        let start = Instant::now();
+        let err = client.fetch(&server.url("/")).await.unwrap_err();
+        assert!(matches!(err, Error::HostCoolingDown(_)));
+        assert!(err.to_string().contains("host 127.0.0.1 cooling down, retry after 1"));
+        assert!(start.elapsed() < Duration::from_millis(50));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        let shared = FetchClient::new(FetchConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            ..Default::default()
+        })
+        .unwrap()
+        .with_host_state(client.host_state().clone());
+        assert!(matches!(
+            shared.fetch(&server.url("/")).await.unwrap_err(),
+            Error::HostCoolingDown(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_circuit_breaker_fast_fail_and_recovery() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let healthy = Arc::new(AtomicBool::new(false));
+        let server = {
+            let (hits, healthy) = (hits.clone(), healthy.clone());
+            FixtureServer::start(move |_| {
+                hits.fetch_add(1, Ordering::SeqCst);
+                if healthy.load(Ordering::SeqCst) {
+                    FixtureResponse::html("ok")
+                } else {
+                    FixtureResponse::status(503)
+                }
+            })
+            .await
+        };
+
+        let config = FetchConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            circuit_cooldown: Duration::from_millis(200),
+            retries: 0,
+            ..Default::default()
+        };
+        let client = FetchClient::new(config).unwrap();
+
+        for _ in 0..3 {
+            let err = client.fetch(&server.url("/")).await.unwrap_err();
+            assert!(matches!(err, Error::HttpError(_)));
+        }
+
+        let start = Instant::now();
+        let err = client.fetch(&server.url("/")).await.unwrap_err();
+        assert!(matches!(err, Error::CircuitOpen(_)));
+        assert!(err.to_string().contains("host 127.0.0.1 circuit open"));
+        assert!(start.elapsed() < Duration::from_millis(50));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        healthy.store(true, Ordering::SeqCst);
+
+        assert!(client.fetch(&server.url("/")).await.is_ok());
+        assert_eq!(
+            client.circuit_breaker().state("127.0.0.1"),
+            CircuitState::Closed { failures: 0 }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_releases_probe_when_robots_deny() {
+        let server = FixtureServer::start(|req| match req.path.as_str() {
+            "/robots.txt" => FixtureResponse::ok("text/plain", "User-agent: *\nDisallow: /private\n"),
+            _ => FixtureResponse::html("<p>ok</p>"),
+        })
+        .await;
+
+        let config = FetchConfig {
+            allow_private_addresses: true,
+            circuit_failure_threshold: 1,
+            circuit_cooldown: Duration::ZERO,
+            per_host_interval: Duration::ZERO,
+            ..Default::default()
+        };
+        let client = FetchClient::new(config).unwrap();
+        client.circuit_breaker().record_failure("127.0.0.1");
+        assert_eq!(client.circuit_breaker().state("127.0.0.1"), CircuitState::HalfOpen);
+
+        let err = client.fetch(&server.url("/private")).await.unwrap_err();
+        assert!(matches!(err, Error::RobotsDisallowed(_)));
+
+        assert!(client.fetch(&server.url("/public")).await.is_ok());
+        assert_eq!(
+            client.circuit_breaker().state("127.0.0.1"),
+            CircuitState::Closed { failures: 0 }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_fetch_releases_probe() {
+        // Accepts connections but never answers, so the fetch is dropped mid-request.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                held.push(stream);
+            }
+        });
+
+        let config = FetchConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            circuit_failure_threshold: 1,
+            circuit_cooldown: Duration::ZERO,
+            ..Default::default()
+        };
+        let client = FetchClient::new(config).unwrap();
+        client.circuit_breaker().record_failure("127.0.0.1");
+
+        let fetch = client.fetch(&format!("http://{addr}/"));
+        assert!(tokio::time::timeout(Duration::from_millis(200), fetch).await.is_err());
+
+        let probe = client.circuit_breaker().check("127.0.0.1").unwrap();
+        assert!(probe.is_probe());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rejects_disallowed_content_type_before_body() {
+        let server = FixtureServer::start(|_| {
+            FixtureResponse::ok("video/mp4", vec![0u8; 4_900_000]).with_body_delay(Duration::from_secs(2))
+        })
+        .await;
+
+        let client = FetchClient::new(FetchConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let start = Instant::now();
+        let err = client.fetch(&server.url("/clip.mp4")).await.unwrap_err();
+
+        assert!(start.elapsed() < Duration::from_secs(1), "body should not be awaited");
+        assert!(matches!(err, Error::UnsupportedContentType(_)));
+        assert!(err.to_string().contains("video/mp4 (4900000 bytes)"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_empty_allowlist_allows_any_type() {
+        let server = FixtureServer::start(|_| FixtureResponse::ok("video/mp4", vec![0u8; 1024])).await;
+
+        let config = FetchConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            allowed_content_types: Vec::new(),
+            ..Default::default()
+        };
+        let client = FetchClient::new(config).unwrap();
+        let response = client.fetch(&server.url("/clip.mp4")).await.unwrap();
+        assert_eq!(response.bytes.len(), 1024);
+        assert_eq!(response.content_type.as_deref(), Some("video/mp4"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_denied_host_is_blocked() {
+        let server = FixtureServer::start(|_| FixtureResponse::html("<p>hi</p>")).await;
+
+        let config = FetchConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            domain_policy: DomainPolicy::new(&[], &["127.0.0.1".to_string()]),
+            ..Default::default()
+        };
+        let client = FetchClient::new(config).unwrap();
+        let err = client.fetch(&server.url("/")).await.unwrap_err();
+        assert!(matches!(err, Error::DomainBlocked(_)));
+        assert!(err.to_string().contains("127.0.0.1 is on the server denylist"));
+    }
+
+    #[tokio::test]
+    async fn test_probe_sends_head() {
+        let server = FixtureServer::start(|req| match req.method.as_str() {
+            "HEAD" => FixtureResponse::status(204),
+            _ => FixtureResponse::status(500),
+        })
+        .await;
+
+        let client = FetchClient::new(FetchConfig { allow_private_addresses: true, ..Default::default() }).unwrap();
+        assert_eq!(client.probe(&server.url("/")).await.unwrap(), StatusCode::NO_CONTENT);
+
+        let config =
+            FetchConfig { domain_policy: DomainPolicy::new(&[], &["127.0.0.1".to_string()]), ..Default::default() };
+        let client = FetchClient::new(config).unwrap();
+        let err = client.probe(&server.url("/")).await.unwrap_err();
+        assert!(matches!(err, Error::DomainBlocked(_)));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_redirect_to_denied_host_is_blocked() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let target_hits = Arc::new(AtomicUsize::new(0));
+        let hits = target_hits.clone();
+        let server = FixtureServer::start(move |req| {
+            let port = req
+                .header("host")
+                .and_then(|h| h.rsplit(':').next())
+                .unwrap_or_default()
+                .to_string();
+            match req.path.as_str() {
+                "/start" => {
+                    FixtureResponse::status(302).with_header("Location", &format!("http://localhost:{port}/target"))
+                }
+                _ => {
+                    hits.fetch_add(1, Ordering::SeqCst);
+                    FixtureResponse::html("<p>target</p>")
+                }
+            }
+        })
+        .await;
+
+        let config = FetchConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            domain_policy: DomainPolicy::new(&[], &["localhost".to_string()]),
+            ..Default::default()
+        };
+        let client = FetchClient::new(config).unwrap();
+        let err = client.fetch(&server.url("/start")).await.unwrap_err();
+        assert!(matches!(err, Error::DomainBlocked(_)));
+        assert_eq!(target_hits.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_records_redirect_chain() {
+        let server = FixtureServer::start(|req| match req.path.as_str() {
+            "/a" => FixtureResponse::status(301).with_header("Location", "/b#frag"),
+            "/b" => FixtureResponse::status(302).with_header("Location", "c"),
+            "/loop" => FixtureResponse::status(302).with_header("Location", "/loop"),
+            _ => FixtureResponse::html("<p>c</p>"),
+        })
+        .await;
+
+        let config = FetchConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = FetchClient::new(config.clone()).unwrap();
+        let response = client.fetch(&server.url("/a")).await.unwrap();
+        let chain: Vec<_> = response.redirect_chain.iter().map(Url::to_string).collect();
+        assert_eq!(chain, vec![server.url("/a"), server.url("/b")]);
+        assert_eq!(response.final_url.as_str(), server.url("/c"));
+
+        let direct = client.fetch(&server.url("/c")).await.unwrap();
+        assert!(direct.redirect_chain.is_empty());
+
+        let client = FetchClient::new(FetchConfig { max_redirects: 2, ..config }).unwrap();
+        let err = client.fetch(&server.url("/loop")).await.unwrap_err();
+        assert!(err.to_string().contains("too many redirects"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_checks_every_redirect_hop() {
+        let server = FixtureServer::start(|req| match req.path.as_str() {
+            "/file" => FixtureResponse::status(302).with_header("Location", "file:///etc/passwd"),
+            "/smtp" => FixtureResponse::status(302).with_header("Location", "/hop"),
+            "/hop" => FixtureResponse::status(302).with_header("Location", "http://example.com:25/"),
+            _ => FixtureResponse::html("<p>ok</p>"),
+        })
+        .await;
+
+        let client = FetchClient::new(FetchConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let err = client.fetch(&server.url("/file")).await.unwrap_err();
+        assert!(matches!(err, Error::SsrfBlocked(_)), "{err}");
+        assert!(
+            err.to_string().contains("redirect hop 1 to file:///etc/passwd"),
+            "{err}"
+        );
+
+        let err = client.fetch(&server.url("/smtp")).await.unwrap_err();
+        assert!(matches!(err, Error::SsrfBlocked(_)), "{err}");
+        assert!(
+            err.to_string().contains("redirect hop 2 to http://example.com:25/"),
+            "{err}"
+        );
+
+        // A public origin redirecting into a private range is stopped before the hop is requested.
+        let client = FetchClient::new(FetchConfig::default()).unwrap();
+        for target in ["http://10.0.0.5/internal", "http://[::1]/", "http://localhost/admin"] {
+            let err = client
+                .check_redirect(3, &Url::parse(target).unwrap())
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::SsrfBlocked(_)), "{target}: {err}");
+            assert!(err.to_string().contains("redirect hop 3 to"), "{err}");
+            assert!(err.to_string().contains("blocked IP"), "{err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redirect_hops_use_the_configured_url_options() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let long = format!("/doc?q={}", "a".repeat(200));
+        let doc_hits = Arc::new(AtomicUsize::new(0));
+        let server = {
+            let doc_hits = doc_hits.clone();
+            FixtureServer::start(move |req| match req.path.as_str() {
+                "/long" => FixtureResponse::status(302).with_header("Location", &long),
+                "/tracked" => FixtureResponse::status(302).with_header("Location", "/doc?utm_source=feed&id=1"),
+                _ => {
+                    doc_hits.fetch_add(1, Ordering::SeqCst);
+                    FixtureResponse::html("<p>ok</p>")
+                }
+            })
+            .await
+        };
+
+        let client = FetchClient::new(FetchConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            strip_tracking_params: true,
+            max_url_len: 128,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let err = client.fetch(&server.url("/long")).await.unwrap_err();
+        assert!(matches!(err, Error::InvalidUrl(_)), "{err}");
+        assert!(err.to_string().contains("redirect hop 1 to"), "{err}");
+        assert!(err.to_string().contains("byte limit"), "{err}");
+        assert_eq!(
+            doc_hits.load(Ordering::SeqCst),
+            0,
+            "the over-long hop is never requested"
+        );
+
+        let response = client.fetch(&server.url("/tracked")).await.unwrap();
+        assert_eq!(response.final_url.as_str(), server.url("/doc?id=1"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_blocked_port() {
+        let client = FetchClient::new(FetchConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let err = client.fetch("http://example.com:25/").await.unwrap_err();
+        assert!(matches!(err, Error::SsrfBlocked(_)));
+        assert!(err.to_string().contains("blocked port: 25"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rejects_private_addresses() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let server = {
+            let hits = hits.clone();
+            FixtureServer::start(move |_| {
+                hits.fetch_add(1, Ordering::SeqCst);
+                FixtureResponse::html("<p>internal</p>")
+            })
+            .await
+        };
+        let client = FetchClient::new(FetchConfig::default()).unwrap();
+
+        let err = client.fetch(&server.url("/")).await.unwrap_err();
+        assert!(matches!(err, Error::SsrfBlocked(_)), "{err}");
+        assert!(err.to_string().contains("blocked IP: 127.0.0.1"), "{err}");
+
+        let by_name = server.url("/").replace("127.0.0.1", "localhost");
+        let err = client.fetch(&by_name).await.unwrap_err();
+        assert!(matches!(err, Error::SsrfBlocked(_)), "{err}");
+
+        let err = client.probe(&server.url("/")).await.unwrap_err();
+        assert!(matches!(err, Error::SsrfBlocked(_)), "{err}");
+        assert_eq!(
+            hits.load(Ordering::SeqCst),
+            0,
+            "robots.txt and the page are never requested"
+        );
+
+        let err = client
+            .fetch("http://169.254.169.254/latest/meta-data/")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("169.254.169.254"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_resolver_refuses_private_connections() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let server = {
+            let hits = hits.clone();
+            FixtureServer::start(move |_| {
+                hits.fetch_add(1, Ordering::SeqCst);
+                FixtureResponse::html("<p>internal</p>")
+            })
+            .await
+        };
+
+        // Skip the pre-send check, as a redirect hop or a re-resolved name would:
+        // the connection itself is refused.
+        let client = FetchClient::new(FetchConfig::default()).unwrap();
+        let err = client
+            .http
+            .get(server.url("/").replace("127.0.0.1", "localhost"))
+            .send()
+            .await
+            .unwrap_err();
+        assert!(ssrf::blocked_ip(&err).is_some_and(|ip| ip.is_loopback()), "{err}");
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+        let allowed = FetchClient::new(FetchConfig { allow_private_addresses: true, ..Default::default() }).unwrap();
+        let by_name = server.url("/").replace("127.0.0.1", "localhost");
+        assert!(allowed.http.get(by_name).send().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_non_default_port_respects_robots() {
+        let server = FixtureServer::start(|req| match req.path.as_str() {
+            "/robots.txt" => FixtureResponse::ok("text/plain", "User-agent: *\nDisallow: /private\n"),
+            _ => FixtureResponse::html("<p>ok</p>"),
+        })
+        .await;
+
+        let client = FetchClient::new(FetchConfig { allow_private_addresses: true, ..Default::default() }).unwrap();
+        assert!(client.fetch(&server.url("/public")).await.is_ok());
+
+        let err = client.fetch(&server.url("/private")).await.unwrap_err();
+        assert!(matches!(err, Error::RobotsDisallowed(_)));
+        assert!(err.to_string().contains(&server.url("/robots.txt")));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_refuses_disallowed_url_on_warm_robots_cache() {
+        let server = FixtureServer::start(|req| match req.path.as_str() {
+            "/robots.txt" => FixtureResponse::ok("text/plain", "User-agent: *\nDisallow: /private\n"),
+            _ => FixtureResponse::html("<p>ok</p>"),
+        })
+        .await;
+
+        let client = FetchClient::new(FetchConfig { allow_private_addresses: true, ..Default::default() }).unwrap();
+        let err = client.fetch(&server.url("/private/a")).await.unwrap_err();
+        assert!(matches!(err, Error::RobotsDisallowed(_)), "{err}");
+
+        for path in ["/private/a", "/private/b"] {
+            let err = client.fetch(&server.url(path)).await.unwrap_err();
+            assert!(matches!(err, Error::RobotsDisallowed(_)), "{path}: {err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_robots_503_follows_failure_policy() {
+        let server = FixtureServer::start(|req| match req.path.as_str() {
+            "/robots.txt" => FixtureResponse::status(503),
+            _ => FixtureResponse::html("<p>ok</p>"),
+        })
+        .await;
+
+        let closed = FetchClient::new(FetchConfig { allow_private_addresses: true, ..Default::default() }).unwrap();
+        let err = closed.fetch(&server.url("/page")).await.unwrap_err();
+        assert!(matches!(err, Error::RobotsUnavailable(_)), "{err}");
+        assert!(err.to_string().contains(&server.url("/robots.txt")));
+
+        let open = FetchClient::new(FetchConfig {
+            allow_private_addresses: true,
+            robots_failure_policy: RobotsFailurePolicy::FailOpen,
+            ..Default::default()
+        })
+        .unwrap();
+        let response = open.fetch(&server.url("/page")).await.unwrap();
+        assert_eq!(response.status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_sanitizes_stored_headers() {
+        let server = FixtureServer::start(|_| {
+            let mut response = FixtureResponse::html("<p>hi</p>")
+                .with_header("ETag", "\"v1\"")
+                .with_raw_header("X-Latin1", b"caf\xe9")
+                .with_header("Set-Cookie", &format!("session={}", "a".repeat(8 * 1024)));
+            for i in 0..40 {
+                response = response.with_header("Link", &format!("</asset/{i}-{}.css>; rel=preload", "x".repeat(1000)));
+            }
+            response
+        })
+        .await;
+
+        let config = FetchConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = FetchClient::new(config.clone()).unwrap();
+        let response = client.fetch(&server.url("/")).await.unwrap();
+
+        assert_eq!(response.headers.get_all("link").iter().count(), 40);
+        assert_eq!(response.headers["etag"], "\"v1\"");
+
+        let stored = &response.stored_headers;
+        let value = |name: &str| stored.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str());
+        assert_eq!(value("etag"), Some("\"v1\""));
+        assert_eq!(value("x-latin1"), Some("caf\u{fffd}"));
+
+        let cookie = value("set-cookie").unwrap();
+        assert!(cookie.ends_with(headers::TRUNCATED_MARKER));
+        assert!(cookie.len() <= config.max_header_value_bytes + headers::TRUNCATED_MARKER.len());
+
+        let total: usize = stored.iter().map(|(n, v)| n.len() + v.len()).sum();
+        assert!(total <= config.max_header_bytes + 64);
+        assert!(value(headers::DROPPED_HEADER).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_inflates_unlabeled_gzip() {
+        use std::io::Write;
+
+        let html = "<html><body><p>compressed but unlabeled</p></body></html>";
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(html.as_bytes()).unwrap();
+        let gzipped = encoder.finish().unwrap();
+        let server = FixtureServer::start(move |_| FixtureResponse::html(gzipped.clone())).await;
+
+        let client = FetchClient::new(FetchConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            max_bytes: 4096,
+            ..Default::default()
+        })
+        .unwrap();
+        let response = client.fetch(&server.url("/")).await.unwrap();
+
+        assert_eq!(&response.bytes[..], html.as_bytes());
+        assert_eq!(response.body_fixups, vec![BodyFixup::GzipSniffed]);
+        // Reserved for the inflated size the cap allows, not the compressed length.
+        assert_eq!(response.inflight_bytes, 4096);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_aborts_endless_body_at_max_bytes() {
+        let server = FixtureServer::start(|_| FixtureResponse::html(vec![b'a'; 64 * 1024]).with_endless_body()).await;
+
+        let client = FetchClient::new(FetchConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            max_bytes: 256 * 1024,
+            ..Default::default()
+        })
+        .unwrap();
+        let start = Instant::now();
+        let err = client.fetch(&server.url("/endless")).await.unwrap_err();
+
+        assert!(matches!(err, Error::FetchTooLarge(_)), "{err}");
+        assert!(err.to_string().contains("body exceeds 262144 bytes"), "{err}");
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert_eq!(client.host_state().byte_budget().reserved(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_accepts_body_of_exactly_max_bytes() {
+        let server = FixtureServer::start(|_| FixtureResponse::html(vec![b'a'; 1000])).await;
+
+        let config =
+            FetchConfig { respect_robots: false, allow_private_addresses: true, max_bytes: 1000, ..Default::default() };
+        let client = FetchClient::new(config).unwrap();
+        assert_eq!(client.fetch(&server.url("/")).await.unwrap().bytes.len(), 1000);
+        assert!(matches!(
+            client.fetch_with_limit(&server.url("/"), 999).await.unwrap_err(),
+            Error::FetchTooLarge(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_reserves_byte_budget() {
+        let server = FixtureServer::start(|req| match req.path.as_str() {
+            "/stall" => FixtureResponse::html("<p>late</p>").with_body_delay(Duration::from_secs(5)),
+            _ => FixtureResponse::html("<p>ok</p>"),
+        })
+        .await;
+        let budget = ByteBudget::new(1024);
+        let client = FetchClient::new(FetchConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            timeout: Duration::from_millis(300),
+            ..Default::default()
+        })
+        .unwrap()
+        .with_host_state(HostState::default().with_byte_budget(budget.clone()));
+
+        let held = budget.reserve(1024, Duration::ZERO).await.unwrap();
+        let err = client.fetch(&server.url("/ok")).await.unwrap_err();
+        assert!(matches!(err, Error::FetchTimeout(_)), "{err}");
+        drop(held);
+
+        let response = client.fetch(&server.url("/ok")).await.unwrap();
+        assert_eq!(response.inflight_bytes, "<p>ok</p>".len());
+        let copy = response.clone();
+        drop(response);
+        assert_eq!(budget.reserved(), "<p>ok</p>".len(), "held while the response is alive");
+        drop(copy);
+        assert_eq!(budget.reserved(), 0, "released with the last copy");
+
+        assert!(client.fetch(&server.url("/stall")).await.is_err());
+        assert_eq!(budget.reserved(), 0, "released after a failed body read");
+    }
 }
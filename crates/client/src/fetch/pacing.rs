@@ -0,0 +1,94 @@
+//! Per-host politeness pacing.
+//!
+//! Requests to one host are spaced at least an interval apart (the larger of
+//! the configured per-host interval and the host's robots.txt `Crawl-delay`),
+//! while requests to different hosts never wait on each other. Each request
+//! reserves its slot under the lock and sleeps outside it, so concurrent
+//! fetches to one host queue up in order.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Hosts tracked before slots too old to delay anyone are dropped.
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// Shared, in-memory table of each host's last scheduled request.
+///
+/// Cloning is cheap and clones share state.
+#[derive(Debug, Clone, Default)]
+pub struct HostPacer {
+    last: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl HostPacer {
+    /// Wait until a request to `host` may go out, `interval` after the previous one.
+    ///
+    /// Returns how long this call slept.
+    pub async fn wait_turn(&self, host: &str, interval: Duration) -> Duration {
+        let wait = {
+            let mut last = self.last.lock().await;
+            let now = Instant::now();
+            if last.len() >= PRUNE_THRESHOLD {
+                last.retain(|_, at| *at + interval > now);
+            }
+            let at = last.get(host).map_or(now, |prev| (*prev + interval).max(now));
+            last.insert(host.to_string(), at);
+            at - now
+        };
+
+        if !wait.is_zero() {
+            tracing::debug!("pacing request to {}, waiting {:?}", host, wait);
+            tokio::time::sleep(wait).await;
+        }
+        wait
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_same_host_requests_are_spaced() {
+        let pacer = HostPacer::default();
+        let interval = Duration::from_millis(100);
+
+        let start = Instant::now();
+        assert_eq!(pacer.wait_turn("example.com", interval).await, Duration::ZERO);
+        pacer.wait_turn("example.com", interval).await;
+        pacer.wait_turn("example.com", interval).await;
+        assert!(start.elapsed() >= Duration::from_millis(200), "{:?}", start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_distinct_hosts_do_not_wait() {
+        let pacer = HostPacer::default();
+        let interval = Duration::from_secs(10);
+
+        for host in ["a.example", "b.example", "c.example"] {
+            assert_eq!(pacer.wait_turn(host, interval).await, Duration::ZERO);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_queue_in_order() {
+        let pacer = HostPacer::default();
+        let interval = Duration::from_millis(50);
+
+        let handles: Vec<_> = (0..3)
+            .map(|_| {
+                let pacer = pacer.clone();
+                tokio::spawn(async move { pacer.wait_turn("example.com", interval).await })
+            })
+            .collect();
+        let mut waits = Vec::new();
+        for handle in handles {
+            waits.push(handle.await.unwrap());
+        }
+        waits.sort();
+        assert_eq!(waits[0], Duration::ZERO);
+        assert!(waits[2] >= Duration::from_millis(90), "{waits:?}");
+    }
+}
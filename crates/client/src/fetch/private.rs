@@ -0,0 +1,137 @@
+//! Detection of personalized or authenticated responses.
+//!
+//! A page fetched with credentials, or one the origin marks as per-user, may
+//! show one session's data. Cached under the plain URL key it would be served
+//! to every later caller. [`private_content_reason`] says why a response must
+//! not be shared so callers can skip or partition the cache write.
+
+use reqwest::header::{AUTHORIZATION, CACHE_CONTROL, COOKIE, HeaderMap, PROXY_AUTHORIZATION, SET_COOKIE};
+use url::Url;
+
+/// Cookie name fragments that mark a session or login cookie; matched case-insensitively.
+const SESSION_COOKIE_MARKERS: &[&str] = &["sess", "auth", "token", "jwt", "login"];
+
+/// Cookie name fragments that look session-like but only carry a CSRF nonce.
+const CSRF_COOKIE_MARKERS: &[&str] = &["csrf", "xsrf"];
+
+/// Why a response is private to the requester, if it is.
+///
+/// Checked in order: credentials in the request URL (sent as an
+/// `Authorization` header) or an `Authorization`, `Proxy-Authorization` or
+/// `Cookie` header among the caller's `request_headers`; `Cache-Control:
+/// private`; and a `Set-Cookie` whose name looks like a session cookie
+/// (`sessionid`, `auth_token`, `PHPSESSID`, ...). CSRF cookies are not counted.
+pub fn private_content_reason(url: &Url, request_headers: &HeaderMap, headers: &HeaderMap) -> Option<&'static str> {
+    let credential_header = [AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE]
+        .iter()
+        .any(|name| request_headers.contains_key(name));
+    if credential_header || !url.username().is_empty() || url.password().is_some() {
+        return Some("request credentials");
+    }
+
+    let private = headers
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|directive| {
+            let name = directive.split('=').next().unwrap_or("").trim();
+            name.eq_ignore_ascii_case("private")
+        });
+    if private {
+        return Some("cache-control: private");
+    }
+
+    let session_cookie = headers
+        .get_all(SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(|v| is_session_cookie(v.split('=').next().unwrap_or("")));
+    session_cookie.then_some("session cookie")
+}
+
+fn is_session_cookie(name: &str) -> bool {
+    let name = name.trim().to_ascii_lowercase();
+    if name.is_empty() || CSRF_COOKIE_MARKERS.iter().any(|m| name.contains(m)) {
+        return false;
+    }
+    SESSION_COOKIE_MARKERS.iter().any(|m| name.contains(m)) || name.ends_with("sid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn reason(url: &str, headers: &[(&'static str, &str)]) -> Option<&'static str> {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.append(*name, HeaderValue::from_str(value).unwrap());
+        }
+        private_content_reason(&Url::parse(url).unwrap(), &HeaderMap::new(), &map)
+    }
+
+    fn request_reason(headers: &[(&'static str, &str)]) -> Option<&'static str> {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.append(*name, HeaderValue::from_str(value).unwrap());
+        }
+        private_content_reason(&Url::parse("https://example.com/").unwrap(), &map, &HeaderMap::new())
+    }
+
+    #[test]
+    fn test_request_credentials() {
+        assert_eq!(reason("https://user:pw@example.com/", &[]), Some("request credentials"));
+        assert_eq!(reason("https://token@example.com/", &[]), Some("request credentials"));
+        assert_eq!(reason("https://example.com/", &[]), None);
+    }
+
+    #[test]
+    fn test_request_credential_headers() {
+        assert_eq!(
+            request_reason(&[("authorization", "Bearer t")]),
+            Some("request credentials")
+        );
+        assert_eq!(request_reason(&[("Cookie", "session=1")]), Some("request credentials"));
+        assert_eq!(
+            request_reason(&[("proxy-authorization", "Basic x")]),
+            Some("request credentials")
+        );
+        assert_eq!(request_reason(&[("accept-language", "de"), ("x-trace", "1")]), None);
+    }
+
+    #[test]
+    fn test_cache_control_private() {
+        let url = "https://example.com/";
+        assert_eq!(
+            reason(url, &[("cache-control", "private, max-age=0")]),
+            Some("cache-control: private")
+        );
+        assert_eq!(
+            reason(url, &[("cache-control", "no-cache, Private=\"set-cookie\"")]),
+            Some("cache-control: private")
+        );
+        assert_eq!(reason(url, &[("cache-control", "public, max-age=600")]), None);
+    }
+
+    #[test]
+    fn test_session_cookies() {
+        let url = "https://example.com/";
+        for cookie in [
+            "sessionid=abc; HttpOnly",
+            "PHPSESSID=abc; path=/",
+            "connect.sid=s%3Aabc",
+            "auth_token=abc",
+            "remember_login=1",
+        ] {
+            assert_eq!(
+                reason(url, &[("set-cookie", cookie)]),
+                Some("session cookie"),
+                "{cookie}"
+            );
+        }
+        for cookie in ["csrftoken=abc", "XSRF-TOKEN=abc", "theme=dark", "_ga=GA1.2.3"] {
+            assert_eq!(reason(url, &[("set-cookie", cookie)]), None, "{cookie}");
+        }
+    }
+}
@@ -0,0 +1,493 @@
+//! Body sniffing for mislabeled responses.
+//!
+//! Some servers gzip a body without sending `Content-Encoding`, or serve a
+//! UTF-16 or Windows-1252 page as if it were UTF-8. Decoded naively these turn
+//! into mojibake, which extraction then caches as garbage markdown.
+//! [`unwrap_compressed`] inflates text bodies that start with gzip or zlib
+//! magic bytes, and [`decode_text`] re-decodes bodies whose UTF-8 reading is
+//! clearly wrong. Each correction is reported as a [`BodyFixup`].
+//!
+//! Bodies in a declared legacy encoding (Shift_JIS, windows-1251, GBK, ...)
+//! are decoded by [`decode_with_charset`], which reads the charset from the
+//! Content-Type, a BOM, or a `<meta charset>` tag.
+//!
+//! [`body_kind`] decides whether a body is HTML, JSON, plain text, or binary,
+//! from its Content-Type and its first bytes.
+
+use bytes::Bytes;
+use encoding_rs::{Encoding, REPLACEMENT, UTF_8, UTF_16BE, UTF_16LE};
+use std::io::Read;
+use thndrs_core::Error;
+
+use super::content_type::essence;
+
+const GZIP_MAGIC: [u8; 3] = [0x1f, 0x8b, 0x08];
+
+/// Signatures of binary formats that are never text, whatever their Content-Type says.
+const BINARY_MAGIC: &[&[u8]] = &[
+    b"\x89PNG",
+    b"\xff\xd8\xff",
+    b"GIF8",
+    b"%PDF-",
+    b"PK\x03\x04",
+    b"RIFF",
+    b"OggS",
+    b"\x00asm",
+    b"\x7fELF",
+];
+
+/// Leading bytes searched for a `<meta charset>` declaration, as browsers do.
+const META_PRESCAN_BYTES: usize = 1024;
+
+/// Invalid UTF-8 sequences tolerated before a re-decode is considered.
+const MIN_REPLACEMENTS: usize = 8;
+
+/// Characters 0x80..=0x9F in Windows-1252; the rest of the upper half matches Latin-1.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20ac}', '\u{81}', '\u{201a}', '\u{192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}', '\u{2c6}', '\u{2030}',
+    '\u{160}', '\u{2039}', '\u{152}', '\u{8d}', '\u{17d}', '\u{8f}', '\u{90}', '\u{2018}', '\u{2019}', '\u{201c}',
+    '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}', '\u{2dc}', '\u{2122}', '\u{161}', '\u{203a}', '\u{153}', '\u{9d}',
+    '\u{17e}', '\u{178}',
+];
+
+/// A correction applied to a response body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyFixup {
+    /// Gzip body served without `Content-Encoding`; inflated.
+    GzipSniffed,
+    /// Zlib body served without `Content-Encoding`; inflated.
+    ZlibSniffed,
+    /// Decoded as UTF-16 because of a byte order mark.
+    Utf16Bom,
+    /// Decoded as UTF-16 because of its NUL byte pattern.
+    Utf16Sniffed,
+    /// Invalid UTF-8 bytes decoded as Windows-1252 instead of U+FFFD.
+    Windows1252Redecoded,
+}
+
+impl BodyFixup {
+    /// Name shown in debug output, e.g. `gzip-sniffed`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::GzipSniffed => "gzip-sniffed",
+            Self::ZlibSniffed => "zlib-sniffed",
+            Self::Utf16Bom => "utf16-bom",
+            Self::Utf16Sniffed => "utf16-sniffed",
+            Self::Windows1252Redecoded => "windows-1252-redecoded",
+        }
+    }
+}
+
+/// What a response body is, for deciding how to present it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyKind {
+    /// HTML or XHTML; goes through readable extraction.
+    Html,
+    /// JSON; returned pretty-printed rather than extracted.
+    Json,
+    /// Plain text, markdown, XML, CSS, scripts, ...; returned as is.
+    Text,
+    /// Images, PDFs, archives, and other bytes that are not text.
+    Binary,
+}
+
+/// Classify a body by its Content-Type, sniffing its first bytes when the
+/// header is missing or unfamiliar.
+///
+/// Known binary signatures (PNG, JPEG, PDF, ZIP, ...) win over any header.
+/// Untyped bodies are HTML when they start with markup, JSON when they parse
+/// as JSON, binary when they hold NUL bytes that are not UTF-16, and text
+/// otherwise.
+pub fn body_kind(content_type: Option<&str>, bytes: &[u8]) -> BodyKind {
+    if BINARY_MAGIC.iter().any(|magic| bytes.starts_with(magic)) {
+        return BodyKind::Binary;
+    }
+
+    let essence = content_type.map(essence).unwrap_or_default();
+    let (main_type, subtype) = essence.split_once('/').unwrap_or_default();
+    match (main_type, subtype) {
+        ("text", "html") | ("application", "xhtml+xml") => return BodyKind::Html,
+        ("application", "json") => return BodyKind::Json,
+        (_, subtype) if subtype.ends_with("+json") => return BodyKind::Json,
+        ("image" | "audio" | "video" | "font", _) => return BodyKind::Binary,
+        ("application", "pdf" | "octet-stream" | "zip" | "gzip" | "x-gzip" | "x-tar" | "x-7z-compressed") => {
+            return BodyKind::Binary;
+        }
+        _ if is_textual(&essence) => return BodyKind::Text,
+        _ => {}
+    }
+
+    let head = bytes.strip_prefix(&[0xef, 0xbb, 0xbf]).unwrap_or(bytes);
+    let head = head.trim_ascii_start();
+    if head.starts_with(b"<") || bytes.starts_with(&[0xff, 0xfe]) || bytes.starts_with(&[0xfe, 0xff]) {
+        BodyKind::Html
+    } else if (head.starts_with(b"{") || head.starts_with(b"["))
+        && serde_json::from_slice::<serde::de::IgnoredAny>(head).is_ok()
+    {
+        BodyKind::Json
+    } else if sniff_utf16(bytes).is_some() {
+        BodyKind::Html
+    } else if bytes[..bytes.len().min(1024)].contains(&0) {
+        BodyKind::Binary
+    } else {
+        BodyKind::Text
+    }
+}
+
+/// A response body decoded to text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedText {
+    pub text: String,
+    /// WHATWG name of the encoding the body was read as, e.g. `Shift_JIS`
+    pub charset: &'static str,
+    /// Correction applied because the body was not what it claimed to be
+    pub fixup: Option<BodyFixup>,
+}
+
+/// Whether [`unwrap_compressed`] would try to inflate this body.
+pub fn looks_compressed(bytes: &[u8], content_type: Option<&str>) -> bool {
+    content_type.is_none_or(is_textual) && (bytes.starts_with(&GZIP_MAGIC) || is_zlib_header(bytes))
+}
+
+/// Inflate a text body that is still gzip- or zlib-compressed.
+///
+/// Bodies with a non-text Content-Type are left alone (an archive download is
+/// supposed to be compressed), as are bodies that merely look compressed but
+/// fail to inflate. The inflated body must fit in `max_bytes`.
+pub fn unwrap_compressed(
+    bytes: Bytes, content_type: Option<&str>, max_bytes: usize,
+) -> Result<(Bytes, Option<BodyFixup>), Error> {
+    if content_type.is_some_and(|ct| !is_textual(ct)) {
+        return Ok((bytes, None));
+    }
+
+    let inflated = if bytes.starts_with(&GZIP_MAGIC) {
+        inflate(flate2::read::MultiGzDecoder::new(&bytes[..]), max_bytes)?.map(|out| (out, BodyFixup::GzipSniffed))
+    } else if is_zlib_header(&bytes) {
+        inflate(flate2::read::ZlibDecoder::new(&bytes[..]), max_bytes)?.map(|out| (out, BodyFixup::ZlibSniffed))
+    } else {
+        None
+    };
+
+    Ok(match inflated {
+        Some((out, fixup)) => (Bytes::from(out), Some(fixup)),
+        None => (bytes, None),
+    })
+}
+
+/// Decode a body in the encoding it declares.
+///
+/// A byte order mark wins, then `charset` (usually the Content-Type
+/// parameter), then a `<meta charset>` or `http-equiv` tag in the first
+/// 1024 bytes. Unknown labels are ignored. UTF-8, declared or assumed, goes
+/// through [`decode_text`], since a UTF-8 label on a Latin-1 or UTF-16 body is
+/// the usual mislabeling.
+pub fn decode_with_charset(bytes: &[u8], charset: Option<&str>) -> DecodedText {
+    let declared = charset
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+        .or_else(|| meta_charset(bytes));
+    let (text, encoding, fixup) = match declared {
+        Some(encoding) if encoding != UTF_8 && encoding != REPLACEMENT && Encoding::for_bom(bytes).is_none() => (
+            encoding.decode_without_bom_handling(bytes).0.into_owned(),
+            encoding,
+            None,
+        ),
+        _ => decode_sniffed(bytes),
+    };
+    DecodedText { text, charset: encoding.name(), fixup }
+}
+
+/// Decode a body as text, falling back from UTF-8 when it is clearly something else.
+///
+/// UTF-16 is detected by BOM or by the NUL bytes ASCII markup leaves in every
+/// other position. A body with many invalid UTF-8 sequences keeps its valid
+/// runs and has the invalid bytes read as Windows-1252; a stray bad byte or
+/// two still becomes U+FFFD.
+pub fn decode_text(bytes: &[u8]) -> (String, Option<BodyFixup>) {
+    let (text, _, fixup) = decode_sniffed(bytes);
+    (text, fixup)
+}
+
+/// [`decode_text`], also reporting the encoding. A Windows-1252 re-decode
+/// reports UTF-8: the valid runs were read as UTF-8 and only the invalid bytes
+/// were patched, which re-running the decode reproduces.
+fn decode_sniffed(bytes: &[u8]) -> (String, &'static Encoding, Option<BodyFixup>) {
+    if let Some(rest) = bytes.strip_prefix(&[0xff, 0xfe]) {
+        return (
+            decode_utf16(rest, u16::from_le_bytes),
+            UTF_16LE,
+            Some(BodyFixup::Utf16Bom),
+        );
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xfe, 0xff]) {
+        return (
+            decode_utf16(rest, u16::from_be_bytes),
+            UTF_16BE,
+            Some(BodyFixup::Utf16Bom),
+        );
+    }
+    if let Some(little_endian) = sniff_utf16(bytes) {
+        let (decoded, encoding) = if little_endian {
+            (decode_utf16(bytes, u16::from_le_bytes), UTF_16LE)
+        } else {
+            (decode_utf16(bytes, u16::from_be_bytes), UTF_16BE)
+        };
+        return (decoded, encoding, Some(BodyFixup::Utf16Sniffed));
+    }
+
+    let bytes = bytes.strip_prefix(&[0xef, 0xbb, 0xbf]).unwrap_or(bytes);
+    let lossy = String::from_utf8_lossy(bytes);
+    let replaced = lossy.chars().filter(|c| *c == char::REPLACEMENT_CHARACTER).count();
+    if replaced < MIN_REPLACEMENTS || replaced * 200 < lossy.chars().count() {
+        return (lossy.into_owned(), UTF_8, None);
+    }
+
+    let mut out = String::with_capacity(bytes.len());
+    for chunk in bytes.utf8_chunks() {
+        out.push_str(chunk.valid());
+        out.extend(chunk.invalid().iter().map(|&b| windows_1252(b)));
+    }
+    (out, UTF_8, Some(BodyFixup::Windows1252Redecoded))
+}
+
+/// Encoding named by a `<meta charset>` or `<meta http-equiv="Content-Type">`
+/// tag near the top of the document.
+///
+/// A declared UTF-16 means UTF-8, as in browsers: the tag could only be read
+/// because the markup is ASCII-compatible.
+fn meta_charset(bytes: &[u8]) -> Option<&'static Encoding> {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(META_PRESCAN_BYTES)]).to_ascii_lowercase();
+    head.split("<meta")
+        .skip(1)
+        .find_map(|tag| {
+            let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+            let value = tag[tag.find("charset")? + "charset".len()..]
+                .trim_start()
+                .strip_prefix('=')?
+                .trim_start()
+                .trim_start_matches(['"', '\'']);
+            let end = value
+                .find(|c: char| matches!(c, '"' | '\'' | ';' | '/') || c.is_ascii_whitespace())
+                .unwrap_or(value.len());
+            Encoding::for_label(value[..end].as_bytes())
+        })
+        .map(|encoding| if encoding == UTF_16LE || encoding == UTF_16BE { UTF_8 } else { encoding })
+}
+
+fn is_textual(content_type: &str) -> bool {
+    let essence = essence(content_type);
+    essence.starts_with("text/")
+        || essence.ends_with("+xml")
+        || essence.ends_with("+json")
+        || matches!(
+            essence.as_str(),
+            "application/xml" | "application/json" | "application/javascript"
+        )
+}
+
+/// CMF/FLG check from RFC 1950, restricted to the 32K window every encoder uses.
+fn is_zlib_header(bytes: &[u8]) -> bool {
+    matches!(bytes, [0x78, flg, ..] if (0x7800 + *flg as u16) % 31 == 0)
+}
+
+fn inflate(reader: impl Read, max_bytes: usize) -> Result<Option<Vec<u8>>, Error> {
+    let mut out = Vec::new();
+    if reader.take(max_bytes as u64 + 1).read_to_end(&mut out).is_err() {
+        return Ok(None);
+    }
+    if out.len() > max_bytes {
+        return Err(Error::FetchTooLarge(format!(
+            "decompressed body exceeds {} bytes",
+            max_bytes
+        )));
+    }
+    Ok(Some(out))
+}
+
+/// Guess UTF-16 without a BOM: `Some(true)` for little-endian, `Some(false)` for big-endian.
+fn sniff_utf16(bytes: &[u8]) -> Option<bool> {
+    let sample = &bytes[..bytes.len().min(1024) & !1];
+    let pairs = sample.len() / 2;
+    if pairs < 4 {
+        return None;
+    }
+
+    let zeros_at = |offset: usize| sample.iter().skip(offset).step_by(2).filter(|b| **b == 0).count();
+    let (even, odd) = (zeros_at(0), zeros_at(1));
+    if odd * 10 >= pairs * 4 && even * 10 < pairs {
+        Some(true)
+    } else if even * 10 >= pairs * 4 && odd * 10 < pairs {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let units = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+fn windows_1252(byte: u8) -> char {
+    match byte {
+        0x80..=0x9f => WINDOWS_1252_HIGH[(byte - 0x80) as usize],
+        _ => byte as char,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn utf16(text: &str, little_endian: bool) -> Vec<u8> {
+        text.encode_utf16()
+            .flat_map(|u| if little_endian { u.to_le_bytes() } else { u.to_be_bytes() })
+            .collect()
+    }
+
+    #[test]
+    fn test_unwrap_compressed() {
+        let html = b"<html><body>hello</body></html>";
+
+        let (body, fixup) = unwrap_compressed(gzip(html).into(), Some("text/html"), 1024).unwrap();
+        assert_eq!(&body[..], html);
+        assert_eq!(fixup, Some(BodyFixup::GzipSniffed));
+
+        let (body, fixup) = unwrap_compressed(zlib(html).into(), None, 1024).unwrap();
+        assert_eq!(&body[..], html);
+        assert_eq!(fixup, Some(BodyFixup::ZlibSniffed));
+
+        let plain = Bytes::from_static(html);
+        assert_eq!(
+            unwrap_compressed(plain.clone(), Some("text/html"), 1024).unwrap(),
+            (plain, None)
+        );
+    }
+
+    #[test]
+    fn test_unwrap_compressed_leaves_archives_and_lookalikes() {
+        let archive = Bytes::from(gzip(b"data"));
+        let (body, fixup) = unwrap_compressed(archive.clone(), Some("application/gzip"), 1024).unwrap();
+        assert_eq!((body, fixup), (archive, None));
+
+        let lookalike = Bytes::from_static(b"x^2 + y^2 = z^2");
+        let (body, fixup) = unwrap_compressed(lookalike.clone(), Some("text/plain"), 1024).unwrap();
+        assert_eq!((body, fixup), (lookalike, None));
+    }
+
+    #[test]
+    fn test_unwrap_compressed_enforces_budget() {
+        let bomb = gzip(&vec![b'a'; 64 * 1024]);
+        let err = unwrap_compressed(bomb.into(), Some("text/html"), 1024).unwrap_err();
+        assert!(matches!(err, Error::FetchTooLarge(_)));
+    }
+
+    #[test]
+    fn test_decode_text_utf16() {
+        let text = "<p>Grüße</p>";
+
+        let mut bom = vec![0xff, 0xfe];
+        bom.extend(utf16(text, true));
+        assert_eq!(decode_text(&bom), (text.to_string(), Some(BodyFixup::Utf16Bom)));
+
+        let mut bom = vec![0xfe, 0xff];
+        bom.extend(utf16(text, false));
+        assert_eq!(decode_text(&bom), (text.to_string(), Some(BodyFixup::Utf16Bom)));
+
+        assert_eq!(
+            decode_text(&utf16(text, true)),
+            (text.to_string(), Some(BodyFixup::Utf16Sniffed))
+        );
+        assert_eq!(
+            decode_text(&utf16(text, false)),
+            (text.to_string(), Some(BodyFixup::Utf16Sniffed))
+        );
+    }
+
+    #[test]
+    fn test_decode_text_windows_1252() {
+        let mut body = "<p>Déjà vu — ".as_bytes().to_vec();
+        body.extend(b"caf\xe9 cr\xe8me br\xfbl\xe9e \x93quoted\x94 na\xefve fa\xe7ade \xe0 la carte");
+
+        let (text, fixup) = decode_text(&body);
+        assert_eq!(
+            text,
+            "<p>Déjà vu — café crème brûlée \u{201c}quoted\u{201d} naïve façade à la carte"
+        );
+        assert_eq!(fixup, Some(BodyFixup::Windows1252Redecoded));
+    }
+
+    #[test]
+    fn test_decode_with_charset_honors_declarations() {
+        let russian = "<title>Привет, мир</title>";
+        let cp1251 = encoding_rs::WINDOWS_1251.encode(russian).0;
+        let decoded = decode_with_charset(&cp1251, Some("windows-1251"));
+        assert_eq!((decoded.text.as_str(), decoded.charset), (russian, "windows-1251"));
+
+        let japanese = "<meta charset=\"shift_jis\"><title>こんにちは世界</title>";
+        let sjis = encoding_rs::SHIFT_JIS.encode(japanese).0;
+        let decoded = decode_with_charset(&sjis, None);
+        assert_eq!((decoded.text.as_str(), decoded.charset), (japanese, "Shift_JIS"));
+
+        let http_equiv = "<meta http-equiv=\"Content-Type\" content=\"text/html; charset=koi8-r\">мир";
+        let decoded = decode_with_charset(&encoding_rs::KOI8_R.encode(http_equiv).0, Some("bogus"));
+        assert_eq!((decoded.text.as_str(), decoded.charset), (http_equiv, "KOI8-R"));
+    }
+
+    #[test]
+    fn test_decode_with_charset_falls_back_to_sniffing() {
+        // A UTF-8 label on a Latin-1 body is still re-decoded.
+        let latin1 = b"caf\xe9 cr\xe8me br\xfbl\xe9e na\xefve fa\xe7ade \xe0 la carte d\xe9j\xe0";
+        let decoded = decode_with_charset(latin1, Some("utf-8"));
+        assert_eq!(decoded.fixup, Some(BodyFixup::Windows1252Redecoded));
+        assert_eq!(decoded.charset, "UTF-8");
+
+        // BOMs beat the label, and a UTF-8 BOM is dropped.
+        let mut bom = vec![0xff, 0xfe];
+        bom.extend(utf16("Grüße", true));
+        assert_eq!(decode_with_charset(&bom, Some("shift_jis")).charset, "UTF-16LE");
+        let decoded = decode_with_charset("\u{feff}Grüße".as_bytes(), Some("windows-1252"));
+        assert_eq!((decoded.text.as_str(), decoded.charset), ("Grüße", "UTF-8"));
+    }
+
+    #[test]
+    fn test_body_kind() {
+        assert_eq!(body_kind(Some("text/html; charset=utf-8"), b"hello"), BodyKind::Html);
+        assert_eq!(body_kind(Some("application/vnd.api+json"), b"{}"), BodyKind::Json);
+        assert_eq!(body_kind(Some("text/markdown"), b"# Title"), BodyKind::Text);
+        assert_eq!(body_kind(Some("application/rss+xml"), b"<rss/>"), BodyKind::Text);
+        assert_eq!(body_kind(Some("application/octet-stream"), b"text?"), BodyKind::Binary);
+        assert_eq!(body_kind(Some("text/html"), b"%PDF-1.7\n"), BodyKind::Binary);
+
+        // Untyped bodies are sniffed.
+        assert_eq!(body_kind(None, b"\n  <!doctype html>"), BodyKind::Html);
+        assert_eq!(body_kind(None, br#"{"ok": true}"#), BodyKind::Json);
+        assert_eq!(body_kind(None, b"{not json"), BodyKind::Text);
+        assert_eq!(body_kind(None, &utf16("<p>hi</p>", true)), BodyKind::Html);
+        assert_eq!(
+            body_kind(Some("application/x-unknown"), b"data\0\x01\x02"),
+            BodyKind::Binary
+        );
+    }
+
+    #[test]
+    fn test_decode_text_keeps_utf8_and_stray_bytes() {
+        assert_eq!(decode_text("Grüße".as_bytes()), ("Grüße".to_string(), None));
+        assert_eq!(decode_text(b"caf\xe9"), ("caf\u{fffd}".to_string(), None));
+    }
+}
@@ -2,7 +2,9 @@
 //!
 //! Validates that URLs and resolved IP addresses are not pointing to
 //! private, internal, or reserved addresses.
-use std::net::IpAddr;
+use std::net::{IpAddr, SocketAddr};
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
 
 /// Denied URL schemes that should never be fetched.
 pub const DENIED_SCHEMES: &[&str] = &[
@@ -28,8 +30,22 @@ pub enum SsrfError {
 
     #[error("DNS resolution failed: {0}")]
     DnsError(String),
+
+    #[error("blocked port: {0}")]
+    BlockedPort(u16),
 }
 
+/// Ports never fetched, following the Fetch standard's "bad port" list.
+///
+/// These belong to non-HTTP services (SSH, SMTP, IRC, ...) that an HTTP
+/// request could be smuggled into.
+pub const BLOCKED_PORTS: &[u16] = &[
+    1, 7, 9, 11, 13, 15, 17, 19, 20, 21, 22, 23, 25, 37, 42, 43, 53, 69, 77, 79, 87, 95, 101, 102, 103, 104, 109, 110,
+    111, 113, 115, 117, 119, 123, 135, 137, 139, 143, 161, 179, 389, 427, 465, 512, 513, 514, 515, 526, 530, 531, 532,
+    540, 548, 554, 556, 563, 587, 601, 636, 989, 990, 993, 995, 1719, 1720, 1723, 2049, 3659, 4045, 4190, 5060, 5061,
+    6000, 6566, 6665, 6666, 6667, 6668, 6669, 6679, 6697, 10080,
+];
+
 /// Check if an IP address is private, reserved, or otherwise blocked.
 ///
 /// This covers:
@@ -67,6 +83,69 @@ pub fn validate_ip(ip: IpAddr) -> Result<(), SsrfError> {
     if is_private_or_reserved(ip) { Err(SsrfError::BlockedIp(ip)) } else { Ok(()) }
 }
 
+/// Validate that an explicit port is not on the [`BLOCKED_PORTS`] list.
+pub fn validate_port(port: u16) -> Result<(), SsrfError> {
+    if BLOCKED_PORTS.contains(&port) { Err(SsrfError::BlockedPort(port)) } else { Ok(()) }
+}
+
+/// Resolve a host and validate that every resolved address is public.
+///
+/// IP literals are validated directly without a DNS lookup.
+pub async fn validate_host(host: &str, port: u16) -> Result<(), SsrfError> {
+    resolve_public(host, port).await.map(|_| ())
+}
+
+/// Resolve a host, returning its addresses only if every one of them is public.
+///
+/// IP literals are validated directly without a DNS lookup.
+pub async fn resolve_public(host: &str, port: u16) -> Result<Vec<SocketAddr>, SsrfError> {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        validate_ip(ip)?;
+        return Ok(vec![SocketAddr::new(ip, port)]);
+    }
+
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| SsrfError::DnsError(format!("{host}: {e}")))?
+        .collect();
+
+    for addr in &addrs {
+        validate_ip(addr.ip())?;
+    }
+
+    if addrs.is_empty() { Err(SsrfError::DnsError(format!("{host}: no addresses"))) } else { Ok(addrs) }
+}
+
+/// DNS resolver that refuses private or reserved answers.
+///
+/// Installed on the fetch client so the addresses that were validated are the
+/// ones connected to, for the first request and every redirect. A name that
+/// re-resolves to a private address after an earlier check still fails.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs = resolve_public(name.as_str(), 0).await?;
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// The private address a failed request was refused for, if [`PublicResolver`] refused it.
+pub fn blocked_ip(error: &reqwest::Error) -> Option<IpAddr> {
+    let mut source = std::error::Error::source(error);
+    while let Some(err) = source {
+        if let Some(SsrfError::BlockedIp(ip)) = err.downcast_ref::<SsrfError>() {
+            return Some(*ip);
+        }
+        source = err.source();
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,4 +241,29 @@ mod tests {
         assert!(validate_ip(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))).is_err());
         assert!(validate_ip(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))).is_err());
     }
+
+    #[tokio::test]
+    async fn test_validate_host_ip_literals() {
+        assert!(validate_host("127.0.0.1", 80).await.is_err());
+        assert!(validate_host("[::1]", 80).await.is_err());
+        assert!(validate_host("10.1.2.3", 443).await.is_err());
+        assert!(validate_host("93.184.216.34", 443).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_public_rejects_names_resolving_to_loopback() {
+        let err = resolve_public("localhost", 80).await.unwrap_err();
+        assert!(matches!(err, SsrfError::BlockedIp(ip) if ip.is_loopback()), "{err}");
+
+        let addrs = resolve_public("93.184.216.34", 8080).await.unwrap();
+        assert_eq!(addrs, vec!["93.184.216.34:8080".parse().unwrap()]);
+    }
+
+    #[test]
+    fn test_validate_port() {
+        assert!(matches!(validate_port(22), Err(SsrfError::BlockedPort(22))));
+        assert!(validate_port(25).is_err());
+        assert!(validate_port(8443).is_ok());
+        assert!(validate_port(8080).is_ok());
+    }
 }
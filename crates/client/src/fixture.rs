@@ -0,0 +1,275 @@
+//! Minimal HTTP fixture server for exercising the fetch pipeline in tests.
+//!
+//! Binds an ephemeral localhost port and answers every connection with the
+//! response produced by a handler closure. Each connection serves a single
+//! request and is then closed, which keeps request parsing trivial.
+//!
+//! Available in this crate's tests and to other crates via the `test-util` feature.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Upper bound on the request head the server will buffer.
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+
+/// Upper bound on the request body the server will buffer.
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// A request received by the fixture server.
+#[derive(Debug, Clone)]
+pub struct FixtureRequest {
+    /// Request method, e.g. `GET`
+    pub method: String,
+    /// Request target including any query string, e.g. `/page?x=1`
+    pub path: String,
+    /// Request headers keyed by lowercased name
+    pub headers: HashMap<String, String>,
+    /// Request body (read according to `Content-Length`)
+    pub body: Vec<u8>,
+}
+
+impl FixtureRequest {
+    /// Look up a header value by (case-insensitive) name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_ascii_lowercase()).map(String::as_str)
+    }
+}
+
+/// A response returned by a fixture handler.
+#[derive(Debug, Clone)]
+pub struct FixtureResponse {
+    /// HTTP status code
+    pub status: u16,
+    /// Extra response headers; `Content-Length` and `Connection` are added automatically
+    pub headers: Vec<(String, Vec<u8>)>,
+    /// Response body
+    pub body: Vec<u8>,
+    /// Pause between sending the head and the body
+    pub body_delay: Duration,
+    /// Repeat `body` until the client hangs up, with no `Content-Length`
+    pub endless: bool,
+}
+
+impl FixtureResponse {
+    /// A `200 OK` response with the given content type and body.
+    pub fn ok(content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("Content-Type".into(), content_type.as_bytes().to_vec())],
+            body: body.into(),
+            body_delay: Duration::ZERO,
+            endless: false,
+        }
+    }
+
+    /// A `200 OK` HTML response.
+    pub fn html(body: impl Into<Vec<u8>>) -> Self {
+        Self::ok("text/html; charset=utf-8", body)
+    }
+
+    /// An empty response with the given status code.
+    pub fn status(status: u16) -> Self {
+        Self { status, headers: Vec::new(), body: Vec::new(), body_delay: Duration::ZERO, endless: false }
+    }
+
+    /// Add a response header.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.with_raw_header(name, value.as_bytes())
+    }
+
+    /// Add a response header whose value is written byte-for-byte, e.g. to send non-UTF-8 values.
+    pub fn with_raw_header(mut self, name: &str, value: &[u8]) -> Self {
+        self.headers.push((name.to_string(), value.to_vec()));
+        self
+    }
+
+    /// Send the head immediately but hold the body back for `delay`.
+    pub fn with_body_delay(mut self, delay: Duration) -> Self {
+        self.body_delay = delay;
+        self
+    }
+
+    /// Stream `body` over and over until the client disconnects, like a hostile server.
+    pub fn with_endless_body(mut self) -> Self {
+        self.endless = true;
+        self
+    }
+}
+
+type Handler = Arc<dyn Fn(&FixtureRequest) -> FixtureResponse + Send + Sync>;
+
+/// A running fixture server; shuts down when dropped.
+pub struct FixtureServer {
+    addr: SocketAddr,
+    handle: JoinHandle<()>,
+}
+
+impl FixtureServer {
+    /// Start a server on `127.0.0.1` with an ephemeral port.
+    pub async fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&FixtureRequest) -> FixtureResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind fixture server");
+        let addr = listener.local_addr().expect("fixture server has no local address");
+        let handler: Handler = Arc::new(handler);
+
+        let handle = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve(stream, &handler).await {
+                        tracing::debug!("fixture connection failed: {}", e);
+                    }
+                });
+            }
+        });
+
+        Self { addr, handle }
+    }
+
+    /// Socket address the server is bound to.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Absolute URL for a path on this server, e.g. `url("/page")`.
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+}
+
+impl Drop for FixtureServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn serve(mut stream: TcpStream, handler: &Handler) -> std::io::Result<()> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let head_end = loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buf.len() > MAX_HEAD_BYTES {
+            return Err(std::io::Error::other("request head too large"));
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]);
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+
+    let mut request = FixtureRequest {
+        method: request_line.next().unwrap_or_default().to_string(),
+        path: request_line.next().unwrap_or_default().to_string(),
+        headers: lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+            .collect(),
+        body: buf[head_end + 4..].to_vec(),
+    };
+
+    let content_length = request
+        .header("content-length")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(0)
+        .min(MAX_BODY_BYTES);
+    while request.body.len() < content_length {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        request.body.extend_from_slice(&chunk[..n]);
+    }
+
+    let response = handler(&request);
+
+    let mut out = format!("HTTP/1.1 {} {}\r\n", response.status, reason_phrase(response.status)).into_bytes();
+    for (name, value) in &response.headers {
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(b": ");
+        out.extend_from_slice(value);
+        out.extend_from_slice(b"\r\n");
+    }
+    if !response.endless {
+        out.extend_from_slice(format!("Content-Length: {}\r\n", response.body.len()).as_bytes());
+    }
+    out.extend_from_slice(b"Connection: close\r\n\r\n");
+
+    stream.write_all(&out).await?;
+    if !response.body_delay.is_zero() {
+        stream.flush().await?;
+        tokio::time::sleep(response.body_delay).await;
+    }
+    if request.method != "HEAD" {
+        stream.write_all(&response.body).await?;
+        while response.endless && !response.body.is_empty() {
+            stream.write_all(&response.body).await?;
+        }
+    }
+    stream.shutdown().await
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        301 => "Moved Permanently",
+        302 => "Found",
+        304 => "Not Modified",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Status",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fixture_server_roundtrip() {
+        let server = FixtureServer::start(|req| {
+            FixtureResponse::ok("text/plain", format!("{} {}", req.method, req.path)).with_header("X-Fixture", "yes")
+        })
+        .await;
+
+        let response = reqwest::get(server.url("/hello?x=1")).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["x-fixture"], "yes");
+        assert_eq!(response.text().await.unwrap(), "GET /hello?x=1");
+    }
+
+    #[tokio::test]
+    async fn test_fixture_server_reads_body() {
+        let server =
+            FixtureServer::start(|req| FixtureResponse::ok("text/plain", format!("{} {}", req.method, req.body.len())))
+                .await;
+
+        let response = reqwest::Client::new()
+            .post(server.url("/hook"))
+            .body(vec![b'x'; 10_000])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "POST 10000");
+    }
+}
@@ -1,24 +1,56 @@
 //! Client code for mcp-web.
 //!
 //! This crate provides HTTP fetch pipeline, content extraction, and related
-//! functionality shared by the server and CLI.
+//! functionality shared by the server and CLI. [`WebPipeline`] combines fetch
+//! and extraction for embedders that want markdown from a URL in one call.
 
 pub mod brave;
+pub mod export;
 pub mod extract;
+pub mod feed;
 pub mod fetch;
+pub mod pipeline;
+pub mod search;
+pub mod sitemap;
+
+#[cfg(any(test, feature = "test-util"))]
+pub mod fixture;
+
+#[cfg(feature = "pdf")]
+pub mod pdf;
 
 #[cfg(feature = "render")]
 pub mod render;
 
 pub use brave::{
-    BraveClient, BraveConfig, BraveError, QueryMeta, SafeSearch, SearchRequest, SearchResponse, SearchResult,
+    BraveClient, BraveConfig, BraveError, DiscussionResult, FaqResult, ImageResult, ImageSearchRequest,
+    ImageSearchResponse, NewsResult, NewsSearchRequest, NewsSearchResponse, QueryMeta, RateLimitStatus,
+    RateLimitWindow, SafeSearch, SearchRequest, SearchResponse, SearchResult, Suggestion, VideoResult,
 };
+pub use export::{ExportConfig, ExportDoc, Exporter};
 pub use extract::{
-    ExtractConfig, ExtractedDoc, ExtractionResult, Extractor, LectitoExtractor, Link, extract_links, extract_readable,
-    normalize_markdown,
+    ArticleData, CodePass, ContentQuality, ExtractConfig, ExtractStrategy, ExtractedDoc, ExtractionResult, Extractor,
+    Heading, Image, LECTITO_EXTRACTOR_VERSION, LectitoExtractor, Link, LinkKind, PIPELINE_VERSION,
+    PLAIN_TEXT_EXTRACTOR_VERSION, PageMetadata, PlainTextExtractor, SiteConfig, SiteConfigRegistry,
+    SiteExtractOverrides, Table, TablePass, article_data, check_dom, check_selector, content_quality, estimate_tokens,
+    extract_images, extract_links, extract_outline, extract_readable, extract_structured_data, extract_with_fallback,
+    link_kind, mark_in_content, normalize_markdown, page_metadata, paginate, plain_outline, refresh_fetched_at,
+    run_with_budget, scope_to_section, scope_to_selector, truncate_to_tokens,
 };
 
-pub use fetch::{FetchClient, FetchConfig, FetchResponse};
+pub use feed::{FEED_EXTRACTOR_VERSION, Feed, FeedEntry, discover_feeds, is_feed, parse_feed};
+pub use fetch::{
+    CircuitBreaker, CircuitState, FetchClient, FetchConfig, FetchResponse, HostCooldowns, HostState, RequestOptions,
+};
+pub use pipeline::{Extraction, OpenMode, OpenOptions, PipelineResult, WebPipeline};
+pub use search::{SearchError, SearchProvider, SearxngClient, SearxngConfig};
+pub use sitemap::{Sitemap, SitemapEntry, SitemapKind};
+
+#[cfg(feature = "pdf")]
+pub use pdf::{PDF_EXTRACTOR_NAME, PDF_EXTRACTOR_VERSION, PdfDoc, extract_pdf_text};
 
 #[cfg(feature = "render")]
-pub use render::{HeadlessRenderer, RenderError, RenderOptions, RenderedPage, Renderer};
+pub use render::{
+    HeadlessRenderer, RenderError, RenderOptions, RenderedPage, Renderer, RendererConfig, RendererPool, RequestRules,
+    WaitUntil,
+};
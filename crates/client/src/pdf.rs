@@ -0,0 +1,164 @@
+//! PDF text extraction.
+//!
+//! Readable mode turns PDFs into markdown with one section per page, each
+//! introduced by a `<!-- page N -->` marker. The page count is read with lopdf
+//! before any text is extracted, so a document over the page cap is refused
+//! without doing the expensive part. Text comes from pdf-extract, which can
+//! panic on malformed files; a panic is reported as an extraction failure.
+//!
+//! Scanned PDFs have no text layer and come back as empty pages.
+
+use lopdf::{Document, Object};
+use thndrs_core::Error;
+
+/// Name recorded as `extractor_name` on snapshots of PDFs.
+pub const PDF_EXTRACTOR_NAME: &str = "pdf-extract";
+
+/// Version string of the PDF extractor.
+///
+/// Must match the version pdf-extract is pinned to in this crate's Cargo.toml.
+pub const PDF_EXTRACTOR_VERSION: &str = "pdf-extract@0.9.0";
+
+/// Text of a PDF document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PdfDoc {
+    /// `Title` from the document information dictionary
+    pub title: Option<String>,
+    /// Text of each page, in order
+    pub pages: Vec<String>,
+    /// Number of pages in the document
+    pub page_count: usize,
+}
+
+impl PdfDoc {
+    /// Markdown body with a `<!-- page N -->` marker before each page.
+    pub fn to_markdown(&self) -> String {
+        self.pages
+            .iter()
+            .enumerate()
+            .map(|(i, text)| format!("<!-- page {} -->\n\n{}", i + 1, tidy_page(text)))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// Whether a body is a PDF, by Content-Type or by its `%PDF-` signature.
+pub fn is_pdf(content_type: Option<&str>, bytes: &[u8]) -> bool {
+    bytes.starts_with(b"%PDF-")
+        || content_type.is_some_and(|ct| crate::fetch::content_type::essence(ct) == "application/pdf")
+}
+
+/// Extract the title and per-page text of a PDF.
+///
+/// Documents with more than `max_pages` pages fail with [`Error::ExtractFailed`].
+pub fn extract_pdf_text(bytes: &[u8], max_pages: Option<usize>) -> Result<PdfDoc, Error> {
+    let doc = Document::load_mem(bytes).map_err(|e| Error::ExtractFailed(format!("failed to parse PDF: {}", e)))?;
+    let page_count = doc.get_pages().len();
+    if let Some(max) = max_pages
+        && page_count > max
+    {
+        return Err(Error::ExtractFailed(format!(
+            "PDF has {} pages, more than the {} page limit",
+            page_count, max
+        )));
+    }
+
+    let pages = std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem_by_pages(bytes))
+        .map_err(|_| Error::ExtractFailed("PDF text extraction panicked".into()))?
+        .map_err(|e| Error::ExtractFailed(format!("PDF text extraction failed: {}", e)))?;
+
+    Ok(PdfDoc { title: info_title(&doc), pages, page_count })
+}
+
+/// `Title` from the trailer's `Info` dictionary, if it is set and not blank.
+fn info_title(doc: &Document) -> Option<String> {
+    let info = doc.trailer.get(b"Info").ok()?;
+    let (_, info) = doc.dereference(info).ok()?;
+    let title = info.as_dict().ok()?.get(b"Title").ok()?;
+    let (_, title) = doc.dereference(title).ok()?;
+    let title = match title {
+        Object::String(bytes, _) => text_string(bytes),
+        _ => return None,
+    };
+    Some(title.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+/// Decode a PDF text string: UTF-16BE or UTF-8 with a BOM, else PDFDocEncoding,
+/// which matches Latin-1 for the characters titles use.
+fn text_string(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        let units: Vec<u16> = utf16
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        return String::from_utf16_lossy(&units);
+    }
+    if let Some(utf8) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return String::from_utf8_lossy(utf8).into_owned();
+    }
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+/// Trim trailing spaces and collapse the runs of blank lines pdf-extract leaves.
+fn tidy_page(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut blank = 0;
+    for line in text.lines().map(str::trim_end) {
+        if line.is_empty() {
+            blank += 1;
+            continue;
+        }
+        if !out.is_empty() {
+            out.push_str(if blank > 0 { "\n\n" } else { "\n" });
+        }
+        out.push_str(line);
+        blank = 0;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &[u8] = include_bytes!("../fixtures/report.pdf");
+
+    #[test]
+    fn test_extract_pdf_text() {
+        let doc = extract_pdf_text(REPORT, None).unwrap();
+        assert_eq!(doc.title.as_deref(), Some("Quarterly Report"));
+        assert_eq!(doc.page_count, 2);
+        assert_eq!(doc.pages.len(), 2);
+        assert!(doc.pages[0].contains("First page of the quarterly report."));
+        assert!(doc.pages[1].contains("Second page with the conclusions."));
+
+        let markdown = doc.to_markdown();
+        assert!(markdown.starts_with("<!-- page 1 -->\n\n"));
+        let second = markdown.find("<!-- page 2 -->").unwrap();
+        assert!(markdown[..second].contains("First page"));
+        assert!(markdown[second..].contains("Second page"));
+    }
+
+    #[test]
+    fn test_page_cap_and_bad_input() {
+        match extract_pdf_text(REPORT, Some(1)) {
+            Err(Error::ExtractFailed(msg)) => assert_eq!(msg, "PDF has 2 pages, more than the 1 page limit"),
+            other => panic!("expected ExtractFailed, got {other:?}"),
+        }
+        assert!(matches!(
+            extract_pdf_text(b"%PDF-1.4\nnot really", None),
+            Err(Error::ExtractFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_is_pdf_and_text_strings() {
+        assert!(is_pdf(Some("application/PDF; qs=1"), b""));
+        assert!(is_pdf(Some("application/octet-stream"), REPORT));
+        assert!(!is_pdf(Some("text/html"), b"<html>"));
+
+        assert_eq!(text_string(b"\xFE\xFF\x00R\x00\xE9\x00s"), "Rés");
+        assert_eq!(text_string(b"Caf\xE9"), "Café");
+        assert_eq!(tidy_page("  a  \n\n\n\nb\nc\n\n"), "  a\n\nb\nc");
+    }
+}
@@ -0,0 +1,937 @@
+//! URL in, normalized markdown + links + metadata out.
+//!
+//! [`WebPipeline`] composes a [`FetchClient`] and an [`Extractor`] into the
+//! same fetch → decode → extract → normalize sequence the `web_open` tool runs,
+//! without MCP or SQLite. Callers that cache results can key them with
+//! [`cache_key`] over the [canonicalized](crate::fetch::canonicalize) URL.
+//!
+//! Only HTML goes through the extractor in readable mode: JSON comes back
+//! pretty-printed in `raw`, RSS and Atom feeds become a list of entries, plain
+//! text is wrapped as markdown as is, PDFs are read page by page with the `pdf`
+//! feature, and other binary bodies (images, archives) fail with
+//! [`Error::UnsupportedContentType`] in either mode.
+//!
+//! ```no_run
+//! use thndrs_client::{FetchClient, FetchConfig, OpenOptions, WebPipeline};
+//!
+//! # async fn run() -> Result<(), thndrs_core::Error> {
+//! let pipeline = WebPipeline::new(FetchClient::new(FetchConfig::default())?);
+//! let result = pipeline.open("https://example.com/docs", &OpenOptions::default()).await?;
+//!
+//! let extraction = result.extraction.expect("readable mode extracts");
+//! println!("{}", extraction.markdown);
+//! for link in &extraction.links {
+//!     println!("{} -> {}", link.text, link.href);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Instant;
+use thndrs_core::cache::FeedLink;
+use thndrs_core::cache::hash::{compute_cache_key, compute_content_hash};
+use thndrs_core::{Error, Mode};
+use url::Url;
+
+use crate::extract::{
+    ContentQuality, ExtractConfig, ExtractStrategy, ExtractedDoc, Extractor, Heading, Image, LectitoExtractor, Link,
+    LinkKind, PageMetadata, SiteConfigRegistry, Table, extract_structured_data, extract_with_fallback, link_kind,
+    normalize_markdown, run_with_budget,
+};
+use crate::feed::{FEED_EXTRACTOR_VERSION, discover_feeds, is_feed, parse_feed};
+use crate::fetch::{BodyKind, FetchClient, FetchResponse, RequestOptions, body_kind, charset, decode_with_charset};
+
+/// What [`WebPipeline::open`] produces from the fetched body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OpenMode {
+    /// Readable markdown extraction.
+    #[default]
+    Readable,
+    /// The decoded body, unextracted.
+    Raw,
+}
+
+impl OpenMode {
+    /// Mode name as used in cache keys and tool parameters.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Readable => "readable",
+            Self::Raw => "raw",
+        }
+    }
+}
+
+/// Per-call options for [`WebPipeline::open`].
+#[derive(Debug, Clone, Default)]
+pub struct OpenOptions {
+    /// Output mode (default: readable)
+    pub mode: OpenMode,
+
+    /// Body size limit overriding the fetch client's `max_bytes` (default: unset)
+    pub max_bytes: Option<usize>,
+
+    /// Extraction tuning (default: `ExtractConfig::default()`)
+    pub extract: ExtractConfig,
+
+    /// Timestamp recorded as `fetched_at` (default: unset, the current time)
+    pub fetched_at: Option<DateTime<Utc>>,
+
+    /// Per-request fetch overrides: timeout, User-Agent, headers (default: none)
+    pub request: RequestOptions,
+}
+
+/// Normalized extraction of a document.
+#[derive(Debug, Clone)]
+pub struct Extraction {
+    /// Page title
+    pub title: Option<String>,
+    /// Markdown with the normalized frontmatter header
+    pub markdown: String,
+    /// Harvested links
+    pub links: Vec<Link>,
+    /// Harvested images (HTML only)
+    pub images: Vec<Image>,
+    /// Tables too complex for markdown (HTML only)
+    pub tables: Vec<Table>,
+    /// Headings of the extracted content (HTML only)
+    pub outline: Vec<Heading>,
+    /// Word count, paragraphs, and link density of the content (HTML only)
+    pub quality: Option<ContentQuality>,
+    /// Feeds the page advertises (HTML only)
+    pub feeds: Vec<FeedLink>,
+    /// Version of the extractor that produced this
+    pub extractor_version: String,
+    /// How HTML content was found (HTML only); plain text means readability fell short
+    pub strategy: Option<ExtractStrategy>,
+    /// Id of the site config applied before extraction (HTML only)
+    pub siteconfig_id: Option<String>,
+    /// Metadata the page declares about itself (HTML only)
+    pub metadata: PageMetadata,
+    /// JSON-LD items the page embeds (HTML only)
+    pub structured_data: Vec<serde_json::Value>,
+    /// Extraction time in milliseconds
+    pub extract_ms: u64,
+}
+
+/// Everything a fetched document carries into a snapshot.
+#[derive(Debug, Clone)]
+pub struct PipelineResult {
+    /// The fetch response, including live and stored headers
+    pub response: FetchResponse,
+    /// When the document was fetched
+    pub fetched_at: DateTime<Utc>,
+    /// sha256 of the response body
+    pub content_hash: String,
+    /// `ETag` response header
+    pub etag: Option<String>,
+    /// `Last-Modified` response header
+    pub last_modified: Option<String>,
+    /// Encoding the body was decoded with; set it before [`WebPipeline::process`]
+    /// to override the Content-Type charset, e.g. for a body reloaded from a cache
+    pub charset: Option<String>,
+    /// Decoded body (raw mode, or pretty-printed JSON in readable mode)
+    pub raw: Option<String>,
+    /// Readable extraction (readable mode only)
+    pub extraction: Option<Extraction>,
+}
+
+impl PipelineResult {
+    /// Wrap a fetch response before any decoding or extraction.
+    pub fn new(response: FetchResponse, fetched_at: DateTime<Utc>) -> Self {
+        let header = |name: &str| {
+            response
+                .headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+
+        Self {
+            content_hash: compute_content_hash(&response.bytes),
+            etag: header("etag"),
+            last_modified: header("last-modified"),
+            response,
+            fetched_at,
+            charset: None,
+            raw: None,
+            extraction: None,
+        }
+    }
+}
+
+/// Fetch client plus extractor.
+///
+/// Extraction runs on the blocking pool under [`ExtractConfig::time_budget`].
+/// HTML is extracted with the site config for its final URL's host, if any
+/// (see [`WebPipeline::with_siteconfigs`]).
+pub struct WebPipeline<E = LectitoExtractor> {
+    fetch: Arc<FetchClient>,
+    extractor: Arc<E>,
+    siteconfigs: Arc<SiteConfigRegistry>,
+}
+
+impl WebPipeline<LectitoExtractor> {
+    /// Create a pipeline using the Lectito extractor.
+    pub fn new(fetch: FetchClient) -> Self {
+        Self::shared(Arc::new(fetch))
+    }
+
+    /// Create a pipeline over a fetch client other callers also use, sharing
+    /// its connection pool and robots.txt cache.
+    pub fn shared(fetch: Arc<FetchClient>) -> Self {
+        Self { fetch, extractor: Arc::new(LectitoExtractor::new()), siteconfigs: Arc::default() }
+    }
+}
+
+impl<E: Extractor + 'static> WebPipeline<E> {
+    /// Swap in a different extractor.
+    pub fn with_extractor<F: Extractor>(self, extractor: F) -> WebPipeline<F> {
+        WebPipeline { fetch: self.fetch, extractor: Arc::new(extractor), siteconfigs: self.siteconfigs }
+    }
+
+    /// Look up site configs for HTML in this registry, see [`SiteConfigRegistry::configure`].
+    pub fn with_siteconfigs(mut self, siteconfigs: Arc<SiteConfigRegistry>) -> Self {
+        self.siteconfigs = siteconfigs;
+        self
+    }
+
+    /// Get reference to the fetch client.
+    pub fn fetch_client(&self) -> &FetchClient {
+        &self.fetch
+    }
+
+    /// Get reference to the extractor.
+    pub fn extractor(&self) -> &E {
+        &self.extractor
+    }
+
+    /// Fetch a URL and decode or extract it according to `options.mode`.
+    pub async fn open(&self, url: &str, options: &OpenOptions) -> Result<PipelineResult, Error> {
+        let mut result = self.fetch(url, options).await?;
+        self.process(&mut result, options).await?;
+        Ok(result)
+    }
+
+    /// Fetch a URL without decoding or extracting it.
+    ///
+    /// Pair with [`WebPipeline::process`] when the caller may skip extraction,
+    /// e.g. because the body is unchanged since it was last extracted.
+    pub async fn fetch(&self, url: &str, options: &OpenOptions) -> Result<PipelineResult, Error> {
+        let max_bytes = options.max_bytes.unwrap_or(self.fetch.config().max_bytes);
+        let response = self.fetch.fetch_with_options(url, max_bytes, &options.request).await?;
+        Ok(PipelineResult::new(
+            response,
+            options.fetched_at.unwrap_or_else(Utc::now),
+        ))
+    }
+
+    /// Fill in `raw` or `extraction` for a fetched result according to
+    /// `options.mode` and what the body is (see [`body_kind`]).
+    pub async fn process(&self, result: &mut PipelineResult, options: &OpenOptions) -> Result<(), Error> {
+        let response = &result.response;
+        #[cfg(feature = "pdf")]
+        if options.mode == OpenMode::Readable && crate::pdf::is_pdf(response.content_type.as_deref(), &response.bytes) {
+            result.extraction = Some(extract_pdf(result, &options.extract).await?);
+            return Ok(());
+        }
+
+        let kind = body_kind(response.content_type.as_deref(), &response.bytes);
+        if kind == BodyKind::Binary {
+            return Err(Error::UnsupportedContentType(format!(
+                "{} is {} ({} bytes), not text",
+                response.final_url,
+                response.content_type.as_deref().unwrap_or("untyped binary data"),
+                response.bytes.len()
+            )));
+        }
+
+        let declared = result
+            .charset
+            .as_deref()
+            .or_else(|| response.content_type.as_deref().and_then(charset));
+        let decoded = decode_with_charset(&response.bytes, declared);
+        result.response.body_fixups.extend(decoded.fixup);
+        result.charset = Some(decoded.charset.to_string());
+        let body = decoded.text;
+        let json = (options.mode == OpenMode::Readable && kind == BodyKind::Json)
+            .then(|| pretty_json(&body))
+            .flatten();
+        match (options.mode, kind) {
+            (OpenMode::Raw, _) => result.raw = Some(body),
+            (OpenMode::Readable, _) if json.is_some() => result.raw = json,
+            (OpenMode::Readable, _) if is_feed(result.response.content_type.as_deref(), &result.response.bytes) => {
+                result.extraction = Some(feed_extraction(result)?);
+            }
+            (OpenMode::Readable, BodyKind::Html) => {
+                let extraction = extract_with_budget(
+                    self.extractor.clone(),
+                    body,
+                    result.response.final_url.clone(),
+                    result.fetched_at,
+                    self.siteconfigs.configure(&options.extract, &result.response.final_url),
+                )
+                .await?;
+                result.extraction = Some(extraction);
+            }
+            (OpenMode::Readable, _) => {
+                let doc =
+                    ExtractedDoc { title: None, markdown: body, extractor_version: self.extractor.version().into() };
+                result.extraction = Some(Extraction {
+                    title: None,
+                    markdown: normalize_markdown(&doc, &result.response.final_url, &result.fetched_at, None),
+                    links: Vec::new(),
+                    images: Vec::new(),
+                    tables: Vec::new(),
+                    outline: Vec::new(),
+                    quality: None,
+                    feeds: Vec::new(),
+                    extractor_version: doc.extractor_version,
+                    strategy: None,
+                    siteconfig_id: None,
+                    metadata: PageMetadata::default(),
+                    structured_data: Vec::new(),
+                    extract_ms: 0,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Extract and normalize an HTML document that was obtained some other way.
+    pub async fn extract(
+        &self, html: &str, final_url: &Url, fetched_at: &DateTime<Utc>, config: &ExtractConfig,
+    ) -> Result<Extraction, Error> {
+        extract_with_budget(
+            self.extractor.clone(),
+            html.to_string(),
+            final_url.clone(),
+            *fetched_at,
+            self.siteconfigs.configure(config, final_url),
+        )
+        .await
+    }
+}
+
+/// Run [`extract_document`] on the blocking pool, giving up after `config.time_budget`.
+pub async fn extract_with_budget(
+    extractor: Arc<dyn Extractor>, html: String, final_url: Url, fetched_at: DateTime<Utc>, config: ExtractConfig,
+) -> Result<Extraction, Error> {
+    run_with_budget(config.time_budget, move || {
+        extract_document(extractor.as_ref(), &html, &final_url, &fetched_at, &config)
+    })
+    .await
+}
+
+/// Read a PDF body's text on the blocking pool and normalize it as markdown.
+#[cfg(feature = "pdf")]
+async fn extract_pdf(result: &PipelineResult, config: &ExtractConfig) -> Result<Extraction, Error> {
+    let (bytes, max_pages) = (result.response.bytes.clone(), config.max_pdf_pages);
+    let start = Instant::now();
+    let pdf = run_with_budget(config.time_budget, move || {
+        crate::pdf::extract_pdf_text(&bytes, max_pages)
+    })
+    .await?;
+    let extract_ms = start.elapsed().as_millis() as u64;
+
+    let doc = ExtractedDoc {
+        title: pdf.title.clone(),
+        markdown: pdf.to_markdown(),
+        extractor_version: crate::pdf::PDF_EXTRACTOR_VERSION.to_string(),
+    };
+    Ok(Extraction {
+        title: pdf.title,
+        markdown: normalize_markdown(&doc, &result.response.final_url, &result.fetched_at, None),
+        links: Vec::new(),
+        images: Vec::new(),
+        tables: Vec::new(),
+        outline: Vec::new(),
+        quality: None,
+        feeds: Vec::new(),
+        extractor_version: doc.extractor_version,
+        strategy: None,
+        siteconfig_id: None,
+        metadata: PageMetadata::default(),
+        structured_data: Vec::new(),
+        extract_ms,
+    })
+}
+
+/// Parse a feed body and render its entries as markdown, linking each entry.
+///
+/// The feed parser reads the bytes as received, so an encoding named in the
+/// XML declaration is honored.
+fn feed_extraction(result: &PipelineResult) -> Result<Extraction, Error> {
+    let start = Instant::now();
+    let final_url = &result.response.final_url;
+    let feed = parse_feed(&result.response.bytes, final_url)?;
+    let extract_ms = start.elapsed().as_millis() as u64;
+
+    let links = feed
+        .entries
+        .iter()
+        .filter_map(|entry| {
+            let href = entry.link.clone()?;
+            let kind = Url::parse(&href).map_or(LinkKind::External, |url| link_kind(&url, final_url));
+            let text = entry.title.clone().unwrap_or_default();
+            Some(Link { text, href, kind, in_content: true })
+        })
+        .collect();
+    let doc = ExtractedDoc {
+        title: feed.title.clone(),
+        markdown: feed.to_markdown(),
+        extractor_version: FEED_EXTRACTOR_VERSION.to_string(),
+    };
+    Ok(Extraction {
+        title: feed.title,
+        markdown: normalize_markdown(&doc, final_url, &result.fetched_at, None),
+        links,
+        images: Vec::new(),
+        tables: Vec::new(),
+        outline: Vec::new(),
+        quality: None,
+        feeds: Vec::new(),
+        extractor_version: doc.extractor_version,
+        strategy: None,
+        siteconfig_id: None,
+        metadata: PageMetadata::default(),
+        structured_data: Vec::new(),
+        extract_ms,
+    })
+}
+
+/// Run an extractor over HTML and normalize its markdown.
+///
+/// See [`extract_with_fallback`] for how `config` picks between `extractor` and plain text.
+pub fn extract_document(
+    extractor: &dyn Extractor, html: &str, final_url: &Url, fetched_at: &DateTime<Utc>, config: &ExtractConfig,
+) -> Result<Extraction, Error> {
+    let start = Instant::now();
+    let result = extract_with_fallback(extractor, html, final_url, config)?;
+    let extract_ms = start.elapsed().as_millis() as u64;
+
+    let doc = ExtractedDoc {
+        title: result.title.clone(),
+        markdown: result.markdown,
+        extractor_version: result.extractor_version.clone(),
+    };
+    let markdown = normalize_markdown(&doc, final_url, fetched_at, result.siteconfig_id.as_deref());
+
+    Ok(Extraction {
+        title: result.title,
+        markdown,
+        links: result.links,
+        images: result.images,
+        tables: result.tables,
+        outline: result.outline,
+        quality: Some(result.quality),
+        feeds: discover_feeds(html, final_url),
+        structured_data: extract_structured_data(html),
+        extractor_version: result.extractor_version,
+        strategy: Some(result.strategy),
+        siteconfig_id: result.siteconfig_id,
+        metadata: result.metadata,
+        extract_ms,
+    })
+}
+
+/// Decode a response body as text in `charset`, if known (see [`decode_with_charset`]).
+pub fn decode_body(bytes: &[u8], charset: Option<&str>) -> String {
+    decode_with_charset(bytes, charset).text
+}
+
+/// Re-indent a JSON document for reading, keeping its key order.
+///
+/// Returns `None` when `text` is not valid JSON.
+pub fn pretty_json(text: &str) -> Option<String> {
+    serde_json::from_str::<serde::de::IgnoredAny>(text).ok()?;
+
+    let newline = |out: &mut String, depth: usize| {
+        out.push('\n');
+        out.extend(std::iter::repeat_n("  ", depth));
+    };
+    let mut out = String::with_capacity(text.len() * 2);
+    let (mut depth, mut in_string, mut escaped) = (0, false, false);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '{' | '[' => {
+                out.push(c);
+                while chars.next_if(|c| c.is_ascii_whitespace()).is_some() {}
+                match chars.next_if(|c| matches!(c, '}' | ']')) {
+                    Some(close) => out.push(close),
+                    None => {
+                        depth += 1;
+                        newline(&mut out, depth);
+                    }
+                }
+            }
+            '}' | ']' => {
+                depth -= 1;
+                newline(&mut out, depth);
+                out.push(c);
+            }
+            ',' => {
+                out.push(c);
+                newline(&mut out, depth);
+            }
+            ':' => out.push_str(": "),
+            c if c.is_ascii_whitespace() => {}
+            c => out.push(c),
+        }
+    }
+    Some(out)
+}
+
+/// Request properties that select a separate cached copy of a document.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheVary<'a> {
+    /// Accept header override.
+    pub accept: Option<&'a str>,
+    /// Accept-Language sent with the request.
+    pub language: Option<&'a str>,
+    /// Per-request User-Agent override.
+    pub user_agent: Option<&'a str>,
+    /// Caller-chosen partition that keeps personalized copies apart.
+    pub partition: Option<&'a str>,
+    /// Extra request headers as `(name, value)` pairs, in any order.
+    pub headers: &'a [(String, String)],
+}
+
+impl CacheVary<'_> {
+    /// The vary component of the cache key.
+    ///
+    /// Unset fields after `accept` are omitted so keys for requests without them are unchanged.
+    fn key(&self) -> String {
+        let mut vary = self.accept.unwrap_or("").to_string();
+        if let Some(lang) = self.language {
+            vary.push_str(&format!("\naccept-language:{lang}"));
+        }
+        if let Some(ua) = self.user_agent {
+            vary.push_str(&format!("\nuser-agent:{ua}"));
+        }
+        if let Some(partition) = self.partition {
+            vary.push_str(&format!("\ncache-partition:{partition}"));
+        }
+        let mut headers: Vec<_> = self
+            .headers
+            .iter()
+            .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.as_str()))
+            .collect();
+        headers.sort();
+        for (name, value) in headers {
+            vary.push_str(&format!("\nheader:{name}:{value}"));
+        }
+        vary
+    }
+}
+
+/// Cache key for a document.
+///
+/// `url` should come from [`canonicalize`](crate::fetch::canonicalize) so default ports, host case, and
+/// fragments don't split one document across several entries. `vary` holds the
+/// request headers and partition that change the response; `mode` is the output
+/// mode requested.
+pub fn cache_key(url: &Url, vary: &CacheVary, mode: Mode) -> String {
+    compute_cache_key(url.as_str(), &vary.key(), mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::ExtractionResult;
+    use crate::fetch::{BodyFixup, FetchConfig, canonicalize};
+    use crate::fixture::{FixtureResponse, FixtureServer};
+    use std::time::Duration;
+
+    fn article_html() -> String {
+        let paragraph = "<p>The pipeline fetches a page, extracts the readable part, and normalizes the markdown \
+                         so embedders get the same output as the web_open tool.</p>";
+        format!(
+            "<html><head><title>Pipeline</title></head><body><article>{}<a href=\"/next\">Next</a></article></body></html>",
+            paragraph.repeat(6)
+        )
+    }
+
+    fn pipeline() -> WebPipeline {
+        WebPipeline::new(
+            FetchClient::new(FetchConfig {
+                respect_robots: false,
+                allow_private_addresses: true,
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_open_readable() {
+        let server =
+            FixtureServer::start(|_| FixtureResponse::html(article_html()).with_header("ETag", "\"v1\"")).await;
+        let fetched_at = DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let options = OpenOptions { fetched_at: Some(fetched_at), ..Default::default() };
+
+        let result = pipeline().open(&server.url("/doc"), &options).await.unwrap();
+        assert_eq!(result.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(result.content_hash, compute_content_hash(article_html().as_bytes()));
+        assert!(result.raw.is_none());
+
+        let extraction = result.extraction.unwrap();
+        assert_eq!(extraction.title.as_deref(), Some("Pipeline"));
+        assert!(extraction.markdown.starts_with("---\n"));
+        assert!(extraction.markdown.contains("2025-01-01T00:00:00Z"));
+        assert!(extraction.markdown.contains("embedders get the same output"));
+        assert!(extraction.links.iter().any(|l| l.href == server.url("/next")));
+    }
+
+    #[tokio::test]
+    async fn test_open_raw() {
+        let server = FixtureServer::start(|_| FixtureResponse::ok("text/plain", b"caf\xe9".to_vec())).await;
+        let options = OpenOptions { mode: OpenMode::Raw, ..Default::default() };
+
+        let result = pipeline().open(&server.url("/plain"), &options).await.unwrap();
+        assert_eq!(result.raw.as_deref(), Some("caf\u{fffd}"));
+        assert!(result.extraction.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_open_respects_max_bytes() {
+        let server = FixtureServer::start(|_| FixtureResponse::html(article_html())).await;
+        let options = OpenOptions { max_bytes: Some(64), ..Default::default() };
+
+        let err = pipeline().open(&server.url("/doc"), &options).await.unwrap_err();
+        assert!(matches!(err, Error::FetchTooLarge(_)));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_then_process() {
+        let server = FixtureServer::start(|_| FixtureResponse::html(article_html())).await;
+        let pipeline = pipeline();
+        let options = OpenOptions::default();
+
+        let mut result = pipeline.fetch(&server.url("/doc"), &options).await.unwrap();
+        assert!(result.extraction.is_none());
+
+        pipeline.process(&mut result, &options).await.unwrap();
+        assert!(result.extraction.is_some());
+    }
+
+    struct SlowExtractor;
+
+    impl Extractor for SlowExtractor {
+        fn extract(&self, _html: &str, _base_url: &Url, _config: &ExtractConfig) -> Result<ExtractionResult, Error> {
+            std::thread::sleep(Duration::from_millis(500));
+            Err(Error::ExtractFailed("too late".into()))
+        }
+
+        fn version(&self) -> &str {
+            "slow@0"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extract_exceeding_time_budget_fails() {
+        let server = FixtureServer::start(|_| FixtureResponse::html(article_html())).await;
+        let pipeline = pipeline().with_extractor(SlowExtractor);
+        let options = OpenOptions {
+            extract: ExtractConfig { time_budget: Some(Duration::from_millis(50)), ..Default::default() },
+            ..Default::default()
+        };
+
+        let start = std::time::Instant::now();
+        let err = pipeline.open(&server.url("/doc"), &options).await.unwrap_err();
+        assert!(matches!(err, Error::ExtractFailed(ref msg) if msg == "extraction exceeded time budget"));
+        assert!(start.elapsed() < Duration::from_millis(450));
+    }
+
+    #[tokio::test]
+    async fn test_open_rejects_pathological_document() {
+        let deep = format!("<html><body>{}deep</body></html>", "<div>".repeat(100_000));
+        let server = FixtureServer::start(move |_| FixtureResponse::html(deep.clone())).await;
+
+        let err = pipeline()
+            .open(&server.url("/deep"), &OpenOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ExtractFailed(_)));
+    }
+
+    #[tokio::test]
+    async fn test_open_redecodes_mislabeled_bodies() {
+        let server = FixtureServer::start(|req| {
+            let text = "<p>Caf\u{e9} cr\u{e8}me br\u{fb}l\u{e9}e, na\u{ef}ve fa\u{e7}ade \u{e0} la carte, d\u{e9}j\u{e0} vu</p>";
+            match req.path.as_str() {
+                "/latin1" => FixtureResponse::ok(
+                    "text/plain; charset=utf-8",
+                    text.chars().map(|c| c as u8).collect::<Vec<u8>>(),
+                ),
+                _ => {
+                    let mut body = vec![0xff, 0xfe];
+                    body.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+                    FixtureResponse::ok("text/plain", body)
+                }
+            }
+        })
+        .await;
+        let pipeline = pipeline();
+        let options = OpenOptions { mode: OpenMode::Raw, ..Default::default() };
+
+        for (path, fixup) in [
+            ("/latin1", BodyFixup::Windows1252Redecoded),
+            ("/utf16", BodyFixup::Utf16Bom),
+        ] {
+            let result = pipeline.open(&server.url(path), &options).await.unwrap();
+            assert!(result.raw.unwrap().contains("Caf\u{e9} cr\u{e8}me"), "{path}");
+            assert_eq!(result.response.body_fixups, vec![fixup]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_open_presents_non_html_bodies_without_extraction() {
+        let server = FixtureServer::start(|req| match req.path.as_str() {
+            "/api" => FixtureResponse::ok("application/json", r#"{"name":"thndrs","tags":["a","b"],"meta":{}}"#),
+            "/notes.txt" => FixtureResponse::ok("text/plain", "Line one.\nLine *two*."),
+            "/untyped" => FixtureResponse { headers: Vec::new(), ..FixtureResponse::ok("", "[1, 2]") },
+            _ => FixtureResponse::ok("image/png", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec()),
+        })
+        .await;
+        let pipeline = pipeline();
+        let options = OpenOptions::default();
+
+        let json = pipeline.open(&server.url("/api"), &options).await.unwrap();
+        assert!(json.extraction.is_none());
+        assert_eq!(
+            json.raw.as_deref(),
+            Some("{\n  \"name\": \"thndrs\",\n  \"tags\": [\n    \"a\",\n    \"b\"\n  ],\n  \"meta\": {}\n}")
+        );
+        let untyped = pipeline.open(&server.url("/untyped"), &options).await.unwrap();
+        assert_eq!(untyped.raw.as_deref(), Some("[\n  1,\n  2\n]"));
+
+        let text = pipeline.open(&server.url("/notes.txt"), &options).await.unwrap();
+        assert!(text.raw.is_none());
+        let markdown = text.extraction.unwrap().markdown;
+        assert!(markdown.starts_with("---\n"));
+        assert!(markdown.contains("Line one.\nLine *two*."));
+
+        // Past the fetch allowlist, binary bodies are still refused in either mode.
+        let request = RequestOptions { any_content_type: true, ..Default::default() };
+        for mode in [OpenMode::Readable, OpenMode::Raw] {
+            let options = OpenOptions { mode, request: request.clone(), ..Default::default() };
+            match pipeline.open(&server.url("/logo"), &options).await {
+                Err(Error::UnsupportedContentType(msg)) => {
+                    assert!(msg.contains("image/png (16 bytes), not text"), "{msg}")
+                }
+                other => panic!("expected UnsupportedContentType, got {other:?}"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_open_lists_feed_entries_and_discovers_feeds() {
+        let rss = r#"<?xml version="1.0"?><rss version="2.0"><channel><title>Release Notes</title>
+            <item><title>v2</title><link>https://example.com/v2</link><description>Faster.</description></item>
+            <item><title>v1</title><link>https://example.com/v1</link></item>
+            </channel></rss>"#;
+        let page = article_html().replace(
+            "<title>",
+            r#"<link rel="alternate" type="application/rss+xml" title="Notes" href="/feed.xml"><title>"#,
+        );
+        let server = FixtureServer::start(move |req| match req.path.as_str() {
+            "/feed.xml" => FixtureResponse::ok("application/rss+xml", rss),
+            "/feed" => FixtureResponse { headers: Vec::new(), ..FixtureResponse::ok("", rss) },
+            _ => FixtureResponse::html(page.clone()),
+        })
+        .await;
+        let pipeline = pipeline();
+        let options = OpenOptions::default();
+
+        for path in ["/feed.xml", "/feed"] {
+            let result = pipeline.open(&server.url(path), &options).await.unwrap();
+            let extraction = result.extraction.unwrap();
+            assert_eq!(extraction.title.as_deref(), Some("Release Notes"));
+            assert_eq!(extraction.extractor_version, FEED_EXTRACTOR_VERSION);
+            assert!(
+                extraction
+                    .markdown
+                    .contains("## [v2](https://example.com/v2)\n\nFaster.")
+            );
+            let hrefs: Vec<&str> = extraction.links.iter().map(|l| l.href.as_str()).collect();
+            assert_eq!(hrefs, ["https://example.com/v2", "https://example.com/v1"]);
+        }
+
+        let page = pipeline.open(&server.url("/post"), &options).await.unwrap();
+        let feeds = page.extraction.unwrap().feeds;
+        assert_eq!(feeds.len(), 1);
+        assert_eq!(feeds[0].href, server.url("/feed.xml"));
+        assert_eq!(feeds[0].title.as_deref(), Some("Notes"));
+    }
+
+    #[cfg(feature = "pdf")]
+    #[tokio::test]
+    async fn test_open_reads_pdf_text_in_readable_mode() {
+        let server = FixtureServer::start(|_| {
+            FixtureResponse::ok("application/pdf", include_bytes!("../fixtures/report.pdf").to_vec())
+        })
+        .await;
+        let pipeline = pipeline();
+
+        let result = pipeline
+            .open(&server.url("/report.pdf"), &OpenOptions::default())
+            .await
+            .unwrap();
+        let extraction = result.extraction.unwrap();
+        assert_eq!(extraction.title.as_deref(), Some("Quarterly Report"));
+        assert_eq!(extraction.extractor_version, crate::pdf::PDF_EXTRACTOR_VERSION);
+        assert!(extraction.markdown.starts_with("---\ntitle: Quarterly Report\n"));
+        assert!(extraction.markdown.contains("<!-- page 2 -->"));
+        assert!(extraction.markdown.contains("Second page with the conclusions."));
+
+        let capped = OpenOptions {
+            extract: ExtractConfig { max_pdf_pages: Some(1), ..Default::default() },
+            ..Default::default()
+        };
+        let err = pipeline.open(&server.url("/report.pdf"), &capped).await.unwrap_err();
+        assert!(matches!(err, Error::ExtractFailed(msg) if msg.contains("more than the 1 page limit")));
+
+        let raw = OpenOptions { mode: OpenMode::Raw, ..Default::default() };
+        let err = pipeline.open(&server.url("/report.pdf"), &raw).await.unwrap_err();
+        assert!(matches!(err, Error::UnsupportedContentType(_)));
+    }
+
+    #[test]
+    fn test_pretty_json_keeps_key_order_and_strings() {
+        assert_eq!(
+            pretty_json(r#" {"b":1, "a":[ ], "s":"x,{\"y\": [1]}"} "#).as_deref(),
+            Some("{\n  \"b\": 1,\n  \"a\": [],\n  \"s\": \"x,{\\\"y\\\": [1]}\"\n}")
+        );
+        assert_eq!(pretty_json("{\"unterminated\": "), None);
+    }
+
+    #[tokio::test]
+    async fn test_open_decodes_declared_charsets() {
+        let page = |head: &str| article_html().replace("<title>Pipeline</title>", head);
+        let server = FixtureServer::start(move |req| match req.path.as_str() {
+            "/cp1251" => FixtureResponse::ok(
+                "text/html; charset=windows-1251",
+                encoding_rs::WINDOWS_1251
+                    .encode(&page("<title>Новости дня</title>"))
+                    .0
+                    .into_owned(),
+            ),
+            _ => FixtureResponse::ok(
+                "text/html",
+                encoding_rs::SHIFT_JIS
+                    .encode(&page("<meta charset=\"Shift_JIS\"><title>今日のニュース</title>"))
+                    .0
+                    .into_owned(),
+            ),
+        })
+        .await;
+        let pipeline = pipeline();
+
+        for (path, title, charset) in [
+            ("/cp1251", "Новости дня", "windows-1251"),
+            ("/sjis", "今日のニュース", "Shift_JIS"),
+        ] {
+            let result = pipeline.open(&server.url(path), &OpenOptions::default()).await.unwrap();
+            assert_eq!(result.extraction.unwrap().title.as_deref(), Some(title), "{path}");
+            assert_eq!(result.charset.as_deref(), Some(charset));
+            assert!(result.response.body_fixups.is_empty());
+        }
+    }
+
+    fn key(url: &str) -> String {
+        cache_key(&canonicalize(url).unwrap(), &CacheVary::default(), Mode::Readable)
+    }
+
+    #[test]
+    fn test_cache_key_varies_by_language() {
+        let url = canonicalize("https://example.com").unwrap();
+        let en = cache_key(
+            &url,
+            &CacheVary { language: Some("en"), ..Default::default() },
+            Mode::Readable,
+        );
+        let de = cache_key(
+            &url,
+            &CacheVary { language: Some("de"), ..Default::default() },
+            Mode::Readable,
+        );
+        let unset = cache_key(&url, &CacheVary::default(), Mode::Readable);
+
+        assert_ne!(en, de);
+        assert_ne!(en, unset);
+        assert_eq!(unset, compute_cache_key("https://example.com/", "", Mode::Readable));
+    }
+
+    #[test]
+    fn test_cache_key_varies_by_user_agent_and_partition() {
+        let url = canonicalize("https://example.com").unwrap();
+        let en = CacheVary { language: Some("en"), ..Default::default() };
+        let browser = cache_key(
+            &url,
+            &CacheVary { user_agent: Some("Mozilla/5.0"), ..en },
+            Mode::Readable,
+        );
+        let other = cache_key(&url, &CacheVary { user_agent: Some("curl/8.0"), ..en }, Mode::Readable);
+        let alice = cache_key(&url, &CacheVary { partition: Some("alice"), ..en }, Mode::Readable);
+        let bob = cache_key(&url, &CacheVary { partition: Some("bob"), ..en }, Mode::Readable);
+        let plain = cache_key(&url, &en, Mode::Readable);
+
+        assert_ne!(browser, other);
+        assert_ne!(browser, plain);
+        assert_ne!(alice, bob);
+        assert_ne!(alice, plain);
+        assert_eq!(
+            plain,
+            compute_cache_key("https://example.com/", "\naccept-language:en", Mode::Readable)
+        );
+    }
+
+    #[test]
+    fn test_cache_key_varies_by_extra_headers() {
+        let url = canonicalize("https://example.com").unwrap();
+        let pairs = |p: &[(&str, &str)]| -> Vec<(String, String)> {
+            p.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect()
+        };
+        let alice = pairs(&[("Authorization", "Bearer a"), ("Cookie", "s=1")]);
+        let reordered = pairs(&[("cookie", "s=1"), ("AUTHORIZATION", "Bearer a")]);
+        let bob = pairs(&[("Authorization", "Bearer b"), ("Cookie", "s=1")]);
+        let key = |headers: &[(String, String)]| {
+            cache_key(&url, &CacheVary { headers, ..Default::default() }, Mode::Readable)
+        };
+
+        assert_eq!(key(&alice), key(&reordered));
+        assert_ne!(key(&alice), key(&bob));
+        assert_ne!(key(&alice), key(&[]));
+        assert_eq!(key(&[]), compute_cache_key("https://example.com/", "", Mode::Readable));
+    }
+
+    #[test]
+    fn test_cache_key_uses_canonical_url() {
+        let plain = key("https://example.com/page");
+        assert_eq!(plain, key("Example.COM/page#frag"));
+        assert_eq!(plain, key("example.com/page"));
+        assert_eq!(plain, key("https://EXAMPLE.com:443/page"));
+        assert_eq!(key("http://example.com/page"), key("http://example.com:80/page"));
+        assert_ne!(plain, key("https://example.com:8443/page"));
+        assert_ne!(plain, key("http://example.com/page"));
+    }
+}
@@ -2,15 +2,32 @@
 //!
 //! This module provides a feature-gated renderer trait and implementation
 //! using chromiumoxide for headless Chrome/Chromium browser control.
-
+//! [`RendererPool`] shares one browser and a few reusable pages across calls.
+//!
+//! Every request a page makes, its own navigation included, is paused and
+//! checked before the browser sends it: only http(s) to a host the domain
+//! policy allows, on a port outside [`BLOCKED_PORTS`], and (unless private
+//! addresses are allowed) resolving only to public addresses. This keeps the
+//! browser behind the same SSRF gates as the fetch pipeline.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 use thiserror::Error;
+use thndrs_core::{DomainDecision, DomainPolicy, RenderSettings};
 use url::Url;
 
+use crate::fetch::ssrf::{BLOCKED_PORTS, DENIED_SCHEMES, resolve_public};
+
+mod pool;
+
+pub use pool::{DEFAULT_MAX_PAGES, RendererPool};
+
 /// Errors that can occur during page rendering.
 #[derive(Debug, Error)]
 pub enum RenderError {
-    /// Failed to launch or connect to browser.
+    /// Failed to launch or connect to browser; includes the browser's stderr when it wrote any.
     #[error("browser launch failed: {0}")]
     BrowserLaunch(String),
 
@@ -30,27 +47,126 @@ pub enum RenderError {
     #[error("wait_for selector not found: {0}")]
     SelectorNotFound(String),
 
+    /// Wait selector is not valid CSS.
+    #[error("wait_for selector is not valid CSS: {0}")]
+    InvalidSelector(String),
+
     /// Browser closed unexpectedly.
     #[error("browser closed unexpectedly")]
     BrowserClosed,
 }
 
+/// How to launch the browser.
+#[derive(Debug, Clone)]
+pub struct RendererConfig {
+    /// Run without a window (default: true), using Chrome's new headless mode.
+    pub headless: bool,
+
+    /// Chrome/Chromium binary; detected on PATH and in the usual install locations when unset.
+    pub chrome_path: Option<PathBuf>,
+
+    /// Extra command-line flags, e.g. `--no-sandbox` for containers.
+    pub extra_args: Vec<String>,
+
+    /// Pages a [`RendererPool`] keeps open at once (default: 4).
+    pub max_pages: usize,
+}
+
+impl Default for RendererConfig {
+    fn default() -> Self {
+        Self { headless: true, chrome_path: None, extra_args: Vec::new(), max_pages: DEFAULT_MAX_PAGES }
+    }
+}
+
+impl From<&RenderSettings> for RendererConfig {
+    fn from(settings: &RenderSettings) -> Self {
+        Self {
+            headless: settings.headless,
+            chrome_path: settings.chrome_path.clone(),
+            extra_args: settings.extra_args.clone(),
+            max_pages: settings.max_pages,
+        }
+    }
+}
+
+/// How often [`RenderOptions::wait_for`] is checked.
+const SELECTOR_POLL: Duration = Duration::from_millis(50);
+
+/// Page lifecycle event a render waits for before capturing HTML.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WaitUntil {
+    /// The `load` event: the document and its subresources have loaded (default).
+    #[default]
+    Load,
+    /// `DOMContentLoaded`: the document is parsed; images and late scripts may still be loading.
+    DomContentLoaded,
+    /// No network activity for 500ms, so client-side rendering has most likely finished.
+    NetworkIdle,
+}
+
+impl WaitUntil {
+    /// Name of the CDP `Page.lifecycleEvent` this condition waits for.
+    fn lifecycle_event(self) -> &'static str {
+        match self {
+            WaitUntil::Load => "load",
+            WaitUntil::DomContentLoaded => "DOMContentLoaded",
+            WaitUntil::NetworkIdle => "networkIdle",
+        }
+    }
+}
+
 /// Options for rendering a page.
 #[derive(Debug, Clone)]
 pub struct RenderOptions {
-    /// Timeout in milliseconds (default: 30000).
+    /// Timeout in milliseconds for navigating and settling (default: 30000).
     pub timeout_ms: u64,
 
-    /// Optional CSS selector to wait for before extracting content.
+    /// Lifecycle event that marks the page as loaded (default: `load`).
+    pub wait_until: WaitUntil,
+
+    /// Optional CSS selector to wait for, after `wait_until`, before extracting content.
     pub wait_for: Option<String>,
 
     /// Viewport dimensions (default: 1280x720).
     pub viewport: (u32, u32),
+
+    /// Hosts the page and its sub-requests may load from (default: unrestricted).
+    pub domain_policy: DomainPolicy,
+
+    /// Let the page load from private and reserved addresses (default: false).
+    pub allow_private_addresses: bool,
+}
+
+impl RenderOptions {
+    /// The request rules these options put on the page.
+    fn request_rules(&self) -> RequestRules {
+        RequestRules {
+            domain_policy: self.domain_policy.clone(),
+            allow_private_addresses: self.allow_private_addresses,
+        }
+    }
+}
+
+/// Which requests a rendered page may make.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestRules {
+    /// Hosts the page may load from.
+    pub domain_policy: DomainPolicy,
+
+    /// Allow hosts that are, or resolve to, private and reserved addresses.
+    pub allow_private_addresses: bool,
 }
 
 impl Default for RenderOptions {
     fn default() -> Self {
-        Self { timeout_ms: 30000, wait_for: None, viewport: (1280, 720) }
+        Self {
+            timeout_ms: 30000,
+            wait_until: WaitUntil::default(),
+            wait_for: None,
+            viewport: (1280, 720),
+            domain_policy: DomainPolicy::default(),
+            allow_private_addresses: false,
+        }
     }
 }
 
@@ -65,6 +181,14 @@ pub struct RenderedPage {
 
     /// Time taken to render in milliseconds.
     pub render_time_ms: u64,
+
+    /// Whether `wait_until` and `wait_for` were both met before the timeout;
+    /// when false the HTML is whatever the page had by then.
+    pub wait_condition_met: bool,
+
+    /// Requests the page made that were refused (denied scheme, host, port,
+    /// or private address); their resources are missing from `html`.
+    pub blocked_requests: usize,
 }
 
 /// Renderer trait for headless browser page rendering.
@@ -75,37 +199,25 @@ pub trait Renderer: Send + Sync {
 }
 
 /// Headless Chrome/Chromium renderer using chromiumoxide.
+///
+/// Opens a new page per render on a browser it owns. Servers rendering more
+/// than the occasional page should use a [`RendererPool`] instead.
 pub struct HeadlessRenderer {
     _browser: chromiumoxide::Browser,
 }
 
 impl HeadlessRenderer {
-    /// Create a new headless renderer by launching a browser instance.
-    ///
-    /// The browser runs in headless mode and uses a background task
-    /// to handle Chrome DevTools Protocol events.
+    /// Launch a headless browser found on PATH.
     pub async fn new() -> Result<Self, RenderError> {
-        use chromiumoxide::browser::{Browser, BrowserConfig};
-        use futures_util::StreamExt;
-
-        let (browser, mut handler) = Browser::launch(
-            BrowserConfig::builder()
-                .with_head()
-                .build()
-                .map_err(RenderError::BrowserLaunch)?,
-        )
-        .await
-        .map_err(|e| RenderError::BrowserLaunch(e.to_string()))?;
-
-        tokio::spawn(async move {
-            while let Some(event) = handler.next().await {
-                if let Err(e) = event {
-                    tracing::debug!("browser handler event error: {e}");
-                    break;
-                }
-            }
-        });
+        Self::with_config(&RendererConfig::default()).await
+    }
 
+    /// Launch a browser as described by `config`.
+    ///
+    /// A background task handles Chrome DevTools Protocol events for the
+    /// browser's lifetime.
+    pub async fn with_config(config: &RendererConfig) -> Result<Self, RenderError> {
+        let (browser, _handler) = launch(config).await?;
         Ok(Self { _browser: browser })
     }
 }
@@ -113,67 +225,313 @@ impl HeadlessRenderer {
 #[async_trait::async_trait]
 impl Renderer for HeadlessRenderer {
     async fn render(&self, url: &Url, opts: &RenderOptions) -> Result<RenderedPage, RenderError> {
-        let page = self
-            ._browser
-            .new_page(url.as_str())
-            .await
-            .map_err(|e| RenderError::Navigation(e.to_string()))?;
+        let page = open_page(&self._browser, &opts.request_rules()).await?;
+        let result = render_page(&page, url, opts).await;
+        page.close().await;
+        result
+    }
+}
+
+/// Launch a browser and spawn the task that drives its CDP connection.
+async fn launch(config: &RendererConfig) -> Result<(chromiumoxide::Browser, tokio::task::JoinHandle<()>), RenderError> {
+    use chromiumoxide::browser::{Browser, BrowserConfig};
+    use futures_util::StreamExt;
+
+    let mut builder = BrowserConfig::builder();
+    builder = if config.headless { builder.new_headless_mode() } else { builder.with_head() };
+    if let Some(path) = &config.chrome_path {
+        builder = builder.chrome_executable(path);
+    }
+    builder = builder.args(config.extra_args.iter());
+    let browser_config = builder
+        .build()
+        .map_err(|e| RenderError::BrowserLaunch(format!("{e}; set render.chrome_path to the browser binary")))?;
+
+    // chromiumoxide's launch errors carry whatever the browser wrote to stderr
+    // before failing (a missing display, a sandbox error, a bad flag).
+    let (browser, mut handler) = Browser::launch(browser_config).await.map_err(|e| {
+        let binary = config
+            .chrome_path
+            .as_ref()
+            .map_or_else(|| "browser from PATH".to_string(), |p| p.display().to_string());
+        RenderError::BrowserLaunch(format!("{binary}: {e}"))
+    })?;
+
+    let handler = tokio::spawn(async move {
+        while let Some(event) = handler.next().await {
+            if let Err(e) = event {
+                tracing::debug!("browser handler event error: {e}");
+                break;
+            }
+        }
+    });
+
+    Ok((browser, handler))
+}
 
-        let start = std::time::Instant::now();
+/// A page whose requests are checked against [`RequestRules`].
+struct GuardedPage {
+    page: chromiumoxide::Page,
+    /// Requests refused since the counter was last reset.
+    blocked: Arc<AtomicUsize>,
+}
+
+impl GuardedPage {
+    async fn close(self) {
+        self.page.close().await.ok();
+    }
+}
+
+/// Open a blank page that fails requests `rules` refuse.
+async fn open_page(browser: &chromiumoxide::Browser, rules: &RequestRules) -> Result<GuardedPage, RenderError> {
+    let page = browser
+        .new_page("about:blank")
+        .await
+        .map_err(|e| RenderError::Navigation(e.to_string()))?;
+    let blocked = Arc::new(AtomicUsize::new(0));
+    guard_requests(&page, rules.clone(), blocked.clone()).await?;
+    Ok(GuardedPage { page, blocked })
+}
 
-        if let Some(selector) = &opts.wait_for {
-            let wait_result = tokio::time::timeout(Duration::from_millis(opts.timeout_ms), async {
-                for _ in 0..30 {
-                    if (page.find_element(selector).await).is_ok() {
-                        return Ok::<(), RenderError>(());
+/// Navigate `page` to `url`, wait for it to settle, and capture its HTML.
+///
+/// Waiting for `opts.wait_until` and then `opts.wait_for` shares one
+/// `timeout_ms` deadline. A page that has not settled by then is still
+/// captured, with `wait_condition_met` false.
+async fn render_page(guarded: &GuardedPage, url: &Url, opts: &RenderOptions) -> Result<RenderedPage, RenderError> {
+    use chromiumoxide::cdp::browser_protocol::page::{
+        EventLifecycleEvent, NavigateParams, SetLifecycleEventsEnabledParams,
+    };
+    use futures_util::StreamExt;
+
+    let page = &guarded.page;
+    guarded.blocked.store(0, Ordering::Relaxed);
+    let start = std::time::Instant::now();
+    let deadline = tokio::time::Instant::now() + Duration::from_millis(opts.timeout_ms);
+    let navigation_error = |e: chromiumoxide::error::CdpError| RenderError::Navigation(e.to_string());
+
+    // Subscribe before navigating so an early lifecycle event cannot be missed.
+    let mut lifecycle = page
+        .event_listener::<EventLifecycleEvent>()
+        .await
+        .map_err(navigation_error)?;
+    page.execute(SetLifecycleEventsEnabledParams::new(true))
+        .await
+        .map_err(navigation_error)?;
+    let navigation = tokio::time::timeout_at(deadline, page.execute(NavigateParams::new(url.as_str())))
+        .await
+        .map_err(|_| RenderError::Timeout(opts.timeout_ms))?
+        .map_err(navigation_error)?;
+    if let Some(error) = &navigation.result.error_text {
+        return Err(RenderError::Navigation(error.clone()));
+    }
+
+    // A same-document navigation has no loader and fires no lifecycle events.
+    let mut wait_condition_met = match navigation.result.loader_id.clone() {
+        None => true,
+        Some(loader_id) => {
+            let event = opts.wait_until.lifecycle_event();
+            tokio::time::timeout_at(deadline, async {
+                while let Some(e) = lifecycle.next().await {
+                    if e.name == event && e.loader_id == loader_id {
+                        return true;
                     }
-                    tokio::time::sleep(Duration::from_millis(500)).await;
                 }
-                Err(RenderError::SelectorNotFound(selector.clone()))
+                false
             })
-            .await;
+            .await
+            .unwrap_or(false)
+        }
+    };
 
-            match wait_result {
-                Ok(Ok(_)) => {}
-                Ok(Err(e)) => {
-                    return Err(e);
-                }
-                Err(_) => {
-                    return Err(RenderError::Timeout(opts.timeout_ms));
-                }
+    if let Some(selector) = &opts.wait_for {
+        wait_condition_met &= wait_for_selector(page, selector, deadline).await?;
+    }
+
+    let html = page
+        .content()
+        .await
+        .map_err(|e| RenderError::ContentRetrieval(e.to_string()))?;
+
+    let page_url = page
+        .url()
+        .await
+        .map_err(|e| RenderError::ContentRetrieval(e.to_string()))?;
+
+    let final_url =
+        Url::parse(page_url.as_deref().unwrap_or(url.as_str())).map_err(|e| RenderError::Navigation(e.to_string()))?;
+
+    let render_time_ms = start.elapsed().as_millis() as u64;
+    let blocked_requests = guarded.blocked.load(Ordering::Relaxed);
+    Ok(RenderedPage { html, final_url, render_time_ms, wait_condition_met, blocked_requests })
+}
+
+/// Check `document.querySelector(selector)` every [`SELECTOR_POLL`] until it
+/// matches (true) or `deadline` passes (false).
+async fn wait_for_selector(
+    page: &chromiumoxide::Page, selector: &str, deadline: tokio::time::Instant,
+) -> Result<bool, RenderError> {
+    use chromiumoxide::error::CdpError;
+
+    let script = format!(
+        "document.querySelector({}) !== null",
+        serde_json::to_string(selector).expect("strings serialize")
+    );
+    loop {
+        match tokio::time::timeout_at(deadline, page.evaluate(script.as_str())).await {
+            Err(_) => return Ok(false),
+            // querySelector throws a SyntaxError for a malformed selector.
+            Ok(Err(CdpError::JavascriptException(_))) => {
+                return Err(RenderError::InvalidSelector(selector.to_string()));
             }
-        } else {
-            tokio::time::timeout(Duration::from_millis(opts.timeout_ms), async {
-                tokio::time::sleep(Duration::from_millis(2000)).await;
-            })
-            .await
-            .map_err(|_| RenderError::Timeout(opts.timeout_ms))?;
+            Ok(Err(e)) => return Err(RenderError::ContentRetrieval(e.to_string())),
+            Ok(Ok(found)) if found.into_value::<bool>().unwrap_or(false) => return Ok(true),
+            Ok(Ok(_)) => {}
         }
 
-        let html = page
-            .content()
-            .await
-            .map_err(|e| RenderError::ContentRetrieval(e.to_string()))?;
+        let now = tokio::time::Instant::now();
+        if now >= deadline {
+            return Ok(false);
+        }
+        tokio::time::sleep_until((now + SELECTOR_POLL).min(deadline)).await;
+    }
+}
 
-        let page_url = page
-            .url()
-            .await
-            .map_err(|e| RenderError::ContentRetrieval(e.to_string()))?;
+/// Pause every request `page` makes and fail the ones `rules` refuse, counting them in `blocked`.
+///
+/// Enables CDP request interception on `page`; a background task checks each
+/// paused request (resolving its host when private addresses are not allowed)
+/// and continues or fails it, until the page goes away. The browser resolves
+/// the host again when it connects, so this does not stop DNS rebinding.
+async fn guard_requests(
+    page: &chromiumoxide::Page, rules: RequestRules, blocked: Arc<AtomicUsize>,
+) -> Result<(), RenderError> {
+    use chromiumoxide::cdp::browser_protocol::fetch::{
+        ContinueRequestParams, EnableParams, EventRequestPaused, FailRequestParams,
+    };
+    use chromiumoxide::cdp::browser_protocol::network::ErrorReason;
+    use futures_util::StreamExt;
+
+    let mut paused = page
+        .event_listener::<EventRequestPaused>()
+        .await
+        .map_err(|e| RenderError::Navigation(e.to_string()))?;
+    page.execute(EnableParams::default())
+        .await
+        .map_err(|e| RenderError::Navigation(e.to_string()))?;
+
+    let page = page.clone();
+    let rules = Arc::new(rules);
+    tokio::spawn(async move {
+        while let Some(event) = paused.next().await {
+            // Checked concurrently: a DNS lookup must not hold up the page's other requests.
+            let (page, rules, blocked) = (page.clone(), rules.clone(), blocked.clone());
+            tokio::spawn(async move {
+                let result = match refusal(&event.request.url, &rules).await {
+                    None => page
+                        .execute(ContinueRequestParams::new(event.request_id.clone()))
+                        .await
+                        .map(|_| ()),
+                    Some(reason) => {
+                        tracing::debug!("render blocked request to {}: {reason}", event.request.url);
+                        blocked.fetch_add(1, Ordering::Relaxed);
+                        page.execute(FailRequestParams::new(
+                            event.request_id.clone(),
+                            ErrorReason::BlockedByClient,
+                        ))
+                        .await
+                        .map(|_| ())
+                    }
+                };
+                if let Err(e) = result {
+                    tracing::debug!("render request interception failed: {e}");
+                }
+            });
+        }
+    });
 
-        let final_url = Url::parse(page_url.as_deref().unwrap_or(url.as_str()))
-            .map_err(|e| RenderError::Navigation(e.to_string()))?;
+    Ok(())
+}
 
-        let render_time_ms = start.elapsed().as_millis() as u64;
+/// Why `rules` refuse a request to `url`, or `None` when it may go ahead.
+async fn refusal(url: &str, rules: &RequestRules) -> Option<String> {
+    let url = match Url::parse(url) {
+        Ok(url) => url,
+        Err(e) => return Some(format!("unparseable URL: {e}")),
+    };
+    let scheme = url.scheme();
+    if DENIED_SCHEMES.contains(&scheme) || !matches!(scheme, "http" | "https") {
+        return Some(format!("blocked scheme: {scheme}"));
+    }
 
-        page.close().await.ok();
-        Ok(RenderedPage { html, final_url, render_time_ms })
+    let host = url.host_str().unwrap_or_default();
+    if let DomainDecision::Deny { reason } = rules.domain_policy.evaluate(host) {
+        return Some(reason);
+    }
+    if let Some(port) = url.port()
+        && BLOCKED_PORTS.contains(&port)
+    {
+        return Some(format!("blocked port: {port}"));
+    }
+    if !rules.allow_private_addresses {
+        let port = url.port_or_known_default().unwrap_or(0);
+        if let Err(e) = resolve_public(host, port).await {
+            return Some(e.to_string());
+        }
     }
+    None
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_refusal_applies_ssrf_rules() {
+        let rules = RequestRules::default();
+        for url in [
+            "http://169.254.169.254/latest/meta-data/",
+            "http://127.0.0.1:8080/",
+            "http://[::1]/",
+            "http://10.0.0.1/x",
+            "http://93.184.216.34:22/",
+            "file:///etc/passwd",
+            "ftp://93.184.216.34/",
+        ] {
+            assert!(refusal(url, &rules).await.is_some(), "{url} should be blocked");
+        }
+        assert_eq!(refusal("https://93.184.216.34/img.png", &rules).await, None);
+
+        let private_ok = RequestRules { allow_private_addresses: true, ..Default::default() };
+        assert_eq!(refusal("http://127.0.0.1:8080/", &private_ok).await, None);
+        assert!(refusal("http://127.0.0.1:1/x", &private_ok).await.is_some());
+
+        let denied =
+            RequestRules { domain_policy: DomainPolicy::new(&[], &["denied.example".into()]), ..Default::default() };
+        assert!(refusal("https://denied.example/", &denied).await.is_some());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires Chrome/Chromium installation"]
+    async fn test_render_blocks_private_subresources() {
+        use crate::fixture::{FixtureResponse, FixtureServer};
+
+        let html = "<html><body><p>page</p><img src=\"http://127.0.0.1:1/x\"></body></html>";
+        let server = FixtureServer::start(move |_| FixtureResponse::html(html)).await;
+        let renderer = HeadlessRenderer::new().await.unwrap();
+        let url = Url::parse(&server.url("/")).unwrap();
+
+        // The fixture itself is on loopback, so only the port rule stops the image here.
+        let opts = RenderOptions { timeout_ms: 5000, allow_private_addresses: true, ..Default::default() };
+        let page = renderer.render(&url, &opts).await.unwrap();
+        assert!(page.html.contains("page"));
+        assert_eq!(page.blocked_requests, 1);
+
+        let opts = RenderOptions { timeout_ms: 5000, ..Default::default() };
+        let err = renderer.render(&url, &opts).await.unwrap_err();
+        assert!(matches!(err, RenderError::Navigation(_)), "{err}");
+    }
+
     #[tokio::test]
     #[ignore = "requires Chrome/Chromium installation"]
     async fn test_headless_renderer_new() {
@@ -181,6 +539,24 @@ mod tests {
         assert!(renderer.is_ok());
     }
 
+    #[tokio::test]
+    #[ignore = "requires Chrome/Chromium installation"]
+    async fn test_headless_launch_needs_no_display() {
+        // SAFETY: ignored test run on its own; nothing else reads the environment concurrently.
+        unsafe { std::env::remove_var("DISPLAY") };
+        let config = RendererConfig { extra_args: vec!["--no-sandbox".into()], ..Default::default() };
+        let renderer = HeadlessRenderer::with_config(&config).await;
+        assert!(renderer.is_ok(), "{:?}", renderer.err());
+    }
+
+    #[tokio::test]
+    async fn test_missing_chrome_path_reports_binary() {
+        let config = RendererConfig { chrome_path: Some("/nonexistent/chromium".into()), ..Default::default() };
+        let err = HeadlessRenderer::with_config(&config).await.err().unwrap();
+        assert!(matches!(err, RenderError::BrowserLaunch(_)));
+        assert!(err.to_string().contains("/nonexistent/chromium"), "{err}");
+    }
+
     #[tokio::test]
     #[ignore = "requires network and Chrome/Chromium"]
     async fn test_render_simple_page() {
@@ -194,5 +570,40 @@ mod tests {
         let page = result.unwrap();
         assert!(page.html.contains("<html>"));
         assert_eq!(page.final_url.as_str(), "https://example.com/");
+        assert!(page.wait_condition_met);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires Chrome/Chromium installation"]
+    async fn test_wait_for_selector_added_by_script() {
+        use crate::fixture::{FixtureResponse, FixtureServer};
+
+        let html = "<html><body><script>\
+                    setTimeout(() => document.body.insertAdjacentHTML('beforeend', '<div id=late>late</div>'), 300);\
+                    </script></body></html>";
+        let server = FixtureServer::start(move |_| FixtureResponse::html(html)).await;
+        let renderer = HeadlessRenderer::new().await.unwrap();
+        let url = Url::parse(&server.url("/")).unwrap();
+
+        let local = RenderOptions { allow_private_addresses: true, ..Default::default() };
+
+        let opts = RenderOptions { timeout_ms: 5000, wait_for: Some("#late".into()), ..local.clone() };
+        let page = renderer.render(&url, &opts).await.unwrap();
+        assert!(page.wait_condition_met);
+        assert!(page.html.contains("late</div>"));
+
+        let opts = RenderOptions { timeout_ms: 500, wait_for: Some("#never".into()), ..local.clone() };
+        let started = std::time::Instant::now();
+        let page = renderer.render(&url, &opts).await.unwrap();
+        assert!(!page.wait_condition_met);
+        assert!(
+            started.elapsed() < Duration::from_millis(1500),
+            "{:?}",
+            started.elapsed()
+        );
+
+        let opts = RenderOptions { wait_for: Some("div[".into()), ..local };
+        let err = renderer.render(&url, &opts).await.unwrap_err();
+        assert!(matches!(err, RenderError::InvalidSelector(_)), "{err}");
     }
 }
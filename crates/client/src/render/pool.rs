@@ -0,0 +1,217 @@
+//! One shared browser with a bounded set of reusable pages.
+//!
+//! Launching Chrome costs far more than rendering a page, so a server launches
+//! one [`RendererPool`] at startup and renders every call on it. At most
+//! `max_pages` pages are open at once; renders beyond that wait for a page to
+//! be checked back in. Idle pages are navigated to the next URL rather than
+//! closed. A page that fails its liveness check on checkout, or fails a
+//! render, is closed and replaced by a fresh one.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chromiumoxide::{Browser, Page};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinHandle;
+use url::Url;
+
+use super::{
+    GuardedPage, RenderError, RenderOptions, RenderedPage, Renderer, RendererConfig, RequestRules, launch, open_page,
+    render_page,
+};
+
+/// Default number of pages a pool keeps open.
+pub const DEFAULT_MAX_PAGES: usize = 4;
+
+/// How long an idle page has to answer before it is treated as crashed.
+const LIVENESS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A launched browser shared by every render.
+///
+/// Cloning is cheap and clones share the browser and its pages.
+#[derive(Clone)]
+pub struct RendererPool {
+    inner: Arc<PoolInner>,
+}
+
+struct PoolInner {
+    browser: tokio::sync::Mutex<Browser>,
+    handler: JoinHandle<()>,
+    idle: Mutex<Vec<GuardedPage>>,
+    slots: Arc<Semaphore>,
+    rules: RequestRules,
+    max_pages: usize,
+}
+
+/// A page checked out of the pool; returned to it on drop unless discarded.
+struct PooledPage {
+    page: Option<GuardedPage>,
+    reusable: bool,
+    inner: Arc<PoolInner>,
+    _slot: OwnedSemaphorePermit,
+}
+
+impl RendererPool {
+    /// Launch a browser as described by `config`.
+    ///
+    /// Pooled pages block requests `rules` refuse; renders asking for
+    /// different rules get a one-off page instead of a pooled one.
+    pub async fn launch(config: &RendererConfig, rules: RequestRules) -> Result<Self, RenderError> {
+        let max_pages = config.max_pages.max(1);
+        let (browser, handler) = launch(config).await?;
+        Ok(Self {
+            inner: Arc::new(PoolInner {
+                browser: tokio::sync::Mutex::new(browser),
+                handler,
+                idle: Mutex::new(Vec::new()),
+                slots: Arc::new(Semaphore::new(max_pages)),
+                rules,
+                max_pages,
+            }),
+        })
+    }
+
+    /// Pages the pool keeps open at most.
+    pub fn max_pages(&self) -> usize {
+        self.inner.max_pages
+    }
+
+    /// Pages open and waiting for the next render (debug accessor).
+    pub fn idle_pages(&self) -> usize {
+        self.inner.idle.lock().unwrap().len()
+    }
+
+    /// Close every page and the browser; later renders fail with `BrowserClosed`.
+    ///
+    /// Renders in flight finish on their page, which is then closed rather than returned.
+    pub async fn shutdown(&self) {
+        self.inner.slots.close();
+        let idle = std::mem::take(&mut *self.inner.idle.lock().unwrap());
+        for page in idle {
+            page.close().await;
+        }
+
+        let mut browser = self.inner.browser.lock().await;
+        if let Err(e) = browser.close().await {
+            tracing::debug!("closing renderer browser failed: {e}");
+        }
+        browser.wait().await.ok();
+        self.inner.handler.abort();
+    }
+
+    /// Wait up to `timeout` for a free slot, then hand out a live page.
+    ///
+    /// With `rules` other than the pool's, the page is a fresh one that will
+    /// be closed after use, so its request filter never leaks into the pool.
+    async fn checkout(&self, rules: &RequestRules, timeout: Duration) -> Result<PooledPage, RenderError> {
+        let slot = tokio::time::timeout(timeout, self.inner.slots.clone().acquire_owned())
+            .await
+            .map_err(|_| RenderError::Timeout(timeout.as_millis() as u64))?
+            .map_err(|_| RenderError::BrowserClosed)?;
+
+        if *rules == self.inner.rules {
+            loop {
+                let Some(page) = self.inner.idle.lock().unwrap().pop() else { break };
+                if is_alive(&page.page).await {
+                    return Ok(PooledPage { page: Some(page), reusable: true, inner: self.inner.clone(), _slot: slot });
+                }
+                tracing::debug!("replacing unresponsive renderer page");
+                page.close().await;
+            }
+        }
+
+        let page = {
+            let browser = self.inner.browser.lock().await;
+            open_page(&browser, rules).await?
+        };
+        let reusable = *rules == self.inner.rules;
+        Ok(PooledPage { page: Some(page), reusable, inner: self.inner.clone(), _slot: slot })
+    }
+}
+
+#[async_trait::async_trait]
+impl Renderer for RendererPool {
+    async fn render(&self, url: &Url, opts: &RenderOptions) -> Result<RenderedPage, RenderError> {
+        let mut page = self
+            .checkout(&opts.request_rules(), Duration::from_millis(opts.timeout_ms))
+            .await?;
+        let result = render_page(page.page(), url, opts).await;
+        if result.is_err() || !page.reusable {
+            page.discard().await;
+        }
+        result
+    }
+}
+
+impl PooledPage {
+    fn page(&self) -> &GuardedPage {
+        self.page.as_ref().expect("page is present until discarded")
+    }
+
+    /// Close the page instead of returning it; the pool opens a new one when needed.
+    async fn discard(&mut self) {
+        if let Some(page) = self.page.take() {
+            page.close().await;
+        }
+    }
+}
+
+impl Drop for PooledPage {
+    fn drop(&mut self) {
+        // Runs before the slot is released, so a waiting render finds the page idle.
+        if let Some(page) = self.page.take()
+            && self.reusable
+            && !self.inner.slots.is_closed()
+        {
+            self.inner.idle.lock().unwrap().push(page);
+        }
+    }
+}
+
+/// Whether `page` still answers over CDP; a crashed tab never does.
+async fn is_alive(page: &Page) -> bool {
+    matches!(
+        tokio::time::timeout(LIVENESS_TIMEOUT, page.evaluate("1")).await,
+        Ok(Ok(_))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    #[ignore = "requires Chrome/Chromium installation"]
+    async fn test_pool_reuses_pages_up_to_max() {
+        use crate::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| FixtureResponse::html("<html><body><p>pooled</p></body></html>")).await;
+        let config = RendererConfig { max_pages: 2, ..Default::default() };
+        let rules = RequestRules { allow_private_addresses: true, ..Default::default() };
+        let pool = RendererPool::launch(&config, rules).await.unwrap();
+        let opts = RenderOptions {
+            timeout_ms: 5000,
+            wait_for: Some("p".into()),
+            allow_private_addresses: true,
+            ..Default::default()
+        };
+
+        let renders = (0..5).map(|i| {
+            let pool = pool.clone();
+            let url = Url::parse(&server.url(&format!("/{i}"))).unwrap();
+            let opts = opts.clone();
+            async move { pool.render(&url, &opts).await }
+        });
+        for page in futures_util::future::join_all(renders).await {
+            assert!(page.unwrap().html.contains("pooled"));
+        }
+        assert_eq!(pool.idle_pages(), 2);
+
+        pool.shutdown().await;
+        let url = Url::parse(&server.url("/after")).unwrap();
+        assert!(matches!(
+            pool.render(&url, &opts).await,
+            Err(RenderError::BrowserClosed)
+        ));
+    }
+}
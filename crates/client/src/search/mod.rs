@@ -0,0 +1,93 @@
+//! Pluggable web search backends.
+//!
+//! [`SearchProvider`] is what web_search queries: [`BraveClient`] by default,
+//! or a self-hosted [`SearxngClient`] when Brave is unavailable or out of
+//! quota. Every provider takes a [`SearchRequest`] and returns the normalized
+//! [`SearchResponse`], with each result's `source` naming the provider.
+
+pub mod searxng;
+
+pub use searxng::{SearxngClient, SearxngConfig};
+
+use std::sync::Arc;
+
+use crate::brave::{BraveClient, BraveError, SearchRequest, SearchResponse};
+
+/// Errors from a search provider.
+#[derive(Debug, thiserror::Error)]
+pub enum SearchError {
+    /// Brave failed; keeps Brave's error so callers can tell auth from rate limiting.
+    #[error(transparent)]
+    Brave(#[from] BraveError),
+
+    /// The request is invalid for this provider.
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+
+    /// HTTP error response.
+    #[error("HTTP error: {status}")]
+    HttpError { status: u16 },
+
+    /// Request timeout.
+    #[error("request timeout")]
+    Timeout,
+
+    /// Network error.
+    #[error("network error: {0}")]
+    Network(Arc<reqwest::Error>),
+
+    /// Response parse error.
+    #[error("parse error: {0}")]
+    Parse(String),
+}
+
+impl From<reqwest::Error> for SearchError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() { SearchError::Timeout } else { SearchError::Network(Arc::new(err)) }
+    }
+}
+
+/// A web search backend.
+#[async_trait::async_trait]
+pub trait SearchProvider: Send + Sync {
+    /// Run `req` and normalize the results.
+    async fn search(&self, req: SearchRequest) -> Result<SearchResponse, SearchError>;
+
+    /// Key `req`'s response is cached under; distinct per provider so one
+    /// backend's results are never served for another's.
+    fn cache_key(&self, req: &SearchRequest) -> String;
+
+    /// Short name reported as each result's `source`, e.g. "brave".
+    fn provider_name(&self) -> &str;
+}
+
+#[async_trait::async_trait]
+impl SearchProvider for BraveClient {
+    async fn search(&self, req: SearchRequest) -> Result<SearchResponse, SearchError> {
+        Ok(BraveClient::search(self, req).await?)
+    }
+
+    fn cache_key(&self, req: &SearchRequest) -> String {
+        BraveClient::cache_key(req)
+    }
+
+    fn provider_name(&self) -> &str {
+        "brave"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brave::BraveConfig;
+
+    #[test]
+    fn test_brave_provider_keeps_existing_cache_keys() {
+        let client = BraveClient::new(BraveConfig { api_key: "test-key".into(), ..Default::default() }).unwrap();
+        let provider: &dyn SearchProvider = &client;
+        let req = SearchRequest { q: "rust".into(), ..Default::default() };
+
+        assert_eq!(provider.provider_name(), "brave");
+        assert_eq!(provider.cache_key(&req), BraveClient::cache_key(&req));
+    }
+}
@@ -0,0 +1,307 @@
+//! SearXNG search provider.
+//!
+//! Queries a self-hosted SearXNG instance's `/search` endpoint with
+//! `format=json` (which the instance's `search.formats` setting must allow)
+//! and normalizes its results into the same shape as Brave's.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::{Duration, Instant};
+
+use super::{SearchError, SearchProvider};
+use crate::brave::response::{DebugInfo, QueryMeta, SearchResult, published_at};
+use crate::brave::{CACHE_KEY_VERSION, SafeSearch, SearchRequest, SearchResponse};
+
+/// SearXNG client configuration.
+#[derive(Debug, Clone)]
+pub struct SearxngConfig {
+    /// Instance base URL, e.g. `http://localhost:8888`.
+    pub base_url: String,
+    /// Request timeout (default: 10s).
+    pub timeout: Duration,
+    /// User-agent string (default: mcp-web/0.1).
+    pub user_agent: String,
+}
+
+impl Default for SearxngConfig {
+    fn default() -> Self {
+        Self { base_url: String::new(), timeout: Duration::from_secs(10), user_agent: "mcp-web/0.1".to_string() }
+    }
+}
+
+/// Client for a SearXNG instance's JSON search API.
+#[derive(Debug, Clone)]
+pub struct SearxngClient {
+    http: reqwest::Client,
+    config: SearxngConfig,
+}
+
+/// Query parameters SearXNG understands.
+#[derive(Debug, Serialize)]
+struct SearxngQuery<'a> {
+    q: &'a str,
+    format: &'static str,
+    pageno: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time_range: Option<&'static str>,
+    safesearch: u8,
+}
+
+/// Raw response from SearXNG.
+#[derive(Debug, Deserialize)]
+struct SearxngApiResponse {
+    #[serde(default)]
+    results: Vec<SearxngResult>,
+}
+
+/// Individual SearXNG result.
+#[derive(Debug, Deserialize)]
+struct SearxngResult {
+    url: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default, rename = "publishedDate")]
+    published_date: Option<String>,
+}
+
+impl SearxngClient {
+    /// Create a client for the instance at `config.base_url`.
+    pub fn new(config: SearxngConfig) -> Result<Self, SearchError> {
+        if url::Url::parse(&config.base_url).is_err() {
+            return Err(SearchError::InvalidRequest(format!(
+                "invalid SearXNG base URL: {:?}",
+                config.base_url
+            )));
+        }
+        let http = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .user_agent(&config.user_agent)
+            .build()?;
+        Ok(Self { http, config })
+    }
+
+    /// Translate a Brave-shaped request into SearXNG's parameters.
+    fn query<'a>(req: &'a SearchRequest) -> Result<SearxngQuery<'a>, SearchError> {
+        if req.goggles.is_some() {
+            return Err(SearchError::InvalidRequest(
+                "goggles are only supported by Brave".to_string(),
+            ));
+        }
+        let time_range = match req.freshness.as_deref() {
+            None => None,
+            Some("pd") => Some("day"),
+            Some("pw") => Some("week"),
+            Some("pm") => Some("month"),
+            Some("py") => Some("year"),
+            Some(other) => {
+                return Err(SearchError::InvalidRequest(format!(
+                    "SearXNG supports freshness pd, pw, pm, or py, not {other:?}"
+                )));
+            }
+        };
+        let language = match (&req.search_lang, &req.country) {
+            (Some(lang), Some(country)) => Some(format!("{lang}-{}", country.to_uppercase())),
+            (Some(lang), None) => Some(lang.clone()),
+            (None, _) => None,
+        };
+        let safesearch = match req.safesearch {
+            Some(SafeSearch::Off) => 0,
+            Some(SafeSearch::Moderate) | None => 1,
+            Some(SafeSearch::Strict) => 2,
+        };
+        Ok(SearxngQuery {
+            q: &req.q,
+            format: "json",
+            pageno: req.offset.unwrap_or(0) + 1,
+            language,
+            time_range,
+            safesearch,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl SearchProvider for SearxngClient {
+    async fn search(&self, req: SearchRequest) -> Result<SearchResponse, SearchError> {
+        req.validate()?;
+        let query = Self::query(&req)?;
+
+        let start = Instant::now();
+        let url = format!("{}/search", self.config.base_url.trim_end_matches('/'));
+        tracing::debug!("searching SearXNG: query={}", req.q);
+        let response = self
+            .http
+            .get(&url)
+            .header("Accept", "application/json")
+            .query(&query)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(SearchError::HttpError { status: status.as_u16() });
+        }
+        let bytes = response.bytes().await?;
+        let raw: SearxngApiResponse = serde_json::from_slice(&bytes).map_err(|e| SearchError::Parse(e.to_string()))?;
+
+        let count = usize::from(req.count.unwrap_or(20));
+        let more_results_available = raw.results.len() > count;
+        let now = chrono::Utc::now();
+        let results = raw
+            .results
+            .into_iter()
+            .take(count)
+            .enumerate()
+            .map(|(idx, r)| SearchResult {
+                title: r.title,
+                url: r.url,
+                description: r.content,
+                extra_snippets: Vec::new(),
+                age: None,
+                page_age: r.published_date.clone(),
+                published_at: published_at(r.published_date.as_deref(), None, now),
+                language: None,
+                favicon: None,
+                hostname: None,
+                source: self.provider_name().to_string(),
+                rank: idx + 1,
+            })
+            .collect();
+
+        Ok(SearchResponse {
+            results,
+            videos: Vec::new(),
+            faq: Vec::new(),
+            discussions: Vec::new(),
+            news: Vec::new(),
+            query: QueryMeta {
+                original: req.q,
+                more_results_available,
+                altered: None,
+                spellcheck_off: None,
+                is_navigational: None,
+            },
+            debug: DebugInfo { request_id: None, attempts: 1 },
+        }
+        .with_timing(start))
+    }
+
+    /// `searxng:` + SHA-256 of the instance URL and the parameters SearXNG uses.
+    fn cache_key(&self, req: &SearchRequest) -> String {
+        let params = serde_json::json!({
+            "base_url": self.config.base_url,
+            "q": req.q,
+            "count": req.count.unwrap_or(20),
+            "offset": req.offset.unwrap_or(0),
+            "freshness": req.freshness,
+            "safesearch": req.safesearch,
+            "country": req.country,
+            "search_lang": req.search_lang,
+        });
+
+        let mut hasher = Sha256::new();
+        hasher.update(CACHE_KEY_VERSION.as_bytes());
+        hasher.update(params.to_string().as_bytes());
+        format!("searxng:{}", hex::encode(hasher.finalize()))
+    }
+
+    fn provider_name(&self) -> &str {
+        "searxng"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{FixtureResponse, FixtureServer};
+
+    #[tokio::test]
+    async fn test_search_normalizes_searxng_results() {
+        let body = serde_json::json!({
+            "query": "rust",
+            "number_of_results": 0,
+            "results": [
+                {
+                    "url": "https://www.rust-lang.org/",
+                    "title": "Rust Programming Language",
+                    "content": "A language empowering everyone.",
+                    "engine": "duckduckgo",
+                    "score": 4.0
+                },
+                {
+                    "url": "https://blog.rust-lang.org/",
+                    "title": "Rust Blog",
+                    "content": "Announcements",
+                    "publishedDate": "2024-03-08T09:30:00"
+                },
+                { "url": "https://docs.rs/", "title": "Docs.rs" }
+            ],
+            "suggestions": []
+        })
+        .to_string();
+        let server = FixtureServer::start(move |req| {
+            let expected = [
+                "format=json",
+                "pageno=2",
+                "time_range=week",
+                "safesearch=2",
+                "language=en-US",
+            ];
+            if req.path.starts_with("/search?") && expected.iter().all(|p| req.path.contains(p)) {
+                FixtureResponse::ok("application/json", body.clone())
+            } else {
+                FixtureResponse::status(400)
+            }
+        })
+        .await;
+
+        let client = SearxngClient::new(SearxngConfig { base_url: server.url("/"), ..Default::default() }).unwrap();
+        let req = SearchRequest {
+            q: "rust".into(),
+            count: Some(2),
+            offset: Some(1),
+            freshness: Some("pw".into()),
+            safesearch: Some(SafeSearch::Strict),
+            search_lang: Some("en".into()),
+            country: Some("us".into()),
+            ..Default::default()
+        };
+        let response = client.search(req.clone()).await.unwrap();
+
+        assert_eq!(response.results.len(), 2);
+        assert!(response.query.more_results_available);
+        assert!(response.results.iter().all(|r| r.source == "searxng"));
+        assert_eq!(response.results[0].description, "A language empowering everyone.");
+        assert_eq!(
+            response.results[1].published_at.as_deref(),
+            Some("2024-03-08T09:30:00Z")
+        );
+
+        let key = client.cache_key(&req);
+        assert!(key.starts_with("searxng:"));
+        assert_ne!(key.trim_start_matches("searxng:"), crate::BraveClient::cache_key(&req));
+    }
+
+    #[tokio::test]
+    async fn test_search_rejects_brave_only_parameters() {
+        let client =
+            SearxngClient::new(SearxngConfig { base_url: "http://localhost:8888".into(), ..Default::default() })
+                .unwrap();
+        let goggles = SearchRequest { q: "rust".into(), goggles: Some("https://goggles".into()), ..Default::default() };
+        assert!(matches!(
+            client.search(goggles).await,
+            Err(SearchError::InvalidRequest(_))
+        ));
+
+        let range =
+            SearchRequest { q: "rust".into(), freshness: Some("2024-01-01to2024-02-01".into()), ..Default::default() };
+        assert!(matches!(
+            client.search(range).await,
+            Err(SearchError::InvalidRequest(_))
+        ));
+    }
+}
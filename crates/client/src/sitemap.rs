@@ -0,0 +1,383 @@
+//! Sitemap discovery and parsing.
+//!
+//! Handles `<urlset>` and `<sitemapindex>` XML, gzipped sitemaps, and plain-text
+//! sitemaps (one URL per line). Sitemaps are discovered from `Sitemap:` lines in
+//! robots.txt, falling back to `/sitemap.xml`. Fetches go through [`FetchClient`]
+//! with the caller's [`RequestOptions`], so the usual SSRF, robots.txt, and size
+//! rules apply, with [`SITEMAP_CONTENT_TYPES`] allowed and bodies up to
+//! [`MAX_SITEMAP_BYTES`].
+
+use std::io::Read;
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::fetch::{FetchClient, FetchResponse, RequestOptions};
+use thndrs_core::Error;
+
+/// Maximum entries kept from a single sitemap file (the protocol limit).
+pub const MAX_SITEMAP_ENTRIES: usize = 50_000;
+
+/// Maximum entries collected across all sitemaps reachable from one index.
+pub const MAX_TOTAL_SITEMAP_ENTRIES: usize = 200_000;
+
+/// Maximum sitemap files fetched while following sitemap indexes.
+pub const MAX_SITEMAP_FILES: usize = 50;
+
+/// Maximum nesting of sitemap indexes below the first sitemap read.
+pub const MAX_SITEMAP_DEPTH: usize = 3;
+
+/// Maximum decompressed size of a gzipped sitemap (the protocol limit).
+pub const MAX_SITEMAP_BYTES: usize = 50 * 1024 * 1024;
+
+/// Content types accepted when fetching robots.txt and sitemaps.
+pub const SITEMAP_CONTENT_TYPES: &[&str] = &[
+    "application/xml",
+    "text/xml",
+    "text/plain",
+    "application/gzip",
+    "application/x-gzip",
+    "application/octet-stream",
+];
+
+/// A `<url>` or `<sitemap>` entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SitemapEntry {
+    /// Page or child sitemap URL
+    pub loc: String,
+    /// `<lastmod>` value as written (W3C datetime)
+    pub lastmod: Option<String>,
+    /// `<changefreq>` value as written, e.g. `weekly`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changefreq: Option<String>,
+    /// `<priority>` value as written, `0.0` to `1.0`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+}
+
+/// Whether a sitemap lists pages or other sitemaps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SitemapKind {
+    /// `<urlset>` or plain-text list of pages
+    UrlSet,
+    /// `<sitemapindex>` listing child sitemaps
+    Index,
+}
+
+/// A parsed sitemap file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sitemap {
+    pub kind: SitemapKind,
+    pub entries: Vec<SitemapEntry>,
+    /// Whether entries beyond [`MAX_SITEMAP_ENTRIES`] were dropped
+    pub truncated: bool,
+}
+
+/// Parse a sitemap body, decompressing it first if it is gzipped.
+pub fn parse_sitemap(body: &[u8]) -> Result<Sitemap, Error> {
+    let body = decompress(body)?;
+    let text = String::from_utf8_lossy(&body);
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+
+    if text.starts_with('<') { parse_xml(text) } else { Ok(parse_text(text)) }
+}
+
+/// Collect `Sitemap:` URLs from a robots.txt body.
+pub fn sitemaps_from_robots(robots_txt: &str) -> Vec<String> {
+    robots_txt
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim().eq_ignore_ascii_case("sitemap").then(|| value.trim())
+        })
+        .filter(|value| value.starts_with("http://") || value.starts_with("https://"))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Find the sitemaps for the site `root` belongs to.
+///
+/// Reads `Sitemap:` lines from the site's robots.txt; when there are none (or
+/// robots.txt cannot be fetched) falls back to `/sitemap.xml`.
+pub async fn discover_sitemaps(client: &FetchClient, root: &Url, options: &RequestOptions) -> Vec<String> {
+    let Ok(robots_url) = root.join("/robots.txt") else {
+        return Vec::new();
+    };
+
+    let listed = match fetch_body(client, robots_url.as_str(), options).await {
+        Ok(response) => sitemaps_from_robots(&String::from_utf8_lossy(&response.bytes)),
+        Err(e) => {
+            tracing::debug!("no robots.txt for {}: {}", root, e);
+            Vec::new()
+        }
+    };
+
+    if !listed.is_empty() {
+        return listed;
+    }
+
+    root.join("/sitemap.xml")
+        .map(|u| vec![u.to_string()])
+        .unwrap_or_default()
+}
+
+/// Fetch and parse a single sitemap file.
+pub async fn fetch_sitemap(client: &FetchClient, url: &str, options: &RequestOptions) -> Result<Sitemap, Error> {
+    let response = fetch_body(client, url, options).await?;
+    parse_sitemap(&response.bytes)
+}
+
+/// Fetch robots.txt or a sitemap, allowing [`SITEMAP_CONTENT_TYPES`] and up to [`MAX_SITEMAP_BYTES`].
+async fn fetch_body(client: &FetchClient, url: &str, options: &RequestOptions) -> Result<FetchResponse, Error> {
+    let options = RequestOptions {
+        content_types: Some(SITEMAP_CONTENT_TYPES.iter().map(|s| s.to_string()).collect()),
+        ..options.clone()
+    };
+    client.fetch_with_options(url, MAX_SITEMAP_BYTES, &options).await
+}
+
+/// Gunzip `body` if it starts with the gzip magic bytes, enforcing [`MAX_SITEMAP_BYTES`].
+fn decompress(body: &[u8]) -> Result<std::borrow::Cow<'_, [u8]>, Error> {
+    if !body.starts_with(&[0x1f, 0x8b]) {
+        return Ok(body.into());
+    }
+
+    let mut out = Vec::new();
+    flate2::read::GzDecoder::new(body)
+        .take(MAX_SITEMAP_BYTES as u64 + 1)
+        .read_to_end(&mut out)
+        .map_err(|e| Error::ExtractFailed(format!("invalid gzipped sitemap: {e}")))?;
+
+    if out.len() > MAX_SITEMAP_BYTES {
+        return Err(Error::FetchTooLarge(format!(
+            "decompressed sitemap exceeds {MAX_SITEMAP_BYTES} bytes"
+        )));
+    }
+    Ok(out.into())
+}
+
+fn parse_text(text: &str) -> Sitemap {
+    let mut entries: Vec<SitemapEntry> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("http://") || line.starts_with("https://"))
+        .map(|loc| SitemapEntry { loc: loc.to_string(), ..Default::default() })
+        .collect();
+
+    let truncated = entries.len() > MAX_SITEMAP_ENTRIES;
+    entries.truncate(MAX_SITEMAP_ENTRIES);
+    Sitemap { kind: SitemapKind::UrlSet, entries, truncated }
+}
+
+#[derive(Clone, Copy)]
+enum Field {
+    Loc,
+    Lastmod,
+    Changefreq,
+    Priority,
+}
+
+fn parse_xml(text: &str) -> Result<Sitemap, Error> {
+    use quick_xml::Reader;
+    use quick_xml::events::Event;
+
+    let mut reader = Reader::from_str(text);
+    reader.config_mut().trim_text(true);
+
+    let mut kind = None;
+    let mut entries = Vec::new();
+    let mut truncated = false;
+
+    let mut depth = 0usize;
+    let mut entry_depth = None;
+    let mut current = SitemapEntry::default();
+    let mut field = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                depth += 1;
+                match (e.local_name().as_ref(), entry_depth) {
+                    (b"urlset", None) if depth == 1 => kind = Some(SitemapKind::UrlSet),
+                    (b"sitemapindex", None) if depth == 1 => kind = Some(SitemapKind::Index),
+                    (b"url" | b"sitemap", None) if depth == 2 => {
+                        entry_depth = Some(depth);
+                        current = SitemapEntry::default();
+                    }
+                    (b"loc", Some(d)) if depth == d + 1 => field = Some(Field::Loc),
+                    (b"lastmod", Some(d)) if depth == d + 1 => field = Some(Field::Lastmod),
+                    (b"changefreq", Some(d)) if depth == d + 1 => field = Some(Field::Changefreq),
+                    (b"priority", Some(d)) if depth == d + 1 => field = Some(Field::Priority),
+                    _ => {}
+                }
+            }
+            Ok(Event::Text(t)) => {
+                if let Some(field) = field {
+                    let value = t
+                        .unescape()
+                        .map_err(|e| Error::ExtractFailed(format!("invalid sitemap XML: {e}")))?;
+                    push_field(&mut current, field, &value);
+                }
+            }
+            Ok(Event::CData(t)) => {
+                if let Some(field) = field {
+                    push_field(&mut current, field, &String::from_utf8_lossy(&t));
+                }
+            }
+            Ok(Event::End(_)) => {
+                if entry_depth == Some(depth) {
+                    entry_depth = None;
+                    if !current.loc.is_empty() {
+                        if entries.len() < MAX_SITEMAP_ENTRIES {
+                            entries.push(std::mem::take(&mut current));
+                        } else {
+                            truncated = true;
+                        }
+                    }
+                }
+                field = None;
+                depth = depth.saturating_sub(1);
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(Error::ExtractFailed(format!("invalid sitemap XML: {e}"))),
+            _ => {}
+        }
+    }
+
+    let kind = kind.ok_or_else(|| Error::ExtractFailed("not a sitemap: missing <urlset> or <sitemapindex>".into()))?;
+    Ok(Sitemap { kind, entries, truncated })
+}
+
+fn push_field(entry: &mut SitemapEntry, field: Field, value: &str) {
+    let value = value.trim();
+    match field {
+        Field::Loc => entry.loc.push_str(value),
+        Field::Lastmod => entry.lastmod.get_or_insert_with(String::new).push_str(value),
+        Field::Changefreq => entry.changefreq.get_or_insert_with(String::new).push_str(value),
+        Field::Priority => entry.priority.get_or_insert_with(String::new).push_str(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetch::FetchConfig;
+    use crate::fixture::{FixtureResponse, FixtureServer};
+    use std::io::Write;
+
+    const URLSET: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"
+        xmlns:image="http://www.google.com/schemas/sitemap-image/1.1">
+  <url>
+    <loc>https://example.com/a?x=1&amp;y=2</loc>
+    <lastmod>2024-05-01</lastmod>
+    <changefreq>weekly</changefreq>
+    <priority>0.8</priority>
+    <image:image><image:loc>https://example.com/a.png</image:loc></image:image>
+  </url>
+  <url><loc><![CDATA[https://example.com/b]]></loc></url>
+</urlset>"#;
+
+    const INDEX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap><loc>https://example.com/posts.xml</loc><lastmod>2024-06-01T00:00:00Z</lastmod></sitemap>
+  <sitemap><loc>https://example.com/pages.xml</loc></sitemap>
+</sitemapindex>"#;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_parse_urlset() {
+        let sitemap = parse_sitemap(URLSET.as_bytes()).unwrap();
+        assert_eq!(sitemap.kind, SitemapKind::UrlSet);
+        assert_eq!(
+            sitemap.entries,
+            vec![
+                SitemapEntry {
+                    loc: "https://example.com/a?x=1&y=2".into(),
+                    lastmod: Some("2024-05-01".into()),
+                    changefreq: Some("weekly".into()),
+                    priority: Some("0.8".into()),
+                },
+                SitemapEntry { loc: "https://example.com/b".into(), ..Default::default() },
+            ]
+        );
+        assert!(!sitemap.truncated);
+    }
+
+    #[test]
+    fn test_parse_index() {
+        let sitemap = parse_sitemap(INDEX.as_bytes()).unwrap();
+        assert_eq!(sitemap.kind, SitemapKind::Index);
+        assert_eq!(sitemap.entries.len(), 2);
+        assert_eq!(sitemap.entries[0].lastmod.as_deref(), Some("2024-06-01T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_parse_gzipped() {
+        let sitemap = parse_sitemap(&gzip(URLSET.as_bytes())).unwrap();
+        assert_eq!(sitemap.entries.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_text_sitemap() {
+        let sitemap = parse_sitemap(b"https://example.com/one\n\nnot a url\nhttps://example.com/two\n").unwrap();
+        assert_eq!(sitemap.kind, SitemapKind::UrlSet);
+        assert_eq!(sitemap.entries.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_rejects_html() {
+        assert!(parse_sitemap(b"<html><body>Not found</body></html>").is_err());
+    }
+
+    #[test]
+    fn test_entry_cap() {
+        let body = (0..MAX_SITEMAP_ENTRIES + 5)
+            .map(|i| format!("https://example.com/{i}\n"))
+            .collect::<String>();
+        let sitemap = parse_sitemap(body.as_bytes()).unwrap();
+        assert_eq!(sitemap.entries.len(), MAX_SITEMAP_ENTRIES);
+        assert!(sitemap.truncated);
+    }
+
+    #[test]
+    fn test_sitemaps_from_robots() {
+        let robots = concat!(
+            "User-agent: *\nDisallow: /private\n",
+            "Sitemap: https://example.com/s1.xml\n",
+            "sitemap:https://example.com/s2.xml.gz\n",
+            "Sitemap: /relative.xml\n",
+        );
+        assert_eq!(
+            sitemaps_from_robots(robots),
+            vec!["https://example.com/s1.xml", "https://example.com/s2.xml.gz"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_discover_and_fetch() {
+        let server = FixtureServer::start(|req| match req.path.as_str() {
+            "/sitemap.xml" => FixtureResponse::ok("application/x-gzip", gzip(URLSET.as_bytes())),
+            _ => FixtureResponse::status(404),
+        })
+        .await;
+
+        // The client's own allowlist doesn't admit gzip; the sitemap fetch does.
+        let config = FetchConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = FetchClient::new(config).unwrap();
+        let root = Url::parse(&server.url("/")).unwrap();
+        let options = RequestOptions::default();
+
+        let found = discover_sitemaps(&client, &root, &options).await;
+        assert_eq!(found, vec![server.url("/sitemap.xml")]);
+
+        let sitemap = fetch_sitemap(&client, &found[0], &options).await.unwrap();
+        assert_eq!(sitemap.entries.len(), 2);
+    }
+}
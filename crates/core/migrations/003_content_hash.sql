@@ -0,0 +1,5 @@
+-- Migration 3: Add content_hash column to snapshots
+-- Stores sha256 of the fetched body so refetches with identical content can
+-- reuse the previous extraction instead of running the pipeline again
+
+ALTER TABLE snapshots ADD COLUMN content_hash TEXT;
@@ -0,0 +1,5 @@
+-- Migration 4: Add pinned flag to snapshots
+-- Pinned snapshots are skipped by every purge strategy unless the caller
+-- explicitly opts in with include_pinned
+
+ALTER TABLE snapshots ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,10 @@
+-- Migration 5: Create sitemap_cache table
+-- Stores parsed sitemaps (JSON) keyed by sitemap URL with a short TTL
+-- This migration is idempotent: using CREATE TABLE IF NOT EXISTS
+
+CREATE TABLE IF NOT EXISTS sitemap_cache (
+    url             TEXT PRIMARY KEY,
+    sitemap_json    TEXT NOT NULL,
+    fetched_at      TEXT NOT NULL,
+    expires_at      TEXT NOT NULL
+);
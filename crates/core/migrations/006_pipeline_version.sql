@@ -0,0 +1,5 @@
+-- Migration 6: Add pipeline_version column to snapshots
+-- Records the normalization/link-extraction pipeline revision that produced the
+-- extraction so snapshots from an older pipeline can be re-extracted on read
+
+ALTER TABLE snapshots ADD COLUMN pipeline_version INTEGER;
@@ -0,0 +1,10 @@
+-- Migration 7: Create brave_usage table
+-- Counts Brave API requests and searches answered from the cache per UTC month
+-- ("YYYY-MM") so spend can be estimated
+-- This migration is idempotent: using CREATE TABLE IF NOT EXISTS
+
+CREATE TABLE IF NOT EXISTS brave_usage (
+    month           TEXT PRIMARY KEY,
+    requests        INTEGER NOT NULL DEFAULT 0,
+    cache_hits      INTEGER NOT NULL DEFAULT 0
+);
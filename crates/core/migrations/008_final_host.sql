@@ -0,0 +1,18 @@
+-- Migration 8: Add final_host column to snapshots
+-- Host of final_url after redirects, so purge-by-domain also reaches content
+-- that was fetched through a redirect from another host
+
+ALTER TABLE snapshots ADD COLUMN final_host TEXT;
+
+UPDATE snapshots SET final_host = lower(
+    CASE
+        WHEN instr(substr(final_url, instr(final_url, '://') + 3), '/') > 0
+        THEN substr(
+            substr(final_url, instr(final_url, '://') + 3),
+            1,
+            instr(substr(final_url, instr(final_url, '://') + 3), '/') - 1
+        )
+        ELSE substr(final_url, instr(final_url, '://') + 3)
+    END
+)
+WHERE final_url LIKE '%://%';
@@ -0,0 +1,13 @@
+-- Migration 9: Add host column to snapshots and index both host columns
+-- host is the requested URL's host. Purge-by-domain matches it and final_host
+-- exactly (or as a parent domain) instead of a substring of the whole URL.
+-- Existing rows are filled in by the Rust backfill that runs after migrations,
+-- which also normalizes final_host values that migration 8 left with a port
+-- or userinfo.
+
+ALTER TABLE snapshots ADD COLUMN host TEXT;
+
+UPDATE snapshots SET final_host = NULL;
+
+CREATE INDEX IF NOT EXISTS idx_snapshots_host ON snapshots(host);
+CREATE INDEX IF NOT EXISTS idx_snapshots_final_host ON snapshots(final_host);
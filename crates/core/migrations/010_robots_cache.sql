@@ -0,0 +1,11 @@
+-- Migration 10: Create robots_cache table
+-- Stores raw robots.txt bodies keyed by origin so the rules (and Crawl-delay)
+-- survive server restarts; the client re-parses the body on load
+-- This migration is idempotent: using CREATE TABLE IF NOT EXISTS
+
+CREATE TABLE IF NOT EXISTS robots_cache (
+    origin          TEXT PRIMARY KEY,
+    body            TEXT NOT NULL,
+    status          INTEGER NOT NULL,
+    fetched_at      TEXT NOT NULL
+);
@@ -0,0 +1,5 @@
+-- Migration 11: Add detected_charset column to snapshots
+-- Records the encoding the body was decoded with (from the Content-Type
+-- charset, a BOM, or a <meta charset> sniff) so cached bodies decode the same way
+
+ALTER TABLE snapshots ADD COLUMN detected_charset TEXT;
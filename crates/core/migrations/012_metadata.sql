@@ -0,0 +1,5 @@
+-- Migration 12: Add metadata_json column to snapshots
+-- Author, publication date, description, social card fields, and canonical URL
+-- read from the page's <meta> and <link> tags; NULL when the page has none
+
+ALTER TABLE snapshots ADD COLUMN metadata_json TEXT;
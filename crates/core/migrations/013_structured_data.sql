@@ -0,0 +1,5 @@
+-- Migration 13: Add structured_data_json column to snapshots
+-- JSON-LD items (schema.org Article, BreadcrumbList, FAQPage, ...) the page
+-- embeds, as a JSON array; NULL when it embeds none
+
+ALTER TABLE snapshots ADD COLUMN structured_data_json TEXT;
@@ -0,0 +1,5 @@
+-- Migration 14: Add images_json column to snapshots
+-- Images harvested from the page (resolved src, alt, title, declared size) as
+-- a JSON array; NULL when the page had none worth keeping
+
+ALTER TABLE snapshots ADD COLUMN images_json TEXT;
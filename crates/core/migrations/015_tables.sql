@@ -0,0 +1,5 @@
+-- Migration 15: Add tables_json column to snapshots
+-- Tables too complex for markdown (row or column spans) as a JSON array of
+-- headers and rows; NULL when the page had none
+
+ALTER TABLE snapshots ADD COLUMN tables_json TEXT;
@@ -0,0 +1,5 @@
+-- Migration 16: Add outline_json column to snapshots
+-- The extracted content's h1-h6 headings, in document order, as a JSON array;
+-- NULL when the content had none
+
+ALTER TABLE snapshots ADD COLUMN outline_json TEXT;
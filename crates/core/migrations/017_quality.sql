@@ -0,0 +1,5 @@
+-- Migration 17: Add quality_json column to snapshots
+-- Word count, reading time, paragraph count, and link density of the extracted
+-- content as a JSON object; NULL for content that is not an HTML extraction
+
+ALTER TABLE snapshots ADD COLUMN quality_json TEXT;
@@ -5,16 +5,21 @@
 
 use super::migrations;
 use crate::Error;
+use crate::clock::{Clock, SystemClock};
+use chrono::{DateTime, Utc};
 use std::path::Path;
+use std::sync::Arc;
 use tokio_rusqlite::Connection;
 
 /// Cache database handle.
 ///
 /// Wraps a tokio-rusqlite Connection that runs database operations
-/// on a background thread.
+/// on a background thread. Timestamps and expiry checks use the handle's
+/// [`Clock`], which defaults to [`SystemClock`].
 #[derive(Clone, Debug)]
 pub struct CacheDb {
     pub(crate) conn: Connection,
+    clock: Arc<dyn Clock>,
 }
 
 impl CacheDb {
@@ -39,7 +44,7 @@ impl CacheDb {
 
         migrations::run(&conn).await?;
 
-        Ok(Self { conn })
+        Ok(Self { conn, clock: Arc::new(SystemClock) })
     }
 
     /// Open an in-memory database for testing.
@@ -65,7 +70,41 @@ impl CacheDb {
 
         migrations::run(&conn).await?;
 
-        Ok(Self { conn })
+        Ok(Self { conn, clock: Arc::new(SystemClock) })
+    }
+
+    /// Replace the clock used for timestamps and expiry checks.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Current time according to this handle's clock.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    /// The time `days` days ago, for age-based purges; `None` when out of range.
+    pub(crate) fn cutoff(&self, days: i64) -> Option<String> {
+        let age = chrono::Duration::try_days(days)?;
+        self.now().checked_sub_signed(age).map(|at| at.to_rfc3339())
+    }
+
+    /// Make every later snapshot insert or update fail, as a full disk would.
+    #[cfg(any(test, feature = "test-util"))]
+    pub async fn fail_snapshot_writes(&self) -> Result<(), Error> {
+        self.conn
+            .call(|conn| {
+                conn.execute_batch(
+                    "CREATE TEMP TRIGGER fail_snapshot_insert BEFORE INSERT ON main.snapshots
+                     BEGIN SELECT RAISE(ABORT, 'database or disk is full'); END;
+                     CREATE TEMP TRIGGER fail_snapshot_update BEFORE UPDATE ON main.snapshots
+                     BEGIN SELECT RAISE(ABORT, 'database or disk is full'); END;",
+                )?;
+                Ok(())
+            })
+            .await
+            .map_err(Error::Database)
     }
 }
 
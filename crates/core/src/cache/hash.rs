@@ -2,46 +2,78 @@
 
 use sha2::{Digest, Sha256};
 
+use crate::Mode;
+
 /// Compute a content-addressed cache key for a document snapshot.
-pub fn compute_cache_key(url: &str, vary_headers: &str, mode: &str) -> String {
+///
+/// The mode is hashed by its lowercase name, so keys are stable across releases.
+pub fn compute_cache_key(url: &str, vary_headers: &str, mode: Mode) -> String {
     let mut hasher = Sha256::new();
     hasher.update(url.as_bytes());
     hasher.update(b"\n");
     hasher.update(vary_headers.as_bytes());
     hasher.update(b"\n");
-    hasher.update(mode.as_bytes());
+    hasher.update(mode.as_str().as_bytes());
     hex::encode(hasher.finalize())
 }
 
+/// Compute a hash of a fetched response body.
+///
+/// Used to detect refetches that returned byte-identical content.
+pub fn compute_content_hash(body: &[u8]) -> String {
+    hex::encode(Sha256::digest(body))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_hash_stability() {
-        let hash1 = compute_cache_key("https://example.com", "", "readable");
-        let hash2 = compute_cache_key("https://example.com", "", "readable");
+        let hash1 = compute_cache_key("https://example.com", "", Mode::Readable);
+        let hash2 = compute_cache_key("https://example.com", "", Mode::Readable);
         assert_eq!(hash1, hash2);
     }
 
     #[test]
     fn test_hash_different_mode() {
-        let hash_raw = compute_cache_key("https://example.com", "", "raw");
-        let hash_readable = compute_cache_key("https://example.com", "", "readable");
+        let hash_raw = compute_cache_key("https://example.com", "", Mode::Raw);
+        let hash_readable = compute_cache_key("https://example.com", "", Mode::Readable);
         assert_ne!(hash_raw, hash_readable);
     }
 
+    #[test]
+    fn test_hash_uses_lowercase_mode_name() {
+        assert_eq!(
+            compute_cache_key("https://example.com", "", Mode::Readable),
+            "4547a757be23b38dc43e3818537e230711710c6e9f6656e80a0f5c5351d21ebe"
+        );
+    }
+
     #[test]
     fn test_hash_different_headers() {
-        let hash1 = compute_cache_key("https://example.com", "gzip", "readable");
-        let hash2 = compute_cache_key("https://example.com", "br", "readable");
+        let hash1 = compute_cache_key("https://example.com", "gzip", Mode::Readable);
+        let hash2 = compute_cache_key("https://example.com", "br", Mode::Readable);
         assert_ne!(hash1, hash2);
     }
 
     #[test]
     fn test_hash_format() {
-        let hash = compute_cache_key("https://example.com", "", "readable");
+        let hash = compute_cache_key("https://example.com", "", Mode::Readable);
         assert_eq!(hash.len(), 64);
         assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
     }
+
+    #[test]
+    fn test_content_hash_stability() {
+        assert_eq!(
+            compute_content_hash(b"<html></html>"),
+            compute_content_hash(b"<html></html>")
+        );
+        assert_ne!(
+            compute_content_hash(b"<html></html>"),
+            compute_content_hash(b"<html> </html>")
+        );
+        assert_eq!(compute_content_hash(b"").len(), 64);
+    }
 }
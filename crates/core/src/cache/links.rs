@@ -0,0 +1,191 @@
+//! Versioned storage format for a snapshot's harvested links.
+//!
+//! `links_json` is written as a [`StoredLinks`] envelope. Rows written before
+//! the envelope existed hold a bare `[{"text":..,"href":..}]` array; those
+//! parse as version 0. Malformed payloads are an error, never an empty list,
+//! so callers can tell "no links" from "links we couldn't read".
+//!
+//! Feeds a page advertises ride along in the same envelope; rows without them
+//! read as having none.
+
+use serde::{Deserialize, Serialize};
+
+use super::snapshots::Snapshot;
+
+/// Version written by [`StoredLinks::new`].
+pub const LINKS_VERSION: u8 = 1;
+
+/// A harvested link.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct Link {
+    /// Link text content.
+    #[serde(default)]
+    pub text: String,
+    /// Resolved href URL.
+    pub href: String,
+    /// Where the link points relative to the page.
+    #[serde(default)]
+    pub kind: LinkKind,
+    /// The link appears in the extracted content, not only in navigation or other page chrome.
+    #[serde(default)]
+    pub in_content: bool,
+}
+
+/// Where a link points relative to the page it was found on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    /// Another page on the same host.
+    #[default]
+    Internal,
+    /// A page on another host, or a non-HTTP target such as `mailto:`.
+    External,
+    /// A fragment of the page itself, e.g. a table-of-contents entry.
+    Anchor,
+}
+
+/// An RSS, Atom, or JSON feed a page advertises with `<link rel="alternate">`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct FeedLink {
+    /// Resolved feed URL.
+    pub href: String,
+    /// The link's `title` attribute.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The link's `type` attribute, e.g. `application/rss+xml`.
+    #[serde(default)]
+    pub media_type: String,
+}
+
+/// The `links_json` envelope.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredLinks {
+    /// Format version; 0 for legacy bare arrays.
+    pub version: u8,
+    pub links: Vec<Link>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub feeds: Vec<FeedLink>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Payload {
+    Envelope(StoredLinks),
+    Legacy(Vec<Link>),
+}
+
+impl StoredLinks {
+    /// Wrap links in the current format version.
+    pub fn new(links: Vec<Link>) -> Self {
+        Self { version: LINKS_VERSION, links, feeds: Vec::new() }
+    }
+
+    /// Attach the feeds the page advertises.
+    pub fn with_feeds(mut self, feeds: Vec<FeedLink>) -> Self {
+        self.feeds = feeds;
+        self
+    }
+
+    /// Serialize for the `links_json` column.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("links serialize to JSON")
+    }
+
+    /// Parse a `links_json` value in any known format.
+    pub fn parse(json: &str) -> Result<Self, serde_json::Error> {
+        match serde_json::from_str(json)? {
+            Payload::Envelope(stored) => Ok(stored),
+            Payload::Legacy(links) => Ok(Self { version: 0, links, feeds: Vec::new() }),
+        }
+    }
+}
+
+impl Snapshot {
+    /// The snapshot's links; empty when none were stored.
+    pub fn links(&self) -> Result<Vec<Link>, serde_json::Error> {
+        match &self.links_json {
+            Some(json) => StoredLinks::parse(json).map(|stored| stored.links),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Feeds the snapshot's page advertised; empty when none were stored.
+    pub fn feeds(&self) -> Result<Vec<FeedLink>, serde_json::Error> {
+        match &self.links_json {
+            Some(json) => StoredLinks::parse(json).map(|stored| stored.feeds),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(text: &str, href: &str) -> Link {
+        Link { text: text.into(), href: href.into(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_round_trip_writes_current_version() {
+        let stored = StoredLinks::new(vec![link("Docs", "https://example.com/docs")]);
+        let json = stored.to_json();
+        assert!(json.starts_with(r#"{"version":1,"#), "{json}");
+        assert_eq!(StoredLinks::parse(&json).unwrap(), stored);
+    }
+
+    #[test]
+    fn test_link_kind_round_trips() {
+        let anchor = Link { kind: LinkKind::Anchor, in_content: true, ..link("Intro", "https://example.com/#intro") };
+        let stored = StoredLinks::new(vec![anchor]);
+        let json = stored.to_json();
+        assert!(json.contains(r#""kind":"anchor","in_content":true"#), "{json}");
+        assert_eq!(StoredLinks::parse(&json).unwrap(), stored);
+
+        let legacy = StoredLinks::parse(r#"{"version":1,"links":[{"text":"A","href":"/a"}]}"#).unwrap();
+        assert_eq!(legacy.links[0].kind, LinkKind::Internal);
+        assert!(!legacy.links[0].in_content);
+    }
+
+    #[test]
+    fn test_legacy_array_parses_as_version_zero() {
+        let parsed = StoredLinks::parse(r#"[{"text":"Home","href":"https://example.com/"},{"href":"/x"}]"#).unwrap();
+        assert_eq!(parsed.version, 0);
+        assert_eq!(parsed.links, vec![link("Home", "https://example.com/"), link("", "/x")]);
+
+        assert_eq!(
+            StoredLinks::parse("[]").unwrap(),
+            StoredLinks { version: 0, links: vec![], feeds: vec![] }
+        );
+    }
+
+    #[test]
+    fn test_feeds_round_trip_and_default_to_empty() {
+        let feed = FeedLink {
+            href: "https://example.com/feed.xml".into(),
+            title: Some("Posts".into()),
+            media_type: "application/rss+xml".into(),
+        };
+        let stored = StoredLinks::new(vec![link("Docs", "/docs")]).with_feeds(vec![feed]);
+        assert_eq!(StoredLinks::parse(&stored.to_json()).unwrap(), stored);
+
+        let json = StoredLinks::new(vec![]).to_json();
+        assert!(!json.contains("feeds"), "{json}");
+        assert!(StoredLinks::parse(&json).unwrap().feeds.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_fields_are_ignored() {
+        let parsed =
+            StoredLinks::parse(r#"{"version":2,"links":[{"text":"A","href":"/a","rel":"nofollow"}]}"#).unwrap();
+        assert_eq!(parsed.version, 2);
+        assert_eq!(parsed.links, vec![link("A", "/a")]);
+    }
+
+    #[test]
+    fn test_corrupted_payloads_are_errors() {
+        for json in ["", "{", r#"{"links":"nope"}"#, r#"[{"text":"no href"}]"#, "null"] {
+            assert!(StoredLinks::parse(json).is_err(), "{json:?}");
+        }
+    }
+}
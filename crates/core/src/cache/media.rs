@@ -0,0 +1,65 @@
+//! Images harvested from a snapshot's page.
+//!
+//! `images_json` holds a JSON array of [`Image`], or NULL when the page had
+//! none worth keeping.
+
+use serde::{Deserialize, Serialize};
+
+use super::snapshots::Snapshot;
+
+/// An `<img>` on a page.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct Image {
+    /// Resolved image URL; the largest `srcset` candidate when there is one.
+    pub src: String,
+    /// The `alt` text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt: Option<String>,
+    /// The `title` attribute.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Declared width in CSS pixels.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    /// Declared height in CSS pixels.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+}
+
+/// Serialize images for the `images_json` column; `None` when there are none.
+pub fn images_json(images: &[Image]) -> Option<String> {
+    (!images.is_empty()).then(|| serde_json::to_string(images).expect("images serialize to JSON"))
+}
+
+impl Snapshot {
+    /// Images harvested from the snapshot's page; empty when none were stored.
+    pub fn images(&self) -> Result<Vec<Image>, serde_json::Error> {
+        match &self.images_json {
+            Some(json) => serde_json::from_str(json),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_images_json_round_trip() {
+        assert!(images_json(&[]).is_none());
+
+        let images = vec![Image {
+            src: "https://example.com/a.png".into(),
+            alt: Some("A chart".into()),
+            width: Some(640),
+            ..Default::default()
+        }];
+        let json = images_json(&images).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"src":"https://example.com/a.png","alt":"A chart","width":640}]"#
+        );
+        assert_eq!(serde_json::from_str::<Vec<Image>>(&json).unwrap(), images);
+    }
+}
@@ -0,0 +1,108 @@
+//! Page metadata stored with a snapshot.
+//!
+//! `metadata_json` holds a [`PageMetadata`] object, or NULL when the page
+//! declared none of its fields. Unknown fields are ignored.
+//!
+//! `structured_data_json` holds the page's JSON-LD items as an array, or NULL
+//! when it embedded none.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::snapshots::Snapshot;
+
+/// Citation and deduplication metadata a page declares about itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct PageMetadata {
+    /// Author name (or profile URL, when that is all the page gives).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Publication date as the page writes it, usually ISO 8601.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+    /// Summary from the description, `og:description`, or `twitter:description`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Name of the site, e.g. from `og:site_name`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub site_name: Option<String>,
+    /// Resolved URL of the preview image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// Twitter card type, e.g. `summary_large_image`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub twitter_card: Option<String>,
+    /// Resolved canonical URL, from `<link rel="canonical">` or `og:url`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical_url: Option<String>,
+}
+
+impl PageMetadata {
+    /// Whether no field is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Serialize for the `metadata_json` column; `None` when empty.
+    pub fn to_json(&self) -> Option<String> {
+        (!self.is_empty()).then(|| serde_json::to_string(self).expect("metadata serializes to JSON"))
+    }
+}
+
+/// Serialize JSON-LD items for the `structured_data_json` column; `None` when empty.
+pub fn structured_data_json(items: &[Value]) -> Option<String> {
+    (!items.is_empty()).then(|| serde_json::to_string(items).expect("JSON values serialize"))
+}
+
+impl Snapshot {
+    /// JSON-LD items stored with the snapshot; empty when none were stored.
+    pub fn structured_data(&self) -> Result<Vec<Value>, serde_json::Error> {
+        match &self.structured_data_json {
+            Some(json) => serde_json::from_str(json),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// The page metadata stored with the snapshot; empty when none was stored.
+    pub fn metadata(&self) -> Result<PageMetadata, serde_json::Error> {
+        match &self.metadata_json {
+            Some(json) => serde_json::from_str(json),
+            None => Ok(PageMetadata::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_skips_empty_fields() {
+        let metadata = PageMetadata {
+            author: Some("Ada".into()),
+            canonical_url: Some("https://example.com/post".into()),
+            ..Default::default()
+        };
+        let json = metadata.to_json().unwrap();
+        assert_eq!(json, r#"{"author":"Ada","canonical_url":"https://example.com/post"}"#);
+        assert_eq!(serde_json::from_str::<PageMetadata>(&json).unwrap(), metadata);
+
+        assert!(PageMetadata::default().to_json().is_none());
+        assert!(
+            serde_json::from_str::<PageMetadata>(r#"{"author":"Ada","locale":"en"}"#)
+                .unwrap()
+                .author
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_structured_data_json() {
+        assert!(structured_data_json(&[]).is_none());
+        let items = vec![serde_json::json!({"@type": "FAQPage"})];
+        assert_eq!(
+            structured_data_json(&items).as_deref(),
+            Some(r#"[{"@type":"FAQPage"}]"#)
+        );
+    }
+}
@@ -6,7 +6,8 @@
 use std::num::ParseIntError;
 
 use super::Error;
-use tokio_rusqlite::{Connection, params};
+use super::snapshots::url_host;
+use tokio_rusqlite::{Connection, params, rusqlite};
 
 /// Migration list: (version, description, SQL).
 ///
@@ -16,6 +17,21 @@ use tokio_rusqlite::{Connection, params};
 const MIGRATIONS: &[(&str, &str)] = &[
     ("1", include_str!("../../migrations/001_snapshots.sql")),
     ("2", include_str!("../../migrations/002_search_cache.sql")),
+    ("3", include_str!("../../migrations/003_content_hash.sql")),
+    ("4", include_str!("../../migrations/004_pinned.sql")),
+    ("5", include_str!("../../migrations/005_sitemap_cache.sql")),
+    ("6", include_str!("../../migrations/006_pipeline_version.sql")),
+    ("7", include_str!("../../migrations/007_brave_usage.sql")),
+    ("8", include_str!("../../migrations/008_final_host.sql")),
+    ("9", include_str!("../../migrations/009_host.sql")),
+    ("10", include_str!("../../migrations/010_robots_cache.sql")),
+    ("11", include_str!("../../migrations/011_detected_charset.sql")),
+    ("12", include_str!("../../migrations/012_metadata.sql")),
+    ("13", include_str!("../../migrations/013_structured_data.sql")),
+    ("14", include_str!("../../migrations/014_images.sql")),
+    ("15", include_str!("../../migrations/015_tables.sql")),
+    ("16", include_str!("../../migrations/016_outline.sql")),
+    ("17", include_str!("../../migrations/017_quality.sql")),
 ];
 
 /// Run any pending migrations.
@@ -61,12 +77,40 @@ pub async fn run(conn: &Connection) -> Result<(), Error> {
             }
         }
 
-        Ok(())
+        backfill_hosts(conn)
     })
     .await
     .map_err(Error::from)
 }
 
+/// Fill `host` and `final_host` for snapshots written before those columns existed.
+///
+/// Host parsing lives in [`url_host`] rather than SQL so that backfilled rows
+/// match new ones exactly. Rows already filled are skipped, so this is cheap
+/// after the first run.
+fn backfill_hosts(conn: &mut rusqlite::Connection) -> Result<(), Error> {
+    let tx = conn.transaction()?;
+    {
+        let mut select =
+            tx.prepare("SELECT hash, url, final_url FROM snapshots WHERE host IS NULL OR final_host IS NULL")?;
+        let rows = select
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut update = tx.prepare("UPDATE snapshots SET host = ?2, final_host = ?3 WHERE hash = ?1")?;
+        for (hash, url, final_url) in rows {
+            update.execute(params![hash, url_host(&url), url_host(&final_url)])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -103,4 +147,41 @@ mod tests {
 
         assert_eq!(count, MIGRATIONS.len() as i64);
     }
+
+    #[tokio::test]
+    async fn test_host_backfill_normalizes_existing_rows() {
+        let conn = Connection::open_in_memory().await.unwrap();
+        conn.call(|conn| -> Result<(), Error> {
+            conn.execute_batch(
+                "CREATE TABLE _migrations (version INTEGER PRIMARY KEY, applied_at TEXT NOT NULL);",
+            )?;
+            for (version, sql) in &MIGRATIONS[..8] {
+                conn.execute_batch(sql)?;
+                conn.execute(
+                    "INSERT INTO _migrations (version, applied_at) VALUES (?1, '2025-01-01T00:00:00Z')",
+                    params![version.parse::<i64>().unwrap()],
+                )?;
+            }
+            conn.execute(
+                "INSERT INTO snapshots (hash, url, final_url, mode, fetched_at)
+                 VALUES ('h', 'https://Docs.Example.com/a', 'http://user@cdn.example.net:8080/b', 'readable', '2025-01-01')",
+                [],
+            )?;
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        run(&conn).await.unwrap();
+
+        let hosts: (String, String) = conn
+            .call(|conn| {
+                conn.query_row("SELECT host, final_host FROM snapshots WHERE hash = 'h'", [], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })
+            })
+            .await
+            .unwrap();
+        assert_eq!(hosts, ("docs.example.com".to_string(), "cdn.example.net".to_string()));
+    }
 }
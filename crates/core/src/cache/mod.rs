@@ -7,17 +7,44 @@
 //!   - `hash = sha256(normalized_url + vary_headers + mode)`
 //! - Automatic schema migrations
 //! - WAL mode for concurrent access, NORMAL synchronous
-//! - Multiple purge strategies (age, domain, LRU-ish size ceiling)
+//! - Harvested links stored in a versioned envelope, classified as internal, external, or anchor
+//! - Page metadata (author, dates, social cards, canonical URL), JSON-LD
+//!   structured data, harvested images, tables too complex for markdown, the
+//!   heading outline, and content-quality signals as JSON
+//! - Parsed sitemaps cached by URL with a short TTL
+//! - Raw robots.txt bodies keyed by origin, so rules outlive restarts
+//! - Monthly Brave API request / cache-hit counters for spend estimates
+//! - Multiple purge strategies (age, domain, LRU-ish size ceiling), all of
+//!   which skip pinned snapshots unless told otherwise
 //! - Revalidation via ETag/Last-Modified or TTL-based expiry
 
 pub mod connection;
 pub mod hash;
+pub mod links;
+pub mod media;
+pub mod metadata;
 pub mod migrations;
+pub mod outline;
+pub mod quality;
+pub mod robots;
 pub mod search;
+pub mod sitemaps;
 pub mod snapshots;
+pub mod stats;
+pub mod tables;
+pub mod usage;
 
 pub use crate::Error;
 
 pub use connection::CacheDb;
+pub use links::{FeedLink, Link, LinkKind, StoredLinks};
+pub use media::{Image, images_json};
+pub use metadata::{PageMetadata, structured_data_json};
+pub use outline::{Heading, outline_json};
+pub use quality::{ContentQuality, quality_json};
+pub use robots::StoredRobots;
 pub use search::SearchCacheMeta;
-pub use snapshots::Snapshot;
+pub use snapshots::{Snapshot, SnapshotSummary};
+pub use stats::CacheStats;
+pub use tables::{Table, tables_json};
+pub use usage::{BravePricing, BraveUsage, UsageReport};
@@ -0,0 +1,58 @@
+//! Heading outline of the extracted content.
+//!
+//! The h1–h6 headings of a page's main content, in document order, so a long
+//! document can be skimmed before reading a section of it. `outline_json`
+//! holds a JSON array of [`Heading`], or NULL when there were none.
+
+use serde::{Deserialize, Serialize};
+
+use super::snapshots::Snapshot;
+
+/// One heading of the outline.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct Heading {
+    /// 1 for `<h1>` through 6 for `<h6>`.
+    pub level: u8,
+    /// Heading text, whitespace collapsed.
+    pub text: String,
+    /// Fragment identifier of the heading, so `#id` against the final URL
+    /// links to it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+}
+
+/// Serialize an outline for the `outline_json` column; `None` when it is empty.
+pub fn outline_json(outline: &[Heading]) -> Option<String> {
+    (!outline.is_empty()).then(|| serde_json::to_string(outline).expect("outline serializes to JSON"))
+}
+
+impl Snapshot {
+    /// Heading outline of the snapshot's content; empty when none was stored.
+    pub fn outline(&self) -> Result<Vec<Heading>, serde_json::Error> {
+        match &self.outline_json {
+            Some(json) => serde_json::from_str(json),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outline_json_round_trip() {
+        assert!(outline_json(&[]).is_none());
+
+        let outline = vec![
+            Heading { level: 1, text: "Guide".into(), id: None },
+            Heading { level: 2, text: "Installation".into(), id: Some("installation".into()) },
+        ];
+        let json = outline_json(&outline).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"level":1,"text":"Guide"},{"level":2,"text":"Installation","id":"installation"}]"#
+        );
+        assert_eq!(serde_json::from_str::<Vec<Heading>>(&json).unwrap(), outline);
+    }
+}
@@ -0,0 +1,66 @@
+//! Content-quality signals of an extraction.
+//!
+//! Word count, reading time, paragraph count, and link density of the
+//! extracted content, so a caller can tell an article from a cookie banner or
+//! a page of navigation that readability settled on. `quality_json` holds a
+//! JSON object of [`ContentQuality`], or NULL when the content was not
+//! measured.
+
+use serde::{Deserialize, Serialize};
+
+use super::snapshots::Snapshot;
+
+/// Signals of how much of the extracted content is prose.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ContentQuality {
+    /// Words of visible text.
+    pub word_count: usize,
+    /// Minutes to read `word_count` words at an average adult pace, rounded up.
+    pub estimated_reading_minutes: usize,
+    /// Blocks of running text long enough to be prose rather than labels or menu items.
+    pub paragraph_count: usize,
+    /// Share of the text's characters that are link text, 0 to 1.
+    pub link_density: f64,
+    /// Mostly links and little prose: likely navigation, a link list, or a
+    /// banner rather than the page's content.
+    pub likely_boilerplate: bool,
+}
+
+/// Serialize quality signals for the `quality_json` column; `None` when there are none.
+pub fn quality_json(quality: Option<&ContentQuality>) -> Option<String> {
+    quality.map(|quality| serde_json::to_string(quality).expect("quality serializes to JSON"))
+}
+
+impl Snapshot {
+    /// Content-quality signals of the snapshot's extraction; `None` when none were stored.
+    pub fn quality(&self) -> Result<Option<ContentQuality>, serde_json::Error> {
+        self.quality_json.as_deref().map(serde_json::from_str).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quality_json_round_trip() {
+        assert!(quality_json(None).is_none());
+
+        let quality = ContentQuality {
+            word_count: 480,
+            estimated_reading_minutes: 3,
+            paragraph_count: 6,
+            link_density: 0.125,
+            likely_boilerplate: false,
+        };
+        let json = quality_json(Some(&quality)).unwrap();
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"word_count":480,"estimated_reading_minutes":3,"paragraph_count":6,"#,
+                r#""link_density":0.125,"likely_boilerplate":false}"#
+            )
+        );
+        assert_eq!(serde_json::from_str::<ContentQuality>(&json).unwrap(), quality);
+    }
+}
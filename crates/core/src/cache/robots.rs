@@ -0,0 +1,118 @@
+//! robots.txt cache operations.
+//!
+//! Stores the raw robots.txt body per origin (`scheme://host[:port]`) so the
+//! fetch client can rebuild its rules after a restart without refetching.
+
+use super::connection::CacheDb;
+use crate::Error;
+use chrono::Duration;
+use tokio_rusqlite::params;
+
+/// A robots.txt body as last fetched from an origin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredRobots {
+    /// Response body; empty when the origin answered with a 4xx (allow all).
+    pub body: String,
+    /// HTTP status of the robots.txt response.
+    pub status: u16,
+    pub fetched_at: String,
+}
+
+impl CacheDb {
+    /// Get the robots.txt stored for `origin` if it was fetched within `max_age`.
+    pub async fn get_robots(&self, origin: &str, max_age: Duration) -> Result<Option<StoredRobots>, Error> {
+        let origin = origin.to_string();
+        let Some(cutoff) = self.now().checked_sub_signed(max_age) else {
+            return Ok(None);
+        };
+        let cutoff = cutoff.to_rfc3339();
+        self.conn
+            .call(move |conn| -> Result<Option<StoredRobots>, Error> {
+                let mut stmt = conn.prepare(
+                    "SELECT body, status, fetched_at FROM robots_cache WHERE origin = ?1 AND fetched_at > ?2",
+                )?;
+
+                match stmt.query_row(params![origin, cutoff], |row| {
+                    Ok(StoredRobots { body: row.get(0)?, status: row.get(1)?, fetched_at: row.get(2)? })
+                }) {
+                    Ok(stored) => Ok(Some(stored)),
+                    Err(tokio_rusqlite::rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                    Err(e) => Err(e.into()),
+                }
+            })
+            .await
+            .map_err(Error::from)
+    }
+
+    /// Insert or replace the robots.txt stored for `origin`, stamped now.
+    pub async fn put_robots(&self, origin: &str, body: &str, status: u16) -> Result<(), Error> {
+        let origin = origin.to_string();
+        let body = body.to_string();
+        let fetched_at = self.now().to_rfc3339();
+
+        self.conn
+            .call(move |conn| -> Result<(), Error> {
+                conn.execute(
+                    "INSERT INTO robots_cache (origin, body, status, fetched_at)
+                    VALUES (?1, ?2, ?3, ?4)
+                    ON CONFLICT(origin) DO UPDATE SET
+                        body = excluded.body,
+                        status = excluded.status,
+                        fetched_at = excluded.fetched_at",
+                    params![origin, body, status, fetched_at],
+                )?;
+                Ok(())
+            })
+            .await
+            .map_err(Error::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ManualClock;
+
+    #[tokio::test]
+    async fn test_put_and_get_robots() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let origin = "https://example.com:8443";
+        let day = Duration::hours(24);
+
+        assert!(db.get_robots(origin, day).await.unwrap().is_none());
+
+        db.put_robots(origin, "User-agent: *\nDisallow: /a\n", 200)
+            .await
+            .unwrap();
+        db.put_robots(origin, "", 404).await.unwrap();
+
+        let stored = db.get_robots(origin, day).await.unwrap().unwrap();
+        assert_eq!((stored.body.as_str(), stored.status), ("", 404));
+        assert!(db.get_robots("https://example.com", day).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stale_robots_is_a_miss() {
+        let clock = ManualClock::default();
+        let db = CacheDb::open_in_memory().await.unwrap().with_clock(clock.clone());
+        db.put_robots("https://example.com", "User-agent: *\n", 200)
+            .await
+            .unwrap();
+
+        clock.advance(Duration::hours(23));
+        assert!(
+            db.get_robots("https://example.com", Duration::hours(24))
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        clock.advance(Duration::hours(2));
+        assert!(
+            db.get_robots("https://example.com", Duration::hours(24))
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -4,7 +4,7 @@
 
 use super::connection::CacheDb;
 use crate::Error;
-use chrono::{Duration, Utc};
+use chrono::Duration;
 use serde::{Deserialize, Serialize};
 use tokio_rusqlite::params;
 
@@ -65,7 +65,7 @@ impl CacheDb {
     /// Returns false if the entry doesn't exist or has expired.
     pub async fn is_search_fresh(&self, key_hash: &str) -> Result<bool, Error> {
         let key_hash = key_hash.to_string();
-        let now = Utc::now().to_rfc3339();
+        let now = self.now().to_rfc3339();
         self.conn
             .call(move |conn| -> Result<bool, Error> {
                 let fresh: bool = conn
@@ -96,8 +96,9 @@ impl CacheDb {
         let query_json = query_json.to_string();
         let response_json = response_json.to_string();
 
-        let fetched_at = Utc::now().to_rfc3339();
-        let expires_at = (Utc::now() + Duration::seconds(ttl_seconds)).to_rfc3339();
+        let now = self.now();
+        let fetched_at = now.to_rfc3339();
+        let expires_at = (now + Duration::seconds(ttl_seconds)).to_rfc3339();
 
         self.conn
             .call(move |conn| -> Result<(), Error> {
@@ -121,7 +122,7 @@ impl CacheDb {
     ///
     /// Returns the number of deleted entries.
     pub async fn purge_expired_search(&self) -> Result<u64, Error> {
-        let now = Utc::now().to_rfc3339();
+        let now = self.now().to_rfc3339();
         self.conn
             .call(move |conn| -> Result<u64, Error> {
                 let count = conn.execute("DELETE FROM search_cache WHERE expires_at < ?1", params![now])?;
@@ -130,10 +131,28 @@ impl CacheDb {
             .await
             .map_err(Error::from)
     }
+
+    /// Delete search cache entries fetched more than `days` days ago.
+    ///
+    /// Returns the number of deleted entries.
+    pub async fn purge_search_older_than(&self, days: i64) -> Result<u64, Error> {
+        let Some(cutoff) = self.cutoff(days) else {
+            return Ok(0);
+        };
+        self.conn
+            .call(move |conn| -> Result<u64, Error> {
+                let count = conn.execute("DELETE FROM search_cache WHERE fetched_at < ?1", params![cutoff])?;
+                Ok(count as u64)
+            })
+            .await
+            .map_err(Error::from)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::Duration;
+    use crate::ManualClock;
 
     #[tokio::test]
     async fn test_put_and_get_search() {
@@ -157,24 +176,54 @@ mod tests {
 
     #[tokio::test]
     async fn test_search_freshness() {
-        let db = super::super::connection::CacheDb::open_in_memory().await.unwrap();
+        let clock = ManualClock::default();
+        let db = super::super::connection::CacheDb::open_in_memory()
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
         let key = "test_freshness";
         assert!(!db.is_search_fresh(key).await.unwrap());
 
-        db.put_search(key, "{}", "{}", 1).await.unwrap();
+        db.put_search(key, "{}", "{}", 60).await.unwrap();
 
         assert!(db.is_search_fresh(key).await.unwrap());
-        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-        assert!(!db.is_search_fresh(key).await.unwrap());
+        clock.advance(Duration::seconds(59));
+        assert!(db.is_search_fresh(key).await.unwrap());
+        clock.advance(Duration::seconds(1));
+        assert!(
+            !db.is_search_fresh(key).await.unwrap(),
+            "entry expires exactly at its TTL"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_purge_search_older_than() {
+        let clock = ManualClock::default();
+        let db = super::super::connection::CacheDb::open_in_memory()
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+        db.put_search("old", "{}", "{}", 3600).await.unwrap();
+        clock.advance(Duration::days(20));
+        db.put_search("recent", "{}", "{}", 3600).await.unwrap();
+        clock.advance(Duration::days(20));
+
+        assert_eq!(db.purge_search_older_than(30).await.unwrap(), 1);
+        assert!(db.get_search("old").await.unwrap().is_none());
+        assert!(db.get_search("recent").await.unwrap().is_some());
     }
 
     #[tokio::test]
     async fn test_purge_expired_search() {
-        let db = super::super::connection::CacheDb::open_in_memory().await.unwrap();
+        let clock = ManualClock::default();
+        let db = super::super::connection::CacheDb::open_in_memory()
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
         db.put_search("expiring", "{}", "{}", 1).await.unwrap();
         db.put_search("fresh", "{}", "{}", 3600).await.unwrap();
 
-        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+        clock.advance(Duration::seconds(2));
 
         let deleted = db.purge_expired_search().await.unwrap();
         assert_eq!(deleted, 1);
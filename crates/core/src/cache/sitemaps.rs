@@ -0,0 +1,103 @@
+//! Sitemap cache operations.
+//!
+//! Stores parsed sitemaps as JSON keyed by sitemap URL, mirroring the search cache.
+
+use super::connection::CacheDb;
+use crate::Error;
+use chrono::Duration;
+use tokio_rusqlite::params;
+
+impl CacheDb {
+    /// Get a fresh cached sitemap by URL.
+    ///
+    /// Returns None if the URL isn't cached or the entry has expired.
+    pub async fn get_sitemap(&self, url: &str) -> Result<Option<String>, Error> {
+        let url = url.to_string();
+        let now = self.now().to_rfc3339();
+        self.conn
+            .call(move |conn| -> Result<Option<String>, Error> {
+                let mut stmt =
+                    conn.prepare("SELECT sitemap_json FROM sitemap_cache WHERE url = ?1 AND expires_at > ?2")?;
+
+                match stmt.query_row(params![url, now], |row| row.get(0)) {
+                    Ok(json) => Ok(Some(json)),
+                    Err(tokio_rusqlite::rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                    Err(e) => Err(e.into()),
+                }
+            })
+            .await
+            .map_err(Error::from)
+    }
+
+    /// Insert or replace a cached sitemap.
+    pub async fn put_sitemap(&self, url: &str, sitemap_json: &str, ttl_seconds: i64) -> Result<(), Error> {
+        let url = url.to_string();
+        let sitemap_json = sitemap_json.to_string();
+
+        let now = self.now();
+        let fetched_at = now.to_rfc3339();
+        let expires_at = (now + Duration::seconds(ttl_seconds)).to_rfc3339();
+
+        self.conn
+            .call(move |conn| -> Result<(), Error> {
+                conn.execute(
+                    "INSERT INTO sitemap_cache (url, sitemap_json, fetched_at, expires_at)
+                    VALUES (?1, ?2, ?3, ?4)
+                    ON CONFLICT(url) DO UPDATE SET
+                        sitemap_json = excluded.sitemap_json,
+                        fetched_at = excluded.fetched_at,
+                        expires_at = excluded.expires_at",
+                    params![url, sitemap_json, fetched_at, expires_at],
+                )?;
+                Ok(())
+            })
+            .await
+            .map_err(Error::from)
+    }
+
+    /// Delete expired sitemap cache entries.
+    ///
+    /// Returns the number of deleted entries.
+    pub async fn purge_expired_sitemaps(&self) -> Result<u64, Error> {
+        let now = self.now().to_rfc3339();
+        self.conn
+            .call(move |conn| -> Result<u64, Error> {
+                let count = conn.execute("DELETE FROM sitemap_cache WHERE expires_at < ?1", params![now])?;
+                Ok(count as u64)
+            })
+            .await
+            .map_err(Error::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_put_and_get_sitemap() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let url = "https://example.com/sitemap.xml";
+
+        assert!(db.get_sitemap(url).await.unwrap().is_none());
+
+        db.put_sitemap(url, r#"{"kind":"url_set"}"#, 3600).await.unwrap();
+        db.put_sitemap(url, r#"{"kind":"index"}"#, 3600).await.unwrap();
+
+        assert_eq!(
+            db.get_sitemap(url).await.unwrap().as_deref(),
+            Some(r#"{"kind":"index"}"#)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expired_sitemap_is_a_miss() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        db.put_sitemap("https://example.com/old.xml", "{}", -1).await.unwrap();
+        db.put_sitemap("https://example.com/new.xml", "{}", 3600).await.unwrap();
+
+        assert!(db.get_sitemap("https://example.com/old.xml").await.unwrap().is_none());
+        assert_eq!(db.purge_expired_sitemaps().await.unwrap(), 1);
+        assert!(db.get_sitemap("https://example.com/new.xml").await.unwrap().is_some());
+    }
+}
@@ -4,7 +4,7 @@
 //! cached document snapshots.
 
 use super::connection::CacheDb;
-use crate::Error;
+use crate::{Error, Mode};
 use serde::{Deserialize, Serialize};
 use tokio_rusqlite::params;
 use tokio_rusqlite::rusqlite;
@@ -13,12 +13,18 @@ use tokio_rusqlite::rusqlite;
 ///
 /// Represents a fetched and extracted web page, with all metadata
 /// needed for cache invalidation and reproducibility.
+///
+/// The `*_json` columns hold serialized types ([`Link`](super::Link),
+/// [`PageMetadata`](super::PageMetadata), [`Image`](super::Image),
+/// [`Table`](super::Table), [`Heading`](super::Heading),
+/// [`ContentQuality`](super::ContentQuality)). Fields added to those types
+/// later must carry `#[serde(default)]` so older rows still parse.
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct Snapshot {
     pub hash: String,
     pub url: String,
     pub final_url: String,
-    pub mode: String,
+    pub mode: Mode,
     pub content_type: Option<String>,
     pub status_code: Option<i32>,
     pub fetched_at: String,
@@ -33,10 +39,26 @@ pub struct Snapshot {
     pub markdown: Option<String>,
     pub text: Option<String>,
     pub links_json: Option<String>,
+    /// [`PageMetadata`](super::PageMetadata) as JSON; `None` when the page declared none
+    pub metadata_json: Option<String>,
+    /// JSON-LD items as a JSON array; `None` when the page embedded none
+    pub structured_data_json: Option<String>,
+    /// [`Image`](super::Image)s as a JSON array; `None` when the page had none
+    pub images_json: Option<String>,
+    /// [`Table`](super::Table)s too complex for markdown as a JSON array; `None` when there were none
+    pub tables_json: Option<String>,
+    /// [`Heading`](super::Heading)s of the extracted content as a JSON array; `None` when there were none
+    pub outline_json: Option<String>,
+    /// [`ContentQuality`](super::ContentQuality) of the extracted content as a JSON object; `None` when not measured
+    pub quality_json: Option<String>,
+    pub content_hash: Option<String>,
 
     // TODO: ExtractorContext struct
     pub extractor_name: Option<String>,
     pub extractor_version: Option<String>,
+    pub pipeline_version: Option<i64>,
+    /// Encoding the body was decoded with, e.g. `windows-1251`
+    pub detected_charset: Option<String>,
     pub siteconfig_id: Option<String>,
     pub extract_cfg_json: Option<String>,
 
@@ -46,6 +68,62 @@ pub struct Snapshot {
     pub extract_ms: Option<i64>,
 }
 
+#[cfg(any(test, feature = "test-util"))]
+impl Snapshot {
+    /// A readable page at `url`, keyed as a plain readable request and fetched now.
+    ///
+    /// For tests; change what a test cares about with struct update syntax.
+    pub fn test_page(url: &str) -> Self {
+        Self {
+            hash: super::hash::compute_cache_key(url, "", Mode::Readable),
+            url: url.to_string(),
+            final_url: url.to_string(),
+            mode: Mode::Readable,
+            content_type: Some("text/html".to_string()),
+            status_code: Some(200),
+            fetched_at: chrono::Utc::now().to_rfc3339(),
+            expires_at: None,
+            etag: None,
+            last_modified: None,
+            raw_bytes: None,
+            raw_truncated: false,
+            title: Some("Test".to_string()),
+            markdown: Some("# Test".to_string()),
+            text: Some("Test".to_string()),
+            links_json: None,
+            metadata_json: None,
+            structured_data_json: None,
+            images_json: None,
+            tables_json: None,
+            outline_json: None,
+            quality_json: None,
+            content_hash: None,
+            extractor_name: Some("lectito-core".to_string()),
+            extractor_version: Some("0.1.0".to_string()),
+            pipeline_version: None,
+            detected_charset: None,
+            siteconfig_id: None,
+            extract_cfg_json: None,
+            headers_json: None,
+            fetch_ms: Some(100),
+            extract_ms: Some(50),
+        }
+    }
+}
+
+/// Snapshot metadata without content, for listings.
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct SnapshotSummary {
+    pub hash: String,
+    pub url: String,
+    pub final_url: String,
+    pub mode: Mode,
+    pub title: Option<String>,
+    pub fetched_at: String,
+    pub expires_at: Option<String>,
+    pub pinned: bool,
+}
+
 impl CacheDb {
     /// Insert or update a cached snapshot.
     ///
@@ -61,10 +139,13 @@ impl CacheDb {
                     fetched_at, expires_at, etag, last_modified,
                     raw_bytes, raw_truncated, title, markdown, text, links_json,
                     extractor_name, extractor_version, siteconfig_id, extract_cfg_json,
-                    headers_json, fetch_ms, extract_ms
+                    headers_json, fetch_ms, extract_ms, content_hash, pipeline_version,
+                    final_host, host, detected_charset, metadata_json, structured_data_json,
+                    images_json, tables_json, outline_json, quality_json
                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10,
                           ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20,
-                          ?21, ?22, ?23)
+                          ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30,
+                          ?31, ?32, ?33, ?34)
                 ON CONFLICT(hash) DO UPDATE SET
                     url = excluded.url,
                     final_url = excluded.final_url,
@@ -87,12 +168,23 @@ impl CacheDb {
                     extract_cfg_json = excluded.extract_cfg_json,
                     headers_json = excluded.headers_json,
                     fetch_ms = excluded.fetch_ms,
-                    extract_ms = excluded.extract_ms",
+                    extract_ms = excluded.extract_ms,
+                    content_hash = excluded.content_hash,
+                    pipeline_version = excluded.pipeline_version,
+                    final_host = excluded.final_host,
+                    host = excluded.host,
+                    detected_charset = excluded.detected_charset,
+                    metadata_json = excluded.metadata_json,
+                    structured_data_json = excluded.structured_data_json,
+                    images_json = excluded.images_json,
+                    tables_json = excluded.tables_json,
+                    outline_json = excluded.outline_json,
+                    quality_json = excluded.quality_json",
                     params![
                         &snapshot.hash,
                         &snapshot.url,
                         &snapshot.final_url,
-                        &snapshot.mode,
+                        snapshot.mode.as_str(),
                         &snapshot.content_type,
                         &snapshot.status_code,
                         &snapshot.fetched_at,
@@ -112,6 +204,17 @@ impl CacheDb {
                         &snapshot.headers_json,
                         &snapshot.fetch_ms,
                         &snapshot.extract_ms,
+                        &snapshot.content_hash,
+                        &snapshot.pipeline_version,
+                        url_host(&snapshot.final_url),
+                        url_host(&snapshot.url),
+                        &snapshot.detected_charset,
+                        &snapshot.metadata_json,
+                        &snapshot.structured_data_json,
+                        &snapshot.images_json,
+                        &snapshot.tables_json,
+                        &snapshot.outline_json,
+                        &snapshot.quality_json,
                     ],
                 )?;
                 Ok(())
@@ -133,7 +236,9 @@ impl CacheDb {
                     fetched_at, expires_at, etag, last_modified,
                     raw_bytes, raw_truncated, title, markdown, text, links_json,
                     extractor_name, extractor_version, siteconfig_id, extract_cfg_json,
-                    headers_json, fetch_ms, extract_ms
+                    headers_json, fetch_ms, extract_ms, content_hash, pipeline_version,
+                    detected_charset, metadata_json, structured_data_json, images_json,
+                    tables_json, outline_json, quality_json
                 FROM snapshots WHERE hash = ?1",
                 )?;
 
@@ -142,7 +247,7 @@ impl CacheDb {
                         hash: row.get(0)?,
                         url: row.get(1)?,
                         final_url: row.get(2)?,
-                        mode: row.get(3)?,
+                        mode: Mode::from_stored(&row.get::<_, String>(3)?),
                         content_type: row.get(4)?,
                         status_code: row.get(5)?,
                         fetched_at: row.get(6)?,
@@ -162,6 +267,15 @@ impl CacheDb {
                         headers_json: row.get(20)?,
                         fetch_ms: row.get(21)?,
                         extract_ms: row.get(22)?,
+                        content_hash: row.get(23)?,
+                        pipeline_version: row.get(24)?,
+                        detected_charset: row.get(25)?,
+                        metadata_json: row.get(26)?,
+                        structured_data_json: row.get(27)?,
+                        images_json: row.get(28)?,
+                        tables_json: row.get(29)?,
+                        outline_json: row.get(30)?,
+                        quality_json: row.get(31)?,
                     })
                 });
 
@@ -175,12 +289,109 @@ impl CacheDb {
             .map_err(Error::from)
     }
 
+    /// List snapshot metadata, newest first.
+    ///
+    /// When `urls` is non-empty only snapshots whose requested URL matches one of
+    /// them are returned; otherwise the newest `limit` snapshots are listed.
+    pub async fn list_snapshots(&self, urls: &[String], limit: usize) -> Result<Vec<SnapshotSummary>, Error> {
+        let urls = urls.to_vec();
+        let limit = limit as i64;
+        self.conn
+            .call(move |conn| -> Result<Vec<SnapshotSummary>, Error> {
+                let filter = if urls.is_empty() {
+                    String::new()
+                } else {
+                    let placeholders = (2..urls.len() + 2)
+                        .map(|i| format!("?{i}"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    format!("WHERE url IN ({placeholders})")
+                };
+                let sql = format!(
+                    "SELECT hash, url, final_url, mode, title, fetched_at, expires_at, pinned
+                FROM snapshots {filter} ORDER BY fetched_at DESC LIMIT ?1"
+                );
+
+                let mut values: Vec<&dyn rusqlite::ToSql> = vec![&limit];
+                values.extend(urls.iter().map(|u| u as &dyn rusqlite::ToSql));
+
+                let mut stmt = conn.prepare(&sql)?;
+                let rows = stmt.query_map(values.as_slice(), |row| {
+                    Ok(SnapshotSummary {
+                        hash: row.get(0)?,
+                        url: row.get(1)?,
+                        final_url: row.get(2)?,
+                        mode: Mode::from_stored(&row.get::<_, String>(3)?),
+                        title: row.get(4)?,
+                        fetched_at: row.get(5)?,
+                        expires_at: row.get(6)?,
+                        pinned: row.get::<_, i32>(7)? == 1,
+                    })
+                })?;
+
+                Ok(rows.collect::<Result<Vec<_>, _>>()?)
+            })
+            .await
+            .map_err(Error::from)
+    }
+
+    /// Set or clear the expiry of a snapshot.
+    ///
+    /// Returns false if the hash doesn't exist in the cache.
+    pub async fn set_snapshot_expiry(&self, hash: &str, expires_at: Option<String>) -> Result<bool, Error> {
+        let hash = hash.to_string();
+        self.conn
+            .call(move |conn| -> Result<bool, Error> {
+                let updated = conn.execute(
+                    "UPDATE snapshots SET expires_at = ?2 WHERE hash = ?1",
+                    params![hash, expires_at],
+                )?;
+                Ok(updated > 0)
+            })
+            .await
+            .map_err(Error::from)
+    }
+
+    /// Pin or unpin a snapshot by hash.
+    ///
+    /// Returns false if the hash doesn't exist in the cache.
+    pub async fn set_snapshot_pinned(&self, hash: &str, pinned: bool) -> Result<bool, Error> {
+        let hash = hash.to_string();
+        self.conn
+            .call(move |conn| -> Result<bool, Error> {
+                let updated = conn.execute(
+                    "UPDATE snapshots SET pinned = ?2 WHERE hash = ?1",
+                    params![hash, pinned as i32],
+                )?;
+                Ok(updated > 0)
+            })
+            .await
+            .map_err(Error::from)
+    }
+
+    /// Pin or unpin every snapshot (all modes) for a URL.
+    ///
+    /// Returns the number of updated entries.
+    pub async fn set_snapshots_pinned_by_url(&self, url: &str, pinned: bool) -> Result<u64, Error> {
+        let url = url.to_string();
+        self.conn
+            .call(move |conn| -> Result<u64, Error> {
+                let updated = conn.execute(
+                    "UPDATE snapshots SET pinned = ?2 WHERE url = ?1",
+                    params![url, pinned as i32],
+                )?;
+                Ok(updated as u64)
+            })
+            .await
+            .map_err(Error::from)
+    }
+
     /// Check if a snapshot exists and is fresh.
     ///
     /// Returns false if the snapshot doesn't exist or has expired.
     pub async fn is_snapshot_fresh(&self, hash: &str) -> Result<bool, Error> {
         let hash = hash.to_string();
-        let now = chrono::Utc::now().to_rfc3339();
+        let now = self.now().to_rfc3339();
         self.conn
             .call(move |conn| -> Result<bool, Error> {
                 let fresh: bool = conn
@@ -203,14 +414,35 @@ impl CacheDb {
 
     /// Delete expired snapshots.
     ///
+    /// Pinned snapshots are kept unless `include_pinned` is set.
+    /// Returns the number of deleted entries.
+    pub async fn purge_expired_snapshots(&self, include_pinned: bool) -> Result<u64, Error> {
+        let now = self.now().to_rfc3339();
+        self.conn
+            .call(move |conn| -> Result<u64, Error> {
+                let count = conn.execute(
+                    "DELETE FROM snapshots WHERE expires_at IS NOT NULL AND expires_at < ?1 AND (pinned = 0 OR ?2)",
+                    params![now, include_pinned],
+                )?;
+                Ok(count as u64)
+            })
+            .await
+            .map_err(Error::from)
+    }
+
+    /// Delete snapshots fetched more than `days` days ago.
+    ///
+    /// Pinned snapshots are kept unless `include_pinned` is set.
     /// Returns the number of deleted entries.
-    pub async fn purge_expired_snapshots(&self) -> Result<u64, Error> {
-        let now = chrono::Utc::now().to_rfc3339();
+    pub async fn purge_snapshots_older_than(&self, days: i64, include_pinned: bool) -> Result<u64, Error> {
+        let Some(cutoff) = self.cutoff(days) else {
+            return Ok(0);
+        };
         self.conn
             .call(move |conn| -> Result<u64, Error> {
                 let count = conn.execute(
-                    "DELETE FROM snapshots WHERE expires_at IS NOT NULL AND expires_at < ?1",
-                    params![now],
+                    "DELETE FROM snapshots WHERE fetched_at < ?1 AND (pinned = 0 OR ?2)",
+                    params![cutoff, include_pinned],
                 )?;
                 Ok(count as u64)
             })
@@ -218,14 +450,35 @@ impl CacheDb {
             .map_err(Error::from)
     }
 
-    /// Delete snapshots by domain pattern.
+    /// Delete snapshots by domain.
     ///
+    /// Matches the host of the requested URL or of the final URL, so content
+    /// that arrived through a cross-host redirect is reachable from either
+    /// host. Hosts must equal `domain` (case-insensitively), or with
+    /// `include_subdomains` also end in `.domain`; other parts of the URL are
+    /// never matched. Pinned snapshots are kept unless `include_pinned` is set.
     /// Returns the number of deleted entries.
-    pub async fn purge_snapshots_by_domain(&self, domain: &str) -> Result<u64, Error> {
-        let pattern = format!("%{domain}%");
+    pub async fn purge_snapshots_by_domain(
+        &self, domain: &str, include_subdomains: bool, include_pinned: bool,
+    ) -> Result<u64, Error> {
+        let domain = domain.trim().trim_end_matches('.').to_ascii_lowercase();
+        if domain.is_empty() {
+            return Ok(0);
+        }
+        // Escape LIKE wildcards so `_` or `%` in the input match literally.
+        let suffix = format!(
+            "%.{}",
+            domain.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
         self.conn
             .call(move |conn| -> Result<u64, Error> {
-                let count = conn.execute("DELETE FROM snapshots WHERE url LIKE ?1", params![pattern])?;
+                let count = conn.execute(
+                    "DELETE FROM snapshots
+                     WHERE (host = ?1 OR final_host = ?1
+                            OR (?2 AND (host LIKE ?3 ESCAPE '\\' OR final_host LIKE ?3 ESCAPE '\\')))
+                     AND (pinned = 0 OR ?4)",
+                    params![domain, include_subdomains, suffix, include_pinned],
+                )?;
                 Ok(count as u64)
             })
             .await
@@ -234,8 +487,10 @@ impl CacheDb {
 
     /// Purge oldest entries until count <= max_entries.
     ///
+    /// Pinned snapshots count towards `max_entries` but are never evicted unless
+    /// `include_pinned` is set, so fewer than `max_entries` may remain deletable.
     /// Returns the number of deleted entries.
-    pub async fn purge_lru_snapshots(&self, max_entries: usize) -> Result<u64, Error> {
+    pub async fn purge_lru_snapshots(&self, max_entries: usize, include_pinned: bool) -> Result<u64, Error> {
         let max = max_entries as i64;
         self.conn
             .call(move |conn| -> Result<u64, Error> {
@@ -247,9 +502,9 @@ impl CacheDb {
                 let to_delete = count - max;
                 let deleted = conn.execute(
                     "DELETE FROM snapshots WHERE hash IN (
-                    SELECT hash FROM snapshots ORDER BY fetched_at ASC LIMIT ?1
+                    SELECT hash FROM snapshots WHERE (pinned = 0 OR ?2) ORDER BY fetched_at ASC LIMIT ?1
                 )",
-                    params![to_delete],
+                    params![to_delete, include_pinned],
                 )?;
                 Ok(deleted as u64)
             })
@@ -258,50 +513,63 @@ impl CacheDb {
     }
 }
 
+/// Lowercased host of an absolute URL, without userinfo or port.
+pub fn url_host(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = match host_port.strip_prefix('[') {
+        Some(v6) => v6.split_once(']').map_or(v6, |(addr, _)| addr),
+        None => host_port.split(':').next().unwrap_or_default(),
+    };
+    (!host.is_empty()).then(|| host.to_ascii_lowercase())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::cache::hash::compute_cache_key;
-
-    fn make_test_snapshot(url: &str) -> Snapshot {
-        let hash = compute_cache_key(url, "", "readable");
-        Snapshot {
-            hash,
-            url: url.to_string(),
-            final_url: url.to_string(),
-            mode: "readable".to_string(),
-            content_type: Some("text/html".to_string()),
-            status_code: Some(200),
-            fetched_at: chrono::Utc::now().to_rfc3339(),
-            expires_at: None,
-            etag: None,
-            last_modified: None,
-            raw_bytes: None,
-            raw_truncated: false,
-            title: Some("Test".to_string()),
-            markdown: Some("# Test".to_string()),
-            text: Some("Test".to_string()),
-            links_json: None,
-            extractor_name: Some("lectito-core".to_string()),
-            extractor_version: Some("0.1.0".to_string()),
-            siteconfig_id: None,
-            extract_cfg_json: None,
-            headers_json: None,
-            fetch_ms: Some(100),
-            extract_ms: Some(50),
-        }
-    }
+    use crate::{Clock, ManualClock};
 
     #[tokio::test]
     async fn test_upsert_and_get() {
         let db = super::super::connection::CacheDb::open_in_memory().await.unwrap();
-        let snapshot = make_test_snapshot("https://example.com");
+        let snapshot = Snapshot {
+            metadata_json: Some(r#"{"author":"Ada"}"#.into()),
+            ..Snapshot::test_page("https://example.com")
+        };
 
         db.upsert_snapshot(&snapshot).await.unwrap();
 
         let retrieved = db.get_snapshot(&snapshot.hash).await.unwrap().unwrap();
         assert_eq!(retrieved.url, snapshot.url);
         assert_eq!(retrieved.title, snapshot.title);
+        assert_eq!(retrieved.metadata().unwrap().author.as_deref(), Some("Ada"));
+    }
+
+    #[tokio::test]
+    async fn test_mode_round_trips_and_legacy_rows_read_as_readable() {
+        let db = super::super::connection::CacheDb::open_in_memory().await.unwrap();
+        let raw = Snapshot { mode: Mode::Raw, ..Snapshot::test_page("https://example.com/raw") };
+        db.upsert_snapshot(&raw).await.unwrap();
+        assert_eq!(db.get_snapshot(&raw.hash).await.unwrap().unwrap().mode, Mode::Raw);
+
+        let legacy = Snapshot::test_page("https://example.com/legacy");
+        db.upsert_snapshot(&legacy).await.unwrap();
+        let hash = legacy.hash.clone();
+        db.conn
+            .call(move |conn| {
+                conn.execute("UPDATE snapshots SET mode = 'markdown' WHERE hash = ?1", params![hash])?;
+                Ok::<_, Error>(())
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            db.get_snapshot(&legacy.hash).await.unwrap().unwrap().mode,
+            Mode::Readable
+        );
+        let listed = db.list_snapshots(&[legacy.url.clone()], 10).await.unwrap();
+        assert_eq!(listed[0].mode, Mode::Readable);
     }
 
     #[tokio::test]
@@ -314,26 +582,236 @@ mod tests {
     #[tokio::test]
     async fn test_purge_by_domain() {
         let db = super::super::connection::CacheDb::open_in_memory().await.unwrap();
-        db.upsert_snapshot(&make_test_snapshot("https://example.com/page1"))
+        db.upsert_snapshot(&Snapshot::test_page("https://example.com/page1"))
             .await
             .unwrap();
-        db.upsert_snapshot(&make_test_snapshot("https://other.com/page2"))
+        db.upsert_snapshot(&Snapshot::test_page("https://other.com/page2"))
             .await
             .unwrap();
 
-        let deleted = db.purge_snapshots_by_domain("example.com").await.unwrap();
+        let deleted = db.purge_snapshots_by_domain("example.com", true, false).await.unwrap();
         assert_eq!(deleted, 1);
 
         let remaining = db
-            .get_snapshot(&compute_cache_key("https://example.com/page1", "", "readable"))
+            .get_snapshot(&compute_cache_key("https://example.com/page1", "", Mode::Readable))
             .await
             .unwrap();
         assert!(remaining.is_none());
 
         let other = db
-            .get_snapshot(&compute_cache_key("https://other.com/page2", "", "readable"))
+            .get_snapshot(&compute_cache_key("https://other.com/page2", "", Mode::Readable))
             .await
             .unwrap();
         assert!(other.is_some());
     }
+
+    #[tokio::test]
+    async fn test_purge_by_domain_matches_final_host() {
+        let db = super::super::connection::CacheDb::open_in_memory().await.unwrap();
+        let redirected = Snapshot {
+            final_url: "https://cdn.other.net/page".to_string(),
+            ..Snapshot::test_page("https://example.com/moved")
+        };
+        db.upsert_snapshot(&redirected).await.unwrap();
+        db.upsert_snapshot(&Snapshot::test_page("https://example.com/stay"))
+            .await
+            .unwrap();
+
+        assert_eq!(db.purge_snapshots_by_domain("other.net", true, false).await.unwrap(), 1);
+        assert!(db.get_snapshot(&redirected.hash).await.unwrap().is_none());
+        assert_eq!(
+            db.purge_snapshots_by_domain("example.com", true, false).await.unwrap(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_purge_by_domain_matches_hosts_not_substrings() {
+        let db = super::super::connection::CacheDb::open_in_memory().await.unwrap();
+        let urls = [
+            "https://notexample.com/a",
+            "https://example.com.evil.net/b",
+            "https://other.com/example.com/path",
+            "https://other.com/?next=https://example.com/",
+            "https://docs.example.com/c",
+            "https://EXAMPLE.com/d",
+        ];
+        for url in urls {
+            db.upsert_snapshot(&Snapshot::test_page(url)).await.unwrap();
+        }
+        let kept = |url: &str| {
+            let hash = compute_cache_key(url, "", Mode::Readable);
+            let db = db.clone();
+            async move { db.get_snapshot(&hash).await.unwrap().is_some() }
+        };
+
+        assert_eq!(
+            db.purge_snapshots_by_domain("Example.com.", false, false)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(!kept("https://EXAMPLE.com/d").await);
+        assert!(kept("https://docs.example.com/c").await);
+
+        assert_eq!(
+            db.purge_snapshots_by_domain("example.com", true, false).await.unwrap(),
+            1
+        );
+        assert!(!kept("https://docs.example.com/c").await);
+        for url in &urls[..4] {
+            assert!(kept(url).await, "{url}");
+        }
+
+        assert_eq!(db.purge_snapshots_by_domain("", true, false).await.unwrap(), 0);
+        assert_eq!(db.purge_snapshots_by_domain("%", true, false).await.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(url_host("https://Example.com/a?b#c").as_deref(), Some("example.com"));
+        assert_eq!(
+            url_host("http://user:pw@example.com:8080/").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(url_host("http://[::1]:3000/x").as_deref(), Some("::1"));
+        assert_eq!(url_host("https://example.com").as_deref(), Some("example.com"));
+        assert_eq!(url_host("not a url"), None);
+    }
+
+    #[tokio::test]
+    async fn test_list_snapshots() {
+        let db = super::super::connection::CacheDb::open_in_memory().await.unwrap();
+        db.upsert_snapshot(&Snapshot::test_page("https://example.com/a"))
+            .await
+            .unwrap();
+        db.upsert_snapshot(&Snapshot::test_page("https://example.com/b"))
+            .await
+            .unwrap();
+
+        let all = db.list_snapshots(&[], 10).await.unwrap();
+        assert_eq!(all.len(), 2);
+
+        let limited = db.list_snapshots(&[], 1).await.unwrap();
+        assert_eq!(limited.len(), 1);
+
+        let filtered = db
+            .list_snapshots(
+                &["https://example.com/b".to_string(), "https://missing.com/".to_string()],
+                10,
+            )
+            .await
+            .unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].url, "https://example.com/b");
+    }
+
+    #[tokio::test]
+    async fn test_set_snapshot_expiry() {
+        let db = super::super::connection::CacheDb::open_in_memory().await.unwrap();
+        let snapshot = Snapshot::test_page("https://example.com");
+        db.upsert_snapshot(&snapshot).await.unwrap();
+
+        let expires_at = "2000-01-01T00:00:00+00:00".to_string();
+        assert!(
+            db.set_snapshot_expiry(&snapshot.hash, Some(expires_at.clone()))
+                .await
+                .unwrap()
+        );
+        assert!(!db.set_snapshot_expiry("nonexistent", None).await.unwrap());
+
+        let retrieved = db.get_snapshot(&snapshot.hash).await.unwrap().unwrap();
+        assert_eq!(retrieved.expires_at, Some(expires_at));
+        assert!(!db.is_snapshot_fresh(&snapshot.hash).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_expiry_follows_clock() {
+        let clock = ManualClock::default();
+        let db = super::super::connection::CacheDb::open_in_memory()
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+        let snapshot = Snapshot::test_page("https://example.com");
+        db.upsert_snapshot(&snapshot).await.unwrap();
+
+        let expires_at = (clock.now() + chrono::Duration::hours(1)).to_rfc3339();
+        db.set_snapshot_expiry(&snapshot.hash, Some(expires_at)).await.unwrap();
+        assert!(db.is_snapshot_fresh(&snapshot.hash).await.unwrap());
+        assert_eq!(db.purge_expired_snapshots(false).await.unwrap(), 0);
+
+        clock.advance(chrono::Duration::hours(2));
+        assert!(!db.is_snapshot_fresh(&snapshot.hash).await.unwrap());
+        assert_eq!(db.cache_stats().await.unwrap().expired, 1);
+        assert_eq!(db.purge_expired_snapshots(false).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_purge_snapshots_older_than() {
+        let clock = ManualClock::default();
+        let db = super::super::connection::CacheDb::open_in_memory()
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+        let at = |days_ago: i64| (clock.now() - chrono::Duration::days(days_ago)).to_rfc3339();
+        let old = Snapshot { fetched_at: at(40), ..Snapshot::test_page("https://example.com/old") };
+        let pinned = Snapshot { fetched_at: at(40), ..Snapshot::test_page("https://example.com/pinned") };
+        let recent = Snapshot { fetched_at: at(10), ..Snapshot::test_page("https://example.com/recent") };
+        for snapshot in [&old, &pinned, &recent] {
+            db.upsert_snapshot(snapshot).await.unwrap();
+        }
+        db.set_snapshot_pinned(&pinned.hash, true).await.unwrap();
+
+        assert_eq!(db.purge_snapshots_older_than(30, false).await.unwrap(), 1);
+        assert!(db.get_snapshot(&old.hash).await.unwrap().is_none());
+        assert!(db.get_snapshot(&recent.hash).await.unwrap().is_some());
+        assert_eq!(db.purge_snapshots_older_than(30, true).await.unwrap(), 1);
+        assert_eq!(db.purge_snapshots_older_than(i64::MAX, true).await.unwrap(), 0);
+        assert_eq!(db.purge_snapshots_older_than(0, false).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pinned_snapshot_survives_purges() {
+        let db = super::super::connection::CacheDb::open_in_memory().await.unwrap();
+        let pinned = Snapshot::test_page("https://example.com/keep");
+        db.upsert_snapshot(&pinned).await.unwrap();
+        db.upsert_snapshot(&Snapshot::test_page("https://example.com/drop"))
+            .await
+            .unwrap();
+
+        assert!(db.set_snapshot_pinned(&pinned.hash, true).await.unwrap());
+        assert!(!db.set_snapshot_pinned("nonexistent", true).await.unwrap());
+
+        assert_eq!(db.purge_lru_snapshots(0, false).await.unwrap(), 1);
+        assert_eq!(
+            db.purge_snapshots_by_domain("example.com", true, false).await.unwrap(),
+            0
+        );
+        assert!(db.get_snapshot(&pinned.hash).await.unwrap().is_some());
+
+        let listed = db.list_snapshots(&[], 10).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].pinned);
+
+        assert_eq!(db.purge_lru_snapshots(0, true).await.unwrap(), 1);
+        assert!(db.get_snapshot(&pinned.hash).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_pin_by_url_survives_upsert() {
+        let db = super::super::connection::CacheDb::open_in_memory().await.unwrap();
+        let snapshot = Snapshot::test_page("https://example.com/page");
+        db.upsert_snapshot(&snapshot).await.unwrap();
+
+        assert_eq!(
+            db.set_snapshots_pinned_by_url("https://example.com/page", true)
+                .await
+                .unwrap(),
+            1
+        );
+        db.upsert_snapshot(&snapshot).await.unwrap();
+
+        let listed = db.list_snapshots(&[], 10).await.unwrap();
+        assert!(listed[0].pinned);
+    }
 }
@@ -0,0 +1,96 @@
+//! Aggregate cache statistics.
+
+use super::connection::CacheDb;
+use crate::Error;
+use serde::{Deserialize, Serialize};
+use tokio_rusqlite::params;
+
+/// Summary counts across the snapshot and search caches.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct CacheStats {
+    /// Total number of snapshots.
+    pub snapshots: u64,
+    /// Snapshots protected from purges.
+    pub pinned: u64,
+    /// Snapshots past their expiry.
+    pub expired: u64,
+    /// Cached search responses.
+    pub search_entries: u64,
+    /// Total size of stored raw bodies, in bytes.
+    #[serde(default)]
+    pub raw_bytes: u64,
+}
+
+impl CacheDb {
+    /// Compute cache statistics.
+    pub async fn cache_stats(&self) -> Result<CacheStats, Error> {
+        let now = self.now().to_rfc3339();
+        self.conn
+            .call(move |conn| -> Result<CacheStats, Error> {
+                let (snapshots, pinned, expired, raw_bytes): (i64, i64, i64, i64) = conn.query_row(
+                    "SELECT
+                    COUNT(*),
+                    COALESCE(SUM(pinned), 0),
+                    COALESCE(SUM(expires_at IS NOT NULL AND expires_at < ?1), 0),
+                    COALESCE(SUM(LENGTH(raw_bytes)), 0)
+                FROM snapshots",
+                    params![now],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+                )?;
+                let search_entries: i64 = conn.query_row("SELECT COUNT(*) FROM search_cache", [], |row| row.get(0))?;
+
+                Ok(CacheStats {
+                    snapshots: snapshots as u64,
+                    pinned: pinned as u64,
+                    expired: expired as u64,
+                    search_entries: search_entries as u64,
+                    raw_bytes: raw_bytes as u64,
+                })
+            })
+            .await
+            .map_err(Error::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::CacheDb;
+    use crate::cache::snapshots::Snapshot;
+
+    #[tokio::test]
+    async fn test_cache_stats() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let empty = db.cache_stats().await.unwrap();
+        assert_eq!(empty.snapshots, 0);
+        assert_eq!(empty.pinned, 0);
+
+        let pinned = Snapshot::test_page("https://example.com/a");
+        db.upsert_snapshot(&pinned).await.unwrap();
+        let expired = Snapshot {
+            expires_at: Some("2000-01-01T00:00:00+00:00".to_string()),
+            ..Snapshot::test_page("https://example.com/b")
+        };
+        db.upsert_snapshot(&expired).await.unwrap();
+        db.set_snapshot_pinned(&pinned.hash, true).await.unwrap();
+        db.put_search("key", "{}", "{}", 60).await.unwrap();
+
+        let stats = db.cache_stats().await.unwrap();
+        assert_eq!(stats.snapshots, 2);
+        assert_eq!(stats.pinned, 1);
+        assert_eq!(stats.expired, 1);
+        assert_eq!(stats.search_entries, 1);
+        assert_eq!(stats.raw_bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn test_cache_stats_raw_bytes() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let with_raw = Snapshot { raw_bytes: Some(vec![0u8; 1234]), ..Snapshot::test_page("https://example.com/a") };
+        db.upsert_snapshot(&with_raw).await.unwrap();
+        db.upsert_snapshot(&Snapshot::test_page("https://example.com/b"))
+            .await
+            .unwrap();
+
+        assert_eq!(db.cache_stats().await.unwrap().raw_bytes, 1234);
+    }
+}
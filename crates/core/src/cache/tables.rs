@@ -0,0 +1,61 @@
+//! Tables kept as structured data.
+//!
+//! Simple tables go into the markdown as GitHub-flavored tables; ones with row
+//! or column spans don't fit that grid and are stored here instead, with a
+//! marker in the markdown where they stood. `tables_json` holds a JSON array of
+//! [`Table`], or NULL when there were none.
+
+use serde::{Deserialize, Serialize};
+
+use super::snapshots::Snapshot;
+
+/// A table as header cells and body rows of cell text.
+///
+/// Spanned cells are repeated across every row and column they cover, so each
+/// row has one entry per column.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct Table {
+    /// The `<caption>`, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    /// Header cells; empty when the table has no header row.
+    #[serde(default)]
+    pub headers: Vec<String>,
+    /// Body rows.
+    #[serde(default)]
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Serialize tables for the `tables_json` column; `None` when there are none.
+pub fn tables_json(tables: &[Table]) -> Option<String> {
+    (!tables.is_empty()).then(|| serde_json::to_string(tables).expect("tables serialize to JSON"))
+}
+
+impl Snapshot {
+    /// Structured tables from the snapshot's page; empty when none were stored.
+    pub fn tables(&self) -> Result<Vec<Table>, serde_json::Error> {
+        match &self.tables_json {
+            Some(json) => serde_json::from_str(json),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tables_json_round_trip() {
+        assert!(tables_json(&[]).is_none());
+
+        let tables = vec![Table {
+            caption: None,
+            headers: vec!["Plan".into(), "Price".into()],
+            rows: vec![vec!["Free".into(), "$0".into()]],
+        }];
+        let json = tables_json(&tables).unwrap();
+        assert_eq!(json, r#"[{"headers":["Plan","Price"],"rows":[["Free","$0"]]}]"#);
+        assert_eq!(serde_json::from_str::<Vec<Table>>(&json).unwrap(), tables);
+    }
+}
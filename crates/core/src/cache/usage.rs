@@ -0,0 +1,188 @@
+//! Brave API usage counters and spend estimates.
+//!
+//! Each web_search either calls the Brave API (billed) or is answered from the
+//! search cache (free). Both are counted per UTC calendar month so operators on
+//! metered plans can see what a session cost and what the cache saved.
+
+use super::connection::CacheDb;
+use crate::Error;
+use serde::{Deserialize, Serialize};
+use tokio_rusqlite::params;
+
+/// Raw Brave usage counts for one month.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct BraveUsage {
+    /// Month the counts cover, as `YYYY-MM` (UTC).
+    pub month: String,
+    /// Brave API requests made.
+    pub requests: u64,
+    /// Searches answered from the cache instead of the API.
+    pub cache_hits: u64,
+}
+
+/// Pricing used to turn usage counts into a spend estimate.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BravePricing {
+    /// Price per 1000 API requests, in the plan's currency.
+    pub cost_per_thousand_requests: Option<f64>,
+    /// Requests included in the plan per month.
+    pub monthly_quota: Option<u64>,
+}
+
+/// Usage counts with spend estimates, as shown in debug output and cache_stats.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct UsageReport {
+    /// Month the counts cover, as `YYYY-MM` (UTC).
+    pub month: String,
+    /// Brave API requests made this month.
+    pub requests: u64,
+    /// Searches answered from the cache this month (not billed).
+    pub cache_hits: u64,
+    /// Estimated spend for `requests` (only when a price is configured).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_cost: Option<f64>,
+    /// Estimated spend avoided by `cache_hits` (only when a price is configured).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_savings: Option<f64>,
+    /// Fraction of the monthly quota used, e.g. 0.25 (only when a quota is configured).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota_used: Option<f64>,
+}
+
+impl BraveUsage {
+    /// Apply `pricing` to these counts.
+    pub fn report(&self, pricing: &BravePricing) -> UsageReport {
+        let cost = |count: u64| {
+            pricing
+                .cost_per_thousand_requests
+                .map(|per_thousand| count as f64 * per_thousand / 1000.0)
+        };
+
+        UsageReport {
+            month: self.month.clone(),
+            requests: self.requests,
+            cache_hits: self.cache_hits,
+            estimated_cost: cost(self.requests),
+            estimated_savings: cost(self.cache_hits),
+            quota_used: pricing
+                .monthly_quota
+                .filter(|quota| *quota > 0)
+                .map(|quota| self.requests as f64 / quota as f64),
+        }
+    }
+}
+
+impl CacheDb {
+    /// Count one Brave API request against the current month.
+    pub async fn record_brave_request(&self) -> Result<(), Error> {
+        self.bump_brave_usage(1, 0).await
+    }
+
+    /// Count one search answered from the cache against the current month.
+    pub async fn record_brave_cache_hit(&self) -> Result<(), Error> {
+        self.bump_brave_usage(0, 1).await
+    }
+
+    /// Usage counts for the current month (zero if nothing was recorded yet).
+    pub async fn brave_usage(&self) -> Result<BraveUsage, Error> {
+        let month = self.usage_month();
+        self.conn
+            .call(move |conn| -> Result<BraveUsage, Error> {
+                let counts = conn.query_row(
+                    "SELECT requests, cache_hits FROM brave_usage WHERE month = ?1",
+                    params![month],
+                    |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+                );
+                let (requests, cache_hits) = match counts {
+                    Ok(counts) => counts,
+                    Err(tokio_rusqlite::rusqlite::Error::QueryReturnedNoRows) => (0, 0),
+                    Err(e) => return Err(e.into()),
+                };
+
+                Ok(BraveUsage { month, requests: requests as u64, cache_hits: cache_hits as u64 })
+            })
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn bump_brave_usage(&self, requests: i64, cache_hits: i64) -> Result<(), Error> {
+        let month = self.usage_month();
+        self.conn
+            .call(move |conn| -> Result<(), Error> {
+                conn.execute(
+                    "INSERT INTO brave_usage (month, requests, cache_hits)
+                    VALUES (?1, ?2, ?3)
+                    ON CONFLICT(month) DO UPDATE SET
+                        requests = requests + excluded.requests,
+                        cache_hits = cache_hits + excluded.cache_hits",
+                    params![month, requests, cache_hits],
+                )?;
+                Ok(())
+            })
+            .await
+            .map_err(Error::from)
+    }
+
+    fn usage_month(&self) -> String {
+        self.now().format("%Y-%m").to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ManualClock;
+
+    #[test]
+    fn test_report_without_pricing() {
+        let usage = BraveUsage { month: "2025-01".into(), requests: 40, cache_hits: 10 };
+        let report = usage.report(&BravePricing::default());
+
+        assert_eq!(report.requests, 40);
+        assert_eq!(report.cache_hits, 10);
+        assert_eq!(report.estimated_cost, None);
+        assert_eq!(report.estimated_savings, None);
+        assert_eq!(report.quota_used, None);
+        assert!(!serde_json::to_string(&report).unwrap().contains("estimated_cost"));
+    }
+
+    #[test]
+    fn test_report_with_pricing() {
+        let usage = BraveUsage { month: "2025-01".into(), requests: 500, cache_hits: 250 };
+        let pricing = BravePricing { cost_per_thousand_requests: Some(5.0), monthly_quota: Some(2000) };
+        let report = usage.report(&pricing);
+
+        assert_eq!(report.estimated_cost, Some(2.5));
+        assert_eq!(report.estimated_savings, Some(1.25));
+        assert_eq!(report.quota_used, Some(0.25));
+
+        let zero_quota = BravePricing { monthly_quota: Some(0), ..pricing };
+        assert_eq!(usage.report(&zero_quota).quota_used, None);
+    }
+
+    #[tokio::test]
+    async fn test_usage_counts_per_month() {
+        let clock = ManualClock::default();
+        let db = CacheDb::open_in_memory().await.unwrap().with_clock(clock.clone());
+
+        assert_eq!(
+            db.brave_usage().await.unwrap(),
+            BraveUsage { month: "2025-01".into(), requests: 0, cache_hits: 0 }
+        );
+
+        db.record_brave_request().await.unwrap();
+        db.record_brave_request().await.unwrap();
+        db.record_brave_cache_hit().await.unwrap();
+        assert_eq!(
+            db.brave_usage().await.unwrap(),
+            BraveUsage { month: "2025-01".into(), requests: 2, cache_hits: 1 }
+        );
+
+        clock.advance(chrono::Duration::days(31));
+        db.record_brave_cache_hit().await.unwrap();
+        assert_eq!(
+            db.brave_usage().await.unwrap(),
+            BraveUsage { month: "2025-02".into(), requests: 0, cache_hits: 1 }
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! Time source for cache timestamps and expiry checks.
+//!
+//! [`CacheDb`](crate::CacheDb) reads the current time through a [`Clock`] so
+//! tests can install a [`ManualClock`] and advance it instead of sleeping.
+//! Production code uses [`SystemClock`].
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use std::sync::{Arc, Mutex};
+
+/// Source of the current time.
+pub trait Clock: Send + Sync + std::fmt::Debug {
+    /// Current time in UTC.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Wall-clock time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock that only moves when told to.
+///
+/// Clones share the same time, so a test can keep one handle and hand another
+/// to the cache.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl ManualClock {
+    /// Create a clock frozen at `start`.
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self { now: Arc::new(Mutex::new(start)) }
+    }
+
+    /// Set the current time.
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) = now;
+    }
+
+    /// Move the clock forward (or backward, for a negative duration).
+    pub fn advance(&self, by: Duration) {
+        let mut now = self.now.lock().unwrap_or_else(|e| e.into_inner());
+        *now += by;
+    }
+}
+
+impl Default for ManualClock {
+    /// Frozen at 2025-01-01T00:00:00Z.
+    fn default() -> Self {
+        Self::new(Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap())
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock_shared_between_clones() {
+        let clock = ManualClock::default();
+        let handle = clock.clone();
+        let start = clock.now();
+
+        handle.advance(Duration::seconds(90));
+        assert_eq!(clock.now() - start, Duration::seconds(90));
+
+        handle.set(start);
+        assert_eq!(clock.now(), start);
+    }
+}
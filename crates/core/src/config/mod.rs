@@ -16,9 +16,11 @@ use figment::{
 };
 use serde::{Deserialize, Serialize};
 
+use crate::cache::BravePricing;
+
 mod validation;
 
-pub use validation::ConfigError;
+pub use validation::{ConfigError, is_valid_language_list};
 
 /// Application configuration with layered loading.
 ///
@@ -65,6 +67,14 @@ pub struct AppConfig {
     #[serde(default = "default_true")]
     pub respect_robots: bool,
 
+    /// Whether hosts resolving to private or reserved addresses (loopback,
+    /// RFC 1918, link-local, ...) may be fetched. Off by default; turn it on
+    /// for intranet deployments.
+    ///
+    /// Set via MCP_WEB_ALLOW_PRIVATE_ADDRESSES environment variable.
+    #[serde(default)]
+    pub allow_private_addresses: bool,
+
     /// Whether rendered mode (headless browser) is enabled.
     ///
     /// Set via MCP_WEB_RENDER_ENABLED environment variable.
@@ -82,6 +92,326 @@ pub struct AppConfig {
     /// Set via MCP_WEB_DENYLIST_DOMAINS environment variable (comma-separated).
     #[serde(default)]
     pub denylist_domains: Vec<String>,
+
+    /// Accept-Language header sent with fetches (e.g. `en-US,en;q=0.8`).
+    ///
+    /// Set via MCP_WEB_ACCEPT_LANGUAGE environment variable. Unset sends no header;
+    /// `web_open` can override it per request with `language`.
+    #[serde(default)]
+    pub accept_language: Option<String>,
+
+    /// Consecutive fetch failures against a host before its circuit opens.
+    ///
+    /// Set via MCP_WEB_CIRCUIT_FAILURE_THRESHOLD environment variable.
+    #[serde(default = "default_circuit_failure_threshold")]
+    pub circuit_failure_threshold: u32,
+
+    /// Window in seconds within which failures count as consecutive.
+    ///
+    /// Set via MCP_WEB_CIRCUIT_WINDOW_SECS environment variable.
+    #[serde(default = "default_circuit_window_secs")]
+    pub circuit_window_secs: u64,
+
+    /// Seconds an open circuit fails fast before a probe is allowed.
+    ///
+    /// Set via MCP_WEB_CIRCUIT_COOLDOWN_SECS environment variable.
+    #[serde(default = "default_circuit_cooldown_secs")]
+    pub circuit_cooldown_secs: u64,
+
+    /// Maximum number of URLs accepted by a single batch or cache warm call.
+    ///
+    /// Set via MCP_WEB_BATCH_MAX_URLS environment variable.
+    #[serde(default = "default_batch_max_urls")]
+    pub batch_max_urls: usize,
+
+    /// Directory that newly cached documents are exported to as markdown files.
+    ///
+    /// Set via MCP_WEB_EXPORT_DIR environment variable. Unset disables file export.
+    #[serde(default)]
+    pub export_dir: Option<PathBuf>,
+
+    /// Directory of per-site extraction configs (`*.toml` or `*.json`), loaded at startup.
+    ///
+    /// Set via MCP_WEB_SITECONFIG_DIR environment variable. Unset applies none.
+    #[serde(default)]
+    pub siteconfig_dir: Option<PathBuf>,
+
+    /// Webhook that receives the web_open output of newly cached documents as JSON.
+    ///
+    /// Set via MCP_WEB_EXPORT_WEBHOOK_URL environment variable. The host must pass
+    /// SSRF validation unless listed in `export_webhook_allow_hosts`.
+    #[serde(default)]
+    pub export_webhook_url: Option<String>,
+
+    /// Webhook hosts exempt from SSRF validation (e.g. `localhost`).
+    ///
+    /// Set via MCP_WEB_EXPORT_WEBHOOK_ALLOW_HOSTS environment variable (comma-separated).
+    #[serde(default)]
+    pub export_webhook_allow_hosts: Vec<String>,
+
+    /// Redo cached extractions made by a different extractor or pipeline version.
+    ///
+    /// Set via MCP_WEB_INVALIDATE_ON_VERSION_MISMATCH environment variable. Stale
+    /// snapshots are re-extracted from stored raw bytes when present, else refetched.
+    #[serde(default = "default_true")]
+    pub invalidate_on_version_mismatch: bool,
+
+    /// How long a fetched snapshot stays fresh, in seconds; 0 never expires.
+    ///
+    /// Set via MCP_WEB_SNAPSHOT_TTL_SECONDS environment variable. A shorter
+    /// `Cache-Control: max-age` on the response wins.
+    #[serde(default = "default_snapshot_ttl_seconds")]
+    pub snapshot_ttl_seconds: u64,
+
+    /// Brave price per 1000 API requests, used for spend estimates.
+    ///
+    /// Set via MCP_WEB_BRAVE_COST_PER_THOUSAND_REQUESTS environment variable.
+    /// Unset reports request counts without a cost.
+    #[serde(default)]
+    pub brave_cost_per_thousand_requests: Option<f64>,
+
+    /// Brave API requests included in the plan per month, for the quota fraction.
+    ///
+    /// Set via MCP_WEB_BRAVE_MONTHLY_QUOTA environment variable.
+    #[serde(default)]
+    pub brave_monthly_quota: Option<u64>,
+
+    /// Minimum milliseconds between Brave API requests, shared by every web_search call.
+    ///
+    /// Set via MCP_WEB_BRAVE_MIN_INTERVAL_MS environment variable. The default
+    /// suits the free tier; once Brave reports a higher per-second limit the
+    /// client paces to that instead.
+    #[serde(default = "default_brave_min_interval_ms")]
+    pub brave_min_interval_ms: u64,
+
+    /// Backend web_search queries: "brave" (default) or "searxng".
+    ///
+    /// Set via MCP_WEB_SEARCH_PROVIDER environment variable. The news, image,
+    /// and suggest tools always use Brave.
+    #[serde(default = "default_search_provider")]
+    pub search_provider: String,
+
+    /// Base URL of the SearXNG instance web_search uses when `search_provider`
+    /// is "searxng", e.g. `http://localhost:8888`. The instance must allow the
+    /// JSON output format.
+    ///
+    /// Set via MCP_WEB_SEARXNG_BASE_URL environment variable.
+    #[serde(default)]
+    pub searxng_base_url: Option<String>,
+
+    /// Most extra snippets returned per web_search result when they are requested.
+    ///
+    /// Set via MCP_WEB_EXTRA_SNIPPETS_MAX environment variable.
+    #[serde(default = "default_extra_snippets_max")]
+    pub extra_snippets_max: usize,
+
+    /// Longest extra snippet, in characters, before it is truncated with a marker.
+    ///
+    /// Set via MCP_WEB_EXTRA_SNIPPET_MAX_CHARS environment variable.
+    #[serde(default = "default_extra_snippet_max_chars")]
+    pub extra_snippet_max_chars: usize,
+
+    /// Largest tool output, in bytes of compact JSON, that is pretty-printed.
+    ///
+    /// Set via MCP_WEB_PRETTY_JSON_MAX_BYTES environment variable. Larger outputs
+    /// are returned compact; 0 always returns compact JSON.
+    #[serde(default = "default_pretty_json_max_bytes")]
+    pub pretty_json_max_bytes: usize,
+
+    /// Public URL the self-check sends a HEAD request to.
+    ///
+    /// Set via MCP_WEB_HEALTH_PROBE_URL environment variable. Unset skips the
+    /// network check, so the self-check makes no outbound fetch.
+    #[serde(default)]
+    pub health_probe_url: Option<String>,
+
+    /// Overall deadline for each tool call, covering fetch, extraction, and cache work.
+    ///
+    /// Set per tool via MCP_WEB_TOOL_TIMEOUT_MS__WEB_OPEN (and __WEB_BATCH_OPEN,
+    /// __WEB_SEARCH, __WEB_EXTRACT) environment variables; 0 disables the deadline.
+    #[serde(default)]
+    pub tool_timeout_ms: ToolTimeouts,
+
+    /// Refuse bot-protection challenge pages instead of extracting and caching them.
+    ///
+    /// Set via MCP_WEB_DETECT_BOT_PROTECTION environment variable.
+    #[serde(default = "default_true")]
+    pub detect_bot_protection: bool,
+
+    /// Drop tracking query parameters (utm_*, fbclid, gclid, ...) from URLs before
+    /// they are fetched and keyed in the cache, so one article shared through
+    /// different campaigns is cached once.
+    ///
+    /// Set via MCP_WEB_STRIP_TRACKING_PARAMS environment variable.
+    #[serde(default = "default_true")]
+    pub strip_tracking_params: bool,
+
+    /// Query parameter names stripped in addition to the built-in tracking list.
+    ///
+    /// Set via MCP_WEB_EXTRA_TRACKING_PARAMS environment variable (comma-separated).
+    #[serde(default)]
+    pub extra_tracking_params: Vec<String>,
+
+    /// Drop `user:password@` from web_open URLs instead of rejecting them.
+    ///
+    /// Set via MCP_WEB_STRIP_URL_CREDENTIALS environment variable. Off by
+    /// default, so credentialed URLs fail with INVALID_URL and never reach the cache.
+    #[serde(default)]
+    pub strip_url_credentials: bool,
+
+    /// Longest canonical URL accepted, in bytes.
+    ///
+    /// Set via MCP_WEB_MAX_URL_LENGTH environment variable.
+    #[serde(default = "default_max_url_length")]
+    pub max_url_length: usize,
+
+    /// Cache behavior for personalized or authenticated responses and stored bodies.
+    ///
+    /// Set via MCP_WEB_CACHE__ALLOW_PRIVATE_CONTENT (`never` or `partitioned`) and
+    /// MCP_WEB_CACHE__STORE_RAW_DEFAULT.
+    #[serde(default)]
+    pub cache: CacheSettings,
+
+    /// Limits shared by every fetch the server makes.
+    ///
+    /// Set via MCP_WEB_FETCH__MAX_INFLIGHT_BYTES, MCP_WEB_FETCH__PER_HOST_INTERVAL_MS,
+    /// MCP_WEB_FETCH__ROBOTS_FAILURE_POLICY (`fail_closed` or `fail_open`),
+    /// MCP_WEB_FETCH__ALLOW_USER_AGENT_OVERRIDE, and MCP_WEB_FETCH__ALLOWED_CONTENT_TYPES.
+    #[serde(default)]
+    pub fetch: FetchSettings,
+
+    /// How the headless browser for rendered mode is launched.
+    ///
+    /// Set via MCP_WEB_RENDER__HEADLESS, MCP_WEB_RENDER__CHROME_PATH,
+    /// MCP_WEB_RENDER__EXTRA_ARGS, and MCP_WEB_RENDER__MAX_PAGES.
+    #[serde(default)]
+    pub render: RenderSettings,
+}
+
+/// Cache settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheSettings {
+    /// What to do with responses that are private to the requester (default: never cache them).
+    pub allow_private_content: PrivateContentPolicy,
+    /// Keep the original body alongside readable extractions unless a request says
+    /// otherwise (default: false, since bodies can be several times the size of the markdown).
+    pub store_raw_default: bool,
+}
+
+/// Fetch settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FetchSettings {
+    /// Response bytes all in-flight fetches may hold at once (default: 64MB; 0 = unbounded).
+    /// Fetches over the budget wait for earlier ones to finish.
+    pub max_inflight_bytes: usize,
+    /// Least time between requests to one host, in milliseconds (default: 500); a
+    /// longer robots.txt Crawl-delay wins. Requests to different hosts never wait.
+    pub per_host_interval_ms: u64,
+    /// What to do when robots.txt can't be retrieved (default: fail closed).
+    pub robots_failure_policy: RobotsFailurePolicy,
+    /// Retries after a connect error, timeout, or 502/503/504 response (default: 2).
+    pub retries: u8,
+    /// Wait before the first retry in milliseconds, doubled for each later one (default: 250).
+    pub retry_backoff_ms: u64,
+    /// Accept a `user_agent` parameter on web_open and web_batch_open (default: false,
+    /// so every fetch identifies as `user_agent`).
+    pub allow_user_agent_override: bool,
+    /// Content types whose bodies are downloaded (`type/subtype` or `type/*`; default: HTML,
+    /// XML, text, Markdown, JSON, feeds, and PDF). Other types are rejected as soon as
+    /// headers arrive; an empty list allows any.
+    pub allowed_content_types: Vec<String>,
+}
+
+impl Default for FetchSettings {
+    fn default() -> Self {
+        Self {
+            max_inflight_bytes: 64 * 1024 * 1024,
+            per_host_interval_ms: 500,
+            robots_failure_policy: RobotsFailurePolicy::FailClosed,
+            retries: 2,
+            retry_backoff_ms: 250,
+            allow_user_agent_override: false,
+            allowed_content_types: default_allowed_content_types(),
+        }
+    }
+}
+
+impl FetchSettings {
+    /// Per-host request spacing as a Duration.
+    pub fn per_host_interval(&self) -> Duration {
+        Duration::from_millis(self.per_host_interval_ms)
+    }
+
+    /// Wait before the first retry as a Duration.
+    pub fn retry_backoff(&self) -> Duration {
+        Duration::from_millis(self.retry_backoff_ms)
+    }
+}
+
+/// How a fetch proceeds when robots.txt answers 5xx or can't be reached.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RobotsFailurePolicy {
+    /// Fail the fetch with `ROBOTS_UNAVAILABLE`.
+    #[default]
+    FailClosed,
+    /// Log a warning and fetch as if robots.txt allowed everything, remembering
+    /// that for a few minutes so a broken endpoint isn't retried on every fetch.
+    FailOpen,
+}
+
+/// Headless browser settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderSettings {
+    /// Launch without a window (default: true). Turn off only to watch renders locally.
+    pub headless: bool,
+    /// Chrome/Chromium binary to launch; found on PATH when unset.
+    pub chrome_path: Option<PathBuf>,
+    /// Extra browser flags, e.g. `--no-sandbox` inside containers.
+    pub extra_args: Vec<String>,
+    /// Pages the shared browser keeps open at once (default: 4). Renders beyond
+    /// that wait for a page to come free.
+    pub max_pages: usize,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self { headless: true, chrome_path: None, extra_args: Vec::new(), max_pages: 4 }
+    }
+}
+
+/// How web_open caches responses fetched with credentials or marked per-user.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivateContentPolicy {
+    /// Return the page but never store it.
+    #[default]
+    Never,
+    /// Store it under a key that includes the caller's `cache_partition`; without one, don't store it.
+    Partitioned,
+}
+
+/// Per-tool deadlines in milliseconds; 0 means no deadline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolTimeouts {
+    /// web_open (default: 60s).
+    pub web_open: u64,
+    /// web_batch_open, for the whole batch (default: 300s).
+    pub web_batch_open: u64,
+    /// web_search (default: 30s).
+    pub web_search: u64,
+    /// web_extract (default: 30s).
+    pub web_extract: u64,
+}
+
+impl Default for ToolTimeouts {
+    fn default() -> Self {
+        Self { web_open: 60_000, web_batch_open: 300_000, web_search: 30_000, web_extract: 30_000 }
+    }
 }
 
 fn default_db_path() -> PathBuf {
@@ -100,6 +430,68 @@ fn default_timeout_ms() -> u64 {
     20_000
 }
 
+fn default_snapshot_ttl_seconds() -> u64 {
+    7 * 24 * 60 * 60
+}
+
+fn default_circuit_failure_threshold() -> u32 {
+    3
+}
+
+fn default_circuit_window_secs() -> u64 {
+    60
+}
+
+fn default_circuit_cooldown_secs() -> u64 {
+    30
+}
+
+fn default_batch_max_urls() -> usize {
+    50
+}
+
+fn default_max_url_length() -> usize {
+    8 * 1024
+}
+
+fn default_brave_min_interval_ms() -> u64 {
+    1000
+}
+
+fn default_search_provider() -> String {
+    "brave".to_string()
+}
+
+fn default_extra_snippets_max() -> usize {
+    5
+}
+
+fn default_extra_snippet_max_chars() -> usize {
+    300
+}
+
+fn default_pretty_json_max_bytes() -> usize {
+    100 * 1024
+}
+
+fn default_allowed_content_types() -> Vec<String> {
+    [
+        "text/html",
+        "application/xhtml+xml",
+        "application/xml",
+        "text/xml",
+        "text/plain",
+        "text/markdown",
+        "application/json",
+        "application/rss+xml",
+        "application/atom+xml",
+        "application/pdf",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
 fn default_true() -> bool {
     true
 }
@@ -113,9 +505,39 @@ impl Default for AppConfig {
             max_bytes: default_max_bytes(),
             timeout_ms: default_timeout_ms(),
             respect_robots: true,
+            allow_private_addresses: false,
             render_enabled: false,
             allowlist_domains: Vec::new(),
             denylist_domains: Vec::new(),
+            accept_language: None,
+            circuit_failure_threshold: default_circuit_failure_threshold(),
+            circuit_window_secs: default_circuit_window_secs(),
+            circuit_cooldown_secs: default_circuit_cooldown_secs(),
+            batch_max_urls: default_batch_max_urls(),
+            export_dir: None,
+            siteconfig_dir: None,
+            export_webhook_url: None,
+            export_webhook_allow_hosts: Vec::new(),
+            invalidate_on_version_mismatch: true,
+            snapshot_ttl_seconds: default_snapshot_ttl_seconds(),
+            brave_cost_per_thousand_requests: None,
+            brave_monthly_quota: None,
+            brave_min_interval_ms: default_brave_min_interval_ms(),
+            search_provider: default_search_provider(),
+            searxng_base_url: None,
+            extra_snippets_max: default_extra_snippets_max(),
+            extra_snippet_max_chars: default_extra_snippet_max_chars(),
+            pretty_json_max_bytes: default_pretty_json_max_bytes(),
+            health_probe_url: None,
+            tool_timeout_ms: ToolTimeouts::default(),
+            detect_bot_protection: true,
+            strip_tracking_params: true,
+            extra_tracking_params: Vec::new(),
+            strip_url_credentials: false,
+            max_url_length: default_max_url_length(),
+            cache: CacheSettings::default(),
+            fetch: FetchSettings::default(),
+            render: RenderSettings::default(),
         }
     }
 }
@@ -159,6 +581,14 @@ impl AppConfig {
         Ok(config)
     }
 
+    /// Pricing for Brave spend estimates.
+    pub fn brave_pricing(&self) -> BravePricing {
+        BravePricing {
+            cost_per_thousand_requests: self.brave_cost_per_thousand_requests,
+            monthly_quota: self.brave_monthly_quota,
+        }
+    }
+
     /// Check if Brave API key is available (for deferred validation).
     ///
     /// # Errors
@@ -184,10 +614,50 @@ mod tests {
         assert_eq!(config.max_bytes, 5_242_880);
         assert_eq!(config.timeout_ms, 20_000);
         assert!(config.respect_robots);
+        assert!(!config.allow_private_addresses);
         assert!(!config.render_enabled);
         assert!(config.allowlist_domains.is_empty());
         assert!(config.denylist_domains.is_empty());
         assert!(config.brave_api_key.is_none());
+        assert!(config.accept_language.is_none());
+        assert_eq!(config.circuit_failure_threshold, 3);
+        assert_eq!(config.circuit_window_secs, 60);
+        assert_eq!(config.circuit_cooldown_secs, 30);
+        assert_eq!(config.batch_max_urls, 50);
+        assert!(config.export_dir.is_none());
+        assert!(config.siteconfig_dir.is_none());
+        assert!(config.export_webhook_url.is_none());
+        assert!(config.invalidate_on_version_mismatch);
+        assert_eq!(config.snapshot_ttl_seconds, 7 * 24 * 60 * 60);
+        assert_eq!(config.brave_pricing(), BravePricing::default());
+        assert_eq!(config.brave_min_interval_ms, 1000);
+        assert_eq!(config.search_provider, "brave");
+        assert!(config.searxng_base_url.is_none());
+        assert_eq!(config.extra_snippets_max, 5);
+        assert_eq!(config.extra_snippet_max_chars, 300);
+        assert_eq!(config.pretty_json_max_bytes, 100 * 1024);
+        assert!(config.health_probe_url.is_none());
+        assert_eq!(config.tool_timeout_ms.web_open, 60_000);
+        assert_eq!(config.tool_timeout_ms.web_batch_open, 300_000);
+        assert_eq!(config.tool_timeout_ms.web_search, 30_000);
+        assert_eq!(config.tool_timeout_ms.web_extract, 30_000);
+        assert!(config.detect_bot_protection);
+        assert!(config.strip_tracking_params);
+        assert!(config.extra_tracking_params.is_empty());
+        assert!(!config.strip_url_credentials);
+        assert_eq!(config.max_url_length, 8192);
+        assert_eq!(config.cache.allow_private_content, PrivateContentPolicy::Never);
+        assert!(!config.cache.store_raw_default);
+        assert_eq!(config.fetch.max_inflight_bytes, 64 * 1024 * 1024);
+        assert_eq!(config.fetch.per_host_interval_ms, 500);
+        assert_eq!(config.fetch.robots_failure_policy, RobotsFailurePolicy::FailClosed);
+        assert_eq!(config.fetch.retries, 2);
+        assert_eq!(config.fetch.retry_backoff_ms, 250);
+        assert!(!config.fetch.allow_user_agent_override);
+        assert!(config.fetch.allowed_content_types.contains(&"text/html".to_string()));
+        assert!(config.render.headless);
+        assert!(config.render.chrome_path.is_none() && config.render.extra_args.is_empty());
+        assert_eq!(config.render.max_pages, 4);
     }
 
     #[test]
@@ -28,6 +28,15 @@ impl AppConfig {
     /// - `max_bytes` is 0 or exceeds 50MB
     /// - `timeout_ms` is less than 100ms or exceeds 5 minutes
     /// - `user_agent` is empty
+    /// - `accept_language` is set but is not a language tag list
+    /// - `circuit_failure_threshold` is 0
+    /// - `batch_max_urls` is 0
+    /// - `max_url_length` is less than 64 bytes
+    /// - `export_webhook_url` is set but is not an http(s) URL
+    /// - a `fetch.allowed_content_types` entry is not of the form `type/subtype` or `type/*`
+    /// - `brave_cost_per_thousand_requests` is negative or not finite
+    /// - `search_provider` is not "brave" or "searxng", or is "searxng" without
+    ///   an http(s) `searxng_base_url`
     pub fn validate(&self) -> Result<(), ConfigError> {
         if self.max_bytes == 0 {
             return Err(ConfigError::Invalid { field: "max_bytes".into(), reason: "must be greater than 0".into() });
@@ -50,6 +59,86 @@ impl AppConfig {
             return Err(ConfigError::Invalid { field: "user_agent".into(), reason: "must not be empty".into() });
         }
 
+        if let Some(lang) = &self.accept_language
+            && !is_valid_language_list(lang)
+        {
+            return Err(ConfigError::Invalid {
+                field: "accept_language".into(),
+                reason: format!("not a valid language tag list: {lang:?}"),
+            });
+        }
+
+        if self.circuit_failure_threshold == 0 {
+            return Err(ConfigError::Invalid {
+                field: "circuit_failure_threshold".into(),
+                reason: "must be at least 1".into(),
+            });
+        }
+
+        if self.batch_max_urls == 0 {
+            return Err(ConfigError::Invalid { field: "batch_max_urls".into(), reason: "must be at least 1".into() });
+        }
+
+        if self.max_url_length < 64 {
+            return Err(ConfigError::Invalid {
+                field: "max_url_length".into(),
+                reason: "must be at least 64 bytes".into(),
+            });
+        }
+
+        if self.render.max_pages == 0 {
+            return Err(ConfigError::Invalid { field: "render.max_pages".into(), reason: "must be at least 1".into() });
+        }
+
+        if let Some(url) = &self.export_webhook_url
+            && !(url.starts_with("http://") || url.starts_with("https://"))
+        {
+            return Err(ConfigError::Invalid {
+                field: "export_webhook_url".into(),
+                reason: "must be an http:// or https:// URL".into(),
+            });
+        }
+
+        if let Some(entry) = self.fetch.allowed_content_types.iter().find(|entry| {
+            let mut parts = entry.trim().splitn(2, '/');
+            let main = parts.next().unwrap_or_default();
+            let sub = parts.next().unwrap_or_default();
+            main.is_empty() || main == "*" || sub.is_empty() || sub.contains(['/', ';'])
+        }) {
+            return Err(ConfigError::Invalid {
+                field: "fetch.allowed_content_types".into(),
+                reason: format!("expected type/subtype or type/*, got {entry:?}"),
+            });
+        }
+
+        if let Some(cost) = self.brave_cost_per_thousand_requests
+            && !(cost.is_finite() && cost >= 0.0)
+        {
+            return Err(ConfigError::Invalid {
+                field: "brave_cost_per_thousand_requests".into(),
+                reason: "must be a non-negative number".into(),
+            });
+        }
+
+        match self.search_provider.as_str() {
+            "brave" => {}
+            "searxng" => {
+                let url = self.searxng_base_url.as_deref().unwrap_or_default();
+                if !(url.starts_with("http://") || url.starts_with("https://")) {
+                    return Err(ConfigError::Invalid {
+                        field: "searxng_base_url".into(),
+                        reason: "must be an http:// or https:// URL when search_provider is searxng".into(),
+                    });
+                }
+            }
+            other => {
+                return Err(ConfigError::Invalid {
+                    field: "search_provider".into(),
+                    reason: format!("expected brave or searxng, got {other:?}"),
+                });
+            }
+        }
+
         if !self.allowlist_domains.is_empty() && !self.denylist_domains.is_empty() {
             tracing::warn!(
                 allowlist_count = self.allowlist_domains.len(),
@@ -63,9 +152,42 @@ impl AppConfig {
     }
 }
 
+/// Check that a value looks like an Accept-Language list, e.g. `en-US,en;q=0.8,*;q=0.1`.
+///
+/// Each entry is `*` or a tag of 1-8 character alphanumeric subtags (the first
+/// alphabetic), optionally followed by a `q` weight between 0 and 1.
+pub fn is_valid_language_list(value: &str) -> bool {
+    if value.trim().is_empty() {
+        return false;
+    }
+
+    value.split(',').all(|entry| {
+        let mut parts = entry.trim().split(';');
+        let tag = parts.next().unwrap_or_default().trim();
+
+        let tag_ok = tag == "*"
+            || tag.split('-').enumerate().all(|(i, sub)| {
+                (1..=8).contains(&sub.len())
+                    && sub.chars().all(|c| c.is_ascii_alphanumeric())
+                    && (i > 0 || sub.chars().all(|c| c.is_ascii_alphabetic()))
+            });
+
+        let weight_ok = parts.all(|param| {
+            param
+                .trim()
+                .strip_prefix("q=")
+                .and_then(|q| q.parse::<f32>().ok())
+                .is_some_and(|q| (0.0..=1.0).contains(&q))
+        });
+
+        tag_ok && weight_ok
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::FetchSettings;
 
     #[test]
     fn test_validate_default_config() {
@@ -119,4 +241,115 @@ mod tests {
         let config = AppConfig { max_bytes: 50 * 1024 * 1024, timeout_ms: 300_000, ..Default::default() }; // exactly 50MB
         assert!(config.validate().is_ok());
     }
+
+    #[test]
+    fn test_validate_accept_language() {
+        let config = AppConfig { accept_language: Some("de-DE,de;q=0.9,en;q=0.5".into()), ..Default::default() };
+        assert!(config.validate().is_ok());
+
+        let config = AppConfig { accept_language: Some("en\r\nX-Injected: 1".into()), ..Default::default() };
+        let result = config.validate();
+        assert!(matches!(result, Err(ConfigError::Invalid { field, .. }) if field == "accept_language"));
+    }
+
+    #[test]
+    fn test_validate_circuit_threshold_zero() {
+        let config = AppConfig { circuit_failure_threshold: 0, ..Default::default() };
+        let result = config.validate();
+        assert!(matches!(result, Err(ConfigError::Invalid { field, .. }) if field == "circuit_failure_threshold"));
+    }
+
+    #[test]
+    fn test_validate_max_url_length() {
+        let config = AppConfig { max_url_length: 16, ..Default::default() };
+        let result = config.validate();
+        assert!(matches!(result, Err(ConfigError::Invalid { field, .. }) if field == "max_url_length"));
+        assert!(
+            AppConfig { max_url_length: 64, ..Default::default() }
+                .validate()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_validate_render_max_pages_zero() {
+        let render = crate::config::RenderSettings { max_pages: 0, ..Default::default() };
+        let config = AppConfig { render, ..Default::default() };
+        let result = config.validate();
+        assert!(matches!(result, Err(ConfigError::Invalid { field, .. }) if field == "render.max_pages"));
+    }
+
+    #[test]
+    fn test_validate_brave_cost() {
+        let config = AppConfig { brave_cost_per_thousand_requests: Some(3.0), ..Default::default() };
+        assert!(config.validate().is_ok());
+
+        for cost in [-1.0, f64::NAN] {
+            let config = AppConfig { brave_cost_per_thousand_requests: Some(cost), ..Default::default() };
+            let result = config.validate();
+            assert!(
+                matches!(result, Err(ConfigError::Invalid { field, .. }) if field == "brave_cost_per_thousand_requests")
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_search_provider() {
+        let config = AppConfig { search_provider: "bing".into(), ..Default::default() };
+        assert!(matches!(config.validate(), Err(ConfigError::Invalid { field, .. }) if field == "search_provider"));
+
+        let config = AppConfig { search_provider: "searxng".into(), ..Default::default() };
+        assert!(matches!(config.validate(), Err(ConfigError::Invalid { field, .. }) if field == "searxng_base_url"));
+
+        let config = AppConfig {
+            search_provider: "searxng".into(),
+            searxng_base_url: Some("http://localhost:8888".into()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_export_webhook_url() {
+        let config = AppConfig { export_webhook_url: Some("file:///tmp/hook".into()), ..Default::default() };
+        let result = config.validate();
+        assert!(matches!(result, Err(ConfigError::Invalid { field, .. }) if field == "export_webhook_url"));
+
+        let config = AppConfig { export_webhook_url: Some("https://hooks.example.com/x".into()), ..Default::default() };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_allowed_content_types() {
+        for bad in ["html", "text/", "*/*", "text/html; charset=utf-8"] {
+            let config = content_types(vec![bad.into()]);
+            let result = config.validate();
+            assert!(
+                matches!(result, Err(ConfigError::Invalid { field, .. }) if field == "fetch.allowed_content_types"),
+                "{bad} should be rejected"
+            );
+        }
+
+        assert!(content_types(vec!["text/*".into()]).validate().is_ok());
+        assert!(content_types(Vec::new()).validate().is_ok());
+    }
+
+    fn content_types(allowed_content_types: Vec<String>) -> AppConfig {
+        AppConfig { fetch: FetchSettings { allowed_content_types, ..Default::default() }, ..Default::default() }
+    }
+
+    #[test]
+    fn test_is_valid_language_list() {
+        assert!(is_valid_language_list("en"));
+        assert!(is_valid_language_list("en-US, fr;q=0.7, *;q=0.1"));
+        assert!(is_valid_language_list("zh-Hant-TW"));
+        assert!(is_valid_language_list("es-419"));
+
+        assert!(!is_valid_language_list(""));
+        assert!(!is_valid_language_list("en,"));
+        assert!(!is_valid_language_list("toolongtag"));
+        assert!(!is_valid_language_list("1en"));
+        assert!(!is_valid_language_list("en;q=2"));
+        assert!(!is_valid_language_list("en;charset=utf-8"));
+    }
 }
@@ -44,6 +44,10 @@ pub enum Error {
     #[error("ROBOTS_DISALLOWED: {0}")]
     RobotsDisallowed(String),
 
+    /// robots.txt couldn't be retrieved (5xx, network failure, or oversized).
+    #[error("ROBOTS_UNAVAILABLE: {0}")]
+    RobotsUnavailable(String),
+
     /// Fetch timeout.
     #[error("FETCH_TIMEOUT: {0}")]
     FetchTimeout(String),
@@ -71,6 +75,30 @@ pub enum Error {
     /// Render failed.
     #[error("RENDER_FAILED: {0}")]
     RenderFailed(String),
+
+    /// Host circuit breaker is open after repeated failures.
+    #[error("CIRCUIT_OPEN: {0}")]
+    CircuitOpen(String),
+
+    /// Response Content-Type is not in the fetch allowlist.
+    #[error("UNSUPPORTED_CONTENT_TYPE: {0}")]
+    UnsupportedContentType(String),
+
+    /// Host is blocked by the domain allow/deny policy.
+    #[error("DOMAIN_BLOCKED: {0}")]
+    DomainBlocked(String),
+
+    /// Host asked us to back off (429/503 with Retry-After) and is still cooling down.
+    #[error("HOST_COOLING_DOWN: {0}")]
+    HostCoolingDown(String),
+
+    /// A tool call exceeded its overall deadline.
+    #[error("TOOL_TIMEOUT: {0}")]
+    ToolTimeout(String),
+
+    /// The site answered with a bot-protection challenge instead of the page.
+    #[error("BLOCKED_BY_BOT_PROTECTION: {0}")]
+    BlockedByBotProtection(String),
 }
 
 impl From<tokio_rusqlite::Error<Error>> for Error {
@@ -112,6 +140,13 @@ impl From<Error> for McpError {
             Error::BraveRateLimited(msg) => (-32010, msg.clone()),
             Error::RenderDisabled => (-32011, "Render mode is disabled".to_string()),
             Error::RenderFailed(msg) => (-32012, msg.clone()),
+            Error::CircuitOpen(msg) => (-32013, msg.clone()),
+            Error::UnsupportedContentType(msg) => (-32014, msg.clone()),
+            Error::DomainBlocked(msg) => (-32015, msg.clone()),
+            Error::HostCoolingDown(msg) => (-32016, msg.clone()),
+            Error::ToolTimeout(msg) => (-32017, msg.clone()),
+            Error::BlockedByBotProtection(msg) => (-32018, msg.clone()),
+            Error::RobotsUnavailable(msg) => (-32019, msg.clone()),
             Error::Database(e) => (-32002, e.to_string()),
             Error::MigrationFailed(msg) => (-32002, msg.clone()),
             Error::InvalidHash => (-32002, "Invalid hash format".to_string()),
@@ -4,11 +4,23 @@
 //! - Cache implementation with SQLite backend
 //! - Unified error types
 //! - Configuration structures
+//! - Domain allow/deny policy
+//! - Injectable clock for cache timestamps
+//! - The document output [`Mode`]
 
 pub mod cache;
+pub mod clock;
 pub mod config;
 pub mod error;
+pub mod mode;
+pub mod policy;
 
 pub use cache::{CacheDb, Snapshot};
-pub use config::{AppConfig, ConfigError};
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use config::{
+    AppConfig, CacheSettings, ConfigError, FetchSettings, PrivateContentPolicy, RenderSettings, RobotsFailurePolicy,
+    ToolTimeouts,
+};
 pub use error::Error;
+pub use mode::Mode;
+pub use policy::{DomainDecision, DomainPolicy};
@@ -0,0 +1,114 @@
+//! Output modes for opened documents.
+//!
+//! The mode is part of every request, the cache key, and the stored snapshot.
+//! Its lowercase name (`readable`, `raw`, `rendered`, `feed`) is the wire,
+//! storage, and hashing form; changing one would orphan every cached snapshot.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+/// How a fetched page is turned into output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    /// Main content extracted as Markdown.
+    #[default]
+    Readable,
+    /// The response body as text, without extraction.
+    Raw,
+    /// Main content extracted after a headless browser ran the page's scripts.
+    Rendered,
+    /// Entries of an RSS or Atom feed, as parsed by web_feed.
+    Feed,
+}
+
+impl Mode {
+    /// Every mode, in documentation order.
+    pub const ALL: [Mode; 4] = [Mode::Readable, Mode::Raw, Mode::Rendered, Mode::Feed];
+
+    /// Stable lowercase name, as sent by callers, stored in snapshots, and hashed into cache keys.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Readable => "readable",
+            Self::Raw => "raw",
+            Self::Rendered => "rendered",
+            Self::Feed => "feed",
+        }
+    }
+
+    /// Mode of a stored snapshot row.
+    ///
+    /// Rows written before modes were validated may hold any text; those read
+    /// as `Readable`, which never exposes a stored body the row didn't mean to return.
+    pub fn from_stored(value: &str) -> Self {
+        value.trim().parse().unwrap_or_else(|_| {
+            tracing::warn!("snapshot has unknown mode {:?}, reading it as readable", value);
+            Self::Readable
+        })
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Mode {
+    type Err = Error;
+
+    /// Parse a mode name, ignoring ASCII case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                Error::InvalidInput(format!(
+                    "unsupported mode: {s} (expected one of: readable, raw, rendered, feed)"
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serde_round_trip() {
+        for mode in Mode::ALL {
+            let json = serde_json::to_string(&mode).unwrap();
+            assert_eq!(json, format!("\"{}\"", mode.as_str()));
+            assert_eq!(serde_json::from_str::<Mode>(&json).unwrap(), mode);
+            assert_eq!(mode.as_str().parse::<Mode>().unwrap(), mode);
+        }
+    }
+
+    #[test]
+    fn test_unknown_mode_lists_allowed_values() {
+        let err = serde_json::from_str::<Mode>("\"pdf\"").unwrap_err().to_string();
+        assert!(
+            err.contains("readable") && err.contains("raw") && err.contains("rendered"),
+            "{err}"
+        );
+
+        let err = "pdf".parse::<Mode>().unwrap_err().to_string();
+        assert!(
+            err.contains("unsupported mode: pdf") && err.contains("readable, raw, rendered, feed"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_from_stored_tolerates_legacy_rows() {
+        assert_eq!(Mode::from_stored("raw"), Mode::Raw);
+        assert_eq!(Mode::from_stored("Rendered "), Mode::Rendered);
+        assert_eq!(Mode::from_stored("feed"), Mode::Feed);
+        assert_eq!(Mode::from_stored("markdown"), Mode::Readable);
+        assert_eq!(Mode::from_stored(""), Mode::Readable);
+    }
+}
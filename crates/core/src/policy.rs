@@ -0,0 +1,276 @@
+//! Domain allow/deny policy shared by fetch, search filtering, and rendering.
+//!
+//! Patterns are matched against a URL host:
+//! - `example.com` matches `example.com` and every subdomain of it
+//! - `*.example.com` (or `.example.com`) matches subdomains only
+//! - `*` matches every host
+//! - IP literals match exactly
+//!
+//! Matching ignores case and a trailing dot. When the server allowlist is
+//! non-empty it takes precedence and the denylist is not consulted; otherwise a
+//! host is allowed unless it matches the denylist. Per-request allowlists added
+//! with [`DomainPolicy::with_request_allowlist`] narrow the server policy and can
+//! never allow a host the server policy denies.
+
+use std::net::IpAddr;
+
+use crate::{AppConfig, Error};
+
+/// Outcome of evaluating a host against a [`DomainPolicy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DomainDecision {
+    Allow,
+    Deny { reason: String },
+}
+
+impl DomainDecision {
+    /// Whether the host is allowed.
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, Self::Allow)
+    }
+}
+
+/// A single allow/deny list entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DomainPattern {
+    /// `*`
+    Any,
+    /// `example.com`: the domain and its subdomains.
+    Domain(String),
+    /// `*.example.com`: subdomains only.
+    Subdomains(String),
+}
+
+impl DomainPattern {
+    /// Parse a list entry, returning None for blank entries.
+    fn parse(entry: &str) -> Option<Self> {
+        let entry = normalize_host(entry);
+        if entry.is_empty() {
+            return None;
+        }
+        if entry == "*" {
+            return Some(Self::Any);
+        }
+
+        match entry.strip_prefix("*.").or_else(|| entry.strip_prefix('.')) {
+            Some(domain) if !domain.is_empty() => Some(Self::Subdomains(domain.to_string())),
+            Some(_) => None,
+            None => Some(Self::Domain(entry)),
+        }
+    }
+
+    /// Match a host already passed through [`normalize_host`].
+    fn matches(&self, host: &str) -> bool {
+        let is_ip = host.parse::<IpAddr>().is_ok();
+        match self {
+            Self::Any => true,
+            Self::Domain(domain) => host == domain || (!is_ip && is_subdomain_of(host, domain)),
+            Self::Subdomains(domain) => !is_ip && is_subdomain_of(host, domain),
+        }
+    }
+}
+
+fn is_subdomain_of(host: &str, domain: &str) -> bool {
+    host.strip_suffix(domain)
+        .is_some_and(|prefix| prefix.len() > 1 && prefix.ends_with('.'))
+}
+
+/// Lowercase, trim whitespace, brackets around IPv6 literals, and a trailing dot.
+fn normalize_host(host: &str) -> String {
+    let host = host.trim();
+    let host = host.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(host);
+    host.strip_suffix('.').unwrap_or(host).to_ascii_lowercase()
+}
+
+fn parse_list(entries: &[String]) -> Vec<DomainPattern> {
+    entries.iter().filter_map(|e| DomainPattern::parse(e)).collect()
+}
+
+/// Server domain policy plus any per-request allowlists layered on top.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DomainPolicy {
+    allow: Vec<DomainPattern>,
+    deny: Vec<DomainPattern>,
+    request_allow: Vec<Vec<DomainPattern>>,
+}
+
+impl DomainPolicy {
+    /// Build a policy from allowlist and denylist entries.
+    pub fn new(allowlist: &[String], denylist: &[String]) -> Self {
+        Self { allow: parse_list(allowlist), deny: parse_list(denylist), request_allow: Vec::new() }
+    }
+
+    /// Build the server policy from `allowlist_domains` and `denylist_domains`.
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self::new(&config.allowlist_domains, &config.denylist_domains)
+    }
+
+    /// Narrow the policy with a per-request allowlist.
+    ///
+    /// A host must pass the server policy and match the request allowlist. An
+    /// empty request allowlist allows nothing.
+    pub fn with_request_allowlist(mut self, allowlist: &[String]) -> Self {
+        self.request_allow.push(parse_list(allowlist));
+        self
+    }
+
+    /// Whether the policy allows every host.
+    pub fn is_unrestricted(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty() && self.request_allow.is_empty()
+    }
+
+    /// Evaluate a URL host against the policy.
+    pub fn evaluate(&self, host: &str) -> DomainDecision {
+        let host = normalize_host(host);
+
+        if !self.allow.is_empty() {
+            if !self.allow.iter().any(|p| p.matches(&host)) {
+                return DomainDecision::Deny { reason: format!("{host} is not on the server allowlist") };
+            }
+        } else if self.deny.iter().any(|p| p.matches(&host)) {
+            return DomainDecision::Deny { reason: format!("{host} is on the server denylist") };
+        }
+
+        if self
+            .request_allow
+            .iter()
+            .any(|list| !list.iter().any(|p| p.matches(&host)))
+        {
+            return DomainDecision::Deny { reason: format!("{host} is not on the request allowlist") };
+        }
+
+        DomainDecision::Allow
+    }
+
+    /// Evaluate a host, mapping a denial to [`Error::DomainBlocked`].
+    pub fn check(&self, host: &str) -> Result<(), Error> {
+        match self.evaluate(host) {
+            DomainDecision::Allow => Ok(()),
+            DomainDecision::Deny { reason } => Err(Error::DomainBlocked(reason)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(entries: &[&str]) -> Vec<String> {
+        entries.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_pattern_matching_table() {
+        let cases: &[(&str, &str, bool)] = &[
+            ("example.com", "example.com", true),
+            ("example.com", "docs.example.com", true),
+            ("example.com", "a.b.example.com", true),
+            ("example.com", "notexample.com", false),
+            ("example.com", "example.com.evil.net", false),
+            ("example.com", "example.org", false),
+            ("Example.COM", "EXAMPLE.com", true),
+            ("example.com.", "example.com", true),
+            ("example.com", "example.com.", true),
+            ("*.example.com", "docs.example.com", true),
+            ("*.example.com", "a.b.example.com", true),
+            ("*.example.com", "example.com", false),
+            (".example.com", "docs.example.com", true),
+            (".example.com", "example.com", false),
+            ("*", "anything.test", true),
+            ("*", "10.0.0.1", true),
+            ("10.0.0.1", "10.0.0.1", true),
+            ("0.0.1", "10.0.0.1", false),
+            ("*.0.0.1", "10.0.0.1", false),
+            ("::1", "[::1]", true),
+            ("[::1]", "::1", true),
+        ];
+
+        for (pattern, host, expected) in cases {
+            let parsed = DomainPattern::parse(pattern).unwrap();
+            assert_eq!(
+                parsed.matches(&normalize_host(host)),
+                *expected,
+                "pattern {pattern:?} vs host {host:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_blank_entries_ignored() {
+        assert_eq!(DomainPattern::parse("  "), None);
+        assert_eq!(DomainPattern::parse("."), None);
+        assert!(DomainPolicy::new(&list(&["", " "]), &list(&[""])).is_unrestricted());
+    }
+
+    #[test]
+    fn test_policy_table() {
+        let unrestricted = DomainPolicy::default();
+        let deny = DomainPolicy::new(&[], &list(&["ads.example.com", "*.tracker.net"]));
+        let allow = DomainPolicy::new(&list(&["example.com"]), &[]);
+        let both = DomainPolicy::new(&list(&["example.com"]), &list(&["example.com"]));
+        let request =
+            DomainPolicy::new(&[], &list(&["evil.com"])).with_request_allowlist(&list(&["docs.rs", "evil.com"]));
+        let narrowed = allow
+            .clone()
+            .with_request_allowlist(&list(&["docs.example.com", "other.org"]));
+        let empty_request = DomainPolicy::default().with_request_allowlist(&[]);
+
+        let cases: &[(&DomainPolicy, &str, bool)] = &[
+            (&unrestricted, "anything.test", true),
+            (&deny, "example.com", true),
+            (&deny, "ads.example.com", false),
+            (&deny, "x.ads.example.com", false),
+            (&deny, "tracker.net", true),
+            (&deny, "cdn.tracker.net", false),
+            (&allow, "example.com", true),
+            (&allow, "www.example.com", true),
+            (&allow, "example.org", false),
+            (&both, "example.com", true),
+            (&request, "docs.rs", true),
+            (&request, "evil.com", false),
+            (&request, "crates.io", false),
+            (&narrowed, "docs.example.com", true),
+            (&narrowed, "example.com", false),
+            (&narrowed, "other.org", false),
+            (&empty_request, "example.com", false),
+        ];
+
+        for (policy, host, expected) in cases {
+            assert_eq!(
+                policy.evaluate(host).is_allowed(),
+                *expected,
+                "{policy:?} vs host {host:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_deny_reasons() {
+        let allow = DomainPolicy::new(&list(&["example.com"]), &[]);
+        assert_eq!(
+            allow.evaluate("Other.org"),
+            DomainDecision::Deny { reason: "other.org is not on the server allowlist".into() }
+        );
+
+        let deny = DomainPolicy::new(&[], &list(&["example.com"]));
+        assert_eq!(
+            deny.evaluate("example.com"),
+            DomainDecision::Deny { reason: "example.com is on the server denylist".into() }
+        );
+
+        let request = DomainPolicy::default().with_request_allowlist(&list(&["docs.rs"]));
+        assert!(
+            matches!(request.check("example.com"), Err(Error::DomainBlocked(reason)) if reason.contains("request"))
+        );
+        assert!(request.check("docs.rs").is_ok());
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config = AppConfig { denylist_domains: list(&["example.com"]), ..Default::default() };
+        let policy = DomainPolicy::from_config(&config);
+        assert!(!policy.evaluate("example.com").is_allowed());
+        assert!(!policy.is_unrestricted());
+        assert!(DomainPolicy::from_config(&AppConfig::default()).is_unrestricted());
+    }
+}
@@ -1,13 +1,25 @@
 //! MCP server handler implementation.
 //!
 //! This module defines the main server handler that routes tool calls
-//! to the appropriate implementations.
+//! to the appropriate implementations. The handler is also the embedding
+//! surface: other rmcp servers can hold one and forward calls to it.
 
-use crate::tools::cache::{CacheGetParams, CachePurgeParams, get_impl, purge_impl};
+use crate::jobs::JobRunner;
+use crate::tools::cache::{
+    CacheGetParams, CacheListParams, CachePinParams, CachePurgeParams, CacheWarmParams, get_impl, list_impl, pin_impl,
+    purge_impl, stats_impl, warm_impl,
+};
+use crate::tools::deadline::with_deadline;
+use crate::tools::health::health_impl;
 use crate::tools::web_batch_open::{WebBatchOpenParams, batch_open_impl};
 use crate::tools::web_extract::{WebExtractParams, extract_impl};
-use crate::tools::web_open::{WebOpenParams, open_impl};
-use crate::tools::web_search::{WebSearchParams, search_impl};
+use crate::tools::web_feed::{WebFeedParams, feed_impl};
+use crate::tools::web_image_search::{WebImageSearchParams, image_search_impl};
+use crate::tools::web_news_search::{WebNewsSearchParams, news_search_impl};
+use crate::tools::web_open::{WebOpenParams, exporter, fetch_client, open_impl};
+use crate::tools::web_search::{WebSearchParams, brave_client, search_impl, searxng_client};
+use crate::tools::web_sitemap::{WebSitemapParams, sitemap_impl};
+use crate::tools::web_suggest::{WebSuggestParams, suggest_impl};
 
 use rmcp::{
     ErrorData as McpError, ServerHandler,
@@ -17,12 +29,18 @@ use rmcp::{
     },
     model::{
         CallToolRequestParam, CallToolResult, Implementation, ListToolsResult, PaginatedRequestParam, ProtocolVersion,
-        ServerCapabilities, ServerInfo,
+        ServerCapabilities, ServerInfo, Tool,
     },
     service::{RequestContext, RoleServer},
     tool, tool_router,
 };
 use std::sync::Arc;
+use std::time::Duration;
+use thndrs_client::{
+    BraveClient, CircuitBreaker, Exporter, FetchClient, HostState, RendererPool, RequestRules, SearchProvider,
+    SiteConfigRegistry, fetch::ByteBudget,
+};
+use thndrs_core::DomainPolicy;
 use thndrs_core::{AppConfig, CacheDb};
 
 /// The main MCP server handler for mcp-web.
@@ -31,42 +49,224 @@ pub struct McpWebServer {
     config: Arc<AppConfig>,
     tool_router: ToolRouter<Self>,
     cache: CacheDb,
+    fetch: Arc<FetchClient>,
+    exporter: Option<Exporter>,
+    brave: Option<BraveClient>,
+    search: Option<Arc<dyn SearchProvider>>,
+    renderer: Option<RendererPool>,
+    siteconfigs: Arc<SiteConfigRegistry>,
+    jobs: JobRunner,
 }
 
-/// Tool router implementation using the #[tool_router] macro.
-///
-/// This macro generates the routing logic that maps tool names to handler methods.
-#[tool_router]
 impl McpWebServer {
     /// Create a new server handler with the given configuration.
     ///
-    /// Opens the SQLite cache database at the configured path and initializes
-    /// the Brave client if an API key is provided.
+    /// Opens the SQLite cache database at the configured path. With
+    /// `render_enabled`, launches the browser rendered mode shares; if that
+    /// fails, each rendered call launches its own and reports the error.
     pub async fn new(config: AppConfig) -> Result<Self, anyhow::Error> {
-        let config = Arc::new(config);
-
         let cache = CacheDb::open(&config.db_path).await?;
+        let renderer = if config.render_enabled {
+            let rules = RequestRules {
+                domain_policy: DomainPolicy::from_config(&config),
+                allow_private_addresses: config.allow_private_addresses,
+            };
+            match RendererPool::launch(&(&config.render).into(), rules).await {
+                Ok(pool) => Some(pool),
+                Err(e) => {
+                    tracing::warn!("renderer launch failed, rendered calls will launch their own: {e}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        Ok(Self { renderer, ..Self::with_cache(config, cache) })
+    }
+
+    /// Create a handler over a cache database the caller has already opened.
+    ///
+    /// Builds the fetch client web_open, web_batch_open, web_feed, web_sitemap, and
+    /// cache_warm share, so connections and robots.txt rules carry over between calls.
+    /// With an API key configured, builds the one Brave client every search
+    /// tool shares, so its rate limiter holds across calls. web_search goes
+    /// through the configured `search_provider`, which is that client unless
+    /// SearXNG is selected. Site configs are read from `siteconfig_dir` once,
+    /// here; a directory that fails to load is logged and none are applied.
+    /// The document exporter is built here too when an export destination is set.
+    pub fn with_cache(config: AppConfig, cache: CacheDb) -> Self {
+        let circuit = CircuitBreaker::new(
+            config.circuit_failure_threshold,
+            Duration::from_secs(config.circuit_window_secs),
+            Duration::from_secs(config.circuit_cooldown_secs),
+        );
+        let hosts = HostState::default().with_byte_budget(ByteBudget::new(config.fetch.max_inflight_bytes));
+        let fetch = Arc::new(shared_fetch_client(&config, &cache, &circuit, &hosts));
+        let exporter = exporter(&config);
+        let brave = config
+            .brave_api_key
+            .is_some()
+            .then(|| brave_client(&config))
+            .and_then(|client| {
+                client
+                    .inspect_err(|e| tracing::warn!("Brave client unavailable: {e}"))
+                    .ok()
+            });
+        let search = match config.search_provider.as_str() {
+            "searxng" => searxng_client(&config)
+                .inspect_err(|e| tracing::warn!("SearXNG client unavailable: {e}"))
+                .ok()
+                .map(|client| Arc::new(client) as Arc<dyn SearchProvider>),
+            _ => brave.clone().map(|client| Arc::new(client) as Arc<dyn SearchProvider>),
+        };
+        let siteconfigs = config
+            .siteconfig_dir
+            .as_deref()
+            .map(SiteConfigRegistry::load)
+            .transpose()
+            .inspect_err(|e| tracing::warn!("siteconfigs unavailable: {e}"))
+            .unwrap_or_default()
+            .unwrap_or_default();
+
+        Self {
+            config: Arc::new(config),
+            tool_router: Self::tool_router(),
+            cache,
+            fetch,
+            exporter,
+            brave,
+            search,
+            renderer: None,
+            siteconfigs: Arc::new(siteconfigs),
+            jobs: JobRunner::default(),
+        }
+    }
+
+    /// Share host failure state with a fetch client the embedder already uses.
+    ///
+    /// The tools keep their own client, configured from [`AppConfig`], but it
+    /// adopts this client's circuit breaker and host state, so a host failing
+    /// for one fails fast for both, and both honor one host's cool-downs and
+    /// pacing and draw on one in-flight byte budget.
+    pub fn with_fetch_client(mut self, client: &FetchClient) -> Self {
+        self.fetch = Arc::new(shared_fetch_client(
+            &self.config,
+            &self.cache,
+            client.circuit_breaker(),
+            client.host_state(),
+        ));
+        self
+    }
+
+    /// Use this Brave client for the search tools instead of one built from the configured API key.
+    ///
+    /// web_search uses it too unless another `search_provider` is configured.
+    pub fn with_brave_client(mut self, client: BraveClient) -> Self {
+        if self.config.search_provider == "brave" {
+            self.search = Some(Arc::new(client.clone()));
+        }
+        self.brave = Some(client);
+        self
+    }
+
+    /// Search with this provider in web_search instead of the configured one.
+    pub fn with_search_provider(mut self, provider: Arc<dyn SearchProvider>) -> Self {
+        self.search = Some(provider);
+        self
+    }
+
+    /// Render rendered-mode calls on this browser pool instead of one launched per call.
+    pub fn with_renderer_pool(mut self, pool: RendererPool) -> Self {
+        self.renderer = Some(pool);
+        self
+    }
+
+    /// Extract with these site configs instead of the ones read from `siteconfig_dir`.
+    pub fn with_siteconfigs(mut self, siteconfigs: SiteConfigRegistry) -> Self {
+        self.siteconfigs = Arc::new(siteconfigs);
+        self
+    }
+
+    /// Close the shared browser, if one was launched; call once the transport has closed.
+    pub async fn shutdown(&self) {
+        if let Some(renderer) = &self.renderer {
+            renderer.shutdown().await;
+        }
+    }
 
-        Ok(Self { config, tool_router: Self::tool_router(), cache })
+    /// The configuration the tools run with.
+    pub fn config(&self) -> &AppConfig {
+        &self.config
     }
 
+    /// The cache database the tools read and write.
+    pub fn cache(&self) -> &CacheDb {
+        &self.cache
+    }
+
+    /// Definitions of every tool this handler serves.
+    pub fn tools(&self) -> Vec<Tool> {
+        self.tool_router.list_all()
+    }
+
+    /// Whether `name` is one of this handler's tools.
+    pub fn has_tool(&self, name: &str) -> bool {
+        self.tool_router.has_route(name)
+    }
+
+    /// Run a tool call against this handler, for servers that embed it.
+    pub async fn call_tool_request(
+        &self, request: CallToolRequestParam, context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        self.tool_router
+            .call(ToolCallContext::new(self, request, context))
+            .await
+    }
+}
+
+/// Tool router implementation using the #[tool_router] macro.
+///
+/// This macro generates the routing logic that maps tool names to handler
+/// methods, as a public `tool_router()` so embedders can merge or inspect it.
+#[tool_router(vis = "pub")]
+impl McpWebServer {
     /// Extract readable content from HTML.
     ///
-    /// This tool takes raw HTML and extracts the main article content, returning it as Markdown.
-    /// No network requests are made.
-    #[tool(description = "Extract readable content from HTML. Returns Markdown with title, links, and main content.")]
+    /// This tool takes raw HTML (or the stored body of a cached snapshot) and extracts the main
+    /// article content, returning it as Markdown. No network requests are made.
+    #[tool(
+        description = "Extract readable content from HTML or a cached snapshot. Returns Markdown with title, links, and main content."
+    )]
     async fn web_extract(&self, params: Parameters<WebExtractParams>) -> Result<CallToolResult, McpError> {
-        extract_impl(params.0).await
+        with_deadline(
+            "web_extract",
+            self.config.tool_timeout_ms.web_extract,
+            extract_impl(&self.cache, &self.config, params.0),
+        )
+        .await
     }
 
     /// Fetch a URL and extract readable content.
     ///
     /// Performs HTTP fetch with SSRF protection and robots.txt compliance,
     /// then extracts the main content as Markdown.
-    /// Modes: "readable" (default) or "raw".
+    /// Modes: "readable" (default), "raw", or "rendered" (headless browser, when enabled).
     #[tool(description = "Fetch a URL and extract readable content with SSRF protection and robots.txt compliance.")]
     async fn web_open(&self, params: Parameters<WebOpenParams>) -> Result<CallToolResult, McpError> {
-        open_impl(&self.cache, &self.config, params.0).await
+        with_deadline(
+            "web_open",
+            self.config.tool_timeout_ms.web_open,
+            open_impl(
+                &self.cache,
+                &self.config,
+                &self.fetch,
+                self.renderer.as_ref(),
+                &self.siteconfigs,
+                self.exporter.as_ref(),
+                params.0,
+            ),
+        )
+        .await
     }
 
     /// Fetch multiple URLs and extract readable content in parallel.
@@ -75,7 +275,45 @@ impl McpWebServer {
     /// and robots.txt compliance. Results are returned in input order.
     #[tool(description = "Fetch multiple URLs in parallel with bounded concurrency and SSRF protection.")]
     async fn web_batch_open(&self, params: Parameters<WebBatchOpenParams>) -> Result<CallToolResult, McpError> {
-        batch_open_impl(&self.cache, &self.config, params.0).await
+        with_deadline(
+            "web_batch_open",
+            self.config.tool_timeout_ms.web_batch_open,
+            batch_open_impl(
+                &self.cache,
+                &self.config,
+                &self.fetch,
+                self.renderer.as_ref(),
+                &self.siteconfigs,
+                self.exporter.as_ref(),
+                params.0,
+            ),
+        )
+        .await
+    }
+
+    /// List the pages a site publishes in its sitemaps.
+    ///
+    /// Discovers sitemaps via robots.txt (falling back to /sitemap.xml), follows
+    /// sitemap indexes, and returns a filtered, paginated list of entries.
+    #[tool(description = "Discover a site's sitemaps and list their URLs with glob, regex, and lastmod filters.")]
+    async fn web_sitemap(&self, params: Parameters<WebSitemapParams>) -> Result<CallToolResult, McpError> {
+        sitemap_impl(&self.cache, &self.config, &self.fetch, params.0).await
+    }
+
+    /// Read the entries of an RSS or Atom feed.
+    ///
+    /// Returns typed entries (title, link, published, summary), optionally only
+    /// those published since a date. Feeds are cached for up to an hour.
+    #[tool(
+        description = "Read an RSS or Atom feed and list its entries, optionally only those published since a date."
+    )]
+    async fn web_feed(&self, params: Parameters<WebFeedParams>) -> Result<CallToolResult, McpError> {
+        with_deadline(
+            "web_feed",
+            self.config.tool_timeout_ms.web_open,
+            feed_impl(&self.cache, &self.config, &self.fetch, params.0),
+        )
+        .await
     }
 
     /// Search the web using Brave Search API.
@@ -84,7 +322,56 @@ impl McpWebServer {
     /// Requires MCP_WEB_BRAVE_API_KEY environment variable to be set.
     #[tool(description = "Search the web using Brave Search API with caching and optional domain filtering.")]
     async fn web_search(&self, params: Parameters<WebSearchParams>) -> Result<CallToolResult, McpError> {
-        search_impl(&self.cache, &self.config, params.0).await
+        with_deadline(
+            "web_search",
+            self.config.tool_timeout_ms.web_search,
+            search_impl(&self.cache, &self.config, self.search.as_deref(), params.0),
+        )
+        .await
+    }
+
+    /// Search recent news using Brave News Search API.
+    ///
+    /// Shares web_search's Brave client and cache; results carry the
+    /// publisher, age, and thumbnail of each article.
+    #[tool(
+        description = "Search recent news articles using Brave Search API with caching and optional domain filtering."
+    )]
+    async fn web_news_search(&self, params: Parameters<WebNewsSearchParams>) -> Result<CallToolResult, McpError> {
+        with_deadline(
+            "web_news_search",
+            self.config.tool_timeout_ms.web_search,
+            news_search_impl(&self.cache, &self.config, self.search.as_deref(), params.0),
+        )
+        .await
+    }
+
+    /// Search for images using Brave Image Search API.
+    ///
+    /// Returns image URLs with their dimensions, thumbnails, and source pages,
+    /// through web_search's Brave client and cache.
+    #[tool(description = "Search for images using Brave Search API. Returns image URLs, dimensions, and source pages.")]
+    async fn web_image_search(&self, params: Parameters<WebImageSearchParams>) -> Result<CallToolResult, McpError> {
+        with_deadline(
+            "web_image_search",
+            self.config.tool_timeout_ms.web_search,
+            image_search_impl(&self.cache, &self.config, self.search.as_deref(), params.0),
+        )
+        .await
+    }
+
+    /// Suggest completions for a vague query using Brave Suggest API.
+    ///
+    /// Cheaper than a full search; cached for 15 minutes so refinement loops
+    /// don't spend quota.
+    #[tool(description = "Get Brave's suggested completions for a partial or vague query before searching.")]
+    async fn web_suggest(&self, params: Parameters<WebSuggestParams>) -> Result<CallToolResult, McpError> {
+        with_deadline(
+            "web_suggest",
+            self.config.tool_timeout_ms.web_search,
+            suggest_impl(&self.cache, &self.config, self.brave.as_ref(), params.0),
+        )
+        .await
     }
 
     /// Retrieve a cached snapshot by hash.
@@ -92,7 +379,52 @@ impl McpWebServer {
     /// Returns the full cached document including metadata and extracted content.
     #[tool(description = "Retrieve a cached snapshot by its content hash.")]
     async fn cache_get(&self, params: Parameters<CacheGetParams>) -> Result<CallToolResult, McpError> {
-        get_impl(&self.cache, params.0).await
+        get_impl(&self.cache, &self.config, params.0).await
+    }
+
+    /// List cached snapshots.
+    ///
+    /// Returns metadata only (hash, URL, mode, title, timestamps), newest first.
+    #[tool(description = "List cached snapshots (metadata only), optionally filtered by URL.")]
+    async fn cache_list(&self, params: Parameters<CacheListParams>) -> Result<CallToolResult, McpError> {
+        list_impl(&self.cache, &self.config, params.0).await
+    }
+
+    /// Prefetch a reading list into the cache in the background.
+    ///
+    /// Returns immediately with a job id; URLs are fetched one at a time with
+    /// per-host spacing and stored as ordinary snapshots.
+    #[tool(description = "Prefetch URLs into the cache in the background. Returns a job id immediately.")]
+    async fn cache_warm(&self, params: Parameters<CacheWarmParams>) -> Result<CallToolResult, McpError> {
+        warm_impl(
+            &self.cache,
+            self.config.clone(),
+            &self.fetch,
+            &self.siteconfigs,
+            self.exporter.as_ref(),
+            &self.jobs,
+            params.0,
+        )
+        .await
+    }
+
+    /// Pin or unpin snapshots by hash or URL.
+    ///
+    /// Pinned snapshots are skipped by every purge strategy unless
+    /// cache_purge is called with include_pinned.
+    #[tool(description = "Pin or unpin cached snapshots by hash or URL to protect them from purges.")]
+    async fn cache_pin(&self, params: Parameters<CachePinParams>) -> Result<CallToolResult, McpError> {
+        pin_impl(&self.cache, &self.config, params.0).await
+    }
+
+    /// Report cache statistics.
+    ///
+    /// Returns snapshot, pinned, expired, and search cache entry counts, plus Brave API usage.
+    #[tool(
+        description = "Report cache statistics: snapshot, pinned, expired, and search entry counts, plus this month's Brave API usage and estimated spend."
+    )]
+    async fn cache_stats(&self) -> Result<CallToolResult, McpError> {
+        stats_impl(&self.cache, &self.config).await
     }
 
     /// Purge cache entries by age, domain, or count.
@@ -101,9 +433,24 @@ impl McpWebServer {
     /// - older_than_days: Delete entries older than N days
     /// - domain: Delete entries matching a domain pattern
     /// - max_entries: Keep only the newest N entries (LRU)
-    #[tool(description = "Purge cache entries by age, domain, or count.")]
+    ///
+    /// Pinned snapshots are kept unless include_pinned is set.
+    #[tool(
+        description = "Purge cache entries by age, domain, or count. Pinned entries are kept unless include_pinned."
+    )]
     async fn cache_purge(&self, params: Parameters<CachePurgeParams>) -> Result<CallToolResult, McpError> {
-        purge_impl(&self.cache, params.0).await
+        purge_impl(&self.cache, &self.config, params.0).await
+    }
+
+    /// Check the cache, network, Brave key, and renderer.
+    ///
+    /// Returns a pass/warn/fail report per component with remediation hints.
+    /// The Brave check spends one API request when a key is configured.
+    #[tool(
+        description = "Self-check the server's dependencies (cache DB, network, Brave API key, renderer). Returns pass/warn/fail per component with fix hints."
+    )]
+    async fn server_health(&self) -> Result<CallToolResult, McpError> {
+        health_impl(&self.config).await
     }
 }
 
@@ -124,14 +471,138 @@ impl ServerHandler for McpWebServer {
     async fn list_tools(
         &self, _request: Option<PaginatedRequestParam>, _context: RequestContext<RoleServer>,
     ) -> Result<ListToolsResult, rmcp::model::ErrorData> {
-        Ok(ListToolsResult { meta: None, tools: self.tool_router.list_all(), next_cursor: None })
+        Ok(ListToolsResult { meta: None, tools: self.tools(), next_cursor: None })
     }
 
     async fn call_tool(
         &self, request: CallToolRequestParam, context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, rmcp::model::ErrorData> {
-        self.tool_router
-            .call(ToolCallContext::new(self, request, context))
+        self.call_tool_request(request, context).await
+    }
+}
+
+/// The fetch client the tools share; building it only fails if TLS can't be initialized.
+fn shared_fetch_client(
+    config: &AppConfig, cache: &CacheDb, circuit: &CircuitBreaker, hosts: &HostState,
+) -> FetchClient {
+    fetch_client(config, cache, circuit)
+        .expect("failed to build HTTP client")
+        .with_host_state(hosts.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::WebExtractOutput;
+    use rmcp::{ServiceExt, model::Content};
+    use serde_json::json;
+
+    const ARTICLE: &str = "<html><head><title>Embedded</title></head><body><article><h1>Embedded</h1>\
+        <p>Tools mounted in another server run the same code paths as the mcp-web binary, \
+        with the same cache, configuration, and deadlines, so embedding them changes nothing \
+        about how pages are extracted or what the caller receives back.</p>\
+        <p>A second paragraph gives the readability scorer enough text, with commas, periods, \
+        and ordinary sentences, to pick this article over the surrounding page chrome.</p>\
+        </article></body></html>";
+
+    /// A server with a tool of its own that mounts the mcp-web tools.
+    #[derive(Clone)]
+    struct HostServer {
+        web: McpWebServer,
+        tool_router: ToolRouter<Self>,
+    }
+
+    #[tool_router]
+    impl HostServer {
+        #[tool(description = "Reply with pong.")]
+        async fn ping(&self) -> Result<CallToolResult, McpError> {
+            Ok(CallToolResult::success(vec![Content::text("pong")]))
+        }
+    }
+
+    impl ServerHandler for HostServer {
+        fn get_info(&self) -> ServerInfo {
+            ServerInfo { capabilities: ServerCapabilities::builder().enable_tools().build(), ..Default::default() }
+        }
+
+        async fn list_tools(
+            &self, _request: Option<PaginatedRequestParam>, _context: RequestContext<RoleServer>,
+        ) -> Result<ListToolsResult, McpError> {
+            let mut tools = self.tool_router.list_all();
+            tools.extend(self.web.tools());
+            Ok(ListToolsResult { meta: None, tools, next_cursor: None })
+        }
+
+        async fn call_tool(
+            &self, request: CallToolRequestParam, context: RequestContext<RoleServer>,
+        ) -> Result<CallToolResult, McpError> {
+            if self.web.has_tool(&request.name) {
+                return self.web.call_tool_request(request, context).await;
+            }
+            self.tool_router
+                .call(ToolCallContext::new(self, request, context))
+                .await
+        }
+    }
+
+    fn request(name: &str, arguments: serde_json::Value) -> CallToolRequestParam {
+        serde_json::from_value(json!({ "name": name, "arguments": arguments })).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tools_mount_in_another_handler() {
+        let cache = CacheDb::open_in_memory().await.unwrap();
+        let host = HostServer {
+            web: McpWebServer::with_cache(AppConfig::default(), cache),
+            tool_router: HostServer::tool_router(),
+        };
+
+        let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+        let (server, client) = tokio::join!(host.serve(server_io), ().serve(client_io));
+        let (_server, client) = (server.unwrap(), client.unwrap());
+
+        let names: Vec<String> = client
+            .list_all_tools()
             .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name.to_string())
+            .collect();
+        assert!(names.contains(&"ping".to_string()));
+        assert!(names.contains(&"web_extract".to_string()));
+        assert!(names.contains(&"web_open".to_string()));
+
+        let args = json!({ "html": ARTICLE, "base_url": "https://example.com/", "config": { "min_score": 15.0 } });
+        let result = client.call_tool(request("web_extract", args)).await.unwrap();
+        assert!(!result.is_error.unwrap_or(false));
+        let output: WebExtractOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(output.strategy_used, "readability");
+        assert!(output.quality.word_count > 0);
+
+        let pong = client.call_tool(request("ping", json!({}))).await.unwrap();
+        assert_eq!(pong.content[0].as_text().unwrap().text, "pong");
+
+        client.cancel().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_brave_client_built_once_when_key_configured() {
+        let cache = CacheDb::open_in_memory().await.unwrap();
+        let server = McpWebServer::with_cache(AppConfig::default(), cache.clone());
+        assert!(server.brave.is_none());
+
+        let config = AppConfig { brave_api_key: Some("test-key".into()), ..Default::default() };
+        let server = McpWebServer::with_cache(config, cache.clone());
+        assert!(server.brave.is_some());
+        assert_eq!(server.search.as_ref().map(|s| s.provider_name()), Some("brave"));
+
+        let config = AppConfig {
+            search_provider: "searxng".into(),
+            searxng_base_url: Some("http://localhost:8888".into()),
+            ..Default::default()
+        };
+        let server = McpWebServer::with_cache(config, cache);
+        assert!(server.brave.is_none());
+        assert_eq!(server.search.as_ref().map(|s| s.provider_name()), Some("searxng"));
     }
 }
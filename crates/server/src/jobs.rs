@@ -0,0 +1,107 @@
+//! Background job runner owned by the server handler.
+//!
+//! Jobs are detached from the tool call that queued them and run one at a
+//! time, so low-priority work such as cache warming never competes with
+//! interactive calls for more than a single fetch slot.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+
+/// Runs queued background jobs sequentially.
+#[derive(Clone)]
+pub struct JobRunner {
+    next_id: Arc<AtomicU64>,
+    slot: Arc<Semaphore>,
+    handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
+
+impl Default for JobRunner {
+    fn default() -> Self {
+        Self { next_id: Arc::new(AtomicU64::new(1)), slot: Arc::new(Semaphore::new(1)), handles: Default::default() }
+    }
+}
+
+impl JobRunner {
+    /// Queue a job and return its id (e.g. `warm-3`) without waiting for it to run.
+    pub fn spawn<F>(&self, kind: &str, job: F) -> String
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let job_id = format!("{kind}-{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        let slot = self.slot.clone();
+        let id = job_id.clone();
+
+        let handle = tokio::spawn(async move {
+            let Ok(_permit) = slot.acquire_owned().await else {
+                return;
+            };
+            tracing::info!(job_id = %id, "job started");
+            job.await;
+            tracing::info!(job_id = %id, "job finished");
+        });
+
+        let mut handles = self.handles.lock().unwrap_or_else(|p| p.into_inner());
+        handles.retain(|h| !h.is_finished());
+        handles.push(handle);
+
+        job_id
+    }
+
+    /// Number of jobs queued or running.
+    #[cfg(test)]
+    pub fn pending(&self) -> usize {
+        let handles = self.handles.lock().unwrap_or_else(|p| p.into_inner());
+        handles.iter().filter(|h| !h.is_finished()).count()
+    }
+
+    /// Wait for every job queued so far to finish.
+    #[cfg(test)]
+    pub async fn wait_idle(&self) {
+        let handles = std::mem::take(&mut *self.handles.lock().unwrap_or_else(|p| p.into_inner()));
+        for handle in handles {
+            let _ = handle.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_jobs_run_sequentially() {
+        let runner = JobRunner::default();
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        for n in 0..3 {
+            let log = log.clone();
+            runner.spawn("test", async move {
+                log.lock().unwrap().push(format!("start {n}"));
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                log.lock().unwrap().push(format!("end {n}"));
+            });
+        }
+
+        runner.wait_idle().await;
+        assert_eq!(runner.pending(), 0);
+
+        let log = log.lock().unwrap();
+        assert_eq!(log.len(), 6);
+        for pair in log.chunks(2) {
+            assert_eq!(pair[0].replace("start", "end"), pair[1]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_job_ids_are_unique() {
+        let runner = JobRunner::default();
+        let a = runner.spawn("warm", async {});
+        let b = runner.spawn("warm", async {});
+        assert_ne!(a, b);
+        assert!(a.starts_with("warm-"));
+    }
+}
@@ -1,14 +1,56 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+//! mcp-web tools as a library.
+//!
+//! The `mcp-web` binary is a thin wrapper around [`McpWebServer`]. Projects
+//! with their own rmcp server can embed the same tools instead of spawning the
+//! binary: build a handler from a config (and, optionally, a cache, fetch
+//! client, or Brave client they already own), then forward tool calls to it.
+//!
+//! ```no_run
+//! use rmcp::{
+//!     ErrorData as McpError, ServerHandler,
+//!     model::{
+//!         CallToolRequestParam, CallToolResult, ListToolsResult, PaginatedRequestParam, ServerCapabilities, ServerInfo,
+//!     },
+//!     service::{RequestContext, RoleServer},
+//! };
+//! use thndrs_core::{AppConfig, CacheDb};
+//! use thndrs_server::McpWebServer;
+//!
+//! #[derive(Clone)]
+//! struct MyServer {
+//!     web: McpWebServer,
+//! }
+//!
+//! impl ServerHandler for MyServer {
+//!     fn get_info(&self) -> ServerInfo {
+//!         ServerInfo { capabilities: ServerCapabilities::builder().enable_tools().build(), ..Default::default() }
+//!     }
+//!
+//!     async fn list_tools(
+//!         &self, _request: Option<PaginatedRequestParam>, _context: RequestContext<RoleServer>,
+//!     ) -> Result<ListToolsResult, McpError> {
+//!         Ok(ListToolsResult { meta: None, tools: self.web.tools(), next_cursor: None })
+//!     }
+//!
+//!     async fn call_tool(
+//!         &self, request: CallToolRequestParam, context: RequestContext<RoleServer>,
+//!     ) -> Result<CallToolResult, McpError> {
+//!         self.web.call_tool_request(request, context).await
+//!     }
+//! }
+//!
+//! # async fn build() -> anyhow::Result<MyServer> {
+//! let config = AppConfig::load()?;
+//! let cache = CacheDb::open(&config.db_path).await?;
+//! Ok(MyServer { web: McpWebServer::with_cache(config, cache) })
+//! # }
+//! ```
+//!
+//! A handler with tools of its own checks [`McpWebServer::has_tool`] first and
+//! routes everything else to its own router.
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub mod handler;
+pub mod jobs;
+pub mod tools;
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub use handler::McpWebServer;
@@ -1,17 +1,19 @@
 //! mcp-web server entry point.
 //!
-//! This is the main binary that boots the MCP server on stdio transport.
+//! This is the main binary that boots the MCP server on stdio transport. The
+//! handler and tools live in the `thndrs_server` library.
 //! Logging goes to stderr to avoid interfering with the JSON-RPC protocol on stdout.
+//!
+//! `mcp-web --check` runs the self-check instead, prints the report as JSON,
+//! and exits non-zero if a critical check failed.
 
 use anyhow::Result;
 use rmcp::service::serve_server;
 use rmcp::transport::io::stdio;
 use thndrs_core::AppConfig;
+use thndrs_server::{McpWebServer, tools};
 use tracing_subscriber::EnvFilter;
 
-mod handler;
-mod tools;
-
 #[tokio::main]
 async fn main() -> Result<()> {
     tracing_subscriber::fmt()
@@ -28,13 +30,20 @@ async fn main() -> Result<()> {
         "Configuration loaded"
     );
 
+    if std::env::args().skip(1).any(|arg| arg == "--check") {
+        let report = tools::health::run_checks(&config).await;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        std::process::exit(if report.healthy { 0 } else { 1 });
+    }
+
     tracing::info!("Starting mcp-web server on stdio transport");
 
-    let handler = handler::McpWebServer::new(config).await?;
+    let handler = McpWebServer::new(config).await?;
     let transport = stdio();
-    let server = serve_server(handler, transport).await?;
+    let server = serve_server(handler.clone(), transport).await?;
 
     server.waiting().await?;
+    handler.shutdown().await;
 
     Ok(())
 }
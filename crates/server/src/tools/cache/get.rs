@@ -1,54 +1,128 @@
 //! cache_get tool implementation.
 //!
-//! Retrieves a cached snapshot by hash.
+//! Retrieves a cached snapshot by hash. Raw bodies stored alongside readable
+//! extractions are omitted unless `include_raw` is set. Stored links are
+//! returned parsed, in any format version the cache has written, as are the
+//! page metadata, heading outline, and content-quality signals.
 
-use rmcp::{
-    ErrorData as McpError,
-    model::{CallToolResult, Content},
-};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use thndrs_core::{CacheDb, Error, Snapshot};
+use thndrs_core::{
+    AppConfig, CacheDb, Error, Mode, Snapshot,
+    cache::{ContentQuality, FeedLink, Heading, Link, PageMetadata},
+};
+
+use crate::tools::output::json_result;
 
 /// Parameters for the cache_get tool.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CacheGetParams {
     /// The hash of the cached snapshot to retrieve.
     pub hash: String,
+
+    /// Include the stored raw body of a readable/rendered snapshot (raw-mode snapshots always include it).
+    #[serde(default)]
+    pub include_raw: bool,
 }
 
 /// Output from the cache_get tool.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CacheGetOutput {
-    /// The cached snapshot.
+    /// The cached snapshot; `links_json`, `metadata_json`, `outline_json`, and `quality_json`
+    /// are cleared once parsed.
     pub snapshot: Snapshot,
+    /// The snapshot's harvested links.
+    #[serde(default)]
+    pub links: Vec<Link>,
+    /// Feeds the snapshot's page advertised.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub feeds: Vec<FeedLink>,
+    /// `links_json` could not be parsed; it is left on the snapshot as stored.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub links_parse_error: bool,
+    /// Metadata the snapshot's page declared; omitted when none.
+    #[serde(default, skip_serializing_if = "PageMetadata::is_empty")]
+    pub metadata: PageMetadata,
+    /// Headings of the snapshot's content; omitted when none were stored.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outline: Vec<Heading>,
+    /// Word count, reading time, paragraph count, and link density of the
+    /// snapshot's content; omitted when none were stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<ContentQuality>,
 }
 
 /// Implementation of the cache_get tool.
-pub async fn get_impl(cache: &CacheDb, params: CacheGetParams) -> Result<CallToolResult, McpError> {
-    let snapshot = cache
+pub async fn get_impl(cache: &CacheDb, config: &AppConfig, params: CacheGetParams) -> Result<CallToolResult, McpError> {
+    let mut snapshot = cache
         .get_snapshot(&params.hash)
         .await?
         .ok_or_else(|| Error::CacheMiss(params.hash.clone()))?;
 
-    let output = CacheGetOutput { snapshot };
-    let json = serde_json::to_string_pretty(&output)
-        .map_err(|e| Error::InvalidInput(format!("Failed to serialize snapshot: {e}")))?;
+    if !params.include_raw && snapshot.mode != Mode::Raw {
+        snapshot.raw_bytes = None;
+    }
+
+    let (links, feeds, links_parse_error) = match snapshot.links() {
+        Ok(links) => {
+            let feeds = snapshot.feeds().unwrap_or_default();
+            snapshot.links_json = None;
+            (links, feeds, false)
+        }
+        Err(e) => {
+            tracing::warn!("unreadable links_json in snapshot {}: {}", snapshot.hash, e);
+            (Vec::new(), Vec::new(), true)
+        }
+    };
+
+    let metadata = match snapshot.metadata() {
+        Ok(metadata) => {
+            snapshot.metadata_json = None;
+            metadata
+        }
+        Err(e) => {
+            tracing::warn!("unreadable metadata_json in snapshot {}: {}", snapshot.hash, e);
+            PageMetadata::default()
+        }
+    };
 
-    Ok(CallToolResult::success(vec![Content::text(json)]))
+    let outline = match snapshot.outline() {
+        Ok(outline) => {
+            snapshot.outline_json = None;
+            outline
+        }
+        Err(e) => {
+            tracing::warn!("unreadable outline_json in snapshot {}: {}", snapshot.hash, e);
+            Vec::new()
+        }
+    };
+
+    let quality = match snapshot.quality() {
+        Ok(quality) => {
+            snapshot.quality_json = None;
+            quality
+        }
+        Err(e) => {
+            tracing::warn!("unreadable quality_json in snapshot {}: {}", snapshot.hash, e);
+            None
+        }
+    };
+
+    let output = CacheGetOutput { snapshot, links, feeds, links_parse_error, metadata, outline, quality };
+    json_result(&output, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use thndrs_core::cache::hash::compute_cache_key;
 
     #[tokio::test]
     async fn test_get_impl_missing() {
         let cache = CacheDb::open_in_memory().await.unwrap();
-        let params = CacheGetParams { hash: "nonexistent".to_string() };
+        let params = CacheGetParams { hash: "nonexistent".to_string(), include_raw: false };
 
-        let result = get_impl(&cache, params).await;
+        let result = get_impl(&cache, &AppConfig::default(), params).await;
         assert!(result.is_err());
     }
 
@@ -56,37 +130,94 @@ mod tests {
     async fn test_get_impl_found() {
         let cache = CacheDb::open_in_memory().await.unwrap();
 
-        let hash = compute_cache_key("https://example.com", "", "readable");
         let snapshot = Snapshot {
-            hash: hash.clone(),
-            url: "https://example.com".to_string(),
-            final_url: "https://example.com".to_string(),
-            mode: "readable".to_string(),
-            content_type: Some("text/html".to_string()),
-            status_code: Some(200),
-            fetched_at: chrono::Utc::now().to_rfc3339(),
-            expires_at: None,
-            etag: None,
-            last_modified: None,
-            raw_bytes: None,
-            raw_truncated: false,
-            title: Some("Test".to_string()),
-            markdown: Some("# Test".to_string()),
-            text: Some("Test".to_string()),
-            links_json: None,
-            extractor_name: Some("lectito-core".to_string()),
-            extractor_version: Some("0.1.0".to_string()),
-            siteconfig_id: None,
-            extract_cfg_json: None,
-            headers_json: None,
-            fetch_ms: Some(100),
-            extract_ms: Some(50),
+            metadata_json: Some(r#"{"canonical_url":"https://example.com/"}"#.to_string()),
+            outline_json: Some(r#"[{"level":2,"text":"Usage","id":"usage"}]"#.to_string()),
+            quality_json: Some(
+                concat!(
+                    r#"{"word_count":2,"estimated_reading_minutes":1,"paragraph_count":0,"#,
+                    r#""link_density":0.0,"likely_boilerplate":false}"#
+                )
+                .to_string(),
+            ),
+            ..Snapshot::test_page("https://example.com")
         };
+        let hash = snapshot.hash.clone();
 
         cache.upsert_snapshot(&snapshot).await.unwrap();
 
-        let params = CacheGetParams { hash };
-        let result = get_impl(&cache, params).await;
-        assert!(result.is_ok());
+        let params = CacheGetParams { hash, include_raw: false };
+        let result = get_impl(&cache, &AppConfig::default(), params).await.unwrap();
+        let output: CacheGetOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(output.metadata.canonical_url.as_deref(), Some("https://example.com/"));
+        assert!(output.snapshot.metadata_json.is_none());
+        assert_eq!(
+            output.outline,
+            [Heading { level: 2, text: "Usage".into(), id: Some("usage".into()) }]
+        );
+        assert!(output.snapshot.outline_json.is_none());
+        assert_eq!(output.quality.map(|q| q.word_count), Some(2));
+        assert!(output.snapshot.quality_json.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_impl_excludes_stored_raw_by_default() {
+        let cache = CacheDb::open_in_memory().await.unwrap();
+        let snapshot = Snapshot {
+            raw_bytes: Some(b"<html>body</html>".to_vec()),
+            markdown: Some("# Body".to_string()),
+            ..Snapshot::test_page("https://example.com/raw")
+        };
+        let hash = snapshot.hash.clone();
+        cache.upsert_snapshot(&snapshot).await.unwrap();
+
+        let get = |include_raw| {
+            get_impl(
+                &cache,
+                &AppConfig::default(),
+                CacheGetParams { hash: hash.clone(), include_raw },
+            )
+        };
+        let text = get(false).await.unwrap().content[0].as_text().unwrap().text.clone();
+        let output: CacheGetOutput = serde_json::from_str(&text).unwrap();
+        assert!(output.snapshot.raw_bytes.is_none());
+        assert_eq!(output.snapshot.markdown.as_deref(), Some("# Body"));
+
+        let text = get(true).await.unwrap().content[0].as_text().unwrap().text.clone();
+        let output: CacheGetOutput = serde_json::from_str(&text).unwrap();
+        assert_eq!(output.snapshot.raw_bytes.as_deref(), Some(&b"<html>body</html>"[..]));
+    }
+
+    #[tokio::test]
+    async fn test_get_impl_parses_stored_links() {
+        let cache = CacheDb::open_in_memory().await.unwrap();
+        let mut snapshot = Snapshot::test_page("https://example.com/links");
+        let hash = snapshot.hash.clone();
+        let (cache, hash) = (&cache, &hash);
+        let get = move || async move {
+            let params = CacheGetParams { hash: hash.clone(), include_raw: false };
+            let result = get_impl(cache, &AppConfig::default(), params).await.unwrap();
+            serde_json::from_str::<CacheGetOutput>(&result.content[0].as_text().unwrap().text).unwrap()
+        };
+        let docs = Link { text: "Docs".into(), href: "https://example.com/docs".into(), ..Default::default() };
+
+        for json in [
+            r#"[{"text":"Docs","href":"https://example.com/docs"}]"#,
+            r#"{"version":1,"links":[{"text":"Docs","href":"https://example.com/docs"}]}"#,
+        ] {
+            snapshot.links_json = Some(json.to_string());
+            cache.upsert_snapshot(&snapshot).await.unwrap();
+            let output = get().await;
+            assert_eq!(output.links, vec![docs.clone()], "{json}");
+            assert!(output.snapshot.links_json.is_none());
+            assert!(!output.links_parse_error);
+        }
+
+        snapshot.links_json = Some("not json".to_string());
+        cache.upsert_snapshot(&snapshot).await.unwrap();
+        let output = get().await;
+        assert!(output.links.is_empty());
+        assert!(output.links_parse_error);
+        assert_eq!(output.snapshot.links_json.as_deref(), Some("not json"));
     }
 }
@@ -0,0 +1,83 @@
+//! cache_list tool implementation.
+//!
+//! Lists cached snapshot metadata, optionally filtered by URL.
+
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thndrs_client::fetch::canonicalize_with_options;
+use thndrs_core::{AppConfig, CacheDb, Error, cache::SnapshotSummary};
+
+use crate::tools::output::json_result;
+use crate::tools::web_open::canonicalize_options;
+
+/// Parameters for the cache_list tool.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct CacheListParams {
+    /// Only list snapshots for these URLs (default: all).
+    #[serde(default)]
+    pub urls: Vec<String>,
+
+    /// Maximum number of entries to return (default: 50, max: 500).
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Output from the cache_list tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CacheListOutput {
+    /// Matching snapshots, newest first.
+    pub entries: Vec<SnapshotSummary>,
+}
+
+/// Implementation of the cache_list tool.
+pub async fn list_impl(
+    cache: &CacheDb, config: &AppConfig, params: CacheListParams,
+) -> Result<CallToolResult, McpError> {
+    let urls = params
+        .urls
+        .iter()
+        .map(|u| canonicalize_with_options(u, &canonicalize_options(config)).map(|u| u.to_string()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| Error::InvalidUrl(e.to_string()))?;
+    let limit = params.limit.unwrap_or(50).min(500);
+
+    let entries = cache.list_snapshots(&urls, limit).await?;
+
+    let output = CacheListOutput { entries };
+    json_result(&output, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use thndrs_core::Snapshot;
+
+    #[tokio::test]
+    async fn test_list_impl_filters_by_canonical_url() {
+        let cache = CacheDb::open_in_memory().await.unwrap();
+        cache
+            .upsert_snapshot(&Snapshot::test_page("https://example.com/a"))
+            .await
+            .unwrap();
+        cache
+            .upsert_snapshot(&Snapshot::test_page("https://example.com/b"))
+            .await
+            .unwrap();
+
+        let params = CacheListParams { urls: vec!["EXAMPLE.com/a#frag".into()], limit: None };
+        let result = list_impl(&cache, &AppConfig::default(), params).await.unwrap();
+        let text = result.content.first().unwrap().as_text().unwrap().text.clone();
+        let output: CacheListOutput = serde_json::from_str(&text).unwrap();
+
+        assert_eq!(output.entries.len(), 1);
+        assert_eq!(output.entries[0].url, "https://example.com/a");
+    }
+
+    #[tokio::test]
+    async fn test_list_impl_invalid_url() {
+        let cache = CacheDb::open_in_memory().await.unwrap();
+        let params = CacheListParams { urls: vec!["ftp://example.com".into()], limit: None };
+        assert!(list_impl(&cache, &AppConfig::default(), params).await.is_err());
+    }
+}
@@ -3,7 +3,15 @@
 //! This module provides tools for interacting with the SQLite cache.
 
 pub mod get;
+pub mod list;
+pub mod pin;
 pub mod purge;
+pub mod stats;
+pub mod warm;
 
 pub use get::{CacheGetParams, get_impl};
+pub use list::{CacheListParams, list_impl};
+pub use pin::{CachePinParams, pin_impl};
 pub use purge::{CachePurgeParams, purge_impl};
+pub use stats::stats_impl;
+pub use warm::{CacheWarmParams, warm_impl};
@@ -0,0 +1,101 @@
+//! cache_pin tool implementation.
+//!
+//! Pins or unpins snapshots so purges leave them alone.
+
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thndrs_client::fetch::canonicalize_with_options;
+use thndrs_core::{AppConfig, CacheDb, Error};
+
+use crate::tools::output::json_result;
+use crate::tools::web_open::canonicalize_options;
+
+/// Parameters for the cache_pin tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CachePinParams {
+    /// Hash of the snapshot to pin.
+    #[serde(default)]
+    pub hash: Option<String>,
+
+    /// URL whose snapshots (all modes) to pin.
+    #[serde(default)]
+    pub url: Option<String>,
+
+    /// Pin (true, default) or unpin (false).
+    #[serde(default = "default_true")]
+    pub pinned: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Output from the cache_pin tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CachePinOutput {
+    /// Number of snapshots updated.
+    pub updated: u64,
+    /// Resulting pin state.
+    pub pinned: bool,
+}
+
+/// Implementation of the cache_pin tool.
+pub async fn pin_impl(cache: &CacheDb, config: &AppConfig, params: CachePinParams) -> Result<CallToolResult, McpError> {
+    let updated = match (&params.hash, &params.url) {
+        (Some(hash), None) => {
+            if !cache.set_snapshot_pinned(hash, params.pinned).await? {
+                return Err(Error::CacheMiss(hash.clone()).into());
+            }
+            1
+        }
+        (None, Some(url)) => {
+            let url = canonicalize_with_options(url, &canonicalize_options(config))
+                .map_err(|e| Error::InvalidUrl(e.to_string()))?;
+            let updated = cache.set_snapshots_pinned_by_url(url.as_str(), params.pinned).await?;
+            if updated == 0 {
+                return Err(Error::CacheMiss(url.to_string()).into());
+            }
+            updated
+        }
+        _ => return Err(Error::InvalidInput("exactly one of hash or url must be specified".to_string()).into()),
+    };
+
+    let output = CachePinOutput { updated, pinned: params.pinned };
+    json_result(&output, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use thndrs_core::Snapshot;
+
+    #[tokio::test]
+    async fn test_pin_by_url_and_unpin_by_hash() {
+        let cache = CacheDb::open_in_memory().await.unwrap();
+        let snapshot = Snapshot::test_page("https://example.com/page");
+        cache.upsert_snapshot(&snapshot).await.unwrap();
+
+        let params = CachePinParams { hash: None, url: Some("EXAMPLE.com/page".into()), pinned: true };
+        pin_impl(&cache, &AppConfig::default(), params).await.unwrap();
+        assert_eq!(cache.cache_stats().await.unwrap().pinned, 1);
+
+        let params = CachePinParams { hash: Some(snapshot.hash.clone()), url: None, pinned: false };
+        pin_impl(&cache, &AppConfig::default(), params).await.unwrap();
+        assert_eq!(cache.cache_stats().await.unwrap().pinned, 0);
+    }
+
+    #[tokio::test]
+    async fn test_pin_requires_exactly_one_target() {
+        let cache = CacheDb::open_in_memory().await.unwrap();
+
+        let neither = CachePinParams { hash: None, url: None, pinned: true };
+        assert!(pin_impl(&cache, &AppConfig::default(), neither).await.is_err());
+
+        let both = CachePinParams { hash: Some("abc".into()), url: Some("https://example.com".into()), pinned: true };
+        assert!(pin_impl(&cache, &AppConfig::default(), both).await.is_err());
+
+        let missing = CachePinParams { hash: Some("abc".into()), url: None, pinned: true };
+        assert!(pin_impl(&cache, &AppConfig::default(), missing).await.is_err());
+    }
+}
@@ -1,37 +1,55 @@
 //! cache_purge tool implementation.
 //!
-//! Purges cache entries by age, domain, or count.
+//! Purges cache entries by age, domain, or count. Age applies to snapshots and
+//! cached searches; domain and count apply to snapshots only.
 
-use rmcp::{
-    ErrorData as McpError,
-    model::{CallToolResult, Content},
-};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use thndrs_core::{CacheDb, Error};
+use thndrs_core::{AppConfig, CacheDb, Error};
+
+use crate::tools::output::json_result;
 
 /// Parameters for the cache_purge tool.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CachePurgeParams {
-    /// Purge entries older than this many days.
+    /// Purge snapshots and cached searches fetched more than this many days ago.
     pub older_than_days: Option<i64>,
 
-    /// Purge entries matching this domain pattern.
+    /// Purge snapshots whose requested or final host is this domain.
     pub domain: Option<String>,
 
+    /// With `domain`, also purge its subdomains (default: true).
+    #[serde(default = "default_true")]
+    pub include_subdomains: bool,
+
     /// Keep only the newest N entries (LRU purge).
     pub max_entries: Option<usize>,
+
+    /// Also delete pinned snapshots (default: false).
+    #[serde(default)]
+    pub include_pinned: bool,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 /// Output from the cache_purge tool.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CachePurgeOutput {
-    /// Number of entries deleted.
+    /// Number of entries deleted across all tables.
     pub deleted: u64,
+    /// Snapshots deleted.
+    pub snapshots_deleted: u64,
+    /// Cached search responses deleted.
+    pub search_deleted: u64,
 }
 
 /// Implementation of the cache_purge tool.
-pub async fn purge_impl(cache: &CacheDb, params: CachePurgeParams) -> Result<CallToolResult, McpError> {
+pub async fn purge_impl(
+    cache: &CacheDb, config: &AppConfig, params: CachePurgeParams,
+) -> Result<CallToolResult, McpError> {
     if params.older_than_days.is_none() && params.domain.is_none() && params.max_entries.is_none() {
         return Err(Error::InvalidInput(
             "At least one of older_than_days, domain, or max_entries must be specified".to_string(),
@@ -39,80 +57,58 @@ pub async fn purge_impl(cache: &CacheDb, params: CachePurgeParams) -> Result<Cal
         .into());
     }
 
-    let mut deleted_total = 0u64;
+    if params.older_than_days.is_some_and(|days| days < 0) {
+        return Err(Error::InvalidInput("older_than_days must not be negative".to_string()).into());
+    }
 
-    if let Some(_days) = params.older_than_days {
-        let deleted = cache.purge_expired_snapshots().await?;
-        deleted_total += deleted;
+    let mut snapshots_deleted = 0u64;
+    let mut search_deleted = 0u64;
+
+    if let Some(days) = params.older_than_days {
+        snapshots_deleted += cache.purge_snapshots_older_than(days, params.include_pinned).await?;
+        search_deleted += cache.purge_search_older_than(days).await?;
     }
 
     if let Some(domain) = params.domain {
-        let deleted = cache.purge_snapshots_by_domain(&domain).await?;
-        deleted_total += deleted;
+        snapshots_deleted += cache
+            .purge_snapshots_by_domain(&domain, params.include_subdomains, params.include_pinned)
+            .await?;
     }
 
     if let Some(max_entries) = params.max_entries {
-        let deleted = cache.purge_lru_snapshots(max_entries).await?;
-        deleted_total += deleted;
+        snapshots_deleted += cache.purge_lru_snapshots(max_entries, params.include_pinned).await?;
     }
 
-    let output = CachePurgeOutput { deleted: deleted_total };
-    let json = serde_json::to_string_pretty(&output)
-        .map_err(|e| Error::InvalidInput(format!("Failed to serialize output: {e}")))?;
-
-    Ok(CallToolResult::success(vec![Content::text(json)]))
+    let output = CachePurgeOutput { deleted: snapshots_deleted + search_deleted, snapshots_deleted, search_deleted };
+    json_result(&output, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use thndrs_core::{Snapshot, cache::hash::compute_cache_key};
-
-    fn make_test_snapshot(url: &str) -> Snapshot {
-        let hash = compute_cache_key(url, "", "readable");
-        Snapshot {
-            hash,
-            url: url.to_string(),
-            final_url: url.to_string(),
-            mode: "readable".to_string(),
-            content_type: Some("text/html".to_string()),
-            status_code: Some(200),
-            fetched_at: chrono::Utc::now().to_rfc3339(),
-            expires_at: None,
-            etag: None,
-            last_modified: None,
-            raw_bytes: None,
-            raw_truncated: false,
-            title: Some("Test".to_string()),
-            markdown: Some("# Test".to_string()),
-            text: Some("Test".to_string()),
-            links_json: None,
-            extractor_name: Some("lectito-core".to_string()),
-            extractor_version: Some("0.1.0".to_string()),
-            siteconfig_id: None,
-            extract_cfg_json: None,
-            headers_json: None,
-            fetch_ms: Some(100),
-            extract_ms: Some(50),
-        }
-    }
+    use thndrs_core::Snapshot;
 
     #[tokio::test]
     async fn test_purge_by_domain() {
         let cache = CacheDb::open_in_memory().await.unwrap();
         cache
-            .upsert_snapshot(&make_test_snapshot("https://example.com/page1"))
+            .upsert_snapshot(&Snapshot::test_page("https://example.com/page1"))
             .await
             .unwrap();
         cache
-            .upsert_snapshot(&make_test_snapshot("https://other.com/page2"))
+            .upsert_snapshot(&Snapshot::test_page("https://other.com/page2"))
             .await
             .unwrap();
 
-        let params =
-            CachePurgeParams { older_than_days: None, domain: Some("example.com".to_string()), max_entries: None };
+        let params = CachePurgeParams {
+            older_than_days: None,
+            domain: Some("example.com".to_string()),
+            include_subdomains: true,
+            max_entries: None,
+            include_pinned: false,
+        };
 
-        let result = purge_impl(&cache, params).await.unwrap();
+        let result = purge_impl(&cache, &AppConfig::default(), params).await.unwrap();
         let content_val = serde_json::to_value(&result.content[0]).unwrap();
         let text = content_val
             .get("text")
@@ -126,17 +122,23 @@ mod tests {
     async fn test_purge_lru() {
         let cache = CacheDb::open_in_memory().await.unwrap();
         cache
-            .upsert_snapshot(&make_test_snapshot("https://example.com/page1"))
+            .upsert_snapshot(&Snapshot::test_page("https://example.com/page1"))
             .await
             .unwrap();
         cache
-            .upsert_snapshot(&make_test_snapshot("https://example.com/page2"))
+            .upsert_snapshot(&Snapshot::test_page("https://example.com/page2"))
             .await
             .unwrap();
 
-        let params = CachePurgeParams { older_than_days: None, domain: None, max_entries: Some(1) };
+        let params = CachePurgeParams {
+            older_than_days: None,
+            domain: None,
+            include_subdomains: true,
+            max_entries: Some(1),
+            include_pinned: false,
+        };
 
-        let result = purge_impl(&cache, params).await.unwrap();
+        let result = purge_impl(&cache, &AppConfig::default(), params).await.unwrap();
         let content_val = serde_json::to_value(&result.content[0]).unwrap();
         let text = content_val
             .get("text")
@@ -147,11 +149,87 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_purge_no_params() {
+    async fn test_purge_lru_skips_pinned() {
         let cache = CacheDb::open_in_memory().await.unwrap();
-        let params = CachePurgeParams { older_than_days: None, domain: None, max_entries: None };
+        let keep = Snapshot::test_page("https://example.com/keep");
+        cache.upsert_snapshot(&keep).await.unwrap();
+        cache
+            .upsert_snapshot(&Snapshot::test_page("https://example.com/other"))
+            .await
+            .unwrap();
+        cache.set_snapshot_pinned(&keep.hash, true).await.unwrap();
+
+        let params = CachePurgeParams {
+            older_than_days: None,
+            domain: None,
+            include_subdomains: true,
+            max_entries: Some(0),
+            include_pinned: false,
+        };
+        let result = purge_impl(&cache, &AppConfig::default(), params).await.unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        let output: CachePurgeOutput = serde_json::from_str(&text).unwrap();
+        assert_eq!(output.deleted, 1);
+        assert!(cache.get_snapshot(&keep.hash).await.unwrap().is_some());
+
+        let params = CachePurgeParams {
+            older_than_days: None,
+            domain: None,
+            include_subdomains: true,
+            max_entries: Some(0),
+            include_pinned: true,
+        };
+        purge_impl(&cache, &AppConfig::default(), params).await.unwrap();
+        assert!(cache.get_snapshot(&keep.hash).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_purge_older_than_days_uses_fetch_age() {
+        let clock = thndrs_core::ManualClock::default();
+        let cache = CacheDb::open_in_memory().await.unwrap().with_clock(clock.clone());
+        cache.put_search("old-search", "{}", "{}", 3600).await.unwrap();
+        clock.advance(chrono::Duration::days(31));
+        cache.put_search("new-search", "{}", "{}", 3600).await.unwrap();
+
+        let days_ago = |days: i64| (clock.now() - chrono::Duration::days(days)).to_rfc3339();
+        let old = Snapshot { fetched_at: days_ago(45), ..Snapshot::test_page("https://example.com/old") };
+        let new = Snapshot { fetched_at: days_ago(5), ..Snapshot::test_page("https://example.com/new") };
+        cache.upsert_snapshot(&old).await.unwrap();
+        cache.upsert_snapshot(&new).await.unwrap();
+
+        let params = CachePurgeParams {
+            older_than_days: Some(30),
+            domain: None,
+            include_subdomains: true,
+            max_entries: None,
+            include_pinned: false,
+        };
+        let result = purge_impl(&cache, &AppConfig::default(), params.clone()).await.unwrap();
+        let output: CachePurgeOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(
+            (output.snapshots_deleted, output.search_deleted, output.deleted),
+            (1, 1, 2)
+        );
+        assert!(cache.get_snapshot(&new.hash).await.unwrap().is_some());
+        assert!(cache.get_search("new-search").await.unwrap().is_some());
+
+        let negative = CachePurgeParams { older_than_days: Some(-1), ..params };
+        let err = purge_impl(&cache, &AppConfig::default(), negative).await.unwrap_err();
+        assert_eq!(err.code.0, -32602);
+    }
 
-        let result = purge_impl(&cache, params).await;
+    #[tokio::test]
+    async fn test_purge_no_params() {
+        let cache = CacheDb::open_in_memory().await.unwrap();
+        let params = CachePurgeParams {
+            older_than_days: None,
+            domain: None,
+            include_subdomains: true,
+            max_entries: None,
+            include_pinned: false,
+        };
+
+        let result = purge_impl(&cache, &AppConfig::default(), params).await;
         assert!(result.is_err());
     }
 }
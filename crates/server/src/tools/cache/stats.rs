@@ -0,0 +1,68 @@
+//! cache_stats tool implementation.
+//!
+//! Reports aggregate counts for the snapshot and search caches, plus this
+//! month's Brave API usage.
+
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use serde::{Deserialize, Serialize};
+use thndrs_core::{
+    AppConfig, CacheDb,
+    cache::{CacheStats, UsageReport},
+};
+
+use crate::tools::output::json_result;
+
+/// Output structure for cache_stats tool.
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct CacheStatsOutput {
+    /// Snapshot and search cache counts.
+    #[serde(flatten)]
+    pub stats: CacheStats,
+    /// Brave API requests and cache hits this month, with spend estimates when pricing is configured.
+    pub brave_usage: UsageReport,
+}
+
+/// Implementation of the cache_stats tool.
+pub async fn stats_impl(cache: &CacheDb, config: &AppConfig) -> Result<CallToolResult, McpError> {
+    let stats = cache.cache_stats().await?;
+    let brave_usage = cache.brave_usage().await?.report(&config.brave_pricing());
+    let output = CacheStatsOutput { stats, brave_usage };
+    json_result(&output, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stats_impl_empty() {
+        let cache = CacheDb::open_in_memory().await.unwrap();
+        let result = stats_impl(&cache, &AppConfig::default()).await.unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        let stats: CacheStats = serde_json::from_str(&text).unwrap();
+        assert_eq!(stats.snapshots, 0);
+        assert_eq!(stats.pinned, 0);
+    }
+
+    #[tokio::test]
+    async fn test_stats_impl_reports_brave_usage() {
+        let cache = CacheDb::open_in_memory().await.unwrap();
+        for _ in 0..3 {
+            cache.record_brave_request().await.unwrap();
+        }
+        cache.record_brave_cache_hit().await.unwrap();
+
+        let config = AppConfig {
+            brave_cost_per_thousand_requests: Some(4.0),
+            brave_monthly_quota: Some(300),
+            ..Default::default()
+        };
+        let result = stats_impl(&cache, &config).await.unwrap();
+        let output: CacheStatsOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+
+        assert_eq!(output.brave_usage.requests, 3);
+        assert_eq!(output.brave_usage.cache_hits, 1);
+        assert_eq!(output.brave_usage.estimated_cost, Some(0.012));
+        assert_eq!(output.brave_usage.quota_used, Some(0.01));
+    }
+}
@@ -0,0 +1,315 @@
+//! cache_warm tool implementation.
+//!
+//! Queues a reading list for low-priority background fetching so later
+//! web_open calls are served from the cache.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thndrs_client::{Exporter, FetchClient, SiteConfigRegistry, fetch::canonicalize};
+use thndrs_core::{AppConfig, CacheDb, Error, Mode};
+
+use crate::jobs::JobRunner;
+use crate::tools::output::json_result;
+use crate::tools::web_open::{LinkFilter, OpenableMode, WebOpenOutput, WebOpenParams, open_impl};
+
+/// Minimum spacing between warm fetches to the same host.
+const WARM_HOST_SPACING: Duration = Duration::from_secs(2);
+
+/// Parameters for the cache_warm tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CacheWarmParams {
+    /// URLs to prefetch into the cache (at most `batch_max_urls`).
+    pub urls: Vec<String>,
+
+    /// Extraction mode: "readable" (default), "raw", or "rendered".
+    #[serde(default)]
+    #[schemars(with = "Option<OpenableMode>")]
+    pub mode: Option<Mode>,
+
+    /// Time-to-live for warmed snapshots in seconds (default: no expiry).
+    #[serde(default)]
+    pub ttl_seconds: Option<u64>,
+}
+
+/// Output from the cache_warm tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CacheWarmOutput {
+    /// Identifier of the background job.
+    pub job_id: String,
+    /// Number of URLs queued.
+    pub queued: usize,
+    /// Mode the URLs will be fetched in.
+    pub mode: Mode,
+}
+
+/// Result of a completed warm job.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WarmSummary {
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+/// Implementation of the cache_warm tool.
+///
+/// Validates the request, queues the job on `jobs`, and returns immediately.
+pub async fn warm_impl(
+    cache: &CacheDb, config: Arc<AppConfig>, fetch: &Arc<FetchClient>, siteconfigs: &Arc<SiteConfigRegistry>,
+    exporter: Option<&Exporter>, jobs: &JobRunner, params: CacheWarmParams,
+) -> Result<CallToolResult, McpError> {
+    if params.urls.is_empty() {
+        return Err(Error::InvalidInput("urls cannot be empty".into()).into());
+    }
+
+    if params.urls.len() > config.batch_max_urls {
+        return Err(Error::InvalidInput(format!(
+            "too many urls: {} exceeds batch_max_urls ({})",
+            params.urls.len(),
+            config.batch_max_urls
+        ))
+        .into());
+    }
+
+    let mode = params.mode.unwrap_or_default();
+    if mode == Mode::Feed {
+        return Err(Error::InvalidInput("mode=feed is for web_feed; warm feeds in readable mode".into()).into());
+    }
+
+    let queued = params.urls.len();
+    let job = run_warm(
+        cache.clone(),
+        config,
+        fetch.clone(),
+        siteconfigs.clone(),
+        exporter.cloned(),
+        params.urls,
+        mode,
+        params.ttl_seconds,
+        WARM_HOST_SPACING,
+    );
+    let job_id = jobs.spawn("warm", async move {
+        let summary = job.await;
+        tracing::info!(
+            succeeded = summary.succeeded,
+            failed = summary.failed,
+            "cache warm complete"
+        );
+    });
+
+    let output = CacheWarmOutput { job_id, queued, mode };
+    json_result(&output, &config)
+}
+
+/// Fetch each URL sequentially into the cache, spacing requests to the same host.
+///
+/// Already-cached URLs are served from the cache without refetching. Snapshot
+/// content is discarded; only the cache side effect matters.
+pub async fn run_warm(
+    cache: CacheDb, config: Arc<AppConfig>, fetch: Arc<FetchClient>, siteconfigs: Arc<SiteConfigRegistry>,
+    exporter: Option<Exporter>, urls: Vec<String>, mode: Mode, ttl_seconds: Option<u64>, host_spacing: Duration,
+) -> WarmSummary {
+    let total = urls.len();
+    let mut summary = WarmSummary::default();
+    let mut last_fetch: HashMap<String, Instant> = HashMap::new();
+
+    for (i, url) in urls.into_iter().enumerate() {
+        let host = canonicalize(&url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .unwrap_or_default();
+
+        if let Some(last) = last_fetch.get(&host) {
+            let wait = host_spacing.saturating_sub(last.elapsed());
+            if !wait.is_zero() {
+                tokio::time::sleep(wait).await;
+            }
+        }
+
+        let params = WebOpenParams {
+            url: url.clone(),
+            mode,
+            max_bytes: config.max_bytes,
+            force_refresh: false,
+            timeout_ms: config.timeout_ms,
+            accept: None,
+            language: None,
+            headers: None,
+            use_siteconfig: true,
+            siteconfig_id: None,
+            extract: None,
+            debug: false,
+            include_structured_data: false,
+            include_images: false,
+            include_tables: false,
+            links: LinkFilter::All,
+            selector: None,
+            section: None,
+            max_tokens: None,
+            max_chars: None,
+            page: None,
+            allow_any_content_type: false,
+            store_raw: None,
+            user_agent: None,
+            cache_partition: None,
+            wait_for: None,
+            render_timeout_ms: None,
+        };
+
+        // Warm jobs outlive the call, so rendered mode launches its own browser per URL.
+        match open_impl(&cache, &config, &fetch, None, &siteconfigs, exporter.as_ref(), params).await {
+            Ok(result) => {
+                summary.succeeded += 1;
+                if let Some(ttl) = ttl_seconds
+                    && let Some(hash) = output_hash(&result)
+                {
+                    let expires_at = (cache.now() + chrono::Duration::seconds(ttl as i64)).to_rfc3339();
+                    if let Err(e) = cache.set_snapshot_expiry(&hash, Some(expires_at)).await {
+                        tracing::warn!("failed to set expiry for {}: {}", url, e);
+                    }
+                }
+            }
+            Err(e) => {
+                summary.failed += 1;
+                tracing::warn!("cache warm failed for {}: {}", url, e.message);
+            }
+        }
+
+        last_fetch.insert(host, Instant::now());
+        tracing::info!(progress = i + 1, total, url = %url, "cache warm progress");
+    }
+
+    summary
+}
+
+fn output_hash(result: &CallToolResult) -> Option<String> {
+    let text = result.content.first()?.as_text()?.text.clone();
+    serde_json::from_str::<WebOpenOutput>(&text).ok().map(|o| o.hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::web_open::fetch_client;
+    use thndrs_client::CircuitBreaker;
+    use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+    fn test_config() -> Arc<AppConfig> {
+        Arc::new(AppConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            batch_max_urls: 3,
+            ..Default::default()
+        })
+    }
+
+    fn shared_client(cache: &CacheDb) -> Arc<FetchClient> {
+        Arc::new(fetch_client(&test_config(), cache, &CircuitBreaker::default()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_warm_impl_validation() {
+        let cache = CacheDb::open_in_memory().await.unwrap();
+        let jobs = JobRunner::default();
+        let fetch = shared_client(&cache);
+
+        let empty = CacheWarmParams { urls: vec![], mode: None, ttl_seconds: None };
+        assert!(
+            warm_impl(&cache, test_config(), &fetch, &Arc::default(), None, &jobs, empty)
+                .await
+                .is_err()
+        );
+
+        let urls = (0..4).map(|i| format!("https://example.com/{i}")).collect();
+        let too_many = CacheWarmParams { urls, mode: None, ttl_seconds: None };
+        assert!(
+            warm_impl(&cache, test_config(), &fetch, &Arc::default(), None, &jobs, too_many)
+                .await
+                .is_err()
+        );
+
+        let bad_mode = serde_json::from_value::<CacheWarmParams>(serde_json::json!({
+            "urls": ["https://example.com"],
+            "mode": "pdf"
+        }));
+        assert!(bad_mode.is_err());
+
+        let feed = CacheWarmParams {
+            urls: vec!["https://example.com/feed".into()],
+            mode: Some(Mode::Feed),
+            ttl_seconds: None,
+        };
+        assert!(
+            warm_impl(&cache, test_config(), &fetch, &Arc::default(), None, &jobs, feed)
+                .await
+                .is_err()
+        );
+
+        assert_eq!(jobs.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_warm_populates_cache() {
+        let server = FixtureServer::start(|req| {
+            FixtureResponse::html(format!(
+                "<html><head><title>Page {0}</title></head><body><article><p>{0}</p></article></body></html>",
+                req.path
+            ))
+        })
+        .await;
+
+        let cache = CacheDb::open_in_memory().await.unwrap();
+        let jobs = JobRunner::default();
+        let urls = vec![server.url("/a"), server.url("/b")];
+        let params = CacheWarmParams { urls: urls.clone(), mode: Some(Mode::Raw), ttl_seconds: Some(3600) };
+
+        let result = warm_impl(
+            &cache,
+            test_config(),
+            &shared_client(&cache),
+            &Arc::default(),
+            None,
+            &jobs,
+            params,
+        )
+        .await
+        .unwrap();
+        let text = result.content.first().unwrap().as_text().unwrap().text.clone();
+        let output: CacheWarmOutput = serde_json::from_str(&text).unwrap();
+        assert_eq!(output.queued, 2);
+        assert!(output.job_id.starts_with("warm-"));
+
+        jobs.wait_idle().await;
+
+        let listed = cache.list_snapshots(&urls, 10).await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert!(listed.iter().all(|s| s.expires_at.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_run_warm_spaces_same_host() {
+        let server = FixtureServer::start(|_| FixtureResponse::html("<p>ok</p>")).await;
+        let cache = CacheDb::open_in_memory().await.unwrap();
+        let urls = vec![server.url("/1"), server.url("/2")];
+
+        let start = Instant::now();
+        let summary = run_warm(
+            cache.clone(),
+            test_config(),
+            shared_client(&cache),
+            Arc::default(),
+            None,
+            urls,
+            Mode::Raw,
+            None,
+            Duration::from_millis(200),
+        )
+        .await;
+
+        assert_eq!(summary, WarmSummary { succeeded: 2, failed: 0 });
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+}
@@ -0,0 +1,244 @@
+//! Overall deadlines for tool calls.
+//!
+//! Fetch and extraction each have their own timeouts, but a web_open call can
+//! spend both plus cache time, and a batch many times that. [`with_deadline`]
+//! bounds the whole call and, when it fires, reports the phase the call was in
+//! (as last marked with [`enter`]) in a `TOOL_TIMEOUT` error.
+//!
+//! Dropping a call mid-way must not leave a cache write running behind the
+//! error: a later call for the same URL could otherwise race it. Writes made
+//! through [`cache_write`] run to completion on their own task, and a timed-out
+//! call waits for them to finish before returning.
+
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use serde_json::json;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+use thndrs_core::Error;
+use tokio::sync::RwLock;
+
+/// Longest a timed-out call waits for its in-flight cache writes.
+const WRITE_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+tokio::task_local! {
+    static CALL: CallState;
+}
+
+/// What a tool call is doing, for timeout reporting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Validating input; no I/O yet.
+    Setup,
+    /// Reading or writing the cache.
+    Cache,
+    /// Waiting on the network (fetch, render, or search API).
+    Fetch,
+    /// Extracting content.
+    Extract,
+}
+
+impl Phase {
+    /// Name used in timeout errors, e.g. `fetch`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Setup => "setup",
+            Self::Cache => "cache",
+            Self::Fetch => "fetch",
+            Self::Extract => "extract",
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Cache,
+            2 => Self::Fetch,
+            3 => Self::Extract,
+            _ => Self::Setup,
+        }
+    }
+}
+
+/// Phase marker and write tracking shared by one tool call and its subtasks.
+#[derive(Debug, Clone, Default)]
+struct CallState {
+    phase: Arc<AtomicU8>,
+    writes: Arc<RwLock<()>>,
+}
+
+impl CallState {
+    fn phase(&self) -> Phase {
+        Phase::from_u8(self.phase.load(Ordering::SeqCst))
+    }
+}
+
+/// Mark the current tool call as being in `phase`.
+///
+/// A no-op outside [`with_deadline`], e.g. for cache_warm's background fetches.
+pub fn enter(phase: Phase) {
+    let _ = CALL.try_with(|call| call.phase.store(phase as u8, Ordering::SeqCst));
+}
+
+/// Run a cache write so that cancelling the caller cannot orphan it.
+///
+/// The write runs on its own task and holds the call's write guard until it
+/// finishes; [`with_deadline`] waits for outstanding guards after a timeout.
+pub async fn cache_write<T, F>(write: F) -> T
+where
+    T: Send + 'static,
+    F: Future<Output = T> + Send + 'static,
+{
+    enter(Phase::Cache);
+    let guard = match CALL.try_with(|call| call.writes.clone()) {
+        Ok(writes) => Some(writes.read_owned().await),
+        Err(_) => None,
+    };
+
+    let task = tokio::spawn(async move {
+        let _guard = guard;
+        write.await
+    });
+    match task.await {
+        Ok(value) => value,
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
+/// Carry the current call's phase marker into a future that will be spawned.
+pub fn in_current_call<F: Future>(fut: F) -> impl Future<Output = F::Output> {
+    let call = CALL.try_with(Clone::clone).ok();
+    async move {
+        match call {
+            Some(call) => CALL.scope(call, fut).await,
+            None => fut.await,
+        }
+    }
+}
+
+/// Run a tool implementation under an overall deadline of `timeout_ms` (0 = none).
+pub async fn with_deadline<F>(tool: &str, timeout_ms: u64, call: F) -> Result<CallToolResult, McpError>
+where
+    F: Future<Output = Result<CallToolResult, McpError>>,
+{
+    let state = CallState::default();
+    if timeout_ms == 0 {
+        return CALL.scope(state, call).await;
+    }
+
+    let timed = tokio::time::timeout(Duration::from_millis(timeout_ms), CALL.scope(state.clone(), call)).await;
+    match timed {
+        Ok(result) => result,
+        Err(_) => {
+            let phase = state.phase();
+            if tokio::time::timeout(WRITE_DRAIN_TIMEOUT, state.writes.write())
+                .await
+                .is_err()
+            {
+                tracing::warn!("{} timed out and its cache writes are still running", tool);
+            }
+            tracing::warn!("{} exceeded {}ms during {}", tool, timeout_ms, phase.as_str());
+            Err(timeout_error(tool, timeout_ms, phase))
+        }
+    }
+}
+
+fn timeout_error(tool: &str, timeout_ms: u64, phase: Phase) -> McpError {
+    let message = format!("{} exceeded {}ms during {}", tool, timeout_ms, phase.as_str());
+    let mut err = McpError::from(Error::ToolTimeout(message));
+    err.data = Some(json!({ "tool": tool, "phase": phase.as_str(), "timeout_ms": timeout_ms }));
+    err
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::Content;
+    use std::sync::atomic::AtomicBool;
+    use std::time::Instant;
+
+    fn ok() -> Result<CallToolResult, McpError> {
+        Ok(CallToolResult::success(vec![Content::text("ok")]))
+    }
+
+    async fn slow_in(phase: Phase) -> Result<CallToolResult, McpError> {
+        enter(Phase::Cache);
+        enter(phase);
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        ok()
+    }
+
+    fn timed_out_phase(err: &McpError) -> &str {
+        assert_eq!(err.code.0, -32017);
+        err.data.as_ref().unwrap()["phase"].as_str().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_fast_call_passes_through() {
+        assert!(with_deadline("web_open", 1_000, async { ok() }).await.is_ok());
+        assert!(with_deadline("web_open", 0, async { ok() }).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_reports_phase() {
+        for phase in [Phase::Fetch, Phase::Extract, Phase::Cache] {
+            let err = with_deadline("web_open", 50, slow_in(phase)).await.unwrap_err();
+            assert_eq!(timed_out_phase(&err), phase.as_str());
+            assert_eq!(err.data.as_ref().unwrap()["tool"], "web_open");
+            assert_eq!(err.message, format!("web_open exceeded 50ms during {}", phase.as_str()));
+        }
+
+        let err = with_deadline("web_search", 50, std::future::pending())
+            .await
+            .unwrap_err();
+        assert_eq!(timed_out_phase(&err), "setup");
+    }
+
+    #[tokio::test]
+    async fn test_timeout_waits_for_cache_write() {
+        let written = Arc::new(AtomicBool::new(false));
+        let flag = written.clone();
+        let call = async move {
+            enter(Phase::Fetch);
+            cache_write(async move {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                flag.store(true, Ordering::SeqCst);
+            })
+            .await;
+            enter(Phase::Extract);
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            ok()
+        };
+
+        let start = Instant::now();
+        let err = with_deadline("web_open", 50, call).await.unwrap_err();
+        assert_eq!(timed_out_phase(&err), "cache");
+        assert!(
+            written.load(Ordering::SeqCst),
+            "write must finish before the error returns"
+        );
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_phase_follows_spawned_subtasks() {
+        let call = async {
+            let task = tokio::spawn(in_current_call(async {
+                enter(Phase::Extract);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }));
+            let _ = task.await;
+            ok()
+        };
+
+        let err = with_deadline("web_batch_open", 50, call).await.unwrap_err();
+        assert_eq!(timed_out_phase(&err), "extract");
+    }
+
+    #[tokio::test]
+    async fn test_helpers_outside_a_call() {
+        enter(Phase::Fetch);
+        assert_eq!(cache_write(async { 7 }).await, 7);
+        assert_eq!(in_current_call(async { 8 }).await, 8);
+    }
+}
@@ -0,0 +1,398 @@
+//! Self-check of the server's external dependencies.
+//!
+//! Misconfiguration (an unwritable cache path, a revoked Brave key, a broken
+//! proxy, a missing Chrome) otherwise only shows up when the first tool call
+//! that needs the component fails. Each check here exercises one component and
+//! reports pass, warn, or fail with a remediation hint. Used by the
+//! `server_health` tool and by `mcp-web --check`, which exits non-zero when a
+//! critical check fails.
+
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use thndrs_client::{BraveClient, BraveConfig, BraveError, FetchClient, FetchConfig, SearchRequest};
+use thndrs_core::{AppConfig, CacheDb, DomainPolicy};
+
+use crate::tools::output::json_result;
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    /// The component works.
+    Pass,
+    /// The component works with reduced functionality, or could not be fully verified.
+    Warn,
+    /// The component is broken.
+    Fail,
+}
+
+/// Result of checking one component.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ComponentCheck {
+    /// Component name: `cache`, `network`, `brave`, or `renderer`.
+    pub component: String,
+    /// Check outcome.
+    pub status: CheckStatus,
+    /// Whether a failure of this check makes the server unusable as configured.
+    pub critical: bool,
+    /// What was found.
+    pub message: String,
+    /// How to fix it, for warnings and failures.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    /// Time the check took in milliseconds.
+    pub elapsed_ms: u64,
+}
+
+impl ComponentCheck {
+    fn pass(component: &str, message: impl Into<String>) -> Self {
+        Self {
+            component: component.to_string(),
+            status: CheckStatus::Pass,
+            critical: false,
+            message: message.into(),
+            hint: None,
+            elapsed_ms: 0,
+        }
+    }
+
+    fn warn(component: &str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { status: CheckStatus::Warn, hint: Some(hint.into()), ..Self::pass(component, message) }
+    }
+
+    fn fail(component: &str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { status: CheckStatus::Fail, critical: true, ..Self::warn(component, message, hint) }
+    }
+
+    fn timed(mut self, start: Instant) -> Self {
+        self.elapsed_ms = start.elapsed().as_millis() as u64;
+        self
+    }
+}
+
+/// Per-component report with an overall verdict.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct HealthReport {
+    /// Worst status across all checks.
+    pub status: CheckStatus,
+    /// False if any critical check failed.
+    pub healthy: bool,
+    /// Individual check results, in the order they ran.
+    pub checks: Vec<ComponentCheck>,
+}
+
+impl HealthReport {
+    /// Assemble a report from individual check results.
+    pub fn new(checks: Vec<ComponentCheck>) -> Self {
+        let status = checks.iter().map(|c| c.status).max().unwrap_or(CheckStatus::Pass);
+        let healthy = !checks.iter().any(|c| c.critical && c.status == CheckStatus::Fail);
+        Self { status, healthy, checks }
+    }
+}
+
+/// Run every check that applies to `config`.
+///
+/// The network check runs only when `health_probe_url` is set, the Brave test
+/// query only when an API key is configured (it spends one API request), and
+/// the renderer check only when rendering is enabled.
+pub async fn run_checks(config: &AppConfig) -> HealthReport {
+    let (cache_check, cache) = check_cache(config).await;
+    let mut checks = vec![cache_check];
+
+    if let Some(url) = &config.health_probe_url {
+        checks.push(check_network(config, url).await);
+    }
+    checks.push(check_brave(config, cache.as_ref()).await);
+    if config.render_enabled {
+        checks.push(check_renderer(config).await);
+    }
+
+    HealthReport::new(checks)
+}
+
+/// Open (and migrate) the cache database at the configured path.
+pub async fn check_cache(config: &AppConfig) -> (ComponentCheck, Option<CacheDb>) {
+    let start = Instant::now();
+    let hint = "Point MCP_WEB_DB_PATH at a file in an existing, writable directory";
+
+    let db = match CacheDb::open(&config.db_path).await {
+        Ok(db) => db,
+        Err(e) => {
+            let message = format!("cannot open {}: {}", config.db_path.display(), e);
+            return (ComponentCheck::fail("cache", message, hint).timed(start), None);
+        }
+    };
+
+    match db.cache_stats().await {
+        Ok(stats) => {
+            let message = format!(
+                "{} opened and migrated ({} snapshots)",
+                config.db_path.display(),
+                stats.snapshots
+            );
+            (ComponentCheck::pass("cache", message).timed(start), Some(db))
+        }
+        Err(e) => {
+            let message = format!("{} opened but cannot be queried: {}", config.db_path.display(), e);
+            (ComponentCheck::fail("cache", message, hint).timed(start), None)
+        }
+    }
+}
+
+/// Send a HEAD request to `url` through the fetch client.
+pub async fn check_network(config: &AppConfig, url: &str) -> ComponentCheck {
+    let start = Instant::now();
+    let hint = "Check network access and HTTPS_PROXY/HTTP_PROXY/NO_PROXY, or unset MCP_WEB_HEALTH_PROBE_URL";
+
+    let fetch_config = FetchConfig {
+        timeout: config.timeout(),
+        user_agent: config.user_agent.clone(),
+        allow_private_addresses: config.allow_private_addresses,
+        domain_policy: DomainPolicy::from_config(config),
+        ..Default::default()
+    };
+    let probe = match FetchClient::new(fetch_config) {
+        Ok(client) => client.probe(url).await,
+        Err(e) => Err(e),
+    };
+
+    let check = match probe {
+        Ok(status) if status.is_server_error() => ComponentCheck::warn(
+            "network",
+            format!("{} answered HEAD with {}", url, status.as_u16()),
+            "The probe URL is reachable but unhealthy; choose a more reliable MCP_WEB_HEALTH_PROBE_URL",
+        ),
+        Ok(status) => ComponentCheck::pass("network", format!("{} answered HEAD with {}", url, status.as_u16())),
+        Err(e) => ComponentCheck::fail("network", format!("HEAD {} failed: {}", url, e), hint),
+    };
+    check.timed(start)
+}
+
+/// Validate the Brave API key with a one-result query.
+///
+/// The request is billed, so it is counted in `cache` usage when available.
+pub async fn check_brave(config: &AppConfig, cache: Option<&CacheDb>) -> ComponentCheck {
+    let start = Instant::now();
+    let Some(api_key) = config.brave_api_key.clone() else {
+        return ComponentCheck::warn(
+            "brave",
+            "no API key configured; web_search is unavailable",
+            "Set MCP_WEB_BRAVE_API_KEY to enable web_search",
+        );
+    };
+
+    let client = BraveClient::new(BraveConfig {
+        api_key,
+        user_agent: config.user_agent.clone(),
+        timeout: config.timeout(),
+        // A probe reports throttling rather than waiting it out.
+        retries: 0,
+        ..Default::default()
+    });
+    let result = match client {
+        Ok(client) => {
+            let req = SearchRequest { q: "mcp-web".into(), count: Some(1), ..Default::default() };
+            let result = client.search(req).await.map(|_| ());
+            if !matches!(result, Err(BraveError::Network(_) | BraveError::Timeout))
+                && let Some(db) = cache
+                && let Err(e) = db.record_brave_request().await
+            {
+                tracing::warn!("failed to record Brave request: {}", e);
+            }
+            result
+        }
+        Err(e) => Err(e),
+    };
+
+    brave_check(result).timed(start)
+}
+
+/// Map the outcome of the Brave test query to a check result.
+fn brave_check(result: Result<(), BraveError>) -> ComponentCheck {
+    match result {
+        Ok(()) => ComponentCheck::pass("brave", "API key accepted"),
+        Err(e @ (BraveError::AuthError | BraveError::MissingApiKey)) => ComponentCheck::fail(
+            "brave",
+            e.to_string(),
+            "Check MCP_WEB_BRAVE_API_KEY against the Brave Search API dashboard",
+        ),
+        Err(e @ BraveError::RateLimited) => ComponentCheck::warn(
+            "brave",
+            e.to_string(),
+            "The key is valid but rate limited; check the plan's quota",
+        ),
+        Err(e @ (BraveError::Network(_) | BraveError::Timeout)) => ComponentCheck::fail(
+            "brave",
+            e.to_string(),
+            "Check network access to api.search.brave.com and any proxy settings",
+        ),
+        Err(e) => ComponentCheck::warn(
+            "brave",
+            e.to_string(),
+            "Brave answered unexpectedly; retry later or check the Brave status page",
+        ),
+    }
+}
+
+/// Launch (and drop) a headless browser with the configured binary and flags.
+pub async fn check_renderer(config: &AppConfig) -> ComponentCheck {
+    let start = Instant::now();
+    let hint = "Install Chrome or Chromium on PATH (or set MCP_WEB_RENDER__CHROME_PATH), \
+                or set MCP_WEB_RENDER_ENABLED=false";
+
+    #[cfg(feature = "render")]
+    let check = match thndrs_client::HeadlessRenderer::with_config(&(&config.render).into()).await {
+        Ok(_) => ComponentCheck::pass("renderer", "headless browser launched"),
+        Err(e) => ComponentCheck::fail("renderer", e.to_string(), hint),
+    };
+    #[cfg(not(feature = "render"))]
+    let check = {
+        let _ = config;
+        ComponentCheck::fail(
+            "renderer",
+            "rendering is enabled but this build lacks the render feature",
+            format!("Rebuild with --features render. {hint}"),
+        )
+    };
+
+    check.timed(start)
+}
+
+/// Run the self-check and return the report.
+pub async fn health_impl(config: &AppConfig) -> Result<CallToolResult, McpError> {
+    let report = run_checks(config).await;
+    json_result(&report, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+    #[test]
+    fn test_report_assembly() {
+        let report = HealthReport::new(vec![
+            ComponentCheck::pass("cache", "ok"),
+            ComponentCheck::pass("network", "ok"),
+        ]);
+        assert_eq!(report.status, CheckStatus::Pass);
+        assert!(report.healthy);
+
+        let report = HealthReport::new(vec![
+            ComponentCheck::pass("cache", "ok"),
+            ComponentCheck::warn("brave", "no key", "set it"),
+        ]);
+        assert_eq!(report.status, CheckStatus::Warn);
+        assert!(report.healthy);
+
+        let report = HealthReport::new(vec![
+            ComponentCheck::warn("brave", "no key", "set it"),
+            ComponentCheck::fail("renderer", "no chrome", "install it"),
+            ComponentCheck::pass("cache", "ok"),
+        ]);
+        assert_eq!(report.status, CheckStatus::Fail);
+        assert!(!report.healthy);
+        assert_eq!(report.checks[1].component, "renderer");
+
+        let advisory = ComponentCheck { critical: false, ..ComponentCheck::fail("network", "down", "fix it") };
+        let report = HealthReport::new(vec![advisory]);
+        assert_eq!(report.status, CheckStatus::Fail);
+        assert!(report.healthy);
+
+        assert!(HealthReport::new(Vec::new()).healthy);
+    }
+
+    #[test]
+    fn test_report_serialization() {
+        let report = HealthReport::new(vec![ComponentCheck::warn(
+            "brave",
+            "no key",
+            "Set MCP_WEB_BRAVE_API_KEY",
+        )]);
+        let json = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(json["status"], "warn");
+        assert_eq!(json["healthy"], true);
+        assert_eq!(json["checks"][0]["hint"], "Set MCP_WEB_BRAVE_API_KEY");
+        assert!(
+            serde_json::to_value(ComponentCheck::pass("cache", "ok"))
+                .unwrap()
+                .get("hint")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_brave_check_classification() {
+        assert_eq!(brave_check(Ok(())).status, CheckStatus::Pass);
+
+        let check = brave_check(Err(BraveError::AuthError));
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.critical);
+        assert!(check.hint.unwrap().contains("MCP_WEB_BRAVE_API_KEY"));
+
+        assert_eq!(brave_check(Err(BraveError::RateLimited)).status, CheckStatus::Warn);
+        assert_eq!(brave_check(Err(BraveError::Timeout)).status, CheckStatus::Fail);
+        assert_eq!(
+            brave_check(Err(BraveError::HttpError { status: 502 })).status,
+            CheckStatus::Warn
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = AppConfig { db_path: dir.path().join("cache.sqlite"), ..Default::default() };
+        let (check, db) = check_cache(&config).await;
+        assert_eq!(check.status, CheckStatus::Pass);
+        assert!(check.message.contains("0 snapshots"));
+        assert!(db.is_some());
+
+        let blocker = dir.path().join("not-a-dir");
+        std::fs::write(&blocker, b"").unwrap();
+        let config = AppConfig { db_path: blocker.join("cache.sqlite"), ..Default::default() };
+        let (check, db) = check_cache(&config).await;
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.critical);
+        assert!(check.hint.unwrap().contains("MCP_WEB_DB_PATH"));
+        assert!(db.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_check_network() {
+        let server = FixtureServer::start(|req| match req.path.as_str() {
+            "/down" => FixtureResponse::status(503),
+            _ => FixtureResponse::status(200),
+        })
+        .await;
+        let config = AppConfig { allow_private_addresses: true, ..Default::default() };
+
+        assert_eq!(check_network(&config, &server.url("/")).await.status, CheckStatus::Pass);
+        assert_eq!(
+            check_network(&config, &server.url("/down")).await.status,
+            CheckStatus::Warn
+        );
+
+        let closed_port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let check = check_network(&config, &format!("http://127.0.0.1:{closed_port}/")).await;
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.hint.unwrap().contains("HTTPS_PROXY"));
+    }
+
+    #[tokio::test]
+    async fn test_run_checks_skips_unconfigured_components() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = AppConfig { db_path: dir.path().join("cache.sqlite"), ..Default::default() };
+        let report = run_checks(&config).await;
+
+        let components: Vec<_> = report.checks.iter().map(|c| c.component.as_str()).collect();
+        assert_eq!(components, vec!["cache", "brave"]);
+        assert_eq!(report.status, CheckStatus::Warn);
+        assert!(report.healthy);
+    }
+}
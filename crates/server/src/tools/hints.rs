@@ -0,0 +1,401 @@
+//! Structured "next action" hints for failed or low-confidence web_open results.
+//!
+//! A small rules engine turns fetch and extraction diagnostics into suggestions
+//! an agent can act on directly, each with the parameters to retry with. Hint
+//! codes are stable and documented in doc/schema.txt (O3).
+
+use rmcp::ErrorData as McpError;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thndrs_client::fetch::{BodyKind, body_kind};
+use thndrs_core::{Error, Mode};
+
+/// The page probably builds its content with JavaScript; retry with `mode=rendered`.
+pub const TRY_RENDERED: &str = "TRY_RENDERED";
+/// Retry with `mode=raw` to get the unprocessed body.
+pub const TRY_RAW: &str = "TRY_RAW";
+/// Retry with a lower `extract.char_threshold`.
+pub const LOWER_CHAR_THRESHOLD: &str = "LOWER_CHAR_THRESHOLD";
+/// The response is a PDF: raw mode cannot return it, and without the `pdf` feature neither can readable mode.
+pub const PDF_CONTENT: &str = "PDF_CONTENT";
+/// The response is binary (an image, archive, ...), which web_open cannot return as text.
+pub const NON_HTML_CONTENT: &str = "NON_HTML_CONTENT";
+/// The site refused the request (401/403).
+pub const ACCESS_DENIED: &str = "ACCESS_DENIED";
+/// Fetch an archived copy instead of the live page.
+pub const TRY_ARCHIVE: &str = "TRY_ARCHIVE";
+/// The site is rate limiting or the host circuit is open; wait before retrying.
+pub const RETRY_LATER: &str = "RETRY_LATER";
+/// The URL does not exist (404/410).
+pub const CHECK_URL: &str = "CHECK_URL";
+/// The fetch timed out; retry with a larger `timeout_ms`.
+pub const INCREASE_TIMEOUT: &str = "INCREASE_TIMEOUT";
+/// The body exceeded `max_bytes`; retry with a larger limit.
+pub const INCREASE_MAX_BYTES: &str = "INCREASE_MAX_BYTES";
+/// Rendering is unavailable; retry with `mode=readable`.
+pub const TRY_READABLE: &str = "TRY_READABLE";
+
+/// Extracted bodies shorter than this (in characters) are considered suspiciously small.
+const TINY_EXTRACTION_CHARS: usize = 250;
+
+/// Documents shorter than this are allowed to extract to little content.
+const TINY_DOCUMENT_BYTES: usize = 2048;
+
+/// Char threshold suggested when extraction drops too much content.
+const SUGGESTED_CHAR_THRESHOLD: usize = 100;
+
+/// A suggested next action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Hint {
+    /// Stable machine-readable code, e.g. `TRY_RENDERED`.
+    pub code: String,
+    /// Human-readable explanation.
+    pub message: String,
+    /// web_open parameters to retry with, if the hint maps to a retry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggested_params: Option<serde_json::Value>,
+}
+
+impl Hint {
+    fn new(code: &str, message: impl Into<String>, suggested_params: Option<serde_json::Value>) -> Self {
+        Self { code: code.to_string(), message: message.into(), suggested_params }
+    }
+}
+
+/// Diagnostics the rules are evaluated against.
+#[derive(Debug, Clone, Default)]
+pub struct HintContext {
+    /// URL as requested.
+    pub url: String,
+    /// Requested mode.
+    pub mode: Mode,
+    /// Requested timeout in milliseconds.
+    pub timeout_ms: u64,
+    /// Requested body size limit.
+    pub max_bytes: usize,
+    /// Response Content-Type, once a response was received.
+    pub content_type: Option<String>,
+    /// Size of the fetched body in bytes.
+    pub body_len: usize,
+    /// Size of the extracted body in characters, excluding frontmatter.
+    pub extracted_len: Option<usize>,
+    /// Whether rendered mode is compiled in.
+    pub render_available: bool,
+}
+
+/// Hints for a failed web_open call.
+pub fn hints_for_error(err: &Error, ctx: &HintContext) -> Vec<Hint> {
+    let mut hints = content_type_hints(ctx);
+
+    match err {
+        Error::HttpError(msg) => match http_status(msg) {
+            Some(401 | 403) => {
+                hints.push(Hint::new(
+                    ACCESS_DENIED,
+                    "The site refused the request; it may block automated clients. Retrying with a different \
+                     user agent (MCP_WEB_USER_AGENT) can help.",
+                    None,
+                ));
+                hints.push(archive_hint(ctx));
+            }
+            Some(404 | 410) => {
+                hints.push(Hint::new(
+                    CHECK_URL,
+                    "The page does not exist; check the URL for typos.",
+                    None,
+                ));
+                hints.push(archive_hint(ctx));
+            }
+            Some(429) => hints.push(Hint::new(
+                RETRY_LATER,
+                "The site is rate limiting requests; wait and retry.",
+                None,
+            )),
+            _ => {}
+        },
+        Error::CircuitOpen(msg) | Error::HostCoolingDown(msg) => hints.push(Hint::new(RETRY_LATER, msg.clone(), None)),
+        Error::RobotsUnavailable(_) => hints.push(Hint::new(
+            RETRY_LATER,
+            "The site's robots.txt could not be retrieved; retry once it is reachable again.",
+            None,
+        )),
+        Error::FetchTimeout(_) => hints.push(Hint::new(
+            INCREASE_TIMEOUT,
+            "The fetch timed out; the site may be slow.",
+            Some(json!({ "timeout_ms": ctx.timeout_ms.saturating_mul(2) })),
+        )),
+        Error::FetchTooLarge(_) => hints.push(Hint::new(
+            INCREASE_MAX_BYTES,
+            "The response exceeded max_bytes.",
+            Some(json!({ "max_bytes": ctx.max_bytes.saturating_mul(2) })),
+        )),
+        Error::RenderDisabled | Error::RenderFailed(_) => hints.push(Hint::new(
+            TRY_READABLE,
+            "Rendering is unavailable; static extraction may still work.",
+            Some(json!({ "mode": "readable" })),
+        )),
+        // A binary body was already explained above; raw mode would not help.
+        Error::UnsupportedContentType(_) if hints.is_empty() && !is_pdf(ctx) => hints.push(Hint::new(
+            TRY_RAW,
+            "The content type is not fetched by default; raw mode can download it anyway.",
+            Some(json!({ "mode": "raw", "allow_any_content_type": true })),
+        )),
+        Error::BlockedByBotProtection(_) => {
+            if ctx.mode != Mode::Rendered && ctx.render_available {
+                hints.push(Hint::new(
+                    TRY_RENDERED,
+                    "The site served a bot-protection challenge; a real browser may get through it.",
+                    Some(json!({ "mode": "rendered" })),
+                ));
+            }
+            hints.push(archive_hint(ctx));
+        }
+        Error::ExtractFailed(_) if !is_pdf(ctx) => hints.extend(extraction_hints(ctx)),
+        _ => {}
+    }
+
+    hints
+}
+
+/// Hints for a successful web_open call whose result looks incomplete.
+///
+/// Empty when the result looks healthy.
+pub fn hints_for_output(ctx: &HintContext) -> Vec<Hint> {
+    let mut hints = content_type_hints(ctx);
+
+    let tiny = ctx
+        .extracted_len
+        .is_some_and(|len| len < TINY_EXTRACTION_CHARS && ctx.body_len >= TINY_DOCUMENT_BYTES);
+    // Rendering or a lower threshold will not find text a PDF does not have.
+    if tiny && hints.is_empty() && !is_pdf(ctx) {
+        hints.extend(extraction_hints(ctx));
+    }
+
+    hints
+}
+
+/// Attach hints to an error's data payload as `{"hints": [...]}`.
+pub fn error_with_hints(err: Error, ctx: &HintContext) -> McpError {
+    let hints = hints_for_error(&err, ctx);
+    let mut mcp_err = McpError::from(err);
+    if !hints.is_empty() {
+        mcp_err.data = Some(json!({ "hints": hints }));
+    }
+    mcp_err
+}
+
+/// Read hints back out of an error produced by [`error_with_hints`].
+pub fn hints_from_error(err: &McpError) -> Vec<Hint> {
+    err.data
+        .as_ref()
+        .and_then(|data| data.get("hints"))
+        .and_then(|hints| serde_json::from_value(hints.clone()).ok())
+        .unwrap_or_default()
+}
+
+fn extraction_hints(ctx: &HintContext) -> Vec<Hint> {
+    let mut hints = Vec::new();
+
+    if ctx.mode != Mode::Rendered && ctx.render_available {
+        hints.push(Hint::new(
+            TRY_RENDERED,
+            "Little content was extracted; the page may build its content with JavaScript.",
+            Some(json!({ "mode": "rendered" })),
+        ));
+    }
+    hints.push(Hint::new(
+        LOWER_CHAR_THRESHOLD,
+        "Extraction may have discarded short content blocks.",
+        Some(json!({ "extract": { "char_threshold": SUGGESTED_CHAR_THRESHOLD } })),
+    ));
+    hints.push(Hint::new(
+        TRY_RAW,
+        "Fetch the unprocessed HTML and inspect it directly.",
+        Some(json!({ "mode": "raw" })),
+    ));
+
+    hints
+}
+
+/// Hints for a body web_open cannot return as text in any mode.
+///
+/// JSON and plain text are served as they are, so only binary types are flagged.
+fn content_type_hints(ctx: &HintContext) -> Vec<Hint> {
+    let Some(ct) = ctx
+        .content_type
+        .as_deref()
+        .filter(|ct| body_kind(Some(ct), &[]) == BodyKind::Binary)
+    else {
+        return Vec::new();
+    };
+
+    if is_pdf(ctx) {
+        if cfg!(feature = "pdf") {
+            // Readable mode reads the text; only raw mode refuses PDFs.
+            return match ctx.mode {
+                Mode::Raw => vec![Hint::new(
+                    PDF_CONTENT,
+                    "The response is a PDF; readable mode extracts its text.",
+                    Some(json!({ "mode": "readable" })),
+                )],
+                _ => Vec::new(),
+            };
+        }
+        return vec![Hint::new(
+            PDF_CONTENT,
+            "The response is a PDF, which web_open cannot turn into text; look for an HTML version of the document.",
+            None,
+        )];
+    }
+    vec![Hint::new(
+        NON_HTML_CONTENT,
+        format!("The response is {ct}, not text; web_open only returns text content."),
+        None,
+    )]
+}
+
+fn archive_hint(ctx: &HintContext) -> Hint {
+    Hint::new(
+        TRY_ARCHIVE,
+        "An archived copy may be available from the Wayback Machine.",
+        Some(json!({ "url": format!("https://web.archive.org/web/{}", ctx.url) })),
+    )
+}
+
+fn is_pdf(ctx: &HintContext) -> bool {
+    ctx.content_type
+        .as_deref()
+        .is_some_and(|ct| ct.to_ascii_lowercase().contains("application/pdf"))
+}
+
+/// Parse the status code out of an `HttpError` message such as `status 403`.
+fn http_status(msg: &str) -> Option<u16> {
+    msg.strip_prefix("status ")?.split_whitespace().next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx() -> HintContext {
+        HintContext {
+            url: "https://example.com/page".into(),
+            mode: Mode::Readable,
+            timeout_ms: 20000,
+            max_bytes: 5 * 1024 * 1024,
+            content_type: Some("text/html; charset=utf-8".into()),
+            body_len: 50_000,
+            extracted_len: Some(1_000),
+            render_available: true,
+        }
+    }
+
+    fn codes(hints: &[Hint]) -> Vec<&str> {
+        hints.iter().map(|h| h.code.as_str()).collect()
+    }
+
+    #[test]
+    fn test_healthy_output_has_no_hints() {
+        assert!(hints_for_output(&ctx()).is_empty());
+    }
+
+    #[test]
+    fn test_tiny_extraction_suggests_rendered_and_raw() {
+        let ctx = HintContext { extracted_len: Some(40), ..ctx() };
+        let hints = hints_for_output(&ctx);
+        assert_eq!(codes(&hints), vec![TRY_RENDERED, LOWER_CHAR_THRESHOLD, TRY_RAW]);
+        assert_eq!(hints[0].suggested_params, Some(json!({ "mode": "rendered" })));
+
+        let no_render = HintContext { render_available: false, ..ctx };
+        assert_eq!(
+            codes(&hints_for_output(&no_render)),
+            vec![LOWER_CHAR_THRESHOLD, TRY_RAW]
+        );
+    }
+
+    #[test]
+    fn test_tiny_document_is_not_flagged() {
+        let ctx = HintContext { body_len: 500, extracted_len: Some(40), ..ctx() };
+        assert!(hints_for_output(&ctx).is_empty());
+    }
+
+    #[test]
+    fn test_binary_content_is_not_retried() {
+        let refused = Error::UnsupportedContentType("not text".into());
+        let pdf = HintContext { mode: Mode::Raw, content_type: Some("application/pdf".into()), ..ctx() };
+        let hints = hints_for_error(&refused, &pdf);
+        assert_eq!(codes(&hints), vec![PDF_CONTENT]);
+        let readable = cfg!(feature = "pdf").then(|| json!({ "mode": "readable" }));
+        assert_eq!(hints[0].suggested_params, readable);
+
+        let image = HintContext { mode: Mode::Raw, content_type: Some("image/png".into()), ..ctx() };
+        assert_eq!(codes(&hints_for_error(&refused, &image)), vec![NON_HTML_CONTENT]);
+
+        let json = HintContext { content_type: Some("application/json".into()), extracted_len: None, ..ctx() };
+        assert!(hints_for_output(&json).is_empty());
+    }
+
+    #[cfg(feature = "pdf")]
+    #[test]
+    fn test_readable_pdf_gets_no_content_hints() {
+        let pdf = HintContext { content_type: Some("application/pdf".into()), extracted_len: Some(10), ..ctx() };
+        assert!(hints_for_output(&pdf).is_empty());
+        assert!(hints_for_error(&Error::ExtractFailed("PDF has 900 pages".into()), &pdf).is_empty());
+    }
+
+    #[test]
+    fn test_forbidden_suggests_user_agent_and_archive() {
+        let hints = hints_for_error(&Error::HttpError("status 403".into()), &ctx());
+        assert_eq!(codes(&hints), vec![ACCESS_DENIED, TRY_ARCHIVE]);
+        assert_eq!(
+            hints[1].suggested_params,
+            Some(json!({ "url": "https://web.archive.org/web/https://example.com/page" }))
+        );
+    }
+
+    #[test]
+    fn test_bot_protection_suggests_rendered_and_archive() {
+        let err = Error::BlockedByBotProtection("cloudflare challenge page (cf-mitigated header)".into());
+        assert_eq!(codes(&hints_for_error(&err, &ctx())), vec![TRY_RENDERED, TRY_ARCHIVE]);
+
+        let rendered = HintContext { mode: Mode::Rendered, ..ctx() };
+        assert_eq!(codes(&hints_for_error(&err, &rendered)), vec![TRY_ARCHIVE]);
+    }
+
+    #[test]
+    fn test_other_errors() {
+        let hints = hints_for_error(&Error::FetchTimeout("timed out".into()), &ctx());
+        assert_eq!(codes(&hints), vec![INCREASE_TIMEOUT]);
+        assert_eq!(hints[0].suggested_params, Some(json!({ "timeout_ms": 40000 })));
+
+        let hints = hints_for_error(&Error::HttpError("status 429".into()), &ctx());
+        assert_eq!(codes(&hints), vec![RETRY_LATER]);
+
+        let cooling = "host example.com cooling down, retry after 120s";
+        let hints = hints_for_error(&Error::HostCoolingDown(cooling.into()), &ctx());
+        assert_eq!(codes(&hints), vec![RETRY_LATER]);
+        assert_eq!(hints[0].message, cooling);
+
+        let hints = hints_for_error(&Error::RobotsUnavailable("status 503".into()), &ctx());
+        assert_eq!(codes(&hints), vec![RETRY_LATER]);
+
+        let hints = hints_for_error(&Error::UnsupportedContentType("video/mp4".into()), &ctx());
+        assert_eq!(
+            hints[0].suggested_params,
+            Some(json!({ "mode": "raw", "allow_any_content_type": true }))
+        );
+
+        assert!(hints_for_error(&Error::InvalidUrl("bad".into()), &ctx()).is_empty());
+    }
+
+    #[test]
+    fn test_error_with_hints_roundtrip() {
+        let err = error_with_hints(Error::HttpError("status 403".into()), &ctx());
+        assert_eq!(err.code.0, -32008);
+        assert_eq!(codes(&hints_from_error(&err)), vec![ACCESS_DENIED, TRY_ARCHIVE]);
+
+        let err = error_with_hints(Error::InvalidUrl("bad".into()), &ctx());
+        assert!(err.data.is_none());
+    }
+}
@@ -4,12 +4,29 @@
 #![allow(unused_imports)]
 
 pub mod cache;
+pub mod deadline;
+pub mod health;
+pub mod hints;
+pub mod output;
 pub mod web_batch_open;
 pub mod web_extract;
+pub mod web_feed;
+pub mod web_image_search;
+pub mod web_news_search;
 pub mod web_open;
 pub mod web_search;
+pub mod web_sitemap;
+pub mod web_suggest;
 
-pub use web_batch_open::{BatchItem, BatchItemStatus, BatchSummary, WebBatchOpenOutput, WebBatchOpenParams};
+pub use health::{CheckStatus, ComponentCheck, HealthReport};
+pub use web_batch_open::{
+    BatchItem, BatchItemStatus, BatchSummary, BatchUrl, BatchUrlItem, WebBatchOpenOutput, WebBatchOpenParams,
+};
 pub use web_extract::{WebExtractOutput, WebExtractParams};
-pub use web_open::{ExtractedLink, ExtractionDiagnostics, WebOpenOutput, WebOpenParams};
+pub use web_feed::{FeedItem, WebFeedOutput, WebFeedParams};
+pub use web_image_search::{ImageResult, WebImageSearchOutput, WebImageSearchParams};
+pub use web_news_search::{NewsResult, WebNewsSearchOutput, WebNewsSearchParams};
+pub use web_open::{ExtractedLink, ExtractionDiagnostics, LinkFilter, WebOpenOutput, WebOpenParams};
 pub use web_search::{DebugInfo, QueryMeta, SearchResult, WebSearchOutput, WebSearchParams};
+pub use web_sitemap::{SitemapUrl, WebSitemapOutput, WebSitemapParams};
+pub use web_suggest::{Suggestion, WebSuggestOutput, WebSuggestParams};
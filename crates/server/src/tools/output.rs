@@ -0,0 +1,88 @@
+//! Serializing tool outputs.
+//!
+//! Small outputs are pretty-printed for people reading transcripts. Outputs
+//! whose compact JSON exceeds `pretty_json_max_bytes` stay compact: indenting a
+//! multi-megabyte markdown payload only adds whitespace for a machine reader.
+//! A value that fails to serialize is an internal error, never empty content.
+
+use rmcp::{ErrorData as McpError, model::*};
+use serde::Serialize;
+use thndrs_core::AppConfig;
+
+/// Serialize `value`, pretty-printed only if its compact form fits in `pretty_max_bytes`.
+pub fn to_json<T: Serialize + ?Sized>(value: &T, pretty_max_bytes: usize) -> Result<String, McpError> {
+    let compact = serde_json::to_string(value).map_err(serialize_error)?;
+    if compact.len() > pretty_max_bytes {
+        return Ok(compact);
+    }
+    serde_json::to_string_pretty(value).map_err(serialize_error)
+}
+
+/// Successful tool result carrying `value` as JSON text.
+pub fn json_result<T: Serialize + ?Sized>(value: &T, config: &AppConfig) -> Result<CallToolResult, McpError> {
+    let json = to_json(value, config.pretty_json_max_bytes)?;
+    Ok(CallToolResult::success(vec![Content::text(json)]))
+}
+
+fn serialize_error(e: serde_json::Error) -> McpError {
+    McpError::internal_error(format!("failed to serialize output: {e}"), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::ser::Error as _;
+
+    #[derive(Serialize)]
+    struct Doc {
+        title: String,
+        markdown: String,
+    }
+
+    struct Poisoned;
+
+    impl Serialize for Poisoned {
+        fn serialize<S: serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+            Err(S::Error::custom("poisoned value"))
+        }
+    }
+
+    fn text(result: &CallToolResult) -> &str {
+        &result.content[0].as_text().unwrap().text
+    }
+
+    #[test]
+    fn test_small_output_is_pretty() {
+        let doc = Doc { title: "Small".into(), markdown: "# Small".into() };
+        let result = json_result(&doc, &AppConfig::default()).unwrap();
+
+        assert!(text(&result).contains("\n  \"title\": \"Small\""));
+    }
+
+    #[test]
+    fn test_large_output_is_compact() {
+        let doc = Doc { title: "Large".into(), markdown: "word ".repeat(30_000) };
+        let config = AppConfig::default();
+        let result = json_result(&doc, &config).unwrap();
+        let json = text(&result);
+
+        assert!(json.len() > config.pretty_json_max_bytes);
+        assert!(json.starts_with("{\"title\":\"Large\",\"markdown\":"));
+        assert!(!json.contains('\n'));
+
+        let always_compact = AppConfig { pretty_json_max_bytes: 0, ..Default::default() };
+        let small = Doc { title: "Small".into(), markdown: String::new() };
+        assert_eq!(
+            text(&json_result(&small, &always_compact).unwrap()),
+            "{\"title\":\"Small\",\"markdown\":\"\"}"
+        );
+    }
+
+    #[test]
+    fn test_serialization_failure_is_internal_error() {
+        let err = json_result(&Poisoned, &AppConfig::default()).unwrap_err();
+
+        assert_eq!(err.code, ErrorCode::INTERNAL_ERROR);
+        assert!(err.message.contains("poisoned value"));
+    }
+}
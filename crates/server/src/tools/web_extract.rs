@@ -1,31 +1,59 @@
 //! web_extract tool implementation.
 //!
-//! This tool extracts readable content from HTML using Lectito.
-//! No network I/O is performed - HTML is provided by the client.
+//! This tool extracts readable content from HTML using Lectito, or with the
+//! plain_text strategy keeps all visible body text without scoring.
+//! No network I/O is performed - HTML is provided by the client, or read from
+//! the body stored with a cached snapshot.
 
 use lectito_core::{Readability, ReadabilityConfig, parse, parse_with_url};
 use rmcp::{ErrorData as McpError, model::*};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use thndrs_core::Error;
+use thndrs_client::{
+    CodePass, ContentQuality, ExtractConfig, ExtractStrategy, Heading, PageMetadata, Table, TablePass, check_dom,
+    check_selector, content_quality, estimate_tokens, extract_outline, extract_structured_data, page_metadata,
+    pipeline::decode_body, plain_outline, plain_text, run_with_budget, scope_to_section, scope_to_selector,
+    truncate_to_tokens,
+};
+use thndrs_core::{AppConfig, CacheDb, Error};
+
+use crate::tools::deadline::{self, Phase};
+use crate::tools::output::json_result;
+use crate::tools::web_open::stored_charset;
 
 /// Input parameters for web_extract tool.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct WebExtractParams {
-    /// The raw HTML content to extract from.
+    /// The raw HTML content to extract from (or use `snapshot_hash`).
+    #[serde(default)]
     pub html: String,
 
+    /// Hash of a cached snapshot whose stored body is extracted instead of `html`.
+    /// The snapshot must have been opened with mode=raw or store_raw=true.
+    #[serde(default)]
+    pub snapshot_hash: Option<String>,
+
     /// Base URL for resolving relative links (optional).
-    /// If not provided, relative links will be preserved as-is.
+    /// Defaults to the snapshot's final URL when extracting from `snapshot_hash`;
+    /// otherwise relative links are preserved as-is.
     #[serde(default)]
     pub base_url: Option<String>,
 
     /// Extraction strategy to use.
     /// - "readability": Main content extraction (default)
-    /// - "plain_text": Simple text extraction, no structure
+    /// - "plain_text": All visible body text (minus scripts, styles, nav, and
+    ///   footers), no scoring; for pages too short for readability
+    ///
+    /// Other values are rejected.
     #[serde(default = "default_strategy")]
     pub strategy: String,
 
+    /// With the readability strategy, fall back to plain_text when readability
+    /// fails or keeps under 200 characters (default: true). `strategy_used`
+    /// reports which one produced the output.
+    #[serde(default = "default_true")]
+    pub fallback: bool,
+
     /// Whether to output as Markdown (true) or plain text (false).
     #[serde(default = "default_true")]
     pub to_markdown: bool,
@@ -33,6 +61,30 @@ pub struct WebExtractParams {
     /// Optional extraction tuning parameters.
     #[serde(default)]
     pub config: Option<ExtractTuning>,
+
+    /// Approximate token budget for the output; trimmed at paragraph boundaries.
+    #[serde(default)]
+    pub max_tokens: Option<usize>,
+
+    /// Return the page's JSON-LD items in `structured_data` (default: false).
+    #[serde(default)]
+    pub include_structured_data: bool,
+
+    /// Return tables too complex for markdown (row or column spans) in `tables`
+    /// (default: false). Readability strategy only.
+    #[serde(default)]
+    pub include_tables: bool,
+
+    /// CSS selector: return only the content of the first element it matches, as
+    /// markdown (requires to_markdown).
+    #[serde(default)]
+    pub selector: Option<String>,
+
+    /// Heading text, matched case-insensitively: return only that heading and the
+    /// content under it, up to the next heading of the same or a higher level
+    /// (requires to_markdown).
+    #[serde(default)]
+    pub section: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -58,6 +110,20 @@ fn default_true() -> bool {
 pub struct WebExtractOutput {
     /// Extracted page title (if found).
     pub title: Option<String>,
+    /// Author, publication date, description, social card fields, and canonical URL
+    /// the page declares; omitted when it declares none.
+    #[serde(default, skip_serializing_if = "PageMetadata::is_empty")]
+    pub metadata: PageMetadata,
+    /// JSON-LD items the page embeds; only with `include_structured_data`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_data: Option<Vec<serde_json::Value>>,
+    /// Tables with row or column spans, as headers and rows; the markdown holds a
+    /// `[Table: see tables[N]]` marker where each stood. Only with `include_tables`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tables: Option<Vec<Table>>,
+    /// Headings of the extracted content in document order; omitted when there are none.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outline: Vec<Heading>,
     /// Extracted content as Markdown (if to_markdown=true).
     pub markdown: Option<String>,
     /// Extracted content as plain text (if to_markdown=false).
@@ -66,8 +132,15 @@ pub struct WebExtractOutput {
     pub links: Vec<ExtractedLink>,
     /// The extraction strategy that was used.
     pub strategy_used: String,
-    /// Word count of extracted content.
-    pub word_count: usize,
+    /// Word count, reading time, paragraph count, link density, and whether the
+    /// content looks like boilerplate rather than an article.
+    #[serde(flatten)]
+    pub quality: ContentQuality,
+    /// Approximate token count of the returned markdown or text.
+    pub approx_tokens: usize,
+    /// Whether the output was trimmed to fit `max_tokens`.
+    #[serde(default)]
+    pub truncated: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -77,12 +150,131 @@ pub struct ExtractedLink {
 }
 
 /// Implementation of the web_extract tool.
-pub async fn extract_impl(params: WebExtractParams) -> Result<CallToolResult, McpError> {
-    if params.html.is_empty() {
-        return Err(Error::InvalidInput("html cannot be empty".into()).into());
+pub async fn extract_impl(
+    db: &CacheDb, config: &AppConfig, mut params: WebExtractParams,
+) -> Result<CallToolResult, McpError> {
+    let strategy: ExtractStrategy = params.strategy.parse()?;
+    if !params.to_markdown && (params.selector.is_some() || params.section.is_some()) {
+        return Err(Error::InvalidInput("selector and section require to_markdown".into()).into());
     }
+    if let Some(selector) = &params.selector {
+        check_selector(selector)?;
+    }
+    match params.snapshot_hash.take() {
+        Some(_) if !params.html.is_empty() => {
+            return Err(Error::InvalidInput("provide either html or snapshot_hash, not both".into()).into());
+        }
+        Some(hash) => {
+            let (html, final_url) = stored_html(db, &hash).await?;
+            params.html = html;
+            params.base_url.get_or_insert(final_url);
+        }
+        None if params.html.is_empty() => {
+            return Err(Error::InvalidInput("html cannot be empty".into()).into());
+        }
+        None => {}
+    }
+
+    deadline::enter(Phase::Extract);
+    let limits = ExtractConfig::default();
+    let output = run_with_budget(limits.time_budget, move || {
+        check_dom(&params.html, limits.max_dom_depth, limits.max_dom_nodes)?;
+        let max_tokens = params.max_tokens;
+        let output = match strategy {
+            ExtractStrategy::Readability if params.fallback => {
+                with_fallback(&params, limits.fallback_min_chars.unwrap_or(0))
+            }
+            ExtractStrategy::Readability => extract_output(&params),
+            ExtractStrategy::PlainText => plain_text_output(&params),
+        }?;
+        let base_url = params.base_url.as_deref().and_then(|u| url::Url::parse(u).ok());
+        let metadata = page_metadata(&params.html, base_url.as_ref());
+        let structured_data = params
+            .include_structured_data
+            .then(|| extract_structured_data(&params.html));
+        let output = scope_output(output, &params)?;
+        Ok::<_, Error>(WebExtractOutput { metadata, structured_data, ..apply_max_tokens(output, max_tokens) })
+    })
+    .await?;
+
+    json_result(&output, config)
+}
 
-    let article = if let Some(ref tuning) = params.config {
+/// Load the stored body of snapshot `hash` as text, with its final URL.
+async fn stored_html(db: &CacheDb, hash: &str) -> Result<(String, String), Error> {
+    deadline::enter(Phase::Cache);
+    let snapshot = db
+        .get_snapshot(hash)
+        .await?
+        .ok_or_else(|| Error::CacheMiss(format!("no snapshot with hash {}", hash)))?;
+    let Some(bytes) = snapshot.raw_bytes.as_deref() else {
+        return Err(Error::InvalidInput(format!(
+            "snapshot {} has no stored body; open the URL with mode=raw or store_raw=true first",
+            hash
+        )));
+    };
+    Ok((decode_body(bytes, stored_charset(&snapshot)), snapshot.final_url))
+}
+
+/// Run readability, retrying as plain text when it fails or keeps fewer than
+/// `min_chars`; plain text is kept only if it finds more (blocking).
+fn with_fallback(params: &WebExtractParams, min_chars: usize) -> Result<WebExtractOutput, Error> {
+    let readable = extract_output(params);
+    let kept = match &readable {
+        Ok(output) if content_chars(output) >= min_chars => return readable,
+        Ok(output) => content_chars(output),
+        Err(Error::ExtractFailed(_)) => 0,
+        Err(_) => return readable,
+    };
+
+    match plain_text_output(params) {
+        Ok(plain) if content_chars(&plain) > kept => Ok(plain),
+        _ => readable,
+    }
+}
+
+/// Characters of extracted markdown or text, ignoring surrounding whitespace.
+fn content_chars(output: &WebExtractOutput) -> usize {
+    output
+        .markdown
+        .as_deref()
+        .or(output.text.as_deref())
+        .map_or(0, |content| content.trim().chars().count())
+}
+
+/// Cut the markdown down to the caller's `selector`, applied to the supplied
+/// HTML, and then to their `section`.
+fn scope_output(mut output: WebExtractOutput, params: &WebExtractParams) -> Result<WebExtractOutput, Error> {
+    let Some(markdown) = output.markdown.take() else {
+        return Ok(output);
+    };
+    let markdown = match &params.selector {
+        Some(selector) => scope_to_selector(&markdown, &params.html, selector)?,
+        None => markdown,
+    };
+    let markdown = match &params.section {
+        Some(section) => scope_to_section(&markdown, section)?,
+        None => markdown,
+    };
+    output.markdown = Some(markdown);
+    Ok(output)
+}
+
+/// Trim the markdown or text to `max_tokens` and count the tokens returned.
+fn apply_max_tokens(mut output: WebExtractOutput, max_tokens: Option<usize>) -> WebExtractOutput {
+    let content = if output.markdown.is_some() { &mut output.markdown } else { &mut output.text };
+    if let (Some(max_tokens), Some(text)) = (max_tokens, content.take()) {
+        let (text, truncated) = truncate_to_tokens(&text, max_tokens);
+        *content = Some(text);
+        output.truncated = truncated;
+    }
+    output.approx_tokens = content.as_deref().map_or(0, estimate_tokens);
+    output
+}
+
+/// Run readability over the supplied HTML (blocking).
+fn extract_output(params: &WebExtractParams) -> Result<WebExtractOutput, Error> {
+    let mut article = if let Some(ref tuning) = params.config {
         let mut config_builder = ReadabilityConfig::builder();
         if let Some(threshold) = tuning.char_threshold {
             config_builder = config_builder.char_threshold(threshold);
@@ -113,28 +305,65 @@ pub async fn extract_impl(params: WebExtractParams) -> Result<CallToolResult, Mc
     };
 
     let links = extract_links_from_html(&article.content, params.base_url.as_deref());
-
-    let (markdown, text) = if params.to_markdown {
-        let md = article
+    let outline = extract_outline(&article.content);
+    let quality = content_quality(&article.content);
+
+    let mut tables = TablePass::new(&article.content);
+    let content = if params.to_markdown {
+        let mut code = CodePass::new(&tables.html);
+        article.content = std::mem::take(&mut code.html);
+        let markdown = article
             .to_markdown()
             .map_err(|e| Error::ExtractFailed(format!("Markdown conversion failed: {}", e)))?;
-        (Some(md), None)
+        tables.splice(&code.splice(&markdown))
     } else {
-        (None, Some(article.to_text()))
+        article.to_text()
     };
+    let (markdown, text) = if params.to_markdown { (Some(content), None) } else { (None, Some(content)) };
 
     let output = WebExtractOutput {
         title: article.metadata.title,
+        metadata: PageMetadata::default(),
+        structured_data: None,
+        tables: params.include_tables.then_some(tables.tables),
+        outline,
         markdown,
         text,
         links,
-        strategy_used: params.strategy.clone(),
-        word_count: article.word_count,
+        strategy_used: ExtractStrategy::Readability.as_str().to_string(),
+        quality,
+        approx_tokens: 0,
+        truncated: false,
     };
 
-    Ok(CallToolResult::success(vec![Content::text(
-        serde_json::to_string_pretty(&output).unwrap_or_default(),
-    )]))
+    Ok(output)
+}
+
+/// Collect the visible text of the supplied HTML without scoring (blocking).
+///
+/// Markdown and text output are the same paragraphs; links come from the whole page.
+fn plain_text_output(params: &WebExtractParams) -> Result<WebExtractOutput, Error> {
+    let (title, content) = plain_text(&params.html);
+    if content.is_empty() {
+        return Err(Error::ExtractFailed("page has no visible text".into()));
+    }
+    let links = extract_links_from_html(&params.html, params.base_url.as_deref());
+    let (markdown, text) = if params.to_markdown { (Some(content), None) } else { (None, Some(content)) };
+
+    Ok(WebExtractOutput {
+        title,
+        metadata: PageMetadata::default(),
+        structured_data: None,
+        tables: params.include_tables.then(Vec::new),
+        outline: plain_outline(&params.html),
+        markdown,
+        text,
+        links,
+        strategy_used: ExtractStrategy::PlainText.as_str().to_string(),
+        quality: content_quality(&params.html),
+        approx_tokens: 0,
+        truncated: false,
+    })
 }
 
 /// Extract links from HTML content.
@@ -182,6 +411,7 @@ fn resolve_url(href: &str, base_url: Option<&str>) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use thndrs_core::{Mode, Snapshot};
 
     const TEST_HTML: &str = r#"
         <!DOCTYPE html>
@@ -213,35 +443,393 @@ mod tests {
 
     #[tokio::test]
     async fn test_extract_simple_article() {
+        let db = CacheDb::open_in_memory().await.unwrap();
         let params = WebExtractParams {
             html: TEST_HTML.into(),
+            snapshot_hash: None,
             base_url: Some("https://test.com".into()),
             strategy: "readability".into(),
+            fallback: true,
             to_markdown: true,
             config: Some(ExtractTuning { char_threshold: None, max_top_candidates: None, min_score: Some(15.0) }),
+            max_tokens: None,
+            include_structured_data: false,
+            include_tables: false,
+            selector: None,
+            section: None,
         };
 
-        let result = extract_impl(params).await;
+        let result = extract_impl(&db, &AppConfig::default(), params).await;
         assert!(result.is_ok(), "extraction should succeed");
 
         let call_result = result.unwrap();
         assert!(!call_result.is_error.unwrap_or(false));
     }
 
+    #[tokio::test]
+    async fn test_extract_max_tokens_truncates() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let params = WebExtractParams {
+            html: TEST_HTML.into(),
+            snapshot_hash: None,
+            base_url: None,
+            strategy: "readability".into(),
+            fallback: true,
+            to_markdown: true,
+            config: Some(ExtractTuning { char_threshold: None, max_top_candidates: None, min_score: Some(15.0) }),
+            max_tokens: Some(20),
+            include_structured_data: false,
+            include_tables: false,
+            selector: None,
+            section: None,
+        };
+
+        let result = extract_impl(&db, &AppConfig::default(), params).await.unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        let output: WebExtractOutput = serde_json::from_str(&text).unwrap();
+
+        assert!(output.truncated);
+        assert!(output.approx_tokens <= 20);
+        assert_eq!(
+            output.approx_tokens,
+            estimate_tokens(output.markdown.as_deref().unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extract_tables() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let paragraph = "<p>Pricing changed this quarter, so the tables below compare each plan with the \
+                         previous one and break sales down by region for the launch period.</p>";
+        let html = format!(
+            r#"<html><head><title>Pricing</title></head><body><article>{}
+            <table><thead><tr><th>Plan</th><th>Price</th></tr></thead>
+            <tbody><tr><td>Free</td><td>$0</td></tr><tr><td>Pro</td><td>$10</td></tr></tbody></table>
+            {}
+            <table><tr><th>Region</th><th colspan="2">Sales</th></tr><tr><td>EU</td><td>Q1</td><td>10</td></tr></table>
+            </article></body></html>"#,
+            paragraph.repeat(3),
+            paragraph.repeat(3)
+        );
+        let params = WebExtractParams { html, to_markdown: true, include_tables: true, ..short_page("readability") };
+
+        let result = extract_impl(&db, &AppConfig::default(), params).await.unwrap();
+        let output: WebExtractOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        let markdown = output.markdown.unwrap();
+        assert!(
+            markdown.contains("| Plan | Price |\n| --- | --- |\n| Free | $0 |\n| Pro | $10 |"),
+            "{markdown}"
+        );
+        assert!(markdown.contains("[Table: see tables[0]]"), "{markdown}");
+        let tables = output.tables.unwrap();
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].headers, ["Region", "Sales", "Sales"]);
+        assert_eq!(tables[0].rows, [["EU", "Q1", "10"]]);
+    }
+
+    #[tokio::test]
+    async fn test_extract_outline() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let paragraph = "<p>Installing from a release archive is the quickest way to start, while building \
+                         from source lets you enable the optional features described below.</p>";
+        let html = format!(
+            r#"<html><head><title>Guide</title></head><body><article>
+            <h2 id="install">Install</h2>{}<h3>From <em>source</em></h3>{}<h5>Notes</h5>{}
+            </article></body></html>"#,
+            paragraph.repeat(3),
+            paragraph.repeat(3),
+            paragraph.repeat(2)
+        );
+        let params = WebExtractParams { html, to_markdown: true, ..short_page("readability") };
+
+        let result = extract_impl(&db, &AppConfig::default(), params).await.unwrap();
+        let output: WebExtractOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(
+            output.outline,
+            [
+                Heading { level: 2, text: "Install".into(), id: Some("install".into()) },
+                Heading { level: 3, text: "From source".into(), id: None },
+                Heading { level: 5, text: "Notes".into(), id: None },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extract_scoped() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let paragraph = |topic: &str| {
+            format!(
+                "<p>{topic} takes a few minutes and is covered step by step here, with the options \
+                 that matter most called out along the way.</p>"
+            )
+        };
+        let html = format!(
+            r#"<html><head><title>Guide</title></head><body><article>
+            <section id="install"><h2>Installation</h2>{}<h3>From source</h3>{}</section>
+            <section id="usage"><h2>Usage</h2>{}</section>
+            </article></body></html>"#,
+            paragraph("Downloading a release").repeat(2),
+            paragraph("Building from source").repeat(2),
+            paragraph("Running the binary").repeat(2)
+        );
+        let scoped = |selector: Option<&str>, section: Option<&str>| WebExtractParams {
+            html: html.clone(),
+            to_markdown: true,
+            selector: selector.map(Into::into),
+            section: section.map(Into::into),
+            ..short_page("readability")
+        };
+        let markdown = |result: CallToolResult| {
+            let output: WebExtractOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+            output.markdown.unwrap()
+        };
+
+        let result = extract_impl(&db, &AppConfig::default(), scoped(None, Some("INSTALLATION")))
+            .await
+            .unwrap();
+        let section = markdown(result);
+        assert!(section.contains("section: \"INSTALLATION\"\n---\n"), "{section}");
+        assert!(
+            section.contains("Installation") && section.contains("From source"),
+            "{section}"
+        );
+        assert!(section.contains("Building from source"), "{section}");
+        assert!(!section.contains("Running the binary"), "{section}");
+
+        let result = extract_impl(&db, &AppConfig::default(), scoped(Some("#usage"), None))
+            .await
+            .unwrap();
+        let selected = markdown(result);
+        assert!(selected.contains("selector: \"#usage\"\n---\n"), "{selected}");
+        assert!(selected.contains("Running the binary"), "{selected}");
+        assert!(!selected.contains("Downloading a release"), "{selected}");
+
+        let err = extract_impl(&db, &AppConfig::default(), scoped(None, Some("Uninstall")))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("selector/section not found"), "{}", err.message);
+        assert!(
+            err.message.contains("\"Installation\", \"From source\", \"Usage\""),
+            "{}",
+            err.message
+        );
+
+        let err = extract_impl(&db, &AppConfig::default(), scoped(Some("#faq"), None))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("no element matches \"#faq\""), "{}", err.message);
+
+        let text_only = WebExtractParams { to_markdown: false, ..scoped(None, Some("Usage")) };
+        assert!(extract_impl(&db, &AppConfig::default(), text_only).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_extract_empty_html_fails() {
+        let db = CacheDb::open_in_memory().await.unwrap();
         let params = WebExtractParams {
             html: "".into(),
+            snapshot_hash: None,
             base_url: None,
             strategy: "readability".into(),
+            fallback: true,
             to_markdown: true,
             config: None,
+            max_tokens: None,
+            include_structured_data: false,
+            include_tables: false,
+            selector: None,
+            section: None,
         };
 
-        let result = extract_impl(params).await;
+        let result = extract_impl(&db, &AppConfig::default(), params).await;
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_extract_deeply_nested_html_fails_fast() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let params = WebExtractParams {
+            html: format!("<html><body>{}deep</body></html>", "<div>".repeat(100_000)),
+            snapshot_hash: None,
+            base_url: None,
+            strategy: "readability".into(),
+            fallback: true,
+            to_markdown: true,
+            config: None,
+            max_tokens: None,
+            include_structured_data: false,
+            include_tables: false,
+            selector: None,
+            section: None,
+        };
+
+        let start = std::time::Instant::now();
+        let err = extract_impl(&db, &AppConfig::default(), params).await.unwrap_err();
+        assert!(err.message.contains("nesting exceeds"));
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    const SHORT_PAGE: &str = r#"<html><head><title>Moved</title></head><body>
+        <nav><a href="/">Home</a></nav>
+        <p>This page moved to <a href="https://example.com/new">the new docs</a>.</p>
+        </body></html>"#;
+
+    fn short_page(strategy: &str) -> WebExtractParams {
+        WebExtractParams {
+            html: SHORT_PAGE.into(),
+            snapshot_hash: None,
+            base_url: None,
+            strategy: strategy.into(),
+            fallback: false,
+            to_markdown: false,
+            config: None,
+            max_tokens: None,
+            include_structured_data: false,
+            include_tables: false,
+            selector: None,
+            section: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plain_text_extracts_short_page() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        assert!(
+            extract_impl(&db, &AppConfig::default(), short_page("readability"))
+                .await
+                .is_err()
+        );
+
+        let result = extract_impl(&db, &AppConfig::default(), short_page("plain_text"))
+            .await
+            .unwrap();
+        let output: WebExtractOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(output.strategy_used, "plain_text");
+        assert_eq!(output.title.as_deref(), Some("Moved"));
+        assert_eq!(output.text.as_deref(), Some("This page moved to the new docs."));
+        assert_eq!(output.quality.word_count, 7);
+        assert!(output.links.iter().any(|l| l.href == "https://example.com/new"));
+    }
+
+    #[tokio::test]
+    async fn test_extract_reports_page_metadata() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let html = SHORT_PAGE.replace(
+            "<head>",
+            r#"<head><meta property="og:description" content="Moved."><link rel="canonical" href="/docs">"#,
+        );
+        let params =
+            WebExtractParams { html, base_url: Some("https://example.com/old?x=1".into()), ..short_page("plain_text") };
+        let result = extract_impl(&db, &AppConfig::default(), params).await.unwrap();
+        let output: WebExtractOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(output.metadata.description.as_deref(), Some("Moved."));
+        assert_eq!(
+            output.metadata.canonical_url.as_deref(),
+            Some("https://example.com/docs")
+        );
+
+        let result = extract_impl(&db, &AppConfig::default(), short_page("plain_text"))
+            .await
+            .unwrap();
+        assert!(!result.content[0].as_text().unwrap().text.contains("metadata"));
+    }
+
+    #[tokio::test]
+    async fn test_extract_structured_data_on_request() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let html = SHORT_PAGE.replace(
+            "</head>",
+            r#"<script type="application/ld+json">[{"@type": "Article", "author": "Ada"}, {"@type": "HowTo"}]</script>
+            <script type="application/ld+json">{ broken </script></head>"#,
+        );
+        let params = WebExtractParams { html, include_structured_data: true, ..short_page("plain_text") };
+        let result = extract_impl(&db, &AppConfig::default(), params).await.unwrap();
+        let output: WebExtractOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        let items = output.structured_data.unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(output.metadata.author.as_deref(), Some("Ada"));
+        assert_eq!(output.text.as_deref(), Some("This page moved to the new docs."));
+    }
+
+    #[tokio::test]
+    async fn test_readability_falls_back_to_plain_text() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let params = WebExtractParams { fallback: true, max_tokens: Some(4), ..short_page("readability") };
+        let result = extract_impl(&db, &AppConfig::default(), params).await.unwrap();
+        let output: WebExtractOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(output.strategy_used, "plain_text");
+        assert_eq!(output.quality.word_count, 7);
+        assert!(output.truncated);
+        assert!(output.approx_tokens <= 4);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_strategy_is_rejected() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let err = extract_impl(&db, &AppConfig::default(), short_page("bogus"))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("unsupported strategy: bogus"), "{}", err.message);
+    }
+
+    fn stored_page(hash: &str, raw_bytes: Option<Vec<u8>>) -> Snapshot {
+        Snapshot { hash: hash.into(), mode: Mode::Raw, raw_bytes, ..Snapshot::test_page("https://test.com/blog/post") }
+    }
+
+    fn from_snapshot(hash: &str) -> WebExtractParams {
+        WebExtractParams {
+            html: String::new(),
+            snapshot_hash: Some(hash.into()),
+            base_url: None,
+            strategy: "readability".into(),
+            fallback: true,
+            to_markdown: true,
+            config: Some(ExtractTuning { char_threshold: None, max_top_candidates: None, min_score: Some(15.0) }),
+            max_tokens: None,
+            include_structured_data: false,
+            include_tables: false,
+            selector: None,
+            section: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extract_from_snapshot_uses_final_url() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        db.upsert_snapshot(&stored_page("h1", Some(TEST_HTML.as_bytes().to_vec())))
+            .await
+            .unwrap();
+
+        let result = extract_impl(&db, &AppConfig::default(), from_snapshot("h1"))
+            .await
+            .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        let output: WebExtractOutput = serde_json::from_str(&text).unwrap();
+
+        assert!(output.quality.word_count > 0);
+        assert!(output.links.iter().any(|l| l.href == "https://test.com/about"));
+    }
+
+    #[tokio::test]
+    async fn test_extract_from_snapshot_errors() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        db.upsert_snapshot(&stored_page("bare", None)).await.unwrap();
+
+        let err = extract_impl(&db, &AppConfig::default(), from_snapshot("missing"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code.0, -32001);
+
+        let err = extract_impl(&db, &AppConfig::default(), from_snapshot("bare"))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("no stored body"));
+
+        let both = WebExtractParams { html: TEST_HTML.into(), ..from_snapshot("bare") };
+        let err = extract_impl(&db, &AppConfig::default(), both).await.unwrap_err();
+        assert!(err.message.contains("not both"));
+    }
+
     #[test]
     fn test_resolve_url_absolute() {
         let resolved = resolve_url("https://other.com/page", Some("https://example.com"));
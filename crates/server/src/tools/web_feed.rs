@@ -0,0 +1,462 @@
+//! web_feed tool implementation.
+//!
+//! Fetches an RSS or Atom feed and returns its entries as structured JSON,
+//! optionally only those published since a date. The feed body is cached as a
+//! snapshot in mode "feed" and reparsed on cache hits, so a site's feed is
+//! fetched at most once an hour. Pointing web_feed at an HTML page fails with
+//! the feeds that page advertises, if any.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rmcp::{ErrorData as McpError, model::*};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thndrs_client::{
+    ExtractedDoc, FEED_EXTRACTOR_VERSION, Feed, FetchClient, OpenOptions, PIPELINE_VERSION, WebPipeline,
+    discover_feeds,
+    fetch::canonicalize_with_options,
+    is_feed, link_kind, normalize_markdown, parse_feed,
+    pipeline::{CacheVary, cache_key, decode_body},
+};
+use thndrs_core::cache::{Link, LinkKind, StoredLinks, snapshots::url_host};
+use thndrs_core::{AppConfig, CacheDb, DomainPolicy, Error, Mode, Snapshot};
+use url::Url;
+
+use crate::tools::deadline::{self, Phase};
+use crate::tools::output::json_result;
+use crate::tools::web_open::{canonicalize_options, snapshot_expiry, store_snapshot};
+
+/// Longest a feed stays cached, whatever the snapshot TTL.
+const FEED_TTL_SECONDS: u64 = 60 * 60;
+
+/// Upper bound on `limit`.
+const MAX_LIMIT: usize = 200;
+
+/// Input parameters for web_feed tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebFeedParams {
+    /// URL of an RSS or Atom feed.
+    pub url: String,
+
+    /// Maximum number of entries to return (default: 20, max: 200).
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+
+    /// Only include entries published after this date (YYYY-MM-DD or RFC 3339).
+    /// Entries without a date are left out.
+    #[serde(default)]
+    pub since: Option<String>,
+
+    /// Bypass the cache.
+    #[serde(default)]
+    pub force_refresh: bool,
+}
+
+fn default_limit() -> usize {
+    20
+}
+
+/// One entry of a feed.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FeedItem {
+    pub title: Option<String>,
+    pub link: Option<String>,
+    /// Publication time (RFC 3339), falling back to the last update.
+    pub published: Option<String>,
+    /// Summary or content as plain text, cut at 500 characters.
+    pub summary: Option<String>,
+}
+
+/// Output structure for web_feed tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebFeedOutput {
+    pub url: String,
+    pub final_url: String,
+    /// `rss`, `atom`, or `json`.
+    pub kind: String,
+    pub title: Option<String>,
+    /// The site the feed belongs to.
+    pub link: Option<String>,
+    /// Entries in the feed.
+    pub total_entries: usize,
+    /// Entries matching `since`.
+    pub matched: usize,
+    /// The first `limit` matching entries, in feed order.
+    pub entries: Vec<FeedItem>,
+    /// Snapshot hash the feed is cached under.
+    pub hash: String,
+    pub fetched_at: String,
+    pub cache_hit: bool,
+    /// Set when the feed could not be saved to the cache.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cache_write_failed: bool,
+}
+
+/// Implementation of the web_feed tool.
+///
+/// Fetches through the shared client, like web_open.
+pub async fn feed_impl(
+    db: &CacheDb, config: &AppConfig, fetch: &Arc<FetchClient>, params: WebFeedParams,
+) -> Result<CallToolResult, McpError> {
+    if params.url.is_empty() {
+        return Err(Error::InvalidInput("url cannot be empty".into()).into());
+    }
+    if params.limit == 0 || params.limit > MAX_LIMIT {
+        return Err(Error::InvalidInput(format!("limit must be between 1 and {MAX_LIMIT}")).into());
+    }
+    let since = params
+        .since
+        .as_deref()
+        .map(|d| parse_since(d).ok_or_else(|| Error::InvalidInput(format!("invalid since: {d}"))))
+        .transpose()?;
+
+    let url = canonicalize_with_options(&params.url, &canonicalize_options(config))
+        .map_err(|e| Error::InvalidUrl(e.to_string()))?;
+    let hash = cache_key(&url, &CacheVary::default(), Mode::Feed);
+
+    deadline::enter(Phase::Cache);
+    let cached = match params.force_refresh {
+        true => None,
+        false => cached_feed(db, config, &hash).await?,
+    };
+
+    let (feed, final_url, fetched_at, cache_hit, cache_write_failed) = match cached {
+        Some((feed, snapshot)) => {
+            tracing::debug!("feed cache hit for {}", url);
+            (feed, snapshot.final_url, snapshot.fetched_at, true, false)
+        }
+        None => {
+            deadline::enter(Phase::Fetch);
+            let options = OpenOptions { fetched_at: Some(db.now()), ..Default::default() };
+            let result = WebPipeline::shared(fetch.clone()).fetch(url.as_str(), &options).await?;
+            let response = &result.response;
+
+            deadline::enter(Phase::Extract);
+            if !is_feed(response.content_type.as_deref(), &response.bytes) {
+                return Err(not_a_feed(
+                    &url,
+                    &response.final_url,
+                    &decode_body(&response.bytes, result.charset.as_deref()),
+                )
+                .into());
+            }
+            let feed = parse_feed(&response.bytes, &response.final_url)?;
+
+            let fetched_at = result.fetched_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+            let links = feed
+                .entries
+                .iter()
+                .filter_map(|entry| {
+                    let href = entry.link.clone()?;
+                    let kind = Url::parse(&href).map_or(LinkKind::External, |url| link_kind(&url, &response.final_url));
+                    let text = entry.title.clone().unwrap_or_default();
+                    Some(Link { text, href, kind, in_content: true })
+                })
+                .collect();
+            let doc = ExtractedDoc {
+                title: feed.title.clone(),
+                markdown: feed.to_markdown(),
+                extractor_version: FEED_EXTRACTOR_VERSION.to_string(),
+            };
+            let snapshot = Snapshot {
+                hash: hash.clone(),
+                url: url.to_string(),
+                final_url: response.final_url.to_string(),
+                mode: Mode::Feed,
+                content_type: response.content_type.clone(),
+                status_code: Some(response.status.as_u16() as i32),
+                fetched_at: fetched_at.clone(),
+                expires_at: snapshot_expiry(result.fetched_at, &response.stored_headers, feed_ttl(config)),
+                etag: result.etag.clone(),
+                last_modified: result.last_modified.clone(),
+                // Kept so cache hits can return entries without refetching.
+                raw_bytes: Some(response.bytes.to_vec()),
+                raw_truncated: false,
+                title: feed.title.clone(),
+                markdown: Some(normalize_markdown(&doc, &response.final_url, &result.fetched_at, None)),
+                text: None,
+                links_json: Some(StoredLinks::new(links).to_json()),
+                metadata_json: None,
+                structured_data_json: None,
+                images_json: None,
+                tables_json: None,
+                outline_json: None,
+                quality_json: None,
+                content_hash: Some(result.content_hash.clone()),
+                extractor_name: Some("feed-rs".to_string()),
+                extractor_version: Some(doc.extractor_version),
+                pipeline_version: Some(PIPELINE_VERSION),
+                detected_charset: None,
+                siteconfig_id: None,
+                extract_cfg_json: None,
+                headers_json: serde_json::to_string(&response.stored_headers).ok(),
+                fetch_ms: Some(response.fetch_ms as i64),
+                extract_ms: None,
+            };
+            let cache_write_failed = store_snapshot(db, snapshot).await;
+            (
+                feed,
+                response.final_url.to_string(),
+                fetched_at,
+                false,
+                cache_write_failed,
+            )
+        }
+    };
+
+    let total_entries = feed.entries.len();
+    let matching: Vec<FeedItem> = feed
+        .entries
+        .into_iter()
+        .filter(|entry| match since {
+            Some(since) => entry.published_at().is_some_and(|published| published > since),
+            None => true,
+        })
+        .map(|entry| FeedItem {
+            title: entry.title,
+            link: entry.link,
+            published: entry.published,
+            summary: entry.summary,
+        })
+        .collect();
+
+    let output = WebFeedOutput {
+        url: url.to_string(),
+        final_url,
+        kind: feed.kind,
+        title: feed.title,
+        link: feed.link,
+        total_entries,
+        matched: matching.len(),
+        entries: matching.into_iter().take(params.limit).collect(),
+        hash,
+        fetched_at,
+        cache_hit,
+        cache_write_failed,
+    };
+
+    json_result(&output, config)
+}
+
+/// A fresh cached feed and its snapshot, or None to fetch.
+///
+/// The domain policy may have changed since the feed was cached, so every host
+/// it touched is checked again.
+async fn cached_feed(db: &CacheDb, config: &AppConfig, hash: &str) -> Result<Option<(Feed, Snapshot)>, Error> {
+    if !db.is_snapshot_fresh(hash).await.unwrap_or(false) {
+        return Ok(None);
+    }
+    let Some(snapshot) = db.get_snapshot(hash).await.ok().flatten() else {
+        return Ok(None);
+    };
+
+    let policy = DomainPolicy::from_config(config);
+    for host in [url_host(&snapshot.url), url_host(&snapshot.final_url)]
+        .into_iter()
+        .flatten()
+    {
+        policy.check(&host)?;
+    }
+
+    let feed = snapshot
+        .raw_bytes
+        .as_deref()
+        .zip(Url::parse(&snapshot.final_url).ok())
+        .and_then(|(bytes, final_url)| parse_feed(bytes, &final_url).ok());
+    Ok(feed.map(|feed| (feed, snapshot)))
+}
+
+/// How long a feed stays fresh: the snapshot TTL, capped at an hour even when snapshots never expire.
+fn feed_ttl(config: &AppConfig) -> u64 {
+    match config.snapshot_ttl_seconds {
+        0 => FEED_TTL_SECONDS,
+        ttl => ttl.min(FEED_TTL_SECONDS),
+    }
+}
+
+/// The error for a body that is not a feed, naming the feeds an HTML page advertises.
+fn not_a_feed(url: &Url, final_url: &Url, body: &str) -> Error {
+    let advertised: Vec<String> = discover_feeds(body, final_url)
+        .into_iter()
+        .map(|feed| feed.href)
+        .collect();
+    match advertised.is_empty() {
+        true => Error::UnsupportedContentType(format!("{url} is not an RSS or Atom feed")),
+        false => Error::UnsupportedContentType(format!(
+            "{url} is not an RSS or Atom feed; it links to: {}",
+            advertised.join(", ")
+        )),
+    }
+}
+
+/// Parse `since`: a full RFC 3339 timestamp or a bare date (midnight UTC).
+fn parse_since(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|dt| dt.and_utc())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+    use crate::tools::web_open::fetch_client;
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel>
+  <title>Example Blog</title>
+  <link>https://blog.example.com/</link>
+  <item>
+    <title>Third post</title>
+    <link>https://blog.example.com/posts/3</link>
+    <pubDate>Mon, 02 Sep 2024 09:00:00 GMT</pubDate>
+    <description>&lt;p&gt;The &lt;b&gt;third&lt;/b&gt; one.&lt;/p&gt;</description>
+  </item>
+  <item>
+    <title>Second post</title>
+    <link>https://blog.example.com/posts/2</link>
+    <pubDate>Sat, 01 Jun 2024 09:00:00 GMT</pubDate>
+  </item>
+  <item>
+    <title>Undated post</title>
+    <link>https://blog.example.com/posts/1</link>
+  </item>
+</channel></rss>"#;
+
+    const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Atom Site</title>
+  <id>urn:example:feed</id>
+  <updated>2024-03-01T00:00:00Z</updated>
+  <entry>
+    <title>Only entry</title>
+    <id>urn:example:1</id>
+    <link href="https://atom.example.com/1"/>
+    <updated>2024-03-01T00:00:00Z</updated>
+    <summary>Short summary.</summary>
+  </entry>
+</feed>"#;
+
+    const PAGE: &str =
+        r#"<html><head><link rel="alternate" type="application/rss+xml" href="/rss"></head><body>Hi</body></html>"#;
+
+    async fn feed_server(hits: Arc<AtomicUsize>) -> FixtureServer {
+        FixtureServer::start(move |req| {
+            hits.fetch_add(1, Ordering::SeqCst);
+            match req.path.as_str() {
+                "/rss" => FixtureResponse::ok("application/rss+xml", RSS),
+                "/atom" => FixtureResponse::ok("text/xml", ATOM),
+                "/" => FixtureResponse::html(PAGE),
+                _ => FixtureResponse::status(404),
+            }
+        })
+        .await
+    }
+
+    fn params(url: String) -> WebFeedParams {
+        WebFeedParams { url, limit: 20, since: None, force_refresh: false }
+    }
+
+    fn test_config() -> AppConfig {
+        AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() }
+    }
+
+    async fn run(db: &CacheDb, params: WebFeedParams) -> Result<WebFeedOutput, McpError> {
+        let config = test_config();
+        let fetch = Arc::new(fetch_client(&config, db, &Default::default()).unwrap());
+        let result = feed_impl(db, &config, &fetch, params).await?;
+        Ok(serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_rss_entries_and_cache() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let server = feed_server(hits.clone()).await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+
+        let output = run(&db, params(server.url("/rss"))).await.unwrap();
+        assert_eq!(output.kind, "rss");
+        assert_eq!(output.title.as_deref(), Some("Example Blog"));
+        assert_eq!(output.total_entries, 3);
+        assert_eq!(output.matched, 3);
+        assert!(!output.cache_hit);
+        let first = &output.entries[0];
+        assert_eq!(first.title.as_deref(), Some("Third post"));
+        assert_eq!(first.link.as_deref(), Some("https://blog.example.com/posts/3"));
+        assert_eq!(first.published.as_deref(), Some("2024-09-02T09:00:00Z"));
+        assert_eq!(first.summary.as_deref(), Some("The third one."));
+
+        let snapshot = db.get_snapshot(&output.hash).await.unwrap().unwrap();
+        assert_eq!(snapshot.mode, Mode::Feed);
+        assert!(snapshot.markdown.unwrap().contains("## [Third post]("));
+        assert!(snapshot.expires_at.is_some());
+
+        let again = run(&db, params(server.url("/rss"))).await.unwrap();
+        assert!(again.cache_hit);
+        assert_eq!(again.total_entries, 3);
+        assert_eq!(
+            hits.load(Ordering::SeqCst),
+            1,
+            "second call should be served from the cache"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_since_and_limit() {
+        let server = feed_server(Arc::new(AtomicUsize::new(0))).await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+
+        let recent = WebFeedParams { since: Some("2024-05-01".into()), ..params(server.url("/rss")) };
+        let output = run(&db, recent).await.unwrap();
+        assert_eq!(output.total_entries, 3);
+        assert_eq!(output.matched, 2, "undated entries are left out");
+
+        let limited = WebFeedParams { limit: 1, ..params(server.url("/rss")) };
+        let output = run(&db, limited).await.unwrap();
+        assert_eq!(output.matched, 3);
+        assert_eq!(output.entries.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_atom_served_as_xml() {
+        let server = feed_server(Arc::new(AtomicUsize::new(0))).await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+
+        let output = run(&db, params(server.url("/atom"))).await.unwrap();
+        assert_eq!(output.kind, "atom");
+        assert_eq!(output.entries.len(), 1);
+        assert_eq!(output.entries[0].link.as_deref(), Some("https://atom.example.com/1"));
+        assert_eq!(output.entries[0].summary.as_deref(), Some("Short summary."));
+    }
+
+    #[tokio::test]
+    async fn test_html_page_names_its_feeds() {
+        let server = feed_server(Arc::new(AtomicUsize::new(0))).await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+
+        let err = run(&db, params(server.url("/"))).await.unwrap_err();
+        assert!(err.message.contains("is not an RSS or Atom feed"), "{}", err.message);
+        assert!(err.message.contains(&server.url("/rss")), "{}", err.message);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_params() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+
+        let bad_limit = WebFeedParams { limit: 0, ..params("https://example.com/feed".into()) };
+        assert!(run(&db, bad_limit).await.is_err());
+
+        let bad_since = WebFeedParams { since: Some("last week".into()), ..params("https://example.com/feed".into()) };
+        assert!(run(&db, bad_since).await.is_err());
+    }
+}
@@ -0,0 +1,257 @@
+//! web_image_search tool implementation.
+//!
+//! Searches Brave's image index through the web_search client, caching
+//! results in `search_cache` under `images:`-prefixed keys.
+
+use rmcp::{ErrorData as McpError, model::*};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thndrs_client::{BraveClient, ImageSearchRequest, SafeSearch};
+use thndrs_core::{AppConfig, CacheDb, DomainPolicy, Error};
+
+use crate::tools::deadline::{self, Phase};
+use crate::tools::output::json_result;
+use crate::tools::web_search::{
+    DebugInfo, QueryMeta, cached_search, host_allowed, require_client, search_error, usage_report,
+};
+
+/// Input parameters for web_image_search tool.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct WebImageSearchParams {
+    /// Search query (required).
+    pub query: String,
+
+    /// Number of results (1-100, default 20).
+    #[serde(default = "default_count")]
+    pub count: Option<u8>,
+
+    /// Safe search: off or strict (default). Brave has no moderate level for images.
+    #[serde(default)]
+    pub safesearch: Option<String>,
+
+    /// Country code (ISO 3166-1 alpha-2, e.g., "US").
+    #[serde(default)]
+    pub country: Option<String>,
+
+    /// Content language (ISO 639-1, e.g., "en").
+    #[serde(default)]
+    pub search_lang: Option<String>,
+
+    /// Force a refresh, bypassing the cache.
+    #[serde(default)]
+    pub force_refresh: bool,
+
+    /// Optional allowlist for the pages images appear on; narrows, never widens, the server domain policy.
+    #[serde(default)]
+    pub domain_allowlist: Option<Vec<String>>,
+}
+
+fn default_count() -> Option<u8> {
+    Some(20)
+}
+
+/// Output structure for web_image_search tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebImageSearchOutput {
+    /// The image results.
+    pub results: Vec<ImageResult>,
+    /// Query metadata.
+    pub query: QueryMeta,
+    /// Debug information.
+    pub debug: DebugInfo,
+}
+
+/// Individual image result.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ImageResult {
+    /// Image title.
+    pub title: String,
+    /// Full-size image URL.
+    pub url: String,
+    /// Page the image appears on.
+    pub source_url: String,
+    /// Width in pixels, when Brave reports it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    /// Height in pixels, when Brave reports it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// Thumbnail URL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+    /// Result rank (1-indexed).
+    pub rank: usize,
+}
+
+/// Implementation of the web_image_search tool.
+pub async fn image_search_impl(
+    db: &CacheDb, config: &AppConfig, brave: Option<&BraveClient>, params: WebImageSearchParams,
+) -> Result<CallToolResult, McpError> {
+    if params.query.is_empty() {
+        return Err(Error::InvalidInput("query cannot be empty".into()).into());
+    }
+
+    let safesearch = match params.safesearch.as_deref() {
+        Some("off") => SafeSearch::Off,
+        Some("strict") | None => SafeSearch::Strict,
+        Some(other) => {
+            return Err(
+                Error::InvalidInput(format!("invalid safesearch: {} (images support off or strict)", other)).into(),
+            );
+        }
+    };
+
+    let req = ImageSearchRequest {
+        q: params.query.clone(),
+        count: params.count,
+        safesearch: Some(safesearch),
+        country: params.country,
+        search_lang: params.search_lang,
+        spellcheck: None,
+    };
+
+    req.validate().map_err(|e| Error::InvalidInput(e.to_string()))?;
+
+    let cache_key = BraveClient::image_cache_key(&req);
+    let ttl = BraveClient::ttl_for_freshness(&None);
+
+    let mut policy = DomainPolicy::from_config(config);
+    if let Some(allowlist) = &params.domain_allowlist {
+        policy = policy.with_request_allowlist(allowlist);
+    }
+
+    deadline::enter(Phase::Cache);
+    let cached: Option<WebImageSearchOutput> =
+        if params.force_refresh { None } else { cached_search(db, &cache_key).await };
+    if let Some(mut output) = cached {
+        tracing::debug!("cache hit for image query: {}", params.query);
+        if let Err(e) = db.record_brave_cache_hit().await {
+            tracing::warn!("failed to record image cache hit: {}", e);
+        }
+        filter_by_domains(&mut output, &policy);
+        output.debug.cache_hit = Some(true);
+        output.debug.attempts = None;
+        output.debug.usage = usage_report(db, config).await;
+        return json_result(&output, config);
+    }
+
+    let client = require_client(brave, config)?;
+    deadline::enter(Phase::Fetch);
+    let response = client.search_images(req).await.map_err(search_error)?;
+    if let Err(e) = db.record_brave_request().await {
+        tracing::warn!("failed to record Brave request: {}", e);
+    }
+
+    let mut output = WebImageSearchOutput {
+        results: response
+            .results
+            .into_iter()
+            .map(|r| ImageResult {
+                title: r.title,
+                url: r.url,
+                source_url: r.source_url,
+                width: r.width,
+                height: r.height,
+                thumbnail: r.thumbnail,
+                rank: r.rank,
+            })
+            .collect(),
+        query: response.query.into(),
+        debug: DebugInfo {
+            request_id: response.debug.request_id,
+            cache_hit: Some(false),
+            usage: None,
+            sorted_by: None,
+            attempts: Some(response.debug.attempts).filter(|n| *n > 1),
+        },
+    };
+
+    match (serde_json::to_string(&params.query), serde_json::to_string(&output)) {
+        (Ok(query_json), Ok(response_json)) => {
+            let writer = db.clone();
+            let write =
+                deadline::cache_write(
+                    async move { writer.put_search(&cache_key, &query_json, &response_json, ttl).await },
+                );
+            if let Err(e) = write.await {
+                tracing::warn!("failed to cache image result: {}", e);
+            }
+        }
+        (Err(e), _) | (_, Err(e)) => tracing::warn!("failed to serialize image result for caching: {}", e),
+    }
+
+    filter_by_domains(&mut output, &policy);
+    output.debug.usage = usage_report(db, config).await;
+
+    json_result(&output, config)
+}
+
+/// Drop images whose source page the domain policy denies; results are cached unfiltered.
+fn filter_by_domains(output: &mut WebImageSearchOutput, policy: &DomainPolicy) {
+    if !policy.is_unrestricted() {
+        output.results.retain(|r| host_allowed(&r.source_url, policy));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rejects_moderate_safesearch() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let params =
+            WebImageSearchParams { query: "crab".into(), safesearch: Some("moderate".into()), ..Default::default() };
+
+        let err = image_search_impl(&db, &AppConfig::default(), None, params)
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("invalid safesearch"), "{}", err.message);
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit_filters_source_pages() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { denylist_domains: vec!["blocked.example.org".into()], ..Default::default() };
+
+        let image = |rank: usize, source_url: &str| ImageResult {
+            title: format!("Image {rank}"),
+            url: format!("https://imgs.example.com/{rank}.png"),
+            source_url: source_url.to_string(),
+            width: None,
+            height: None,
+            thumbnail: None,
+            rank,
+        };
+        let cached = WebImageSearchOutput {
+            results: vec![
+                image(1, "https://blocked.example.org/a"),
+                image(2, "https://ok.example.com/b"),
+            ],
+            query: QueryMeta { original: "crab".into(), ..Default::default() },
+            debug: DebugInfo { request_id: None, cache_hit: Some(false), usage: None, sorted_by: None, attempts: None },
+        };
+        let req = ImageSearchRequest {
+            q: "crab".into(),
+            count: Some(20),
+            safesearch: Some(SafeSearch::Strict),
+            ..Default::default()
+        };
+        db.put_search(
+            &BraveClient::image_cache_key(&req),
+            "\"crab\"",
+            &serde_json::to_string(&cached).unwrap(),
+            BraveClient::ttl_for_freshness(&None),
+        )
+        .await
+        .unwrap();
+
+        let params = WebImageSearchParams { query: "crab".into(), count: Some(20), ..Default::default() };
+        let result = image_search_impl(&db, &config, None, params).await.unwrap();
+        let output: WebImageSearchOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+
+        assert_eq!(output.debug.cache_hit, Some(true));
+        let ranks: Vec<usize> = output.results.iter().map(|r| r.rank).collect();
+        assert_eq!(ranks, vec![2]);
+    }
+}
@@ -0,0 +1,337 @@
+//! web_news_search tool implementation.
+//!
+//! Searches Brave's news index for time-sensitive results. Shares the
+//! web_search client, cache table, and result shaping, but caches under
+//! `news:`-prefixed keys with a shorter default TTL.
+
+use rmcp::{ErrorData as McpError, model::*};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thndrs_client::{BraveClient, NewsSearchRequest};
+use thndrs_core::{AppConfig, CacheDb, DomainPolicy, Error};
+
+use crate::tools::deadline::{self, Phase};
+use crate::tools::output::json_result;
+use crate::tools::web_search::{
+    DebugInfo, QueryMeta, cached_search, host_allowed, limit_snippets, newest_first, parse_safesearch, parse_sort,
+    require_client, search_error, usage_report,
+};
+
+/// Input parameters for web_news_search tool.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct WebNewsSearchParams {
+    /// Search query (required).
+    pub query: String,
+
+    /// Number of results (1-50, default 20).
+    #[serde(default = "default_count")]
+    pub count: Option<u8>,
+
+    /// Page offset (0-9, default 0).
+    #[serde(default)]
+    pub offset: Option<u8>,
+
+    /// Freshness filter: pd (past day), pw (past week), pm (past month), py (past year).
+    #[serde(default)]
+    pub freshness: Option<String>,
+
+    /// Safe search: off, moderate (default), strict.
+    #[serde(default)]
+    pub safesearch: Option<String>,
+
+    /// Country code (ISO 3166-1 alpha-2, e.g., "US").
+    #[serde(default)]
+    pub country: Option<String>,
+
+    /// Content language (ISO 639-1, e.g., "en").
+    #[serde(default)]
+    pub search_lang: Option<String>,
+
+    /// UI/response metadata language (e.g., "en-US").
+    #[serde(default)]
+    pub ui_lang: Option<String>,
+
+    /// Include extra snippets per result (default false); capped by the server's
+    /// snippet count and length limits.
+    #[serde(default)]
+    pub extra_snippets: Option<bool>,
+
+    /// Let Brave correct the query's spelling (default true).
+    #[serde(default)]
+    pub spellcheck: Option<bool>,
+
+    /// Force a refresh, bypassing the cache.
+    #[serde(default)]
+    pub force_refresh: bool,
+
+    /// Optional domain allowlist to filter results; narrows, never widens, the server domain policy.
+    #[serde(default)]
+    pub domain_allowlist: Option<Vec<String>>,
+
+    /// Result order: relevance (default, Brave's ranking) or date (newest `published_at` first, undated last).
+    #[serde(default)]
+    pub sort: Option<String>,
+}
+
+fn default_count() -> Option<u8> {
+    Some(20)
+}
+
+/// Output structure for web_news_search tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebNewsSearchOutput {
+    /// The news results.
+    pub results: Vec<NewsResult>,
+    /// Query metadata.
+    pub query: QueryMeta,
+    /// Debug information.
+    pub debug: DebugInfo,
+}
+
+/// Individual news result.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NewsResult {
+    /// Article title.
+    pub title: String,
+    /// Article URL.
+    pub url: String,
+    /// Article description/snippet.
+    pub description: String,
+    /// Extra snippets (if requested).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_snippets: Vec<String>,
+    /// Age as reported by Brave, e.g. "3 hours ago".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub age: Option<String>,
+    /// Publication date normalized to RFC3339 UTC, when Brave's age could be parsed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<String>,
+    /// Publisher host, e.g. "www.example.com".
+    pub source: String,
+    /// Article preview image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail_url: Option<String>,
+    /// Result rank (1-indexed).
+    pub rank: usize,
+}
+
+/// Implementation of the web_news_search tool.
+///
+/// Mirrors [`search_impl`](crate::tools::web_search::search_impl) against
+/// Brave's news endpoint, through the same shared client.
+pub async fn news_search_impl(
+    db: &CacheDb, config: &AppConfig, brave: Option<&BraveClient>, params: WebNewsSearchParams,
+) -> Result<CallToolResult, McpError> {
+    if params.query.is_empty() {
+        return Err(Error::InvalidInput("query cannot be empty".into()).into());
+    }
+
+    let safesearch = parse_safesearch(params.safesearch.as_deref())?;
+    let sort_by_date = parse_sort(params.sort.as_deref())?;
+
+    let ttl = BraveClient::news_ttl_for_freshness(&params.freshness);
+    let want_snippets = params.extra_snippets.unwrap_or(false);
+
+    let req = NewsSearchRequest {
+        q: params.query.clone(),
+        count: params.count,
+        offset: params.offset,
+        freshness: params.freshness,
+        safesearch,
+        country: params.country,
+        search_lang: params.search_lang,
+        ui_lang: params.ui_lang,
+        extra_snippets: params.extra_snippets,
+        spellcheck: params.spellcheck,
+    };
+
+    req.validate().map_err(|e| Error::InvalidInput(e.to_string()))?;
+
+    let cache_key = BraveClient::news_cache_key(&req);
+    let snippets_key = (req.extra_snippets != Some(true))
+        .then(|| BraveClient::news_cache_key(&NewsSearchRequest { extra_snippets: Some(true), ..req.clone() }));
+
+    let mut policy = DomainPolicy::from_config(config);
+    if let Some(allowlist) = &params.domain_allowlist {
+        policy = policy.with_request_allowlist(allowlist);
+    }
+
+    deadline::enter(Phase::Cache);
+    let mut cached: Option<WebNewsSearchOutput> = None;
+    if !params.force_refresh {
+        cached = cached_search(db, &cache_key).await;
+        if cached.is_none()
+            && let Some(key) = &snippets_key
+        {
+            cached = cached_search(db, key).await;
+        }
+    }
+    if let Some(mut output) = cached {
+        tracing::debug!("cache hit for news query: {}", params.query);
+        if let Err(e) = db.record_brave_cache_hit().await {
+            tracing::warn!("failed to record news cache hit: {}", e);
+        }
+        shape_results(&mut output, &policy, want_snippets, sort_by_date, config);
+        output.debug.cache_hit = Some(true);
+        output.debug.attempts = None;
+        output.debug.usage = usage_report(db, config).await;
+        return json_result(&output, config);
+    }
+
+    let client = require_client(brave, config)?;
+    deadline::enter(Phase::Fetch);
+    let response = client.search_news(req).await.map_err(search_error)?;
+    if let Err(e) = db.record_brave_request().await {
+        tracing::warn!("failed to record Brave request: {}", e);
+    }
+
+    let mut output = WebNewsSearchOutput {
+        results: response
+            .results
+            .into_iter()
+            .map(|r| NewsResult {
+                title: r.title,
+                url: r.url,
+                description: r.description,
+                extra_snippets: r.extra_snippets,
+                age: r.age,
+                published_at: r.published_at,
+                source: r.source,
+                thumbnail_url: r.thumbnail_url,
+                rank: r.rank,
+            })
+            .collect(),
+        query: response.query.into(),
+        debug: DebugInfo {
+            request_id: response.debug.request_id,
+            cache_hit: Some(false),
+            usage: None,
+            sorted_by: None,
+            attempts: Some(response.debug.attempts).filter(|n| *n > 1),
+        },
+    };
+
+    match (serde_json::to_string(&params.query), serde_json::to_string(&output)) {
+        (Ok(query_json), Ok(response_json)) => {
+            let writer = db.clone();
+            let write =
+                deadline::cache_write(
+                    async move { writer.put_search(&cache_key, &query_json, &response_json, ttl).await },
+                );
+            if let Err(e) = write.await {
+                tracing::warn!("failed to cache news result: {}", e);
+            }
+        }
+        (Err(e), _) | (_, Err(e)) => tracing::warn!("failed to serialize news result for caching: {}", e),
+    }
+
+    shape_results(&mut output, &policy, want_snippets, sort_by_date, config);
+    output.debug.usage = usage_report(db, config).await;
+
+    json_result(&output, config)
+}
+
+/// Filter, trim, and order results for the caller; results are cached before this runs.
+fn shape_results(
+    output: &mut WebNewsSearchOutput, policy: &DomainPolicy, want_snippets: bool, sort_by_date: bool,
+    config: &AppConfig,
+) {
+    if !policy.is_unrestricted() {
+        output.results.retain(|r| host_allowed(&r.url, policy));
+    }
+    for result in &mut output.results {
+        limit_snippets(&mut result.extra_snippets, want_snippets, config);
+    }
+    if sort_by_date {
+        output.results.sort_by_key(|r| newest_first(r.published_at.as_deref()));
+        output.debug.sorted_by = Some("date".to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use thndrs_client::{SafeSearch, SearchRequest};
+
+    fn news_output() -> WebNewsSearchOutput {
+        let result = |rank: usize, url: &str, published_at: Option<&str>| NewsResult {
+            title: format!("Story {rank}"),
+            url: url.to_string(),
+            description: String::new(),
+            extra_snippets: vec!["snippet".into()],
+            age: None,
+            published_at: published_at.map(str::to_string),
+            source: "news.example.com".into(),
+            thumbnail_url: None,
+            rank,
+        };
+        WebNewsSearchOutput {
+            results: vec![
+                result(1, "https://news.example.com/a", Some("2024-03-01T00:00:00Z")),
+                result(2, "https://blocked.example.org/b", None),
+                result(3, "https://news.example.com/c", Some("2024-03-08T09:30:00Z")),
+            ],
+            query: QueryMeta { original: "rust".into(), ..Default::default() },
+            debug: DebugInfo { request_id: None, cache_hit: Some(false), usage: None, sorted_by: None, attempts: None },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_empty_query() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let params = WebNewsSearchParams { query: "".into(), ..Default::default() };
+
+        let result = news_search_impl(&db, &AppConfig::default(), None, params).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_count_limit_is_fifty() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig::default();
+        let params = |count| WebNewsSearchParams { query: "rust".into(), count: Some(count), ..Default::default() };
+
+        let err = news_search_impl(&db, &config, None, params(51)).await.unwrap_err();
+        assert!(err.message.contains("1-50"), "{}", err.message);
+        let err = news_search_impl(&db, &config, None, params(50)).await.unwrap_err();
+        assert_eq!(err.code.0, -32009, "a valid count should reach the missing API key");
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit_is_keyed_apart_from_web_search() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { denylist_domains: vec!["blocked.example.org".into()], ..Default::default() };
+
+        let req = NewsSearchRequest {
+            q: "rust".into(),
+            count: Some(20),
+            safesearch: Some(SafeSearch::Moderate),
+            ..Default::default()
+        };
+        db.put_search(
+            &BraveClient::news_cache_key(&req),
+            "\"rust\"",
+            &serde_json::to_string(&news_output()).unwrap(),
+            BraveClient::news_ttl_for_freshness(&None),
+        )
+        .await
+        .unwrap();
+        let web = SearchRequest { q: "rust".into(), safesearch: Some(SafeSearch::Moderate), ..Default::default() };
+        assert!(db.get_search(&BraveClient::cache_key(&web)).await.unwrap().is_none());
+
+        let params = WebNewsSearchParams {
+            query: "rust".into(),
+            count: Some(20),
+            sort: Some("date".into()),
+            ..Default::default()
+        };
+        let result = news_search_impl(&db, &config, None, params).await.unwrap();
+        let output: WebNewsSearchOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+
+        assert_eq!(output.debug.cache_hit, Some(true));
+        assert_eq!(output.debug.sorted_by.as_deref(), Some("date"));
+        let ranks: Vec<usize> = output.results.iter().map(|r| r.rank).collect();
+        assert_eq!(ranks, vec![3, 1]);
+        assert!(output.results.iter().all(|r| r.extra_snippets.is_empty()));
+    }
+}
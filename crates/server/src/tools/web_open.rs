@@ -1,14 +1,47 @@
 //! web_open tool implementation.
 //!
-//! Fetches a URL and extracts readable content using the full fetch pipeline.
+//! Fetches a URL and extracts readable content using the full fetch pipeline
+//! ([`WebPipeline`]), caching the result as a snapshot.
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use rmcp::{ErrorData as McpError, model::*};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::time::Instant;
-use thndrs_client::{ExtractConfig, Extractor, FetchClient, FetchConfig, LectitoExtractor, normalize_markdown};
-use thndrs_core::{AppConfig, CacheDb, Error, Snapshot, cache::hash::compute_cache_key};
+use std::collections::HashMap;
+use std::sync::Arc;
+use thndrs_client::{
+    CircuitBreaker, ExportConfig, ExportDoc, Exporter, ExtractConfig, ExtractStrategy, Extraction, Extractor,
+    FEED_EXTRACTOR_VERSION, FetchClient, FetchConfig, FetchResponse, LECTITO_EXTRACTOR_VERSION, LectitoExtractor,
+    OpenMode, OpenOptions, PIPELINE_VERSION, PLAIN_TEXT_EXTRACTOR_VERSION, PipelineResult, RendererPool,
+    RequestOptions, SiteConfigRegistry, WebPipeline, check_selector, estimate_tokens,
+    fetch::{
+        BodyKind, CanonicalizeOptions, HeaderMap, body_kind, canonicalize_with_options, charset, detect_challenge,
+        is_allowed_content_type, private_content_reason, request_headers,
+    },
+    paginate,
+    pipeline::{CacheVary, cache_key, decode_body, extract_with_budget, pretty_json},
+    refresh_fetched_at, scope_to_section, scope_to_selector, truncate_to_tokens,
+};
+use thndrs_core::cache::{
+    ContentQuality, FeedLink, Heading, Image, LinkKind, PageMetadata, StoredLinks, Table, images_json, outline_json,
+    quality_json, snapshots::url_host, structured_data_json, tables_json,
+};
+use thndrs_core::{
+    AppConfig, CacheDb, DomainPolicy, Error, Mode, PrivateContentPolicy, Snapshot, config::is_valid_language_list,
+};
+
+use crate::tools::deadline::{self, Phase};
+use crate::tools::hints::{Hint, HintContext, error_with_hints, hints_for_output};
+use crate::tools::output::json_result;
+
+/// Stored-header entry recording a per-call User-Agent override, for auditing cached snapshots.
+pub const USER_AGENT_OVERRIDE_HEADER: &str = "x-thndrs-user-agent";
+
+/// Longest accepted per-call User-Agent, in bytes.
+const MAX_USER_AGENT_LEN: usize = 512;
+
+/// Longest accepted cache partition, in bytes.
+const MAX_CACHE_PARTITION_LEN: usize = 128;
 
 /// Input parameters for web_open tool.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -17,8 +50,9 @@ pub struct WebOpenParams {
     pub url: String,
 
     /// Extraction mode: "readable" (default), "raw", or "rendered" (requires render feature).
-    #[serde(default = "default_mode")]
-    pub mode: String,
+    #[serde(default)]
+    #[schemars(with = "OpenableMode")]
+    pub mode: Mode,
 
     /// Maximum response body size in bytes (default: 5MB).
     #[serde(default = "default_max_bytes")]
@@ -36,6 +70,23 @@ pub struct WebOpenParams {
     #[serde(default)]
     pub accept: Option<String>,
 
+    /// Optional Accept-Language for this fetch (e.g. "fr-FR,fr;q=0.9"), overriding the configured default.
+    #[serde(default)]
+    pub language: Option<String>,
+
+    /// Extra request headers, e.g. `Authorization` or `Cookie`. `Host`, `Content-Length`,
+    /// and connection headers can't be set; `User-Agent` requires `fetch.allow_user_agent_override`.
+    #[serde(default)]
+    pub headers: Option<HashMap<String, String>>,
+
+    /// Extract with the server's site config for the page's host, if one matches (default: true).
+    #[serde(default = "default_true")]
+    pub use_siteconfig: bool,
+
+    /// Extract with this site config instead of the one the host matches (advanced).
+    #[serde(default)]
+    pub siteconfig_id: Option<String>,
+
     /// Optional extraction tuning parameters.
     #[serde(default)]
     pub extract: Option<ExtractTuning>,
@@ -43,10 +94,90 @@ pub struct WebOpenParams {
     /// Enable extraction diagnostics output for debugging.
     #[serde(default)]
     pub debug: bool,
+
+    /// Return the page's JSON-LD items in `structured_data` (default: false).
+    #[serde(default)]
+    pub include_structured_data: bool,
+
+    /// Return the page's images in `images` (default: false).
+    #[serde(default)]
+    pub include_images: bool,
+
+    /// Return tables too complex for markdown (row or column spans) in `tables`
+    /// (default: false). Simple tables are always in the markdown.
+    #[serde(default)]
+    pub include_tables: bool,
+
+    /// Which harvested links to return: "all" (default), "content" (inside the
+    /// extracted article), or "external" (other hosts).
+    #[serde(default)]
+    pub links: LinkFilter,
+
+    /// CSS selector: return only the content of the first element it matches, as
+    /// markdown. Needs the page body, so a cached snapshot without a stored body
+    /// is fetched again.
+    #[serde(default)]
+    pub selector: Option<String>,
+
+    /// Heading text, matched case-insensitively: return only that heading and the
+    /// content under it, up to the next heading of the same or a higher level.
+    #[serde(default)]
+    pub section: Option<String>,
+
+    /// Approximate token budget for the returned markdown; trimmed at paragraph boundaries.
+    #[serde(default)]
+    pub max_tokens: Option<usize>,
+
+    /// Split the markdown into pages of at most this many characters, between
+    /// paragraphs and never inside a code block, and return one page.
+    #[serde(default)]
+    pub max_chars: Option<usize>,
+
+    /// Page to return with `max_chars`, counting from 1 (default: 1). Other pages
+    /// of a cached document are served from the cache.
+    #[serde(default)]
+    pub page: Option<u32>,
+
+    /// Skip the Content-Type allowlist and download any body (mode=raw only).
+    #[serde(default)]
+    pub allow_any_content_type: bool,
+
+    /// Keep the original body in the cache alongside the readable extraction
+    /// (default: the server's `cache.store_raw_default`).
+    #[serde(default)]
+    pub store_raw: Option<bool>,
+
+    /// User-Agent for this fetch and its robots.txt check (requires `fetch.allow_user_agent_override`).
+    #[serde(default)]
+    pub user_agent: Option<String>,
+
+    /// Cache partition for personalized or authenticated pages, e.g. a session
+    /// id (requires `cache.allow_private_content = partitioned`).
+    #[serde(default)]
+    pub cache_partition: Option<String>,
+
+    /// CSS selector to wait for before capturing the rendered page (mode=rendered only).
+    #[serde(default)]
+    pub wait_for: Option<String>,
+
+    /// How long the browser may spend loading the page in milliseconds
+    /// (mode=rendered only; default: `timeout_ms`).
+    #[serde(default)]
+    pub render_timeout_ms: Option<u64>,
 }
 
-fn default_mode() -> String {
-    "readable".into()
+/// The [`Mode`]s a page can be opened in, as advertised in tool schemas.
+///
+/// `feed` is left out: it belongs to web_feed and web_open rejects it.
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum OpenableMode {
+    /// Main content extracted as Markdown.
+    Readable,
+    /// The response body as text, without extraction.
+    Raw,
+    /// Main content extracted after a headless browser ran the page's scripts.
+    Rendered,
 }
 
 fn default_max_bytes() -> usize {
@@ -57,16 +188,27 @@ fn default_false() -> bool {
     false
 }
 
+fn default_true() -> bool {
+    true
+}
+
 fn default_timeout_ms() -> u64 {
     20000
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ExtractTuning {
     /// Minimum character threshold for content blocks.
     pub char_threshold: Option<usize>,
     /// Maximum number of top candidates to consider.
     pub max_top_candidates: Option<usize>,
+    /// "readability" (default) or "plain_text": all visible body text without
+    /// scoring, for pages too short for readability.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<String>,
+    /// Fall back to plain text when readability fails or keeps under 200 characters (default: true).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<bool>,
 }
 
 /// Extraction diagnostics for debugging and tuning.
@@ -80,8 +222,54 @@ pub struct ExtractionDiagnostics {
     pub char_count: usize,
     /// Number of links extracted from the content.
     pub links_count: usize,
+    /// Word count, reading time, paragraph count, link density, and whether the
+    /// content looks like boilerplate rather than an article (HTML only).
+    #[serde(flatten)]
+    pub quality: Option<ContentQuality>,
     /// Extraction time in milliseconds.
     pub extraction_time_ms: u64,
+    /// Whether the previous extraction was reused because the body was unchanged.
+    #[serde(default)]
+    pub reused_extraction: bool,
+    /// Response headers as stored with the snapshot (sanitized and size-capped).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub response_headers: Vec<(String, String)>,
+    /// Time spent waiting out a host cool-down (429/503 Retry-After) before fetching.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cooldown_wait_ms: Option<u64>,
+    /// Time spent spacing this request after the previous one to the host (per-host interval or Crawl-delay).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pacing_wait_ms: Option<u64>,
+    /// Time spent queued for the shared in-flight byte budget before reading the body.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_wait_ms: Option<u64>,
+    /// Bytes reserved by all in-flight fetches, this one included, when the body was read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inflight_bytes: Option<usize>,
+    /// Requests sent for the body when transient failures were retried; omitted when the first succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempts: Option<u8>,
+    /// Hash of the snapshot (same URL, another mode) whose stored body was used instead of fetching.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_reused_from: Option<String>,
+    /// Corrections applied to a mislabeled body, e.g. `gzip-sniffed`, `utf16-bom`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub body_fixups: Vec<String>,
+    /// User-Agent the body was fetched with: the override from `user_agent` or
+    /// `headers`, else the configured one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// Time the headless browser spent loading the page (rendered mode only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render_ms: Option<u64>,
+    /// Whether the rendered page reached its load state and `wait_for` selector
+    /// before the render timeout; false means extraction ran on a partial page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render_settled: Option<bool>,
+    /// Requests the rendered page made that were refused (denied scheme, host,
+    /// port, or private address); zero is omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render_blocked_requests: Option<usize>,
 }
 
 /// Output structure for web_open tool.
@@ -91,265 +279,3434 @@ pub struct WebOpenOutput {
     pub url: String,
     /// The final URL after redirects.
     pub final_url: String,
+    /// Host the request was redirected to, when it differs from the requested host.
+    /// The content (and its links) come from this host, not the requested one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cross_host_redirect: Option<String>,
     /// Content-Type header.
     pub content_type: Option<String>,
+    /// Encoding the body was decoded with, e.g. `windows-1251`; absent for rendered pages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detected_charset: Option<String>,
     /// ISO8601 timestamp of when the content was fetched.
     pub fetched_at: String,
     /// The mode used for extraction.
-    pub mode: String,
+    pub mode: Mode,
     /// Raw HTML content (only if mode=raw).
     pub raw: Option<String>,
     /// Extracted Markdown content (if mode=readable).
     pub markdown: Option<String>,
+    /// How an HTML page's content was found: "readability", or "plain_text" when
+    /// readability failed or kept too little and the lower-fidelity body text was used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<String>,
     /// Extracted page title.
     pub title: Option<String>,
+    /// Author, publication date, description, social card fields, and canonical URL
+    /// the page declares; omitted when it declares none.
+    #[serde(default, skip_serializing_if = "PageMetadata::is_empty")]
+    pub metadata: PageMetadata,
+    /// JSON-LD items (schema.org Article, BreadcrumbList, FAQPage, ...) the page
+    /// embeds; only with `include_structured_data`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_data: Option<Vec<serde_json::Value>>,
+    /// Images in the page with resolved source, alt text, title, and declared
+    /// size; only with `include_images`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub images: Option<Vec<Image>>,
+    /// Tables with row or column spans, as headers and rows; the markdown holds a
+    /// `[Table: see tables[N]]` marker where each stood. Only with `include_tables`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tables: Option<Vec<Table>>,
+    /// Headings of the extracted content in document order, with level and anchor
+    /// id (link as `final_url#id`); omitted when there are none.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outline: Vec<Heading>,
     /// Harvested links from the content.
     pub links: Vec<ExtractedLink>,
+    /// The cached links could not be parsed; `links` is empty rather than complete.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub links_parse_error: bool,
+    /// RSS, Atom, or JSON feeds the page advertises with `<link rel="alternate">`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub feeds: Vec<FeedLink>,
     /// Content hash for cache lookup.
     pub hash: String,
+    /// Approximate token count of the returned markdown (or raw) payload.
+    #[serde(default)]
+    pub approx_tokens: usize,
+    /// Whether the markdown was trimmed to fit `max_tokens`.
+    #[serde(default)]
+    pub truncated: bool,
+    /// Which page of the markdown was returned; only with `max_chars`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<Pagination>,
+    /// Suggested next actions when the result looks incomplete.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hints: Vec<Hint>,
+    /// The result could not be saved to the cache; `hash` will not resolve with cache_get.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cache_write_failed: bool,
+    /// How the cache handled a response private to the requester; absent for shareable responses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheDecision>,
+    /// Served from a cached snapshot without contacting the origin.
+    #[serde(default)]
+    pub cache_hit: bool,
+    /// Body bytes downloaded for this call; 0 when served from the cache or a stored body.
+    #[serde(default)]
+    pub bytes_fetched: usize,
+    /// Time spent fetching in milliseconds; 0 when nothing was fetched.
+    #[serde(default)]
+    pub fetch_ms: u64,
     /// Extraction diagnostics (only if debug=true).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub debug: Option<ExtractionDiagnostics>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
-pub struct ExtractedLink {
-    pub text: String,
-    pub href: String,
+/// Position of the returned page in the whole markdown.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Pagination {
+    /// Page returned, counting from 1.
+    pub page: u32,
+    /// Pages the markdown splits into at this `max_chars`.
+    pub total_pages: u32,
+    /// Characters in the whole markdown.
+    pub total_chars: usize,
+    /// Whether pages after this one remain.
+    pub truncated: bool,
+}
+
+/// Cache handling of a response that is private to the requester.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CacheDecision {
+    /// `skipped` (not stored) or `partitioned` (stored under `partition` only).
+    pub decision: String,
+    /// What marked the response private, e.g. `cache-control: private`.
+    pub reason: String,
+    /// Partition the snapshot was stored under.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partition: Option<String>,
+}
+
+pub use thndrs_core::cache::Link as ExtractedLink;
+
+/// Which harvested links web_open returns; the snapshot always keeps all of them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkFilter {
+    /// Every link on the page.
+    #[default]
+    All,
+    /// Links that appear in the extracted content, not only in navigation or footers.
+    Content,
+    /// Links to other hosts.
+    External,
+}
+
+impl LinkFilter {
+    /// Keep the links this filter selects, in order.
+    pub fn apply(self, links: Vec<ExtractedLink>) -> Vec<ExtractedLink> {
+        links
+            .into_iter()
+            .filter(|link| match self {
+                Self::All => true,
+                Self::Content => link.in_content,
+                Self::External => link.kind == LinkKind::External,
+            })
+            .collect()
+    }
 }
 
 /// Implementation of the web_open tool.
-pub async fn open_impl(db: &CacheDb, config: &AppConfig, params: WebOpenParams) -> Result<CallToolResult, McpError> {
+///
+/// `fetch` is shared across calls (see [`fetch_client`]) so connections, robots.txt
+/// rules, and host failure state carry over; per-call limits, agent, and headers
+/// are applied to each request instead.
+/// Rendered mode uses `renderer` when given and otherwise launches a browser for the call.
+/// HTML is extracted with the config in `siteconfigs` its final host matches,
+/// unless the call names another or turns them off.
+/// Newly cached documents are handed to `exporter`, shared like `fetch`.
+pub async fn open_impl(
+    db: &CacheDb, config: &AppConfig, fetch: &Arc<FetchClient>, renderer: Option<&RendererPool>,
+    siteconfigs: &Arc<SiteConfigRegistry>, exporter: Option<&Exporter>, params: WebOpenParams,
+) -> Result<CallToolResult, McpError> {
     if params.url.is_empty() {
         return Err(Error::InvalidInput("url cannot be empty".into()).into());
     }
 
-    if params.mode != "readable" && params.mode != "raw" && params.mode != "rendered" {
-        return Err(Error::InvalidInput(format!("unsupported mode: {}", params.mode)).into());
+    if params.mode == Mode::Feed {
+        return Err(Error::InvalidInput("mode=feed is for web_feed; open feeds in readable mode".into()).into());
     }
 
-    if params.mode == "rendered" {
-        #[cfg(not(feature = "render"))]
-        return Err(Error::RenderDisabled.into());
+    if params.allow_any_content_type && params.mode != Mode::Raw {
+        return Err(Error::InvalidInput("allow_any_content_type requires mode=raw".into()).into());
+    }
+
+    if params.mode != Mode::Rendered && (params.wait_for.is_some() || params.render_timeout_ms.is_some()) {
+        return Err(Error::InvalidInput("wait_for and render_timeout_ms require mode=rendered".into()).into());
     }
 
-    let vary_headers = params.accept.as_deref().unwrap_or("");
-    let hash = compute_cache_key(&params.url, vary_headers, &params.mode);
+    if params.mode == Mode::Rendered && params.headers.is_some() {
+        return Err(Error::InvalidInput("headers are not supported with mode=rendered".into()).into());
+    }
+
+    if params.mode == Mode::Raw && (params.selector.is_some() || params.section.is_some()) {
+        return Err(Error::InvalidInput("selector and section require mode=readable or mode=rendered".into()).into());
+    }
+    if let Some(selector) = &params.selector {
+        check_selector(selector)?;
+    }
+
+    if params.mode == Mode::Raw && params.max_chars.is_some() {
+        return Err(Error::InvalidInput("max_chars requires mode=readable or mode=rendered".into()).into());
+    }
+    match (params.max_chars, params.page) {
+        (Some(0), _) => return Err(Error::InvalidInput("max_chars must be at least 1".into()).into()),
+        (None, Some(_)) => return Err(Error::InvalidInput("page requires max_chars".into()).into()),
+        (_, Some(0)) => return Err(Error::InvalidInput("page counts from 1".into()).into()),
+        _ => {}
+    }
+
+    let extra_headers = check_headers(config, params.headers.as_ref())?;
+    // A User-Agent passed as a header is an override like `user_agent`, so robots.txt is checked for it too.
+    // Extra headers are applied last and win on the wire, so the header takes precedence.
+    let user_agent = extra_headers
+        .get("user-agent")
+        .and_then(|v| v.to_str().ok())
+        .or(params.user_agent.as_deref());
+    check_user_agent(config, user_agent)?;
+    check_cache_partition(config, params.cache_partition.as_deref())?;
 
-    if !params.force_refresh
-        && let Ok(Some(snapshot)) = db.get_snapshot(&hash).await
+    let render_available = cfg!(feature = "render") && config.render_enabled;
+    let mut hint_ctx = HintContext {
+        url: params.url.clone(),
+        mode: params.mode,
+        timeout_ms: params.timeout_ms,
+        max_bytes: params.max_bytes,
+        render_available,
+        ..Default::default()
+    };
+
+    if params.mode == Mode::Rendered && !render_available {
+        return Err(error_with_hints(Error::RenderDisabled, &hint_ctx));
+    }
+
+    let language = params.language.as_deref().or(config.accept_language.as_deref());
+    if let Some(lang) = language
+        && !is_valid_language_list(lang)
     {
+        return Err(Error::InvalidInput(format!("invalid language: {lang}")).into());
+    }
+    let extract = site_extract_config(&params, siteconfigs)?;
+
+    // Canonicalize once: the same string keys the cache, is stored as the
+    // snapshot URL, and is what the fetch client requests. Tracking parameters
+    // are stripped here so equivalent links collapse to one snapshot.
+    let url = canonicalize_with_options(&params.url, &canonicalize_options(config))
+        .map_err(|e| error_with_hints(Error::InvalidUrl(e.to_string()), &hint_ctx))?;
+    let header_pairs: Vec<(String, String)> = params.headers.clone().unwrap_or_default().into_iter().collect();
+    let vary = CacheVary {
+        accept: params.accept.as_deref(),
+        language,
+        user_agent,
+        partition: params.cache_partition.as_deref(),
+        headers: &header_pairs,
+    };
+    let hash = cache_key(&url, &vary, params.mode);
+    deadline::enter(Phase::Cache);
+    let previous = db.get_snapshot(&hash).await.ok().flatten();
+    let fresh = previous.is_some() && db.is_snapshot_fresh(&hash).await.unwrap_or(false);
+
+    // A call that names a site config, or turns them off, wants an extraction made that way.
+    let pinned_siteconfig = (!extract.use_siteconfig || extract.siteconfig.is_some())
+        .then(|| extract.siteconfig.as_ref().map(|site| site.id.clone()));
+    let cached = match previous.clone() {
+        // Past its expiry, a snapshot is only a source of validators for the refetch.
+        Some(_) if !fresh => None,
+        Some(snapshot)
+            if pinned_siteconfig
+                .as_ref()
+                .is_some_and(|id| snapshot.siteconfig_id != *id) =>
+        {
+            None
+        }
+        // A selector is applied to the page body, which readable snapshots only keep with store_raw.
+        Some(snapshot)
+            if params.selector.is_some() && (snapshot.raw_bytes.is_none() || snapshot.mode != Mode::Readable) =>
+        {
+            None
+        }
+        Some(snapshot) if !params.force_refresh => {
+            if config.invalidate_on_version_mismatch && is_stale_extraction(&snapshot) {
+                let reextracted =
+                    reextract_stored(db, snapshot, params.extract.as_ref(), &extract, siteconfigs).await?;
+                if reextracted.is_none() {
+                    tracing::debug!(
+                        "cached extraction for {} is from another version, refetching",
+                        params.url
+                    );
+                }
+                reextracted
+            } else {
+                Some((snapshot, false))
+            }
+        }
+        _ => None,
+    };
+
+    if let Some((snapshot, cache_write_failed)) = cached {
         tracing::debug!("cache hit for {}", params.url);
 
+        // The policy may have changed since this was cached; check every host it touched.
+        let policy = DomainPolicy::from_config(config);
+        for host in [url_host(&snapshot.url), url_host(&snapshot.final_url)]
+            .into_iter()
+            .flatten()
+        {
+            policy.check(&host).map_err(|e| error_with_hints(e, &hint_ctx))?;
+        }
+
+        let (links, links_parse_error) = cached_links(&snapshot);
+        let links = params.links.apply(links);
+        // Bodies stored alongside readable extractions stay in the cache only. A
+        // readable request recorded as raw was a JSON body, returned pretty-printed.
+        let raw = snapshot
+            .raw_bytes
+            .as_deref()
+            .filter(|_| snapshot.mode == Mode::Raw)
+            .map(|b| decode_body(b, stored_charset(&snapshot)))
+            .map(|text| match params.mode {
+                Mode::Raw => text,
+                _ => pretty_json(&text).unwrap_or(text),
+            });
+        let page = params
+            .selector
+            .as_ref()
+            .and(snapshot.raw_bytes.as_deref())
+            .map(|b| decode_body(b, stored_charset(&snapshot)));
+        let strategy = snapshot_strategy(&snapshot).map(|s| s.as_str().to_string());
+        let metadata = snapshot.metadata().unwrap_or_default();
+        let structured_data = params
+            .include_structured_data
+            .then(|| snapshot.structured_data().unwrap_or_default());
+        let images = params.include_images.then(|| snapshot.images().unwrap_or_default());
+        let tables = params.include_tables.then(|| snapshot.tables().unwrap_or_default());
         let output = WebOpenOutput {
+            cross_host_redirect: cross_host_redirect(&snapshot.url, &snapshot.final_url),
             url: snapshot.url,
             final_url: snapshot.final_url,
             content_type: snapshot.content_type,
+            detected_charset: snapshot.detected_charset,
             fetched_at: snapshot.fetched_at,
             mode: snapshot.mode,
-            raw: snapshot.raw_bytes.map(|b| String::from_utf8_lossy(&b).to_string()),
+            raw,
             markdown: snapshot.markdown,
+            strategy,
             title: snapshot.title,
-            links: snapshot
-                .links_json
-                .and_then(|j| serde_json::from_str(&j).ok())
-                .unwrap_or_default(),
+            metadata,
+            structured_data,
+            images,
+            tables,
+            outline: snapshot.outline().unwrap_or_default(),
+            links,
+            links_parse_error,
+            feeds: snapshot.feeds().unwrap_or_default(),
             hash,
+            approx_tokens: 0,
+            truncated: false,
+            pagination: None,
+            hints: Vec::new(),
+            cache_write_failed,
+            cache: None,
+            cache_hit: true,
+            bytes_fetched: 0,
+            fetch_ms: 0,
             debug: None,
         };
+        let output = scope_output(output, &params, page.as_deref())?;
+        let output = paginate_output(output, &params)?;
+        let output = apply_token_budget(output, params.max_tokens);
 
-        return Ok(CallToolResult::success(vec![Content::text(
-            serde_json::to_string_pretty(&output).unwrap_or_default(),
-        )]));
+        return json_result(&output, config);
     }
 
-    let fetch_config = FetchConfig {
-        max_bytes: params.max_bytes,
-        timeout: std::time::Duration::from_millis(params.timeout_ms),
-        user_agent: config.user_agent.clone(),
-        respect_robots: config.respect_robots,
-        ..Default::default()
+    let pipeline = WebPipeline::shared(fetch.clone()).with_siteconfigs(siteconfigs.clone());
+    let options = OpenOptions {
+        mode: if params.mode == Mode::Raw { OpenMode::Raw } else { OpenMode::Readable },
+        max_bytes: Some(params.max_bytes),
+        extract,
+        fetched_at: Some(db.now()),
+        request: RequestOptions {
+            timeout: Some(std::time::Duration::from_millis(params.timeout_ms)),
+            // The robots.txt check uses this too, so rules are evaluated for the agent we fetch as.
+            user_agent: user_agent.map(str::to_string),
+            accept_language: language.map(str::to_string),
+            any_content_type: params.allow_any_content_type,
+            headers: extra_headers.clone(),
+        },
     };
 
-    let fetch_client = FetchClient::new(fetch_config)?;
-    let response = fetch_client.fetch(&params.url).await?;
-    let fetched_at = Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
-
-    let (title, markdown, raw, links, debug_info) = match params.mode.as_str() {
-        "raw" => {
-            let html = String::from_utf8_lossy(&response.bytes).to_string();
-            (None, None, Some(html), Vec::new(), None)
+    let reused_body = if params.force_refresh { None } else { reusable_body(db, config, &url, &vary, &params).await };
+    let body_reused_from = reused_body.as_ref().map(|(hash, _)| hash.clone());
+    let mut result = match reused_body {
+        Some((sibling, result)) => {
+            tracing::debug!("reusing body of {} from snapshot {}", params.url, sibling);
+            result
         }
-        "readable" => {
-            let html = String::from_utf8_lossy(&response.bytes).to_string();
-
-            let extract_config = params
-                .extract
-                .as_ref()
-                .map(|t| ExtractConfig { char_threshold: t.char_threshold, max_top_candidates: t.max_top_candidates })
-                .unwrap_or_default();
-
-            let extract_start = Instant::now();
-
-            let extractor = thndrs_client::LectitoExtractor::new();
-            let result = extractor.extract(&html, &response.final_url, &extract_config)?;
-            let extraction_time_ms = extract_start.elapsed().as_millis() as u64;
+        None => {
+            deadline::enter(Phase::Fetch);
+            let mut result = pipeline
+                .fetch(url.as_str(), &options)
+                .await
+                .map_err(|e| error_with_hints(e, &hint_ctx))?;
+            if let Some(ua) = user_agent {
+                result
+                    .response
+                    .stored_headers
+                    .push((USER_AGENT_OVERRIDE_HEADER.to_string(), ua.to_string()));
+            }
+            result
+        }
+    };
+    hint_ctx.content_type = result.response.content_type.clone();
+    hint_ctx.body_len = result.response.bytes.len();
+    // Rendered mode gets its chance to pass the challenge in a browser; its page is checked below.
+    if params.mode != Mode::Rendered {
+        refuse_challenge(config, &result.response, None, &hint_ctx)?;
+    }
+    let fetched_at = result.fetched_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
 
-            let doc = thndrs_client::ExtractedDoc {
-                title: result.title.clone(),
-                markdown: result.markdown.clone(),
-                extractor_version: result.extractor_version,
-            };
+    let extract_cfg_json = serde_json::to_string(&params.extract.clone().unwrap_or_default()).ok();
+    // The site config an HTML body would be extracted with now; one made with another is redone.
+    let siteconfig_id = (body_kind(result.response.content_type.as_deref(), &result.response.bytes) == BodyKind::Html)
+        .then(|| {
+            siteconfigs
+                .configure(&options.extract, &result.response.final_url)
+                .siteconfig
+        })
+        .flatten()
+        .map(|site| site.id.clone());
+    let reusable = reusable_extraction(
+        previous.as_ref(),
+        &result.content_hash,
+        extract_cfg_json.as_deref(),
+        siteconfig_id.as_deref(),
+    );
+    let reused_extraction = reusable.is_some();
+    let mut links_parse_error = false;
+    let mut render_ms = None;
+    let mut render_settled = None;
+    let mut render_blocked_requests = None;
+    let mut rendered_html = None;
 
-            let normalized = normalize_markdown(&doc, &response.final_url, &Utc::now(), None);
+    if let Some(previous) = reusable {
+        tracing::debug!("body unchanged for {}, reusing previous extraction", params.url);
+        let (links, parse_error) = cached_links(previous);
+        links_parse_error = parse_error;
+        result.charset = previous.detected_charset.clone();
 
-            let links: Vec<ExtractedLink> = result
-                .links
+        result.extraction = Some(Extraction {
+            title: previous.title.clone(),
+            markdown: previous
+                .markdown
+                .as_deref()
+                .map(|m| refresh_fetched_at(m, &result.fetched_at))
+                .unwrap_or_default(),
+            links: links
                 .into_iter()
-                .map(|l| ExtractedLink { text: l.text, href: l.href })
-                .collect();
-
-            let debug_info = params.debug.then_some(ExtractionDiagnostics {
-                char_count: normalized.len(),
-                links_count: links.len(),
-                extraction_time_ms,
-            });
-
-            (result.title, Some(normalized), None, links, debug_info)
-        }
+                .map(|l| thndrs_client::Link { text: l.text, href: l.href, kind: l.kind, in_content: l.in_content })
+                .collect(),
+            images: previous.images().unwrap_or_default(),
+            tables: previous.tables().unwrap_or_default(),
+            outline: previous.outline().unwrap_or_default(),
+            quality: previous.quality().unwrap_or_default(),
+            feeds: previous.feeds().unwrap_or_default(),
+            extractor_version: previous.extractor_version.clone().unwrap_or_default(),
+            strategy: snapshot_strategy(previous),
+            siteconfig_id: previous.siteconfig_id.clone(),
+            metadata: previous.metadata().unwrap_or_default(),
+            structured_data: previous.structured_data().unwrap_or_default(),
+            extract_ms: 0,
+        });
+    } else if params.mode == Mode::Rendered {
         #[cfg(feature = "render")]
-        "rendered" => {
-            use thndrs_client::{HeadlessRenderer, RenderOptions, Renderer};
-            use url::Url;
-
-            let renderer = HeadlessRenderer::new()
-                .await
-                .map_err(|e| Error::RenderFailed(e.to_string()))?;
-
-            let render_opts = RenderOptions { timeout_ms: params.timeout_ms, wait_for: None, viewport: (1280, 720) };
-            let url = Url::parse(&params.url).map_err(|e| Error::InvalidUrl(e.to_string()))?;
-
-            let rendered_page = renderer
-                .render(&url, &render_opts)
-                .await
-                .map_err(|e| Error::RenderFailed(e.to_string()))?;
-
-            let extract_config = params
-                .extract
-                .as_ref()
-                .map(|t| ExtractConfig { char_threshold: t.char_threshold, max_top_candidates: t.max_top_candidates })
-                .unwrap_or_default();
+        {
+            use thndrs_client::{HeadlessRenderer, RenderOptions, Renderer, RendererConfig};
 
-            let extract_start = Instant::now();
+            deadline::enter(Phase::Fetch);
 
-            let extractor = thndrs_client::LectitoExtractor::new();
-            let result = extractor.extract(&rendered_page.html, &rendered_page.final_url, &extract_config)?;
-            let extraction_time_ms = extract_start.elapsed().as_millis() as u64;
-
-            let doc = thndrs_client::ExtractedDoc {
-                title: result.title.clone(),
-                markdown: result.markdown.clone(),
-                extractor_version: result.extractor_version,
+            let render_opts = RenderOptions {
+                timeout_ms: params.render_timeout_ms.unwrap_or(params.timeout_ms),
+                wait_for: params.wait_for.clone(),
+                domain_policy: DomainPolicy::from_config(config),
+                allow_private_addresses: config.allow_private_addresses,
+                ..Default::default()
             };
-
-            let normalized = normalize_markdown(&doc, &rendered_page.final_url, &Utc::now(), None);
-
-            let links: Vec<ExtractedLink> = result
-                .links
-                .into_iter()
-                .map(|l| ExtractedLink { text: l.text, href: l.href })
-                .collect();
-
-            let debug_info = params.debug.then_some(ExtractionDiagnostics {
-                char_count: normalized.len(),
-                links_count: links.len(),
-                extraction_time_ms: rendered_page.render_time_ms + extraction_time_ms,
-            });
-
-            (result.title, Some(normalized), None, links, debug_info)
+            let rendered = match renderer {
+                Some(pool) => pool.render(&url, &render_opts).await,
+                None => {
+                    let renderer = HeadlessRenderer::with_config(&RendererConfig::from(&config.render))
+                        .await
+                        .map_err(|e| Error::RenderFailed(e.to_string()))?;
+                    renderer.render(&url, &render_opts).await
+                }
+            };
+            let rendered_page =
+                rendered.map_err(|e| error_with_hints(Error::RenderFailed(e.to_string()), &hint_ctx))?;
+            refuse_challenge(config, &result.response, Some(&rendered_page.html), &hint_ctx)?;
+            deadline::enter(Phase::Extract);
+            let extraction = pipeline
+                .extract(
+                    &rendered_page.html,
+                    &rendered_page.final_url,
+                    &result.fetched_at,
+                    &options.extract,
+                )
+                .await
+                .map_err(|e| error_with_hints(e, &hint_ctx))?;
+            render_ms = Some(rendered_page.render_time_ms);
+            render_settled = Some(rendered_page.wait_condition_met);
+            render_blocked_requests = Some(rendered_page.blocked_requests).filter(|n| *n > 0);
+            rendered_html = params.selector.as_ref().map(|_| rendered_page.html);
+            result.extraction = Some(extraction);
         }
         #[cfg(not(feature = "render"))]
-        "rendered" => {
-            return Err(Error::RenderDisabled.into());
-        }
-        _ => return Err(Error::InvalidInput(format!("unsupported mode: {}", params.mode)).into()),
-    };
+        return Err(error_with_hints(Error::RenderDisabled, &hint_ctx));
+    } else {
+        deadline::enter(Phase::Extract);
+        pipeline
+            .process(&mut result, &options)
+            .await
+            .map_err(|e| error_with_hints(e, &hint_ctx))?;
+    }
 
+    // The selector reads the page as rendered, or else as fetched.
+    let page = rendered_html.or_else(|| {
+        params
+            .selector
+            .as_ref()
+            .map(|_| decode_body(&result.response.bytes, result.charset.as_deref()))
+    });
+    // JSON opened in readable mode comes back pretty-printed as raw, and is recorded that way.
+    let mode = if result.raw.is_some() { Mode::Raw } else { params.mode };
+    let extraction = result.extraction.as_ref();
+    let title = extraction.and_then(|e| e.title.clone());
+    let markdown = extraction.map(|e| e.markdown.clone());
+    let raw = result.raw.clone();
+    let links: Vec<ExtractedLink> = extraction
+        .map(|e| {
+            e.links
+                .iter()
+                .map(|l| ExtractedLink {
+                    text: l.text.clone(),
+                    href: l.href.clone(),
+                    kind: l.kind,
+                    in_content: l.in_content,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let feeds = extraction.map(|e| e.feeds.clone()).unwrap_or_default();
+    let metadata = extraction.map(|e| e.metadata.clone()).unwrap_or_default();
+    let structured_data = extraction.map(|e| e.structured_data.as_slice()).unwrap_or_default();
+    let images = extraction.map(|e| e.images.as_slice()).unwrap_or_default();
+    let tables = extraction.map(|e| e.tables.as_slice()).unwrap_or_default();
+    let outline = extraction.map(|e| e.outline.as_slice()).unwrap_or_default();
+    let debug_info = extraction.filter(|_| params.debug).map(|e| ExtractionDiagnostics {
+        char_count: e.markdown.len(),
+        links_count: links.len(),
+        quality: e.quality.clone(),
+        extraction_time_ms: e.extract_ms,
+        reused_extraction,
+        response_headers: result.response.stored_headers.clone(),
+        cooldown_wait_ms: Some(result.response.cooldown_wait_ms).filter(|ms| *ms > 0),
+        pacing_wait_ms: Some(result.response.pacing_wait_ms).filter(|ms| *ms > 0),
+        budget_wait_ms: Some(result.response.budget_wait_ms).filter(|ms| *ms > 0),
+        inflight_bytes: Some(result.response.inflight_bytes).filter(|bytes| *bytes > 0),
+        attempts: Some(result.response.attempts).filter(|attempts| *attempts > 1),
+        body_reused_from: body_reused_from.clone(),
+        body_fixups: result
+            .response
+            .body_fixups
+            .iter()
+            .map(|f| f.as_str().to_string())
+            .collect(),
+        user_agent: Some(user_agent.unwrap_or(&config.user_agent).to_string()),
+        render_ms,
+        render_settled,
+        render_blocked_requests,
+    });
+
+    let response = &result.response;
+    // Extractions name their own extractor (lectito, plain-text, pdf-extract, feed-rs); raw bodies get lectito.
+    let extractor_version = extraction.map_or_else(
+        || pipeline.extractor().version().to_string(),
+        |e| e.extractor_version.clone(),
+    );
     let snapshot = Snapshot {
         hash: hash.clone(),
-        url: response.url.to_string(),
+        url: url.to_string(),
         final_url: response.final_url.to_string(),
-        mode: params.mode.clone(),
+        mode,
         content_type: response.content_type.clone(),
         status_code: Some(response.status.as_u16() as i32),
         fetched_at: fetched_at.clone(),
-        expires_at: None,
-        etag: response
-            .headers
-            .get("etag")
-            .and_then(|v| v.to_str().ok())
-            .map(|s| s.to_string()),
-        last_modified: response
-            .headers
-            .get("last-modified")
-            .and_then(|v| v.to_str().ok())
-            .map(|s| s.to_string()),
-        raw_bytes: raw.clone().map(|s| s.into_bytes()),
-        raw_truncated: response.bytes.len() >= params.max_bytes,
+        expires_at: snapshot_expiry(result.fetched_at, &response.stored_headers, config.snapshot_ttl_seconds),
+        etag: result.etag.clone(),
+        last_modified: result.last_modified.clone(),
+        // The body as received; `detected_charset` decodes it again.
+        raw_bytes: (mode == Mode::Raw
+            || mode == Mode::Readable && params.store_raw.unwrap_or(config.cache.store_raw_default))
+        .then(|| response.bytes.to_vec()),
+        // Fetches fail past max_bytes instead of truncating, so a stored body is always whole.
+        raw_truncated: false,
         title: title.clone(),
         markdown: markdown.clone(),
         text: None,
-        links_json: Some(serde_json::to_string(&links).unwrap_or_default()),
-        extractor_name: Some("lectito-core".to_string()),
-        extractor_version: Some("0.2.0".to_string()),
-        siteconfig_id: None,
-        extract_cfg_json: None,
-        headers_json: None,
+        links_json: Some(StoredLinks::new(links.clone()).with_feeds(feeds.clone()).to_json()),
+        metadata_json: metadata.to_json(),
+        structured_data_json: structured_data_json(structured_data),
+        images_json: images_json(images),
+        tables_json: tables_json(tables),
+        outline_json: outline_json(outline),
+        quality_json: quality_json(extraction.and_then(|e| e.quality.as_ref())),
+        content_hash: Some(result.content_hash.clone()),
+        extractor_name: Some(extractor_name(&extractor_version).to_string()),
+        extractor_version: Some(extractor_version),
+        pipeline_version: Some(PIPELINE_VERSION),
+        detected_charset: result.charset.clone(),
+        siteconfig_id: extraction.and_then(|e| e.siteconfig_id.clone()),
+        extract_cfg_json,
+        headers_json: serde_json::to_string(&response.stored_headers).ok(),
         fetch_ms: Some(response.fetch_ms as i64),
         extract_ms: debug_info.as_ref().map(|d| d.extraction_time_ms as i64),
     };
 
-    db.upsert_snapshot(&snapshot).await?;
+    let cache = private_content_decision(&url, &extra_headers, response, params.cache_partition.as_deref());
+    let cache_write_failed = match &cache {
+        Some(CacheDecision { partition: None, reason, .. }) => {
+            tracing::debug!("not caching {}: {}", url, reason);
+            false
+        }
+        _ => store_snapshot(db, snapshot).await,
+    };
+
+    hint_ctx.extracted_len = markdown.as_deref().map(|m| markdown_body(m).trim().chars().count());
+    let hints = hints_for_output(&hint_ctx);
 
+    let (bytes_fetched, fetch_ms) = match body_reused_from {
+        Some(_) => (0, 0),
+        None => (response.bytes.len(), response.fetch_ms),
+    };
+    let cross_host = cross_host_redirect(url.as_str(), response.final_url.as_str());
+    if let Some(host) = &cross_host {
+        tracing::info!("{} redirected across hosts to {}", url, host);
+    }
     let output = WebOpenOutput {
-        url: response.url.to_string(),
+        url: url.to_string(),
         final_url: response.final_url.to_string(),
-        content_type: response.content_type,
+        cross_host_redirect: cross_host,
+        content_type: response.content_type.clone(),
+        detected_charset: result.charset.clone(),
         fetched_at,
-        mode: params.mode,
+        mode,
         raw,
         markdown,
+        strategy: extraction.and_then(|e| e.strategy).map(|s| s.as_str().to_string()),
         title,
-        links,
+        metadata,
+        structured_data: params.include_structured_data.then(|| structured_data.to_vec()),
+        images: params.include_images.then(|| images.to_vec()),
+        tables: params.include_tables.then(|| tables.to_vec()),
+        outline: outline.to_vec(),
+        links: params.links.apply(links),
+        links_parse_error,
+        feeds,
         hash,
+        approx_tokens: 0,
+        truncated: false,
+        pagination: None,
+        hints,
+        cache_write_failed,
+        cache,
+        cache_hit: false,
+        bytes_fetched,
+        fetch_ms,
         debug: debug_info,
     };
 
-    Ok(CallToolResult::success(vec![Content::text(
-        serde_json::to_string_pretty(&output).unwrap_or_default(),
-    )]))
+    // Exports are shared destinations; private pages stay out of them even when partitioned.
+    if let Some(exporter) = exporter
+        && output.cache.is_none()
+    {
+        spawn_export(exporter, &output);
+    }
+    let output = scope_output(output, &params, page.as_deref())?;
+    let output = paginate_output(output, &params)?;
+    let output = apply_token_budget(output, params.max_tokens);
+
+    json_result(&output, config)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Exporter for the configured destinations, or `None` when export is off or
+/// its client can't be built.
+///
+/// The server builds one at startup and passes it to every call, so webhook
+/// connections are pooled across documents.
+pub fn exporter(config: &AppConfig) -> Option<Exporter> {
+    let export_config = ExportConfig {
+        dir: config.export_dir.clone(),
+        webhook_url: config.export_webhook_url.clone(),
+        webhook_allow_hosts: config.export_webhook_allow_hosts.clone(),
+    };
+    if !export_config.is_enabled() {
+        return None;
+    }
+    Exporter::new(export_config)
+        .inspect_err(|e| tracing::warn!("export disabled: {}", e))
+        .ok()
+}
 
-    #[tokio::test]
-    async fn test_open_empty_url() {
-        let db = CacheDb::open_in_memory().await.unwrap();
-        let config = AppConfig::default();
-        let params = WebOpenParams {
-            url: "".into(),
-            mode: "readable".into(),
-            max_bytes: 5 * 1024 * 1024,
-            force_refresh: false,
-            timeout_ms: 20000,
-            accept: None,
-            extract: None,
-            debug: false,
-        };
+/// Export a newly cached document in the background.
+fn spawn_export(exporter: &Exporter, output: &WebOpenOutput) {
+    let exporter = exporter.clone();
+    let doc = ExportDoc {
+        hash: output.hash.clone(),
+        title: output.title.clone(),
+        markdown: output.markdown.clone(),
+        payload: serde_json::to_value(output).unwrap_or_default(),
+    };
+    tokio::spawn(async move { exporter.export(&doc).await });
+}
 
-        let result = open_impl(&db, &config, params).await;
-        assert!(result.is_err());
+/// Cut the markdown down to the caller's `selector`, applied to the page body
+/// `page`, and then to their `section`.
+fn scope_output(mut output: WebOpenOutput, params: &WebOpenParams, page: Option<&str>) -> Result<WebOpenOutput, Error> {
+    let Some(markdown) = output.markdown.take() else {
+        return Ok(output);
+    };
+    let markdown = match (&params.selector, page) {
+        (Some(selector), Some(page)) => scope_to_selector(&markdown, page, selector)?,
+        _ => markdown,
+    };
+    let markdown = match &params.section {
+        Some(section) => scope_to_section(&markdown, section)?,
+        None => markdown,
+    };
+    output.markdown = Some(markdown);
+    Ok(output)
+}
+
+/// Keep only the caller's `page` of the markdown when they set `max_chars`.
+fn paginate_output(mut output: WebOpenOutput, params: &WebOpenParams) -> Result<WebOpenOutput, Error> {
+    let (Some(max_chars), Some(markdown)) = (params.max_chars, output.markdown.as_deref()) else {
+        return Ok(output);
+    };
+    let pages = paginate(markdown, max_chars);
+    let total_pages = u32::try_from(pages.len()).unwrap_or(u32::MAX);
+    let page = params.page.unwrap_or(1);
+    let Some(content) = pages.get(page as usize - 1) else {
+        return Err(Error::InvalidInput(format!(
+            "page {page} is past the last page; the markdown has {total_pages} at max_chars={max_chars}"
+        )));
+    };
+    let pagination =
+        Pagination { page, total_pages, total_chars: markdown.chars().count(), truncated: page < total_pages };
+    output.markdown = Some(content.to_string());
+    output.pagination = Some(pagination);
+    Ok(output)
+}
+
+/// Apply the caller's `max_tokens` budget to the markdown and record the payload's token estimate.
+///
+/// Only the returned copy is trimmed; the cached snapshot keeps the full document.
+fn apply_token_budget(mut output: WebOpenOutput, max_tokens: Option<usize>) -> WebOpenOutput {
+    if let Some(max_tokens) = max_tokens
+        && let Some(markdown) = output.markdown.as_deref()
+    {
+        let (trimmed, truncated) = truncate_to_tokens(markdown, max_tokens);
+        output.markdown = Some(trimmed);
+        output.truncated = truncated;
+    }
+
+    output.approx_tokens = output
+        .markdown
+        .as_deref()
+        .or(output.raw.as_deref())
+        .map_or(0, estimate_tokens);
+    output
+}
+
+/// Strip the normalized frontmatter header, returning only the document body.
+fn markdown_body(markdown: &str) -> &str {
+    markdown
+        .strip_prefix("---\n")
+        .and_then(|rest| rest.find("\n---\n").map(|end| &rest[end + 5..]))
+        .unwrap_or(markdown)
+}
+
+/// Host of `final_url` when a redirect left the host of `url`.
+fn cross_host_redirect(url: &str, final_url: &str) -> Option<String> {
+    let final_host = url_host(final_url)?;
+    (url_host(url).as_deref() != Some(final_host.as_str())).then_some(final_host)
+}
+
+/// The call's extraction config with its site config choice: the config it
+/// names, or none when `use_siteconfig` is off; otherwise one is looked up by
+/// host once the final URL is known.
+fn site_extract_config(params: &WebOpenParams, siteconfigs: &SiteConfigRegistry) -> Result<ExtractConfig, Error> {
+    let mut extract = extract_config(params.extract.as_ref())?;
+    extract.use_siteconfig = params.use_siteconfig;
+    let Some(id) = &params.siteconfig_id else {
+        return Ok(extract);
+    };
+    if !params.use_siteconfig {
+        return Err(Error::InvalidInput("siteconfig_id requires use_siteconfig".into()));
+    }
+    let site = siteconfigs.get(id).ok_or_else(|| {
+        let loaded: Vec<&str> = siteconfigs.ids().collect();
+        let loaded = if loaded.is_empty() { "none".to_string() } else { loaded.join(", ") };
+        Error::InvalidInput(format!("unknown siteconfig_id: {id} (loaded: {loaded})"))
+    })?;
+    extract.siteconfig = Some(site.clone());
+    Ok(extract)
+}
+
+/// Extraction tuning as an [`ExtractConfig`], falling back to the defaults.
+fn extract_config(tuning: Option<&ExtractTuning>) -> Result<ExtractConfig, Error> {
+    let Some(t) = tuning else {
+        return Ok(ExtractConfig::default());
+    };
+    Ok(ExtractConfig {
+        strategy: t.strategy.as_deref().map(str::parse).transpose()?.unwrap_or_default(),
+        fallback: t.fallback.unwrap_or(true),
+        char_threshold: t.char_threshold,
+        max_top_candidates: t.max_top_candidates,
+        ..Default::default()
+    })
+}
+
+/// Links stored with a cached snapshot, and whether they failed to parse.
+///
+/// A malformed `links_json` is logged and flagged on the output rather than
+/// passed off as a page without links.
+fn cached_links(snapshot: &Snapshot) -> (Vec<ExtractedLink>, bool) {
+    match snapshot.links() {
+        Ok(links) => (links, false),
+        Err(e) => {
+            tracing::warn!("unreadable links_json in snapshot {}: {}", snapshot.hash, e);
+            (Vec::new(), true)
+        }
+    }
+}
+
+/// Return the previous snapshot when its extraction can be reused for a refetched body.
+///
+/// Reuse requires a byte-identical body, the same extraction settings, and the
+/// same extractor and pipeline versions as were recorded on the snapshot;
+/// anything else goes through the full pipeline.
+fn reusable_extraction<'a>(
+    previous: Option<&'a Snapshot>, content_hash: &str, extract_cfg_json: Option<&str>, siteconfig_id: Option<&str>,
+) -> Option<&'a Snapshot> {
+    previous.filter(|s| {
+        s.markdown.is_some()
+            && s.content_hash.as_deref() == Some(content_hash)
+            && s.extract_cfg_json.as_deref() == extract_cfg_json
+            && s.siteconfig_id.as_deref() == siteconfig_id
+            && !is_stale_extraction(s)
+    })
+}
+
+/// When a snapshot fetched at `fetched_at` expires: after `ttl_seconds`, or the
+/// response's `Cache-Control: max-age` if that is shorter. `None` (never) when
+/// the TTL is 0 or too large to represent.
+pub fn snapshot_expiry(fetched_at: DateTime<Utc>, headers: &[(String, String)], ttl_seconds: u64) -> Option<String> {
+    if ttl_seconds == 0 {
+        return None;
+    }
+    let max_age = headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("cache-control"))
+        .flat_map(|(_, value)| value.split(','))
+        .filter_map(|directive| directive.trim().split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("max-age"))
+        .and_then(|(_, secs)| secs.trim().trim_matches('"').parse::<u64>().ok());
+    let ttl = max_age.map_or(ttl_seconds, |age| age.min(ttl_seconds));
+    let ttl = chrono::Duration::try_seconds(i64::try_from(ttl).ok()?)?;
+    fetched_at.checked_add_signed(ttl).map(|at| at.to_rfc3339())
+}
+
+/// Find a fresh snapshot of the same document in another mode and rebuild a fetch result from its stored body.
+///
+/// Raw snapshots always keep the body; readable ones do when `store_raw` was on.
+/// The body must be complete, fit in this request's `max_bytes`, and have an
+/// allowed Content-Type. Returns the sibling's hash with the rebuilt result.
+async fn reusable_body(
+    db: &CacheDb, config: &AppConfig, url: &url::Url, vary: &CacheVary<'_>, params: &WebOpenParams,
+) -> Option<(String, PipelineResult)> {
+    let sibling_mode = match params.mode {
+        Mode::Readable => Mode::Raw,
+        Mode::Raw => Mode::Readable,
+        Mode::Rendered | Mode::Feed => return None,
+    };
+    let hash = cache_key(url, vary, sibling_mode);
+    if !db.is_snapshot_fresh(&hash).await.unwrap_or(false) {
+        return None;
+    }
+
+    let snapshot = db.get_snapshot(&hash).await.ok().flatten()?;
+    let charset = stored_charset(&snapshot).map(str::to_string);
+    let bytes = snapshot
+        .raw_bytes
+        .filter(|b| !snapshot.raw_truncated && b.len() <= params.max_bytes)?;
+    if !params.allow_any_content_type
+        && let Some(ct) = &snapshot.content_type
+        && !is_allowed_content_type(ct, &config.fetch.allowed_content_types)
+    {
+        return None;
+    }
+
+    let final_url = url::Url::parse(&snapshot.final_url).ok()?;
+    let stored_headers = snapshot
+        .headers_json
+        .as_deref()
+        .and_then(|j| serde_json::from_str(j).ok())
+        .unwrap_or_default();
+    let fetched_at = DateTime::parse_from_rfc3339(&snapshot.fetched_at)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| db.now());
+
+    let response = FetchResponse::from_stored(
+        url.clone(),
+        final_url,
+        snapshot.status_code.unwrap_or(200) as u16,
+        snapshot.content_type,
+        bytes,
+        stored_headers,
+    );
+    let mut result = PipelineResult::new(response, fetched_at);
+    result.charset = charset;
+    // Older raw snapshots kept the decoded text, so keep the hash of the original bytes.
+    if let Some(content_hash) = snapshot.content_hash {
+        result.content_hash = content_hash;
+    }
+    Some((hash, result))
+}
+
+/// Charset to decode a snapshot's stored body with.
+///
+/// Snapshots from before `detected_charset` was recorded kept raw-mode bodies
+/// as UTF-8 text; readable ones fall back to the stored Content-Type.
+pub fn stored_charset(snapshot: &Snapshot) -> Option<&str> {
+    snapshot.detected_charset.as_deref().or(match snapshot.mode {
+        Mode::Raw => Some("utf-8"),
+        _ => snapshot.content_type.as_deref().and_then(charset),
+    })
+}
+
+/// URL canonicalization the server applies to web_open URLs and cache lookups by URL.
+pub fn canonicalize_options(config: &AppConfig) -> CanonicalizeOptions {
+    CanonicalizeOptions {
+        strip_tracking_params: config.strip_tracking_params,
+        extra_tracking_params: config.extra_tracking_params.clone(),
+        strip_credentials: config.strip_url_credentials,
+        max_len: config.max_url_length,
+    }
+}
+
+/// Build the fetch client web_open and web_batch_open share across calls.
+///
+/// It holds the server-wide settings; each call passes its own limits, agent,
+/// and headers as [`RequestOptions`]. robots.txt persists to `db`, and host
+/// failure state lives in `circuit`.
+pub fn fetch_client(config: &AppConfig, db: &CacheDb, circuit: &CircuitBreaker) -> Result<FetchClient, Error> {
+    let fetch_config = FetchConfig {
+        user_agent: config.user_agent.clone(),
+        max_bytes: default_max_bytes(),
+        timeout: std::time::Duration::from_millis(default_timeout_ms()),
+        respect_robots: config.respect_robots,
+        accept_language: config.accept_language.clone(),
+        allowed_content_types: config.fetch.allowed_content_types.clone(),
+        domain_policy: DomainPolicy::from_config(config),
+        allow_private_addresses: config.allow_private_addresses,
+        per_host_interval: config.fetch.per_host_interval(),
+        robots_failure_policy: config.fetch.robots_failure_policy,
+        strip_tracking_params: config.strip_tracking_params,
+        extra_tracking_params: config.extra_tracking_params.clone(),
+        strip_url_credentials: config.strip_url_credentials,
+        max_url_len: config.max_url_length,
+        retries: config.fetch.retries,
+        retry_backoff: config.fetch.retry_backoff(),
+        detect_bot_protection: config.detect_bot_protection,
+        ..Default::default()
+    };
+    Ok(FetchClient::new_with_cache(fetch_config, db.clone())?.with_circuit_breaker(circuit.clone()))
+}
+
+/// Reject a per-call User-Agent unless the server allows overrides and it is a usable header value.
+pub fn check_user_agent(config: &AppConfig, user_agent: Option<&str>) -> Result<(), Error> {
+    let Some(ua) = user_agent else {
+        return Ok(());
+    };
+    if !config.fetch.allow_user_agent_override {
+        return Err(Error::InvalidInput(
+            "user_agent overrides are disabled (set MCP_WEB_FETCH__ALLOW_USER_AGENT_OVERRIDE=true)".into(),
+        ));
+    }
+    if ua.trim().is_empty() || ua.len() > MAX_USER_AGENT_LEN || ua.chars().any(char::is_control) {
+        return Err(Error::InvalidInput(format!(
+            "user_agent must be non-empty printable text of at most {} bytes",
+            MAX_USER_AGENT_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// Check caller-supplied request headers and build the map sent with the fetch.
+pub fn check_headers(config: &AppConfig, headers: Option<&HashMap<String, String>>) -> Result<HeaderMap, Error> {
+    let Some(headers) = headers else {
+        return Ok(Default::default());
+    };
+    request_headers(
+        headers.iter().map(|(name, value)| (name.as_str(), value.as_str())),
+        config.fetch.allow_user_agent_override,
+    )
+}
+
+/// Reject a cache partition unless the server caches private content per partition.
+pub fn check_cache_partition(config: &AppConfig, partition: Option<&str>) -> Result<(), Error> {
+    let Some(partition) = partition else {
+        return Ok(());
+    };
+    if config.cache.allow_private_content != PrivateContentPolicy::Partitioned {
+        return Err(Error::InvalidInput(
+            "cache_partition requires MCP_WEB_CACHE__ALLOW_PRIVATE_CONTENT=partitioned".into(),
+        ));
+    }
+    if partition.trim().is_empty()
+        || partition.len() > MAX_CACHE_PARTITION_LEN
+        || partition.chars().any(char::is_control)
+    {
+        return Err(Error::InvalidInput(format!(
+            "cache_partition must be non-empty printable text of at most {} bytes",
+            MAX_CACHE_PARTITION_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// How to cache a response that is private to the requester; `None` when it can be shared.
+///
+/// `request_headers` are the caller's extra headers; any credential among them
+/// makes the response private. With a partition (only accepted under the
+/// `partitioned` policy) the snapshot is stored under the partitioned hash;
+/// without one it is not stored at all.
+fn private_content_decision(
+    url: &url::Url, request_headers: &HeaderMap, response: &FetchResponse, partition: Option<&str>,
+) -> Option<CacheDecision> {
+    let reason = private_content_reason(url, request_headers, &response.headers)?;
+    Some(CacheDecision {
+        decision: if partition.is_some() { "partitioned" } else { "skipped" }.into(),
+        reason: reason.into(),
+        partition: partition.map(str::to_string),
+    })
+}
+
+/// Fail with `BLOCKED_BY_BOT_PROTECTION` if the page is a challenge, so it is never cached.
+///
+/// `rendered_html` stands in for the fetched body and headers in rendered mode.
+fn refuse_challenge(
+    config: &AppConfig, response: &FetchResponse, rendered_html: Option<&str>, hint_ctx: &HintContext,
+) -> Result<(), McpError> {
+    if !config.detect_bot_protection {
+        return Ok(());
+    }
+    let challenge = match rendered_html {
+        Some(html) => detect_challenge(response.status, &Default::default(), html.as_bytes()),
+        None => detect_challenge(response.status, &response.headers, &response.bytes),
+    };
+    match challenge {
+        Some(challenge) => {
+            tracing::info!("{} served a {}", response.final_url, challenge);
+            Err(error_with_hints(
+                Error::BlockedByBotProtection(challenge.to_string()),
+                hint_ctx,
+            ))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Whether a snapshot's extraction came from a different extractor or pipeline version.
+///
+/// Raw snapshots carry no extraction and are never stale.
+fn is_stale_extraction(snapshot: &Snapshot) -> bool {
+    snapshot.mode != Mode::Raw
+        && (snapshot.pipeline_version != Some(PIPELINE_VERSION)
+            || !is_current_extractor(snapshot.extractor_version.as_deref()))
+}
+
+/// Whether `version` is one of the extractors web_open runs today.
+fn is_current_extractor(version: Option<&str>) -> bool {
+    match version {
+        Some(LECTITO_EXTRACTOR_VERSION) => true,
+        #[cfg(feature = "pdf")]
+        Some(thndrs_client::PDF_EXTRACTOR_VERSION) => true,
+        Some(FEED_EXTRACTOR_VERSION | PLAIN_TEXT_EXTRACTOR_VERSION) => true,
+        _ => false,
+    }
+}
+
+/// The extraction strategy a snapshot's markdown came from, for HTML pages.
+fn snapshot_strategy(snapshot: &Snapshot) -> Option<ExtractStrategy> {
+    let html = snapshot.mode == Mode::Rendered
+        || snapshot.mode == Mode::Readable && body_kind(snapshot.content_type.as_deref(), &[]) == BodyKind::Html;
+    match snapshot.extractor_name.as_deref() {
+        Some("plain-text") if html => Some(ExtractStrategy::PlainText),
+        Some("lectito-core") if html => Some(ExtractStrategy::Readability),
+        _ => None,
+    }
+}
+
+/// Name part of an extractor version such as `pdf-extract@0.9.0`.
+fn extractor_name(version: &str) -> &str {
+    version.split_once('@').map_or(version, |(name, _)| name)
+}
+
+/// Save a snapshot, returning whether the write failed.
+///
+/// A failed write (disk full, database locked) is logged instead of failing the
+/// call: the fetched and extracted result is still good, so the caller returns
+/// it with `cache_write_failed` set rather than making the user fetch again.
+pub async fn store_snapshot(db: &CacheDb, snapshot: Snapshot) -> bool {
+    let (writer, url) = (db.clone(), snapshot.url.clone());
+    match deadline::cache_write(async move { writer.upsert_snapshot(&snapshot).await }).await {
+        Ok(()) => false,
+        Err(e) => {
+            tracing::warn!("failed to cache snapshot for {}: {}", url, e);
+            true
+        }
+    }
+}
+
+/// Re-run readable extraction over a stale snapshot's stored body and save the result.
+///
+/// Returns the snapshot and whether saving it failed, or None when the snapshot
+/// has no stored HTML body to re-extract (or was rendered), in which case the
+/// caller refetches.
+async fn reextract_stored(
+    db: &CacheDb, mut snapshot: Snapshot, tuning: Option<&ExtractTuning>, extract: &ExtractConfig,
+    siteconfigs: &SiteConfigRegistry,
+) -> Result<Option<(Snapshot, bool)>, Error> {
+    if snapshot.mode != Mode::Readable {
+        return Ok(None);
+    }
+    let Some(bytes) = snapshot.raw_bytes.as_deref() else {
+        return Ok(None);
+    };
+    if body_kind(snapshot.content_type.as_deref(), bytes) != BodyKind::Html {
+        return Ok(None);
+    }
+
+    let html = decode_body(bytes, stored_charset(&snapshot));
+    let final_url = url::Url::parse(&snapshot.final_url).map_err(|e| Error::InvalidUrl(e.to_string()))?;
+    let fetched_at = DateTime::parse_from_rfc3339(&snapshot.fetched_at)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| db.now());
+
+    let extract = siteconfigs.configure(extract, &final_url);
+    deadline::enter(Phase::Extract);
+    let extraction =
+        extract_with_budget(Arc::new(LectitoExtractor::new()), html, final_url, fetched_at, extract).await?;
+    let links: Vec<ExtractedLink> = extraction
+        .links
+        .into_iter()
+        .map(|l| ExtractedLink { text: l.text, href: l.href, kind: l.kind, in_content: l.in_content })
+        .collect();
+
+    snapshot.title = extraction.title;
+    snapshot.markdown = Some(extraction.markdown);
+    snapshot.links_json = Some(StoredLinks::new(links).with_feeds(extraction.feeds).to_json());
+    snapshot.metadata_json = extraction.metadata.to_json();
+    snapshot.structured_data_json = structured_data_json(&extraction.structured_data);
+    snapshot.images_json = images_json(&extraction.images);
+    snapshot.tables_json = tables_json(&extraction.tables);
+    snapshot.outline_json = outline_json(&extraction.outline);
+    snapshot.quality_json = quality_json(extraction.quality.as_ref());
+    snapshot.extractor_name = Some(extractor_name(&extraction.extractor_version).to_string());
+    snapshot.extractor_version = Some(extraction.extractor_version);
+    snapshot.pipeline_version = Some(PIPELINE_VERSION);
+    snapshot.siteconfig_id = extraction.siteconfig_id;
+    snapshot.extract_cfg_json = serde_json::to_string(&tuning.cloned().unwrap_or_default()).ok();
+    snapshot.extract_ms = Some(extraction.extract_ms as i64);
+
+    let cache_write_failed = store_snapshot(db, snapshot.clone()).await;
+    tracing::debug!("re-extracted {} from stored body", snapshot.url);
+    Ok(Some((snapshot, cache_write_failed)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use thndrs_client::fetch::canonicalize;
+    use thndrs_core::cache::hash::compute_content_hash;
+
+    #[test]
+    fn test_schema_omits_feed_mode() {
+        let schema = serde_json::to_value(schemars::schema_for!(WebOpenParams))
+            .unwrap()
+            .to_string();
+        for mode in ["\"readable\"", "\"raw\"", "\"rendered\""] {
+            assert!(schema.contains(mode), "{mode}");
+        }
+        assert!(!schema.contains("\"feed\""));
+    }
+
+    #[tokio::test]
+    async fn test_open_empty_url() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig::default();
+        let params = WebOpenParams {
+            url: "".into(),
+            mode: Mode::Readable,
+            max_bytes: 5 * 1024 * 1024,
+            force_refresh: false,
+            timeout_ms: 20000,
+            accept: None,
+            language: None,
+            headers: None,
+            use_siteconfig: true,
+            siteconfig_id: None,
+            extract: None,
+            debug: false,
+            include_structured_data: false,
+            include_images: false,
+            include_tables: false,
+            links: LinkFilter::All,
+            selector: None,
+            section: None,
+            max_tokens: None,
+            max_chars: None,
+            page: None,
+            allow_any_content_type: false,
+            store_raw: None,
+            user_agent: None,
+            cache_partition: None,
+            wait_for: None,
+            render_timeout_ms: None,
+        };
+
+        let result = open_impl(
+            &db,
+            &config,
+            &shared_client(&db, &config),
+            None,
+            &Default::default(),
+            None,
+            params,
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_open_allow_any_content_type_requires_raw() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig::default();
+        let params = WebOpenParams {
+            url: "https://example.com/clip.mp4".into(),
+            mode: Mode::Readable,
+            max_bytes: 5 * 1024 * 1024,
+            force_refresh: false,
+            timeout_ms: 20000,
+            accept: None,
+            language: None,
+            headers: None,
+            use_siteconfig: true,
+            siteconfig_id: None,
+            extract: None,
+            debug: false,
+            include_structured_data: false,
+            include_images: false,
+            include_tables: false,
+            links: LinkFilter::All,
+            selector: None,
+            section: None,
+            max_tokens: None,
+            max_chars: None,
+            page: None,
+            allow_any_content_type: true,
+            store_raw: None,
+            user_agent: None,
+            cache_partition: None,
+            wait_for: None,
+            render_timeout_ms: None,
+        };
+
+        let err = open_impl(
+            &db,
+            &config,
+            &shared_client(&db, &config),
+            None,
+            &Default::default(),
+            None,
+            params,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.message.contains("allow_any_content_type"));
+    }
+
+    #[tokio::test]
+    async fn test_open_invalid_language() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig::default();
+        let params = WebOpenParams {
+            url: "https://example.com".into(),
+            mode: Mode::Readable,
+            max_bytes: 5 * 1024 * 1024,
+            force_refresh: false,
+            timeout_ms: 20000,
+            accept: None,
+            language: Some("en\r\nX-Injected: 1".into()),
+            headers: None,
+            use_siteconfig: true,
+            siteconfig_id: None,
+            extract: None,
+            debug: false,
+            include_structured_data: false,
+            include_images: false,
+            include_tables: false,
+            links: LinkFilter::All,
+            selector: None,
+            section: None,
+            max_tokens: None,
+            max_chars: None,
+            page: None,
+            allow_any_content_type: false,
+            store_raw: None,
+            user_agent: None,
+            cache_partition: None,
+            wait_for: None,
+            render_timeout_ms: None,
+        };
+
+        let result = open_impl(
+            &db,
+            &config,
+            &shared_client(&db, &config),
+            None,
+            &Default::default(),
+            None,
+            params,
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_open_exports_markdown() {
+        use thndrs_client::export::export_filename;
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let paragraph = "<p>Exported documents land in the notes directory with the normalized frontmatter \
+                         so they can be ingested without another client. This sentence pads the article.</p>";
+        let html = format!(
+            "<html><head><title>Export Guide</title></head><body><article>{}</article></body></html>",
+            paragraph.repeat(8)
+        );
+        let server = FixtureServer::start(move |_| FixtureResponse::html(html.clone())).await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            export_dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let params = WebOpenParams {
+            url: server.url("/guide"),
+            mode: Mode::Readable,
+            max_bytes: 5 * 1024 * 1024,
+            force_refresh: false,
+            timeout_ms: 20000,
+            accept: None,
+            language: None,
+            headers: None,
+            use_siteconfig: true,
+            siteconfig_id: None,
+            extract: None,
+            debug: false,
+            include_structured_data: false,
+            include_images: false,
+            include_tables: false,
+            links: LinkFilter::All,
+            selector: None,
+            section: None,
+            max_tokens: None,
+            max_chars: None,
+            page: None,
+            allow_any_content_type: false,
+            store_raw: None,
+            user_agent: None,
+            cache_partition: None,
+            wait_for: None,
+            render_timeout_ms: None,
+        };
+
+        let result = open_impl(
+            &db,
+            &config,
+            &shared_client(&db, &config),
+            None,
+            &Default::default(),
+            exporter(&config).as_ref(),
+            params,
+        )
+        .await
+        .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        let output: WebOpenOutput = serde_json::from_str(&text).unwrap();
+
+        let path = dir.path().join(export_filename(output.title.as_deref(), &output.hash));
+        for _ in 0..100 {
+            if path.exists() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(Some(written), output.markdown);
+    }
+
+    #[tokio::test]
+    async fn test_open_forbidden_attaches_hints() {
+        use crate::tools::hints::{ACCESS_DENIED, hints_from_error};
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| FixtureResponse::status(403)).await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let params = WebOpenParams {
+            url: server.url("/private"),
+            mode: Mode::Readable,
+            max_bytes: 5 * 1024 * 1024,
+            force_refresh: false,
+            timeout_ms: 20000,
+            accept: None,
+            language: None,
+            headers: None,
+            use_siteconfig: true,
+            siteconfig_id: None,
+            extract: None,
+            debug: false,
+            include_structured_data: false,
+            include_images: false,
+            include_tables: false,
+            links: LinkFilter::All,
+            selector: None,
+            section: None,
+            max_tokens: None,
+            max_chars: None,
+            page: None,
+            allow_any_content_type: false,
+            store_raw: None,
+            user_agent: None,
+            cache_partition: None,
+            wait_for: None,
+            render_timeout_ms: None,
+        };
+
+        let err = open_impl(
+            &db,
+            &config,
+            &shared_client(&db, &config),
+            None,
+            &Default::default(),
+            None,
+            params,
+        )
+        .await
+        .unwrap_err();
+        let hints = hints_from_error(&err);
+        assert_eq!(hints.first().map(|h| h.code.as_str()), Some(ACCESS_DENIED));
+    }
+
+    #[test]
+    fn test_apply_token_budget() {
+        let output = WebOpenOutput {
+            url: "https://example.com".into(),
+            final_url: "https://example.com".into(),
+            cross_host_redirect: None,
+            content_type: Some("text/html".into()),
+            detected_charset: None,
+            fetched_at: Utc::now().to_rfc3339(),
+            mode: Mode::Readable,
+            raw: None,
+            markdown: Some("# Title\n\nFirst paragraph.\n\nSecond paragraph.".into()),
+            strategy: None,
+            title: Some("Title".into()),
+            metadata: PageMetadata::default(),
+            structured_data: None,
+            images: None,
+            tables: None,
+            outline: Vec::new(),
+            links: vec![],
+            links_parse_error: false,
+            feeds: Vec::new(),
+            hash: "abc".into(),
+            approx_tokens: 0,
+            truncated: false,
+            pagination: None,
+            hints: Vec::new(),
+            cache_write_failed: false,
+            cache: None,
+            cache_hit: false,
+            bytes_fetched: 0,
+            fetch_ms: 0,
+            debug: None,
+        };
+
+        let full = apply_token_budget(output.clone(), None);
+        assert_eq!(full.approx_tokens, 13);
+        assert!(!full.truncated);
+
+        let trimmed = apply_token_budget(output, Some(8));
+        assert_eq!(trimmed.markdown.as_deref(), Some("# Title\n\nFirst paragraph."));
+        assert_eq!(trimmed.approx_tokens, 7);
+        assert!(trimmed.truncated);
+    }
+
+    #[test]
+    fn test_markdown_body() {
+        assert_eq!(markdown_body("---\ntitle: T\n---\n# Body"), "# Body");
+        assert_eq!(markdown_body("# No header"), "# No header");
+    }
+
+    fn make_snapshot(content_hash: &str, extract_cfg_json: &str) -> Snapshot {
+        Snapshot {
+            content_hash: Some(content_hash.to_string()),
+            extractor_version: Some(LECTITO_EXTRACTOR_VERSION.to_string()),
+            pipeline_version: Some(PIPELINE_VERSION),
+            extract_cfg_json: Some(extract_cfg_json.to_string()),
+            ..Snapshot::test_page("https://example.com")
+        }
+    }
+
+    #[test]
+    fn test_reusable_extraction_identical_body() {
+        let body_hash = compute_content_hash(b"<html>same</html>");
+        let cfg = serde_json::to_string(&ExtractTuning::default()).unwrap();
+        let previous = make_snapshot(&body_hash, &cfg);
+
+        let reused = reusable_extraction(Some(&previous), &body_hash, Some(&cfg), None);
+        assert!(reused.is_some());
+    }
+
+    #[test]
+    fn test_reusable_extraction_changed_body() {
+        let cfg = serde_json::to_string(&ExtractTuning::default()).unwrap();
+        let previous = make_snapshot(&compute_content_hash(b"<html>old</html>"), &cfg);
+
+        let new_hash = compute_content_hash(b"<html>new</html>");
+        assert!(reusable_extraction(Some(&previous), &new_hash, Some(&cfg), None).is_none());
+    }
+
+    #[test]
+    fn test_reusable_extraction_config_change_forces_reextract() {
+        let body_hash = compute_content_hash(b"<html>same</html>");
+        let old_cfg = serde_json::to_string(&ExtractTuning::default()).unwrap();
+        let new_cfg = serde_json::to_string(&ExtractTuning { char_threshold: Some(50), ..Default::default() }).unwrap();
+        let previous = make_snapshot(&body_hash, &old_cfg);
+
+        assert!(reusable_extraction(Some(&previous), &body_hash, Some(&new_cfg), None).is_none());
+        assert!(reusable_extraction(None, &body_hash, Some(&old_cfg), None).is_none());
+        assert!(reusable_extraction(Some(&previous), &body_hash, Some(&old_cfg), Some("docs")).is_none());
+    }
+
+    #[test]
+    fn test_reusable_extraction_version_change_forces_reextract() {
+        let body_hash = compute_content_hash(b"<html>same</html>");
+        let cfg = serde_json::to_string(&ExtractTuning::default()).unwrap();
+
+        let old_pipeline = Snapshot { pipeline_version: Some(PIPELINE_VERSION - 1), ..make_snapshot(&body_hash, &cfg) };
+        assert!(is_stale_extraction(&old_pipeline));
+        assert!(reusable_extraction(Some(&old_pipeline), &body_hash, Some(&cfg), None).is_none());
+
+        let old_extractor =
+            Snapshot { extractor_version: Some("lectito-core@0.2.0".into()), ..make_snapshot(&body_hash, &cfg) };
+        assert!(is_stale_extraction(&old_extractor));
+
+        let raw = Snapshot { mode: Mode::Raw, pipeline_version: None, ..make_snapshot(&body_hash, &cfg) };
+        assert!(!is_stale_extraction(&raw));
+    }
+
+    fn article_html(title: &str) -> String {
+        let paragraph = "<p>Cached extractions are redone when the pipeline version changes so that readers \
+                         never see output from an older normalizer. This sentence pads the article.</p>";
+        format!(
+            "<html><head><title>{title}</title></head><body><article>{}</article></body></html>",
+            paragraph.repeat(8)
+        )
+    }
+
+    fn readable_params(url: String) -> WebOpenParams {
+        WebOpenParams {
+            url,
+            mode: Mode::Readable,
+            max_bytes: 5 * 1024 * 1024,
+            force_refresh: false,
+            timeout_ms: 20000,
+            accept: None,
+            language: None,
+            headers: None,
+            use_siteconfig: true,
+            siteconfig_id: None,
+            extract: None,
+            debug: false,
+            include_structured_data: false,
+            include_images: false,
+            include_tables: false,
+            links: LinkFilter::All,
+            selector: None,
+            section: None,
+            max_tokens: None,
+            max_chars: None,
+            page: None,
+            allow_any_content_type: false,
+            store_raw: None,
+            user_agent: None,
+            cache_partition: None,
+            wait_for: None,
+            render_timeout_ms: None,
+        }
+    }
+
+    fn shared_client(db: &CacheDb, config: &AppConfig) -> Arc<FetchClient> {
+        Arc::new(fetch_client(config, db, &CircuitBreaker::default()).unwrap())
+    }
+
+    fn user_agent_override() -> thndrs_core::FetchSettings {
+        thndrs_core::FetchSettings { allow_user_agent_override: true, ..Default::default() }
+    }
+
+    async fn open_output(db: &CacheDb, config: &AppConfig, url: String) -> WebOpenOutput {
+        let result = open_impl(
+            db,
+            config,
+            &shared_client(db, config),
+            None,
+            &Default::default(),
+            None,
+            readable_params(url),
+        )
+        .await
+        .unwrap();
+        serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_open_sets_expiry_and_purge_removes_expired() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|req| match req.path.as_str() {
+            "/short" => FixtureResponse::html(article_html("Short")).with_header("Cache-Control", "public, max-age=60"),
+            _ => FixtureResponse::html(article_html("Long")),
+        })
+        .await;
+        let clock = thndrs_core::ManualClock::default();
+        let db = CacheDb::open_in_memory().await.unwrap().with_clock(clock.clone());
+        let config = AppConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            snapshot_ttl_seconds: 3600,
+            ..Default::default()
+        };
+
+        let long = open_output(&db, &config, server.url("/long")).await;
+        let short = open_output(&db, &config, server.url("/short")).await;
+        for (hash, ttl) in [(&long.hash, 3600), (&short.hash, 60)] {
+            let snapshot = db.get_snapshot(hash).await.unwrap().unwrap();
+            let expires_at = DateTime::parse_from_rfc3339(snapshot.expires_at.as_deref().unwrap()).unwrap();
+            assert_eq!(expires_at, clock.now() + chrono::Duration::seconds(ttl));
+        }
+
+        clock.advance(chrono::Duration::seconds(61));
+        assert_eq!(db.purge_expired_snapshots(false).await.unwrap(), 1);
+        assert!(db.get_snapshot(&short.hash).await.unwrap().is_none());
+        assert!(db.is_snapshot_fresh(&long.hash).await.unwrap());
+
+        clock.advance(chrono::Duration::seconds(3600));
+        assert!(!db.is_snapshot_fresh(&long.hash).await.unwrap());
+        assert_eq!(db.purge_expired_snapshots(false).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_snapshot_is_refetched() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let server = FixtureServer::start(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            FixtureResponse::html(article_html("Expiring")).with_header("Cache-Control", "public, max-age=60")
+        })
+        .await;
+        let clock = thndrs_core::ManualClock::default();
+        let db = CacheDb::open_in_memory().await.unwrap().with_clock(clock.clone());
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+
+        assert!(!open_output(&db, &config, server.url("/page")).await.cache_hit);
+        assert!(open_output(&db, &config, server.url("/page")).await.cache_hit);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        clock.advance(chrono::Duration::seconds(61));
+        let refetched = open_output(&db, &config, server.url("/page")).await;
+        assert!(!refetched.cache_hit);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert!(db.is_snapshot_fresh(&refetched.hash).await.unwrap());
+    }
+
+    #[test]
+    fn test_snapshot_expiry_caps_max_age_at_ttl() {
+        let fetched = DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let header = |value: &str| vec![("cache-control".to_string(), value.to_string())];
+        let expiry = |headers: &[(String, String)], ttl| {
+            snapshot_expiry(fetched, headers, ttl)
+                .map(|at| (DateTime::parse_from_rfc3339(&at).unwrap().with_timezone(&Utc) - fetched).num_seconds())
+        };
+
+        assert_eq!(expiry(&[], 600), Some(600));
+        assert_eq!(expiry(&header("max-age=30"), 600), Some(30));
+        assert_eq!(expiry(&header("no-cache, Max-Age=\"0\""), 600), Some(0));
+        assert_eq!(expiry(&header("max-age=86400"), 600), Some(600));
+        assert_eq!(expiry(&header("max-age=soon"), 600), Some(600));
+        assert_eq!(expiry(&header("max-age=30"), 0), None);
+        assert_eq!(expiry(&[], u64::MAX), None);
+    }
+
+    #[tokio::test]
+    async fn test_open_stores_sanitized_headers() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| {
+            FixtureResponse::html(article_html("Headers"))
+                .with_header("ETag", "\"v1\"")
+                .with_raw_header("X-Latin1", b"caf\xe9")
+                .with_header("Set-Cookie", &"a".repeat(4096))
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+
+        let params = WebOpenParams { debug: true, ..readable_params(server.url("/doc")) };
+        let result = open_impl(
+            &db,
+            &config,
+            &shared_client(&db, &config),
+            None,
+            &Default::default(),
+            None,
+            params,
+        )
+        .await
+        .unwrap();
+        let output: WebOpenOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+
+        let stored = db.get_snapshot(&output.hash).await.unwrap().unwrap();
+        assert_eq!(stored.etag.as_deref(), Some("\"v1\""));
+        let headers: Vec<(String, String)> = serde_json::from_str(stored.headers_json.as_deref().unwrap()).unwrap();
+        assert!(headers.contains(&("x-latin1".to_string(), "caf\u{fffd}".to_string())));
+        let cookie = &headers.iter().find(|(name, _)| name == "set-cookie").unwrap().1;
+        assert!(cookie.len() < 4096 && cookie.ends_with("[truncated]"));
+
+        let debug = output.debug.unwrap();
+        assert_eq!(debug.response_headers, headers);
+        assert_eq!(debug.user_agent.as_deref(), Some(config.user_agent.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_url_spellings_share_one_snapshot() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let server = FixtureServer::start(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            FixtureResponse::html(article_html("Spellings"))
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+
+        let base = server.url("/page");
+        let spellings = [
+            base.clone(),
+            format!("{base}#frag"),
+            format!("  {}  ", base.replacen("http://", "HTTP://", 1)),
+        ];
+        let mut hashes = Vec::new();
+        for spelling in spellings {
+            let output = open_output(&db, &config, spelling).await;
+            assert_eq!(output.url, base);
+            hashes.push(output.hash);
+        }
+
+        assert!(hashes.iter().all(|h| *h == hashes[0]));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        assert_eq!(db.get_snapshot(&hashes[0]).await.unwrap().unwrap().url, base);
+    }
+
+    #[tokio::test]
+    async fn test_tracking_params_share_one_snapshot() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let requested = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = requested.clone();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let server = FixtureServer::start(move |req| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            log.lock().unwrap().push(req.path.clone());
+            FixtureResponse::html(article_html("Tracked"))
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            extra_tracking_params: vec!["source".into()],
+            ..Default::default()
+        };
+
+        let base = server.url("/post?id=42");
+        let mut hashes = Vec::new();
+        for spelling in [
+            server.url("/post?utm_source=news&id=42&fbclid=abc"),
+            server.url("/post?id=42&utm_medium=email&gclid=1&source=feed"),
+            base.clone(),
+        ] {
+            let output = open_output(&db, &config, spelling).await;
+            assert_eq!(output.url, base);
+            hashes.push(output.hash);
+        }
+        assert!(hashes.iter().all(|h| *h == hashes[0]));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        assert_eq!(*requested.lock().unwrap(), vec!["/post?id=42".to_string()]);
+
+        let config = AppConfig { strip_tracking_params: false, ..config };
+        let kept = open_output(&db, &config, server.url("/post?id=42&utm_source=news")).await;
+        assert_eq!(kept.url, server.url("/post?id=42&utm_source=news"));
+        assert_ne!(kept.hash, hashes[0]);
+    }
+
+    #[tokio::test]
+    async fn test_cached_links_parse_legacy_and_flag_corruption() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let html = article_html("Links").replace("</article>", "<p><a href=\"/next\">Next page</a></p></article>");
+        let server = FixtureServer::start(move |_| FixtureResponse::html(html.clone())).await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+
+        let fresh = open_output(&db, &config, server.url("/doc")).await;
+        let next = ExtractedLink { text: "Next page".into(), href: server.url("/next"), ..Default::default() };
+        assert!(fresh.links.contains(&next));
+        let stored = db.get_snapshot(&fresh.hash).await.unwrap().unwrap();
+        let envelope = StoredLinks::parse(stored.links_json.as_deref().unwrap()).unwrap();
+        assert_eq!(envelope.version, thndrs_core::cache::links::LINKS_VERSION);
+
+        let mut legacy = stored.clone();
+        legacy.links_json = Some(serde_json::to_string(&envelope.links).unwrap());
+        db.upsert_snapshot(&legacy).await.unwrap();
+        let hit = open_output(&db, &config, server.url("/doc")).await;
+        assert_eq!(hit.links, fresh.links);
+        assert!(!hit.links_parse_error);
+
+        let mut corrupt = stored;
+        corrupt.links_json = Some("[{\"text\":".into());
+        db.upsert_snapshot(&corrupt).await.unwrap();
+        let hit = open_output(&db, &config, server.url("/doc")).await;
+        assert!(hit.links.is_empty());
+        assert!(hit.links_parse_error);
+    }
+
+    async fn age_snapshot(db: &CacheDb, hash: &str, edit: impl FnOnce(&mut Snapshot)) {
+        let mut snapshot = db.get_snapshot(hash).await.unwrap().unwrap();
+        snapshot.pipeline_version = Some(PIPELINE_VERSION - 1);
+        edit(&mut snapshot);
+        db.upsert_snapshot(&snapshot).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_version_mismatch_refetches_once() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let server = FixtureServer::start(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            FixtureResponse::html(article_html("Versioned"))
+        })
+        .await;
+
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+
+        let output = open_output(&db, &config, server.url("/doc")).await;
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        let stored = db.get_snapshot(&output.hash).await.unwrap().unwrap();
+        assert_eq!(stored.pipeline_version, Some(PIPELINE_VERSION));
+        assert_eq!(stored.extractor_version.as_deref(), Some(LECTITO_EXTRACTOR_VERSION));
+
+        age_snapshot(&db, &output.hash, |_| {}).await;
+        open_output(&db, &config, server.url("/doc")).await;
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        let stored = db.get_snapshot(&output.hash).await.unwrap().unwrap();
+        assert_eq!(stored.pipeline_version, Some(PIPELINE_VERSION));
+
+        open_output(&db, &config, server.url("/doc")).await;
+        assert_eq!(
+            hits.load(Ordering::SeqCst),
+            2,
+            "refreshed snapshot should be served from cache"
+        );
+
+        age_snapshot(&db, &output.hash, |_| {}).await;
+        let keep_stale = AppConfig { invalidate_on_version_mismatch: false, ..config };
+        open_output(&db, &keep_stale, server.url("/doc")).await;
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_version_mismatch_reextracts_stored_body() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let server = FixtureServer::start(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            FixtureResponse::html(article_html("Original"))
+        })
+        .await;
+
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+
+        let output = open_output(&db, &config, server.url("/doc")).await;
+        age_snapshot(&db, &output.hash, |s| {
+            s.raw_bytes = Some(article_html("Stored Body").into_bytes());
+            s.markdown = Some("stale".into());
+        })
+        .await;
+
+        let output = open_output(&db, &config, server.url("/doc")).await;
+        assert_eq!(
+            hits.load(Ordering::SeqCst),
+            1,
+            "stored body should be re-extracted without a fetch"
+        );
+        assert_eq!(output.title.as_deref(), Some("Stored Body"));
+        assert!(output.markdown.unwrap().contains("pipeline version changes"));
+
+        let stored = db.get_snapshot(&output.hash).await.unwrap().unwrap();
+        assert_eq!(stored.pipeline_version, Some(PIPELINE_VERSION));
+    }
+
+    #[tokio::test]
+    async fn test_open_store_raw_keeps_body_with_markdown() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let html = article_html("Stored");
+        let body = html.clone();
+        let server = FixtureServer::start(move |_| FixtureResponse::html(body.clone())).await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+
+        let params = WebOpenParams { store_raw: Some(true), ..readable_params(server.url("/doc")) };
+        let result = open_impl(
+            &db,
+            &config,
+            &shared_client(&db, &config),
+            None,
+            &Default::default(),
+            None,
+            params,
+        )
+        .await
+        .unwrap();
+        let output: WebOpenOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert!(output.raw.is_none());
+        assert!(!output.cache_hit);
+
+        let stored = db.get_snapshot(&output.hash).await.unwrap().unwrap();
+        assert!(stored.markdown.is_some());
+        assert_eq!(stored.raw_bytes.as_deref(), Some(html.as_bytes()));
+        assert_eq!(db.cache_stats().await.unwrap().raw_bytes, html.len() as u64);
+
+        let cached = open_output(&db, &config, server.url("/doc")).await;
+        assert!(cached.raw.is_none(), "stored body is not returned on cache hits");
+        assert!(cached.cache_hit);
+
+        let other = open_output(&db, &config, server.url("/other")).await;
+        let stored = db.get_snapshot(&other.hash).await.unwrap().unwrap();
+        assert!(stored.raw_bytes.is_none(), "cache.store_raw_default is off");
+    }
+
+    #[tokio::test]
+    async fn test_open_decodes_and_records_declared_charset() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let title = "Новости дня";
+        let body = encoding_rs::WINDOWS_1251.encode(&article_html(title)).0.into_owned();
+        let stored_body = body.clone();
+        let server =
+            FixtureServer::start(move |_| FixtureResponse::ok("text/html; charset=windows-1251", body.clone())).await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+
+        let output = open_output(&db, &config, server.url("/doc")).await;
+        assert_eq!(output.title.as_deref(), Some(title));
+        assert_eq!(output.detected_charset.as_deref(), Some("windows-1251"));
+        let stored = db.get_snapshot(&output.hash).await.unwrap().unwrap();
+        assert_eq!(stored.detected_charset.as_deref(), Some("windows-1251"));
+
+        let raw_params = || WebOpenParams { mode: Mode::Raw, ..readable_params(server.url("/doc")) };
+        for cache_hit in [false, true] {
+            let result = open_impl(
+                &db,
+                &config,
+                &shared_client(&db, &config),
+                None,
+                &Default::default(),
+                None,
+                raw_params(),
+            )
+            .await
+            .unwrap();
+            let output: WebOpenOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+            assert_eq!(output.cache_hit, cache_hit);
+            assert!(output.raw.unwrap().contains(title));
+            assert_eq!(output.detected_charset.as_deref(), Some("windows-1251"));
+
+            let stored = db.get_snapshot(&output.hash).await.unwrap().unwrap();
+            assert_eq!(
+                stored.raw_bytes.as_deref(),
+                Some(&stored_body[..]),
+                "the body is stored as received"
+            );
+        }
+    }
+
+    #[cfg(feature = "pdf")]
+    #[tokio::test]
+    async fn test_open_extracts_pdf_text() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let pdf = include_bytes!("../../../client/fixtures/report.pdf");
+        let server = FixtureServer::start(move |_| FixtureResponse::ok("application/pdf", pdf.to_vec())).await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+
+        let output = open_output(&db, &config, server.url("/report.pdf")).await;
+        assert_eq!(output.title.as_deref(), Some("Quarterly Report"));
+        let markdown = output.markdown.unwrap();
+        assert!(markdown.contains("<!-- page 1 -->"));
+        assert!(markdown.contains("Second page with the conclusions."));
+        assert!(output.hints.is_empty());
+
+        let stored = db.get_snapshot(&output.hash).await.unwrap().unwrap();
+        assert_eq!(stored.extractor_name.as_deref(), Some("pdf-extract"));
+        assert_eq!(
+            stored.extractor_version.as_deref(),
+            Some(thndrs_client::PDF_EXTRACTOR_VERSION)
+        );
+        assert!(!is_stale_extraction(&stored));
+
+        let cached = open_output(&db, &config, server.url("/report.pdf")).await;
+        assert!(cached.cache_hit);
+    }
+
+    #[tokio::test]
+    async fn test_open_plain_text_strategy() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| {
+            FixtureResponse::html("<html><head><title>Moved</title></head><body><p>Now at /v2.</p></body></html>")
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = shared_client(&db, &config);
+        let plain = |strategy: &str| WebOpenParams {
+            extract: Some(ExtractTuning { strategy: Some(strategy.into()), ..Default::default() }),
+            ..readable_params(server.url("/moved"))
+        };
+
+        let err = open_impl(&db, &config, &client, None, &Default::default(), None, plain("bogus"))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("unsupported strategy: bogus"), "{}", err.message);
+
+        let result = open_impl(
+            &db,
+            &config,
+            &client,
+            None,
+            &Default::default(),
+            None,
+            plain("plain_text"),
+        )
+        .await
+        .unwrap();
+        let output: WebOpenOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(output.title.as_deref(), Some("Moved"));
+        assert_eq!(output.strategy.as_deref(), Some("plain_text"));
+        assert!(output.markdown.unwrap().ends_with("---\nNow at /v2."));
+
+        let stored = db.get_snapshot(&output.hash).await.unwrap().unwrap();
+        assert_eq!(stored.extractor_name.as_deref(), Some("plain-text"));
+        assert!(!is_stale_extraction(&stored));
+        assert_eq!(
+            stored.extract_cfg_json.as_deref(),
+            Some(r#"{"char_threshold":null,"max_top_candidates":null,"strategy":"plain_text"}"#)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_open_falls_back_to_plain_text() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| {
+            FixtureResponse::html(
+                "<html><head><title>Status</title></head><body><p>All systems operational.</p></body></html>",
+            )
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = shared_client(&db, &config);
+
+        let result = open_impl(
+            &db,
+            &config,
+            &client,
+            None,
+            &Default::default(),
+            None,
+            readable_params(server.url("/status")),
+        )
+        .await
+        .unwrap();
+        let output: WebOpenOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(output.strategy.as_deref(), Some("plain_text"));
+        assert!(output.markdown.unwrap().ends_with("All systems operational."));
+        let stored = db.get_snapshot(&output.hash).await.unwrap().unwrap();
+        assert_eq!(stored.extractor_name.as_deref(), Some("plain-text"));
+
+        let cached = open_output(&db, &config, server.url("/status")).await;
+        assert!(cached.cache_hit);
+        assert_eq!(cached.strategy.as_deref(), Some("plain_text"));
+
+        let strict = WebOpenParams {
+            extract: Some(ExtractTuning { fallback: Some(false), ..Default::default() }),
+            force_refresh: true,
+            ..readable_params(server.url("/status"))
+        };
+        if let Ok(result) = open_impl(&db, &config, &client, None, &Default::default(), None, strict).await {
+            let output: WebOpenOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+            assert_ne!(output.strategy.as_deref(), Some("plain_text"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_open_reports_page_metadata() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| {
+            let head = r#"<meta name="author" content="Ada"><meta property="og:image" content="/cover.png">
+                <link rel="canonical" href="/post">"#;
+            FixtureResponse::html(article_html("Metadata").replace("<head>", &format!("<head>{head}")))
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+
+        let output = open_output(&db, &config, server.url("/post?ref=home")).await;
+        assert_eq!(output.metadata.author.as_deref(), Some("Ada"));
+        assert_eq!(output.metadata.canonical_url, Some(server.url("/post")));
+        assert_eq!(output.metadata.image, Some(server.url("/cover.png")));
+        let stored = db.get_snapshot(&output.hash).await.unwrap().unwrap();
+        assert_eq!(stored.metadata().unwrap(), output.metadata);
+
+        let cached = open_output(&db, &config, server.url("/post?ref=home")).await;
+        assert!(cached.cache_hit);
+        assert_eq!(cached.metadata, output.metadata);
+    }
+
+    #[tokio::test]
+    async fn test_open_structured_data_on_request() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| {
+            let head = r#"<script type="application/ld+json">{"@type": "FAQPage", "mainEntity": [],}</script>
+                <script type="application/ld+json">{"@type": </script>"#;
+            FixtureResponse::html(article_html("Structured").replace("<head>", &format!("<head>{head}")))
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = shared_client(&db, &config);
+        let open = |include_structured_data| {
+            let params = WebOpenParams { include_structured_data, ..readable_params(server.url("/faq")) };
+            let (db, config, client) = (&db, &config, &client);
+            async move {
+                let result = open_impl(db, config, client, None, &Default::default(), None, params)
+                    .await
+                    .unwrap();
+                serde_json::from_str::<WebOpenOutput>(&result.content[0].as_text().unwrap().text).unwrap()
+            }
+        };
+
+        let output = open(false).await;
+        assert!(output.structured_data.is_none());
+        assert_eq!(output.title.as_deref(), Some("Structured"));
+
+        let cached = open(true).await;
+        assert!(cached.cache_hit);
+        let items = cached.structured_data.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["@type"], "FAQPage");
+    }
+
+    #[tokio::test]
+    async fn test_open_filters_links() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| {
+            let cited = r##"<p>Background is in <a href="https://www.rust-lang.org/learn">the book</a>, and
+                setup is covered <a href="/docs#setup">in the docs</a> and <a href="#notes">below</a>.</p>"##;
+            let page = article_html("Links")
+                .replace("<article>", &format!("<article>{cited}"))
+                .replace(
+                    "<body>",
+                    r#"<body><nav><a href="/">Home</a><a href="/docs">Docs</a></nav>"#,
+                )
+                .replace(
+                    "</body>",
+                    r#"<footer><a href="https://social.example.net/@site">Follow</a></footer></body>"#,
+                );
+            FixtureResponse::html(page)
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = shared_client(&db, &config);
+        let open = |links| {
+            let params = WebOpenParams { links, ..readable_params(server.url("/post")) };
+            let (db, config, client) = (&db, &config, &client);
+            async move {
+                let result = open_impl(db, config, client, None, &Default::default(), None, params)
+                    .await
+                    .unwrap();
+                let output: WebOpenOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+                output.links.into_iter().map(|l| (l.href, l.kind)).collect::<Vec<_>>()
+            }
+        };
+
+        let all = open(LinkFilter::All).await;
+        assert_eq!(
+            all,
+            [
+                (server.url("/"), LinkKind::Internal),
+                (server.url("/docs"), LinkKind::Internal),
+                ("https://www.rust-lang.org/learn".to_string(), LinkKind::External),
+                (server.url("/post#notes"), LinkKind::Anchor),
+                ("https://social.example.net/@site".to_string(), LinkKind::External),
+            ]
+        );
+
+        let content: Vec<String> = open(LinkFilter::Content)
+            .await
+            .into_iter()
+            .map(|(href, _)| href)
+            .collect();
+        assert_eq!(
+            content,
+            [
+                server.url("/docs"),
+                "https://www.rust-lang.org/learn".into(),
+                server.url("/post#notes")
+            ]
+        );
+
+        let external = open(LinkFilter::External).await;
+        assert_eq!(
+            external,
+            all.iter()
+                .filter(|(_, kind)| *kind == LinkKind::External)
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_open_images_on_request() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| {
+            let figures = r#"<img src="/img/chart.png" alt="Chart" width="800" height="600">
+                <img src="https://stats.wp.com/g.gif" width="1" height="1">"#;
+            FixtureResponse::html(article_html("Figures").replace("<article>", &format!("<article>{figures}")))
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = shared_client(&db, &config);
+        let open = |include_images| {
+            let params = WebOpenParams { include_images, ..readable_params(server.url("/figures")) };
+            let (db, config, client) = (&db, &config, &client);
+            async move {
+                let result = open_impl(db, config, client, None, &Default::default(), None, params)
+                    .await
+                    .unwrap();
+                serde_json::from_str::<WebOpenOutput>(&result.content[0].as_text().unwrap().text).unwrap()
+            }
+        };
+
+        let output = open(false).await;
+        assert!(output.images.is_none());
+
+        let cached = open(true).await;
+        assert!(cached.cache_hit);
+        assert_eq!(
+            cached.images.unwrap(),
+            vec![Image {
+                src: server.url("/img/chart.png"),
+                alt: Some("Chart".into()),
+                width: Some(800),
+                height: Some(600),
+                ..Default::default()
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_open_tables_on_request() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| {
+            let table = r#"<table><caption>Sales</caption>
+                <thead><tr><th>Region</th><th colspan="2">Quarter</th></tr></thead>
+                <tbody><tr><td rowspan="2">EU</td><td>Q1</td><td>10</td></tr><tr><td>Q2</td><td>12</td></tr></tbody>
+                </table>"#;
+            FixtureResponse::html(article_html("Sales").replace("</article>", &format!("{table}</article>")))
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = shared_client(&db, &config);
+        let open = |include_tables| {
+            let params = WebOpenParams { include_tables, ..readable_params(server.url("/sales")) };
+            let (db, config, client) = (&db, &config, &client);
+            async move {
+                let result = open_impl(db, config, client, None, &Default::default(), None, params)
+                    .await
+                    .unwrap();
+                serde_json::from_str::<WebOpenOutput>(&result.content[0].as_text().unwrap().text).unwrap()
+            }
+        };
+
+        let output = open(false).await;
+        assert!(output.tables.is_none());
+        assert!(output.markdown.unwrap().contains("[Table: Sales, see tables[0]]"));
+
+        let cached = open(true).await;
+        assert!(cached.cache_hit);
+        assert_eq!(
+            cached.tables.unwrap(),
+            vec![Table {
+                caption: Some("Sales".into()),
+                headers: vec!["Region".into(), "Quarter".into(), "Quarter".into()],
+                rows: vec![
+                    vec!["EU".into(), "Q1".into(), "10".into()],
+                    vec!["EU".into(), "Q2".into(), "12".into()]
+                ],
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_open_outline() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| {
+            let article = article_html("Guide");
+            let (first, rest) = article.split_at(article.find("<p>").unwrap());
+            let (second, third) = rest.split_at(rest.rfind("<p>").unwrap());
+            FixtureResponse::html(format!(
+                r#"{first}<h2 id="install">Install</h2>{second}<h3>From <code>source</code></h3>{third}"#
+            ))
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = shared_client(&db, &config);
+        let open = || {
+            let params = readable_params(server.url("/guide"));
+            let (db, config, client) = (&db, &config, &client);
+            async move {
+                let result = open_impl(db, config, client, None, &Default::default(), None, params)
+                    .await
+                    .unwrap();
+                serde_json::from_str::<WebOpenOutput>(&result.content[0].as_text().unwrap().text).unwrap()
+            }
+        };
+
+        let expected = vec![
+            Heading { level: 2, text: "Install".into(), id: Some("install".into()) },
+            Heading { level: 3, text: "From source".into(), id: None },
+        ];
+        let output = open().await;
+        assert_eq!(output.outline, expected);
+
+        let cached = open().await;
+        assert!(cached.cache_hit);
+        assert_eq!(cached.outline, expected);
+        let snapshot = db.get_snapshot(&cached.hash).await.unwrap().unwrap();
+        assert_eq!(snapshot.outline().unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_open_reports_content_quality() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| FixtureResponse::html(article_html("Quality"))).await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+
+        let params = WebOpenParams { debug: true, ..readable_params(server.url("/doc")) };
+        let result = open_impl(
+            &db,
+            &config,
+            &shared_client(&db, &config),
+            None,
+            &Default::default(),
+            None,
+            params,
+        )
+        .await
+        .unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains("\"likely_boilerplate\":false"), "{text}");
+        let output: WebOpenOutput = serde_json::from_str(text).unwrap();
+
+        let quality = output.debug.unwrap().quality.unwrap();
+        assert_eq!(quality.paragraph_count, 8);
+        assert!(quality.word_count >= 8 * 24, "{quality:?}");
+        assert_eq!(quality.estimated_reading_minutes, 1);
+        assert_eq!(quality.link_density, 0.0);
+        assert!(!quality.likely_boilerplate);
+
+        let stored = db.get_snapshot(&output.hash).await.unwrap().unwrap();
+        assert_eq!(stored.quality().unwrap(), Some(quality));
+    }
+
+    #[tokio::test]
+    async fn test_open_paginated() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| {
+            let article = article_html("Long read");
+            let code = "<pre><code class=\"language-rust\">fn main() {\n\n    let pages = 3;\n\n    \
+                        println!(\"{pages}\");\n}</code></pre>";
+            let middle = article.find("</p>").unwrap() + "</p>".len();
+            FixtureResponse::html(format!("{}{code}{}", &article[..middle], &article[middle..]))
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = shared_client(&db, &config);
+        let open = |max_chars: Option<usize>, page: Option<u32>| {
+            let params = WebOpenParams { max_chars, page, ..readable_params(server.url("/long")) };
+            let (db, config, client) = (&db, &config, &client);
+            async move {
+                let result = open_impl(db, config, client, None, &Default::default(), None, params).await?;
+                Ok::<_, McpError>(
+                    serde_json::from_str::<WebOpenOutput>(&result.content[0].as_text().unwrap().text).unwrap(),
+                )
+            }
+        };
+
+        let first = open(Some(200), None).await.unwrap();
+        let pagination = first.pagination.clone().unwrap();
+        assert_eq!(pagination.page, 1);
+        assert!(pagination.total_pages > 4 && pagination.truncated, "{pagination:?}");
+        assert!(
+            first
+                .markdown
+                .as_deref()
+                .unwrap()
+                .starts_with("---\ntitle: Long read\n")
+        );
+
+        let mut pages = vec![first.markdown.unwrap()];
+        for page in 2..=pagination.total_pages {
+            let output = open(Some(200), Some(page)).await.unwrap();
+            assert!(output.cache_hit);
+            let returned = output.pagination.unwrap();
+            assert_eq!(
+                (returned.page, returned.truncated),
+                (page, page < pagination.total_pages)
+            );
+            pages.push(output.markdown.unwrap());
+        }
+        for page in &pages {
+            let fences = page.lines().filter(|line| line.starts_with("```")).count();
+            assert!(fences % 2 == 0, "code block split across pages: {page}");
+        }
+        assert!(
+            pages
+                .iter()
+                .any(|page| page.contains("```rust\nfn main() {\n\n    let pages = 3;"))
+        );
+
+        let whole = open(None, None).await.unwrap();
+        assert!(whole.pagination.is_none());
+        let whole = whole.markdown.unwrap();
+        assert_eq!(pages.concat(), whole);
+        assert_eq!(pagination.total_chars, whole.chars().count());
+
+        let err = open(Some(200), Some(pagination.total_pages + 1)).await.unwrap_err();
+        assert!(err.message.contains("past the last page"), "{}", err.message);
+        assert!(open(None, Some(2)).await.is_err());
+        assert!(open(Some(0), None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_open_scoped() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| {
+            let article = article_html("Guide");
+            let (first, rest) = article.split_at(article.find("<p>").unwrap());
+            let (second, third) = rest.split_at(rest.rfind("<p>").unwrap());
+            FixtureResponse::html(format!(
+                r#"{first}<h2 id="install">Install</h2>{second}<h2 id="usage">Usage</h2>{third}"#
+            ))
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = shared_client(&db, &config);
+        let open = |selector: Option<&str>, section: Option<&str>| {
+            let params = WebOpenParams {
+                selector: selector.map(Into::into),
+                section: section.map(Into::into),
+                ..readable_params(server.url("/guide"))
+            };
+            let (db, config, client) = (&db, &config, &client);
+            async move {
+                let result = open_impl(db, config, client, None, &Default::default(), None, params).await?;
+                Ok::<_, McpError>(
+                    serde_json::from_str::<WebOpenOutput>(&result.content[0].as_text().unwrap().text).unwrap(),
+                )
+            }
+        };
+
+        let section = open(None, Some("install")).await.unwrap();
+        let markdown = section.markdown.unwrap();
+        assert!(markdown.contains("section: \"install\"\n---\n## Install"), "{markdown}");
+        assert!(
+            markdown.contains("Cached extractions") && !markdown.contains("Usage"),
+            "{markdown}"
+        );
+        assert_eq!(section.outline.len(), 2);
+
+        // Sections come from the cached markdown; a selector needs the page again.
+        assert!(open(None, Some("usage")).await.unwrap().cache_hit);
+        let selected = open(Some("h2#usage"), None).await.unwrap();
+        assert!(!selected.cache_hit);
+        let markdown = selected.markdown.unwrap();
+        assert!(markdown.contains("selector: \"h2#usage\"\n---\n"), "{markdown}");
+        assert!(
+            markdown.contains("Usage") && !markdown.contains("Install"),
+            "{markdown}"
+        );
+
+        let err = open(None, Some("Uninstall")).await.unwrap_err();
+        assert!(
+            err.message.contains("selector/section not found")
+                && err.message.contains("available headings: \"Install\", \"Usage\""),
+            "{}",
+            err.message
+        );
+        assert!(open(Some("#faq"), None).await.is_err());
+
+        let raw =
+            WebOpenParams { mode: Mode::Raw, section: Some("install".into()), ..readable_params(server.url("/guide")) };
+        assert!(
+            open_impl(&db, &config, &client, None, &Default::default(), None, raw)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_other_mode_reuses_stored_body() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let server = FixtureServer::start(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            FixtureResponse::html(article_html("Shared Body"))
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let open = |params: WebOpenParams| {
+            let (db, config) = (db.clone(), config.clone());
+            async move {
+                let result = open_impl(
+                    &db,
+                    &config,
+                    &shared_client(&db, &config),
+                    None,
+                    &Default::default(),
+                    None,
+                    params,
+                )
+                .await
+                .unwrap();
+                serde_json::from_str::<WebOpenOutput>(&result.content[0].as_text().unwrap().text).unwrap()
+            }
+        };
+
+        let raw = open(WebOpenParams { mode: Mode::Raw, ..readable_params(server.url("/doc")) }).await;
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        let readable = open(WebOpenParams { debug: true, ..readable_params(server.url("/doc")) }).await;
+        assert_eq!(
+            hits.load(Ordering::SeqCst),
+            1,
+            "readable mode should reuse the raw body"
+        );
+        assert_eq!(readable.title.as_deref(), Some("Shared Body"));
+        assert_eq!(readable.fetched_at, raw.fetched_at);
+        assert_eq!(
+            readable.debug.unwrap().body_reused_from.as_deref(),
+            Some(raw.hash.as_str())
+        );
+
+        let stored = db.get_snapshot(&readable.hash).await.unwrap().unwrap();
+        let raw_stored = db.get_snapshot(&raw.hash).await.unwrap().unwrap();
+        assert_eq!(stored.content_hash, raw_stored.content_hash);
+
+        let params = WebOpenParams { force_refresh: true, debug: true, ..readable_params(server.url("/doc")) };
+        let refreshed = open(params).await;
+        assert_eq!(hits.load(Ordering::SeqCst), 2, "force_refresh always fetches");
+        assert!(refreshed.debug.unwrap().body_reused_from.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_open_applies_siteconfig() {
+        use thndrs_client::SiteConfig;
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| {
+            let sidebar = r#"<div class="sidebar"><p>Related posts: seven tips for faster builds.</p></div>"#;
+            FixtureResponse::html(article_html("Notes").replacen("<article>", &format!("<article>{sidebar}"), 1))
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let client = shared_client(&db, &config);
+        let host = url::Url::parse(&server.url("/"))
+            .unwrap()
+            .host_str()
+            .unwrap()
+            .to_string();
+        let siteconfigs = Arc::new(
+            SiteConfigRegistry::new(vec![SiteConfig {
+                id: "fixture".into(),
+                hosts: vec![host],
+                ignore: vec![".sidebar".into()],
+                ..Default::default()
+            }])
+            .unwrap(),
+        );
+        let open = |params: WebOpenParams| open_impl(&db, &config, &client, None, &siteconfigs, None, params);
+        let output = |result: CallToolResult| {
+            serde_json::from_str::<WebOpenOutput>(&result.content[0].as_text().unwrap().text).unwrap()
+        };
+
+        let configured = output(open(readable_params(server.url("/notes"))).await.unwrap());
+        let markdown = configured.markdown.unwrap();
+        assert!(markdown.contains("\nsiteconfig: fixture\n"), "{markdown}");
+        assert!(markdown.contains("Cached extractions") && !markdown.contains("Related posts"));
+        let stored = db.get_snapshot(&configured.hash).await.unwrap().unwrap();
+        assert_eq!(stored.siteconfig_id.as_deref(), Some("fixture"));
+
+        // Turning site configs off skips the snapshot extracted with one.
+        let unconfigured = WebOpenParams {
+            use_siteconfig: false,
+            extract: Some(ExtractTuning { strategy: Some("plain_text".into()), ..Default::default() }),
+            ..readable_params(server.url("/notes"))
+        };
+        let unconfigured = output(open(unconfigured).await.unwrap());
+        assert!(!unconfigured.cache_hit);
+        let markdown = unconfigured.markdown.unwrap();
+        assert!(markdown.contains("\nsiteconfig: none\n"), "{markdown}");
+        assert!(markdown.contains("Related posts"), "{markdown}");
+        let stored = db.get_snapshot(&unconfigured.hash).await.unwrap().unwrap();
+        assert!(stored.siteconfig_id.is_none());
+
+        let named = WebOpenParams { siteconfig_id: Some("fixture".into()), ..readable_params(server.url("/notes")) };
+        let named = output(open(named).await.unwrap());
+        assert!(!named.markdown.unwrap().contains("Related posts"));
+
+        let unknown = WebOpenParams { siteconfig_id: Some("docs".into()), ..readable_params(server.url("/notes")) };
+        let err = open(unknown).await.unwrap_err();
+        assert!(
+            err.message.contains("unknown siteconfig_id: docs (loaded: fixture)"),
+            "{}",
+            err.message
+        );
+        let conflicting = WebOpenParams {
+            use_siteconfig: false,
+            siteconfig_id: Some("fixture".into()),
+            ..readable_params(server.url("/notes"))
+        };
+        assert!(open(conflicting).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_open_inflates_unlabeled_gzip_body() {
+        use std::io::Write;
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(article_html("Gzipped").as_bytes()).unwrap();
+        let gzipped = encoder.finish().unwrap();
+        let server = FixtureServer::start(move |_| FixtureResponse::html(gzipped.clone())).await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+
+        let params = WebOpenParams { debug: true, ..readable_params(server.url("/doc")) };
+        let result = open_impl(
+            &db,
+            &config,
+            &shared_client(&db, &config),
+            None,
+            &Default::default(),
+            None,
+            params,
+        )
+        .await
+        .unwrap();
+        let output: WebOpenOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+
+        assert_eq!(output.title.as_deref(), Some("Gzipped"));
+        assert!(output.markdown.unwrap().contains("older normalizer"));
+        assert_eq!(output.debug.unwrap().body_fixups, vec!["gzip-sniffed".to_string()]);
+    }
+
+    /// Fixture that redirects `/start` on 127.0.0.1 to `/target` on localhost.
+    async fn cross_host_server() -> thndrs_client::fixture::FixtureServer {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        FixtureServer::start(|req| {
+            let port = req
+                .header("host")
+                .and_then(|h| h.rsplit(':').next())
+                .unwrap_or_default()
+                .to_string();
+            match req.path.as_str() {
+                "/start" => {
+                    FixtureResponse::status(302).with_header("Location", &format!("http://localhost:{port}/target"))
+                }
+                _ => FixtureResponse::html(article_html("Redirected")),
+            }
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_redirect_to_denied_host_is_not_cached() {
+        let server = cross_host_server().await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            denylist_domains: vec!["localhost".into()],
+            ..Default::default()
+        };
+
+        let err = open_impl(
+            &db,
+            &config,
+            &shared_client(&db, &config),
+            None,
+            &Default::default(),
+            None,
+            readable_params(server.url("/start")),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.message.contains("localhost is on the server denylist"));
+        assert!(db.list_snapshots(&[], 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cross_host_redirect_is_flagged_and_purgeable_from_both_hosts() {
+        let server = cross_host_server().await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+
+        let output = open_output(&db, &config, server.url("/start")).await;
+        assert_eq!(output.cross_host_redirect.as_deref(), Some("localhost"));
+        assert!(output.final_url.starts_with("http://localhost:"));
+
+        let cached = open_output(&db, &config, server.url("/start")).await;
+        assert_eq!(cached.cross_host_redirect.as_deref(), Some("localhost"));
+        assert_eq!(db.purge_snapshots_by_domain("localhost", true, false).await.unwrap(), 1);
+
+        open_output(&db, &config, server.url("/start")).await;
+        assert_eq!(db.purge_snapshots_by_domain("127.0.0.1", true, false).await.unwrap(), 1);
+
+        open_output(&db, &config, server.url("/start")).await;
+        let denied = AppConfig { denylist_domains: vec!["localhost".into()], ..config };
+        let err = open_impl(
+            &db,
+            &denied,
+            &shared_client(&db, &denied),
+            None,
+            &Default::default(),
+            None,
+            readable_params(server.url("/start")),
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            err.message.contains("localhost is on the server denylist"),
+            "cached content from a now-denied host is not served"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_open_returns_content_when_cache_write_fails() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| FixtureResponse::html(article_html("Unsaved"))).await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+
+        let saved = open_output(&db, &config, server.url("/saved")).await;
+        assert!(!saved.cache_write_failed);
+        assert!(!serde_json::to_string(&saved).unwrap().contains("cache_write_failed"));
+
+        db.fail_snapshot_writes().await.unwrap();
+        let output = open_output(&db, &config, server.url("/doc")).await;
+        assert_eq!(output.title.as_deref(), Some("Unsaved"));
+        assert!(output.markdown.is_some());
+        assert!(output.cache_write_failed);
+        assert!(db.get_snapshot(&output.hash).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_challenge_page_is_refused_and_not_cached() {
+        use crate::tools::hints::{TRY_ARCHIVE, hints_from_error};
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let challenge = "<html><head><title>Just a moment...</title></head><body>\
+                         <script>window._cf_chl_opt={cType: 'managed'};</script></body></html>";
+        let server = FixtureServer::start(move |_| FixtureResponse::html(challenge)).await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let url = server.url("/article");
+
+        let err = open_impl(
+            &db,
+            &config,
+            &shared_client(&db, &config),
+            None,
+            &Default::default(),
+            None,
+            readable_params(url.clone()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code.0, -32018);
+        assert!(err.message.contains("cloudflare"));
+        assert!(hints_from_error(&err).iter().any(|h| h.code == TRY_ARCHIVE));
+        let hash = cache_key(&canonicalize(&url).unwrap(), &CacheVary::default(), Mode::Readable);
+        assert!(db.get_snapshot(&hash).await.unwrap().is_none());
+
+        let config = AppConfig { detect_bot_protection: false, ..config };
+        let params = WebOpenParams { mode: Mode::Raw, ..readable_params(url) };
+        let result = open_impl(
+            &db,
+            &config,
+            &shared_client(&db, &config),
+            None,
+            &Default::default(),
+            None,
+            params,
+        )
+        .await
+        .unwrap();
+        let output: WebOpenOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert!(output.raw.unwrap().contains("_cf_chl_opt"));
+    }
+
+    #[tokio::test]
+    async fn test_rendered_mode_requires_render_enabled() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let rendered = WebOpenParams {
+            mode: Mode::Rendered,
+            wait_for: Some("#app".into()),
+            ..readable_params("https://example.com".into())
+        };
+
+        let err = open_impl(
+            &db,
+            &AppConfig::default(),
+            &shared_client(&db, &AppConfig::default()),
+            None,
+            &Default::default(),
+            None,
+            rendered,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code.0, -32011, "{}", err.message);
+
+        let readable = WebOpenParams { render_timeout_ms: Some(5000), ..readable_params("https://example.com".into()) };
+        let err = open_impl(
+            &db,
+            &AppConfig::default(),
+            &shared_client(&db, &AppConfig::default()),
+            None,
+            &Default::default(),
+            None,
+            readable,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code.0, -32602);
+        assert!(err.message.contains("mode=rendered"), "{}", err.message);
+    }
+
+    #[tokio::test]
+    async fn test_user_agent_override_requires_config() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let params =
+            WebOpenParams { user_agent: Some("Mozilla/5.0".into()), ..readable_params("https://example.com".into()) };
+
+        let err = open_impl(
+            &db,
+            &AppConfig::default(),
+            &shared_client(&db, &AppConfig::default()),
+            None,
+            &Default::default(),
+            None,
+            params.clone(),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.message.contains("MCP_WEB_FETCH__ALLOW_USER_AGENT_OVERRIDE"));
+
+        let config = AppConfig { fetch: user_agent_override(), ..Default::default() };
+        for bad in ["", "  ", "Mozilla\r\nX-Injected: 1"] {
+            let params = WebOpenParams { user_agent: Some(bad.into()), ..params.clone() };
+            let err = open_impl(
+                &db,
+                &config,
+                &shared_client(&db, &config),
+                None,
+                &Default::default(),
+                None,
+                params,
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(err.code.0, -32602, "{bad:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_user_agent_override_fetches_and_checks_robots_as_that_agent() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|req| match req.path.as_str() {
+            "/robots.txt" => FixtureResponse::ok("text/plain", "User-agent: NosyBot\nDisallow: /\n"),
+            _ => FixtureResponse::html(article_html(req.header("user-agent").unwrap_or("none"))),
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { fetch: user_agent_override(), allow_private_addresses: true, ..Default::default() };
+        let url = server.url("/doc");
+
+        let default = open_output(&db, &config, url.clone()).await;
+        assert_eq!(default.title.as_deref(), Some(config.user_agent.as_str()));
+
+        let nosy = WebOpenParams { user_agent: Some("NosyBot".into()), ..readable_params(url.clone()) };
+        let err = open_impl(
+            &db,
+            &config,
+            &shared_client(&db, &config),
+            None,
+            &Default::default(),
+            None,
+            nosy,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code.0, -32005, "robots.txt must be evaluated for the override");
+
+        let browser = WebOpenParams {
+            user_agent: Some("Mozilla/5.0 (X11; Linux x86_64)".into()),
+            debug: true,
+            ..readable_params(url)
+        };
+        let result = open_impl(
+            &db,
+            &config,
+            &shared_client(&db, &config),
+            None,
+            &Default::default(),
+            None,
+            browser,
+        )
+        .await
+        .unwrap();
+        let output: WebOpenOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(output.title.as_deref(), Some("Mozilla/5.0 (X11; Linux x86_64)"));
+        assert_ne!(output.hash, default.hash, "the override joins the cache key");
+
+        let debug = output.debug.unwrap();
+        assert_eq!(debug.user_agent.as_deref(), Some("Mozilla/5.0 (X11; Linux x86_64)"));
+        let snapshot = db.get_snapshot(&output.hash).await.unwrap().unwrap();
+        let headers: Vec<(String, String)> = serde_json::from_str(&snapshot.headers_json.unwrap()).unwrap();
+        assert!(headers.contains(&(
+            USER_AGENT_OVERRIDE_HEADER.into(),
+            "Mozilla/5.0 (X11; Linux x86_64)".into()
+        )));
+
+        let cached = open_output(&db, &config, server.url("/doc")).await;
+        assert_eq!(cached.hash, default.hash);
+
+        let open_debug = |params: WebOpenParams| {
+            let (db, config) = (&db, &config);
+            async move {
+                let result = open_impl(
+                    db,
+                    config,
+                    &shared_client(db, config),
+                    None,
+                    &Default::default(),
+                    None,
+                    WebOpenParams { debug: true, force_refresh: true, ..params },
+                )
+                .await
+                .unwrap();
+                serde_json::from_str::<WebOpenOutput>(&result.content[0].as_text().unwrap().text).unwrap()
+            }
+        };
+        let header = open_debug(WebOpenParams {
+            headers: Some(HashMap::from([("User-Agent".to_string(), "HeaderBot/1.0".to_string())])),
+            ..readable_params(server.url("/doc"))
+        })
+        .await;
+        assert_eq!(header.title.as_deref(), Some("HeaderBot/1.0"));
+        assert_eq!(header.debug.unwrap().user_agent.as_deref(), Some("HeaderBot/1.0"));
+
+        let plain = open_debug(readable_params(server.url("/doc"))).await;
+        assert_eq!(
+            plain.debug.unwrap().user_agent.as_deref(),
+            Some(config.user_agent.as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_calls_share_one_client_and_its_robots_cache() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let robots_hits = Arc::new(AtomicUsize::new(0));
+        let server = {
+            let robots_hits = robots_hits.clone();
+            FixtureServer::start(move |req| match req.path.as_str() {
+                "/robots.txt" => {
+                    robots_hits.fetch_add(1, Ordering::SeqCst);
+                    FixtureResponse::ok("text/plain", "User-agent: *\nDisallow: /private\n")
+                }
+                path => FixtureResponse::html(article_html(path)),
+            })
+            .await
+        };
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { allow_private_addresses: true, ..Default::default() };
+        let fetch = shared_client(&db, &config);
+
+        for path in ["/one", "/two"] {
+            let result = open_impl(
+                &db,
+                &config,
+                &fetch,
+                None,
+                &Default::default(),
+                None,
+                readable_params(server.url(path)),
+            )
+            .await
+            .unwrap();
+            let output: WebOpenOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+            assert_eq!(output.title.as_deref(), Some(path));
+        }
+
+        assert_eq!(robots_hits.load(Ordering::SeqCst), 1);
+        let stats = fetch.robots_cache().stats().await;
+        assert_eq!(
+            (stats.misses, stats.hits),
+            (1, 1),
+            "the second call is answered from memory"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extra_headers_reach_the_origin_and_key_the_cache() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|req| {
+            let auth = req.header("authorization").unwrap_or("anonymous");
+            FixtureResponse::html(article_html(&format!("{auth} {}", req.header("cookie").unwrap_or("-"))))
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let url = server.url("/docs");
+        let with_headers = |pairs: &[(&str, &str)]| WebOpenParams {
+            headers: Some(pairs.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect()),
+            ..readable_params(url.clone())
+        };
+        let open = |params: WebOpenParams| {
+            let db = db.clone();
+            let config = config.clone();
+            async move {
+                let result = open_impl(
+                    &db,
+                    &config,
+                    &shared_client(&db, &config),
+                    None,
+                    &Default::default(),
+                    None,
+                    params,
+                )
+                .await
+                .unwrap();
+                serde_json::from_str::<WebOpenOutput>(&result.content[0].as_text().unwrap().text).unwrap()
+            }
+        };
+
+        let anonymous = open_output(&db, &config, url.clone()).await;
+        let alice = open(with_headers(&[("Authorization", "Bearer alice"), ("Cookie", "s=1")])).await;
+        let bob = open(with_headers(&[("Authorization", "Bearer bob"), ("Cookie", "s=1")])).await;
+        assert_eq!(alice.title.as_deref(), Some("Bearer alice s=1"));
+        assert_eq!(bob.title.as_deref(), Some("Bearer bob s=1"));
+        assert_ne!(alice.hash, bob.hash);
+        assert_ne!(alice.hash, anonymous.hash);
+
+        // Credentialed responses are private, so they are fetched again rather than cached.
+        let again = open(with_headers(&[("cookie", "s=1"), ("authorization", "Bearer alice")])).await;
+        assert!(!again.cache_hit);
+        assert_eq!(again.hash, alice.hash);
+
+        let tenant = open(with_headers(&[("X-Tenant", "acme")])).await;
+        assert_ne!(tenant.hash, anonymous.hash);
+        assert!(tenant.cache.is_none());
+        let tenant_again = open(with_headers(&[("x-tenant", "acme")])).await;
+        assert!(tenant_again.cache_hit);
+        assert_eq!(tenant_again.hash, tenant.hash);
+
+        for bad in ["Host", "Content-Length", "User-Agent"] {
+            let err = open_impl(
+                &db,
+                &config,
+                &shared_client(&db, &config),
+                None,
+                &Default::default(),
+                None,
+                with_headers(&[(bad, "x")]),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(err.code.0, -32602, "{bad}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_private_responses_are_not_cached() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|req| match req.path.as_str() {
+            "/account" => {
+                FixtureResponse::html(article_html("Account")).with_header("Cache-Control", "private, max-age=0")
+            }
+            "/login" => {
+                FixtureResponse::html(article_html("Login")).with_header("Set-Cookie", "sessionid=abc; HttpOnly")
+            }
+            _ => FixtureResponse::html(article_html("Public")).with_header("Set-Cookie", "csrftoken=abc"),
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+
+        let cases = [
+            (server.url("/account"), "cache-control: private"),
+            (server.url("/login"), "session cookie"),
+        ];
+        for (url, reason) in cases {
+            let output = open_output(&db, &config, url.clone()).await;
+            let cache = output.cache.expect("private response must report its cache decision");
+            assert_eq!(cache.decision, "skipped", "{url}");
+            assert_eq!(cache.reason, reason);
+            assert!(!output.cache_write_failed);
+            assert!(
+                db.get_snapshot(&output.hash).await.unwrap().is_none(),
+                "{url} was cached"
+            );
+        }
+
+        let public = open_output(&db, &config, server.url("/doc")).await;
+        assert!(public.cache.is_none());
+        assert!(db.get_snapshot(&public.hash).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_credential_headers_are_not_cached_or_exported() {
+        use thndrs_client::export::export_filename;
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|req| {
+            let who = req.header("authorization").unwrap_or("Anonymous");
+            FixtureResponse::html(article_html(&format!("Inbox for {who}")))
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            export_dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let (fetch, exporter) = (shared_client(&db, &config), exporter(&config));
+        let open = |params: WebOpenParams| {
+            let (db, config, fetch, exporter) = (&db, &config, &fetch, exporter.as_ref());
+            async move {
+                let result = open_impl(db, config, fetch, None, &Default::default(), exporter, params)
+                    .await
+                    .unwrap();
+                serde_json::from_str::<WebOpenOutput>(&result.content[0].as_text().unwrap().text).unwrap()
+            }
+        };
+
+        let public = open(readable_params(server.url("/public"))).await;
+        let public_path = dir.path().join(export_filename(public.title.as_deref(), &public.hash));
+        for _ in 0..100 {
+            if public_path.exists() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert!(public_path.exists(), "anonymous pages are still exported");
+
+        let params = WebOpenParams {
+            headers: Some(HashMap::from([(
+                "Authorization".to_string(),
+                "Bearer alice".to_string(),
+            )])),
+            ..readable_params(server.url("/inbox"))
+        };
+        let output = open(params).await;
+        assert_eq!(output.title.as_deref(), Some("Inbox for Bearer alice"));
+        let cache = output.cache.expect("credentialed fetch must report its cache decision");
+        assert_eq!(cache.decision, "skipped");
+        assert_eq!(cache.reason, "request credentials");
+        assert!(db.get_snapshot(&output.hash).await.unwrap().is_none());
+
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        let exported: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        assert_eq!(exported, vec![public_path]);
+    }
+
+    #[tokio::test]
+    async fn test_credentialed_and_overlong_urls_are_rejected() {
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| FixtureResponse::html(article_html("Doc"))).await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let credentialed = server.url("/doc").replacen("://", "://user:secret@", 1);
+
+        let err = open_impl(
+            &db,
+            &config,
+            &shared_client(&db, &config),
+            None,
+            &Default::default(),
+            None,
+            readable_params(credentialed.clone()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code.0, -32003);
+        assert!(err.message.contains("embedded credentials"), "{}", err.message);
+        assert!(!err.message.contains("secret"));
+
+        let long = format!("{}?q={}", server.url("/doc"), "a".repeat(config.max_url_length));
+        let err = open_impl(
+            &db,
+            &config,
+            &shared_client(&db, &config),
+            None,
+            &Default::default(),
+            None,
+            readable_params(long),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code.0, -32003);
+        assert!(err.message.contains("byte limit"), "{}", err.message);
+
+        let config = AppConfig { strip_url_credentials: true, ..config };
+        let output = open_output(&db, &config, credentialed).await;
+        assert_eq!(output.url, server.url("/doc"));
+        let stored = db.get_snapshot(&output.hash).await.unwrap().unwrap();
+        assert!(!stored.url.contains("secret") && !stored.final_url.contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn test_cache_partition_keeps_entries_separate() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+        use thndrs_core::CacheSettings;
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let server = FixtureServer::start(move |_| {
+            let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            FixtureResponse::html(article_html(&format!("Inbox {n}"))).with_header("Cache-Control", "private")
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let url = server.url("/inbox");
+        let partitioned =
+            |partition: &str| WebOpenParams { cache_partition: Some(partition.into()), ..readable_params(url.clone()) };
+
+        let err = open_impl(
+            &db,
+            &AppConfig::default(),
+            &shared_client(&db, &AppConfig::default()),
+            None,
+            &Default::default(),
+            None,
+            partitioned("alice"),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.message.contains("MCP_WEB_CACHE__ALLOW_PRIVATE_CONTENT"));
+
+        let config = AppConfig {
+            respect_robots: false,
+            allow_private_addresses: true,
+            cache: CacheSettings { allow_private_content: PrivateContentPolicy::Partitioned, ..Default::default() },
+            ..Default::default()
+        };
+        let open = |params: WebOpenParams| {
+            let (db, config) = (&db, &config);
+            async move {
+                let result = open_impl(
+                    db,
+                    config,
+                    &shared_client(db, config),
+                    None,
+                    &Default::default(),
+                    None,
+                    params,
+                )
+                .await
+                .unwrap();
+                serde_json::from_str::<WebOpenOutput>(&result.content[0].as_text().unwrap().text).unwrap()
+            }
+        };
+
+        let alice = open(partitioned("alice")).await;
+        let cache = alice.cache.as_ref().unwrap();
+        assert_eq!(cache.decision, "partitioned");
+        assert_eq!(cache.partition.as_deref(), Some("alice"));
+        assert!(db.get_snapshot(&alice.hash).await.unwrap().is_some());
+
+        let bob = open(partitioned("bob")).await;
+        assert_ne!(bob.hash, alice.hash);
+        assert_eq!(bob.title.as_deref(), Some("Inbox 2"));
+
+        let alice_again = open(partitioned("alice")).await;
+        assert_eq!(alice_again.hash, alice.hash);
+        assert_eq!(alice_again.title, alice.title, "alice is served her own copy");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        let unpartitioned = open(readable_params(url.clone())).await;
+        assert_eq!(unpartitioned.cache.unwrap().decision, "skipped");
+        assert!(db.get_snapshot(&unpartitioned.hash).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_slow_fetch_hits_tool_deadline() {
+        use crate::tools::deadline::with_deadline;
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(|_| {
+            FixtureResponse::html(article_html("Slow")).with_body_delay(std::time::Duration::from_secs(2))
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+
+        let call = open_impl(
+            &db,
+            &config,
+            &shared_client(&db, &config),
+            None,
+            &Default::default(),
+            None,
+            readable_params(server.url("/doc")),
+        );
+        let err = with_deadline("web_open", 200, call).await.unwrap_err();
+
+        assert_eq!(err.code.0, -32017);
+        assert_eq!(err.data.unwrap()["phase"], "fetch");
+        assert!(db.list_snapshots(&[], 10).await.unwrap().is_empty());
     }
 }
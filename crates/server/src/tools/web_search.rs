@@ -1,12 +1,21 @@
 //! web_search tool implementation.
 //!
-//! Performs web searches using the Brave Search API with caching.
+//! Performs web searches through the configured [`SearchProvider`] (Brave by
+//! default, or SearXNG) with caching.
+
+use std::time::Duration;
 
 use rmcp::{ErrorData as McpError, model::*};
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
-use thndrs_client::{BraveClient, BraveConfig, SafeSearch, SearchRequest};
-use thndrs_core::{AppConfig, CacheDb, Error};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use thndrs_client::{
+    BraveClient, BraveConfig, SafeSearch, SearchError, SearchProvider, SearchRequest, SearxngClient, SearxngConfig,
+};
+use thndrs_core::{AppConfig, CacheDb, DomainPolicy, Error, cache::UsageReport};
+
+use crate::tools::deadline::{self, Phase};
+use crate::tools::output::json_result;
+use crate::tools::web_news_search::NewsResult;
 
 /// Input parameters for web_search tool.
 #[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -42,7 +51,8 @@ pub struct WebSearchParams {
     #[serde(default)]
     pub ui_lang: Option<String>,
 
-    /// Enable up to 5 extra snippets per result.
+    /// Include extra snippets per result (default false); capped by the server's
+    /// snippet count and length limits.
     #[serde(default)]
     pub extra_snippets: Option<bool>,
 
@@ -50,13 +60,28 @@ pub struct WebSearchParams {
     #[serde(default)]
     pub goggles: Option<String>,
 
+    /// Let Brave correct the query's spelling (default true); when it does,
+    /// `query.altered` holds the query actually searched.
+    #[serde(default)]
+    pub spellcheck: Option<bool>,
+
+    /// Sections Brave returns, comma-separated from discussions, faq, infobox,
+    /// locations, news, query, summarizer, videos, web (default: all); "web"
+    /// alone skips the rest for a smaller, faster response.
+    #[serde(default)]
+    pub result_filter: Option<String>,
+
     /// Force a refresh, bypassing the cache.
     #[serde(default = "default_false")]
     pub force_refresh: bool,
 
-    /// Optional domain allowlist to filter results.
+    /// Optional domain allowlist to filter results; narrows, never widens, the server domain policy.
     #[serde(default)]
     pub domain_allowlist: Option<Vec<String>>,
+
+    /// Result order: relevance (default, Brave's ranking) or date (newest `published_at` first, undated last).
+    #[serde(default)]
+    pub sort: Option<String>,
 }
 
 fn default_count() -> Option<u8> {
@@ -72,6 +97,18 @@ fn default_false() -> bool {
 pub struct WebSearchOutput {
     /// The search results.
     pub results: Vec<SearchResult>,
+    /// Videos Brave returned alongside the web results.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub videos: Vec<VideoResult>,
+    /// Questions and answers Brave extracted for the query.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub faq: Vec<FaqResult>,
+    /// Forum threads about the query.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub discussions: Vec<DiscussionResult>,
+    /// News cluster Brave returned for topical queries.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub news: Vec<NewsResult>,
     /// Query metadata.
     pub query: QueryMeta,
     /// Debug information.
@@ -88,21 +125,114 @@ pub struct SearchResult {
     /// Result description/snippet.
     pub description: String,
     /// Extra snippets (if requested).
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub extra_snippets: Vec<String>,
+    /// Age as reported by Brave, e.g. "2 days ago".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub age: Option<String>,
+    /// Publication date normalized to RFC3339 UTC, when Brave's age could be parsed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<String>,
+    /// Page language, e.g. "en".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Site favicon URL (proxied by Brave).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub favicon: Option<String>,
+    /// Site hostname, e.g. "www.rust-lang.org".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
     /// Search source (always "brave").
     pub source: String,
     /// Result rank (1-indexed).
     pub rank: usize,
 }
 
-/// Query metadata.
+/// Video result.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct VideoResult {
+    /// Video title.
+    pub title: String,
+    /// Video page URL.
+    pub url: String,
+    /// Video description.
+    pub description: String,
+    /// Age as reported by Brave, e.g. "1 year ago".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub age: Option<String>,
+    /// Duration as displayed, e.g. "12:34".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<String>,
+    /// Channel or creator, else the hosting platform.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+    /// Thumbnail URL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail_url: Option<String>,
+}
+
+/// FAQ entry.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FaqResult {
+    /// The question.
+    pub question: String,
+    /// The answer, as excerpted by Brave.
+    pub answer: String,
+    /// Title of the page the answer comes from.
+    pub title: String,
+    /// URL of the page the answer comes from.
+    pub url: String,
+}
+
+/// Forum discussion.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DiscussionResult {
+    /// Thread title.
+    pub title: String,
+    /// Thread URL.
+    pub url: String,
+    /// Thread excerpt.
+    pub description: String,
+    /// Age as reported by Brave.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub age: Option<String>,
+    /// Forum name, e.g. "r/rust".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forum_name: Option<String>,
+    /// Number of replies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_answers: Option<u32>,
+}
+
+/// Query metadata.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct QueryMeta {
     /// Original query string.
     pub original: String,
     /// Whether more results are available.
     pub more_results_available: bool,
+    /// The query Brave searched for instead, when it rewrote the original
+    /// (e.g. a spelling correction); results match this query.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub altered: Option<String>,
+    /// Whether spellcheck was off for this query.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spellcheck_off: Option<bool>,
+    /// Whether Brave read the query as a lookup of a specific site.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_navigational: Option<bool>,
+}
+
+impl From<thndrs_client::QueryMeta> for QueryMeta {
+    fn from(query: thndrs_client::QueryMeta) -> Self {
+        Self {
+            original: query.original,
+            more_results_available: query.more_results_available,
+            altered: query.altered,
+            spellcheck_off: query.spellcheck_off,
+            is_navigational: query.is_navigational,
+        }
+    }
 }
 
 /// Debug information.
@@ -114,26 +244,62 @@ pub struct DebugInfo {
     /// Cache hit status.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cache_hit: Option<bool>,
+    /// Brave usage this month, with spend estimates when pricing is configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<UsageReport>,
+    /// Set to "date" when results were re-sorted by `published_at` instead of relevance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sorted_by: Option<String>,
+    /// Brave requests this search took when Brave throttled (429) or failed
+    /// transiently and was retried; absent when the first request succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempts: Option<u8>,
+}
+
+/// Build the Brave client web_search shares, from the configured API key and limits.
+pub fn brave_client(config: &AppConfig) -> Result<BraveClient, Error> {
+    let api_key = config
+        .require_brave_api_key()
+        .map_err(|e| Error::BraveAuthError(e.to_string()))?;
+    BraveClient::new(BraveConfig {
+        api_key: api_key.to_string(),
+        user_agent: config.user_agent.clone(),
+        timeout: config.timeout(),
+        min_request_interval: Duration::from_millis(config.brave_min_interval_ms),
+        ..Default::default()
+    })
+    .map_err(|e| match e {
+        thndrs_client::BraveError::MissingApiKey => Error::BraveAuthError(e.to_string()),
+        _ => Error::HttpError(e.to_string()),
+    })
+}
+
+/// Build the SearXNG client web_search uses when `search_provider` is "searxng".
+pub fn searxng_client(config: &AppConfig) -> Result<SearxngClient, Error> {
+    let base_url = config
+        .searxng_base_url
+        .clone()
+        .ok_or_else(|| Error::InvalidInput("searxng_base_url is required for the searxng provider".into()))?;
+    SearxngClient::new(SearxngConfig { base_url, timeout: config.timeout(), user_agent: config.user_agent.clone() })
+        .map_err(|e| Error::InvalidInput(e.to_string()))
 }
 
 /// Implementation of the web_search tool.
+///
+/// `provider` is the long-lived backend every call searches with, so Brave's
+/// rate limiter paces requests across calls; without one, uncached searches fail.
 pub async fn search_impl(
-    db: &CacheDb, config: &AppConfig, params: WebSearchParams,
+    db: &CacheDb, config: &AppConfig, provider: Option<&dyn SearchProvider>, params: WebSearchParams,
 ) -> Result<CallToolResult, McpError> {
     if params.query.is_empty() {
         return Err(Error::InvalidInput("query cannot be empty".into()).into());
     }
 
-    let safesearch = match params.safesearch.as_deref() {
-        Some("off") => Some(SafeSearch::Off),
-        Some("moderate") | None => Some(SafeSearch::Moderate),
-        Some("strict") => Some(SafeSearch::Strict),
-        Some(other) => {
-            return Err(Error::InvalidInput(format!("invalid safesearch: {}", other)).into());
-        }
-    };
+    let safesearch = parse_safesearch(params.safesearch.as_deref())?;
+    let sort_by_date = parse_sort(params.sort.as_deref())?;
 
     let ttl = BraveClient::ttl_for_freshness(&params.freshness);
+    let want_snippets = params.extra_snippets.unwrap_or(false);
 
     let req = SearchRequest {
         q: params.query.clone(),
@@ -146,101 +312,323 @@ pub async fn search_impl(
         ui_lang: params.ui_lang,
         extra_snippets: params.extra_snippets,
         goggles: params.goggles,
-        spellcheck: None,
+        spellcheck: params.spellcheck,
+        result_filter: params.result_filter,
     };
 
     req.validate().map_err(|e| Error::InvalidInput(e.to_string()))?;
 
-    let cache_key = BraveClient::cache_key(&req);
+    let key_for = |req: &SearchRequest| provider.map_or_else(|| BraveClient::cache_key(req), |p| p.cache_key(req));
+    let cache_key = key_for(&req);
+    // A cached response with extra snippets also answers a request without
+    // them; limit_extra_snippets strips them below.
+    let snippets_key = (req.extra_snippets != Some(true))
+        .then(|| key_for(&SearchRequest { extra_snippets: Some(true), ..req.clone() }));
 
-    if !params.force_refresh
-        && let Ok(Some(cached_json)) = db.get_search(&cache_key).await
-        && let Ok(cached) = serde_json::from_str::<WebSearchOutput>(&cached_json)
-    {
+    let mut policy = DomainPolicy::from_config(config);
+    if let Some(allowlist) = &params.domain_allowlist {
+        policy = policy.with_request_allowlist(allowlist);
+    }
+
+    deadline::enter(Phase::Cache);
+    let mut cached: Option<WebSearchOutput> = None;
+    if !params.force_refresh {
+        cached = cached_search(db, &cache_key).await;
+        if cached.is_none()
+            && let Some(key) = &snippets_key
+        {
+            cached = cached_search(db, key).await;
+        }
+    }
+    if let Some(cached) = cached {
         tracing::debug!("cache hit for search query: {}", params.query);
+        if provider.is_none_or(|p| p.provider_name() == "brave")
+            && let Err(e) = db.record_brave_cache_hit().await
+        {
+            tracing::warn!("failed to record search cache hit: {}", e);
+        }
         let mut output = cached;
+        filter_sections(&mut output, &policy, want_snippets, config);
+        output.results = filter_by_domains(output.results, &policy);
+        limit_extra_snippets(&mut output.results, want_snippets, config);
+        if sort_by_date {
+            sort_by_published(&mut output);
+        }
         output.debug.cache_hit = Some(true);
-        return Ok(CallToolResult::success(vec![Content::text(
-            serde_json::to_string_pretty(&output).unwrap_or_default(),
-        )]));
+        output.debug.attempts = None;
+        output.debug.usage = usage_report(db, config).await;
+        return json_result(&output, config);
     }
 
-    let client = BraveClient::new(BraveConfig {
-        api_key: config
-            .require_brave_api_key()
-            .map_err(|e| Error::BraveAuthError(e.to_string()))?
-            .to_string(),
-        user_agent: config.user_agent.clone(),
-        timeout: config.timeout(),
-        ..Default::default()
-    })
-    .map_err(|e| match e {
-        thndrs_client::BraveError::MissingApiKey => Error::BraveAuthError(e.to_string()),
-        _ => Error::HttpError(e.to_string()),
-    })?;
-
-    let response = client.search(req).await.map_err(|e| match e {
-        thndrs_client::BraveError::AuthError => Error::BraveAuthError(e.to_string()),
-        thndrs_client::BraveError::RateLimited => Error::BraveRateLimited(e.to_string()),
-        thndrs_client::BraveError::InvalidQuery(msg) => Error::InvalidInput(msg),
-        thndrs_client::BraveError::HttpError { status } => Error::HttpError(format!("HTTP {}", status)),
-        _ => Error::HttpError(e.to_string()),
-    })?;
-
-    let results = if let Some(allowlist) = &params.domain_allowlist {
-        filter_by_domains(&response.results, allowlist)
-    } else {
-        response.results
-    };
+    let provider = require_provider(provider, config)?;
+    deadline::enter(Phase::Fetch);
+    let response = provider.search(req).await.map_err(provider_error)?;
+    if provider.provider_name() == "brave"
+        && let Err(e) = db.record_brave_request().await
+    {
+        tracing::warn!("failed to record Brave request: {}", e);
+    }
 
-    let output = WebSearchOutput {
-        results: results
+    let mut output = WebSearchOutput {
+        results: response
+            .results
             .into_iter()
             .map(|r| SearchResult {
                 title: r.title,
                 url: r.url,
                 description: r.description,
                 extra_snippets: r.extra_snippets,
+                age: r.age,
+                published_at: r.published_at,
+                language: r.language,
+                favicon: r.favicon,
+                hostname: r.hostname,
                 source: r.source,
                 rank: r.rank,
             })
             .collect(),
-        query: QueryMeta {
-            original: response.query.original,
-            more_results_available: response.query.more_results_available,
+        videos: response
+            .videos
+            .into_iter()
+            .map(|v| VideoResult {
+                title: v.title,
+                url: v.url,
+                description: v.description,
+                age: v.age,
+                duration: v.duration,
+                creator: v.creator,
+                thumbnail_url: v.thumbnail_url,
+            })
+            .collect(),
+        faq: response
+            .faq
+            .into_iter()
+            .map(|f| FaqResult { question: f.question, answer: f.answer, title: f.title, url: f.url })
+            .collect(),
+        discussions: response
+            .discussions
+            .into_iter()
+            .map(|d| DiscussionResult {
+                title: d.title,
+                url: d.url,
+                description: d.description,
+                age: d.age,
+                forum_name: d.forum_name,
+                num_answers: d.num_answers,
+            })
+            .collect(),
+        news: response
+            .news
+            .into_iter()
+            .map(|n| NewsResult {
+                title: n.title,
+                url: n.url,
+                description: n.description,
+                extra_snippets: n.extra_snippets,
+                age: n.age,
+                published_at: n.published_at,
+                source: n.source,
+                thumbnail_url: n.thumbnail_url,
+                rank: n.rank,
+            })
+            .collect(),
+        query: response.query.into(),
+        debug: DebugInfo {
+            request_id: response.debug.request_id,
+            cache_hit: Some(false),
+            usage: None,
+            sorted_by: None,
+            attempts: Some(response.debug.attempts).filter(|n| *n > 1),
         },
-        debug: DebugInfo { request_id: response.debug.request_id, cache_hit: Some(false) },
     };
 
-    let query_json = serde_json::to_string(&params.query).unwrap_or_default();
-    let response_json = serde_json::to_string(&output).unwrap_or_default();
-    if let Err(e) = db.put_search(&cache_key, &query_json, &response_json, ttl).await {
-        tracing::warn!("failed to cache search result: {}", e);
+    match (serde_json::to_string(&params.query), serde_json::to_string(&output)) {
+        (Ok(query_json), Ok(response_json)) => {
+            let writer = db.clone();
+            let write =
+                deadline::cache_write(
+                    async move { writer.put_search(&cache_key, &query_json, &response_json, ttl).await },
+                );
+            if let Err(e) = write.await {
+                tracing::warn!("failed to cache search result: {}", e);
+            }
+        }
+        (Err(e), _) | (_, Err(e)) => tracing::warn!("failed to serialize search result for caching: {}", e),
     }
 
-    Ok(CallToolResult::success(vec![Content::text(
-        serde_json::to_string_pretty(&output).unwrap_or_default(),
-    )]))
+    filter_sections(&mut output, &policy, want_snippets, config);
+    output.results = filter_by_domains(output.results, &policy);
+    limit_extra_snippets(&mut output.results, want_snippets, config);
+    if sort_by_date {
+        sort_by_published(&mut output);
+    }
+    output.debug.usage = usage_report(db, config).await;
+
+    json_result(&output, config)
 }
 
-/// Filter search results by domain allowlist.
-fn filter_by_domains(
-    results: &[thndrs_client::SearchResult], allowlist: &[String],
-) -> Vec<thndrs_client::SearchResult> {
-    results
-        .iter()
-        .filter(|r| {
-            if let Ok(url) = url::Url::parse(&r.url)
-                && let Some(host) = url.host_str()
-            {
-                return allowlist
-                    .iter()
-                    .any(|domain| host == domain.as_str() || host.ends_with(&format!(".{}", domain)));
-            }
-            false
-        })
-        .cloned()
-        .collect()
+/// Parse the `safesearch` parameter; moderate when absent.
+pub(crate) fn parse_safesearch(value: Option<&str>) -> Result<Option<SafeSearch>, Error> {
+    match value {
+        Some("off") => Ok(Some(SafeSearch::Off)),
+        Some("moderate") | None => Ok(Some(SafeSearch::Moderate)),
+        Some("strict") => Ok(Some(SafeSearch::Strict)),
+        Some(other) => Err(Error::InvalidInput(format!("invalid safesearch: {}", other))),
+    }
+}
+
+/// Parse the `sort` parameter; true when results should be sorted by date.
+pub(crate) fn parse_sort(value: Option<&str>) -> Result<bool, Error> {
+    match value {
+        Some("relevance") | None => Ok(false),
+        Some("date") => Ok(true),
+        Some(other) => Err(Error::InvalidInput(format!("invalid sort: {}", other))),
+    }
+}
+
+/// The shared Brave client, or the auth error explaining why there is none.
+///
+/// The handler builds its shared client only when an API key is configured.
+pub(crate) fn require_client<'a>(brave: Option<&'a BraveClient>, config: &AppConfig) -> Result<&'a BraveClient, Error> {
+    brave.ok_or_else(|| {
+        let reason = config
+            .require_brave_api_key()
+            .err()
+            .map_or_else(|| "no Brave client configured".to_string(), |e| e.to_string());
+        Error::BraveAuthError(reason)
+    })
+}
+
+/// The configured search provider, or the error explaining why there is none.
+fn require_provider<'a>(
+    provider: Option<&'a dyn SearchProvider>, config: &AppConfig,
+) -> Result<&'a dyn SearchProvider, Error> {
+    match provider {
+        Some(provider) => Ok(provider),
+        None if config.search_provider == "brave" => require_client(None, config).map(|c| c as &dyn SearchProvider),
+        None => Err(Error::HttpError(format!(
+            "{} search provider unavailable",
+            config.search_provider
+        ))),
+    }
+}
+
+/// Map a failed provider search onto the server's error codes.
+fn provider_error(e: SearchError) -> Error {
+    match e {
+        SearchError::Brave(e) => search_error(e),
+        SearchError::InvalidRequest(msg) => Error::InvalidInput(msg),
+        SearchError::HttpError { status } => Error::HttpError(format!("HTTP {}", status)),
+        _ => Error::HttpError(e.to_string()),
+    }
+}
+
+/// Map a failed Brave search onto the server's error codes.
+pub(crate) fn search_error(e: thndrs_client::BraveError) -> Error {
+    match e {
+        thndrs_client::BraveError::AuthError => Error::BraveAuthError(e.to_string()),
+        thndrs_client::BraveError::RateLimited => Error::BraveRateLimited(e.to_string()),
+        thndrs_client::BraveError::InvalidQuery(msg) => Error::InvalidInput(msg),
+        thndrs_client::BraveError::HttpError { status } => Error::HttpError(format!("HTTP {}", status)),
+        _ => Error::HttpError(e.to_string()),
+    }
+}
+
+/// The unexpired search output cached under `key`, if any.
+pub(crate) async fn cached_search<T: DeserializeOwned>(db: &CacheDb, key: &str) -> Option<T> {
+    if !db.is_search_fresh(key).await.unwrap_or(false) {
+        return None;
+    }
+    let json = db.get_search(key).await.ok()??;
+    serde_json::from_str(&json).ok()
+}
+
+/// This month's Brave usage with the configured pricing applied.
+pub(crate) async fn usage_report(db: &CacheDb, config: &AppConfig) -> Option<UsageReport> {
+    match db.brave_usage().await {
+        Ok(usage) => Some(usage.report(&config.brave_pricing())),
+        Err(e) => {
+            tracing::warn!("failed to read Brave usage: {}", e);
+            None
+        }
+    }
+}
+
+/// Re-sort results newest first by `published_at`, undated results last.
+///
+/// The sort is stable and `rank` keeps Brave's relevance order, so ties and
+/// undated results stay in relevance order. Cached results are stored unsorted.
+fn sort_by_published(output: &mut WebSearchOutput) {
+    output.results.sort_by_key(|r| newest_first(r.published_at.as_deref()));
+    output.debug.sorted_by = Some("date".to_string());
+}
+
+/// Sort key putting the newest `published_at` first and undated results last.
+pub(crate) fn newest_first(
+    published_at: Option<&str>,
+) -> std::cmp::Reverse<Option<chrono::DateTime<chrono::FixedOffset>>> {
+    std::cmp::Reverse(published_at.and_then(|d| chrono::DateTime::parse_from_rfc3339(d).ok()))
+}
+
+/// Shape extra snippets to what the caller asked for.
+///
+/// A request without `extra_snippets` may be answered from the cached
+/// response to one with them; those snippets are dropped. Requested
+/// snippets are capped at `extra_snippets_max` per result and
+/// `extra_snippet_max_chars` each, with cut snippets ending in "…".
+fn limit_extra_snippets(results: &mut [SearchResult], requested: bool, config: &AppConfig) {
+    for result in results {
+        limit_snippets(&mut result.extra_snippets, requested, config);
+    }
+}
+
+/// Apply [`limit_extra_snippets`] to one result's snippets.
+pub(crate) fn limit_snippets(snippets: &mut Vec<String>, requested: bool, config: &AppConfig) {
+    if !requested {
+        snippets.clear();
+        return;
+    }
+    snippets.truncate(config.extra_snippets_max);
+    for snippet in snippets {
+        if let Some((cut, _)) = snippet.char_indices().nth(config.extra_snippet_max_chars) {
+            snippet.truncate(cut);
+            snippet.push('…');
+        }
+    }
+}
+
+/// Drop search results whose host the domain policy denies.
+///
+/// Results are cached unfiltered, so this runs on cache hits as well as fresh responses.
+fn filter_by_domains(results: Vec<SearchResult>, policy: &DomainPolicy) -> Vec<SearchResult> {
+    if policy.is_unrestricted() {
+        return results;
+    }
+
+    results.into_iter().filter(|r| host_allowed(&r.url, policy)).collect()
+}
+
+/// Apply the domain policy to the non-web sections, and trim news snippets
+/// like [`limit_extra_snippets`].
+fn filter_sections(output: &mut WebSearchOutput, policy: &DomainPolicy, want_snippets: bool, config: &AppConfig) {
+    for news in &mut output.news {
+        limit_snippets(&mut news.extra_snippets, want_snippets, config);
+    }
+    if policy.is_unrestricted() {
+        return;
+    }
+    output.videos.retain(|r| host_allowed(&r.url, policy));
+    output.faq.retain(|r| host_allowed(&r.url, policy));
+    output.discussions.retain(|r| host_allowed(&r.url, policy));
+    output.news.retain(|r| host_allowed(&r.url, policy));
+}
+
+/// Whether the domain policy allows `url`'s host; unparseable URLs are not.
+pub(crate) fn host_allowed(url: &str, policy: &DomainPolicy) -> bool {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| policy.evaluate(host).is_allowed()))
+        .unwrap_or(false)
 }
 
 #[cfg(test)]
@@ -253,7 +641,7 @@ mod tests {
         let config = AppConfig::default();
         let params = WebSearchParams { query: "".into(), ..Default::default() };
 
-        let result = search_impl(&db, &config, params).await;
+        let result = search_impl(&db, &config, None, params).await;
         assert!(result.is_err());
     }
 
@@ -263,30 +651,249 @@ mod tests {
         let config = AppConfig::default();
         let params = WebSearchParams { query: "test".into(), safesearch: Some("invalid".into()), ..Default::default() };
 
-        let result = search_impl(&db, &config, params).await;
+        let result = search_impl(&db, &config, None, params).await;
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_invalid_sort() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let params = WebSearchParams { query: "test".into(), sort: Some("newest".into()), ..Default::default() };
+
+        let err = search_impl(&db, &AppConfig::default(), None, params).await.unwrap_err();
+        assert!(err.message.contains("invalid sort"));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_result_filter() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let params =
+            WebSearchParams { query: "test".into(), result_filter: Some("web,pictures".into()), ..Default::default() };
+
+        let err = search_impl(&db, &AppConfig::default(), None, params).await.unwrap_err();
+        assert!(err.message.contains("\"pictures\""), "{}", err.message);
+    }
+
     #[tokio::test]
     async fn test_missing_api_key() {
         let db = CacheDb::open_in_memory().await.unwrap();
         let config = AppConfig::default(); // No brave_api_key set
         let params = WebSearchParams { query: "test".into(), ..Default::default() };
 
-        let result = search_impl(&db, &config, params).await;
+        let result = search_impl(&db, &config, None, params).await;
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_expired_cache_entry_is_not_served() {
+        let clock = thndrs_core::ManualClock::default();
+        let db = CacheDb::open_in_memory().await.unwrap().with_clock(clock.clone());
+        let config = AppConfig { brave_cost_per_thousand_requests: Some(5.0), ..Default::default() };
+        let params = WebSearchParams { query: "rust".into(), ..Default::default() };
+
+        let req = SearchRequest { q: "rust".into(), safesearch: Some(SafeSearch::Moderate), ..Default::default() };
+        let cached = WebSearchOutput {
+            results: vec![],
+            videos: vec![],
+            faq: vec![],
+            discussions: vec![],
+            news: vec![],
+            query: QueryMeta { original: "rust".into(), ..Default::default() },
+            debug: DebugInfo { request_id: None, cache_hit: Some(false), usage: None, sorted_by: None, attempts: None },
+        };
+        let ttl = BraveClient::ttl_for_freshness(&None);
+        db.put_search(
+            &BraveClient::cache_key(&req),
+            "\"rust\"",
+            &serde_json::to_string(&cached).unwrap(),
+            ttl,
+        )
+        .await
+        .unwrap();
+
+        let result = search_impl(&db, &config, None, params.clone()).await.unwrap();
+        let output: WebSearchOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(output.debug.cache_hit, Some(true));
+        let usage = output.debug.usage.unwrap();
+        assert_eq!((usage.requests, usage.cache_hits), (0, 1));
+        assert_eq!(usage.estimated_cost, Some(0.0));
+        assert!(usage.estimated_savings.unwrap() > 0.0);
+
+        clock.advance(chrono::Duration::seconds(ttl));
+        let err = search_impl(&db, &config, None, params).await.unwrap_err();
+        assert_eq!(err.code.0, -32009, "expired entry should fall through to the API");
+    }
+
+    fn snippet_output(snippets: Vec<String>) -> WebSearchOutput {
+        WebSearchOutput {
+            results: vec![SearchResult {
+                title: "Rust".into(),
+                url: "https://www.rust-lang.org/".into(),
+                description: "A language".into(),
+                extra_snippets: snippets,
+                age: None,
+                published_at: None,
+                language: None,
+                favicon: None,
+                hostname: None,
+                source: "brave".into(),
+                rank: 1,
+            }],
+            videos: vec![],
+            faq: vec![],
+            discussions: vec![],
+            news: vec![],
+            query: QueryMeta { original: "rust".into(), ..Default::default() },
+            debug: DebugInfo { request_id: None, cache_hit: Some(false), usage: None, sorted_by: None, attempts: None },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_snippets_only_returned_when_requested() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig::default();
+
+        let req = SearchRequest {
+            q: "rust".into(),
+            safesearch: Some(SafeSearch::Moderate),
+            extra_snippets: Some(true),
+            ..Default::default()
+        };
+        let cached = snippet_output(vec!["First snippet".into(), "Second snippet".into()]);
+        db.put_search(
+            &BraveClient::cache_key(&req),
+            "\"rust\"",
+            &serde_json::to_string(&cached).unwrap(),
+            BraveClient::ttl_for_freshness(&None),
+        )
+        .await
+        .unwrap();
+
+        let search = |extra_snippets: Option<bool>| {
+            let params = WebSearchParams { query: "rust".into(), extra_snippets, ..Default::default() };
+            let (db, config) = (db.clone(), config.clone());
+            async move {
+                let result = search_impl(&db, &config, None, params).await.unwrap();
+                let text = result.content[0].as_text().unwrap().text.clone();
+                serde_json::from_str::<serde_json::Value>(&text).unwrap()
+            }
+        };
+
+        let without = search(None).await;
+        assert_eq!(without["debug"]["cache_hit"], true);
+        assert!(without["results"][0].get("extra_snippets").is_none());
+
+        let with = search(Some(true)).await;
+        assert_eq!(
+            with["results"][0]["extra_snippets"],
+            serde_json::json!(["First snippet", "Second snippet"])
+        );
+    }
+
     #[test]
-    fn test_filter_by_domains() {
-        use thndrs_client::SearchResult;
+    fn test_limit_extra_snippets_caps_count_and_length() {
+        let config = AppConfig { extra_snippets_max: 2, extra_snippet_max_chars: 5, ..Default::default() };
+        let mut output = snippet_output(vec!["short".into(), "héllo wörld".into(), "dropped".into()]);
 
+        limit_extra_snippets(&mut output.results, true, &config);
+        assert_eq!(output.results[0].extra_snippets, vec!["short", "héllo…"]);
+
+        limit_extra_snippets(&mut output.results, false, &config);
+        assert!(output.results[0].extra_snippets.is_empty());
+    }
+
+    #[test]
+    fn test_sections_follow_domain_policy() {
+        let mut output: WebSearchOutput = serde_json::from_value(serde_json::json!({
+            "results": [],
+            "query": { "original": "rust", "more_results_available": false },
+            "debug": {}
+        }))
+        .unwrap();
+        assert!(
+            output.videos.is_empty(),
+            "outputs cached before sections existed still load"
+        );
+
+        output.videos = ["https://www.youtube.com/watch?v=1", "https://blocked.example.org/v"]
+            .into_iter()
+            .map(|url| VideoResult {
+                title: "Video".into(),
+                url: url.into(),
+                description: String::new(),
+                age: None,
+                duration: None,
+                creator: None,
+                thumbnail_url: None,
+            })
+            .collect();
+        output.faq = vec![FaqResult {
+            question: "Q?".into(),
+            answer: "A.".into(),
+            title: "FAQ".into(),
+            url: "https://blocked.example.org/faq".into(),
+        }];
+
+        let policy = DomainPolicy::new(&[], &["blocked.example.org".to_string()]);
+        filter_sections(&mut output, &policy, false, &AppConfig::default());
+        assert_eq!(output.videos.len(), 1);
+        assert_eq!(output.videos[0].url, "https://www.youtube.com/watch?v=1");
+        assert!(output.faq.is_empty());
+
+        let json = serde_json::to_value(&output).unwrap();
+        assert!(json.get("faq").is_none() && json.get("news").is_none());
+    }
+
+    #[test]
+    fn test_sort_by_published() {
+        let result = |rank: usize, published_at: Option<&str>| SearchResult {
+            title: format!("Result {rank}"),
+            url: format!("https://example.com/{rank}"),
+            description: String::new(),
+            extra_snippets: vec![],
+            age: None,
+            published_at: published_at.map(str::to_string),
+            language: None,
+            favicon: None,
+            hostname: None,
+            source: "brave".into(),
+            rank,
+        };
+        let mut output = WebSearchOutput {
+            results: vec![
+                result(1, None),
+                result(2, Some("2024-01-05T00:00:00Z")),
+                result(3, Some("2024-03-08T09:30:00Z")),
+                result(4, None),
+                result(5, Some("2024-03-01T00:00:00Z")),
+            ],
+            videos: vec![],
+            faq: vec![],
+            discussions: vec![],
+            news: vec![],
+            query: QueryMeta { original: "rust".into(), ..Default::default() },
+            debug: DebugInfo { request_id: None, cache_hit: Some(false), usage: None, sorted_by: None, attempts: None },
+        };
+
+        sort_by_published(&mut output);
+        let ranks: Vec<usize> = output.results.iter().map(|r| r.rank).collect();
+        assert_eq!(ranks, vec![3, 5, 2, 1, 4]);
+        assert_eq!(output.debug.sorted_by.as_deref(), Some("date"));
+    }
+
+    #[test]
+    fn test_filter_by_domains() {
         let results = vec![
             SearchResult {
                 title: "Example 1".into(),
                 url: "https://example.com/page1".into(),
                 description: "Test 1".into(),
                 extra_snippets: vec![],
+                age: None,
+                published_at: None,
+                language: None,
+                favicon: None,
+                hostname: None,
                 source: "test".into(),
                 rank: 1,
             },
@@ -295,6 +902,11 @@ mod tests {
                 url: "https://other.com/page".into(),
                 description: "Test 2".into(),
                 extra_snippets: vec![],
+                age: None,
+                published_at: None,
+                language: None,
+                favicon: None,
+                hostname: None,
                 source: "test".into(),
                 rank: 2,
             },
@@ -303,16 +915,88 @@ mod tests {
                 url: "https://sub.example.com/page2".into(),
                 description: "Test 3".into(),
                 extra_snippets: vec![],
+                age: None,
+                published_at: None,
+                language: None,
+                favicon: None,
+                hostname: None,
                 source: "test".into(),
                 rank: 3,
             },
         ];
 
         let allowlist = vec!["example.com".to_string()];
-        let filtered = filter_by_domains(&results, &allowlist);
+        let policy = DomainPolicy::default().with_request_allowlist(&allowlist);
+        let filtered = filter_by_domains(results.clone(), &policy);
 
         assert_eq!(filtered.len(), 2);
         assert_eq!(filtered[0].url, "https://example.com/page1");
         assert_eq!(filtered[1].url, "https://sub.example.com/page2");
+
+        let server = DomainPolicy::new(&[], &["sub.example.com".to_string()]).with_request_allowlist(&allowlist);
+        let filtered = filter_by_domains(results, &server);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].url, "https://example.com/page1");
+    }
+
+    #[tokio::test]
+    async fn test_search_caches_fixture_response_with_freshness_ttl() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let server = FixtureServer::start(move |req| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            if req.path.starts_with("/web/search?") && req.path.contains("freshness=pd") {
+                let body = r#"{"query":{"original":"rust"},"web":{"results":[{"title":"Rust","url":"https://www.rust-lang.org/","description":"Rust"}]}}"#;
+                FixtureResponse::ok("application/json", body)
+            } else {
+                FixtureResponse::status(404)
+            }
+        })
+        .await;
+        let brave = BraveClient::new(BraveConfig {
+            api_key: "test-key".into(),
+            base_url: server.url(""),
+            ..Default::default()
+        })
+        .unwrap();
+        let provider: &dyn SearchProvider = &brave;
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig::default();
+        let params = WebSearchParams {
+            query: "rust".into(),
+            count: default_count(),
+            freshness: Some("pd".into()),
+            ..Default::default()
+        };
+
+        let result = search_impl(&db, &config, Some(provider), params.clone()).await.unwrap();
+        let output: WebSearchOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(output.debug.cache_hit, Some(false));
+        assert_eq!(output.results[0].url, "https://www.rust-lang.org/");
+
+        let key = BraveClient::cache_key(&SearchRequest {
+            q: "rust".into(),
+            count: Some(20),
+            freshness: Some("pd".into()),
+            safesearch: Some(SafeSearch::Moderate),
+            ..Default::default()
+        });
+        let meta = db
+            .get_search_meta(&key)
+            .await
+            .unwrap()
+            .expect("search_cache row written");
+        let fetched_at = chrono::DateTime::parse_from_rfc3339(&meta.fetched_at).unwrap();
+        let expires_at = chrono::DateTime::parse_from_rfc3339(&meta.expires_at).unwrap();
+        assert_eq!((expires_at - fetched_at).num_seconds(), 3600);
+
+        let result = search_impl(&db, &config, Some(provider), params).await.unwrap();
+        let output: WebSearchOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(output.debug.cache_hit, Some(true));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
     }
 }
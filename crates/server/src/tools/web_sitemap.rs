@@ -0,0 +1,520 @@
+//! web_sitemap tool implementation.
+//!
+//! Discovers a site's sitemaps (robots.txt `Sitemap:` lines, then `/sitemap.xml`),
+//! follows sitemap indexes, and returns a filtered page of entries suitable for
+//! feeding into web_batch_open. Parsed sitemaps are cached by URL for six hours.
+
+use std::collections::{HashSet, VecDeque};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use regex::Regex;
+use rmcp::{ErrorData as McpError, model::*};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thndrs_client::fetch::canonicalize_with_options;
+use thndrs_client::sitemap::{
+    MAX_SITEMAP_DEPTH, MAX_SITEMAP_FILES, MAX_TOTAL_SITEMAP_ENTRIES, discover_sitemaps, fetch_sitemap,
+};
+use thndrs_client::{FetchClient, RequestOptions, Sitemap, SitemapEntry, SitemapKind};
+use thndrs_core::{AppConfig, CacheDb, Error};
+use url::Url;
+
+use crate::tools::output::json_result;
+use crate::tools::web_open::canonicalize_options;
+
+/// How long parsed sitemaps stay cached.
+const SITEMAP_TTL_SECONDS: i64 = 6 * 60 * 60;
+
+/// Upper bound on `limit`.
+const MAX_LIMIT: usize = 1000;
+
+/// Input parameters for web_sitemap tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebSitemapParams {
+    /// Site root (e.g. "https://example.com") or an explicit sitemap URL.
+    pub url: String,
+
+    /// Glob filter on entry URLs (`*` matches any run of characters, `?` a single one).
+    #[serde(default)]
+    pub glob: Option<String>,
+
+    /// Regular expression filter on entry URLs.
+    #[serde(default)]
+    pub regex: Option<String>,
+
+    /// Only include entries modified after this date (YYYY-MM-DD or RFC 3339).
+    #[serde(default, alias = "include_lastmod_after")]
+    pub lastmod_after: Option<String>,
+
+    /// Maximum number of entries to return (default: 100, max: 1000).
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+
+    /// Number of matching entries to skip.
+    #[serde(default)]
+    pub offset: usize,
+
+    /// Bypass the sitemap cache.
+    #[serde(default)]
+    pub force_refresh: bool,
+}
+
+fn default_limit() -> usize {
+    100
+}
+
+/// A page listed in a sitemap.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SitemapUrl {
+    pub loc: String,
+    pub lastmod: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changefreq: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+}
+
+/// Output structure for web_sitemap tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebSitemapOutput {
+    /// Sitemap files read (indexes and URL sets), in discovery order.
+    pub sitemaps: Vec<String>,
+    /// Sitemap files that could not be fetched or parsed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed: Vec<String>,
+    /// Total page entries across all sitemaps.
+    pub total_entries: usize,
+    /// Entries matching the filters.
+    pub matched: usize,
+    /// The requested page of matching entries.
+    pub entries: Vec<SitemapUrl>,
+    /// Whether entry, file, or index depth caps cut the listing short.
+    pub truncated: bool,
+}
+
+/// Implementation of the web_sitemap tool.
+///
+/// Fetches go through the server's shared client, so robots.txt rules, pacing,
+/// and host failure state carry over from the other tools.
+pub async fn sitemap_impl(
+    db: &CacheDb, config: &AppConfig, fetch: &FetchClient, params: WebSitemapParams,
+) -> Result<CallToolResult, McpError> {
+    if params.url.is_empty() {
+        return Err(Error::InvalidInput("url cannot be empty".into()).into());
+    }
+    if params.limit == 0 || params.limit > MAX_LIMIT {
+        return Err(Error::InvalidInput(format!("limit must be between 1 and {MAX_LIMIT}")).into());
+    }
+
+    let url = canonicalize_with_options(&params.url, &canonicalize_options(config))
+        .map_err(|e| Error::InvalidUrl(e.to_string()))?;
+    let filter = EntryFilter::new(&params)?;
+
+    let options = RequestOptions { timeout: Some(config.timeout()), ..Default::default() };
+
+    let roots = if is_sitemap_url(&url) {
+        vec![url.to_string()]
+    } else {
+        discover_sitemaps(fetch, &url, &options).await
+    };
+
+    let mut seen: HashSet<String> = roots.iter().cloned().collect();
+    let mut queue: VecDeque<(String, usize)> = roots.into_iter().map(|root| (root, 0)).collect();
+    let mut sitemaps = Vec::new();
+    let mut failed = Vec::new();
+    let mut entries: Vec<SitemapEntry> = Vec::new();
+    let mut truncated = false;
+    let mut last_error = None;
+
+    while let Some((sitemap_url, depth)) = queue.pop_front() {
+        if sitemaps.len() >= MAX_SITEMAP_FILES || entries.len() >= MAX_TOTAL_SITEMAP_ENTRIES {
+            truncated = true;
+            break;
+        }
+
+        let sitemap = match load_sitemap(db, fetch, &options, &sitemap_url, params.force_refresh).await {
+            Ok(sitemap) => sitemap,
+            Err(e) => {
+                tracing::warn!("failed to read sitemap {}: {}", sitemap_url, e);
+                failed.push(sitemap_url);
+                last_error = Some(e);
+                continue;
+            }
+        };
+
+        sitemaps.push(sitemap_url);
+        truncated |= sitemap.truncated;
+
+        match sitemap.kind {
+            SitemapKind::Index if depth >= MAX_SITEMAP_DEPTH => {
+                tracing::debug!(
+                    "not following {}: indexes nested past {}",
+                    sitemap_url,
+                    MAX_SITEMAP_DEPTH
+                );
+                truncated = true;
+            }
+            SitemapKind::Index => {
+                for child in sitemap.entries {
+                    if seen.insert(child.loc.clone()) {
+                        queue.push_back((child.loc, depth + 1));
+                    }
+                }
+            }
+            SitemapKind::UrlSet => {
+                let room = MAX_TOTAL_SITEMAP_ENTRIES - entries.len();
+                truncated |= sitemap.entries.len() > room;
+                entries.extend(sitemap.entries.into_iter().take(room));
+            }
+        }
+    }
+
+    if sitemaps.is_empty() {
+        let err = last_error.unwrap_or_else(|| Error::InvalidUrl(format!("no sitemap found for {url}")));
+        return Err(err.into());
+    }
+
+    let total_entries = entries.len();
+    let matching: Vec<SitemapEntry> = entries.into_iter().filter(|e| filter.matches(e)).collect();
+    let output = WebSitemapOutput {
+        sitemaps,
+        failed,
+        total_entries,
+        matched: matching.len(),
+        entries: matching
+            .into_iter()
+            .skip(params.offset)
+            .take(params.limit)
+            .map(|e| SitemapUrl { loc: e.loc, lastmod: e.lastmod, changefreq: e.changefreq, priority: e.priority })
+            .collect(),
+        truncated,
+    };
+
+    json_result(&output, config)
+}
+
+/// Read a sitemap from the cache, fetching and caching it on a miss.
+async fn load_sitemap(
+    db: &CacheDb, client: &FetchClient, options: &RequestOptions, url: &str, force_refresh: bool,
+) -> Result<Sitemap, Error> {
+    if !force_refresh
+        && let Some(json) = db.get_sitemap(url).await.ok().flatten()
+        && let Ok(sitemap) = serde_json::from_str(&json)
+    {
+        tracing::debug!("sitemap cache hit for {}", url);
+        return Ok(sitemap);
+    }
+
+    let sitemap = fetch_sitemap(client, url, options).await?;
+    if let Ok(json) = serde_json::to_string(&sitemap)
+        && let Err(e) = db.put_sitemap(url, &json, SITEMAP_TTL_SECONDS).await
+    {
+        tracing::warn!("failed to cache sitemap {}: {}", url, e);
+    }
+
+    Ok(sitemap)
+}
+
+/// Whether `url` points at a sitemap file rather than a page on the site.
+fn is_sitemap_url(url: &Url) -> bool {
+    let path = url.path().to_ascii_lowercase();
+    path.contains("sitemap") || path.ends_with(".xml") || path.ends_with(".xml.gz") || path.ends_with(".txt")
+}
+
+/// Compiled loc and lastmod filters.
+struct EntryFilter {
+    glob: Option<Regex>,
+    regex: Option<Regex>,
+    lastmod_after: Option<DateTime<Utc>>,
+}
+
+impl EntryFilter {
+    fn new(params: &WebSitemapParams) -> Result<Self, Error> {
+        let glob = params.glob.as_deref().map(glob_to_regex).transpose()?;
+        let regex = params
+            .regex
+            .as_deref()
+            .map(|r| Regex::new(r).map_err(|e| Error::InvalidInput(format!("invalid regex: {e}"))))
+            .transpose()?;
+        let lastmod_after = params
+            .lastmod_after
+            .as_deref()
+            .map(|d| parse_w3c_datetime(d).ok_or_else(|| Error::InvalidInput(format!("invalid lastmod_after: {d}"))))
+            .transpose()?;
+
+        Ok(Self { glob, regex, lastmod_after })
+    }
+
+    fn matches(&self, entry: &SitemapEntry) -> bool {
+        if let Some(glob) = &self.glob
+            && !glob.is_match(&entry.loc)
+        {
+            return false;
+        }
+        if let Some(regex) = &self.regex
+            && !regex.is_match(&entry.loc)
+        {
+            return false;
+        }
+        match self.lastmod_after {
+            Some(after) => entry
+                .lastmod
+                .as_deref()
+                .and_then(parse_w3c_datetime)
+                .is_some_and(|lastmod| lastmod > after),
+            None => true,
+        }
+    }
+}
+
+/// Translate a `*`/`?` glob into an anchored regex.
+fn glob_to_regex(glob: &str) -> Result<Regex, Error> {
+    let mut pattern = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+
+    Regex::new(&pattern).map_err(|e| Error::InvalidInput(format!("invalid glob: {e}")))
+}
+
+/// Parse a sitemap `<lastmod>` value: a full RFC 3339 timestamp or a bare date.
+fn parse_w3c_datetime(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|dt| dt.and_utc())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use thndrs_client::CircuitBreaker;
+    use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+
+    use crate::tools::web_open::fetch_client;
+
+    fn gzip(data: &str) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn urlset(base: &str, entries: &[(&str, Option<&str>)]) -> String {
+        let urls: String = entries
+            .iter()
+            .map(|(path, lastmod)| match lastmod {
+                Some(lastmod) => format!("<url><loc>{base}{path}</loc><lastmod>{lastmod}</lastmod></url>"),
+                None => format!("<url><loc>{base}{path}</loc></url>"),
+            })
+            .collect();
+        format!(r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">{urls}</urlset>"#)
+    }
+
+    async fn site(hits: Arc<AtomicUsize>) -> FixtureServer {
+        FixtureServer::start(move |req| {
+            let base = format!("http://{}", req.header("host").unwrap_or_default());
+            if req.path != "/robots.txt" {
+                hits.fetch_add(1, Ordering::SeqCst);
+            }
+
+            match req.path.as_str() {
+                "/robots.txt" => FixtureResponse::ok("text/plain", format!("Sitemap: {base}/sitemap_index.xml\n")),
+                "/sitemap_index.xml" => FixtureResponse::ok(
+                    "application/xml",
+                    format!(
+                        r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+                        <sitemap><loc>{base}/posts.xml.gz</loc></sitemap>
+                        <sitemap><loc>{base}/pages.xml</loc></sitemap>
+                        </sitemapindex>"#
+                    ),
+                ),
+                "/posts.xml.gz" => FixtureResponse::ok(
+                    "application/x-gzip",
+                    gzip(&urlset(
+                        &base,
+                        &[
+                            ("/posts/1", Some("2024-01-01")),
+                            ("/posts/2", Some("2024-06-01T12:00:00Z")),
+                            ("/posts/3", Some("2024-09-01")),
+                        ],
+                    )),
+                ),
+                "/pages.xml" => FixtureResponse::ok("text/xml", urlset(&base, &[("/about", None), ("/contact", None)])),
+                _ => FixtureResponse::status(404),
+            }
+        })
+        .await
+    }
+
+    fn params(url: String) -> WebSitemapParams {
+        WebSitemapParams {
+            url,
+            glob: None,
+            regex: None,
+            lastmod_after: None,
+            limit: 100,
+            offset: 0,
+            force_refresh: false,
+        }
+    }
+
+    async fn run(db: &CacheDb, params: WebSitemapParams) -> WebSitemapOutput {
+        let config = AppConfig { respect_robots: false, allow_private_addresses: true, ..Default::default() };
+        let fetch = fetch_client(&config, db, &CircuitBreaker::default()).unwrap();
+        let result = sitemap_impl(db, &config, &fetch, params).await.unwrap();
+        serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sitemap_index_discovery_and_cache() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let server = site(hits.clone()).await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+
+        let output = run(&db, params(server.url("/"))).await;
+        assert_eq!(
+            output.sitemaps,
+            vec![
+                server.url("/sitemap_index.xml"),
+                server.url("/posts.xml.gz"),
+                server.url("/pages.xml")
+            ]
+        );
+        assert_eq!(output.total_entries, 5);
+        assert_eq!(output.matched, 5);
+        assert!(!output.truncated);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        let output = run(&db, params(server.url("/"))).await;
+        assert_eq!(output.total_entries, 5);
+        assert_eq!(
+            hits.load(Ordering::SeqCst),
+            3,
+            "second call should be served from the cache"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sitemap_filters_and_paging() {
+        let server = site(Arc::new(AtomicUsize::new(0))).await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+
+        let filtered = WebSitemapParams {
+            glob: Some("*/posts/*".into()),
+            lastmod_after: Some("2024-05-01".into()),
+            limit: 1,
+            offset: 1,
+            ..params(server.url("/"))
+        };
+        let output = run(&db, filtered).await;
+        assert_eq!(output.matched, 2);
+        assert_eq!(output.entries.len(), 1);
+        assert_eq!(output.entries[0].loc, server.url("/posts/3"));
+
+        let by_regex = WebSitemapParams { regex: Some("/(about|contact)$".into()), ..params(server.url("/")) };
+        assert_eq!(run(&db, by_regex).await.matched, 2);
+    }
+
+    #[tokio::test]
+    async fn test_explicit_sitemap_url() {
+        let server = site(Arc::new(AtomicUsize::new(0))).await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+
+        let output = run(&db, params(server.url("/pages.xml"))).await;
+        assert_eq!(output.sitemaps, vec![server.url("/pages.xml")]);
+        assert_eq!(output.total_entries, 2);
+        assert!(output.entries.iter().all(|e| e.lastmod.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_nested_indexes_stop_at_depth_limit() {
+        let server = FixtureServer::start(|req| {
+            let base = format!("http://{}", req.header("host").unwrap_or_default());
+            let level: usize = req
+                .path
+                .trim_start_matches("/index")
+                .trim_end_matches(".xml")
+                .parse()
+                .unwrap_or(usize::MAX);
+            if level == usize::MAX {
+                return FixtureResponse::status(404);
+            }
+            FixtureResponse::ok(
+                "application/xml",
+                format!(
+                    r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+                    <sitemap><loc>{base}/index{}.xml</loc></sitemap></sitemapindex>"#,
+                    level + 1
+                ),
+            )
+        })
+        .await;
+        let db = CacheDb::open_in_memory().await.unwrap();
+
+        let output = run(&db, params(server.url("/index0.xml"))).await;
+        assert_eq!(output.sitemaps.len(), MAX_SITEMAP_DEPTH + 1);
+        assert!(output.truncated);
+    }
+
+    #[test]
+    fn test_entry_fields_and_param_alias() {
+        let params: WebSitemapParams = serde_json::from_value(serde_json::json!({
+            "url": "https://example.com",
+            "include_lastmod_after": "2024-01-01"
+        }))
+        .unwrap();
+        assert_eq!(params.lastmod_after.as_deref(), Some("2024-01-01"));
+
+        let url = SitemapUrl { loc: "https://example.com/a".into(), lastmod: None, changefreq: None, priority: None };
+        assert_eq!(
+            serde_json::to_value(&url).unwrap(),
+            serde_json::json!({ "loc": "https://example.com/a", "lastmod": null })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sitemap_invalid_params() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let config = AppConfig::default();
+        let fetch = fetch_client(&config, &db, &CircuitBreaker::default()).unwrap();
+
+        let bad_limit = WebSitemapParams { limit: 0, ..params("https://example.com".into()) };
+        assert!(sitemap_impl(&db, &config, &fetch, bad_limit).await.is_err());
+
+        let bad_regex = WebSitemapParams { regex: Some("(".into()), ..params("https://example.com".into()) };
+        assert!(sitemap_impl(&db, &config, &fetch, bad_regex).await.is_err());
+
+        let bad_date =
+            WebSitemapParams { lastmod_after: Some("yesterday".into()), ..params("https://example.com".into()) };
+        assert!(sitemap_impl(&db, &config, &fetch, bad_date).await.is_err());
+    }
+
+    #[test]
+    fn test_glob_to_regex() {
+        let re = glob_to_regex("https://example.com/docs/*.html").unwrap();
+        assert!(re.is_match("https://example.com/docs/a/b.html"));
+        assert!(!re.is_match("https://example.com/blog/a.html"));
+        assert!(glob_to_regex("/v?/").unwrap().is_match("/v2/"));
+    }
+
+    #[test]
+    fn test_parse_w3c_datetime() {
+        assert!(parse_w3c_datetime("2024-01-15").is_some());
+        assert!(parse_w3c_datetime("2024-01-15T10:00:00+02:00").is_some());
+        assert!(parse_w3c_datetime("Jan 15").is_none());
+    }
+}
@@ -0,0 +1,162 @@
+//! web_suggest tool implementation.
+//!
+//! Fetches Brave's query completions so a vague query can be refined before
+//! spending a full search. Cached for 15 minutes under `suggest:` keys, so
+//! refinement loops that repeat a prefix don't spend quota.
+
+use rmcp::{ErrorData as McpError, model::*};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thndrs_client::BraveClient;
+use thndrs_core::{AppConfig, CacheDb, Error};
+
+use crate::tools::deadline::{self, Phase};
+use crate::tools::output::json_result;
+use crate::tools::web_search::{DebugInfo, cached_search, require_client, search_error, usage_report};
+
+/// How long suggestions stay cached, in seconds.
+const SUGGEST_TTL_SECS: i64 = 900;
+
+/// Input parameters for web_suggest tool.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct WebSuggestParams {
+    /// Partial or vague query to complete (required, max 400 chars).
+    pub query: String,
+
+    /// Country code (ISO 3166-1 alpha-2, e.g., "US").
+    #[serde(default)]
+    pub country: Option<String>,
+
+    /// Force a refresh, bypassing the cache.
+    #[serde(default)]
+    pub force_refresh: bool,
+}
+
+/// Output structure for web_suggest tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebSuggestOutput {
+    /// The query that was completed.
+    pub query: String,
+    /// Suggested queries, in Brave's order.
+    pub suggestions: Vec<Suggestion>,
+    /// Debug information.
+    pub debug: DebugInfo,
+}
+
+/// One suggested query.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Suggestion {
+    /// The suggested query.
+    pub query: String,
+    /// Whether the suggestion names an entity (a person, place, product, ...).
+    pub is_entity: bool,
+}
+
+/// Implementation of the web_suggest tool.
+pub async fn suggest_impl(
+    db: &CacheDb, config: &AppConfig, brave: Option<&BraveClient>, params: WebSuggestParams,
+) -> Result<CallToolResult, McpError> {
+    if params.query.is_empty() {
+        return Err(Error::InvalidInput("query cannot be empty".into()).into());
+    }
+    if params.query.len() > 400 {
+        return Err(Error::InvalidInput(format!("query too long: {} chars (max 400)", params.query.len())).into());
+    }
+
+    let country = params.country.as_deref();
+    let cache_key = BraveClient::suggest_cache_key(&params.query, country);
+
+    deadline::enter(Phase::Cache);
+    let cached: Option<WebSuggestOutput> =
+        if params.force_refresh { None } else { cached_search(db, &cache_key).await };
+    if let Some(mut output) = cached {
+        tracing::debug!("cache hit for suggest query: {}", params.query);
+        if let Err(e) = db.record_brave_cache_hit().await {
+            tracing::warn!("failed to record suggest cache hit: {}", e);
+        }
+        output.debug.cache_hit = Some(true);
+        output.debug.usage = usage_report(db, config).await;
+        return json_result(&output, config);
+    }
+
+    let client = require_client(brave, config)?;
+    deadline::enter(Phase::Fetch);
+    let suggestions = client.suggest(&params.query, country).await.map_err(search_error)?;
+    if let Err(e) = db.record_brave_request().await {
+        tracing::warn!("failed to record Brave request: {}", e);
+    }
+
+    let mut output = WebSuggestOutput {
+        query: params.query.clone(),
+        suggestions: suggestions
+            .into_iter()
+            .map(|s| Suggestion { query: s.query, is_entity: s.is_entity })
+            .collect(),
+        debug: DebugInfo { request_id: None, cache_hit: Some(false), usage: None, sorted_by: None, attempts: None },
+    };
+
+    match (serde_json::to_string(&params.query), serde_json::to_string(&output)) {
+        (Ok(query_json), Ok(response_json)) => {
+            let writer = db.clone();
+            let write = deadline::cache_write(async move {
+                writer
+                    .put_search(&cache_key, &query_json, &response_json, SUGGEST_TTL_SECS)
+                    .await
+            });
+            if let Err(e) = write.await {
+                tracing::warn!("failed to cache suggestions: {}", e);
+            }
+        }
+        (Err(e), _) | (_, Err(e)) => tracing::warn!("failed to serialize suggestions for caching: {}", e),
+    }
+
+    output.debug.usage = usage_report(db, config).await;
+    json_result(&output, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rejects_long_query() {
+        let db = CacheDb::open_in_memory().await.unwrap();
+        let params = WebSuggestParams { query: "a".repeat(401), ..Default::default() };
+
+        let err = suggest_impl(&db, &AppConfig::default(), None, params)
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("max 400"), "{}", err.message);
+    }
+
+    #[tokio::test]
+    async fn test_cached_suggestions_expire_after_fifteen_minutes() {
+        let clock = thndrs_core::ManualClock::default();
+        let db = CacheDb::open_in_memory().await.unwrap().with_clock(clock.clone());
+        let config = AppConfig::default();
+
+        let cached = WebSuggestOutput {
+            query: "rust".into(),
+            suggestions: vec![Suggestion { query: "rust lang".into(), is_entity: false }],
+            debug: DebugInfo { request_id: None, cache_hit: Some(false), usage: None, sorted_by: None, attempts: None },
+        };
+        db.put_search(
+            &BraveClient::suggest_cache_key("rust", None),
+            "\"rust\"",
+            &serde_json::to_string(&cached).unwrap(),
+            SUGGEST_TTL_SECS,
+        )
+        .await
+        .unwrap();
+
+        let params = WebSuggestParams { query: "rust".into(), ..Default::default() };
+        let result = suggest_impl(&db, &config, None, params.clone()).await.unwrap();
+        let output: WebSuggestOutput = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(output.debug.cache_hit, Some(true));
+        assert_eq!(output.suggestions[0].query, "rust lang");
+
+        clock.advance(chrono::Duration::minutes(15));
+        let err = suggest_impl(&db, &config, None, params).await.unwrap_err();
+        assert_eq!(err.code.0, -32009, "expired suggestions should fall through to the API");
+    }
+}
@@ -0,0 +1,206 @@
+//! Shared harness for end-to-end tests.
+//!
+//! [`Site`] describes a fixture website (pages, robots.txt, redirects, slow
+//! and rate-limited routes) served on an ephemeral localhost port and counts
+//! hits per path. [`TestServer`] runs an [`McpWebServer`] over an in-memory
+//! MCP transport with its cache in a temporary directory, optionally searching
+//! against a fixture Brave endpoint. Nothing leaves the machine.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rmcp::{
+    ErrorData, ServiceExt,
+    model::CallToolRequestParam,
+    service::{RoleClient, RoleServer, RunningService, ServiceError},
+};
+use serde_json::{Value, json};
+use tempfile::TempDir;
+use thndrs_client::fixture::{FixtureResponse, FixtureServer};
+use thndrs_client::{BraveClient, BraveConfig};
+use thndrs_core::{AppConfig, CacheDb};
+use thndrs_server::McpWebServer;
+
+/// An article long enough for readability extraction to pick it.
+pub fn article(title: &str) -> String {
+    let paragraph = "<p>End-to-end tests drive the tools through the same MCP transport a client uses, \
+                     against a local site, so fetching, extraction, and caching run exactly as in production. \
+                     This sentence pads the article so the extractor has enough text to score.</p>";
+    format!(
+        "<html><head><title>{title}</title></head><body><nav><a href=\"/\">Home</a></nav>\
+         <article><h1>{title}</h1>{}<p><a href=\"/next\">Next article</a></p></article></body></html>",
+        paragraph.repeat(6)
+    )
+}
+
+/// Routes for a fixture website; unknown paths answer 404.
+#[derive(Default)]
+pub struct Site {
+    routes: HashMap<String, FixtureResponse>,
+}
+
+impl Site {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve `html` at `path`.
+    pub fn page(self, path: &str, html: impl Into<Vec<u8>>) -> Self {
+        self.route(path, FixtureResponse::html(html))
+    }
+
+    /// Serve `body` as `/robots.txt`.
+    pub fn robots(self, body: &str) -> Self {
+        self.route("/robots.txt", FixtureResponse::ok("text/plain", body))
+    }
+
+    /// Answer `from` with a 301 to `to` (a path or absolute URL).
+    pub fn redirect(self, from: &str, to: &str) -> Self {
+        self.route(from, FixtureResponse::status(301).with_header("Location", to))
+    }
+
+    /// Serve `html` at `path`, holding the body back for `delay`.
+    pub fn slow(self, path: &str, delay: Duration, html: impl Into<Vec<u8>>) -> Self {
+        self.route(path, FixtureResponse::html(html).with_body_delay(delay))
+    }
+
+    /// Answer `path` with 429 Too Many Requests and `Retry-After: <secs>`.
+    pub fn rate_limited(self, path: &str, retry_after_secs: u64) -> Self {
+        self.route(
+            path,
+            FixtureResponse::status(429).with_header("Retry-After", &retry_after_secs.to_string()),
+        )
+    }
+
+    /// Answer `path` with an arbitrary response.
+    pub fn route(mut self, path: &str, response: FixtureResponse) -> Self {
+        self.routes.insert(path.to_string(), response);
+        self
+    }
+
+    /// Start serving on an ephemeral port.
+    pub async fn start(self) -> TestSite {
+        let hits: Arc<Mutex<HashMap<String, usize>>> = Arc::default();
+        let counter = hits.clone();
+        let routes = self.routes;
+        let server = FixtureServer::start(move |req| {
+            let path = req.path.split('?').next().unwrap_or_default().to_string();
+            let response = routes
+                .get(&path)
+                .cloned()
+                .unwrap_or_else(|| FixtureResponse::status(404));
+            *counter.lock().unwrap().entry(path).or_default() += 1;
+            response
+        })
+        .await;
+        TestSite { server, hits }
+    }
+}
+
+/// A running fixture website.
+pub struct TestSite {
+    server: FixtureServer,
+    hits: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl TestSite {
+    /// Absolute URL for `path`.
+    pub fn url(&self, path: &str) -> String {
+        self.server.url(path)
+    }
+
+    /// Requests received for `path`, ignoring the query string.
+    pub fn hits(&self, path: &str) -> usize {
+        self.hits.lock().unwrap().get(path).copied().unwrap_or(0)
+    }
+}
+
+/// A fixture Brave Search API answering every query with `results` (title, url) pairs.
+pub async fn brave_fixture(results: &[(&str, &str)]) -> TestSite {
+    let results: Vec<Value> = results
+        .iter()
+        .map(|(title, url)| json!({ "title": title, "url": url, "description": format!("About {title}") }))
+        .collect();
+    let body = json!({
+        "query": { "original": "fixture", "moreResultsAvailable": false },
+        "web": { "results": results }
+    });
+    Site::new()
+        .route("/web/search", FixtureResponse::ok("application/json", body.to_string()))
+        .start()
+        .await
+}
+
+/// An mcp-web server reached through an MCP client, with its cache in a temp directory.
+pub struct TestServer {
+    client: RunningService<RoleClient, ()>,
+    _server: RunningService<RoleServer, McpWebServer>,
+    cache: CacheDb,
+    _dir: TempDir,
+}
+
+impl TestServer {
+    /// Start a server with `config`; `db_path` is replaced by a fresh temp file and
+    /// private addresses are allowed so the localhost fixtures can be fetched.
+    pub async fn start(config: AppConfig) -> Self {
+        Self::build(config, None).await
+    }
+
+    /// Start a server whose web_search queries the fixture Brave API at `brave`.
+    ///
+    /// The client does not pace its requests, so retries and repeated calls
+    /// reach the fixture immediately.
+    pub async fn with_brave(config: AppConfig, brave: &TestSite) -> Self {
+        let client = BraveClient::new(BraveConfig {
+            api_key: "test-key".into(),
+            base_url: brave.url(""),
+            min_request_interval: Duration::ZERO,
+            ..Default::default()
+        })
+        .unwrap();
+        Self::build(config, Some(client)).await
+    }
+
+    async fn build(config: AppConfig, brave: Option<BraveClient>) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let config = AppConfig { db_path: dir.path().join("cache.sqlite"), allow_private_addresses: true, ..config };
+        let mut web = McpWebServer::new(config).await.unwrap();
+        if let Some(brave) = brave {
+            web = web.with_brave_client(brave);
+        }
+        let cache = web.cache().clone();
+
+        let (server_io, client_io) = tokio::io::duplex(1024 * 1024);
+        let (server, client) = tokio::join!(web.serve(server_io), ().serve(client_io));
+        Self { client: client.unwrap(), _server: server.unwrap(), cache, _dir: dir }
+    }
+
+    /// The server's cache, for checking side effects.
+    pub fn cache(&self) -> &CacheDb {
+        &self.cache
+    }
+
+    /// Call `tool` with `arguments`, returning its JSON output or the tool error.
+    pub async fn call(&self, tool: &str, arguments: Value) -> Result<Value, ErrorData> {
+        let request: CallToolRequestParam =
+            serde_json::from_value(json!({ "name": tool, "arguments": arguments })).unwrap();
+        match self.client.call_tool(request).await {
+            Ok(result) => {
+                let text = &result.content[0].as_text().expect("tool output is text").text;
+                Ok(serde_json::from_str(text).expect("tool output is JSON"))
+            }
+            Err(ServiceError::McpError(e)) => Err(e),
+            Err(e) => panic!("{tool} call failed in transport: {e}"),
+        }
+    }
+
+    /// Call `tool`, panicking on a tool error.
+    pub async fn ok(&self, tool: &str, arguments: Value) -> Value {
+        self.call(tool, arguments)
+            .await
+            .unwrap_or_else(|e| panic!("{tool} failed: {} ({})", e.message, e.code.0))
+    }
+}
@@ -0,0 +1,387 @@
+//! End-to-end tool scenarios against local fixture sites.
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{Site, TestServer, article, brave_fixture};
+use serde_json::json;
+use thndrs_client::fixture::FixtureResponse;
+use thndrs_core::{AppConfig, FetchSettings, RobotsFailurePolicy};
+
+#[tokio::test]
+async fn test_robots_disallow_blocks_only_disallowed_paths() {
+    let site = Site::new()
+        .robots("User-agent: *\nDisallow: /private\n")
+        .page("/private/doc", article("Private"))
+        .page("/public/doc", article("Public"))
+        .start()
+        .await;
+    let server = TestServer::start(AppConfig::default()).await;
+
+    let err = server
+        .call("web_open", json!({ "url": site.url("/private/doc") }))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code.0, -32005, "{}", err.message);
+    assert_eq!(site.hits("/private/doc"), 0);
+
+    let open = server.ok("web_open", json!({ "url": site.url("/public/doc") })).await;
+    assert_eq!(open["title"], "Public");
+}
+
+#[tokio::test]
+async fn test_unavailable_robots_follows_failure_policy() {
+    let site = Site::new()
+        .route("/robots.txt", FixtureResponse::status(503))
+        .page("/doc", article("Doc"))
+        .start()
+        .await;
+
+    let closed = TestServer::start(AppConfig::default()).await;
+    let err = closed
+        .call("web_open", json!({ "url": site.url("/doc") }))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code.0, -32019, "{}", err.message);
+    assert_eq!(site.hits("/doc"), 0);
+
+    let fetch = FetchSettings { robots_failure_policy: RobotsFailurePolicy::FailOpen, ..Default::default() };
+    let open = TestServer::start(AppConfig { fetch, ..Default::default() }).await;
+    let doc = open.ok("web_open", json!({ "url": site.url("/doc") })).await;
+    assert_eq!(doc["title"], "Doc");
+}
+
+#[tokio::test]
+async fn test_redirect_is_followed_extracted_and_cached() {
+    let site = Site::new()
+        .redirect("/old", "/article")
+        .page("/article", article("Moved Article"))
+        .start()
+        .await;
+    let server = TestServer::start(AppConfig::default()).await;
+
+    let first = server.ok("web_open", json!({ "url": site.url("/old") })).await;
+    assert_eq!(first["url"], site.url("/old"));
+    assert_eq!(first["final_url"], site.url("/article"));
+    assert_eq!(first["title"], "Moved Article");
+    assert!(
+        first["markdown"]
+            .as_str()
+            .unwrap()
+            .contains("End-to-end tests drive the tools")
+    );
+    let links = first["links"].as_array().unwrap();
+    assert!(links.iter().any(|l| l["href"] == site.url("/next")));
+    assert!(first["bytes_fetched"].as_u64().unwrap() > 0);
+
+    let second = server.ok("web_open", json!({ "url": site.url("/old") })).await;
+    assert_eq!(second["hash"], first["hash"]);
+    assert_eq!(second["bytes_fetched"], 0);
+    assert_eq!(second["markdown"], first["markdown"]);
+    assert_eq!((site.hits("/old"), site.hits("/article")), (1, 1));
+
+    let cached = server.ok("cache_get", json!({ "hash": first["hash"] })).await;
+    assert_eq!(cached["snapshot"]["final_url"], site.url("/article"));
+}
+
+#[tokio::test]
+async fn test_force_refresh_refetches() {
+    let site = Site::new().page("/doc", article("Fresh")).start().await;
+    let server = TestServer::start(AppConfig::default()).await;
+
+    server.ok("web_open", json!({ "url": site.url("/doc") })).await;
+    server
+        .ok("web_open", json!({ "url": site.url("/doc"), "force_refresh": true }))
+        .await;
+    assert_eq!(site.hits("/doc"), 2);
+}
+
+#[tokio::test]
+async fn test_batch_reports_mixed_outcomes() {
+    let site = Site::new()
+        .page("/a", article("Batch A"))
+        .page("/b", article("Batch B"))
+        .route("/broken", FixtureResponse::status(500))
+        .start()
+        .await;
+    let server = TestServer::start(AppConfig::default()).await;
+
+    let urls = [
+        site.url("/a"),
+        site.url("/missing"),
+        site.url("/b"),
+        site.url("/broken"),
+    ];
+    let batch = server.ok("web_batch_open", json!({ "urls": urls })).await;
+
+    let results = batch["results"].as_array().unwrap();
+    assert_eq!(results.len(), 4);
+    let item = |path: &str| {
+        let url = site.url(path);
+        results.iter().find(|item| item["url"] == url.as_str()).unwrap().clone()
+    };
+    assert_eq!(item("/a")["status"], "Success");
+    assert_eq!(item("/a")["result"]["title"], "Batch A");
+    assert_eq!(item("/b")["status"], "Success");
+    assert_eq!(item("/missing")["status"], "Failed");
+    assert!(item("/missing")["error"].as_str().unwrap().contains("404"));
+    assert_eq!(item("/broken")["status"], "Failed");
+    assert!(item("/broken")["error"].as_str().unwrap().contains("500"));
+
+    let summary = &batch["summary"];
+    assert_eq!(summary["total"], 4);
+    assert_eq!(summary["succeeded"], 2);
+    assert_eq!(summary["failed"], 2);
+    assert!(summary["total_bytes_fetched"].as_u64().unwrap() > 0);
+}
+
+#[tokio::test]
+async fn test_rate_limited_host_fails_fast_while_cooling_down() {
+    let site = Site::new()
+        .rate_limited("/limited", 60)
+        .page("/other", article("Other"))
+        .start()
+        .await;
+    let server = TestServer::start(AppConfig::default()).await;
+
+    let err = server
+        .call("web_open", json!({ "url": site.url("/limited") }))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code.0, -32008, "{}", err.message);
+
+    let err = server
+        .call("web_open", json!({ "url": site.url("/other") }))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code.0, -32016, "{}", err.message);
+    assert_eq!(site.hits("/limited"), 1);
+    assert_eq!(site.hits("/other"), 0);
+}
+
+#[tokio::test]
+async fn test_slow_body_times_out() {
+    let site = Site::new()
+        .slow("/slow", Duration::from_secs(10), article("Slow"))
+        .start()
+        .await;
+    let server = TestServer::start(AppConfig::default()).await;
+
+    let start = Instant::now();
+    let result = server
+        .call("web_open", json!({ "url": site.url("/slow"), "timeout_ms": 300 }))
+        .await;
+    assert!(result.is_err());
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_cache_purge_by_domain_spares_pinned_snapshots() {
+    let site = Site::new()
+        .page("/keep", article("Keep"))
+        .page("/drop", article("Drop"))
+        .start()
+        .await;
+    let server = TestServer::start(AppConfig::default()).await;
+
+    let keep = server.ok("web_open", json!({ "url": site.url("/keep") })).await;
+    let drop = server.ok("web_open", json!({ "url": site.url("/drop") })).await;
+    server.ok("cache_pin", json!({ "hash": keep["hash"] })).await;
+
+    let purged = server.ok("cache_purge", json!({ "domain": "127.0.0.1" })).await;
+    assert_eq!(purged["deleted"], 1);
+
+    server.ok("cache_get", json!({ "hash": keep["hash"] })).await;
+    let err = server
+        .call("cache_get", json!({ "hash": drop["hash"] }))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code.0, -32001);
+
+    server.ok("web_open", json!({ "url": site.url("/keep") })).await;
+    server.ok("web_open", json!({ "url": site.url("/drop") })).await;
+    assert_eq!((site.hits("/keep"), site.hits("/drop")), (1, 2));
+}
+
+#[tokio::test]
+async fn test_cache_list_and_stats_reflect_opened_pages() {
+    let site = Site::new()
+        .page("/one", article("One"))
+        .page("/two", article("Two"))
+        .start()
+        .await;
+    let server = TestServer::start(AppConfig::default()).await;
+
+    for path in ["/one", "/two"] {
+        server.ok("web_open", json!({ "url": site.url(path) })).await;
+    }
+
+    let listed = server.ok("cache_list", json!({})).await;
+    let entries = listed["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(entries.iter().any(|e| e["title"] == "One"));
+
+    let stats = server.ok("cache_stats", json!({})).await;
+    assert_eq!(stats["snapshots"], 2);
+    assert_eq!(server.cache().cache_stats().await.unwrap().snapshots, 2);
+}
+
+#[tokio::test]
+async fn test_search_uses_fixture_brave_and_caches() {
+    let brave = brave_fixture(&[
+        ("Rust", "https://www.rust-lang.org/"),
+        ("Blocked", "https://ads.example.com/"),
+    ])
+    .await;
+    let config = AppConfig { denylist_domains: vec!["ads.example.com".into()], ..Default::default() };
+    let server = TestServer::with_brave(config, &brave).await;
+
+    let first = server.ok("web_search", json!({ "query": "rust" })).await;
+    let results = first["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["url"], "https://www.rust-lang.org/");
+    assert_eq!(first["debug"]["cache_hit"], false);
+
+    let second = server.ok("web_search", json!({ "query": "rust" })).await;
+    assert_eq!(second["debug"]["cache_hit"], true);
+    assert_eq!(brave.hits("/web/search"), 1);
+}
+
+#[tokio::test]
+async fn test_search_maps_brave_failures_and_caches_only_successes() {
+    let cases = [
+        (
+            Site::new().route("/web/search", FixtureResponse::status(401)),
+            Some(-32009),
+            1,
+        ),
+        (Site::new().rate_limited("/web/search", 0), Some(-32010), 4),
+        (
+            Site::new().route("/web/search", FixtureResponse::ok("application/json", "{\"web\": [")),
+            Some(-32008),
+            1,
+        ),
+        (
+            Site::new().route(
+                "/web/search",
+                FixtureResponse::ok(
+                    "application/json",
+                    json!({ "query": { "original": "rust" } }).to_string(),
+                ),
+            ),
+            None,
+            1,
+        ),
+    ];
+
+    for (site, code, hits_per_call) in cases {
+        let brave = site.start().await;
+        let server = TestServer::with_brave(AppConfig::default(), &brave).await;
+
+        for call in 1..=2 {
+            match (server.call("web_search", json!({ "query": "rust" })).await, code) {
+                (Err(e), Some(code)) => assert_eq!(e.code.0, code, "{}", e.message),
+                (Ok(output), None) => {
+                    assert_eq!(output["results"], json!([]));
+                    assert_eq!(output["debug"]["cache_hit"], call == 2);
+                }
+                (result, code) => panic!("expected {code:?}, got {result:?}"),
+            }
+        }
+        let expected_hits = if code.is_some() { 2 * hits_per_call } else { hits_per_call };
+        assert_eq!(brave.hits("/web/search"), expected_hits, "failures are not cached");
+    }
+}
+
+#[tokio::test]
+async fn test_search_reports_altered_query() {
+    let body = json!({
+        "query": { "original": "rust programing", "altered": "rust programming", "spellcheck_off": false },
+        "web": { "results": [{ "title": "Rust", "url": "https://www.rust-lang.org/", "description": "Rust" }] }
+    });
+    let brave = Site::new()
+        .route("/web/search", FixtureResponse::ok("application/json", body.to_string()))
+        .start()
+        .await;
+    let server = TestServer::with_brave(AppConfig::default(), &brave).await;
+
+    for cache_hit in [false, true] {
+        let output = server.ok("web_search", json!({ "query": "rust programing" })).await;
+        assert_eq!(output["debug"]["cache_hit"], cache_hit);
+        assert_eq!(output["query"]["altered"], "rust programming");
+        assert_eq!(output["query"]["spellcheck_off"], false);
+        assert!(output["query"].get("is_navigational").is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_search_through_searxng() {
+    let body = json!({
+        "query": "tokio",
+        "results": [{ "url": "https://tokio.rs/", "title": "Tokio", "content": "An asynchronous runtime" }]
+    });
+    let searxng = Site::new()
+        .route("/search", FixtureResponse::ok("application/json", body.to_string()))
+        .start()
+        .await;
+    let config =
+        AppConfig { search_provider: "searxng".into(), searxng_base_url: Some(searxng.url("")), ..Default::default() };
+    let server = TestServer::start(config).await;
+
+    for cache_hit in [false, true] {
+        let output = server.ok("web_search", json!({ "query": "tokio" })).await;
+        assert_eq!(output["debug"]["cache_hit"], cache_hit);
+        assert_eq!(output["results"][0]["url"], "https://tokio.rs/");
+        assert_eq!(output["results"][0]["source"], "searxng");
+    }
+    assert_eq!(searxng.hits("/search"), 1);
+    assert_eq!(server.cache().brave_usage().await.unwrap().requests, 0);
+}
+
+#[tokio::test]
+async fn test_search_result_metadata_survives_cache() {
+    let body = json!({
+        "query": { "original": "tokio" },
+        "web": { "results": [{
+            "title": "Tokio",
+            "url": "https://tokio.rs/",
+            "description": "An asynchronous runtime",
+            "age": "2 days ago",
+            "language": "en",
+            "meta_url": { "hostname": "tokio.rs", "favicon": "https://imgs.search.brave.com/tokio.png" }
+        }] }
+    });
+    let brave = Site::new()
+        .route("/web/search", FixtureResponse::ok("application/json", body.to_string()))
+        .start()
+        .await;
+    let server = TestServer::with_brave(AppConfig::default(), &brave).await;
+
+    for cache_hit in [false, true] {
+        let output = server.ok("web_search", json!({ "query": "tokio" })).await;
+        assert_eq!(output["debug"]["cache_hit"], cache_hit);
+        let result = &output["results"][0];
+        assert_eq!(result["age"], "2 days ago");
+        assert_eq!(result["language"], "en");
+        assert_eq!(result["hostname"], "tokio.rs");
+        assert_eq!(result["favicon"], "https://imgs.search.brave.com/tokio.png");
+    }
+    assert_eq!(brave.hits("/web/search"), 1);
+}
+
+#[tokio::test]
+async fn test_invalid_input_is_rejected_before_fetching() {
+    let site = Site::new().page("/doc", article("Doc")).start().await;
+    let server = TestServer::start(AppConfig::default()).await;
+
+    let err = server
+        .call("web_open", json!({ "url": site.url("/doc"), "mode": "pdf" }))
+        .await
+        .unwrap_err();
+    assert!(err.message.contains("rendered"), "{}", err.message);
+
+    let err = server.call("web_open", json!({ "url": "" })).await.unwrap_err();
+    assert_eq!(err.code.0, -32602);
+    assert_eq!(site.hits("/doc"), 0);
+}